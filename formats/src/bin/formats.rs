@@ -0,0 +1,203 @@
+//! Converts archived sprites and textures to PNG sheets for editing, and back.
+//!
+//! `export` writes `<sheet>.png` with a `<sheet>.json` beside it describing the
+//! frames; `import` reads both and writes the asset again. Sprites are saved
+//! as indexed PNGs so palette indices survive editing, coloured with a palette
+//! from the archive when one is given and a grey ramp otherwise.
+
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use formats::game_files::ArxArchive;
+use formats::ktx2::read_ktx2;
+use formats::sheet::{Sheet, SheetMeta};
+
+const USAGE: &str = "\
+Usage:
+  formats export <asset> <sheet.png> [--archive <data.arx>] [--palette <palette.ktx2>] [--row <n>]
+      Convert an .epf.bin, .mpf.bin or .ktx2 into a PNG and JSON frame data.
+      With --archive, the asset and palette are paths inside the archive.
+      The palette row defaults to the MPF palette number, or 0.
+  formats import <sheet.png> <asset>
+      Convert an edited sheet and its JSON back into the asset.";
+
+#[derive(Default)]
+struct ExportArgs {
+    asset: String,
+    sheet: PathBuf,
+    archive: Option<PathBuf>,
+    palette: Option<String>,
+    row: Option<usize>,
+}
+
+fn parse_export(args: &[String]) -> Result<ExportArgs, String> {
+    let mut export = ExportArgs::default();
+    let mut positional = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+        match arg.as_str() {
+            "--archive" => export.archive = Some(value(arg)?.into()),
+            "--palette" => export.palette = Some(value(arg)?),
+            "--row" => {
+                let row = value(arg)?;
+                export.row = Some(row.parse().map_err(|_| format!("Bad row: {}", row))?);
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+    let [asset, sheet] = positional.as_slice() else {
+        return Err("export needs an asset and a sheet path".into());
+    };
+    export.asset = asset.clone();
+    export.sheet = sheet.into();
+    Ok(export)
+}
+
+fn read_input(archive: Option<&ArxArchive>, path: &str) -> anyhow::Result<Vec<u8>> {
+    match archive {
+        Some(archive) => archive
+            .get_file(path)
+            .map_err(|e| anyhow::anyhow!("{}: {}", path, e)),
+        None => Ok(std::fs::read(path)?),
+    }
+}
+
+/// RGB palette and transparency for an indexed PNG. Index 0 is transparent,
+/// as it is in game.
+fn png_palette(palette: Option<&[u8]>) -> (Vec<u8>, Vec<u8>) {
+    let rgb = match palette {
+        Some(rgba) => rgba
+            .chunks_exact(4)
+            .take(256)
+            .flat_map(|color| [color[0], color[1], color[2]])
+            .collect(),
+        None => (0..=255u8).flat_map(|v| [v, v, v]).collect(),
+    };
+    (rgb, vec![0])
+}
+
+fn export(args: ExportArgs) -> anyhow::Result<()> {
+    let archive = args
+        .archive
+        .as_ref()
+        .map(|path| ArxArchive::new(path).map_err(|e| anyhow::anyhow!("{}", e)))
+        .transpose()?;
+    let bytes = read_input(archive.as_ref(), &args.asset)?;
+    let sheet = Sheet::from_asset(&args.asset, &bytes)?;
+
+    let palette = match &args.palette {
+        Some(path) => {
+            let image = read_ktx2(&read_input(archive.as_ref(), path)?)?;
+            let row = args.row.unwrap_or(match sheet.meta {
+                SheetMeta::Mpf { palette_number, .. } => palette_number as usize,
+                _ => 0,
+            });
+            let row_len = image.width as usize * 4;
+            let colors = image
+                .data
+                .get(row * row_len..(row + 1) * row_len)
+                .ok_or_else(|| anyhow::anyhow!("Palette has no row {}", row))?;
+            Some(colors.to_vec())
+        }
+        None => None,
+    };
+
+    let file = std::fs::File::create(&args.sheet)?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), sheet.width, sheet.height);
+    encoder.set_depth(png::BitDepth::Eight);
+    if sheet.channels == 1 {
+        let (rgb, trns) = png_palette(palette.as_deref());
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_palette(rgb);
+        encoder.set_trns(trns);
+    } else {
+        encoder.set_color(png::ColorType::Rgba);
+    }
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&sheet.pixels)?;
+    writer.finish()?;
+
+    std::fs::write(
+        args.sheet.with_extension("json"),
+        serde_json::to_string_pretty(&sheet.meta)?,
+    )?;
+    println!(
+        "Wrote {} ({}x{})",
+        args.sheet.display(),
+        sheet.width,
+        sheet.height
+    );
+    Ok(())
+}
+
+fn import(sheet_path: &Path, asset: &Path) -> anyhow::Result<()> {
+    let meta: SheetMeta =
+        serde_json::from_str(&std::fs::read_to_string(sheet_path.with_extension("json"))?)?;
+
+    let file = BufReader::new(std::fs::File::open(sheet_path)?);
+    let mut reader = png::Decoder::new(file).read_info()?;
+    let mut pixels = vec![0; reader.output_buffer_size().unwrap_or_default()];
+    let info = reader.next_frame(&mut pixels)?;
+    pixels.truncate(info.buffer_size());
+
+    let channels = match (info.color_type, info.bit_depth) {
+        (png::ColorType::Indexed | png::ColorType::Grayscale, png::BitDepth::Eight) => 1,
+        (png::ColorType::Rgba, png::BitDepth::Eight) => 4,
+        other => anyhow::bail!(
+            "Sheets must be saved as 8-bit indexed or RGBA, not {:?}",
+            other
+        ),
+    };
+    let expected = match meta {
+        SheetMeta::Ktx2 { format } if format == formats::ktx2::VK_FORMAT_R8G8B8A8_UNORM => 4,
+        _ => 1,
+    };
+    if channels != expected {
+        anyhow::bail!("The sheet was saved with a different colour type than it was exported");
+    }
+
+    let sheet = Sheet {
+        width: info.width,
+        height: info.height,
+        channels,
+        pixels,
+        meta,
+    };
+    std::fs::write(asset, sheet.to_asset()?)?;
+    println!("Wrote {}", asset.display());
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.split_first() {
+        Some((command, rest)) if command == "export" => match parse_export(rest) {
+            Ok(export_args) => export(export_args),
+            Err(e) => {
+                eprintln!("{}\n\n{}", e, USAGE);
+                return ExitCode::from(2);
+            }
+        },
+        Some((command, [sheet, asset])) if command == "import" => {
+            import(Path::new(sheet), Path::new(asset))
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -3,7 +3,7 @@ pub const VK_FORMAT_R8G8B8A8_UNORM: u32 = 37;
 
 use std::{io::Write, mem};
 
-use byteorder::{LE, WriteBytesExt};
+use byteorder::{LE, ReadBytesExt, WriteBytesExt};
 use vk2dfd::vk2dfd;
 
 const IDENTIFIER: [u8; 12] = [
@@ -78,3 +78,39 @@ pub fn get_ktx2_header(
 
     Ok(buf)
 }
+
+/// The parts of a KTX2 file written by [`get_ktx2_header`]: a single
+/// uncompressed level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ktx2Image {
+    pub format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+pub fn read_ktx2(bytes: &[u8]) -> anyhow::Result<Ktx2Image> {
+    if !bytes.starts_with(&IDENTIFIER) {
+        anyhow::bail!("Not a KTX2 file");
+    }
+    let mut reader = &bytes[IDENTIFIER.len()..];
+    let format = reader.read_u32::<LE>()?;
+    let _type_size = reader.read_u32::<LE>()?;
+    let width = reader.read_u32::<LE>()?;
+    let height = reader.read_u32::<LE>()?;
+    // depth, layers, faces, levels, supercompression, then the index header
+    let mut reader = &reader[9 * 4..];
+    let _sgd_offset = reader.read_u64::<LE>()?;
+    let _sgd_length = reader.read_u64::<LE>()?;
+    let offset = reader.read_u64::<LE>()? as usize;
+    let length = reader.read_u64::<LE>()? as usize;
+    let data = bytes
+        .get(offset..offset + length)
+        .ok_or_else(|| anyhow::anyhow!("KTX2 level data is truncated"))?;
+    Ok(Ktx2Image {
+        format,
+        width,
+        height,
+        data: data.to_vec(),
+    })
+}
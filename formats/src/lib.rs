@@ -5,4 +5,6 @@ pub mod hpf;
 pub mod ktx2;
 pub mod meta_file;
 pub mod mpf;
+pub mod mus;
+pub mod sheet;
 pub mod spf;
@@ -0,0 +1,152 @@
+//! Background music. The `.mus` files are MPEG layer III streams, sometimes
+//! with padding or a tag in front that keeps decoders from recognising them,
+//! so [`MusFile`] finds where the audio really starts.
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MusFile {
+    pub sample_rate: u32,
+    pub channels: u8,
+    /// Of the first frame, in kbit/s.
+    pub bitrate: u32,
+    /// Where the first frame starts, past anything in front of it.
+    pub audio_offset: usize,
+    /// Found by walking the frames, so variable bitrates are counted right.
+    pub duration: Duration,
+}
+
+impl MusFile {
+    pub fn read(bytes: &[u8]) -> anyhow::Result<Self> {
+        let audio_offset = (skip_id3(bytes)..bytes.len())
+            .find(|&offset| is_frame_start(bytes, offset))
+            .ok_or_else(|| anyhow::anyhow!("No MPEG audio frames found"))?;
+        let first = FrameHeader::parse(&bytes[audio_offset..]).unwrap();
+
+        let mut samples = 0u64;
+        let mut offset = audio_offset;
+        while let Some(frame) = bytes.get(offset..).and_then(FrameHeader::parse) {
+            samples += frame.samples as u64;
+            offset += frame.len;
+        }
+
+        Ok(Self {
+            sample_rate: first.sample_rate,
+            channels: first.channels,
+            bitrate: first.bitrate,
+            audio_offset,
+            duration: Duration::from_secs_f64(samples as f64 / first.sample_rate as f64),
+        })
+    }
+
+    /// The part of `bytes` a decoder should be given.
+    pub fn audio<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        &bytes[self.audio_offset..]
+    }
+}
+
+/// Offset past an ID3v2 tag, or 0 if there isn't one.
+fn skip_id3(bytes: &[u8]) -> usize {
+    match bytes.get(..10) {
+        Some([b'I', b'D', b'3', _, _, flags, size @ ..]) => {
+            // Sizes are syncsafe, 7 bits per byte
+            let size = size
+                .iter()
+                .fold(0usize, |acc, b| (acc << 7) | (*b & 0x7f) as usize);
+            let footer = if flags & 0x10 != 0 { 10 } else { 0 };
+            (10 + size + footer).min(bytes.len())
+        }
+        _ => 0,
+    }
+}
+
+/// A frame header that is followed by another frame, or by the end of the
+/// file, so stray sync bytes in padding aren't taken for audio.
+fn is_frame_start(bytes: &[u8], offset: usize) -> bool {
+    let Some(frame) = FrameHeader::parse(&bytes[offset..]) else {
+        return false;
+    };
+    let next = offset + frame.len;
+    next == bytes.len() || bytes.get(next..).and_then(FrameHeader::parse).is_some()
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FrameHeader {
+    sample_rate: u32,
+    channels: u8,
+    bitrate: u32,
+    samples: u32,
+    /// Including the header.
+    len: usize,
+}
+
+const BITRATES_V1: [u32; 14] = [
+    32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+const BITRATES_V2: [u32; 14] = [8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+impl FrameHeader {
+    /// Layer III headers only, which is all the game ships.
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let [b0, b1, b2, b3, ..] = *bytes else {
+            return None;
+        };
+        if b0 != 0xff || b1 & 0xe0 != 0xe0 || (b1 >> 1) & 0x3 != 0x1 {
+            return None;
+        }
+        let version = (b1 >> 3) & 0x3;
+        let (bitrates, sample_rates, samples) = match version {
+            0x3 => (&BITRATES_V1, [44100, 48000, 32000], 1152),
+            0x2 => (&BITRATES_V2, [22050, 24000, 16000], 576),
+            0x0 => (&BITRATES_V2, [11025, 12000, 8000], 576),
+            _ => return None,
+        };
+        let bitrate = *bitrates.get(((b2 >> 4) as usize).checked_sub(1)?)?;
+        let sample_rate = *sample_rates.get(((b2 >> 2) & 0x3) as usize)?;
+        let padding = ((b2 >> 1) & 0x1) as usize;
+        let len = (samples / 8 * bitrate * 1000 / sample_rate) as usize + padding;
+
+        Some(Self {
+            sample_rate,
+            channels: if b3 >> 6 == 0x3 { 1 } else { 2 },
+            bitrate,
+            samples,
+            len,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A silent 128 kbit/s, 44.1 kHz stereo frame.
+    fn frame() -> Vec<u8> {
+        let mut frame = vec![0u8; 417];
+        frame[..4].copy_from_slice(&[0xff, 0xfb, 0x90, 0x00]);
+        frame
+    }
+
+    #[test]
+    fn audio_starts_after_padding_and_tags() {
+        let mut bytes = b"ID3\x04\x00\x00\x00\x00\x00\x02xx".to_vec();
+        // Junk with a stray sync word that isn't followed by a frame
+        bytes.extend_from_slice(&[0x00, 0xff, 0xfb, 0x90, 0x00, 0x00]);
+        let start = bytes.len();
+        for _ in 0..38 {
+            bytes.extend(frame());
+        }
+
+        let mus = MusFile::read(&bytes).unwrap();
+        assert_eq!(mus.audio_offset, start);
+        assert_eq!(
+            (mus.sample_rate, mus.channels, mus.bitrate),
+            (44100, 2, 128)
+        );
+        let duration = mus.duration.as_secs_f64();
+        assert!((duration - 38.0 * 1152.0 / 44100.0).abs() < 1e-6);
+        assert_eq!(mus.audio(&bytes)[..2], [0xff, 0xfb]);
+
+        assert!(MusFile::read(&[0u8; 64]).is_err());
+    }
+}
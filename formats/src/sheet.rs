@@ -0,0 +1,360 @@
+//! Sprite sheets for editing archived assets outside the game. Frames are laid
+//! out in a grid, and everything needed to turn the sheet back into the
+//! original asset is kept in [`SheetMeta`], so an edited sheet converts back
+//! as long as the frames stay in their cells.
+//!
+//! Sprites keep their palette indices, one byte per pixel, so nothing is lost
+//! to colour matching on the way back.
+
+use serde::{Deserialize, Serialize};
+
+use crate::epf::{EpfFrame, EpfImage};
+use crate::ktx2::{VK_FORMAT_R8_UNORM, VK_FORMAT_R8G8B8A8_UNORM, get_ktx2_header, read_ktx2};
+use crate::mpf::{MpfAnimation, MpfAnimationType, MpfFile, MpfFrame};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SheetMeta {
+    Epf {
+        width: usize,
+        height: usize,
+        frames: Vec<FrameMeta>,
+    },
+    Mpf {
+        palette_number: u8,
+        width: u16,
+        height: u16,
+        animations: Vec<AnimationMeta>,
+        frames: Vec<FrameMeta>,
+    },
+    Ktx2 {
+        format: u32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FrameMeta {
+    /// Cell in the sheet the frame was drawn into.
+    pub x: u32,
+    pub y: u32,
+    pub top: i32,
+    pub left: i32,
+    pub bottom: i32,
+    pub right: i32,
+    /// MPF only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub center: Option<(i16, i16)>,
+}
+
+impl FrameMeta {
+    fn size(&self) -> anyhow::Result<(u32, u32)> {
+        let width = u32::try_from(self.right - self.left)?;
+        let height = u32::try_from(self.bottom - self.top)?;
+        Ok((width, height))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnimationMeta {
+    /// `walk`, `standing`, `attack`, `attack2`, `attack3` or `extra<n>`.
+    pub animation: String,
+    pub frame_count: u8,
+    pub frame_index_towards: u8,
+    pub frame_index_away: u8,
+}
+
+impl AnimationMeta {
+    fn new(animation: &MpfAnimation) -> Self {
+        Self {
+            animation: match animation.animation_type {
+                MpfAnimationType::Walk => "walk".to_string(),
+                MpfAnimationType::Standing => "standing".to_string(),
+                MpfAnimationType::Attack => "attack".to_string(),
+                MpfAnimationType::Attack2 => "attack2".to_string(),
+                MpfAnimationType::Attack3 => "attack3".to_string(),
+                MpfAnimationType::Extra(n) => format!("extra{}", n),
+            },
+            frame_count: animation.frame_count,
+            frame_index_towards: animation.frame_index_towards,
+            frame_index_away: animation.frame_index_away,
+        }
+    }
+
+    fn to_animation(&self) -> anyhow::Result<MpfAnimation> {
+        let animation_type = match self.animation.as_str() {
+            "walk" => MpfAnimationType::Walk,
+            "standing" => MpfAnimationType::Standing,
+            "attack" => MpfAnimationType::Attack,
+            "attack2" => MpfAnimationType::Attack2,
+            "attack3" => MpfAnimationType::Attack3,
+            other => match other.strip_prefix("extra").map(str::parse) {
+                Some(Ok(n)) => MpfAnimationType::Extra(n),
+                _ => anyhow::bail!("Unknown animation: {}", other),
+            },
+        };
+        Ok(MpfAnimation {
+            animation_type,
+            frame_count: self.frame_count,
+            frame_index_towards: self.frame_index_towards,
+            frame_index_away: self.frame_index_away,
+        })
+    }
+}
+
+pub struct Sheet {
+    pub width: u32,
+    pub height: u32,
+    /// 1 for palette indices, 4 for RGBA.
+    pub channels: u8,
+    pub pixels: Vec<u8>,
+    pub meta: SheetMeta,
+}
+
+impl Sheet {
+    /// From an archived `.epf.bin`, `.mpf.bin` or `.ktx2`, told apart by name.
+    pub fn from_asset(name: &str, bytes: &[u8]) -> anyhow::Result<Self> {
+        let config = bincode::config::standard();
+        if name.ends_with(".epf.bin") {
+            let (epf, _): (EpfImage, _) = bincode::decode_from_slice(bytes, config)?;
+            let frames = epf
+                .frames
+                .iter()
+                .map(|frame| FrameMeta {
+                    x: 0,
+                    y: 0,
+                    top: frame.top as i32,
+                    left: frame.left as i32,
+                    bottom: frame.bottom as i32,
+                    right: frame.right as i32,
+                    center: None,
+                })
+                .collect();
+            let data: Vec<&[u8]> = epf.frames.iter().map(|f| f.data.as_slice()).collect();
+            Self::pack(
+                SheetMeta::Epf {
+                    width: epf.width,
+                    height: epf.height,
+                    frames,
+                },
+                &data,
+            )
+        } else if name.ends_with(".mpf.bin") {
+            let (mpf, _): (MpfFile, _) = bincode::decode_from_slice(bytes, config)?;
+            let frames = mpf
+                .frames
+                .iter()
+                .map(|frame| FrameMeta {
+                    x: 0,
+                    y: 0,
+                    top: frame.top as i32,
+                    left: frame.left as i32,
+                    bottom: frame.bottom as i32,
+                    right: frame.right as i32,
+                    center: Some((frame.center_x, frame.center_y)),
+                })
+                .collect();
+            let data: Vec<&[u8]> = mpf.frames.iter().map(|f| f.data.as_slice()).collect();
+            Self::pack(
+                SheetMeta::Mpf {
+                    palette_number: mpf.palette_number,
+                    width: mpf.width,
+                    height: mpf.height,
+                    animations: mpf.animations.iter().map(AnimationMeta::new).collect(),
+                    frames,
+                },
+                &data,
+            )
+        } else if name.ends_with(".ktx2") {
+            let image = read_ktx2(bytes)?;
+            let channels = match image.format {
+                VK_FORMAT_R8_UNORM => 1,
+                VK_FORMAT_R8G8B8A8_UNORM => 4,
+                other => anyhow::bail!("Unsupported KTX2 format {}", other),
+            };
+            Ok(Self {
+                width: image.width,
+                height: image.height,
+                channels,
+                pixels: image.data,
+                meta: SheetMeta::Ktx2 {
+                    format: image.format,
+                },
+            })
+        } else {
+            anyhow::bail!("Don't know how to convert {}", name)
+        }
+    }
+
+    /// Encodes the sheet back into the asset it was exported from.
+    pub fn to_asset(&self) -> anyhow::Result<Vec<u8>> {
+        let config = bincode::config::standard();
+        match &self.meta {
+            SheetMeta::Epf {
+                width,
+                height,
+                frames,
+            } => {
+                let frames = frames
+                    .iter()
+                    .map(|frame| {
+                        Ok(EpfFrame {
+                            top: usize::try_from(frame.top)?,
+                            left: usize::try_from(frame.left)?,
+                            bottom: usize::try_from(frame.bottom)?,
+                            right: usize::try_from(frame.right)?,
+                            data: self.cut(frame)?,
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                let epf = EpfImage {
+                    width: *width,
+                    height: *height,
+                    frames,
+                };
+                Ok(bincode::encode_to_vec(epf, config)?)
+            }
+            SheetMeta::Mpf {
+                palette_number,
+                width,
+                height,
+                animations,
+                frames,
+            } => {
+                let frames = frames
+                    .iter()
+                    .map(|frame| {
+                        let (center_x, center_y) = frame.center.unwrap_or_default();
+                        Ok(MpfFrame {
+                            top: i16::try_from(frame.top)?,
+                            left: i16::try_from(frame.left)?,
+                            bottom: i16::try_from(frame.bottom)?,
+                            right: i16::try_from(frame.right)?,
+                            center_x,
+                            center_y,
+                            data: self.cut(frame)?,
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                let mpf = MpfFile {
+                    palette_number: *palette_number,
+                    width: *width,
+                    height: *height,
+                    animations: animations
+                        .iter()
+                        .map(AnimationMeta::to_animation)
+                        .collect::<anyhow::Result<_>>()?,
+                    frames,
+                };
+                Ok(bincode::encode_to_vec(mpf, config)?)
+            }
+            SheetMeta::Ktx2 { format } => {
+                let mut bytes =
+                    get_ktx2_header(self.width, self.height, *format, self.pixels.len() as u64)?;
+                bytes.extend_from_slice(&self.pixels);
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Lays frames out in a roughly square grid of equally sized cells.
+    fn pack(mut meta: SheetMeta, data: &[&[u8]]) -> anyhow::Result<Self> {
+        let frames = match &mut meta {
+            SheetMeta::Epf { frames, .. } | SheetMeta::Mpf { frames, .. } => frames,
+            SheetMeta::Ktx2 { .. } => unreachable!("KTX2 images aren't split into frames"),
+        };
+        let sizes = frames
+            .iter()
+            .map(FrameMeta::size)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let cell_width = sizes.iter().map(|(w, _)| *w).max().unwrap_or(0).max(1);
+        let cell_height = sizes.iter().map(|(_, h)| *h).max().unwrap_or(0).max(1);
+        let columns = (frames.len() as f64).sqrt().ceil().max(1.0) as u32;
+        let rows = (frames.len() as u32).div_ceil(columns).max(1);
+
+        let width = columns * cell_width;
+        let height = rows * cell_height;
+        let mut pixels = vec![0u8; (width * height) as usize];
+        for (i, (frame, (w, h))) in frames.iter_mut().zip(sizes).enumerate() {
+            frame.x = (i as u32 % columns) * cell_width;
+            frame.y = (i as u32 / columns) * cell_height;
+            let source = data[i];
+            if source.len() != (w * h) as usize {
+                anyhow::bail!("Frame {} has {} bytes for {}x{}", i, source.len(), w, h);
+            }
+            for row in 0..h {
+                let start = ((frame.y + row) * width + frame.x) as usize;
+                pixels[start..start + w as usize]
+                    .copy_from_slice(&source[(row * w) as usize..((row + 1) * w) as usize]);
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            channels: 1,
+            pixels,
+            meta,
+        })
+    }
+
+    fn cut(&self, frame: &FrameMeta) -> anyhow::Result<Vec<u8>> {
+        let (w, h) = frame.size()?;
+        if frame.x + w > self.width || frame.y + h > self.height {
+            anyhow::bail!("Frame at {},{} doesn't fit in the sheet", frame.x, frame.y);
+        }
+        let mut data = Vec::with_capacity((w * h) as usize);
+        for row in 0..h {
+            let start = ((frame.y + row) * self.width + frame.x) as usize;
+            data.extend_from_slice(&self.pixels[start..start + w as usize]);
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epf_survives_a_round_trip_through_a_sheet() {
+        let epf = EpfImage {
+            width: 8,
+            height: 6,
+            frames: vec![
+                EpfFrame {
+                    top: 1,
+                    left: 2,
+                    bottom: 3,
+                    right: 5,
+                    data: vec![1, 2, 3, 4, 5, 6],
+                },
+                EpfFrame {
+                    top: 0,
+                    left: 0,
+                    bottom: 1,
+                    right: 1,
+                    data: vec![9],
+                },
+                EpfFrame {
+                    top: 0,
+                    left: 0,
+                    bottom: 0,
+                    right: 0,
+                    data: vec![],
+                },
+            ],
+        };
+        let bytes = bincode::encode_to_vec(&epf, bincode::config::standard()).unwrap();
+
+        let sheet = Sheet::from_asset("khanmad/wb001.epf.bin", &bytes).unwrap();
+        assert_eq!((sheet.width, sheet.height), (6, 4));
+        assert_eq!(&sheet.pixels[..3], &[1, 2, 3]);
+
+        let json = serde_json::to_string(&sheet.meta).unwrap();
+        let sheet = Sheet {
+            meta: serde_json::from_str(&json).unwrap(),
+            ..sheet
+        };
+        assert_eq!(sheet.to_asset().unwrap(), bytes);
+    }
+}
@@ -1,6 +1,7 @@
 use super::GameAction;
 use bevy::input::ButtonInput;
 use bevy::input::keyboard::KeyCode;
+use bevy::input::mouse::MouseButton;
 use bevy::prelude::Resource;
 use game_types::KeyBindings;
 
@@ -29,30 +30,78 @@ impl Modifiers {
     }
 }
 
+/// What a binding is triggered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingInput {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+/// A key or mouse button plus the modifiers that must be held with it.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KeyBinding {
-    pub key: KeyCode,
+    pub input: BindingInput,
     pub modifiers: Modifiers,
 }
 
 impl KeyBinding {
     pub fn new(key: KeyCode) -> Self {
         Self {
-            key,
+            input: BindingInput::Key(key),
             modifiers: Modifiers::none(),
         }
     }
 
     pub fn with_modifiers(key: KeyCode, modifiers: Modifiers) -> Self {
-        Self { key, modifiers }
+        Self {
+            input: BindingInput::Key(key),
+            modifiers,
+        }
+    }
+
+    pub fn mouse(button: MouseButton) -> Self {
+        Self {
+            input: BindingInput::Mouse(button),
+            modifiers: Modifiers::none(),
+        }
     }
 
-    pub fn is_pressed(&self, input: &ButtonInput<KeyCode>) -> bool {
-        input.pressed(self.key) && self.modifiers.matches(input)
+    pub fn is_pressed(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+    ) -> bool {
+        let down = match self.input {
+            BindingInput::Key(key) => keyboard.pressed(key),
+            BindingInput::Mouse(button) => mouse.pressed(button),
+        };
+        down && self.held_modifiers().matches(keyboard)
     }
 
-    pub fn is_just_pressed(&self, input: &ButtonInput<KeyCode>) -> bool {
-        input.just_pressed(self.key) && self.modifiers.matches(input)
+    pub fn is_just_pressed(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+    ) -> bool {
+        let pressed = match self.input {
+            BindingInput::Key(key) => keyboard.just_pressed(key),
+            BindingInput::Mouse(button) => mouse.just_pressed(button),
+        };
+        pressed && self.held_modifiers().matches(keyboard)
+    }
+
+    /// Modifiers expected to be down, counting the key itself when it is one.
+    fn held_modifiers(&self) -> Modifiers {
+        let mut modifiers = self.modifiers;
+        match self.input {
+            BindingInput::Key(KeyCode::ControlLeft | KeyCode::ControlRight) => {
+                modifiers.ctrl = true
+            }
+            BindingInput::Key(KeyCode::ShiftLeft | KeyCode::ShiftRight) => modifiers.shift = true,
+            BindingInput::Key(KeyCode::AltLeft | KeyCode::AltRight) => modifiers.alt = true,
+            _ => {}
+        }
+        modifiers
     }
 
     pub fn from_dom_code(code: &str) -> Option<Self> {
@@ -77,11 +126,21 @@ impl KeyBinding {
             }
         }
 
-        dom_code_to_keycode(key_part).map(|key| KeyBinding { key, modifiers })
+        let input = match key_part {
+            "Mouse4" => BindingInput::Mouse(MouseButton::Back),
+            "Mouse5" => BindingInput::Mouse(MouseButton::Forward),
+            _ => BindingInput::Key(dom_code_to_keycode(key_part)?),
+        };
+        Some(KeyBinding { input, modifiers })
     }
 
     pub fn to_dom_code(&self) -> String {
-        let key_str = keycode_to_dom_code(self.key);
+        let key_str = match self.input {
+            BindingInput::Key(key) => keycode_to_dom_code(key),
+            BindingInput::Mouse(MouseButton::Back) => "Mouse4",
+            BindingInput::Mouse(MouseButton::Forward) => "Mouse5",
+            BindingInput::Mouse(_) => "Unknown",
+        };
         if self.modifiers.is_empty() {
             key_str.to_string()
         } else {
@@ -137,52 +196,15 @@ impl InputBindings {
     pub fn from_settings(settings: &KeyBindings) -> Self {
         let mut bindings = std::collections::HashMap::new();
 
-        macro_rules! bind {
-            ($field:ident, $action:ident) => {
-                let mut action_bindings = Vec::new();
-                for key_str in &settings.$field {
-                    if !key_str.is_empty() {
-                        if let Some(kb) = KeyBinding::from_dom_code(key_str) {
-                            action_bindings.push(kb);
-                        }
-                    }
-                }
-                bindings.insert(GameAction::$action, action_bindings);
-            };
+        for (action, binding) in settings.iter() {
+            let action_bindings = binding
+                .iter()
+                .filter(|key_str| !key_str.is_empty())
+                .filter_map(|key_str| KeyBinding::from_dom_code(key_str))
+                .collect();
+            bindings.insert(action, action_bindings);
         }
 
-        bind!(move_up, MoveUp);
-        bind!(move_down, MoveDown);
-        bind!(move_left, MoveLeft);
-        bind!(move_right, MoveRight);
-        bind!(inventory, Inventory);
-        bind!(skills, Skills);
-        bind!(spells, Spells);
-        bind!(settings, Settings);
-        bind!(refresh, Refresh);
-        bind!(basic_attack, BasicAttack);
-        bind!(item_pickup_below, ItemPickupBelow);
-
-        bind!(hotbar_slot_1, HotbarSlot1);
-        bind!(hotbar_slot_2, HotbarSlot2);
-        bind!(hotbar_slot_3, HotbarSlot3);
-        bind!(hotbar_slot_4, HotbarSlot4);
-        bind!(hotbar_slot_5, HotbarSlot5);
-        bind!(hotbar_slot_6, HotbarSlot6);
-        bind!(hotbar_slot_7, HotbarSlot7);
-        bind!(hotbar_slot_8, HotbarSlot8);
-        bind!(hotbar_slot_9, HotbarSlot9);
-        bind!(hotbar_slot_10, HotbarSlot10);
-        bind!(hotbar_slot_11, HotbarSlot11);
-        bind!(hotbar_slot_12, HotbarSlot12);
-
-        bind!(switch_to_inventory, SwitchToInventory);
-        bind!(switch_to_skills, SwitchToSkills);
-        bind!(switch_to_spells, SwitchToSpells);
-        bind!(switch_to_hotbar_1, SwitchToHotbar1);
-        bind!(switch_to_hotbar_2, SwitchToHotbar2);
-        bind!(switch_to_hotbar_3, SwitchToHotbar3);
-
         Self { bindings }
     }
 
@@ -217,26 +239,48 @@ impl InputBindings {
         }
     }
 
-    pub fn is_pressed(&self, action: GameAction, input: &ButtonInput<KeyCode>) -> bool {
+    pub fn is_pressed(
+        &self,
+        action: GameAction,
+        input: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+    ) -> bool {
         self.get(action)
-            .map(|kbs| kbs.iter().any(|kb| kb.is_pressed(input)))
+            .map(|kbs| kbs.iter().any(|kb| kb.is_pressed(input, mouse)))
             .unwrap_or(false)
     }
 
-    pub fn is_just_pressed(&self, action: GameAction, input: &ButtonInput<KeyCode>) -> bool {
+    pub fn is_just_pressed(
+        &self,
+        action: GameAction,
+        input: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+    ) -> bool {
         self.get(action)
-            .map(|kbs| kbs.iter().any(|kb| kb.is_just_pressed(input)))
+            .map(|kbs| kbs.iter().any(|kb| kb.is_just_pressed(input, mouse)))
             .unwrap_or(false)
     }
 
-    pub fn any_pressed(&self, actions: &[GameAction], input: &ButtonInput<KeyCode>) -> bool {
-        actions.iter().any(|&action| self.is_pressed(action, input))
+    pub fn any_pressed(
+        &self,
+        actions: &[GameAction],
+        input: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+    ) -> bool {
+        actions
+            .iter()
+            .any(|&action| self.is_pressed(action, input, mouse))
     }
 
-    pub fn any_just_pressed(&self, actions: &[GameAction], input: &ButtonInput<KeyCode>) -> bool {
+    pub fn any_just_pressed(
+        &self,
+        actions: &[GameAction],
+        input: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+    ) -> bool {
         actions
             .iter()
-            .any(|&action| self.is_just_pressed(action, input))
+            .any(|&action| self.is_just_pressed(action, input, mouse))
     }
 }
 
@@ -315,6 +359,14 @@ fn dom_code_to_keycode(code: &str) -> Option<KeyCode> {
         "F10" => Some(KeyCode::F10),
         "F11" => Some(KeyCode::F11),
         "F12" => Some(KeyCode::F12),
+        "AltLeft" => Some(KeyCode::AltLeft),
+        "AltRight" => Some(KeyCode::AltRight),
+        "ControlLeft" => Some(KeyCode::ControlLeft),
+        "ControlRight" => Some(KeyCode::ControlRight),
+        "ShiftLeft" => Some(KeyCode::ShiftLeft),
+        "ShiftRight" => Some(KeyCode::ShiftRight),
+        "Backspace" => Some(KeyCode::Backspace),
+        "Delete" => Some(KeyCode::Delete),
         _ => None,
     }
 }
@@ -388,6 +440,49 @@ fn keycode_to_dom_code(code: KeyCode) -> &'static str {
         KeyCode::F10 => "F10",
         KeyCode::F11 => "F11",
         KeyCode::F12 => "F12",
+        KeyCode::AltLeft => "AltLeft",
+        KeyCode::AltRight => "AltRight",
+        KeyCode::ControlLeft => "ControlLeft",
+        KeyCode::ControlRight => "ControlRight",
+        KeyCode::ShiftLeft => "ShiftLeft",
+        KeyCode::ShiftRight => "ShiftRight",
+        KeyCode::Backspace => "Backspace",
+        KeyCode::Delete => "Delete",
         _ => "Unknown",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_chords_and_side_buttons() {
+        let chord = KeyBinding::from_dom_code("Ctrl+Shift+KeyR").unwrap();
+        assert_eq!(chord.input, BindingInput::Key(KeyCode::KeyR));
+        assert!(chord.modifiers.ctrl && chord.modifiers.shift && !chord.modifiers.alt);
+        assert_eq!(chord.to_dom_code(), "Ctrl+Shift+KeyR");
+
+        let side = KeyBinding::from_dom_code("Alt+Mouse5").unwrap();
+        assert_eq!(side.input, BindingInput::Mouse(MouseButton::Forward));
+        assert_eq!(side.to_dom_code(), "Alt+Mouse5");
+
+        assert!(KeyBinding::from_dom_code("Ctrl+Nonsense").is_none());
+    }
+
+    #[test]
+    fn chords_need_exactly_their_modifiers() {
+        let binding = KeyBinding::from_dom_code("Ctrl+KeyR").unwrap();
+        let mouse = ButtonInput::<MouseButton>::default();
+        let mut keyboard = ButtonInput::<KeyCode>::default();
+
+        keyboard.press(KeyCode::KeyR);
+        assert!(!binding.is_pressed(&keyboard, &mouse));
+
+        keyboard.press(KeyCode::ControlLeft);
+        assert!(binding.is_pressed(&keyboard, &mouse));
+
+        keyboard.press(KeyCode::ShiftLeft);
+        assert!(!binding.is_pressed(&keyboard, &mouse));
+    }
+}
@@ -1,10 +1,9 @@
-mod actions;
 mod bindings;
 pub mod gamepad;
 mod unified;
 
-pub use actions::GameAction;
-pub use bindings::{InputBindings, KeyBinding, Modifiers};
+pub use bindings::{BindingInput, InputBindings, KeyBinding, Modifiers};
+pub use game_types::GameAction;
 pub use gamepad::{GamepadConfig, GamepadInputType, GilrsResource};
 pub use gamepad::{gamepad_connection_system, gilrs_event_polling_system};
 pub use unified::{InputSource, UnifiedInputBindings};
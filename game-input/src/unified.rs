@@ -1,7 +1,10 @@
 use super::{GameAction, GamepadConfig, GamepadInputType, KeyBinding};
 use bevy::input::ButtonInput;
 use bevy::input::keyboard::KeyCode;
+use bevy::input::mouse::MouseButton;
 use bevy::prelude::*;
+use game_types::PressMode;
+use std::collections::HashSet;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InputSource {
@@ -27,12 +30,26 @@ impl InputSource {
 #[derive(Resource)]
 pub struct UnifiedInputBindings {
     bindings: std::collections::HashMap<GameAction, Vec<InputSource>>,
+    /// Held actions that flip on and off with each press instead.
+    toggle_actions: HashSet<GameAction>,
+    toggled_on: HashSet<GameAction>,
+    just_toggled_on: HashSet<GameAction>,
+    /// Mouse state as of the last `update`, for side-button bindings.
+    mouse: ButtonInput<MouseButton>,
 }
 
 impl UnifiedInputBindings {
     pub fn new() -> Self {
+        Self::from_map(std::collections::HashMap::new())
+    }
+
+    fn from_map(bindings: std::collections::HashMap<GameAction, Vec<InputSource>>) -> Self {
         Self {
-            bindings: std::collections::HashMap::new(),
+            bindings,
+            toggle_actions: HashSet::new(),
+            toggled_on: HashSet::new(),
+            just_toggled_on: HashSet::new(),
+            mouse: ButtonInput::default(),
         }
     }
 
@@ -116,56 +133,25 @@ impl UnifiedInputBindings {
             ],
         );
 
-        Self { bindings }
+        Self::from_map(bindings)
     }
 
     pub fn from_settings(settings: &game_types::KeyBindings) -> Self {
         let mut unified = Self::with_defaults();
 
-        macro_rules! bind {
-            ($field:ident, $action:ident) => {
-                for (i, key_str) in settings.$field.iter().enumerate() {
-                    if !key_str.is_empty() {
-                        if let Some(kb) = KeyBinding::from_dom_code(key_str) {
-                            unified.set_keyboard_binding(GameAction::$action, kb, i);
-                        }
-                    } else {
-                        unified.unbind_keyboard_at(GameAction::$action, i);
+        for (action, binding) in settings.iter() {
+            for (i, key_str) in binding.iter().enumerate() {
+                if !key_str.is_empty() {
+                    if let Some(kb) = KeyBinding::from_dom_code(key_str) {
+                        unified.set_keyboard_binding(action, kb, i);
                     }
+                } else {
+                    unified.unbind_keyboard_at(action, i);
                 }
-            };
+            }
+            unified.set_press_mode(action, binding.mode);
         }
 
-        bind!(move_up, MoveUp);
-        bind!(move_down, MoveDown);
-        bind!(move_left, MoveLeft);
-        bind!(move_right, MoveRight);
-        bind!(inventory, Inventory);
-        bind!(skills, Skills);
-        bind!(spells, Spells);
-        bind!(settings, Settings);
-        bind!(refresh, Refresh);
-        bind!(basic_attack, BasicAttack);
-        bind!(item_pickup_below, ItemPickupBelow);
-        bind!(hotbar_slot_1, HotbarSlot1);
-        bind!(hotbar_slot_2, HotbarSlot2);
-        bind!(hotbar_slot_3, HotbarSlot3);
-        bind!(hotbar_slot_4, HotbarSlot4);
-        bind!(hotbar_slot_5, HotbarSlot5);
-        bind!(hotbar_slot_6, HotbarSlot6);
-        bind!(hotbar_slot_7, HotbarSlot7);
-        bind!(hotbar_slot_8, HotbarSlot8);
-        bind!(hotbar_slot_9, HotbarSlot9);
-        bind!(hotbar_slot_10, HotbarSlot10);
-        bind!(hotbar_slot_11, HotbarSlot11);
-        bind!(hotbar_slot_12, HotbarSlot12);
-        bind!(switch_to_inventory, SwitchToInventory);
-        bind!(switch_to_skills, SwitchToSkills);
-        bind!(switch_to_spells, SwitchToSpells);
-        bind!(switch_to_hotbar_1, SwitchToHotbar1);
-        bind!(switch_to_hotbar_2, SwitchToHotbar2);
-        bind!(switch_to_hotbar_3, SwitchToHotbar3);
-
         unified
     }
 
@@ -234,12 +220,82 @@ impl UnifiedInputBindings {
         }
     }
 
+    /// Toggle mode only applies to held actions, see [`GameAction::is_held`].
+    pub fn set_press_mode(&mut self, action: GameAction, mode: PressMode) {
+        if mode == PressMode::Toggle && action.is_held() {
+            self.toggle_actions.insert(action);
+        } else {
+            self.toggle_actions.remove(&action);
+            self.toggled_on.remove(&action);
+        }
+    }
+
+    /// Snapshots the mouse and flips toggled actions. Runs once a frame after
+    /// input is pumped, before anything reads the bindings.
+    pub fn update(
+        &mut self,
+        keyboard: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+        gamepad_query: Option<&Query<&Gamepad>>,
+        gamepad_config: Option<&GamepadConfig>,
+    ) {
+        self.mouse = mouse.clone();
+        self.just_toggled_on.clear();
+
+        let pressed: Vec<GameAction> = self
+            .toggle_actions
+            .iter()
+            .copied()
+            .filter(|&action| {
+                self.raw_is_just_pressed(action, keyboard, gamepad_query, gamepad_config)
+            })
+            .collect();
+        for action in pressed {
+            if !self.toggled_on.remove(&action) {
+                self.toggled_on.insert(action);
+                self.just_toggled_on.insert(action);
+            }
+        }
+    }
+
+    /// Turns every toggled action off, e.g. when leaving the game.
+    pub fn release_toggles(&mut self) {
+        self.toggled_on.clear();
+        self.just_toggled_on.clear();
+    }
+
     pub fn is_pressed(
         &self,
         action: GameAction,
         keyboard: &ButtonInput<KeyCode>,
         gamepad_query: Option<&Query<&Gamepad>>,
         gamepad_config: Option<&GamepadConfig>,
+    ) -> bool {
+        if self.toggle_actions.contains(&action) {
+            return self.toggled_on.contains(&action);
+        }
+        self.raw_is_pressed(action, keyboard, gamepad_query, gamepad_config)
+    }
+
+    pub fn is_just_pressed(
+        &self,
+        action: GameAction,
+        keyboard: &ButtonInput<KeyCode>,
+        gamepad_query: Option<&Query<&Gamepad>>,
+        gamepad_config: Option<&GamepadConfig>,
+    ) -> bool {
+        if self.toggle_actions.contains(&action) {
+            return self.just_toggled_on.contains(&action);
+        }
+        self.raw_is_just_pressed(action, keyboard, gamepad_query, gamepad_config)
+    }
+
+    fn raw_is_pressed(
+        &self,
+        action: GameAction,
+        keyboard: &ButtonInput<KeyCode>,
+        gamepad_query: Option<&Query<&Gamepad>>,
+        gamepad_config: Option<&GamepadConfig>,
     ) -> bool {
         let Some(sources) = self.bindings.get(&action) else {
             return false;
@@ -248,7 +304,7 @@ impl UnifiedInputBindings {
         for source in sources {
             match source {
                 InputSource::Keyboard(kb) => {
-                    if kb.is_pressed(keyboard) {
+                    if kb.is_pressed(keyboard, &self.mouse) {
                         return true;
                     }
                 }
@@ -269,7 +325,7 @@ impl UnifiedInputBindings {
         false
     }
 
-    pub fn is_just_pressed(
+    fn raw_is_just_pressed(
         &self,
         action: GameAction,
         keyboard: &ButtonInput<KeyCode>,
@@ -283,7 +339,7 @@ impl UnifiedInputBindings {
         for source in sources {
             match source {
                 InputSource::Keyboard(kb) => {
-                    if kb.is_just_pressed(keyboard) {
+                    if kb.is_just_pressed(keyboard, &self.mouse) {
                         return true;
                     }
                 }
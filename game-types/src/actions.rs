@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+/// Heading an action is listed under on the controls screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ActionSection {
+    Movement,
+    WorldInteraction,
+    Combat,
+    Interface,
+    HotbarSlots,
+    PanelSwitching,
+}
+
+impl ActionSection {
+    pub fn id(&self) -> &'static str {
+        match self {
+            ActionSection::Movement => "movement",
+            ActionSection::WorldInteraction => "world_interaction",
+            ActionSection::Combat => "combat",
+            ActionSection::Interface => "interface",
+            ActionSection::HotbarSlots => "hotbar_slots",
+            ActionSection::PanelSwitching => "panel_switching",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ActionSection::Movement => "Movement",
+            ActionSection::WorldInteraction => "World Interaction",
+            ActionSection::Combat => "Combat",
+            ActionSection::Interface => "Interface",
+            ActionSection::HotbarSlots => "Hotbar Slots",
+            ActionSection::PanelSwitching => "Panel Switching",
+        }
+    }
+}
+
+/// Declares every bindable action. Adding a row here is all it takes to get a
+/// settings entry, a default binding and a row on the controls screen; the
+/// game still has to read the action somewhere.
+macro_rules! game_actions {
+    ($($action:ident => $id:literal, $label:literal, $section:ident, $default:literal;)*) => {
+        /// A bindable action. Declaration order is the order actions are saved
+        /// and listed on the controls screen.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum GameAction {
+            $($action,)*
+        }
+
+        impl GameAction {
+            pub const ALL: &'static [GameAction] = &[$(GameAction::$action,)*];
+
+            /// Key in `settings.toml` and the id the UI refers to the action by.
+            pub fn action_id(&self) -> &'static str {
+                match self {
+                    $(GameAction::$action => $id,)*
+                }
+            }
+
+            pub fn from_action_id(id: &str) -> Option<Self> {
+                match id {
+                    $($id => Some(GameAction::$action),)*
+                    _ => None,
+                }
+            }
+
+            pub fn label(&self) -> &'static str {
+                match self {
+                    $(GameAction::$action => $label,)*
+                }
+            }
+
+            pub fn section(&self) -> ActionSection {
+                match self {
+                    $(GameAction::$action => ActionSection::$section,)*
+                }
+            }
+
+            /// Primary key bound on a fresh install, as a DOM key code.
+            pub fn default_key(&self) -> &'static str {
+                match self {
+                    $(GameAction::$action => $default,)*
+                }
+            }
+        }
+    };
+}
+
+game_actions! {
+    MoveUp => "move_up", "Move Up", Movement, "ArrowUp";
+    MoveDown => "move_down", "Move Down", Movement, "ArrowDown";
+    MoveLeft => "move_left", "Move Left", Movement, "ArrowLeft";
+    MoveRight => "move_right", "Move Right", Movement, "ArrowRight";
+    ItemPickupBelow => "item_pickup_below", "Item Pickup", WorldInteraction, "KeyB";
+    BasicAttack => "basic_attack", "Basic Attack", Combat, "Space";
+    Inventory => "inventory", "Inventory", Interface, "KeyI";
+    Skills => "skills", "Skills", Interface, "KeyK";
+    Spells => "spells", "Spells", Interface, "KeyP";
+    Settings => "settings", "Settings", Interface, "Escape";
+    Refresh => "refresh", "Refresh", Interface, "F5";
+    ToggleDebugOverlay => "toggle_debug_overlay", "Debug Overlay", Interface, "F3";
+    EntityInspector => "entity_inspector", "Entity Inspector", Interface, "F4";
+    CaptureFrame => "capture_frame", "Capture Frame", Interface, "F9";
+    ArchiveBrowser => "archive_browser", "Archive Browser", Interface, "F6";
+    ToggleUi => "toggle_ui", "Hide Interface", Interface, "F1";
+    HighlightInteractables => "highlight_interactables", "Highlight Doors & Exits", Interface, "AltLeft";
+    PreviewPath => "preview_path", "Preview Walking Path", Interface, "ControlLeft";
+    ToggleMute => "toggle_mute", "Mute Sound", Interface, "KeyM";
+    CombatLog => "combat_log", "Combat Log", Interface, "KeyL";
+    DialogHistory => "dialog_history", "Conversation History", Interface, "KeyJ";
+    MenuShortcut => "menu_shortcut", "Favorite NPC Menu", Interface, "KeyN";
+    HotbarSlot1 => "hotbar_slot_1", "Slot 1", HotbarSlots, "Digit1";
+    HotbarSlot2 => "hotbar_slot_2", "Slot 2", HotbarSlots, "Digit2";
+    HotbarSlot3 => "hotbar_slot_3", "Slot 3", HotbarSlots, "Digit3";
+    HotbarSlot4 => "hotbar_slot_4", "Slot 4", HotbarSlots, "Digit4";
+    HotbarSlot5 => "hotbar_slot_5", "Slot 5", HotbarSlots, "Digit5";
+    HotbarSlot6 => "hotbar_slot_6", "Slot 6", HotbarSlots, "Digit6";
+    HotbarSlot7 => "hotbar_slot_7", "Slot 7", HotbarSlots, "Digit7";
+    HotbarSlot8 => "hotbar_slot_8", "Slot 8", HotbarSlots, "Digit8";
+    HotbarSlot9 => "hotbar_slot_9", "Slot 9", HotbarSlots, "Digit9";
+    HotbarSlot10 => "hotbar_slot_10", "Slot 10", HotbarSlots, "Digit0";
+    HotbarSlot11 => "hotbar_slot_11", "Slot 11", HotbarSlots, "Minus";
+    HotbarSlot12 => "hotbar_slot_12", "Slot 12", HotbarSlots, "Equal";
+    SwitchToInventory => "switch_to_inventory", "Inventory Panel", PanelSwitching, "KeyA";
+    SwitchToSkills => "switch_to_skills", "Skills Panel", PanelSwitching, "KeyS";
+    SwitchToSpells => "switch_to_spells", "Spells Panel", PanelSwitching, "KeyD";
+    SwitchToHotbar1 => "switch_to_hotbar_1", "Hotbar 1", PanelSwitching, "KeyF";
+    SwitchToHotbar2 => "switch_to_hotbar_2", "Hotbar 2", PanelSwitching, "KeyG";
+    SwitchToHotbar3 => "switch_to_hotbar_3", "Hotbar 3", PanelSwitching, "KeyH";
+}
+
+impl GameAction {
+    pub fn all() -> &'static [GameAction] {
+        Self::ALL
+    }
+
+    /// Actions that stay active while their input is held, and so can be
+    /// switched to toggle with `PressMode::Toggle`.
+    pub fn is_held(&self) -> bool {
+        matches!(
+            self,
+            GameAction::MoveUp
+                | GameAction::MoveDown
+                | GameAction::MoveLeft
+                | GameAction::MoveRight
+                | GameAction::HighlightInteractables
+                | GameAction::PreviewPath
+        )
+    }
+}
+
+impl Serialize for GameAction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.action_id())
+    }
+}
+
+impl<'de> Deserialize<'de> for GameAction {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let id = String::deserialize(deserializer)?;
+        GameAction::from_action_id(&id)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown action `{}`", id)))
+    }
+}
@@ -1,7 +1,9 @@
+mod actions;
 mod settings;
 mod hotbar;
 mod ui_types;
 
+pub use actions::*;
 pub use settings::*;
 pub use hotbar::*;
 pub use ui_types::*;
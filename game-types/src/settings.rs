@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::GameAction;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[repr(u8)]
 pub enum XRaySize {
@@ -39,19 +43,220 @@ impl XRaySize {
     }
 }
 
+/// Which walls the x-ray effect fades around the local player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[repr(u8)]
+pub enum XRayMode {
+    /// Everything in front of the player within a circle sized to their sprite.
+    #[default]
+    Radius = 0,
+    /// Only the wall pixels that actually cover the player's sprite.
+    Occluding = 1,
+}
+
+impl XRayMode {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Occluding,
+            _ => Self::Radius,
+        }
+    }
+}
+
+/// How the low-resolution game frame is scaled up to fill the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[repr(u8)]
+pub enum RenderScaling {
+    /// Exact zoom, with a sharpened bilinear filter at fractional zoom levels.
+    #[default]
+    Smooth = 0,
+    /// Zoom snapped to whole multiples and scaled with nearest-neighbor sampling.
+    Integer = 1,
+}
+
+impl RenderScaling {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Integer,
+            _ => Self::Smooth,
+        }
+    }
+}
+
+/// Graphics API the renderer is asked to use, applied on the next launch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[repr(u8)]
+pub enum GpuBackend {
+    /// Whatever the platform prefers, or `WGPU_BACKEND` when set.
+    #[default]
+    Auto = 0,
+    Vulkan = 1,
+    Dx12 = 2,
+    Metal = 3,
+    Gl = 4,
+}
+
+impl GpuBackend {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Vulkan,
+            2 => Self::Dx12,
+            3 => Self::Metal,
+            4 => Self::Gl,
+            _ => Self::Auto,
+        }
+    }
+}
+
+/// Which kind of GPU to prefer when the system has more than one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[repr(u8)]
+pub enum GpuPreference {
+    #[default]
+    Auto = 0,
+    /// The integrated GPU, which uses less power.
+    Integrated = 1,
+    /// The discrete GPU, which is faster.
+    Discrete = 2,
+}
+
+impl GpuPreference {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Integrated,
+            2 => Self::Discrete,
+            _ => Self::Auto,
+        }
+    }
+}
+
+/// Which direction wins while several movement keys are held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[repr(u8)]
+pub enum DirectionPriority {
+    /// Up, then down, left and right.
+    #[default]
+    Fixed = 0,
+    /// The most recently pressed key that is still held.
+    LastPressed = 1,
+}
+
+impl DirectionPriority {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::LastPressed,
+            _ => Self::Fixed,
+        }
+    }
+}
+
+/// What the notification center lets through while a social status is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[repr(u8)]
+pub enum NotificationPolicy {
+    /// Every notification, and alert sounds such as group invites.
+    #[default]
+    All = 0,
+    /// Whisper notifications only, without alert sounds.
+    WhispersOnly = 1,
+    /// No notifications or alert sounds.
+    Off = 2,
+}
+
+impl NotificationPolicy {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::WhispersOnly,
+            2 => Self::Off,
+            _ => Self::All,
+        }
+    }
+
+    pub fn shows(&self, whisper: bool) -> bool {
+        match self {
+            Self::All => true,
+            Self::WhispersOnly => whisper,
+            Self::Off => false,
+        }
+    }
+
+    pub fn plays_sounds(&self) -> bool {
+        *self == Self::All
+    }
+}
+
+/// Volume group a sound effect belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[repr(u8)]
+pub enum SoundCategory {
+    /// Weapon swings and hits.
+    Combat = 0,
+    /// Sounds mapped from spell and effect animations.
+    Spells = 1,
+    /// Sounds the server plays without a source.
+    #[default]
+    Ambient = 2,
+}
+
+impl SoundCategory {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            0 => Self::Combat,
+            1 => Self::Spells,
+            _ => Self::Ambient,
+        }
+    }
+}
+
+/// Whether a held action is active only while its key is down or flips on and
+/// off with each press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[repr(u8)]
+pub enum PressMode {
+    #[default]
+    Hold = 0,
+    Toggle = 1,
+}
+
+impl PressMode {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            1 => Self::Toggle,
+            _ => Self::Hold,
+        }
+    }
+}
+
+/// Primary and secondary input for one action, empty when unbound.
+///
+/// Each input is a key code with optional modifiers such as `"Ctrl+KeyR"`, or a
+/// side mouse button (`"Mouse4"`, `"Mouse5"`). Hold bindings are stored as a
+/// string or a pair of strings, toggles as `{ keys = [...], mode = "Toggle" }`.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
-pub struct KeyBinding(pub [String; 2]);
+pub struct KeyBinding {
+    pub keys: [String; 2],
+    pub mode: PressMode,
+}
+
+impl KeyBinding {
+    pub fn new(primary: &str) -> Self {
+        Self {
+            keys: [primary.to_string(), String::new()],
+            mode: PressMode::Hold,
+        }
+    }
+}
 
 impl std::ops::Deref for KeyBinding {
     type Target = [String; 2];
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.keys
     }
 }
 
 impl std::ops::DerefMut for KeyBinding {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.keys
     }
 }
 
@@ -60,7 +265,7 @@ impl<'a> IntoIterator for &'a KeyBinding {
     type IntoIter = std::slice::Iter<'a, String>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.iter()
+        self.keys.iter()
     }
 }
 
@@ -69,7 +274,7 @@ impl IntoIterator for KeyBinding {
     type IntoIter = std::array::IntoIter<String, 2>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+        self.keys.into_iter()
     }
 }
 
@@ -78,10 +283,22 @@ impl Serialize for KeyBinding {
     where
         S: serde::Serializer,
     {
-        if self.0[1].is_empty() {
-            serializer.serialize_str(&self.0[0])
+        #[derive(Serialize)]
+        struct WithMode<'a> {
+            keys: &'a [String; 2],
+            mode: PressMode,
+        }
+
+        if self.mode != PressMode::Hold {
+            WithMode {
+                keys: &self.keys,
+                mode: self.mode,
+            }
+            .serialize(serializer)
+        } else if self.keys[1].is_empty() {
+            serializer.serialize_str(&self.keys[0])
         } else {
-            self.0.serialize(serializer)
+            self.keys.serialize(serializer)
         }
     }
 }
@@ -96,88 +313,113 @@ impl<'de> Deserialize<'de> for KeyBinding {
         enum Either {
             Single(String),
             Multiple(Vec<String>),
+            WithMode {
+                keys: Vec<String>,
+                #[serde(default)]
+                mode: PressMode,
+            },
         }
 
-        match Either::deserialize(deserializer)? {
-            Either::Single(s) => Ok(KeyBinding([s, "".to_string()])),
-            Either::Multiple(v) => {
-                let mut bindings = ["".to_string(), "".to_string()];
-                for (i, s) in v.into_iter().enumerate().take(2) {
-                    bindings[i] = s;
+        let (keys, mode) = match Either::deserialize(deserializer)? {
+            Either::Single(s) => (vec![s], PressMode::Hold),
+            Either::Multiple(v) => (v, PressMode::Hold),
+            Either::WithMode { keys, mode } => (keys, mode),
+        };
+
+        let mut bindings = ["".to_string(), "".to_string()];
+        for (i, s) in keys.into_iter().enumerate().take(2) {
+            bindings[i] = s;
+        }
+        Ok(KeyBinding {
+            keys: bindings,
+            mode,
+        })
+    }
+}
+
+/// Bindings for every [`GameAction`].
+///
+/// Saved as a table keyed by action id. Actions missing from the file keep
+/// their default key and ids this build doesn't know are dropped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBindings(BTreeMap<GameAction, KeyBinding>);
+
+impl KeyBindings {
+    pub fn get(&self, action: GameAction) -> &KeyBinding {
+        &self.0[&action]
+    }
+
+    pub fn get_mut(&mut self, action: GameAction) -> &mut KeyBinding {
+        self.0
+            .entry(action)
+            .or_insert_with(|| KeyBinding::new(action.default_key()))
+    }
+
+    /// Every action with its binding, in [`GameAction::ALL`] order.
+    pub fn iter(&self) -> impl Iterator<Item = (GameAction, &KeyBinding)> {
+        self.0.iter().map(|(action, binding)| (*action, binding))
+    }
+
+    /// Binds `key` to slot `index` of `action`, clearing it from any other
+    /// slot it was bound to.
+    pub fn rebind(&mut self, action: GameAction, index: usize, key: &str) {
+        if index >= 2 {
+            return;
+        }
+        if !key.is_empty() {
+            for binding in self.0.values_mut() {
+                for k in binding.keys.iter_mut().filter(|k| k.as_str() == key) {
+                    k.clear();
                 }
-                Ok(KeyBinding(bindings))
             }
         }
+        self.get_mut(action)[index] = key.to_string();
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(default)]
-pub struct KeyBindings {
-    pub move_up: KeyBinding,
-    pub move_down: KeyBinding,
-    pub move_left: KeyBinding,
-    pub move_right: KeyBinding,
-    pub inventory: KeyBinding,
-    pub skills: KeyBinding,
-    pub spells: KeyBinding,
-    pub settings: KeyBinding,
-    pub refresh: KeyBinding,
-    pub basic_attack: KeyBinding,
-    pub item_pickup_below: KeyBinding,
-    pub hotbar_slot_1: KeyBinding,
-    pub hotbar_slot_2: KeyBinding,
-    pub hotbar_slot_3: KeyBinding,
-    pub hotbar_slot_4: KeyBinding,
-    pub hotbar_slot_5: KeyBinding,
-    pub hotbar_slot_6: KeyBinding,
-    pub hotbar_slot_7: KeyBinding,
-    pub hotbar_slot_8: KeyBinding,
-    pub hotbar_slot_9: KeyBinding,
-    pub hotbar_slot_10: KeyBinding,
-    pub hotbar_slot_11: KeyBinding,
-    pub hotbar_slot_12: KeyBinding,
-    pub switch_to_inventory: KeyBinding,
-    pub switch_to_skills: KeyBinding,
-    pub switch_to_spells: KeyBinding,
-    pub switch_to_hotbar_1: KeyBinding,
-    pub switch_to_hotbar_2: KeyBinding,
-    pub switch_to_hotbar_3: KeyBinding,
+    pub fn unbind(&mut self, action: GameAction, index: usize) {
+        if let Some(key) = self.get_mut(action).get_mut(index) {
+            key.clear();
+        }
+    }
 }
 
 impl Default for KeyBindings {
     fn default() -> Self {
-        Self {
-            move_up: KeyBinding(["ArrowUp".to_string(), "".to_string()]),
-            move_down: KeyBinding(["ArrowDown".to_string(), "".to_string()]),
-            move_left: KeyBinding(["ArrowLeft".to_string(), "".to_string()]),
-            move_right: KeyBinding(["ArrowRight".to_string(), "".to_string()]),
-            inventory: KeyBinding(["KeyI".to_string(), "".to_string()]),
-            skills: KeyBinding(["KeyK".to_string(), "".to_string()]),
-            spells: KeyBinding(["KeyP".to_string(), "".to_string()]),
-            settings: KeyBinding(["Escape".to_string(), "".to_string()]),
-            refresh: KeyBinding(["F5".to_string(), "".to_string()]),
-            basic_attack: KeyBinding(["Space".to_string(), "".to_string()]),
-            item_pickup_below: KeyBinding(["KeyB".to_string(), "".to_string()]),
-            hotbar_slot_1: KeyBinding(["Digit1".to_string(), "".to_string()]),
-            hotbar_slot_2: KeyBinding(["Digit2".to_string(), "".to_string()]),
-            hotbar_slot_3: KeyBinding(["Digit3".to_string(), "".to_string()]),
-            hotbar_slot_4: KeyBinding(["Digit4".to_string(), "".to_string()]),
-            hotbar_slot_5: KeyBinding(["Digit5".to_string(), "".to_string()]),
-            hotbar_slot_6: KeyBinding(["Digit6".to_string(), "".to_string()]),
-            hotbar_slot_7: KeyBinding(["Digit7".to_string(), "".to_string()]),
-            hotbar_slot_8: KeyBinding(["Digit8".to_string(), "".to_string()]),
-            hotbar_slot_9: KeyBinding(["Digit9".to_string(), "".to_string()]),
-            hotbar_slot_10: KeyBinding(["Digit0".to_string(), "".to_string()]),
-            hotbar_slot_11: KeyBinding(["Minus".to_string(), "".to_string()]),
-            hotbar_slot_12: KeyBinding(["Equal".to_string(), "".to_string()]),
-            switch_to_inventory: KeyBinding(["KeyA".to_string(), "".to_string()]),
-            switch_to_skills: KeyBinding(["KeyS".to_string(), "".to_string()]),
-            switch_to_spells: KeyBinding(["KeyD".to_string(), "".to_string()]),
-            switch_to_hotbar_1: KeyBinding(["KeyF".to_string(), "".to_string()]),
-            switch_to_hotbar_2: KeyBinding(["KeyG".to_string(), "".to_string()]),
-            switch_to_hotbar_3: KeyBinding(["KeyH".to_string(), "".to_string()]),
+        Self(
+            GameAction::ALL
+                .iter()
+                .map(|action| (*action, KeyBinding::new(action.default_key())))
+                .collect(),
+        )
+    }
+}
+
+impl Serialize for KeyBindings {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_map(
+            self.0
+                .iter()
+                .map(|(action, binding)| (action.action_id(), binding)),
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyBindings {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let saved = BTreeMap::<String, KeyBinding>::deserialize(deserializer)?;
+        let mut bindings = KeyBindings::default();
+        for (id, binding) in saved {
+            if let Some(action) = GameAction::from_action_id(&id) {
+                bindings.0.insert(action, binding);
+            }
         }
+        Ok(bindings)
     }
 }
 
@@ -186,6 +428,82 @@ pub struct ServerEntry {
     pub id: u32,
     pub name: String,
     pub address: String,
+    /// Protocol profile named by the community manifest, shown next to the address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    /// Icon URL from the community manifest; the image itself is cached on disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// JSON or RSS/Atom feed shown on the login screen while this server is selected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub news_url: Option<String>,
+    /// Manifest URL this entry was imported from. Such entries are replaced
+    /// whenever the manifest is refreshed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "ConnectionProfile::is_default")]
+    pub connection: ConnectionProfile,
+}
+
+/// Socket tuning for one server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionProfile {
+    /// Send each packet as soon as it's written (TCP_NODELAY).
+    pub nodelay: bool,
+    /// When non-zero, outgoing packets are held for up to this many
+    /// milliseconds and written together, so walking a long way sends a few
+    /// larger segments instead of one per step.
+    pub batch_window_ms: u16,
+    pub rate_limits: RateLimits,
+}
+
+impl Default for ConnectionProfile {
+    fn default() -> Self {
+        Self {
+            nodelay: true,
+            batch_window_ms: 0,
+            rate_limits: RateLimits::default(),
+        }
+    }
+}
+
+/// Outgoing packets allowed per second by kind, 0 for no limit. Packets over
+/// the limit are held back and sent in order once there's room, so macros
+/// don't get the character kicked for flooding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimits {
+    /// Walking and turning.
+    pub movement: u16,
+    /// Spells, skills, items, pickups, drops and clicks.
+    pub actions: u16,
+    /// Public messages, whispers and emotes.
+    pub chat: u16,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            movement: 0,
+            actions: 12,
+            chat: 3,
+        }
+    }
+}
+
+impl ConnectionProfile {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A community server list the player subscribed to by URL.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServerSubscription {
+    pub url: String,
+    /// Title the manifest gives itself; the URL until it has been fetched once.
+    pub name: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -369,3 +687,12 @@ impl<'de> Deserialize<'de> for CharacterPreview {
         })
     }
 }
+
+/// Position and size of a floating HUD window, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
@@ -3,13 +3,27 @@ use std::sync::Mutex;
 
 #[derive(Debug)]
 pub enum ControlMessage {
-    ReleaseFrontBufferTexture { texture: wgpu::Texture },
-    ResizeBuffers { width: u32, height: u32, scale: f32 },
+    ReleaseFrontBufferTexture {
+        texture: wgpu::Texture,
+    },
+    ReleasePipTexture {
+        texture: wgpu::Texture,
+    },
+    /// Render at `width`x`height`, publishing frames of `output_width`x`output_height`.
+    ResizeBuffers {
+        width: u32,
+        height: u32,
+        scale: f32,
+        output_width: u32,
+        output_height: u32,
+    },
 }
 
 #[derive(Resource)]
 pub struct FrameChannels {
     pub latest_front_buffer: Mutex<Option<wgpu::Texture>>,
+    /// Newest picture-in-picture frame, shown in its own Slint image.
+    pub latest_pip_buffer: Mutex<Option<wgpu::Texture>>,
     pub control_tx: smol::channel::Sender<ControlMessage>,
     pub control_rx: smol::channel::Receiver<ControlMessage>,
 }
@@ -19,6 +33,7 @@ impl FrameChannels {
         let (control_tx, control_rx) = smol::channel::bounded(8);
         Self {
             latest_front_buffer: Mutex::new(None),
+            latest_pip_buffer: Mutex::new(None),
             control_tx,
             control_rx,
         }
@@ -27,3 +42,6 @@ impl FrameChannels {
 
 #[derive(Resource, Default)]
 pub struct BackBufferPool(pub Vec<wgpu::Texture>);
+
+#[derive(Resource, Default)]
+pub struct PipBufferPool(pub Vec<wgpu::Texture>);
@@ -1,8 +1,8 @@
 use bevy::input::ButtonInput;
-use bevy::input::keyboard::KeyCode;
+use bevy::input::keyboard::{KeyCode, NativeKeyCode};
 use bevy::input::mouse::MouseButton;
 use bevy::prelude::*;
-use i_slint_core::items::{KeyEvent, PointerEventButton, PointerEventKind};
+use i_slint_core::items::{KeyEvent, KeyboardModifiers, PointerEventButton, PointerEventKind};
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 
@@ -43,6 +43,8 @@ pub fn new_shared_double_click_queue() -> SharedDoubleClickQueue {
 pub enum QueuedKeyAction {
     Press,
     Release,
+    /// Focus moved into a text field, so the game stops seeing key events.
+    ReleaseAll,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -54,6 +56,18 @@ pub struct QueuedKeyEvent {
     pub alt: bool,
 }
 
+impl QueuedKeyEvent {
+    pub fn release_all() -> Self {
+        Self {
+            code: KeyCode::Unidentified(NativeKeyCode::Unidentified),
+            action: QueuedKeyAction::ReleaseAll,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct QueuedPointerEvent {
     pub kind: PointerEventKind,
@@ -97,6 +111,11 @@ pub fn pump_slint_key_events_system(
     };
 
     for event in events {
+        if let QueuedKeyAction::ReleaseAll = event.action {
+            kb.release_all();
+            continue;
+        }
+
         // Synchronize modifier states to Bevy's input set
         if event.ctrl {
             kb.press(KeyCode::ControlLeft);
@@ -123,6 +142,7 @@ pub fn pump_slint_key_events_system(
             QueuedKeyAction::Release => {
                 kb.release(event.code);
             }
+            QueuedKeyAction::ReleaseAll => {}
         }
     }
 }
@@ -152,6 +172,8 @@ pub fn pump_slint_pointer_events_system(
             PointerEventButton::Left => Some(MouseButton::Left),
             PointerEventButton::Right => Some(MouseButton::Right),
             PointerEventButton::Middle => Some(MouseButton::Middle),
+            PointerEventButton::Back => Some(MouseButton::Back),
+            PointerEventButton::Forward => Some(MouseButton::Forward),
             _ => None,
         };
 
@@ -287,6 +309,35 @@ pub fn keycode_to_string(code: KeyCode) -> String {
     .to_string()
 }
 
+/// Binding string for a captured key or mouse button, e.g. `Ctrl+Shift+KeyR`.
+/// A modifier pressed on its own isn't repeated as its own prefix.
+pub fn binding_string(key: &str, modifiers: &KeyboardModifiers) -> String {
+    let mut binding = String::new();
+    if modifiers.control && !key.starts_with("Control") {
+        binding.push_str("Ctrl+");
+    }
+    if modifiers.shift && !key.starts_with("Shift") {
+        binding.push_str("Shift+");
+    }
+    if modifiers.alt && !key.starts_with("Alt") {
+        binding.push_str("Alt+");
+    }
+    binding.push_str(key);
+    binding
+}
+
+pub fn is_modifier_key(code: KeyCode) -> bool {
+    matches!(
+        code,
+        KeyCode::ControlLeft
+            | KeyCode::ControlRight
+            | KeyCode::ShiftLeft
+            | KeyCode::ShiftRight
+            | KeyCode::AltLeft
+            | KeyCode::AltRight
+    )
+}
+
 fn map_special_key(text: &str) -> Option<KeyCode> {
     use KeyCode::*;
     match text {
@@ -307,6 +358,17 @@ fn map_special_key(text: &str) -> Option<KeyCode> {
         "." | ">" => Some(Period),
         "/" | "?" => Some(Slash),
         "`" | "~" => Some(Backquote),
+        // Shifted digits, so Shift+Digit chords can be bound and pressed
+        "!" => Some(Digit1),
+        "@" => Some(Digit2),
+        "#" => Some(Digit3),
+        "$" => Some(Digit4),
+        "%" => Some(Digit5),
+        "^" => Some(Digit6),
+        "&" => Some(Digit7),
+        "*" => Some(Digit8),
+        "(" => Some(Digit9),
+        ")" => Some(Digit0),
         " " => Some(Space),
         "\t" => Some(Tab),
         "\u{8}" => Some(Backspace),
@@ -323,9 +385,9 @@ fn map_special_key(text: &str) -> Option<KeyCode> {
         "\u{f70d}" | "F10" => Some(F10),
         "\u{f70e}" | "F11" => Some(F11),
         "\u{f70f}" | "F12" => Some(F12),
-        "Control" => Some(ControlLeft),
-        "Shift" => Some(ShiftLeft),
-        "Alt" => Some(AltLeft),
+        "\u{11}" | "Control" => Some(ControlLeft),
+        "\u{10}" | "Shift" => Some(ShiftLeft),
+        "\u{12}" | "Alt" => Some(AltLeft),
         _ => None,
     }
 }
@@ -1,16 +1,47 @@
 use std::fmt;
 use std::time::{Duration, Instant};
 
-use game_types::{KeyBindings, SavedCredentialPublic, ServerEntry, SlotPanelType};
+use game_types::{
+    ActionSection, GameAction, KeyBindings, PressMode, SavedCredentialPublic, ServerEntry,
+    SlotPanelType, WindowGeometry,
+};
 use packets::server::{LoginMessageType, SpellType};
 
 #[derive(Debug, Clone)]
 pub enum LoginError {
     Response(LoginMessageType),
     Network(String),
+    /// The server turned the login away because it is full or queueing players.
+    ServerFull(String),
     Unknown,
 }
 
+/// Result of probing a server's lobby before login.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerStatus {
+    Online { latency_ms: u32 },
+    Offline,
+}
+
+/// How urgent a notification is; sets its color and how long it stays up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifySeverity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// What raised a notification, so whispers, friends etc. can be told apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyKind {
+    General,
+    Whisper,
+    Friend,
+    Durability,
+    Connection,
+}
+
 #[derive(Debug)]
 pub enum UiToCore {
     LoginSubmit {
@@ -22,10 +53,34 @@ pub enum UiToCore {
     LoginUseSaved {
         id: String,
     },
+    /// Master password for the encrypted credential store, creating it if there is none yet.
+    VaultUnlock {
+        password: String,
+    },
+    /// The master password prompt was dismissed; the login waiting on it is dropped.
+    VaultCancel,
     LoginRemoveSaved {
         id: String,
     },
     RequestSnapshot,
+    /// Copy the pending crash report to the clipboard and archive it.
+    CrashReportCopy {
+        path: String,
+    },
+    /// Send the pending crash report to the configured upload URL and archive it.
+    CrashReportUpload {
+        path: String,
+    },
+    /// Archive the pending crash report without copying it.
+    CrashReportDismiss {
+        path: String,
+    },
+    /// Replace unreadable settings with the last good backup, or with defaults.
+    SettingsRecover {
+        restore_backup: bool,
+    },
+    /// Stop waiting to retry a login the server turned away as full.
+    LoginRetryCancel,
     ServersChangeCurrent {
         id: u32,
     },
@@ -38,6 +93,14 @@ pub enum UiToCore {
     ServersRemove {
         id: u32,
     },
+    /// Subscribe to a community server list published as a JSON manifest.
+    ServerSubscriptionAdd {
+        url: String,
+    },
+    /// Drop a subscription along with the servers it brought in.
+    ServerSubscriptionRemove {
+        url: String,
+    },
     InputKeyboard {
         action: String,
         code: String,
@@ -96,9 +159,121 @@ pub enum UiToCore {
         sfx: Option<f32>,
         music: Option<f32>,
     },
+    /// Volume for one sound category, see `SoundCategory`.
+    CategoryVolumeChange {
+        category: u8,
+        volume: f32,
+    },
+    /// Output device by name, empty for the system default.
+    AudioDeviceChange {
+        device: String,
+    },
+    XRayModeChange {
+        mode: u8,
+    },
+    OpenInteriorsChange {
+        enabled: bool,
+    },
+    AttackTelegraphsChange {
+        enabled: bool,
+    },
     ScaleChange {
         scale: f32,
     },
+    RenderScalingChange {
+        mode: u8,
+    },
+    GammaChange {
+        gamma: f32,
+    },
+    DebugOverlayChange {
+        enabled: bool,
+    },
+    PotatoModeChange {
+        enabled: bool,
+    },
+    GpuAdapterChange {
+        backend: u8,
+        preference: u8,
+    },
+    TextureBudgetChange {
+        mb: u32,
+    },
+    StreamerModeChange {
+        enabled: bool,
+    },
+    StreamOutputChange {
+        enabled: bool,
+    },
+    DebugConsoleChange {
+        enabled: bool,
+    },
+    IdleDaydreamChange {
+        minutes: u32,
+    },
+    AntiIdleChange {
+        enabled: bool,
+        max_minutes: u32,
+    },
+    DurabilityWarningChange {
+        percent: u32,
+    },
+    SpellQueueWindowChange {
+        ms: u32,
+    },
+    ChatBubblesChange {
+        enabled: bool,
+    },
+    MentionAlertsChange {
+        highlight: bool,
+        sound: bool,
+    },
+    /// Chat filter switch and word list, words separated by commas or lines.
+    ChatFilterChange {
+        enabled: bool,
+        words: String,
+    },
+    /// Whether the chat filter applies to one channel, see `ChatChannel`.
+    ChatFilterChannelChange {
+        channel: u8,
+        enabled: bool,
+    },
+    AutoPickupChange {
+        enabled: bool,
+        gold: bool,
+    },
+    /// NPC options to confirm before picking, separated by commas or lines.
+    DangerousDialogOptionsChange {
+        options: String,
+    },
+    HotbarLayoutChange {
+        rows: u32,
+        icon_size: u32,
+    },
+    /// New hotbar top-left corner, `None` docks it again.
+    HotbarMoved {
+        position: Option<(f32, f32)>,
+    },
+    ClassicHudChange {
+        enabled: bool,
+    },
+    /// Context-driven hotbar page switching, pages as `HotbarPanel` values.
+    HotbarAutoSwitchChange {
+        enabled: bool,
+        town_panel: u8,
+        combat_panel: u8,
+    },
+    /// Notification policy (see `NotificationPolicy`) for one social status.
+    NotificationPolicyChange {
+        status: u8,
+        policy: u8,
+    },
+    TurnTapChange {
+        ms: u32,
+    },
+    DirectionPriorityChange {
+        mode: u8,
+    },
     RebindKey {
         action: String,
         new_key: String,
@@ -108,6 +283,11 @@ pub enum UiToCore {
         action: String,
         index: usize,
     },
+    /// Switch a held action between hold and toggle, see `PressMode`.
+    KeyPressModeChange {
+        action: String,
+        mode: u8,
+    },
     /// Quit the application.
     ExitApplication,
     /// Return to the main menu from in-game UI.
@@ -138,6 +318,164 @@ pub enum UiToCore {
         id: i32,
     },
     WorldContextMenuClose,
+    /// Count chosen in the split-stack dialog for the pending drop.
+    SplitStackConfirm {
+        count: u32,
+    },
+    SplitStackCancel,
+    /// Name entered for a creature sprite; blank forgets the saved name.
+    CreatureNameSubmit {
+        sprite: u16,
+        name: String,
+    },
+    CreatureNameCancel,
+    /// Right-click on a spell in the spells panel, at window coordinates.
+    SpellContextMenu {
+        slot: u8,
+        x: f32,
+        y: f32,
+    },
+    /// Duration entered for the prompted spell timer, e.g. "90", "1:30" or "2m".
+    SpellTimerSubmit {
+        duration: String,
+    },
+    SpellTimerPromptCancel,
+    /// Right-click on an inventory item, at window coordinates.
+    ItemContextMenu {
+        slot: u8,
+        x: f32,
+        y: f32,
+    },
+    /// Go ahead with the use or drop of a flagged item.
+    ItemConfirmAccept,
+    ItemConfirmCancel,
+    /// Send the dangerous NPC option held back for confirmation.
+    DialogConfirmAccept,
+    DialogConfirmCancel,
+    /// Turn to the next page of the NPC dialog on screen.
+    DialogNext,
+    /// Keep turning pages until one has options or a text field.
+    DialogSkip,
+    /// Always skip through the pages of the NPC on screen.
+    DialogAutoNext {
+        enabled: bool,
+    },
+    /// Save the options picked to reach the NPC menu on screen as its
+    /// shortcut, or forget the shortcut when it is already saved.
+    MenuShortcutToggle,
+    /// Copy the location readout to the clipboard for sharing.
+    CopyLocation {
+        text: String,
+    },
+    /// Name entered for a pin on a tile of the current map; blank places none.
+    MapPinSubmit {
+        x: u16,
+        y: u16,
+        name: String,
+    },
+    MapPinCancel,
+    /// Write every pin on the current server to a file for sharing.
+    MapPinsExport,
+    /// Merge the pin files in the shared pins folder into the current server.
+    MapPinsImport,
+    /// A countdown chip was clicked away.
+    SpellTimerCancel {
+        id: u32,
+    },
+    /// Combat log source/target name filters; blank shows everything.
+    CombatLogFilter {
+        source: String,
+        target: String,
+    },
+    CombatLogClear,
+    /// Save the filtered combat log to a text file.
+    CombatLogExport,
+    /// Conversation history search box; matches NPC names and dialog text.
+    DialogHistorySearch {
+        text: String,
+    },
+    DialogHistoryClear,
+    /// Bank viewer search box; matches item names.
+    BankSearch {
+        text: String,
+    },
+    /// Bank viewer order: most held first, or by name.
+    BankSort {
+        by_amount: bool,
+    },
+    /// Withdraw every stack of an item from the open withdraw list.
+    BankWithdrawAll {
+        name: String,
+    },
+    AbilityStatsReset,
+    /// Entity inspector search box; matches server ids and names.
+    EntityInspectorSearch {
+        query: String,
+    },
+    EntityInspectorClose,
+    /// Archive browser search box; matches paths.
+    ArchiveBrowserSearch {
+        query: String,
+    },
+    ArchiveBrowserSelect {
+        path: String,
+    },
+    /// Frame, dye and palette row to preview; `row` is `None` to pick it the
+    /// way the renderer does.
+    ArchiveBrowserView {
+        frame: usize,
+        dye: u8,
+        row: Option<usize>,
+    },
+    ArchiveBrowserClose,
+    /// Item link clicked in chat.
+    ShowItemLink {
+        name: String,
+    },
+    /// The chat log was scrolled to the top of the lines it holds.
+    ChatLoadOlder,
+    /// The chat log left the bottom, or returned to it.
+    ChatScrolledBack {
+        scrolled_back: bool,
+    },
+    /// A hidden whisper in this chat log row was clicked open.
+    ChatReveal {
+        row: usize,
+    },
+    /// A floating HUD window was moved or resized.
+    WindowGeometryChange {
+        id: String,
+        geometry: WindowGeometry,
+    },
+    /// Put every HUD window back in its default place.
+    WindowLayoutReset,
+    /// Group member to show in the picture-in-picture view, `None` closes it.
+    PipWatch {
+        name: Option<String>,
+    },
+    /// The whole HUD, chat included, was hidden or shown again.
+    UiHiddenChange {
+        hidden: bool,
+    },
+    /// A notification was clicked away before it expired.
+    NotificationDismiss {
+        id: u32,
+    },
+    /// The notification history drawer was opened, so everything in it is read.
+    NotificationHistoryOpened,
+    NotificationHistoryClear,
+    /// Add or remove a character from the friends list, whose logins are announced.
+    FriendToggle {
+        name: String,
+    },
+    /// Social status picked on the profile.
+    SocialStatusChange {
+        status: u8,
+    },
+    /// The pet frame was clicked, targeting the pet with a waiting spell.
+    PetFrameClick,
+    /// Stop tracking the pet.
+    PetRelease,
 }
 
 /// A menu entry that can be a text option or an item with sprite
@@ -154,6 +492,13 @@ pub struct MenuEntryUi {
     pub cost: i32,
 }
 
+#[derive(Debug, Clone)]
+pub struct SessionSummaryRowUi {
+    pub label: String,
+    pub session: String,
+    pub lifetime: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct WorldContextMenuEntryUi {
     pub id: i32,
@@ -215,6 +560,8 @@ pub enum CoreToUi {
         current_server_id: Option<u32>,
         logins: Vec<SavedCredentialPublic>,
         login_error: Option<LoginError>,
+        /// Usernames and server addresses are masked for display
+        streamer_mode: bool,
     },
     EnteredGame,
     ChatAppend {
@@ -241,6 +588,24 @@ pub enum CoreToUi {
         entries: Vec<WorldContextMenuEntryUi>,
     },
     HideWorldContextMenu,
+    /// Tooltip for a chat item link; details are empty when the item isn't ours.
+    ItemLinkTooltip {
+        name: String,
+        sprite: Option<u16>,
+        details: String,
+    },
+    /// Saved HUD window geometry for the current character; missing ids use defaults.
+    WindowLayoutSync {
+        windows: Vec<(String, WindowGeometry)>,
+    },
+    /// Names of the audio output devices currently available.
+    AudioDevices {
+        devices: Vec<String>,
+    },
+    /// Adapter, limits and formats of the GPU in use, one line each.
+    GpuDiagnostics {
+        lines: Vec<String>,
+    },
     /// Close any open menu/dialog
     DisplayMenuClose,
     /// Text entry dialog (e.g., quantity input)
@@ -255,77 +620,155 @@ pub enum CoreToUi {
     },
     SettingsSync {
         xray_size: u8,
+        xray_mode: u8,
+        open_interiors: bool,
+        attack_telegraphs: bool,
         sfx_volume: f32,
         music_volume: f32,
+        combat_volume: f32,
+        spell_volume: f32,
+        ambient_volume: f32,
+        /// Selected output device, empty for the system default.
+        audio_device: String,
         scale: f32,
+        render_scaling: u8,
+        gamma: f32,
+        debug_overlay: bool,
+        potato_mode: bool,
+        gpu_backend: u8,
+        gpu_preference: u8,
+        texture_budget_mb: u32,
+        streamer_mode: bool,
+        stream_output_enabled: bool,
+        stream_output_port: u16,
+        debug_console_enabled: bool,
+        debug_console_port: u16,
+        idle_daydream_minutes: u32,
+        anti_idle: bool,
+        anti_idle_max_minutes: u32,
+        durability_warning_percent: u32,
+        spell_queue_window_ms: u32,
+        chat_bubbles: bool,
+        mention_highlight: bool,
+        mention_sound: bool,
+        chat_filter: bool,
+        chat_filter_words: String,
+        /// Whether the chat filter applies, indexed by `ChatChannel`.
+        chat_filter_channels: Vec<bool>,
+        auto_pickup: bool,
+        auto_pickup_gold: bool,
+        /// Comma separated, see `DangerousDialogOptionsChange`.
+        dangerous_dialog_options: String,
+        /// `NotificationPolicy` values indexed by social status.
+        notification_policies: Vec<u8>,
+        hotbar_rows: u32,
+        hotbar_icon_size: u32,
+        hotbar_position: Option<(f32, f32)>,
+        classic_hud: bool,
+        hotbar_auto_switch: bool,
+        hotbar_town_panel: u8,
+        hotbar_combat_panel: u8,
+        turn_tap_ms: u32,
+        direction_priority: u8,
         key_bindings: KeyBindingsUi,
     },
+    /// Transient notice shown over the game view
+    Toast {
+        text: String,
+    },
+    /// Notification card that dismisses itself and is kept in the history drawer
+    Notify {
+        severity: NotifySeverity,
+        kind: NotifyKind,
+        text: String,
+    },
+    /// A crash report from a previous run hasn't been handled yet; `error` says
+    /// why the last attempt to copy or upload it failed.
+    CrashReportPending {
+        path: String,
+        error: Option<String>,
+        can_upload: bool,
+    },
+    /// settings.toml couldn't be loaded and defaults are in use until the player chooses
+    SettingsRecoveryPending {
+        error: String,
+        has_backup: bool,
+    },
+    /// What the character did in the session just logged out of, next to their lifetime totals.
+    SessionSummary {
+        character: String,
+        rows: Vec<SessionSummaryRowUi>,
+    },
+    /// The game dropped back to the login screen because the connection died.
+    ConnectionLost {
+        reason: String,
+    },
+    /// Lobby probe result for one configured server.
+    ServerStatus {
+        server_id: u32,
+        status: ServerStatus,
+    },
+    /// News feed of the selected server; status explains an empty or stale feed.
+    ServerNews {
+        items: Vec<ServerNewsItemUi>,
+        status: String,
+    },
+    /// The system keyring is unavailable and the encrypted credential store needs its
+    /// master password (or a new one, when `create`) before the login can go ahead.
+    VaultPrompt {
+        create: bool,
+        error: Option<String>,
+    },
+    /// Subscribed community server lists and how their last refresh went.
+    ServerSubscriptions {
+        subscriptions: Vec<ServerSubscriptionUi>,
+    },
+    /// The server is full; the login is retried when the countdown ends.
+    LoginQueue {
+        message: String,
+        seconds_left: u32,
+    },
+}
+
+/// One row on the controls screen.
+#[derive(Debug, Clone)]
+pub struct KeyBindingUi {
+    pub action_id: String,
+    pub label: String,
+    pub section: ActionSection,
+    pub keys: [String; 2],
+    /// Held actions can switch between hold and toggle.
+    pub can_toggle: bool,
+    pub toggle: bool,
 }
 
+/// Every binding, in controls-screen order.
 #[derive(Debug, Clone)]
-pub struct KeyBindingsUi {
-    pub move_up: [String; 2],
-    pub move_down: [String; 2],
-    pub move_left: [String; 2],
-    pub move_right: [String; 2],
-    pub inventory: [String; 2],
-    pub skills: [String; 2],
-    pub spells: [String; 2],
-    pub settings: [String; 2],
-    pub refresh: [String; 2],
-    pub basic_attack: [String; 2],
-    pub hotbar_slot_1: [String; 2],
-    pub hotbar_slot_2: [String; 2],
-    pub hotbar_slot_3: [String; 2],
-    pub hotbar_slot_4: [String; 2],
-    pub hotbar_slot_5: [String; 2],
-    pub hotbar_slot_6: [String; 2],
-    pub hotbar_slot_7: [String; 2],
-    pub hotbar_slot_8: [String; 2],
-    pub hotbar_slot_9: [String; 2],
-    pub hotbar_slot_10: [String; 2],
-    pub hotbar_slot_11: [String; 2],
-    pub hotbar_slot_12: [String; 2],
-    pub switch_to_inventory: [String; 2],
-    pub switch_to_skills: [String; 2],
-    pub switch_to_spells: [String; 2],
-    pub switch_to_hotbar_1: [String; 2],
-    pub switch_to_hotbar_2: [String; 2],
-    pub switch_to_hotbar_3: [String; 2],
+pub struct KeyBindingsUi(pub Vec<KeyBindingUi>);
+
+impl KeyBindingsUi {
+    pub fn keys(&self, action: GameAction) -> Option<&[String; 2]> {
+        self.0
+            .iter()
+            .find(|b| b.action_id == action.action_id())
+            .map(|b| &b.keys)
+    }
 }
 
 impl From<&KeyBindings> for KeyBindingsUi {
     fn from(kb: &KeyBindings) -> Self {
-        Self {
-            move_up: kb.move_up.0.clone(),
-            move_down: kb.move_down.0.clone(),
-            move_left: kb.move_left.0.clone(),
-            move_right: kb.move_right.0.clone(),
-            inventory: kb.inventory.0.clone(),
-            skills: kb.skills.0.clone(),
-            spells: kb.spells.0.clone(),
-            settings: kb.settings.0.clone(),
-            refresh: kb.refresh.0.clone(),
-            basic_attack: kb.basic_attack.0.clone(),
-            hotbar_slot_1: kb.hotbar_slot_1.0.clone(),
-            hotbar_slot_2: kb.hotbar_slot_2.0.clone(),
-            hotbar_slot_3: kb.hotbar_slot_3.0.clone(),
-            hotbar_slot_4: kb.hotbar_slot_4.0.clone(),
-            hotbar_slot_5: kb.hotbar_slot_5.0.clone(),
-            hotbar_slot_6: kb.hotbar_slot_6.0.clone(),
-            hotbar_slot_7: kb.hotbar_slot_7.0.clone(),
-            hotbar_slot_8: kb.hotbar_slot_8.0.clone(),
-            hotbar_slot_9: kb.hotbar_slot_9.0.clone(),
-            hotbar_slot_10: kb.hotbar_slot_10.0.clone(),
-            hotbar_slot_11: kb.hotbar_slot_11.0.clone(),
-            hotbar_slot_12: kb.hotbar_slot_12.0.clone(),
-            switch_to_inventory: kb.switch_to_inventory.0.clone(),
-            switch_to_skills: kb.switch_to_skills.0.clone(),
-            switch_to_spells: kb.switch_to_spells.0.clone(),
-            switch_to_hotbar_1: kb.switch_to_hotbar_1.0.clone(),
-            switch_to_hotbar_2: kb.switch_to_hotbar_2.0.clone(),
-            switch_to_hotbar_3: kb.switch_to_hotbar_3.0.clone(),
-        }
+        Self(
+            kb.iter()
+                .map(|(action, binding)| KeyBindingUi {
+                    action_id: action.action_id().to_string(),
+                    label: action.label().to_string(),
+                    section: action.section(),
+                    keys: binding.keys.clone(),
+                    can_toggle: action.is_held(),
+                    toggle: binding.mode == PressMode::Toggle,
+                })
+                .collect(),
+        )
     }
 }
 
@@ -360,6 +803,7 @@ pub struct WorldListMemberUi {
     pub class: String,
     pub color: [f32; 4],
     pub is_master: bool,
+    pub is_friend: bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -377,6 +821,11 @@ pub struct ChatEntryUi {
     pub show_in_message_box: bool,
     pub show_in_action_bar: bool,
     pub color: Option<String>,
+    /// Shown instead of the text until clicked in the chat log (whispers in
+    /// streamer mode, filtered words)
+    pub concealed: Option<String>,
+    /// Names the player's character
+    pub mention: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -553,6 +1002,7 @@ pub struct SpellUi {
 pub struct ServerNoId {
     pub name: String,
     pub address: String,
+    pub news_url: String,
 }
 
 #[derive(Debug, Clone)]
@@ -560,4 +1010,22 @@ pub struct ServerWithId {
     pub id: u32,
     pub name: String,
     pub address: String,
+    pub news_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerNewsItemUi {
+    pub title: String,
+    pub date: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerSubscriptionUi {
+    pub url: String,
+    pub name: String,
+    /// Servers currently imported from this manifest.
+    pub server_count: u32,
+    /// Why the last refresh failed; the servers from the refresh before are kept.
+    pub error: Option<String>,
 }
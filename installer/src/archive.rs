@@ -0,0 +1,314 @@
+//! Reading back an installed archive: listing it, checking every file can be
+//! read, and checksumming its contents.
+//!
+//! The installer writes a checksum manifest next to the archive
+//! (`<archive>.manifest`, one `crc32 size path` line per file), which
+//! [`verify`] compares the contents against.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crc32fast::Hasher;
+use jubako as jbk;
+use libarx::{self as arx, CommonEntry, FullBuilder};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub size: u64,
+    /// Only filled in when the contents were read.
+    pub crc32: Option<u32>,
+}
+
+#[derive(Debug, Default)]
+pub struct Verification {
+    pub files: usize,
+    pub bytes: u64,
+    /// The archive was written by this version of the installer.
+    pub up_to_date: bool,
+    /// Files whose contents couldn't be read, with the reason.
+    pub unreadable: Vec<(String, String)>,
+    /// Whether a checksum manifest was found to compare against.
+    pub has_manifest: bool,
+    /// Files that don't match the manifest, or are missing from the archive.
+    pub mismatched: Vec<String>,
+}
+
+impl Verification {
+    pub fn is_ok(&self) -> bool {
+        self.up_to_date && self.unreadable.is_empty() && self.mismatched.is_empty()
+    }
+
+    /// Every file that needs extracting again.
+    pub fn damaged(&self) -> Vec<String> {
+        self.unreadable
+            .iter()
+            .map(|(path, _)| path.clone())
+            .chain(self.mismatched.iter().cloned())
+            .collect()
+    }
+}
+
+pub fn manifest_path(archive: &Path) -> PathBuf {
+    let mut path = archive.as_os_str().to_owned();
+    path.push(".manifest");
+    PathBuf::from(path)
+}
+
+pub fn format_manifest(entries: &[ArchiveEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "{:08x}  {:>10}  {}\n",
+                entry.crc32.unwrap_or_default(),
+                entry.size,
+                entry.path
+            )
+        })
+        .collect()
+}
+
+pub fn parse_manifest(text: &str) -> anyhow::Result<Vec<ArchiveEntry>> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (crc32, rest) = line
+                .split_once(' ')
+                .ok_or_else(|| anyhow::anyhow!("Malformed manifest line: {}", line))?;
+            let (size, path) = rest
+                .trim_start()
+                .split_once(' ')
+                .ok_or_else(|| anyhow::anyhow!("Malformed manifest line: {}", line))?;
+            Ok(ArchiveEntry {
+                path: path.trim_start().to_string(),
+                size: size.parse()?,
+                crc32: Some(u32::from_str_radix(crc32, 16)?),
+            })
+        })
+        .collect()
+}
+
+/// Checksums the archive and writes its manifest alongside it.
+pub fn write_manifest(archive: &Path) -> anyhow::Result<()> {
+    let entries = manifest(archive)?;
+    std::fs::write(manifest_path(archive), format_manifest(&entries))?;
+    Ok(())
+}
+
+/// Contents of a file in the archive, or `None` if it isn't there.
+pub fn read_file(archive: &arx::Arx, path: &str) -> Option<Vec<u8>> {
+    let arx::Entry::File(entry) = archive
+        .get_entry::<FullBuilder>(arx::Path::new(path))
+        .ok()?
+    else {
+        return None;
+    };
+    read_content(archive, &entry).ok()
+}
+
+/// Whether the archive's `VERSION` entry is `version`, see
+/// [`crate::InstallerProfile::archive_version`].
+pub fn is_up_to_date(archive: &arx::Arx, version: &[u8]) -> bool {
+    read_file(archive, "VERSION").is_some_and(|found| found == version)
+}
+
+fn read_content(archive: &arx::Arx, entry: &arx::FileEntry) -> anyhow::Result<Vec<u8>> {
+    match archive.get_bytes(entry.content())? {
+        Some(jbk::reader::MayMissPack::FOUND(Some(bytes))) => {
+            let mut buf = vec![];
+            bytes.stream().read_to_end(&mut buf)?;
+            Ok(buf)
+        }
+        Some(jbk::reader::MayMissPack::MISSING(_)) => Err(anyhow::anyhow!("content pack missing")),
+        _ => Err(anyhow::anyhow!("no content")),
+    }
+}
+
+#[derive(Default)]
+struct WalkState {
+    dirs: Vec<String>,
+    entries: Vec<ArchiveEntry>,
+    unreadable: Vec<(String, String)>,
+}
+
+impl WalkState {
+    fn path_of(&self, name: &[u8]) -> String {
+        let name = String::from_utf8_lossy(name);
+        if self.dirs.is_empty() {
+            name.into_owned()
+        } else {
+            format!("{}/{}", self.dirs.join("/"), name)
+        }
+    }
+}
+
+/// The walker keeps its context to itself, so the state lives here instead.
+struct Lister<'a> {
+    archive: &'a arx::Arx,
+    read_contents: bool,
+    state: RefCell<WalkState>,
+}
+
+impl arx::walk::Operator<(), FullBuilder> for Lister<'_> {
+    type Error = anyhow::Error;
+
+    fn on_start(&self, _: &mut ()) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn on_stop(&self, _: &mut ()) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn on_directory_enter(&self, _: &mut (), dir: &arx::Dir) -> anyhow::Result<bool> {
+        self.state
+            .borrow_mut()
+            .dirs
+            .push(String::from_utf8_lossy(dir.path()).into_owned());
+        Ok(true)
+    }
+
+    fn on_directory_exit(&self, _: &mut (), _dir: &arx::Dir) -> anyhow::Result<()> {
+        self.state.borrow_mut().dirs.pop();
+        Ok(())
+    }
+
+    fn on_file(&self, _: &mut (), file: &arx::FileEntry) -> anyhow::Result<()> {
+        let mut state = self.state.borrow_mut();
+        let path = state.path_of(file.path());
+        let mut crc32 = None;
+        if self.read_contents {
+            match read_content(self.archive, file) {
+                Ok(content) => {
+                    let mut hasher = Hasher::new();
+                    hasher.update(&content);
+                    crc32 = Some(hasher.finalize());
+                }
+                Err(e) => state.unreadable.push((path.clone(), e.to_string())),
+            }
+        }
+        state.entries.push(ArchiveEntry {
+            path,
+            size: file.size().into_u64(),
+            crc32,
+        });
+        Ok(())
+    }
+
+    fn on_link(&self, _: &mut (), _link: &arx::Link) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+fn walk(path: &Path, read_contents: bool) -> anyhow::Result<(arx::Arx, WalkState)> {
+    let archive = arx::Arx::new(path)?;
+    let lister = Lister {
+        archive: &archive,
+        read_contents,
+        state: RefCell::default(),
+    };
+    arx::walk::Walker::new(&archive, ()).run::<FullBuilder, _>(&lister)?;
+    let state = lister.state.into_inner();
+    Ok((archive, state))
+}
+
+/// Every file in the archive, without reading contents.
+pub fn list(path: &Path) -> anyhow::Result<Vec<ArchiveEntry>> {
+    Ok(walk(path, false)?.1.entries)
+}
+
+/// Every file in the archive with the CRC32 of its contents. Fails on the
+/// first file that can't be read.
+pub fn manifest(path: &Path) -> anyhow::Result<Vec<ArchiveEntry>> {
+    let (_, state) = walk(path, true)?;
+    if let Some((file, reason)) = state.unreadable.first() {
+        anyhow::bail!("Failed to read {}: {}", file, reason);
+    }
+    Ok(state.entries)
+}
+
+/// Reads every file in the archive and checks it has the given version, and
+/// its contents against the manifest if there is one.
+pub fn verify(path: &Path, version: &[u8]) -> anyhow::Result<Verification> {
+    let (archive, state) = walk(path, true)?;
+    let expected = match std::fs::read_to_string(manifest_path(path)) {
+        Ok(text) => Some(parse_manifest(&text)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    let mismatched = expected
+        .as_deref()
+        .map(|expected| mismatched(expected, &state.entries))
+        .unwrap_or_default();
+    Ok(Verification {
+        files: state.entries.len(),
+        bytes: state.entries.iter().map(|entry| entry.size).sum(),
+        up_to_date: is_up_to_date(&archive, version),
+        unreadable: state.unreadable,
+        has_manifest: expected.is_some(),
+        mismatched,
+    })
+}
+
+/// Paths in `expected` that are missing from `actual` or differ in size or
+/// checksum. Unreadable files have no checksum and are reported separately.
+fn mismatched(expected: &[ArchiveEntry], actual: &[ArchiveEntry]) -> Vec<String> {
+    let actual: HashMap<&str, &ArchiveEntry> = actual
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry))
+        .collect();
+    expected
+        .iter()
+        .filter(|entry| match actual.get(entry.path.as_str()) {
+            None => true,
+            Some(found) => {
+                found.size != entry.size || found.crc32.is_some_and(|crc| Some(crc) != entry.crc32)
+            }
+        })
+        .map(|entry| entry.path.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_join_the_directories_walked() {
+        let mut state = WalkState::default();
+        assert_eq!(state.path_of(b"VERSION"), "VERSION");
+        state.dirs.push("khan".into());
+        state.dirs.push("mb".into());
+        assert_eq!(state.path_of(b"001.epfanim"), "khan/mb/001.epfanim");
+    }
+
+    #[test]
+    fn manifest_round_trips_and_finds_damaged_files() {
+        let entry = |path: &str, crc32| ArchiveEntry {
+            path: path.into(),
+            size: 120,
+            crc32: Some(crc32),
+        };
+        let expected = vec![
+            entry("seo/tile 1.ktx2", 0xdeadbeef),
+            entry("hades/mns001.epf.bin", 7),
+            entry("music/1.mus", 9),
+        ];
+        assert_eq!(
+            parse_manifest(&format_manifest(&expected)).unwrap(),
+            expected
+        );
+
+        let actual = vec![
+            entry("seo/tile 1.ktx2", 0xdeadbeef),
+            entry("hades/mns001.epf.bin", 8),
+        ];
+        assert_eq!(
+            mismatched(&expected, &actual),
+            vec!["hades/mns001.epf.bin", "music/1.mus"]
+        );
+    }
+}
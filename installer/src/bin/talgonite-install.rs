@@ -0,0 +1,234 @@
+//! Standalone front end for the installer, for building or checking the game
+//! archive without starting the client.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use indicatif::{ProgressBar, ProgressStyle};
+use installer::{
+    AssetCategory, InstallOptions, InstallProgress, InstallSource, InstallerProfile, archive,
+};
+
+const USAGE: &str = "\
+Usage: talgonite-install <command> <archive>
+
+Commands:
+  install <archive> [--only <categories>]
+                      Extract the game assets into the archive. Categories are
+                      a comma separated list of maps, creatures, players,
+                      music and other. Defaults to all of them.
+  repair <archive> [--only <categories>]
+                      Re-extract only the files that fail verification
+  verify <archive>    Read every file and check it against the manifest
+  list <archive>      List the files in the archive
+  manifest <archive>  Print the CRC32 and size of every file
+
+Options:
+  --version <version> Client version to install or verify against
+  --source <source>   Installer URL or local path to try before the
+                      built-in mirrors. Can be given more than once.";
+
+struct BarProgress(ProgressBar);
+
+impl InstallProgress for BarProgress {
+    fn report(&self, percent: f32, message: String) {
+        self.0.set_position((percent * 1000.0) as u64);
+        self.0.set_message(message);
+    }
+}
+
+enum Command {
+    Install(PathBuf, InstallOptions),
+    Repair(PathBuf, InstallOptions),
+    Verify(PathBuf, &'static InstallerProfile),
+    List(PathBuf),
+    Manifest(PathBuf),
+}
+
+fn parse_categories(list: &str) -> Result<Vec<AssetCategory>, String> {
+    list.split(',')
+        .filter(|name| !name.trim().is_empty())
+        .map(|name| {
+            AssetCategory::from_name(name).ok_or_else(|| format!("Unknown category: {}", name))
+        })
+        .collect()
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let [command, rest @ ..] = args else {
+        return Err("Missing command".into());
+    };
+    let mut archive = None;
+    let mut options = InstallOptions::default();
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--only" if command == "install" || command == "repair" => {
+                let list = rest.next().ok_or("--only needs a list of categories")?;
+                options.categories = parse_categories(list)?;
+                if options.categories.is_empty() {
+                    return Err("--only needs at least one category".into());
+                }
+            }
+            "--version" if command != "list" && command != "manifest" => {
+                let version = rest.next().ok_or("--version needs a client version")?;
+                options.profile = InstallerProfile::find(version)
+                    .ok_or_else(|| format!("Unknown client version: {}", version))?;
+            }
+            "--source" if command == "install" || command == "repair" => {
+                let source = rest.next().ok_or("--source needs a URL or path")?;
+                options.sources.push(InstallSource::parse(source));
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            path if archive.is_none() => archive = Some(PathBuf::from(path)),
+            extra => return Err(format!("Unexpected argument: {}", extra)),
+        }
+    }
+    let archive = archive.ok_or("Missing archive path")?;
+    match command.as_str() {
+        "install" => Ok(Command::Install(archive, options)),
+        "repair" => Ok(Command::Repair(archive, options)),
+        "verify" => Ok(Command::Verify(archive, options.profile)),
+        "list" => Ok(Command::List(archive)),
+        "manifest" => Ok(Command::Manifest(archive)),
+        other => Err(format!("Unknown command: {}", other)),
+    }
+}
+
+fn progress_bar() -> anyhow::Result<ProgressBar> {
+    let bar = ProgressBar::new(1000);
+    bar.set_style(ProgressStyle::with_template("{bar:40} {msg}")?);
+    Ok(bar)
+}
+
+fn install(path: &Path, options: &InstallOptions) -> anyhow::Result<bool> {
+    let bar = progress_bar()?;
+    installer::install_with(path, options, Some(Arc::new(BarProgress(bar.clone()))))?;
+    bar.finish();
+    Ok(true)
+}
+
+fn repair(path: &Path, options: &InstallOptions) -> anyhow::Result<bool> {
+    let bar = progress_bar()?;
+    let damaged = installer::repair(path, options, Some(Arc::new(BarProgress(bar.clone()))))?;
+    bar.finish();
+    for file in &damaged {
+        println!("Repaired {}", file);
+    }
+    if damaged.is_empty() {
+        println!("Nothing to repair");
+    }
+    Ok(true)
+}
+
+fn verify(path: &Path, profile: &InstallerProfile) -> anyhow::Result<bool> {
+    let result = archive::verify(path, &profile.archive_version())?;
+    for (file, reason) in &result.unreadable {
+        eprintln!("{}: {}", file, reason);
+    }
+    for file in &result.mismatched {
+        eprintln!("{}: doesn't match the manifest", file);
+    }
+    println!(
+        "{} files, {} bytes, {} unreadable, {} mismatched",
+        result.files,
+        result.bytes,
+        result.unreadable.len(),
+        result.mismatched.len()
+    );
+    if !result.has_manifest {
+        println!("No checksum manifest, only checked that files can be read");
+    }
+    if !result.up_to_date {
+        println!("The archive is partial or from a different installer version");
+    }
+    Ok(result.is_ok())
+}
+
+fn list(path: &Path) -> anyhow::Result<bool> {
+    for entry in archive::list(path)? {
+        println!("{:>10}  {}", entry.size, entry.path);
+    }
+    Ok(true)
+}
+
+fn manifest(path: &Path) -> anyhow::Result<bool> {
+    print!("{}", archive::format_manifest(&archive::manifest(path)?));
+    Ok(true)
+}
+
+fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = match &command {
+        Command::Install(path, options) => install(path, options),
+        Command::Repair(path, options) => repair(path, options),
+        Command::Verify(path, profile) => verify(path, profile),
+        Command::List(path) => list(path),
+        Command::Manifest(path) => manifest(path),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn install_takes_a_category_list() {
+        let Ok(Command::Install(path, options)) =
+            parse_args(&args("install game.arx --only maps,music"))
+        else {
+            panic!("expected an install command");
+        };
+        assert_eq!(path, PathBuf::from("game.arx"));
+        assert_eq!(
+            options.categories,
+            vec![AssetCategory::Maps, AssetCategory::Music]
+        );
+
+        assert!(parse_args(&args("install game.arx --only tiles")).is_err());
+        assert!(parse_args(&args("verify game.arx --only maps")).is_err());
+        assert!(parse_args(&args("list")).is_err());
+
+        let Ok(Command::Install(_, options)) = parse_args(&args(
+            "install game.arx --source ./DarkAges741single.exe --version 741",
+        )) else {
+            panic!("expected an install command");
+        };
+        assert_eq!(options.profile.version, "741");
+        assert_eq!(
+            options.sources,
+            vec![InstallSource::File("./DarkAges741single.exe".into())]
+        );
+        assert!(parse_args(&args("install game.arx --version 600")).is_err());
+    }
+}
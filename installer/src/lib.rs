@@ -1,5 +1,3 @@
-const INSTALLER_URL: &str = "https://s3.amazonaws.com/kru-downloads/da/DarkAges741single.exe";
-
 use byteorder::{LE, ReadBytesExt};
 use circbuf::CircBuf;
 use crc32fast::Hasher;
@@ -12,50 +10,269 @@ use formats::{
     mpf::MpfFile,
 };
 use jubako::{self as jbk, creator::ContentAdder};
-use libarx::{self as arx, CreatorError, FullBuilder};
+use libarx::{self as arx, CreatorError};
 use rangemap::RangeMap;
 use std::{
     io::{self, BufRead, BufReader, Cursor, Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
 };
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 const HEADER_SIZE_TO_SKIP: u64 = 1024 * 50;
-const VERSION_BUF: &[u8] = b"741_2";
+/// Bumped whenever the archive layout changes, so existing archives are
+/// rebuilt. Written to `VERSION` after the client version.
+const ARCHIVE_REVISION: u32 = 2;
+
+pub mod archive;
+
+/// What differs between releases of the Dark Ages installer.
+#[derive(Debug)]
+pub struct InstallerProfile {
+    /// Client version, e.g. `"741"`.
+    pub version: &'static str,
+    /// Looked for next to the archive before downloading.
+    pub file_name: &'static str,
+    /// Where the Wise overlay starts in the executable.
+    pub executable_offset: u64,
+    /// Download locations, tried in order.
+    pub mirrors: &'static [&'static str],
+}
+
+pub const PROFILES: &[InstallerProfile] = &[InstallerProfile {
+    version: "741",
+    file_name: "DarkAges741single.exe",
+    executable_offset: 0x3A00,
+    mirrors: &["https://s3.amazonaws.com/kru-downloads/da/DarkAges741single.exe"],
+}];
+
+impl InstallerProfile {
+    pub fn find(version: &str) -> Option<&'static InstallerProfile> {
+        PROFILES
+            .iter()
+            .find(|profile| profile.version == version.trim())
+    }
+
+    /// Contents of the `VERSION` entry of an up to date archive.
+    pub fn archive_version(&self) -> Vec<u8> {
+        format!("{}_{}", self.version, ARCHIVE_REVISION).into_bytes()
+    }
+}
+
+/// Somewhere to read the installer executable from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallSource {
+    Url(String),
+    File(PathBuf),
+}
+
+impl InstallSource {
+    /// `http://` and `https://` addresses are URLs, anything else is a path.
+    pub fn parse(source: &str) -> Self {
+        let source = source.trim();
+        if source.starts_with("http://") || source.starts_with("https://") {
+            InstallSource::Url(source.to_string())
+        } else {
+            InstallSource::File(PathBuf::from(source))
+        }
+    }
+
+    fn open(&self) -> anyhow::Result<ExeReader> {
+        match self {
+            InstallSource::File(path) => {
+                let file = std::fs::File::open(path)?;
+                debug!("Using local {}", path.display());
+                Ok(ExeReader::File(file))
+            }
+            InstallSource::Url(url) => {
+                debug!("Streaming installer from {}", url);
+
+                let client = reqwest::blocking::Client::builder()
+                    .timeout(std::time::Duration::from_secs(30))
+                    .connect_timeout(std::time::Duration::from_secs(10))
+                    .build()?;
+
+                let response = client
+                    .get(url)
+                    .send()
+                    .map_err(|e| anyhow::anyhow!("Download request failed: {}", e))?;
+
+                if !response.status().is_success() {
+                    anyhow::bail!("Download failed with status: {}", response.status());
+                }
+
+                Ok(ExeReader::Http(response))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for InstallSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallSource::Url(url) => f.write_str(url),
+            InstallSource::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
 
 pub trait InstallProgress: Send + Sync {
     fn report(&self, percent: f32, message: String);
 }
 
+/// Groups of installer files that can be extracted on their own. Each `.dat`
+/// carries its own palettes, so any subset makes a usable archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetCategory {
+    /// Map tiles (`seo`, `ia`)
+    Maps,
+    /// Creature sprites (`hades`)
+    Creatures,
+    /// Player equipment sprites and palettes (`khan*`)
+    Players,
+    /// `.mus` music tracks
+    Music,
+    /// Items, effects, interface and everything else
+    Other,
+}
+
+impl AssetCategory {
+    pub const ALL: [AssetCategory; 5] = [
+        AssetCategory::Maps,
+        AssetCategory::Creatures,
+        AssetCategory::Players,
+        AssetCategory::Music,
+        AssetCategory::Other,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AssetCategory::Maps => "maps",
+            AssetCategory::Creatures => "creatures",
+            AssetCategory::Players => "players",
+            AssetCategory::Music => "music",
+            AssetCategory::Other => "other",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Category of a file in the installer, or `None` if it isn't extracted.
+    pub fn of_installer_file(path: &str) -> Option<Self> {
+        if path.ends_with(".mus") {
+            return Some(AssetCategory::Music);
+        }
+        let dat_name = Path::new(path.strip_suffix(".dat")?)
+            .file_name()?
+            .to_string_lossy();
+        Some(match dat_name.as_ref() {
+            "seo" | "ia" => AssetCategory::Maps,
+            "hades" => AssetCategory::Creatures,
+            name if name.starts_with("khan") => AssetCategory::Players,
+            _ => AssetCategory::Other,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InstallOptions {
+    /// Categories to extract. The archive is only marked up to date when all
+    /// of them are included, so the game will reinstall a partial archive.
+    pub categories: Vec<AssetCategory>,
+    pub profile: &'static InstallerProfile,
+    /// Tried before the profile's own file name and mirrors.
+    pub sources: Vec<InstallSource>,
+}
+
+impl Default for InstallOptions {
+    fn default() -> Self {
+        Self {
+            categories: AssetCategory::ALL.to_vec(),
+            profile: &PROFILES[0],
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl InstallOptions {
+    fn includes(&self, path: &str) -> bool {
+        AssetCategory::of_installer_file(path)
+            .is_some_and(|category| self.categories.contains(&category))
+    }
+
+    fn is_complete(&self) -> bool {
+        AssetCategory::ALL
+            .iter()
+            .all(|category| self.categories.contains(category))
+    }
+
+    /// Opens the first source that works: the configured ones, then the
+    /// installer next to the archive, then the profile's mirrors.
+    fn open_installer(&self, output_dir: &Path) -> anyhow::Result<ExeReader> {
+        let local = InstallSource::File(output_dir.join(self.profile.file_name));
+        let mirrors = self
+            .profile
+            .mirrors
+            .iter()
+            .map(|url| InstallSource::Url(url.to_string()));
+        let mut errors = vec![];
+        for source in self.sources.iter().cloned().chain([local]).chain(mirrors) {
+            match source.open() {
+                Ok(reader) => return Ok(reader),
+                Err(e) => {
+                    debug!("Can't use {}: {}", source, e);
+                    errors.push(format!("{}: {}", source, e));
+                }
+            }
+        }
+        anyhow::bail!("No installer source could be opened\n{}", errors.join("\n"))
+    }
+}
+
+/// Whether extracting `installer_file` produces the archive entry at `path`.
+/// The khan sprite dats all write into the shared `khan/` animations, so they
+/// own each other's entries and are always re-extracted together.
+fn owns_entry(installer_file: &str, path: &str) -> bool {
+    let Some(dat_path) = installer_file.strip_suffix(".dat") else {
+        return installer_file == path;
+    };
+    let is_khan_sprites = |dat: &str| dat.starts_with("khan") && dat != "khanpal";
+    let top_dir = path.split('/').next().unwrap_or_default();
+    path.strip_prefix(dat_path)
+        .is_some_and(|rest| rest.starts_with('/'))
+        || (is_khan_sprites(dat_path) && is_khan_sprites(top_dir))
+}
+
+/// An existing archive being rebuilt around its damaged entries.
+struct Repair {
+    archive: arx::Arx,
+    entries: Vec<String>,
+    damaged: Vec<String>,
+}
+
 pub fn install(output: &Path, progress: Option<Arc<dyn InstallProgress>>) -> anyhow::Result<()> {
+    install_with(output, &InstallOptions::default(), progress)
+}
+
+pub fn install_with(
+    output: &Path,
+    options: &InstallOptions,
+    progress: Option<Arc<dyn InstallProgress>>,
+) -> anyhow::Result<()> {
     if let Some(p) = &progress {
         p.report(0.0, "Checking archive...".to_string());
     }
     if output.exists() {
         let existing_archive = libarx::Arx::new(output).unwrap();
 
-        let version_file =
-            match existing_archive.get_entry::<FullBuilder>(arx::Path::new("VERSION")) {
-                Ok(arx::Entry::File(content_address)) => {
-                    match existing_archive.get_bytes(content_address.content()) {
-                        Ok(Some(jbk::reader::MayMissPack::FOUND(Some(bytes)))) => {
-                            let mut buf = vec![];
-                            bytes.stream().read_to_end(&mut buf).unwrap();
-                            Some(buf)
-                        }
-                        _ => None,
-                    }
-                }
-                _ => None,
-            };
-
-        if let Some(version_file) = version_file {
-            if version_file == VERSION_BUF {
-                info!("Archive is up to date");
-                return Ok(());
-            }
+        if archive::is_up_to_date(&existing_archive, &options.profile.archive_version()) {
+            info!("Archive is up to date");
+            return Ok(());
         }
 
         info!("Archive is not up to date, updating");
@@ -63,6 +280,51 @@ pub fn install(output: &Path, progress: Option<Arc<dyn InstallProgress>>) -> any
         info!("Archive does not exist, creating");
     }
 
+    extract(output, options, None, progress)
+}
+
+/// Checks the archive against its checksum manifest and re-extracts only the
+/// installer files behind damaged entries, copying everything else over from
+/// the existing archive. Returns the damaged entries.
+pub fn repair(
+    output: &Path,
+    options: &InstallOptions,
+    progress: Option<Arc<dyn InstallProgress>>,
+) -> anyhow::Result<Vec<String>> {
+    if let Some(p) = &progress {
+        p.report(0.0, "Verifying archive...".to_string());
+    }
+    let check = archive::verify(output, &options.profile.archive_version())?;
+    let damaged = check.damaged();
+    if damaged.is_empty() {
+        info!("Archive has no damaged entries");
+        return Ok(damaged);
+    }
+    let existing_archive = arx::Arx::new(output)?;
+    if archive::read_file(&existing_archive, "VERSION")
+        .is_some_and(|v| v != options.profile.archive_version())
+    {
+        anyhow::bail!("The archive is from another installer version, reinstall it instead");
+    }
+    info!("Repairing {} damaged entries", damaged.len());
+    let repair = Repair {
+        archive: existing_archive,
+        entries: archive::list(output)?
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect(),
+        damaged: damaged.clone(),
+    };
+    extract(output, options, Some(repair), progress)?;
+    Ok(damaged)
+}
+
+fn extract(
+    output: &Path,
+    options: &InstallOptions,
+    repair: Option<Repair>,
+    progress: Option<Arc<dyn InstallProgress>>,
+) -> anyhow::Result<()> {
     #[cfg(feature = "exe")]
     let executable_offset = {
         let pe = exe::VecPE::from_disk_file(INSTALLER_PATH).unwrap();
@@ -73,30 +335,10 @@ pub fn install(output: &Path, progress: Option<Arc<dyn InstallProgress>>) -> any
         executable_offset as u64
     };
     #[cfg(not(feature = "exe"))]
-    let executable_offset = 0x3A00;
+    let executable_offset = options.profile.executable_offset;
 
     let output_dir = output.parent().unwrap();
-    let exe_file = if output_dir.join("DarkAges741single.exe").exists() {
-        debug!("Using local DarkAges741single.exe");
-        let file = std::fs::File::open(output_dir.join("DarkAges741single.exe"))?;
-        ExeReader::File(file)
-    } else {
-        debug!("Streaming DarkAges741single.exe from {}", INSTALLER_URL);
-        
-        let client = reqwest::blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .connect_timeout(std::time::Duration::from_secs(10))
-            .build()?;
-
-        let response = client.get(INSTALLER_URL).send()
-            .map_err(|e| anyhow::anyhow!("Download request failed: {}", e))?;
-        
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("Download failed with status: {} (URL: {})", response.status(), INSTALLER_URL));
-        }
-
-        ExeReader::Http(response)
-    };
+    let exe_file = options.open_installer(output_dir)?;
 
     let mut exe_reader = BufReader::new(exe_file);
     let mut header_reader = (&mut exe_reader).take(HEADER_SIZE_TO_SKIP);
@@ -144,21 +386,48 @@ pub fn install(output: &Path, progress: Option<Arc<dyn InstallProgress>>) -> any
     let mut dat_buffer = CircBuf::with_capacity(8192)?;
     let mut buffer = vec![0u8; 4096];
 
+    // A repaired archive is built next to the damaged one it copies from
+    let target = match &repair {
+        Some(_) => output.with_extension("arx.repair"),
+        None => output.to_path_buf(),
+    };
     let mut arx_creator = libarx::create::SimpleCreator::new(
-        jbk::Utf8Path::new(output.to_str().unwrap()),
+        jbk::Utf8Path::new(target.to_str().unwrap()),
         jbk::creator::ConcatMode::OneFile,
         Arc::new(()),
         Rc::new(()),
         jbk::creator::Compression::zstd(),
     )?;
 
+    let redo: Option<Vec<String>> = repair.as_ref().map(|repair| {
+        operations
+            .iter()
+            .filter_map(|op| match op {
+                Operation::CreateFile(file_header)
+                    if options.includes(&file_header.file_path)
+                        && repair
+                            .damaged
+                            .iter()
+                            .any(|path| owns_entry(&file_header.file_path, path)) =>
+                {
+                    Some(file_header.file_path.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    });
+    let selected = |file_path: &str| {
+        options.includes(file_path)
+            && redo
+                .as_ref()
+                .is_none_or(|redo| redo.iter().any(|file| file == file_path))
+    };
+
     let total_compressed_size: u64 = operations
         .iter()
         .filter_map(|op| match op {
             Operation::CreateFile(file_header) => {
-                let is_dat = file_header.file_path.ends_with(".dat");
-                let is_music = file_header.file_path.ends_with(".mus");
-                if is_dat || is_music {
+                if selected(&file_header.file_path) {
                     Some((file_header.deflate_end - file_header.deflate_start - 4) as u64)
                 } else {
                     None
@@ -174,7 +443,7 @@ pub fn install(output: &Path, progress: Option<Arc<dyn InstallProgress>>) -> any
             let is_dat = file_header.file_path.ends_with(".dat");
             let is_music = file_header.file_path.ends_with(".mus");
 
-            if !is_dat && !is_music {
+            if !selected(&file_header.file_path) {
                 continue;
             }
 
@@ -967,21 +1236,52 @@ pub fn install(output: &Path, progress: Option<Arc<dyn InstallProgress>>) -> any
         }
     }
 
+    if let (Some(repair), Some(redo)) = (&repair, &redo) {
+        for path in &repair.entries {
+            if path == "VERSION" || redo.iter().any(|file| owns_entry(file, path)) {
+                continue;
+            }
+            let Some(content) = archive::read_file(&repair.archive, path) else {
+                warn!("Dropping unreadable entry {}", path);
+                continue;
+            };
+            let entry = SimpleDataEntry::new(
+                &mut Cursor::new(content),
+                Path::new(path),
+                arx_creator.adder(),
+            )?;
+            arx_creator.add_entry(&entry)?;
+        }
+    }
+
     if let Some(p) = &progress {
         p.report(0.95, "Finalizing archive...".to_string());
     }
 
-    let entry = SimpleDataEntry::new(
-        &mut Cursor::new(&VERSION_BUF),
-        Path::new("VERSION"),
-        arx_creator.adder(),
-    )?;
-    arx_creator.add_entry(&entry)?;
+    if options.is_complete() {
+        let entry = SimpleDataEntry::new(
+            &mut Cursor::new(options.profile.archive_version()),
+            Path::new("VERSION"),
+            arx_creator.adder(),
+        )?;
+        arx_creator.add_entry(&entry)?;
+    }
     if let Some(p) = &progress {
         p.report(0.98, "Writing indexes...".to_string());
     }
     arx_creator.finalize()?;
 
+    if repair.is_some() {
+        // Release the damaged archive before replacing it
+        drop(repair);
+        std::fs::rename(&target, output)?;
+    }
+
+    if let Some(p) = &progress {
+        p.report(0.99, "Writing checksum manifest...".to_string());
+    }
+    archive::write_manifest(output)?;
+
     if let Some(p) = &progress {
         p.report(1.0, "Installation complete".to_string());
     }
@@ -1270,3 +1570,70 @@ impl Read for ExeReader {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installer_files_are_sorted_into_categories() {
+        assert_eq!(
+            AssetCategory::of_installer_file("seo.dat"),
+            Some(AssetCategory::Maps)
+        );
+        assert_eq!(
+            AssetCategory::of_installer_file("khanpal.dat"),
+            Some(AssetCategory::Players)
+        );
+        assert_eq!(
+            AssetCategory::of_installer_file("hades.dat"),
+            Some(AssetCategory::Creatures)
+        );
+        assert_eq!(
+            AssetCategory::of_installer_file("music/1.mus"),
+            Some(AssetCategory::Music)
+        );
+        assert_eq!(
+            AssetCategory::of_installer_file("Legend.dat"),
+            Some(AssetCategory::Other)
+        );
+        assert_eq!(AssetCategory::of_installer_file("Darkages.exe"), None);
+        assert_eq!(
+            AssetCategory::from_name(" Music"),
+            Some(AssetCategory::Music)
+        );
+        let maps_only = InstallOptions {
+            categories: vec![AssetCategory::Maps],
+        };
+        assert!(!maps_only.is_complete());
+        assert!(maps_only.includes("ia.dat"));
+        assert!(!maps_only.includes("hades.dat"));
+    }
+
+    #[test]
+    fn sources_are_urls_or_paths() {
+        assert_eq!(
+            InstallSource::parse(" https://example.com/DarkAges741single.exe"),
+            InstallSource::Url("https://example.com/DarkAges741single.exe".into())
+        );
+        assert_eq!(
+            InstallSource::parse("C:/Games/DarkAges741single.exe"),
+            InstallSource::File(PathBuf::from("C:/Games/DarkAges741single.exe"))
+        );
+        let profile = InstallerProfile::find("741").unwrap();
+        assert_eq!(profile.archive_version(), b"741_2");
+        assert!(InstallerProfile::find("600").is_none());
+    }
+
+    #[test]
+    fn damaged_entries_lead_back_to_their_installer_file() {
+        assert!(owns_entry("seo.dat", "seo/tile1.ktx2"));
+        assert!(!owns_entry("seo.dat", "setoa/gui.ktx2"));
+        assert!(owns_entry("music/1.mus", "music/1.mus"));
+        assert!(!owns_entry("music/1.mus", "music/10.mus"));
+        // Sprite dats share the combined animations
+        assert!(owns_entry("khanmad.dat", "khan/mb/001.epfanim"));
+        assert!(owns_entry("khanmad.dat", "khanmeh/wb001.epf.bin"));
+        assert!(!owns_entry("khanpal.dat", "khan/mb/001.epfanim"));
+    }
+}
@@ -0,0 +1,155 @@
+//! Resolving server addresses and opening connections to them.
+//!
+//! Addresses are `host`, `host:port`, `[v6]:port` or a bare IPv6 literal. When
+//! no port is given the host's `_da._tcp` SRV record is consulted, falling back
+//! to the default port. Hosts with several addresses are tried happy-eyeballs
+//! style: IPv6 and IPv4 interleaved, each attempt started shortly after the
+//! previous one, first to connect wins.
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use async_std::net::{TcpStream, ToSocketAddrs};
+
+pub const DEFAULT_PORT: u16 = 2610;
+pub const SRV_SERVICE: &str = "_da._tcp";
+/// Head start each connection attempt gets over the next one.
+const ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Splits a server address into its host and, if given, its port.
+pub fn split_host_port(address: &str) -> (String, Option<u16>) {
+    let address = address.trim();
+    if let Some(rest) = address.strip_prefix('[')
+        && let Some((host, after)) = rest.split_once(']')
+    {
+        let port = after.strip_prefix(':').and_then(|p| p.parse().ok());
+        return (host.to_owned(), port);
+    }
+    // More than one colon without brackets can only be a bare IPv6 literal.
+    match address.split_once(':') {
+        Some((host, port)) if !port.contains(':') => (host.to_owned(), port.parse().ok()),
+        _ => (address.to_owned(), None),
+    }
+}
+
+/// Formats `host:port`, bracketing IPv6 literals.
+pub fn join_host_port(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// Looks up the server's `_da._tcp` SRV record, picking the lowest priority
+/// and then highest weight target. Blocks while the query runs.
+pub fn lookup_srv(host: &str) -> Option<(String, u16)> {
+    if host.parse::<IpAddr>().is_ok() || host.eq_ignore_ascii_case("localhost") {
+        return None;
+    }
+    let resolver = hickory_resolver::Resolver::from_system_conf().ok()?;
+    let lookup = resolver
+        .srv_lookup(format!("{}.{}.", SRV_SERVICE, host.trim_end_matches('.')))
+        .ok()?;
+    let record = lookup
+        .iter()
+        .min_by_key(|srv| (srv.priority(), std::cmp::Reverse(srv.weight())))?;
+    let target = record.target().to_utf8();
+    Some((target.trim_end_matches('.').to_owned(), record.port()))
+}
+
+/// Where `address` should be reached: its explicit port, else its SRV record,
+/// else the default port.
+pub async fn resolve_server(address: &str) -> (String, u16) {
+    let (host, port) = split_host_port(address);
+    if let Some(port) = port {
+        return (host, port);
+    }
+    let lookup_host = host.clone();
+    match async_std::task::spawn_blocking(move || lookup_srv(&lookup_host)).await {
+        Some(target) => target,
+        None => (host, DEFAULT_PORT),
+    }
+}
+
+/// Orders addresses IPv6 first, alternating families from there.
+fn interleave(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let (mut v6, mut v4): (Vec<_>, Vec<_>) = addrs.into_iter().partition(SocketAddr::is_ipv6);
+    v6.reverse();
+    v4.reverse();
+    let mut ordered = Vec::with_capacity(v6.len() + v4.len());
+    while !v6.is_empty() || !v4.is_empty() {
+        ordered.extend(v6.pop());
+        ordered.extend(v4.pop());
+    }
+    ordered
+}
+
+/// Connects to `host:port`, racing its addresses.
+pub async fn connect(host: &str, port: u16) -> io::Result<TcpStream> {
+    let addrs = interleave((host, port).to_socket_addrs().await?.collect());
+    let total = addrs.len();
+    let (tx, rx) = async_std::channel::unbounded();
+    for (i, addr) in addrs.into_iter().enumerate() {
+        let tx = tx.clone();
+        async_std::task::spawn(async move {
+            async_std::task::sleep(ATTEMPT_DELAY * i as u32).await;
+            // Another attempt already won.
+            if tx.is_closed() {
+                return;
+            }
+            let _ = tx.send(TcpStream::connect(addr).await).await;
+        });
+    }
+    drop(tx);
+
+    let mut last_error = None;
+    for _ in 0..total {
+        match rx.recv().await {
+            Ok(Ok(stream)) => return Ok(stream),
+            Ok(Err(e)) => last_error = Some(e),
+            Err(_) => break,
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} has no addresses", host),
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_split_into_host_and_port() {
+        assert_eq!(
+            split_host_port("da0.kru.com:2610"),
+            ("da0.kru.com".into(), Some(2610))
+        );
+        assert_eq!(split_host_port("da0.kru.com"), ("da0.kru.com".into(), None));
+        assert_eq!(
+            split_host_port("[2001:db8::1]:2611"),
+            ("2001:db8::1".into(), Some(2611))
+        );
+        assert_eq!(split_host_port("[::1]"), ("::1".into(), None));
+        assert_eq!(split_host_port("2001:db8::1"), ("2001:db8::1".into(), None));
+        assert_eq!(join_host_port("2001:db8::1", 2610), "[2001:db8::1]:2610");
+        assert_eq!(join_host_port("127.0.0.1", 2610), "127.0.0.1:2610");
+    }
+
+    #[test]
+    fn address_families_alternate_starting_with_ipv6() {
+        let addrs: Vec<SocketAddr> = ["1.1.1.1:1", "2.2.2.2:1", "[::1]:1"]
+            .iter()
+            .map(|a| a.parse().unwrap())
+            .collect();
+        assert_eq!(
+            interleave(addrs.clone()),
+            vec![addrs[2], addrs[0], addrs[1]]
+        );
+    }
+}
@@ -1,17 +1,58 @@
 pub mod cipher;
+pub mod connect;
 pub mod packet;
 pub mod protocol;
 
 use packets::ToBytes;
 use std::io;
+use std::time::{Duration, Instant};
 
 use self::cipher::{PacketDecrypter, PacketEncrypter};
 use self::packet::{PacketDecoder, PacketEncoder};
 use self::protocol::{EncryptionType, PACKET_MAGIC};
 
+/// A batched sender writes out once this much is buffered, about one TCP segment.
+const BATCH_FLUSH_BYTES: usize = 1400;
+
+/// Packets a batching sender holds back, until a segment's worth has built
+/// up or the oldest has waited out the batch window.
+#[derive(Default)]
+struct Batch {
+    window: Duration,
+    pending: Vec<u8>,
+    /// When the oldest pending packet has to go out.
+    deadline: Option<Instant>,
+}
+
+impl Batch {
+    fn enabled(&self) -> bool {
+        !self.window.is_zero()
+    }
+
+    /// Queues `data`, returning everything pending once it fills a segment.
+    fn push(&mut self, data: &[u8], now: Instant) -> Option<Vec<u8>> {
+        self.pending.extend_from_slice(data);
+        self.deadline.get_or_insert(now + self.window);
+        (self.pending.len() >= BATCH_FLUSH_BYTES).then(|| self.take())
+    }
+
+    /// Everything pending, once the oldest packet's window has run out.
+    fn due(&mut self, now: Instant) -> Option<Vec<u8>> {
+        self.deadline
+            .is_some_and(|deadline| now >= deadline)
+            .then(|| self.take())
+    }
+
+    fn take(&mut self) -> Vec<u8> {
+        self.deadline = None;
+        std::mem::take(&mut self.pending)
+    }
+}
+
 pub struct EncryptedSender {
     encoder: PacketEncoder,
     encrypter: PacketEncrypter,
+    batch: Batch,
 }
 
 pub struct DecryptedReceiver {
@@ -21,7 +62,27 @@ pub struct DecryptedReceiver {
 
 impl EncryptedSender {
     pub fn new(encoder: PacketEncoder, encrypter: PacketEncrypter) -> Self {
-        Self { encoder, encrypter }
+        Self {
+            encoder,
+            encrypter,
+            batch: Batch::default(),
+        }
+    }
+
+    /// Holds sent packets for up to `window`, or until a segment's worth has
+    /// built up, instead of writing each one to the socket on its own. A zero
+    /// window turns batching off.
+    pub fn set_batch_window(&mut self, window: Duration) {
+        self.batch.window = window;
+    }
+
+    /// When the oldest held packet is due out, while any are held.
+    pub fn flush_deadline(&self) -> Option<Instant> {
+        self.batch.deadline
+    }
+
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.encoder.set_nodelay(nodelay)
     }
 
     pub fn encrypt(&mut self, data: &[u8]) -> Vec<u8> {
@@ -50,10 +111,32 @@ impl EncryptedSender {
     }
 
     pub async fn send_raw(&mut self, data: &[u8]) -> io::Result<()> {
-        self.encoder.write_raw(data).await
+        if !self.batch.enabled() {
+            return self.encoder.write_raw(data).await;
+        }
+        match self.batch.push(data, Instant::now()) {
+            Some(full) => self.encoder.write_raw(&full).await,
+            None => Ok(()),
+        }
     }
 
+    /// Writes out held packets once the oldest has waited out the window.
+    pub async fn flush_due(&mut self) -> io::Result<()> {
+        match self.batch.due(Instant::now()) {
+            Some(pending) => {
+                self.encoder.write_raw(&pending).await?;
+                self.encoder.flush().await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Writes out everything held right away.
     pub async fn flush(&mut self) -> io::Result<()> {
+        let pending = self.batch.take();
+        if !pending.is_empty() {
+            self.encoder.write_raw(&pending).await?;
+        }
         self.encoder.flush().await
     }
 
@@ -129,3 +212,37 @@ impl DecryptedReceiver {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_go_out_when_full_or_when_the_window_ends() {
+        let start = Instant::now();
+        let window = Duration::from_millis(10);
+        let mut batch = Batch {
+            window,
+            ..Default::default()
+        };
+        assert!(batch.enabled());
+        assert_eq!(batch.due(start + window), None);
+
+        // The window counts from the first packet, not the latest
+        assert_eq!(batch.push(&[1; 100], start), None);
+        assert_eq!(batch.push(&[2; 100], start + window / 2), None);
+        assert_eq!(batch.deadline, Some(start + window));
+        assert_eq!(batch.due(start + window / 2), None);
+        let due = batch.due(start + window).unwrap();
+        assert_eq!(due.len(), 200);
+        assert_eq!((batch.deadline, batch.pending.len()), (None, 0));
+
+        // A segment's worth goes out at once
+        assert_eq!(batch.push(&[3; BATCH_FLUSH_BYTES - 1], start), None);
+        let full = batch.push(&[4], start).unwrap();
+        assert_eq!(full.len(), BATCH_FLUSH_BYTES);
+        assert_eq!(batch.deadline, None);
+
+        assert!(!Batch::default().enabled());
+    }
+}
@@ -72,4 +72,8 @@ impl PacketEncoder {
     pub async fn flush(&mut self) -> io::Result<()> {
         (&*self.stream).flush().await
     }
+
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.stream.set_nodelay(nodelay)
+    }
 }
@@ -116,7 +116,7 @@ mod map_data;
 pub use map_data::MapData;
 
 mod map_info;
-pub use map_info::MapInfo;
+pub use map_info::{MapFlags, MapInfo, Weather};
 
 mod map_load_complete;
 pub use map_load_complete::MapLoadComplete;
@@ -32,6 +32,55 @@ impl MapInfo {
     pub fn get_stride(&self) -> usize {
         self.width as usize * 6
     }
+
+    pub fn map_flags(&self) -> MapFlags {
+        MapFlags(self.flags)
+    }
+}
+
+/// Weather falling on a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Snow,
+    Rain,
+}
+
+/// How a map should look and what the client allows on it. Indoor maps are
+/// simply the ones without weather.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MapFlags(pub u8);
+
+impl MapFlags {
+    pub const SNOW: u8 = 0x01;
+    pub const RAIN: u8 = 0x02;
+    /// Both weather bits together mean a dark map, like a cave, with no weather.
+    pub const DARKNESS: u8 = Self::SNOW | Self::RAIN;
+    /// The tab map can't be opened.
+    pub const NO_TAB_MAP: u8 = 0x40;
+    /// Walls use the winter palette table.
+    pub const SNOW_TILESET: u8 = 0x80;
+
+    pub fn weather(self) -> Weather {
+        match self.0 & Self::DARKNESS {
+            Self::SNOW => Weather::Snow,
+            Self::RAIN => Weather::Rain,
+            _ => Weather::Clear,
+        }
+    }
+
+    pub fn is_dark(self) -> bool {
+        self.0 & Self::DARKNESS == Self::DARKNESS
+    }
+
+    pub fn no_tab_map(self) -> bool {
+        self.0 & Self::NO_TAB_MAP != 0
+    }
+
+    pub fn snow_tileset(self) -> bool {
+        self.0 & Self::SNOW_TILESET != 0
+    }
 }
 
 impl TryFromBytes for MapInfo {
@@ -60,3 +109,31 @@ impl TryFromBytes for MapInfo {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn darkness_is_both_weather_bits() {
+        let flags = |bits| {
+            MapInfo {
+                flags: bits,
+                ..Default::default()
+            }
+            .map_flags()
+        };
+
+        assert_eq!(flags(0).weather(), Weather::Clear);
+        assert_eq!(flags(0x01).weather(), Weather::Snow);
+        assert_eq!(flags(0x82).weather(), Weather::Rain);
+        assert!(flags(0x82).snow_tileset());
+        assert!(!flags(0x02).is_dark());
+
+        let cave = flags(0x43);
+        assert!(cave.is_dark());
+        assert_eq!(cave.weather(), Weather::Clear);
+        assert!(cave.no_tab_map());
+        assert!(!cave.snow_tileset());
+    }
+}
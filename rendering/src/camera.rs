@@ -18,11 +18,17 @@ impl Camera {
         }
     }
 
+    /// Screen-space rectangle the camera sees, as (min, max).
+    pub fn visible_bounds(&self) -> (Vec2, Vec2) {
+        let half = Vec2::new(self.width, self.height) / (2.0 * self.zoom);
+        (self.position - half, self.position + half)
+    }
+
     pub fn build_view_projection_matrix(&self) -> [[f32; 4]; 4] {
         // Z range [-1, 1] with reversed-Z (CompareFunction::Greater).
         // Higher z values map to lower NDC, but with Greater comparison and
         // clear to 0.0, higher world z (closer tiles) correctly wins.
-        // Changed divisor from 1000 to 512 in calculate_tile_z for better precision.
+        // World z comes from scene::depth.
         let projection = Mat4::orthographic_rh(0.0, self.width, self.height, 0.0, 1.0, -1.0);
         let center_translation =
             Mat4::from_translation(Vec3::new(self.width / 2.0, self.height / 2.0, 0.0).floor());
@@ -39,8 +45,17 @@ pub struct CameraUniform {
     pub view_proj: [[f32; 4]; 4],
     pub position: [f32; 2],
     pub xray_size: f32,
-    pub _padding: f32,
+    /// 1.0 while every door and map exit on screen should glow.
+    pub highlight: f32,
     pub tint: [f32; 4],
+    /// Screen position of the tile the x-ray is centered on.
+    pub xray_anchor: [f32; 2],
+    /// Screen-space bounds of the sprite kept visible through walls.
+    pub xray_min: [f32; 2],
+    pub xray_max: [f32; 2],
+    /// 0 fades a radius around the sprite, 1 fades only walls covering it.
+    pub xray_mode: u32,
+    pub _padding: u32,
 }
 
 impl CameraUniform {
@@ -49,11 +64,28 @@ impl CameraUniform {
             view_proj: Mat4::default().to_cols_array_2d(),
             position: [0.0; 2],
             xray_size: 1.0,
-            _padding: 0.0,
+            highlight: 0.0,
             tint: [0.0, 0.0, 0.0, 0.0],
+            xray_anchor: [0.0; 2],
+            xray_min: [0.0; 2],
+            xray_max: [0.0; 2],
+            xray_mode: 0,
+            _padding: 0,
         }
     }
 
+    /// Centers the x-ray on `anchor`, fitting it to `bounds` when the sprite is known.
+    /// Without bounds it falls back to a 40x60 box standing on the anchor tile.
+    pub fn set_xray_target(&mut self, anchor: Vec2, bounds: Option<(Vec2, Vec2)>) {
+        let (min, max) = bounds.unwrap_or((
+            anchor + Vec2::new(-20.0, -50.0),
+            anchor + Vec2::new(20.0, 10.0),
+        ));
+        self.xray_anchor = anchor.to_array();
+        self.xray_min = min.to_array();
+        self.xray_max = max.to_array();
+    }
+
     pub fn update_view_proj(&mut self, camera: &Camera) {
         self.view_proj = camera.build_view_projection_matrix();
         self.position = camera.position.to_array();
@@ -0,0 +1,38 @@
+//! Reading instance data back from the GPU, for frame captures attached to
+//! bug reports.
+
+use crate::InstanceRaw;
+
+/// Copies the first `count` instances out of an instance buffer. Blocks until
+/// the GPU has finished the work submitted so far.
+pub fn read_instances(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    buffer: &wgpu::Buffer,
+    count: usize,
+) -> Vec<InstanceRaw> {
+    let size = (count * std::mem::size_of::<InstanceRaw>()) as wgpu::BufferAddress;
+    if size == 0 {
+        return Vec::new();
+    }
+
+    let staging = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Instance Capture Buffer"),
+        size,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Instance Capture Encoder"),
+    });
+    encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, size);
+    queue.submit(Some(encoder.finish()));
+
+    let slice = staging.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |_| {});
+    let _ = device.poll(wgpu::PollType::wait_indefinitely());
+
+    let instances = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+    staging.unmap();
+    instances
+}
@@ -0,0 +1,248 @@
+use wgpu::util::DeviceExt;
+
+use crate::shaders;
+use crate::texture::Texture;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct CompositeUniform {
+    gamma: f32,
+    /// 1.0 shows the frame, 0.0 is fully black.
+    fade: f32,
+    _padding: [f32; 2],
+}
+
+impl Default for CompositeUniform {
+    fn default() -> Self {
+        Self {
+            gamma: 1.0,
+            fade: 1.0,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+/// Final pass from the scene into the frame handed to the UI: scales a low-resolution
+/// frame up with a sharpened bilinear filter, applies the user's gamma and fades to black.
+pub struct FrameCompositor {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    format: wgpu::TextureFormat,
+    source: Option<(Texture, wgpu::BindGroup)>,
+    uniform_buffer: wgpu::Buffer,
+    uniform: CompositeUniform,
+    pipeline_layout: wgpu::PipelineLayout,
+}
+
+impl FrameCompositor {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("composite_bind_group_layout"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Composite Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            immediate_size: 0,
+        });
+
+        let pipeline = Self::create_pipeline(
+            device,
+            &pipeline_layout,
+            format,
+            shaders::embedded(shaders::COMPOSITE),
+        );
+
+        let uniform = CompositeUniform::default();
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Composite Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            format,
+            source: None,
+            uniform_buffer,
+            uniform,
+            pipeline_layout,
+        }
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        format: wgpu::TextureFormat,
+        source: &str,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Composite Shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            cache: None,
+            label: Some("Composite Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview_mask: None,
+        })
+    }
+
+    /// Rebuilds the composite pipeline from new shader source, see [`crate::shaders`].
+    pub fn reload_pipeline(&mut self, device: &wgpu::Device, source: &str) {
+        self.pipeline = Self::create_pipeline(device, &self.pipeline_layout, self.format, source);
+    }
+
+    /// Values above 1.0 lift dark tones, below 1.0 deepen them.
+    pub fn set_gamma(&mut self, queue: &wgpu::Queue, gamma: f32) {
+        self.write_uniform(
+            queue,
+            CompositeUniform {
+                gamma,
+                ..self.uniform
+            },
+        );
+    }
+
+    /// Scales the frame towards black, used to cover map transitions.
+    pub fn set_fade(&mut self, queue: &wgpu::Queue, fade: f32) {
+        self.write_uniform(
+            queue,
+            CompositeUniform {
+                fade,
+                ..self.uniform
+            },
+        );
+    }
+
+    fn write_uniform(&mut self, queue: &wgpu::Queue, uniform: CompositeUniform) {
+        if uniform != self.uniform {
+            self.uniform = uniform;
+            queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+        }
+    }
+
+    /// Whether the frame needs a composite pass even when it is not being upscaled.
+    pub fn adjusts_color(&self) -> bool {
+        self.uniform.gamma != 1.0 || self.uniform.fade < 1.0
+    }
+
+    /// Offscreen target the scene renders into before compositing, reallocated on resize.
+    pub fn source_view(
+        &mut self,
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+    ) -> &wgpu::TextureView {
+        let stale = self.source.as_ref().is_none_or(|(texture, _)| {
+            texture.texture.width() != width || texture.texture.height() != height
+        });
+
+        if stale {
+            let texture = Texture::create_render_texture(
+                device,
+                "composite_source",
+                width,
+                height,
+                self.format,
+            );
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&texture.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: self.uniform_buffer.as_entire_binding(),
+                    },
+                ],
+                label: Some("composite_bind_group"),
+            });
+            self.source = Some((texture, bind_group));
+        }
+
+        &self.source.as_ref().unwrap().0.view
+    }
+
+    /// Draws the last frame rendered into [`Self::source_view`] over all of `target`.
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        let Some((_, bind_group)) = &self.source else {
+            return;
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            ..Default::default()
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -13,7 +13,24 @@ pub enum InstanceFlag {
     #[default]
     None = 0,
     XRay = 1,
+    /// Creature or player under the cursor, drawn with an outline.
     Hover = 2,
+    /// Door or map exit, lit up near the player or while the overlay key is held.
+    Glow = 4,
+    /// X-ray wall that also glows.
+    XRayGlow = 5,
+    /// Creature or player picked by a targeted spell, drawn with a blue outline.
+    Target = 8,
+}
+
+impl InstanceFlag {
+    /// Adds the glow bit, keeping x-ray on walls that already have it.
+    pub fn with_glow(self) -> Self {
+        match self {
+            InstanceFlag::XRay | InstanceFlag::XRayGlow => InstanceFlag::XRayGlow,
+            _ => InstanceFlag::Glow,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -215,8 +232,19 @@ impl InstanceBatch {
         vertices: Vec<Vertex>,
         bind_group: wgpu::BindGroup,
     ) -> Self {
-        let buffer_capacity = instances.len().max(BATCH_SIZE);
+        let capacity = instances.len().max(BATCH_SIZE);
+        Self::with_capacity(device, instances, vertices, bind_group, capacity)
+    }
 
+    /// Batch whose buffer holds exactly `buffer_capacity` instances, for
+    /// batches that never grow.
+    pub fn with_capacity(
+        device: &wgpu::Device,
+        instances: Vec<Instance>,
+        vertices: Vec<Vertex>,
+        bind_group: wgpu::BindGroup,
+        buffer_capacity: usize,
+    ) -> Self {
         let mut buffer_data = Vec::with_capacity(buffer_capacity);
 
         for instance in &instances {
@@ -229,7 +257,9 @@ impl InstanceBatch {
         let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Instance Buffer"),
             contents: bytemuck::cast_slice(&buffer_data),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            usage: wgpu::BufferUsages::VERTEX
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
         });
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -310,6 +340,11 @@ impl InstanceBatch {
         }
     }
 
+    /// Instances as the GPU currently has them, see [`crate::capture`].
+    pub fn read_back(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Vec<InstanceRaw> {
+        crate::capture::read_instances(device, queue, &self.instance_buffer, self.instances.len())
+    }
+
     pub fn get_instance(&self, index: usize) -> Option<&Instance> {
         if index < self.instances.len() {
             Some(&self.instances[index])
@@ -337,7 +372,9 @@ impl SharedInstanceBatch {
         let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Instance Buffer"),
             contents: bytemuck::cast_slice(&buffer_data),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            usage: wgpu::BufferUsages::VERTEX
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
         });
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -360,6 +397,12 @@ impl SharedInstanceBatch {
         self.next_index.load(Ordering::Relaxed)
     }
 
+    /// Instances as the GPU currently has them, freed slots included, see
+    /// [`crate::capture`].
+    pub fn read_back(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Vec<InstanceRaw> {
+        crate::capture::read_instances(device, queue, &self.instance_buffer, self.len())
+    }
+
     pub fn clear(&self) {
         self.next_index.store(0, Ordering::Relaxed);
         if let Ok(mut free_indices) = self.free_indices.lock() {
@@ -1,9 +1,13 @@
 pub mod camera;
+pub mod capture;
+pub mod composite;
 pub mod instance;
 pub mod scene;
+pub mod shaders;
 pub mod texture;
 pub mod vertex;
 
 pub use camera::{Camera, CameraUniform};
+pub use composite::FrameCompositor;
 pub use instance::{Instance, InstanceBatch, InstanceRaw, SharedInstanceBatch};
 pub use vertex::{Vertex, make_quad};
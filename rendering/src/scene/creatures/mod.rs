@@ -13,11 +13,13 @@ use wgpu;
 use crate::{
     instance::{InstanceFlag, SharedInstanceBatch},
     make_quad,
-    scene::utils::calculate_tile_z,
+    scene::depth::{RenderLayer, tile_depth},
 };
 use crate::{
     scene::{
-        Instance, get_isometric_coordinate, texture_atlas::TextureAtlas, texture_bind::TextureBind,
+        Instance, get_isometric_coordinate,
+        texture_atlas::{AtlasStats, TextureAtlas},
+        texture_bind::TextureBind,
     },
     texture,
 };
@@ -43,6 +45,10 @@ pub struct CreatureBatch {
 }
 
 impl CreatureAssetStore {
+    pub fn atlas_stats(&self) -> AtlasStats {
+        self.atlas.stats()
+    }
+
     pub async fn new(device: &wgpu::Device, queue: &wgpu::Queue, archive: &Archive) -> Self {
         let diffuse_texture = texture::Texture::from_data(
             device,
@@ -96,6 +102,10 @@ impl CreatureAssetStore {
 }
 
 impl CreatureBatch {
+    pub fn instances(&self) -> &SharedInstanceBatch {
+        &self.instances
+    }
+
     pub fn new(device: &wgpu::Device, store: &CreatureAssetStore) -> Self {
         let vertices = make_quad(VERTEX_WIDTH as u32, VERTEX_HEIGHT as u32).to_vec();
         let creature_batch = SharedInstanceBatch::new(device, vertices, store.bind_group.clone());
@@ -222,6 +232,7 @@ impl CreatureBatch {
         anim: &MpfAnimation,
         anim_frame: usize,
         direction: u8,
+        flags: InstanceFlag,
         tint: Vec3,
     ) -> bool {
         if let Some(loaded_sprite) = store.loaded_sprites.get(&handle.sprite_id) {
@@ -231,6 +242,7 @@ impl CreatureBatch {
             if let Ok(mut instance) =
                 get_instance_for_frame(loaded_sprite, frame_index, Vec2::new(x, y), flip)
             {
+                instance.flags = flags;
                 instance.tint = tint;
                 self.instances.update(queue, handle.index, instance);
                 return true;
@@ -289,7 +301,12 @@ fn get_instance_for_frame(
     Ok(Instance::with_texture_atlas(
         (get_isometric_coordinate(position.x, position.y)
             - Vec2::new(offset_x, (frame_detail.center_y - frame_detail.top) as f32))
-        .extend(calculate_tile_z(position.x, position.y, 0.21)),
+        .extend(tile_depth(
+            position.x,
+            position.y,
+            RenderLayer::Creature,
+            0.0,
+        )),
         Vec2::new(
             first_frame.rectangle.min.x as f32 / ATLAS_WIDTH as f32,
             first_frame.rectangle.min.y as f32 / ATLAS_HEIGHT as f32,
@@ -0,0 +1,92 @@
+//! Draw order of everything placed on the map.
+//!
+//! Depth is tested with `Greater` against a buffer cleared to 0.0, so higher
+//! z is drawn in front. Each tile gets a unit of depth at x + y, tiles closer
+//! to the camera being further along, and within it every kind of sprite has
+//! its own band, see [`RenderLayer`]. Renderers ask [`tile_depth`] for their
+//! z instead of picking numbers, so a new one can't slot in between two
+//! layers by accident.
+
+/// Depth of floor tiles, behind everything else on the map.
+pub const FLOOR_DEPTH: f32 = 0.0;
+
+/// Tile depths are scaled down by this to stay inside the camera's z range.
+const DEPTH_SCALE: f32 = 1000.0;
+
+/// Kinds of sprites on a tile, from back to front.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderLayer {
+    Item,
+    Player,
+    Creature,
+    /// The X-ray shader tells walls in front of the player apart by this depth.
+    Wall,
+    Effect,
+}
+
+impl RenderLayer {
+    pub const ALL: [RenderLayer; 5] = [
+        RenderLayer::Item,
+        RenderLayer::Player,
+        RenderLayer::Creature,
+        RenderLayer::Wall,
+        RenderLayer::Effect,
+    ];
+
+    /// Where the layer's band starts within a tile.
+    pub const fn base(self) -> f32 {
+        match self {
+            RenderLayer::Item => 0.0,
+            RenderLayer::Player => 0.1,
+            RenderLayer::Creature => 0.21,
+            RenderLayer::Wall => 0.98,
+            RenderLayer::Effect => 0.99,
+        }
+    }
+
+    /// Room the layer has to order its own sprites, up to the next layer.
+    pub const fn range(self) -> f32 {
+        let end = match self {
+            RenderLayer::Item => RenderLayer::Player.base(),
+            RenderLayer::Player => RenderLayer::Creature.base(),
+            RenderLayer::Creature => RenderLayer::Wall.base(),
+            RenderLayer::Wall => RenderLayer::Effect.base(),
+            RenderLayer::Effect => 1.0,
+        };
+        end - self.base()
+    }
+}
+
+/// Z for a sprite of `layer` on tile (x, y). `offset` orders sprites within
+/// the layer and must stay below [`RenderLayer::range`].
+pub fn tile_depth(x: f32, y: f32, layer: RenderLayer, offset: f32) -> f32 {
+    debug_assert!(
+        (0.0..layer.range()).contains(&offset),
+        "{layer:?} offset {offset} is outside its band"
+    );
+    (x + y + layer.base() + offset) / DEPTH_SCALE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_stack_within_a_tile() {
+        for pair in RenderLayer::ALL.windows(2) {
+            let (back, front) = (pair[0], pair[1]);
+            let back_max = tile_depth(3.0, 4.0, back, back.range() * 0.999);
+            assert!(
+                back_max < tile_depth(3.0, 4.0, front, 0.0),
+                "{back:?} / {front:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn closer_tiles_cover_every_layer_behind_them() {
+        let front_of_tile = tile_depth(3.0, 4.0, RenderLayer::Effect, 0.0099);
+        let back_of_next = tile_depth(4.0, 4.0, RenderLayer::Item, 0.0);
+        assert!(front_of_tile < back_of_next);
+    }
+}
@@ -8,13 +8,17 @@ use formats::game_files::ArxArchive;
 use glam::Vec2;
 
 use crate::instance::InstanceFlag;
-use crate::scene::texture_atlas::TextureAtlas;
-use crate::scene::utils::calculate_tile_z;
+use crate::scene::depth::{RenderLayer, tile_depth};
+use crate::scene::texture_atlas::{AtlasStats, TextureAtlas};
 use crate::scene::{TILE_HEIGHT, get_isometric_coordinate};
-use crate::{Instance, InstanceRaw, SharedInstanceBatch, Vertex, make_quad, texture};
-
-const ATLAS_WIDTH: usize = 2048;
-const ATLAS_HEIGHT: usize = 2048;
+use crate::{Instance, InstanceRaw, SharedInstanceBatch, Vertex, make_quad, shaders, texture};
+
+/// Side of the effect atlas, which is square. Smaller atlases save memory on
+/// weak GPUs at the cost of fewer effects fitting at once.
+pub const EFFECT_ATLAS_SIZE: u32 = 2048;
+/// Unused effect sheets are dropped before loading a new one once the atlas
+/// is this full, so loads don't run out of space.
+const EVICT_AT_PERCENT: f32 = 75.0;
 const VERTEX_SIZE: usize = 512;
 
 pub struct EffectFrameSequence {
@@ -31,6 +35,8 @@ struct LoadedEffect {
     /// Sheet dimensions for EPF-based positioning (0,0 for EFA which uses direct offsets)
     sheet_width: u16,
     sheet_height: u16,
+    /// Effects currently playing from this sheet.
+    playing: u32,
 }
 
 #[derive(Clone)]
@@ -49,23 +55,33 @@ pub struct EffectManager {
     instances: SharedInstanceBatch,
     atlas: TextureAtlas,
     pipeline: wgpu::RenderPipeline,
+    pipeline_layout: wgpu::PipelineLayout,
 }
 
 impl EffectManager {
+    pub fn atlas_stats(&self) -> AtlasStats {
+        self.atlas.stats()
+    }
+
+    pub fn instances(&self) -> &SharedInstanceBatch {
+        &self.instances
+    }
+
     pub fn new(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         archive: &ArxArchive,
         camera_bind_group_layout: &wgpu::BindGroupLayout,
+        atlas_size: u32,
     ) -> Self {
         let diffuse_texture = texture::Texture::from_data(
             device,
             queue,
             "effect_atlas",
-            ATLAS_WIDTH as u32,
-            ATLAS_HEIGHT as u32,
+            atlas_size,
+            atlas_size,
             wgpu::TextureFormat::Rgba8Unorm,
-            &vec![0; ATLAS_WIDTH * ATLAS_HEIGHT * 4],
+            &vec![0; atlas_size as usize * atlas_size as usize * 4],
         )
         .unwrap();
 
@@ -118,21 +134,44 @@ impl EffectManager {
             label: Some("effect_bind_group"),
         });
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Effect Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/effect.wgsl").into()),
-        });
-
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Effect Pipeline Layout"),
             bind_group_layouts: &[&bind_group_layout, camera_bind_group_layout],
             immediate_size: 0,
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let pipeline =
+            Self::create_pipeline(device, &pipeline_layout, shaders::embedded(shaders::EFFECT));
+
+        let vertices = make_quad(VERTEX_SIZE as u32, VERTEX_SIZE as u32).to_vec();
+        let instances = SharedInstanceBatch::new(device, vertices, bind_group);
+
+        Self {
+            loaded_effects: HashMap::new(),
+            frame_sequences,
+            palette_data,
+            palette_indices,
+            instances,
+            atlas: TextureAtlas::new(diffuse_texture.texture),
+            pipeline,
+            pipeline_layout,
+        }
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        source: &str,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Effect Shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             cache: None,
             label: Some("Effect Pipeline"),
-            layout: Some(&pipeline_layout),
+            layout: Some(layout),
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: Some("vs_main"),
@@ -162,20 +201,12 @@ impl EffectManager {
             }),
             multisample: wgpu::MultisampleState::default(),
             multiview_mask: None,
-        });
-
-        let vertices = make_quad(VERTEX_SIZE as u32, VERTEX_SIZE as u32).to_vec();
-        let instances = SharedInstanceBatch::new(device, vertices, bind_group);
+        })
+    }
 
-        Self {
-            loaded_effects: HashMap::new(),
-            frame_sequences,
-            palette_data,
-            palette_indices,
-            instances,
-            atlas: TextureAtlas::new(diffuse_texture.texture),
-            pipeline,
-        }
+    /// Rebuilds the effect pipeline from new shader source, see [`crate::shaders`].
+    pub fn reload_pipeline(&mut self, device: &wgpu::Device, source: &str) {
+        self.pipeline = Self::create_pipeline(device, &self.pipeline_layout, source);
     }
 
     fn parse_effect_tbl(archive: &ArxArchive) -> Vec<EffectFrameSequence> {
@@ -239,6 +270,9 @@ impl EffectManager {
         z_offset: f32,
     ) -> Option<EffectHandle> {
         if !self.loaded_effects.contains_key(&effect_id) {
+            if self.atlas.stats().percent_used() >= EVICT_AT_PERCENT {
+                self.evict_unused();
+            }
             self.load_effect(queue, archive, effect_id)?;
         }
 
@@ -248,6 +282,8 @@ impl EffectManager {
         let instance = self.create_instance(loaded, first_frame, x, y, z_offset)?;
 
         let instance_index = self.instances.add(queue, instance)?;
+        let loaded = self.loaded_effects.get_mut(&effect_id)?;
+        loaded.playing += 1;
 
         Some(EffectHandle {
             instance_index,
@@ -326,6 +362,7 @@ impl EffectManager {
                 frame_sequence,
                 sheet_width: 0,
                 sheet_height: 0,
+                playing: 0,
             },
         );
 
@@ -389,6 +426,7 @@ impl EffectManager {
                 frame_sequence,
                 sheet_width: epf.width as u16,
                 sheet_height: epf.height as u16,
+                playing: 0,
             },
         );
 
@@ -426,7 +464,7 @@ impl EffectManager {
         let (offset_x, offset_y) = *loaded.frame_offsets.get(frame_index)?;
 
         let world_pos = get_isometric_coordinate(x, y);
-        let z = calculate_tile_z(x, y, 1.0) + z_offset;
+        let z = tile_depth(x, y, RenderLayer::Effect, z_offset);
 
         let alloc = match loaded.allocations.get(frame_index).and_then(|a| a.as_ref()) {
             Some(alloc) => alloc,
@@ -438,8 +476,8 @@ impl EffectManager {
             }
         };
 
-        let atlas_w = ATLAS_WIDTH as f32;
-        let atlas_h = ATLAS_HEIGHT as f32;
+        let (atlas_w, atlas_h) = self.atlas.size();
+        let (atlas_w, atlas_h) = (atlas_w as f32, atlas_h as f32);
 
         // EPF-based effects use sheet dimensions for centering (like items)
         // EFA-based effects (sheet_width == 0) use direct frame offsets
@@ -508,6 +546,28 @@ impl EffectManager {
 
     pub fn remove_effect(&mut self, queue: &wgpu::Queue, handle: &EffectHandle) {
         self.instances.remove(queue, handle.instance_index);
+        if let Some(loaded) = self.loaded_effects.get_mut(&handle.effect_id) {
+            loaded.playing = loaded.playing.saturating_sub(1);
+        }
+    }
+
+    /// Frees the atlas space of effect sheets that aren't playing, returning
+    /// how many were dropped. They are loaded again the next time they play.
+    pub fn evict_unused(&mut self) -> usize {
+        let unused: Vec<u16> = self
+            .loaded_effects
+            .iter()
+            .filter(|(_, loaded)| loaded.playing == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &unused {
+            if let Some(loaded) = self.loaded_effects.remove(id) {
+                for allocation in loaded.allocations.into_iter().flatten() {
+                    self.atlas.deallocate(allocation.id);
+                }
+            }
+        }
+        unused.len()
     }
 
     pub fn render<'a>(
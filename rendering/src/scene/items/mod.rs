@@ -13,7 +13,11 @@ use crate::{
     SharedInstanceBatch,
     instance::InstanceFlag,
     scene::{
-        Instance, get_isometric_coordinate, texture_bind::TextureBind, utils::calculate_tile_z,
+        Instance,
+        depth::{RenderLayer, tile_depth},
+        get_isometric_coordinate,
+        texture_atlas::AtlasStats,
+        texture_bind::TextureBind,
     },
     texture,
 };
@@ -35,11 +39,21 @@ pub struct ItemBatch {
     handles: std::sync::Mutex<FxHashMap<usize, u16>>,
 }
 
-pub const ITEM_Z_RANGE: f32 = 0.1;
 /// Item z-order is based on item.id % this value for deterministic ordering
 const ITEM_COUNT_BUCKET_SIZE: u32 = 20;
 
 impl ItemAssetStore {
+    pub fn atlas_stats(&self) -> AtlasStats {
+        AtlasStats::from_allocator(
+            &self.allocation_atlas,
+            self.diffuse
+                .texture
+                .format()
+                .block_copy_size(None)
+                .unwrap_or_default(),
+        )
+    }
+
     pub fn new(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
@@ -138,6 +152,10 @@ impl ItemAssetStore {
 }
 
 impl ItemBatch {
+    pub fn instances(&self) -> &SharedInstanceBatch {
+        &self.instances
+    }
+
     pub fn new(device: &wgpu::Device, store: &ItemAssetStore) -> Self {
         let vertices = crate::make_quad(512, 512).to_vec();
         let batch = SharedInstanceBatch::new(device, vertices, store.bind_group.clone());
@@ -242,10 +260,16 @@ impl ItemBatch {
         let item_offset = Vec2::new(offset_x, offset_y);
 
         // Use spawn_order for z-ordering (set by network receive order)
-        // Modulo ensures we stay within ITEM_Z_RANGE even if spawn_order exceeds bucket size
+        // Clamping keeps it inside the item layer even if spawn_order exceeds the bucket size
         let item_order = item.spawn_order.min(ITEM_COUNT_BUCKET_SIZE as u8 - 1);
-        let z_within_tile = (item_order as f32 / ITEM_COUNT_BUCKET_SIZE as f32) * ITEM_Z_RANGE;
-        let z = calculate_tile_z(item.x as f32, item.y as f32, z_within_tile);
+        let z_within_tile =
+            (item_order as f32 / ITEM_COUNT_BUCKET_SIZE as f32) * RenderLayer::Item.range();
+        let z = tile_depth(
+            item.x as f32,
+            item.y as f32,
+            RenderLayer::Item,
+            z_within_tile,
+        );
 
         let instance = Instance::with_texture_atlas(
             (world_pos + item_offset).extend(z),
@@ -324,10 +348,16 @@ impl ItemBatch {
 
         let item_offset = Vec2::new(offset_x, offset_y);
 
-        // Apply modulo to ensure z stays within ITEM_Z_RANGE
+        // Clamp to keep z inside the item layer
         let item_order = item.spawn_order.min(ITEM_COUNT_BUCKET_SIZE as u8 - 1);
-        let z_within_tile = (item_order as f32 / ITEM_COUNT_BUCKET_SIZE as f32) * ITEM_Z_RANGE;
-        let z = calculate_tile_z(item.x as f32, item.y as f32, z_within_tile);
+        let z_within_tile =
+            (item_order as f32 / ITEM_COUNT_BUCKET_SIZE as f32) * RenderLayer::Item.range();
+        let z = tile_depth(
+            item.x as f32,
+            item.y as f32,
+            RenderLayer::Item,
+            z_within_tile,
+        );
 
         let instance = Instance::with_texture_atlas(
             (world_pos + item_offset).extend(z),
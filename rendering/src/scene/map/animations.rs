@@ -1,12 +1,18 @@
 use crate::{Instance, instance::InstanceFlag};
 use std::time::{Duration, Instant};
 
-#[derive(Clone, Debug)]
+/// A cycle of floor or wall tiles from `seo/gndani.tbl` or `ia/stcani.tbl`.
+/// Each line of the table lists the tile ids in order, then the interval
+/// between frames in tenths of a second.
+#[derive(Clone, Debug, PartialEq)]
 pub struct WorldAnimation {
     pub ids: Vec<u16>,
     pub interval: Duration,
 }
 
+/// Tables with an interval of 0 would otherwise animate every frame.
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
 impl WorldAnimation {
     pub fn from_string(input: &str) -> Vec<WorldAnimation> {
         input
@@ -14,23 +20,37 @@ impl WorldAnimation {
             .filter_map(|line| {
                 let numbers: Vec<u16> = line
                     .split_whitespace()
-                    .filter_map(|s| s.parse().ok())
+                    .map_while(|s| s.parse().ok())
                     .collect();
 
-                if numbers.len() <= 1 {
+                // An id and an interval is a single frame, nothing to animate
+                if numbers.len() <= 2 {
                     return None;
                 }
 
-                let interval = numbers.last().copied().unwrap_or(100);
+                let interval = numbers.last().copied().unwrap_or(1);
                 let ids = numbers[..numbers.len() - 1].to_vec();
 
                 Some(WorldAnimation {
                     ids,
-                    interval: Duration::from_millis((interval as u64) * 100),
+                    interval: Duration::from_millis((interval as u64) * 100).max(MIN_INTERVAL),
                 })
             })
             .collect()
     }
+
+    /// Reads an animation table from the archive. Maps still draw without
+    /// one, just without animated tiles.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(archive: &formats::game_files::ArxArchive, path: &str) -> Vec<WorldAnimation> {
+        match archive.get_file(path) {
+            Ok(data) => Self::from_string(&String::from_utf8_lossy(&data)),
+            Err(e) => {
+                tracing::warn!("No tile animations, {}: {}", path, e);
+                Vec::new()
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -109,6 +129,10 @@ impl WorldAnimationInstanceData {
         self.animation.ids.contains(&id)
     }
 
+    pub fn ids(&self) -> &[u16] {
+        &self.animation.ids
+    }
+
     pub fn instances(&self) -> &[InstanceReference] {
         &self.data.instances
     }
@@ -141,3 +165,31 @@ fn convert_instance_positions_to_offset(frames: &[Instance]) -> Vec<Instance> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_list_frames_then_the_interval() {
+        let table = "10 11 12 13 5\r\n\r\n200 201 0\r\n7 3\r\n300 301 2 // torch\r\n";
+        let animations = WorldAnimation::from_string(table);
+        assert_eq!(
+            animations,
+            vec![
+                WorldAnimation {
+                    ids: vec![10, 11, 12, 13],
+                    interval: Duration::from_millis(500),
+                },
+                WorldAnimation {
+                    ids: vec![200, 201],
+                    interval: MIN_INTERVAL,
+                },
+                WorldAnimation {
+                    ids: vec![300, 301],
+                    interval: Duration::from_millis(200),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Map chunks: square groups of tiles whose instances are uploaded and drawn
+//! together. Only chunks around a view get an instance buffer, and only the
+//! ones on screen are drawn, so big maps cost about as much as the visible
+//! region. The CPU copy of every instance stays so animations keep their
+//! frame while a chunk is unloaded.
+
+use std::ops::Range;
+
+use glam::Vec2;
+
+use crate::{Instance, InstanceBatch, Vertex};
+
+/// Chunk side in tiles.
+pub const CHUNK_TILES: u8 = 16;
+
+/// Screen space around a view whose chunks are loaded ahead of scrolling.
+const LOAD_MARGIN: f32 = 256.0;
+/// Loaded chunks are only dropped this far outside every view, so walking
+/// along a chunk edge doesn't reload them over and over.
+const UNLOAD_MARGIN: f32 = 768.0;
+
+/// Screen-space rectangle as (min, max).
+pub type Bounds = (Vec2, Vec2);
+
+/// Index of the chunk holding tile (x, y) on a map `map_width` tiles wide.
+pub fn chunk_index(x: u8, y: u8, map_width: u8) -> usize {
+    let columns = map_width.div_ceil(CHUNK_TILES) as usize;
+    (y / CHUNK_TILES) as usize * columns + (x / CHUNK_TILES) as usize
+}
+
+fn overlaps(a: Bounds, b: Bounds, margin: f32) -> bool {
+    a.0.x <= b.1.x + margin
+        && a.1.x >= b.0.x - margin
+        && a.0.y <= b.1.y + margin
+        && a.1.y >= b.0.y - margin
+}
+
+/// Instances of one chunk in a [`ChunkedBatch`] and their buffer while loaded.
+struct ChunkSpan {
+    range: Range<usize>,
+    bounds: Bounds,
+    batch: Option<InstanceBatch>,
+}
+
+/// Floor tiles or the walls of one height, ordered by chunk.
+pub struct ChunkedBatch {
+    instances: Vec<Instance>,
+    vertices: Vec<Vertex>,
+    bind_group: wgpu::BindGroup,
+    spans: Vec<ChunkSpan>,
+}
+
+impl ChunkedBatch {
+    /// `instances` pairs each instance with its chunk and must be sorted by chunk.
+    pub fn new(
+        instances: Vec<(usize, Instance)>,
+        vertices: Vec<Vertex>,
+        bind_group: wgpu::BindGroup,
+    ) -> Self {
+        let quad_size = vertices
+            .iter()
+            .fold(Vec2::ZERO, |size, v| size.max(Vec2::from(v.position)));
+
+        let mut spans: Vec<ChunkSpan> = Vec::new();
+        let mut current_chunk = None;
+        for (index, (chunk, instance)) in instances.iter().enumerate() {
+            let min = instance.position.truncate();
+            let max = min + quad_size;
+            match spans.last_mut() {
+                Some(span) if current_chunk == Some(*chunk) => {
+                    span.range.end = index + 1;
+                    span.bounds = (span.bounds.0.min(min), span.bounds.1.max(max));
+                }
+                _ => spans.push(ChunkSpan {
+                    range: index..index + 1,
+                    bounds: (min, max),
+                    batch: None,
+                }),
+            }
+            current_chunk = Some(*chunk);
+        }
+
+        Self {
+            instances: instances
+                .into_iter()
+                .map(|(_, instance)| instance)
+                .collect(),
+            vertices,
+            bind_group,
+            spans,
+        }
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    pub fn get_instance(&self, index: usize) -> Option<&Instance> {
+        self.instances.get(index)
+    }
+
+    /// Bounds of every instance, loaded or not.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.spans
+            .iter()
+            .map(|span| span.bounds)
+            .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+    }
+
+    /// Buffers of the chunks currently loaded.
+    pub fn loaded(&self) -> impl Iterator<Item = &InstanceBatch> {
+        self.spans.iter().filter_map(|span| span.batch.as_ref())
+    }
+
+    pub fn update_instance(&mut self, queue: &wgpu::Queue, index: usize, instance: Instance) {
+        let Some(slot) = self.instances.get_mut(index) else {
+            return;
+        };
+        let span_index = self.spans.partition_point(|span| span.range.end <= index);
+        if let Some(span) = self.spans.get_mut(span_index)
+            && let Some(batch) = &mut span.batch
+        {
+            batch.update_instance(queue, index - span.range.start, instance.clone());
+        }
+        *slot = instance;
+    }
+
+    /// Loads the chunks near any of `views` and drops the ones far from all of them.
+    pub fn stream(&mut self, device: &wgpu::Device, views: &[Bounds]) {
+        for span in &mut self.spans {
+            if span.batch.is_none() {
+                if views.iter().any(|v| overlaps(span.bounds, *v, LOAD_MARGIN)) {
+                    span.batch = Some(InstanceBatch::with_capacity(
+                        device,
+                        self.instances[span.range.clone()].to_vec(),
+                        self.vertices.clone(),
+                        self.bind_group.clone(),
+                        span.range.len(),
+                    ));
+                }
+            } else if !views
+                .iter()
+                .any(|v| overlaps(span.bounds, *v, UNLOAD_MARGIN))
+            {
+                span.batch = None;
+            }
+        }
+    }
+
+    /// Draws the loaded chunks that overlap `view`.
+    pub fn render(&self, render_pass: &mut wgpu::RenderPass<'_>, view: Bounds) {
+        for span in &self.spans {
+            let Some(batch) = &span.batch else {
+                continue;
+            };
+            if !overlaps(span.bounds, view, 0.0) {
+                continue;
+            }
+            render_pass.set_bind_group(0, &batch.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, batch.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, batch.instance_buffer.slice(..));
+            render_pass.draw(0..batch.vertices.len() as _, 0..batch.instances.len() as _);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_follow_rows_of_chunks() {
+        // 100 tiles wide is 7 chunks per row, the last one partial
+        assert_eq!(chunk_index(0, 0, 100), 0);
+        assert_eq!(chunk_index(15, 15, 100), 0);
+        assert_eq!(chunk_index(16, 0, 100), 1);
+        assert_eq!(chunk_index(99, 0, 100), 6);
+        assert_eq!(chunk_index(0, 16, 100), 7);
+        assert_eq!(chunk_index(99, 99, 100), 48);
+    }
+
+    #[test]
+    fn margins_reach_past_the_view() {
+        let view = (Vec2::ZERO, Vec2::new(640.0, 480.0));
+        let beside = (Vec2::new(700.0, 0.0), Vec2::new(800.0, 100.0));
+        assert!(!overlaps(beside, view, 0.0));
+        assert!(overlaps(beside, view, LOAD_MARGIN));
+    }
+}
@@ -0,0 +1,47 @@
+//! Doors and map exits picked out of the map data so they can be highlighted.
+//!
+//! Doors are recognised by their wall ids. Servers never send warp locations, so
+//! exits are taken to be the open floor tiles along the edge of the map.
+
+use crate::scene::map::{door_data::DOOR_DATA, map_tile::MapTile};
+
+pub fn is_door_wall(wall_id: u16) -> bool {
+    DOOR_DATA.contains(&wall_id)
+}
+
+pub fn is_map_exit(tile: &MapTile, x: u8, y: u8, map_width: u8, map_height: u8) -> bool {
+    let on_edge = x == 0 || y == 0 || x + 1 == map_width || y + 1 == map_height;
+    on_edge && tile.floor.show() && !tile.wall_left.show() && !tile.wall_right.show()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::map::{
+        floor::FloorTile,
+        wall::{Wall, WallSide},
+    };
+
+    fn tile(floor: u16, wall_left: u16) -> MapTile {
+        MapTile {
+            floor: FloorTile { id: floor },
+            wall_left: Wall {
+                id: wall_left,
+                side: WallSide::Left,
+            },
+            wall_right: Wall {
+                id: 0,
+                side: WallSide::Right,
+            },
+        }
+    }
+
+    #[test]
+    fn exits_are_open_edge_tiles() {
+        assert!(is_map_exit(&tile(5, 0), 0, 4, 10, 10));
+        assert!(is_map_exit(&tile(5, 0), 4, 9, 10, 10));
+        assert!(!is_map_exit(&tile(5, 0), 4, 4, 10, 10));
+        assert!(!is_map_exit(&tile(0, 0), 0, 4, 10, 10));
+        assert!(!is_map_exit(&tile(5, 120), 0, 4, 10, 10));
+    }
+}
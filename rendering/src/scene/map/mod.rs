@@ -1,6 +1,8 @@
 pub mod animations;
+pub mod chunks;
 pub mod door_data;
 pub mod floor;
+pub mod interactables;
 pub mod map_tile;
 pub mod renderer;
 pub mod wall;
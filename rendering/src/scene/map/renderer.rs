@@ -1,8 +1,11 @@
 use crate::{
-    Instance, InstanceBatch,
+    Instance,
     instance::InstanceFlag,
     make_quad,
-    scene::{map::door_data::DOOR_DATA, utils::calculate_tile_z},
+    scene::{
+        depth::{FLOOR_DEPTH, RenderLayer, tile_depth},
+        map::door_data::DOOR_DATA,
+    },
 };
 use etagere::Allocation;
 use glam::Vec2;
@@ -16,7 +19,9 @@ use crate::{
         TILEMAP_TILE_WIDTH, TILEMAP_TILES_PER_PAGE_ROWS, TILEMAP_TILES_PER_ROW, WALL_ATLAS_HEIGHT,
         WALL_ATLAS_WIDTH, WorldAnimation, WorldAnimationInstanceData, make_bind_group,
         map::{
+            chunks::{Bounds, ChunkedBatch, chunk_index},
             floor::FloorTile,
+            interactables::{is_door_wall, is_map_exit},
             map_tile::MapTile,
             wall::{Wall, WallSide},
         },
@@ -43,15 +48,27 @@ pub struct PreparedMap {
     pub animations: Vec<WorldAnimationInstanceData>,
     pub wall_toggle_animations: HashMap<(u8, u8), AnimationInstanceData>,
     pub wall_toggle_tracker: HashMap<(u16, usize), ((u8, u8), Vec<Instance>)>,
+    /// Tile of each wall instance, keyed like `wall_toggle_tracker`.
+    wall_tiles: HashMap<(u16, usize), (u8, u8)>,
     wall_animations: Vec<WorldAnimationInstanceData>,
-    tile_instances: Vec<Instance>,
-    allocated: HashMap<u16, (Allocation, Vec<Instance>)>,
+    tile_instances: ChunkInstances,
+    allocated: HashMap<u16, (Allocation, ChunkInstances)>,
 }
 
+/// Instances paired with their chunk, see [`chunk_index`].
+type ChunkInstances = Vec<(usize, Instance)>;
+
 pub struct MapRenderer {
     animations: Vec<WorldAnimationInstanceData>,
     wall_toggle_animations: HashMap<(u8, u8), AnimationInstanceData>,
-    instance_batches: Vec<InstanceBatch>,
+    instance_batches: Vec<ChunkedBatch>,
+    /// Wall instances standing on each tile.
+    wall_instances: HashMap<(u8, u8), Vec<InstanceReference>>,
+    /// Palette offsets of the walls hidden by [`MapRenderer::set_hidden_walls`],
+    /// by (batch, instance). Animations keep updating them while hidden.
+    hidden_walls: HashMap<(usize, usize), f32>,
+    /// GPU memory held by the tile and wall textures and their palettes.
+    texture_bytes: u64,
 }
 
 impl MapRenderer {
@@ -60,73 +77,67 @@ impl MapRenderer {
             instance_batches: Vec::new(),
             animations: Vec::new(),
             wall_toggle_animations: HashMap::new(),
+            wall_instances: HashMap::new(),
+            hidden_walls: HashMap::new(),
+            texture_bytes: 0,
         }
     }
 
     pub fn new(
-        instance_batches: Vec<InstanceBatch>,
+        instance_batches: Vec<ChunkedBatch>,
         animations: Vec<WorldAnimationInstanceData>,
         wall_toggle_animations: HashMap<(u8, u8), AnimationInstanceData>,
+        wall_instances: HashMap<(u8, u8), Vec<InstanceReference>>,
+        texture_bytes: u64,
     ) -> Self {
         Self {
             instance_batches,
             animations,
             wall_toggle_animations,
+            wall_instances,
+            hidden_walls: HashMap::new(),
+            texture_bytes,
         }
     }
 
-    pub fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+    pub fn texture_bytes(&self) -> u64 {
+        self.texture_bytes
+    }
+
+    /// Floor tiles first, then one batch per wall height.
+    pub fn instance_batches(&self) -> &[ChunkedBatch] {
+        &self.instance_batches
+    }
+
+    /// Loads the chunks around each view and drops the ones far from all of
+    /// them. Views are screen-space bounds, see `Camera::visible_bounds`.
+    pub fn stream_chunks(&mut self, device: &wgpu::Device, views: &[Bounds]) {
+        for batch in &mut self.instance_batches {
+            batch.stream(device, views);
+        }
+    }
+
+    /// Draws the loaded chunks that overlap `view`.
+    pub fn render(&self, render_pass: &mut wgpu::RenderPass<'_>, view: Bounds) {
         for batch in &self.instance_batches {
-            render_pass.set_bind_group(0, &batch.bind_group, &[]);
-            render_pass.set_vertex_buffer(0, batch.vertex_buffer.slice(..));
-            render_pass.set_vertex_buffer(1, batch.instance_buffer.slice(..));
-            render_pass.draw(0..batch.vertices.len() as _, 0..batch.instances.len() as _);
+            batch.render(render_pass, view);
         }
     }
 
     /// Compute 2D bounds (min, max) of all tile & wall instances in screen space.
     /// Returns None if there are no instances.
-    pub fn bounds(&self) -> Option<(glam::Vec2, glam::Vec2)> {
-        let mut min: Option<glam::Vec2> = None;
-        let mut max: Option<glam::Vec2> = None;
-        for (batch_index, batch) in self.instance_batches.iter().enumerate() {
-            // First batch is floor tiles (quads of TILE_WIDTH x TILE_HEIGHT). Others are walls whose
-            // quad size differs. We expand bounds by their quad vertex size where possible.
-            let (quad_w, quad_h) = if batch_index == 0 {
-                (
-                    crate::scene::TILE_WIDTH as f32,
-                    crate::scene::TILE_HEIGHT as f32,
-                )
-            } else {
-                // Walls: width is 28, height varies – we approximate height from first instance delta in tex coords * atlas size.
-                // Fallback: just use tile width/height minima so map never collapses.
-                (28.0_f32, crate::scene::TILE_HEIGHT as f32)
-            };
-            for inst in &batch.instances {
-                let p = inst.position.truncate();
-                let tl = p; // instance positions are already top-left for floors, adjusted for walls.
-                let br = p + glam::Vec2::new(quad_w, quad_h);
-                min = Some(match min {
-                    Some(m) => m.min(tl),
-                    None => tl,
-                });
-                max = Some(match max {
-                    Some(m) => m.max(br),
-                    None => br,
-                });
-            }
-        }
-        match (min, max) {
-            (Some(a), Some(b)) => Some((a, b)),
-            _ => None,
-        }
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.instance_batches
+            .iter()
+            .filter_map(ChunkedBatch::bounds)
+            .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
     }
 
     /// Returns floor tile instances (batch 0) for CPU fallback visualization.
     pub fn floor_instances(&self) -> &[crate::Instance] {
         self.instance_batches
-            .get(0)
-            .map(|b| b.instances.as_slice())
+            .first()
+            .map(|b| b.instances())
             .unwrap_or(&[])
     }
 
@@ -141,24 +152,13 @@ impl MapRenderer {
             let changes_to_apply = anim.advance(now);
 
             for instance_ref in anim.instances().iter() {
-                if let Some(batch) = self.instance_batches.get_mut(instance_ref.batch_index) {
-                    if let Some(instance) = batch.get_instance(instance_ref.instance_index) {
-                        batch.update_instance(
-                            queue,
-                            instance_ref.instance_index,
-                            Instance {
-                                position: instance.position + changes_to_apply.position,
-                                tex_min: changes_to_apply.tex_min,
-                                tex_max: changes_to_apply.tex_max,
-                                sprite_size: changes_to_apply.sprite_size,
-                                palette_offset: changes_to_apply.palette_offset,
-                                dye_v_offset: instance.dye_v_offset,
-                                flags: instance.flags,
-                                tint: instance.tint,
-                            },
-                        );
-                    }
-                }
+                apply_frame(
+                    &mut self.instance_batches,
+                    &mut self.hidden_walls,
+                    queue,
+                    instance_ref,
+                    &changes_to_apply,
+                );
             }
         }
     }
@@ -169,30 +169,66 @@ impl MapRenderer {
             let instance_data = anim.set_frame(frame);
 
             for instance_ref in &anim.instances {
-                if let Some(batch) = self.instance_batches.get_mut(instance_ref.batch_index) {
-                    if let Some(instance) = batch.get_instance(instance_ref.instance_index) {
-                        batch.update_instance(
-                            queue,
-                            instance_ref.instance_index,
-                            Instance {
-                                position: instance.position + instance_data.position,
-                                tex_min: instance_data.tex_min,
-                                tex_max: instance_data.tex_max,
-                                sprite_size: instance_data.sprite_size,
-                                palette_offset: instance_data.palette_offset,
-                                dye_v_offset: instance.dye_v_offset,
-                                flags: instance.flags,
-                                tint: instance.tint,
-                            },
-                        );
-                    }
-                }
+                apply_frame(
+                    &mut self.instance_batches,
+                    &mut self.hidden_walls,
+                    queue,
+                    instance_ref,
+                    &instance_data,
+                );
             }
         } else {
             tracing::warn!("No wall toggle animation found at ({}, {})", x, y);
         }
     }
 
+    /// Hides the walls standing on `tiles` and brings back the ones hidden
+    /// before that aren't in it.
+    pub fn set_hidden_walls(&mut self, queue: &wgpu::Queue, tiles: &HashSet<(u8, u8)>) {
+        let hide: HashSet<(usize, usize)> = tiles
+            .iter()
+            .filter_map(|tile| self.wall_instances.get(tile))
+            .flatten()
+            .map(|r| (r.batch_index, r.instance_index))
+            .collect();
+
+        let shown: Vec<(usize, usize)> = self
+            .hidden_walls
+            .keys()
+            .filter(|key| !hide.contains(key))
+            .copied()
+            .collect();
+        for key @ (batch_index, instance_index) in shown {
+            let palette_offset = self.hidden_walls.remove(&key).unwrap_or_default();
+            if let Some(batch) = self.instance_batches.get_mut(batch_index)
+                && let Some(instance) = batch.get_instance(instance_index)
+            {
+                let instance = Instance {
+                    palette_offset,
+                    ..instance.clone()
+                };
+                batch.update_instance(queue, instance_index, instance);
+            }
+        }
+
+        for key @ (batch_index, instance_index) in hide {
+            if self.hidden_walls.contains_key(&key) {
+                continue;
+            }
+            if let Some(batch) = self.instance_batches.get_mut(batch_index)
+                && let Some(instance) = batch.get_instance(instance_index)
+            {
+                self.hidden_walls.insert(key, instance.palette_offset);
+                // A negative palette offset is discarded by the shader
+                let instance = Instance {
+                    palette_offset: -1.,
+                    ..instance.clone()
+                };
+                batch.update_instance(queue, instance_index, instance);
+            }
+        }
+    }
+
     pub fn prepare_map(
         archive: &Archive,
         map_data: Vec<u8>,
@@ -206,7 +242,8 @@ impl MapRenderer {
         let (tile_palette_table, _): (rangemap::RangeMap<u16, u16>, usize) =
             bincode::serde::decode_from_slice(&mpt_data, bincode::config::standard()).unwrap();
 
-        let wall_table_name = format!("ia/st{}.tbl.bin", if is_snow { "s" } else { "c" });
+        let wall_palette = if is_snow { "ia/sts" } else { "ia/stc" };
+        let wall_table_name = format!("{}.tbl.bin", wall_palette);
         let wall_table_data = archive.get_file_or_panic(&wall_table_name);
 
         let (wall_palette_table, _): (rangemap::RangeMap<u16, u16>, usize) =
@@ -221,7 +258,7 @@ impl MapRenderer {
             let tilemap_x = tile_id - (tilemap_y * TILEMAP_COLUMNS);
             let coord = FloorTile::get_position(x as f32, y as f32);
             Instance {
-                position: coord.extend(0.0),
+                position: coord.extend(FLOOR_DEPTH),
                 tex_min: Vec2::new(
                     tilemap_x as f32 * TILEMAP_TILE_WIDTH,
                     tilemap_y as f32 * TILEMAP_TILE_HEIGHT,
@@ -253,10 +290,7 @@ impl MapRenderer {
                     }
                 }
             }
-            let wall_anim_data = archive.get_file_or_panic("ia/stcani.tbl");
-
-            let wall_anim_store =
-                WorldAnimation::from_string(&String::from_utf8_lossy(&wall_anim_data));
+            let wall_anim_store = WorldAnimation::load(archive, "ia/stcani.tbl");
 
             let mut matching_wall_anims: Vec<WorldAnimation> = Vec::new();
 
@@ -300,12 +334,10 @@ impl MapRenderer {
         let door_pairs = crate::scene::map::door_data::get_door_tile_toggle_pairs();
         let mut wall_toggle_tracker: HashMap<(u16, usize), ((u8, u8), Vec<Instance>)> =
             HashMap::new();
+        let mut wall_tiles: HashMap<(u16, usize), (u8, u8)> = HashMap::new();
 
         let (mut animations, map_data) = {
-            let floor_anim_data = archive.get_file_or_panic("seo/gndani.tbl");
-
-            let all_floor_animations =
-                WorldAnimation::from_string(&String::from_utf8_lossy(&floor_anim_data));
+            let all_floor_animations = WorldAnimation::load(archive, "seo/gndani.tbl");
 
             let mut map_reader = std::io::Cursor::new(map_data);
 
@@ -320,13 +352,12 @@ impl MapRenderer {
                 .into_iter()
                 .filter(|anim| anim.ids.iter().any(|id| floors.contains_key(id)))
                 .map(|anim| {
+                    // Frames needn't be on the map themselves, so build them from the ids.
+                    // The positions are converted into offsets in the animation.
                     let frames: Vec<Instance> = anim
                         .ids
                         .iter()
-                        .map(|id| {
-                            let tile = floors.get(id).unwrap();
-                            build_tile_instance(tile, 0, 0) // These positions will be converted into offsets in the animation
-                        })
+                        .map(|id| build_tile_instance(&FloorTile { id: id + 1 }, 0, 0))
                         .collect();
 
                     WorldAnimationInstanceData::new(anim.clone(), frames)
@@ -347,15 +378,18 @@ impl MapRenderer {
                 needed_pages.insert(tile_id / tiles_per_page);
             }
         }
-        // Include pages for any animated frames (ids are guaranteed to be in map per existing logic)
-        // The existing animations builder only includes anims whose ids are present in floors.
-        // So the pass above already marked those pages.
+        // Animated tiles cycle through frames that may not be on the map
+        for anim in &animations {
+            for tile_id in anim.ids() {
+                needed_pages.insert(*tile_id as usize / tiles_per_page);
+            }
+        }
 
         // Clone map_data for the first reading pass
         let mut map_reader = std::io::Cursor::new(map_data);
 
         let mut wall_map_buf = vec![0u8; WALL_ATLAS_WIDTH * WALL_ATLAS_HEIGHT];
-        let mut tile_instances: Vec<Instance> = Vec::new();
+        let mut tile_instances: ChunkInstances = Vec::new();
         let mut atlas = etagere::AtlasAllocator::with_options(
             etagere::size2(WALL_ATLAS_WIDTH as i32, WALL_ATLAS_HEIGHT as i32),
             &etagere::AllocatorOptions {
@@ -364,7 +398,7 @@ impl MapRenderer {
             },
         );
 
-        let mut allocated: HashMap<u16, (etagere::Allocation, Vec<Instance>)> = HashMap::new();
+        let mut allocated: HashMap<u16, (etagere::Allocation, ChunkInstances)> = HashMap::new();
         let mut wall_heights: HashMap<u16, u16> = HashMap::new();
 
         {
@@ -401,7 +435,7 @@ impl MapRenderer {
             let coord = wall.side.get_position(x, y, height as f32);
             let palette_offset = *wall_palette_table.get(&wall.palette_index()).unwrap_or(&0);
             Instance {
-                position: coord.extend(calculate_tile_z(x, y, 0.98)),
+                position: coord.extend(tile_depth(x, y, RenderLayer::Wall, 0.0)),
                 tex_min: Vec2::new(
                     a.rectangle.min.x as f32 / WALL_ATLAS_WIDTH as f32,
                     a.rectangle.min.y as f32 / WALL_ATLAS_HEIGHT as f32,
@@ -422,75 +456,93 @@ impl MapRenderer {
             }
         };
 
-        for y in 0..map_height {
-            for x in 0..map_width {
-                let MapTile {
-                    floor,
-                    wall_left,
-                    wall_right,
-                } = MapTile::read_from_reader(&mut map_reader);
-
-                for wall in [wall_left, wall_right] {
-                    if !wall.show() {
-                        continue;
-                    }
+        let tiles: Vec<MapTile> = (0..((map_width as usize) * (map_height as usize)))
+            .map(|_| MapTile::read_from_reader(&mut map_reader))
+            .collect();
 
-                    // Change to the first frames wall ID if it matches an animation
-                    // Otherwise the position offset will behave incorrectly
-                    let wall_id = if let Some(anim) = active_wall_animations
-                        .iter()
-                        .find(|anim| anim.ids.contains(&wall.id))
-                    {
-                        anim.ids[0]
-                    } else {
-                        wall.id
-                    };
+        // Chunk by chunk, so each chunk's instances end up next to each other
+        let mut positions: Vec<(u8, u8)> = (0..map_height)
+            .flat_map(|y| (0..map_width).map(move |x| (x, y)))
+            .collect();
+        positions.sort_by_key(|&(x, y)| chunk_index(x, y, map_width));
+
+        for (x, y) in positions {
+            let chunk = chunk_index(x, y, map_width);
+            let tile = tiles[y as usize * map_width as usize + x as usize];
+            let MapTile {
+                floor,
+                wall_left,
+                wall_right,
+            } = tile;
+
+            for wall in [wall_left, wall_right] {
+                if !wall.show() {
+                    continue;
+                }
 
-                    let (a, instances) = allocated.get_mut(&wall_id).unwrap();
-                    let instance_idx = instances.len();
-                    instances.push(build_wall_instance(wall, x as f32, y as f32, a));
+                // Change to the first frames wall ID if it matches an animation
+                // Otherwise the position offset will behave incorrectly
+                let wall_id = if let Some(anim) = active_wall_animations
+                    .iter()
+                    .find(|anim| anim.ids.contains(&wall.id))
+                {
+                    anim.ids[0]
+                } else {
+                    wall.id
+                };
+
+                let (a, instances) = allocated.get_mut(&wall_id).unwrap();
+                let instance_idx = instances.len();
+                let mut instance = build_wall_instance(wall, x as f32, y as f32, a);
+                if is_door_wall(wall.id) {
+                    instance.flags = instance.flags.with_glow();
+                }
+                instances.push((chunk, instance));
+                wall_tiles.insert((wall_id, instance_idx), (x, y));
 
-                    if let Some(pair) = door_pairs
-                        .iter()
-                        .find(|p| p.open_tile == wall.id || p.closed_tile == wall.id)
-                    {
-                        let open_wall = Wall {
-                            id: pair.open_tile,
-                            side: wall.side,
-                        };
-                        let closed_wall = Wall {
-                            id: pair.closed_tile,
-                            side: wall.side,
-                        };
+                if let Some(pair) = door_pairs
+                    .iter()
+                    .find(|p| p.open_tile == wall.id || p.closed_tile == wall.id)
+                {
+                    let open_wall = Wall {
+                        id: pair.open_tile,
+                        side: wall.side,
+                    };
+                    let closed_wall = Wall {
+                        id: pair.closed_tile,
+                        side: wall.side,
+                    };
 
-                        let (a_open, _) = allocated.get(&pair.open_tile).unwrap();
-                        let (a_closed, _) = allocated.get(&pair.closed_tile).unwrap();
+                    let (a_open, _) = allocated.get(&pair.open_tile).unwrap();
+                    let (a_closed, _) = allocated.get(&pair.closed_tile).unwrap();
 
-                        let open_instance =
-                            build_wall_instance(open_wall, x as f32, y as f32, a_open);
-                        let closed_instance =
-                            build_wall_instance(closed_wall, x as f32, y as f32, a_closed);
+                    let open_instance = build_wall_instance(open_wall, x as f32, y as f32, a_open);
+                    let closed_instance =
+                        build_wall_instance(closed_wall, x as f32, y as f32, a_closed);
 
-                        wall_toggle_tracker.insert(
-                            (wall_id, instance_idx),
-                            ((x, y), vec![open_instance, closed_instance]),
-                        );
-                    }
+                    wall_toggle_tracker.insert(
+                        (wall_id, instance_idx),
+                        ((x, y), vec![open_instance, closed_instance]),
+                    );
                 }
+            }
 
-                if floor.show() {
-                    let instance_idx = tile_instances.len();
-                    tile_instances.push(build_tile_instance(&floor, x, y));
-
-                    if let Some(anim) = animations
-                        .iter_mut()
-                        .find(|anim| anim.contains_id(floor.tile_id()))
-                    {
-                        anim.data.instances.push(InstanceReference {
-                            batch_index: 0, // Tile animations are always in the first batch
-                            instance_index: instance_idx,
-                        });
-                    }
+            if floor.show() {
+                let instance_idx = tile_instances.len();
+                let mut instance = build_tile_instance(&floor, x, y);
+                if is_map_exit(&tile, x, y, map_width, map_height) {
+                    instance.flags = InstanceFlag::Glow;
+                }
+                tile_instances.push((chunk, instance));
+
+                if let Some(anim) = animations
+                    .iter_mut()
+                    .find(|anim| anim.contains_id(floor.tile_id()))
+                {
+                    anim.data.instances.push(InstanceReference {
+                        batch_index: 0, // Tile animations are always in the first batch
+                        instance_index: instance_idx,
+                    });
                 }
             }
         }
@@ -542,7 +594,7 @@ impl MapRenderer {
         }
 
         let palette_texture_data = archive.get_file_or_panic("seo/mpt.ktx2");
-        let wall_palette_data = archive.get_file_or_panic("ia/stc.ktx2");
+        let wall_palette_data = archive.get_file_or_panic(&format!("{}.ktx2", wall_palette));
 
         PreparedMap {
             tile_texture_data,
@@ -553,6 +605,7 @@ impl MapRenderer {
             animations,
             wall_toggle_animations: HashMap::new(),
             wall_toggle_tracker,
+            wall_tiles,
             wall_animations,
             tile_instances,
             allocated,
@@ -580,6 +633,9 @@ impl MapRenderer {
         )
         .unwrap();
 
+        let mut texture_bytes = texture::texture_bytes(&diffuse_texture.texture)
+            + texture::texture_bytes(&palette_texture.texture);
+
         let texture_bind_group_layout = Self::make_texture_bind_group_layout(&device);
 
         let tile_bind_group = make_bind_group(
@@ -590,10 +646,9 @@ impl MapRenderer {
             &texture::Texture::empty_view(device, "tile_empty"),
         );
 
-        let mut instance_batches: Vec<InstanceBatch> = Vec::new();
+        let mut instance_batches: Vec<ChunkedBatch> = Vec::new();
 
-        instance_batches.push(InstanceBatch::new(
-            &device,
+        instance_batches.push(ChunkedBatch::new(
             map.tile_instances,
             make_quad(TILE_WIDTH, TILE_HEIGHT).to_vec(),
             tile_bind_group,
@@ -617,10 +672,12 @@ impl MapRenderer {
             &map.wall_palette_data,
         )
         .unwrap();
+        texture_bytes += texture::texture_bytes(&diffuse_texture.texture)
+            + texture::texture_bytes(&palette_texture.texture);
 
         // find each different height allocated and create a batch for it
         // group the allocations by height so that they can allocate more tightly on the atlas
-        let mut height_map: HashMap<i32, Vec<(etagere::Allocation, u16, Vec<Instance>)>> =
+        let mut height_map: HashMap<i32, Vec<(etagere::Allocation, u16, ChunkInstances)>> =
             HashMap::new();
 
         for (wall_id, (a, instances)) in map.allocated {
@@ -631,45 +688,66 @@ impl MapRenderer {
         }
 
         let door_pairs = crate::scene::map::door_data::get_door_tile_toggle_pairs();
+        let mut wall_instances: HashMap<(u8, u8), Vec<InstanceReference>> = HashMap::new();
         for (height, instances_at_height) in height_map {
             let vertices = make_quad(28, height as u32).to_vec();
 
             let batch_index = instance_batches.len();
-            let mut instances: Vec<Instance> = Vec::new();
-
-            for (_, wall_id, curr_instances) in instances_at_height {
-                for (idx_in_wall_list, instance) in curr_instances.into_iter().enumerate() {
-                    let instance_index = instances.len();
-
-                    for anim in map.wall_animations.iter_mut() {
-                        if anim.contains_id(wall_id) {
-                            anim.data.instances.push(InstanceReference {
-                                batch_index,
-                                instance_index: instance_index,
-                            });
-                        }
-                    }
+            let mut instances: ChunkInstances = Vec::new();
+
+            // Walls of every id at this height, regrouped by chunk
+            let mut walls_at_height: Vec<(usize, u16, usize, Instance)> = instances_at_height
+                .into_iter()
+                .flat_map(|(_, wall_id, curr_instances)| {
+                    curr_instances
+                        .into_iter()
+                        .enumerate()
+                        .map(move |(idx, (chunk, instance))| (chunk, wall_id, idx, instance))
+                })
+                .collect();
+            walls_at_height.sort_by_key(|(chunk, ..)| *chunk);
 
-                    if let Some(((x, y), frames)) =
-                        map.wall_toggle_tracker.get(&(wall_id, idx_in_wall_list))
-                    {
-                        let mut anim = AnimationInstanceData::new(frames.clone());
-                        anim.instances.push(InstanceReference {
+            for (chunk, wall_id, idx_in_wall_list, instance) in walls_at_height {
+                let instance_index = instances.len();
+
+                for anim in map.wall_animations.iter_mut() {
+                    if anim.contains_id(wall_id) {
+                        anim.data.instances.push(InstanceReference {
                             batch_index,
-                            instance_index,
+                            instance_index: instance_index,
                         });
+                    }
+                }
 
-                        // If the wall we found was the closed one, set frame to 1
-                        // We can check this by looking at the door pairs
-                        if door_pairs.iter().any(|p| p.closed_tile == wall_id) {
-                            anim.frame = 1;
-                        }
-
-                        map.wall_toggle_animations.insert((*x, *y), anim);
+                if let Some(((x, y), frames)) =
+                    map.wall_toggle_tracker.get(&(wall_id, idx_in_wall_list))
+                {
+                    let mut anim = AnimationInstanceData::new(frames.clone());
+                    anim.instances.push(InstanceReference {
+                        batch_index,
+                        instance_index,
+                    });
+
+                    // If the wall we found was the closed one, set frame to 1
+                    // We can check this by looking at the door pairs
+                    if door_pairs.iter().any(|p| p.closed_tile == wall_id) {
+                        anim.frame = 1;
                     }
 
-                    instances.push(instance);
+                    map.wall_toggle_animations.insert((*x, *y), anim);
+                }
+
+                if let Some(tile) = map.wall_tiles.get(&(wall_id, idx_in_wall_list)) {
+                    wall_instances
+                        .entry(*tile)
+                        .or_default()
+                        .push(InstanceReference {
+                            batch_index,
+                            instance_index,
+                        });
                 }
+
+                instances.push((chunk, instance));
             }
 
             let wall_bind_group = make_bind_group(
@@ -680,17 +758,18 @@ impl MapRenderer {
                 &texture::Texture::empty_view(device, "wall_empty"),
             );
 
-            instance_batches.push(InstanceBatch::new(
-                &device,
-                instances,
-                vertices,
-                wall_bind_group,
-            ));
+            instance_batches.push(ChunkedBatch::new(instances, vertices, wall_bind_group));
         }
 
         map.animations.extend(map.wall_animations);
 
-        MapRenderer::new(instance_batches, map.animations, map.wall_toggle_animations)
+        MapRenderer::new(
+            instance_batches,
+            map.animations,
+            map.wall_toggle_animations,
+            wall_instances,
+            texture_bytes,
+        )
     }
 
     fn make_texture_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
@@ -698,7 +777,7 @@ impl MapRenderer {
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Texture {
                         multisampled: false,
                         view_dimension: wgpu::TextureViewDimension::D2,
@@ -743,3 +822,42 @@ impl MapRenderer {
         })
     }
 }
+
+/// Moves an animated instance on to `frame`. A hidden wall stays hidden and
+/// keeps the frame's palette offset for when it's shown again.
+fn apply_frame(
+    batches: &mut [ChunkedBatch],
+    hidden_walls: &mut HashMap<(usize, usize), f32>,
+    queue: &wgpu::Queue,
+    instance_ref: &InstanceReference,
+    frame: &Instance,
+) {
+    let Some(batch) = batches.get_mut(instance_ref.batch_index) else {
+        return;
+    };
+    let Some(instance) = batch.get_instance(instance_ref.instance_index) else {
+        return;
+    };
+    let palette_offset =
+        match hidden_walls.get_mut(&(instance_ref.batch_index, instance_ref.instance_index)) {
+            Some(hidden) => {
+                *hidden = frame.palette_offset;
+                -1.
+            }
+            None => frame.palette_offset,
+        };
+    batch.update_instance(
+        queue,
+        instance_ref.instance_index,
+        Instance {
+            position: instance.position + frame.position,
+            tex_min: frame.tex_min,
+            tex_max: frame.tex_max,
+            sprite_size: frame.sprite_size,
+            palette_offset,
+            dye_v_offset: instance.dye_v_offset,
+            flags: instance.flags,
+            tint: instance.tint,
+        },
+    );
+}
@@ -1,3 +1,4 @@
+use crate::shaders;
 use crate::texture;
 use crate::{Camera, CameraUniform, Instance, InstanceRaw, Vertex};
 use glam::UVec2;
@@ -7,6 +8,7 @@ use wgpu::{self};
 // pub mod adapters;
 pub mod constants;
 pub mod creatures;
+pub mod depth;
 pub mod effects;
 pub mod items;
 pub mod map;
@@ -20,6 +22,7 @@ pub mod texture_bind;
 pub mod utils;
 
 pub use constants::*;
+pub use depth::{RenderLayer, tile_depth};
 pub use map::animations::{
     AnimationInstanceData, InstanceReference, WorldAnimation, WorldAnimationInstanceData,
 };
@@ -32,6 +35,8 @@ pub struct CameraState {
     size: UVec2,
     pub camera: Camera,
     camera_uniform: CameraUniform,
+    /// Whether the x-ray follows an explicit target instead of the camera center.
+    xray_tracked: bool,
     camera_buffer: wgpu::Buffer,
     pub camera_bind_group: wgpu::BindGroup,
     pub bind_group_layout: wgpu::BindGroupLayout,
@@ -78,6 +83,7 @@ impl CameraState {
             size,
             camera,
             camera_uniform,
+            xray_tracked: false,
             camera_buffer,
             camera_bind_group,
             bind_group_layout: camera_bind_group_layout,
@@ -117,6 +123,11 @@ impl CameraState {
         self.camera.position
     }
 
+    /// Values last uploaded to the camera buffer.
+    pub fn uniform(&self) -> &CameraUniform {
+        &self.camera_uniform
+    }
+
     pub fn set_position_world(&mut self, queue: &wgpu::Queue, x: f32, y: f32) {
         self.set_position(queue, x, y);
     }
@@ -137,8 +148,35 @@ impl CameraState {
         self.update(queue);
     }
 
+    /// Pins the x-ray to the sprite standing on `tile`, sized to its screen bounds.
+    pub fn set_xray_target(
+        &mut self,
+        queue: &wgpu::Queue,
+        tile: glam::Vec2,
+        bounds: Option<(glam::Vec2, glam::Vec2)>,
+    ) {
+        self.xray_tracked = true;
+        self.camera_uniform
+            .set_xray_target(get_isometric_coordinate(tile.x, tile.y), bounds);
+        self.update(queue);
+    }
+
+    pub fn set_xray_mode(&mut self, queue: &wgpu::Queue, occluding: bool) {
+        self.camera_uniform.xray_mode = occluding as u32;
+        self.update(queue);
+    }
+
+    pub fn set_highlight(&mut self, queue: &wgpu::Queue, highlight: bool) {
+        self.camera_uniform.highlight = if highlight { 1.0 } else { 0.0 };
+        self.update(queue);
+    }
+
     fn update(&mut self, queue: &wgpu::Queue) {
         self.camera_uniform.update_view_proj(&self.camera);
+        if !self.xray_tracked {
+            self.camera_uniform
+                .set_xray_target(self.camera.position, None);
+        }
         queue.write_buffer(
             &self.camera_buffer,
             0,
@@ -149,6 +187,8 @@ impl CameraState {
 
 pub struct Scene {
     pub pipeline: wgpu::RenderPipeline,
+    pipeline_layout: wgpu::PipelineLayout,
+    texture_format: wgpu::TextureFormat,
     pub depth_texture: texture::Texture,
     pub depth_bind_group_layout: wgpu::BindGroupLayout,
     pub depth_bind_group: wgpu::BindGroup,
@@ -166,7 +206,7 @@ impl Scene {
                 entries: &[
                     wgpu::BindGroupLayoutEntry {
                         binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
                         ty: wgpu::BindingType::Texture {
                             multisampled: false,
                             view_dimension: wgpu::TextureViewDimension::D2,
@@ -240,11 +280,6 @@ impl Scene {
                 label: Some("depth_bind_group_layout"),
             });
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/shader.wgsl").into()),
-        });
-
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
@@ -272,10 +307,38 @@ impl Scene {
             label: Some("depth_bind_group"),
         });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let render_pipeline = Self::create_pipeline(
+            device,
+            &render_pipeline_layout,
+            texture_format,
+            shaders::embedded(shaders::SCENE),
+        );
+
+        Self {
+            pipeline: render_pipeline,
+            pipeline_layout: render_pipeline_layout,
+            texture_format,
+            depth_texture,
+            depth_bind_group_layout,
+            depth_bind_group,
+        }
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        texture_format: wgpu::TextureFormat,
+        source: &str,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             cache: None,
             label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
+            layout: Some(layout),
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: Some("vs_main"),
@@ -305,14 +368,13 @@ impl Scene {
             }),
             multisample: wgpu::MultisampleState::default(),
             multiview_mask: None,
-        });
+        })
+    }
 
-        Self {
-            pipeline: render_pipeline,
-            depth_texture,
-            depth_bind_group_layout,
-            depth_bind_group,
-        }
+    /// Rebuilds the sprite pipeline from new shader source, see [`crate::shaders`].
+    pub fn reload_pipeline(&mut self, device: &wgpu::Device, source: &str) {
+        self.pipeline =
+            Self::create_pipeline(device, &self.pipeline_layout, self.texture_format, source);
     }
 
     pub fn resize_depth_texture(&mut self, device: &wgpu::Device, width: u32, height: u32) {
@@ -13,11 +13,12 @@ use tracing::error;
 use wgpu;
 
 use crate::instance::InstanceFlag;
-use crate::scene::utils::calculate_tile_z;
+use crate::scene::depth::{RenderLayer, tile_depth};
 use crate::{SharedInstanceBatch, make_quad};
 use crate::{
     scene::{
-        Instance, TILE_WIDTH_HALF, get_isometric_coordinate, texture_atlas::TextureAtlas,
+        Instance, TILE_WIDTH_HALF, get_isometric_coordinate,
+        texture_atlas::{AtlasStats, TextureAtlas},
         texture_bind::TextureBind,
     },
     texture,
@@ -49,9 +50,15 @@ const PLAYER_STACK_Z_RANGE: f32 = 0.003;
 pub struct PlayerBatch {
     instances: SharedInstanceBatch,
     handles: std::sync::Mutex<FxHashMap<usize, PlayerSpriteKey>>,
+    /// Screen-space bounds of every visible piece, used to fit the x-ray cutout.
+    bounds: std::sync::Mutex<FxHashMap<usize, (Vec2, Vec2)>>,
 }
 
 impl PlayerAssetStore {
+    pub fn atlas_stats(&self) -> AtlasStats {
+        self.atlas.stats()
+    }
+
     pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, archive: &Archive) -> Self {
         let diffuse_texture = texture::Texture::from_data(
             device,
@@ -156,12 +163,13 @@ impl PlayerAssetStore {
             iso_coord_offset = Vec2::new(1., -1.);
         }
 
-        let z = calculate_tile_z(
+        let z = tile_depth(
             position.x,
             position.y,
-            // Player Z range is 0.1 to 0.2, with stack_order adding a small offset
+            RenderLayer::Player,
+            // Pieces by priority, with stack_order adding a small offset
             // to separate multiple players on the same tile
-            0.1 + (sprite.slot.z_priority(is_towards) * 0.1)
+            (sprite.slot.z_priority(is_towards) * 0.1)
                 + (stack_order as f32 / PLAYERS_PER_TILE as f32) * PLAYER_STACK_Z_RANGE,
         );
 
@@ -280,6 +288,10 @@ impl PlayerAssetStore {
 }
 
 impl PlayerBatch {
+    pub fn instances(&self) -> &SharedInstanceBatch {
+        &self.instances
+    }
+
     pub fn new(device: &wgpu::Device, store: &PlayerAssetStore) -> Self {
         let vertices = make_quad(VERTEX_WIDTH as u32, VERTEX_HEIGHT as u32).to_vec();
         let instances = SharedInstanceBatch::new(device, vertices, store.bind_group.clone());
@@ -287,6 +299,7 @@ impl PlayerBatch {
         Self {
             instances,
             handles: std::sync::Mutex::new(FxHashMap::default()),
+            bounds: std::sync::Mutex::new(FxHashMap::default()),
         }
     }
 
@@ -297,6 +310,7 @@ impl PlayerBatch {
     pub fn clear(&self) {
         self.instances.clear();
         self.handles.lock().unwrap().clear();
+        self.bounds.lock().unwrap().clear();
     }
 
     pub fn clear_and_unload(&self, store: &mut PlayerAssetStore) {
@@ -306,6 +320,7 @@ impl PlayerBatch {
         }
         handles.clear();
         self.instances.clear();
+        self.bounds.lock().unwrap().clear();
     }
 
     pub fn add_player_sprite(
@@ -368,8 +383,9 @@ impl PlayerBatch {
 
         let instance_index = self
             .instances
-            .add(queue, instance)
+            .add(queue, instance.clone())
             .expect("Failed to add instance to batch");
+        self.record_bounds_for(instance_index, &instance);
 
         let handle = PlayerSpriteHandle {
             key: sprite,
@@ -418,6 +434,7 @@ impl PlayerBatch {
             tint,
             handle.stack_order,
         )?;
+        self.record_bounds_for(handle.index.0, &instance);
         self.instances.update(queue, handle.index.0, instance);
 
         Ok(())
@@ -476,6 +493,7 @@ impl PlayerBatch {
         )
         .unwrap_or_default();
 
+        self.record_bounds_for(handle.index.0, &instance);
         self.instances.update(queue, handle.index.0, instance);
         Ok(())
     }
@@ -487,6 +505,7 @@ impl PlayerBatch {
     ) -> anyhow::Result<()> {
         self.instances
             .update(queue, handle.index.0, Instance::default());
+        self.bounds.lock().unwrap().remove(&handle.index.0);
         Ok(())
     }
 
@@ -500,6 +519,31 @@ impl PlayerBatch {
         store.unload_sprite(handle.key);
 
         self.handles.lock().unwrap().remove(&handle.index.0);
+        self.bounds.lock().unwrap().remove(&handle.index.0);
+    }
+
+    /// Union of the on-screen bounds of the given pieces, skipping hidden ones.
+    pub fn sprite_bounds<'a>(
+        &self,
+        handles: impl IntoIterator<Item = &'a PlayerSpriteHandle>,
+    ) -> Option<(Vec2, Vec2)> {
+        let bounds = self.bounds.lock().unwrap();
+        handles
+            .into_iter()
+            .filter_map(|handle| bounds.get(&handle.index.0))
+            .copied()
+            .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+    }
+
+    fn record_bounds_for(&self, index: usize, instance: &Instance) {
+        let mut bounds = self.bounds.lock().unwrap();
+        if instance.palette_offset < 0.0 || instance.sprite_size == Vec2::ZERO {
+            bounds.remove(&index);
+            return;
+        }
+        let min = instance.position.truncate();
+        let size = instance.sprite_size * Vec2::new(VERTEX_WIDTH as f32, VERTEX_HEIGHT as f32);
+        bounds.insert(index, (min, min + size));
     }
 
     pub fn render(&self, render_pass: &mut wgpu::RenderPass) {
@@ -126,7 +126,7 @@ impl PlayerPalettes {
                 "dye_palette",
                 256,
                 total_h,
-                wgpu::TextureFormat::Rgba8Unorm,
+                wgpu::TextureFormat::Rgba8UnormSrgb,
                 &data,
             )
             .unwrap(),
@@ -6,6 +6,41 @@ pub struct TextureAtlas {
     bytes_per_pixel: u32,
 }
 
+/// Space usage of an atlas, in texels.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AtlasStats {
+    pub used: u64,
+    pub total: u64,
+    pub bytes_per_texel: u64,
+}
+
+impl AtlasStats {
+    pub fn from_allocator(atlas: &etagere::AtlasAllocator, bytes_per_texel: u32) -> Self {
+        let size = atlas.size();
+        Self {
+            used: atlas.allocated_space().max(0) as u64,
+            total: (size.width.max(0) as u64) * (size.height.max(0) as u64),
+            bytes_per_texel: bytes_per_texel as u64,
+        }
+    }
+
+    pub fn used_bytes(&self) -> u64 {
+        self.used * self.bytes_per_texel
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.total * self.bytes_per_texel
+    }
+
+    pub fn percent_used(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used as f32 * 100.0 / self.total as f32
+        }
+    }
+}
+
 pub struct FrameUpload<'a> {
     pub width: usize,
     pub height: usize,
@@ -65,7 +100,16 @@ impl TextureAtlas {
         }
     }
 
+    /// Width and height of the atlas texture, in texels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+
     pub fn deallocate(&mut self, id: AllocId) {
         self.atlas.deallocate(id);
     }
+
+    pub fn stats(&self) -> AtlasStats {
+        AtlasStats::from_allocator(&self.atlas, self.bytes_per_pixel)
+    }
 }
@@ -0,0 +1,89 @@
+//! WGSL sources for the render pipelines. With the `shader-hot-reload` feature the
+//! files under `src/shaders` can be re-read at runtime and the pipelines rebuilt.
+
+pub const SCENE: &str = "shader.wgsl";
+pub const EFFECT: &str = "effect.wgsl";
+pub const COMPOSITE: &str = "composite.wgsl";
+
+pub const ALL: [&str; 3] = [SCENE, EFFECT, COMPOSITE];
+
+/// The source compiled into the binary.
+pub fn embedded(name: &str) -> &'static str {
+    match name {
+        SCENE => include_str!("shaders/shader.wgsl"),
+        EFFECT => include_str!("shaders/effect.wgsl"),
+        COMPOSITE => include_str!("shaders/composite.wgsl"),
+        _ => panic!("unknown shader {name}"),
+    }
+}
+
+#[cfg(feature = "shader-hot-reload")]
+pub use hot_reload::{ShaderWatcher, load};
+
+#[cfg(feature = "shader-hot-reload")]
+mod hot_reload {
+    use std::path::PathBuf;
+    use std::time::SystemTime;
+
+    fn path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/shaders")
+            .join(name)
+    }
+
+    /// Reads a shader from the source tree and validates it, so a typo is reported
+    /// instead of taking the device down when the pipeline is created.
+    pub fn load(name: &str) -> anyhow::Result<String> {
+        let source = std::fs::read_to_string(path(name))?;
+        let module = wgpu::naga::front::wgsl::parse_str(&source)
+            .map_err(|e| anyhow::anyhow!(e.emit_to_string(&source)))?;
+        wgpu::naga::valid::Validator::new(
+            wgpu::naga::valid::ValidationFlags::all(),
+            wgpu::naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|e| anyhow::anyhow!(e.emit_to_string(&source)))?;
+        Ok(source)
+    }
+
+    /// Polls the shader files for modification.
+    pub struct ShaderWatcher {
+        modified: Vec<(&'static str, Option<SystemTime>)>,
+    }
+
+    impl ShaderWatcher {
+        pub fn new() -> Self {
+            Self {
+                modified: super::ALL
+                    .iter()
+                    .map(|name| (*name, Self::modified_time(name)))
+                    .collect(),
+            }
+        }
+
+        fn modified_time(name: &str) -> Option<SystemTime> {
+            std::fs::metadata(path(name))
+                .and_then(|m| m.modified())
+                .ok()
+        }
+
+        /// Shaders whose file changed since the last call.
+        pub fn changed(&mut self) -> Vec<&'static str> {
+            let mut changed = Vec::new();
+            for (name, last) in &mut self.modified {
+                let current = Self::modified_time(name);
+                if current != *last {
+                    *last = current;
+                    changed.push(*name);
+                }
+            }
+            changed
+        }
+    }
+
+    impl Default for ShaderWatcher {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
@@ -0,0 +1,46 @@
+struct Grade {
+    gamma: f32,
+    fade: f32,
+    _padding: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var t_frame: texture_2d<f32>;
+@group(0) @binding(1)
+var s_frame: sampler;
+@group(0) @binding(2)
+var<uniform> grade: Grade;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+// Single triangle covering the whole target
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.tex_coords = uv;
+    return out;
+}
+
+// Sharp bilinear: each source pixel stays a flat block, and only the seams
+// between pixels are blended across a single display pixel. At 1:1 this is a
+// plain copy, so the pass also serves to apply gamma and fades alone.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(t_frame));
+    let texel = in.tex_coords * size;
+    let scale = max(1.0 / fwidth(texel), vec2<f32>(1.0));
+
+    let center_dist = fract(texel) - 0.5;
+    let region = 0.5 - 0.5 / scale;
+    let offset = (center_dist - clamp(center_dist, -region, region)) * scale + 0.5;
+
+    let color = textureSampleLevel(t_frame, s_frame, (floor(texel) + offset) / size, 0.0);
+    let graded = pow(max(color.rgb, vec3<f32>(0.0)), vec3<f32>(1.0 / grade.gamma));
+    return vec4<f32>(graded * grade.fade, color.a);
+}
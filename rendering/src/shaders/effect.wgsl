@@ -2,7 +2,12 @@ struct Camera {
     view_proj: mat4x4<f32>,
     position: vec2<f32>,
     xray_size: f32,
+    highlight: f32,
     tint: vec3<f32>,
+    xray_anchor: vec2<f32>,
+    xray_min: vec2<f32>,
+    xray_max: vec2<f32>,
+    xray_mode: u32,
 }
 @group(1) @binding(0)
 var<uniform> camera: Camera;
@@ -2,7 +2,12 @@ struct Camera {
     view_proj: mat4x4<f32>,
     position: vec2<f32>,
     xray_size: f32,
+    highlight: f32,
     tint: vec3<f32>,
+    xray_anchor: vec2<f32>,
+    xray_min: vec2<f32>,
+    xray_max: vec2<f32>,
+    xray_mode: u32,
 }
 @group(1) @binding(0)
 var<uniform> camera: Camera;
@@ -33,19 +38,41 @@ struct VertexOutput {
     @location(6) local_y: f32,
     @location(7) normalized_y: f32,
     @location(8) tint: vec3<f32>,
+    @location(9) tex_rect: vec4<f32>,
+    @location(10) outline_depth: f32,
 }
 
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @builtin(frag_depth) depth: f32,
+}
+
+// Hover (2) and target (8) flags draw a one pixel outline around the sprite
+const OUTLINE_FLAGS: u32 = 10u;
+
 @vertex
 fn vs_main(
     model: VertexInput,
     instance: InstanceInput,
 ) -> VertexOutput {
-    let scaled_position = model.position * instance.sprite_size;
+    // Outlined sprites grow by a pixel on each side to make room for the outline
+    var grow = vec2<f32>(0.0);
+    if (instance.flags & OUTLINE_FLAGS) != 0u {
+        grow = model.tex_coords * 2.0 - 1.0;
+    }
+    let scaled_position = model.position * instance.sprite_size + grow;
     let position = vec3<f32>(scaled_position, 0.0) + instance.position;
+    let texel = sign(instance.tex_max - instance.tex_min) / vec2<f32>(textureDimensions(t_diffuse));
 
     var out: VertexOutput;
-    out.tex_coords = mix(instance.tex_min, instance.tex_max, model.tex_coords);
+    out.tex_coords = mix(instance.tex_min, instance.tex_max, model.tex_coords) + grow * texel;
     out.clip_position = camera.view_proj * vec4<f32>(position, 1.0);
+    out.tex_rect = vec4<f32>(
+        min(instance.tex_min, instance.tex_max),
+        max(instance.tex_min, instance.tex_max),
+    );
+    // Just behind every other piece of the same entity, so layered player pieces don't outline each other
+    out.outline_depth = (camera.view_proj * vec4<f32>(position.xy, position.z - 0.0001, 1.0)).z;
     out.palette_offset = instance.palette_offset;
     out.dye_v_offset = instance.dye_v_offset;
     out.world_position = position.xy;
@@ -71,13 +98,59 @@ var t_dye_palette: texture_2d<f32>;
 @group(2) @binding(0)
 var t_depth: texture_depth_2d;
 
+// Palette index at a texel, treating anything outside the sprite's atlas rect as empty.
+fn sprite_texel(texel: vec2<i32>, rect_min: vec2<i32>, rect_max: vec2<i32>) -> f32 {
+    if any(texel < rect_min) || any(texel >= rect_max) {
+        return 0.0;
+    }
+    return textureLoad(t_diffuse, texel, 0).r;
+}
+
+// The frame targets are plain Rgba8Unorm, so encode to sRGB by hand
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let c = max(color, vec3<f32>(0.0));
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3<f32>(0.0031308));
+}
+
 @fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+fn fs_main(in: VertexOutput) -> FragmentOutput {
     if in.palette_offset < 0.0 {
         discard; 
     }
-    
-    let color_index = textureSampleBaseClampToEdge(t_diffuse, s_diffuse, in.tex_coords).r;
+
+    var out: FragmentOutput;
+    out.depth = in.clip_position.z;
+
+    var color_index = 0.0;
+    if (in.flags & OUTLINE_FLAGS) != 0u {
+        let dims = vec2<f32>(textureDimensions(t_diffuse));
+        let texel = vec2<i32>(floor(in.tex_coords * dims));
+        let rect_min = vec2<i32>(round(in.tex_rect.xy * dims));
+        let rect_max = vec2<i32>(round(in.tex_rect.zw * dims));
+        color_index = sprite_texel(texel, rect_min, rect_max);
+
+        if color_index == 0 {
+            let neighbours = sprite_texel(texel + vec2<i32>(1, 0), rect_min, rect_max)
+                + sprite_texel(texel - vec2<i32>(1, 0), rect_min, rect_max)
+                + sprite_texel(texel + vec2<i32>(0, 1), rect_min, rect_max)
+                + sprite_texel(texel - vec2<i32>(0, 1), rect_min, rect_max);
+            if neighbours == 0.0 {
+                discard;
+            }
+
+            var outline = vec3<f32>(0.95, 0.85, 0.45);
+            if (in.flags & 8u) != 0u {
+                outline = vec3<f32>(0.45, 0.7, 1.0);
+            }
+            out.color = vec4<f32>(outline, 1.0);
+            out.depth = in.outline_depth;
+            return out;
+        }
+    } else {
+        color_index = textureSampleBaseClampToEdge(t_diffuse, s_diffuse, in.tex_coords).r;
+    }
     if color_index == 0 {
         discard;
     }
@@ -106,9 +179,9 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
 
     // X-Ray effect for local player (only when xray_size > 0)
     if (in.flags & 1u) != 0u && camera.xray_size > 0.0 {
-        // Use screen coords to calculate player position
-        let a = camera.position.x / 28.0;
-        let b = camera.position.y / 14.0;
+        // Use the tracked sprite's tile to decide which walls stand in front of it
+        let a = camera.xray_anchor.x / 28.0;
+        let b = camera.xray_anchor.y / 14.0;
         let player_tile_x = (a + b) * 0.5;
         let player_tile_y = (b - a) * 0.5;
         let z_to_check = (player_tile_x
@@ -127,11 +200,11 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
             // Fade out X-ray over the bottom 20 pixels (from 10px to 30px)
             let bottom_fade = smoothstep(10.0, 30.0, dist_from_bottom);
 
-            let xray_pos = delta - vec2<f32>(0.0, -20.0);
-            // Scale ellipse dimensions by xray_size (base: 20px width, 30px height)
-            let scaled_width = 20.0 * camera.xray_size;
-            let scaled_height = 30.0 * camera.xray_size;
-            let xray_dist = length(vec2<f32>(xray_pos.x / scaled_width, xray_pos.y / scaled_height));
+            // Ellipse fitted to the sprite bounds, scaled by xray_size
+            let xray_center = (camera.xray_min + camera.xray_max) * 0.5;
+            let xray_radii = max((camera.xray_max - camera.xray_min) * 0.5, vec2<f32>(1.0)) * camera.xray_size;
+            let xray_pos = in.world_position - xray_center;
+            let xray_dist = length(xray_pos / xray_radii);
 
             // Bayer 8x8 ordered dither for smooth transparency
             let bayer_8x8 = array<f32, 64>(
@@ -148,7 +221,17 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
             let bayer_idx = (coord.y % 8u) * 8u + (coord.x % 8u);
             let threshold = bayer_8x8[bayer_idx] / 64.0;
 
-            let opacity = mix(1.0, smoothstep(0.7, 1.4, xray_dist), bottom_fade);
+            var cutout = smoothstep(0.7, 1.4, xray_dist);
+            if camera.xray_mode == 1u {
+                // Only fade the pixels that actually cover the sprite
+                let pad = 4.0 * camera.xray_size;
+                let inside = min(
+                    in.world_position - (camera.xray_min - vec2<f32>(pad)),
+                    (camera.xray_max + vec2<f32>(pad)) - in.world_position,
+                );
+                cutout = 1.0 - smoothstep(-4.0, 4.0, min(inside.x, inside.y));
+            }
+            let opacity = mix(1.0, cutout, bottom_fade);
 
             if opacity < threshold {
                 discard;
@@ -160,10 +243,17 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let outer_radius = 13.0;
     let factor = smoothstep(inner_radius, outer_radius, dist);
 
-    // Convert to grayscale
-    let gray = dot(final_color.rgb, vec3<f32>(0.299, 0.587, 0.114));
-    // Mix between full color and slightly dimmed grayscale
-    var out_rgb = mix(final_color.rgb, vec3<f32>(gray) * 0.5, factor);
+    // Palettes sample as linear colour, so blend in linear light and encode afterwards
+    let gray = dot(final_color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    // Mix between full color and slightly dimmed grayscale (0.218 is half brightness once encoded)
+    var out_rgb = linear_to_srgb(mix(final_color.rgb, vec3<f32>(gray) * 0.218, factor));
 
-    return vec4<f32>(out_rgb.rgb + camera.tint + in.tint, final_color.a);
+    // Doors and map exits glow when close, or everywhere while the overlay key is held
+    if (in.flags & 4u) != 0u {
+        let glow = max(1.0 - smoothstep(3.0, 5.0, dist), camera.highlight);
+        out_rgb += vec3<f32>(0.16, 0.13, 0.05) * glow;
+    }
+
+    out.color = vec4<f32>(out_rgb.rgb + camera.tint + in.tint, final_color.a);
+    return out;
 }
\ No newline at end of file
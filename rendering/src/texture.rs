@@ -8,6 +8,14 @@ pub struct Texture {
     pub sampler: wgpu::Sampler,
 }
 
+/// Bytes a texture's first mip level takes on the GPU, not counting any
+/// padding the driver adds.
+pub fn texture_bytes(texture: &wgpu::Texture) -> u64 {
+    let size = texture.size();
+    let texel = texture.format().block_copy_size(None).unwrap_or_default() as u64;
+    size.width as u64 * size.height as u64 * size.depth_or_array_layers as u64 * texel
+}
+
 impl Texture {
     pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
@@ -39,6 +47,7 @@ impl Texture {
         )
     }
 
+    /// Loads a palette; the colours are stored as sRGB, so samples come back linear.
     pub fn from_ktx2_rgba8(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
@@ -55,7 +64,7 @@ impl Texture {
             label,
             info.pixel_width,
             info.pixel_height,
-            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
             &buf.data,
         )
     }
@@ -4,7 +4,7 @@ use crate::ecs::components::InGameScoped;
 use crate::ecs::hotbar::{HotbarPanelState, HotbarState};
 use crate::events::MapEvent;
 use crate::game_files::GameFiles;
-use crate::resources::PlayerAttributes;
+use crate::resources::{MapPreload, MapTransition, PlayerAttributes};
 use crate::session::runtime::{NetBgTask, NetEventRx, NetSessionState};
 use crate::slint_support::assets::SlintAssetLoader;
 use crate::slint_support::state_bridge::SlintAssetLoaderRes;
@@ -88,6 +88,8 @@ pub fn cleanup_ingame_resources(
     menu_ctx: Option<ResMut<ActiveMenuContext>>,
     session: Option<ResMut<NetSessionState>>,
     outbox: Option<ResMut<PacketOutbox>>,
+    map_preload: Option<ResMut<MapPreload>>,
+    map_transition: Option<ResMut<MapTransition>>,
 ) {
     for e in net_tasks.iter() {
         commands.entity(e).despawn();
@@ -133,4 +135,10 @@ pub fn cleanup_ingame_resources(
     if let Some(mut state) = outbox {
         *state = PacketOutbox::default();
     }
+    if let Some(mut state) = map_preload {
+        *state = MapPreload::default();
+    }
+    if let Some(mut state) = map_transition {
+        *state = MapTransition::default();
+    }
 }
@@ -1,38 +1,156 @@
 use bevy::platform::collections::HashMap;
 use bevy::prelude::*;
-use kira::sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings};
+use bevy::tasks::{IoTaskPool, Task};
+use cpal::traits::{DeviceTrait, HostTrait};
+use formats::mus::MusFile;
+use futures_lite::future;
+use game_ui::CoreToUi;
+use kira::backend::cpal::CpalBackendSettings;
+use kira::sound::FromFileError;
+use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
+use kira::sound::streaming::{StreamingSoundData, StreamingSoundHandle};
 use kira::track::{TrackBuilder, TrackHandle};
-use kira::{AudioManager, Decibels, Tween};
+use kira::{AudioManager, AudioManagerSettings, Decibels, Tween};
 use std::io::Cursor;
 use std::time::Duration;
-use tracing::error;
+use tracing::{debug, error, info};
 
-use crate::{events::AudioEvent, game_files};
+use crate::ecs::components::{EntityId, LocalPlayer, Position};
+use crate::events::{AudioEvent, SoundOrigin};
+use crate::game_files;
+use crate::input::{GameAction, GamepadConfig, UnifiedInputBindings};
+use crate::settings_types::SoundCategory;
+use crate::webui::plugin::UiOutbound;
+
+/// Owns the audio output and the systems that follow device and mute changes.
+/// Sound playback itself runs with the game systems.
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Audio::default()).add_systems(
+            Update,
+            (
+                refresh_audio_device,
+                toggle_mute.run_if(in_state(crate::app_state::AppState::InGame)),
+            ),
+        );
+    }
+}
+
+/// How often output devices are checked for changes.
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// How long the previous output keeps playing after a device switch, so
+/// effects already under way can finish.
+const SFX_TAIL: Duration = Duration::from_secs(4);
+
+/// Output devices as seen by a background scan.
+struct DeviceScan {
+    names: Vec<String>,
+    default: Option<String>,
+}
+
+/// The output replaced by a device switch, kept until its effects end.
+struct RetiredOutput {
+    _manager: AudioManager,
+    _sfx_track: TrackHandle,
+    linger: Timer,
+}
 
 #[derive(Resource)]
 pub struct Audio {
-    _manager: AudioManager,
+    manager: AudioManager,
     music_track: TrackHandle,
     sfx_track: TrackHandle,
     cache: HashMap<String, StaticSoundData>,
-    music: Option<StaticSoundHandle>,
+    /// Music is decoded as it plays rather than cached, tracks run for minutes.
+    music: Option<StreamingSoundHandle<FromFileError>>,
+    /// Path of the playing music, so it can resume after switching devices.
+    music_path: Option<String>,
+    /// Device the user picked, `None` to follow the system default.
+    requested_device: Option<String>,
+    /// Name of the device the manager is currently playing through.
+    active_device: Option<String>,
+    /// Output device names as last reported to the UI.
+    devices: Vec<String>,
+    device_poll: Timer,
+    /// Device enumeration in flight; it can block for a while on some backends.
+    device_scan: Option<Task<DeviceScan>>,
+    retired: Option<RetiredOutput>,
+    muted: bool,
 }
 
 impl Default for Audio {
     fn default() -> Self {
-        let mut manager = AudioManager::new(Default::default()).unwrap();
-        let music_track = manager.add_sub_track(TrackBuilder::default()).unwrap();
-        let sfx_track = manager.add_sub_track(TrackBuilder::default()).unwrap();
+        let (manager, music_track, sfx_track) = open_output(None).unwrap();
+        let mut device_poll = Timer::new(DEVICE_POLL_INTERVAL, TimerMode::Repeating);
+        // Poll on the first frame so the device list reaches the UI right away.
+        device_poll.set_elapsed(DEVICE_POLL_INTERVAL);
         Self {
-            _manager: manager,
+            manager,
             music_track,
             sfx_track,
             cache: Default::default(),
             music: Default::default(),
+            music_path: None,
+            requested_device: None,
+            active_device: default_output_device_name(),
+            devices: Vec::new(),
+            device_poll,
+            device_scan: None,
+            retired: None,
+            muted: false,
         }
     }
 }
 
+/// Starts an audio manager on `device`, or on the system default when `None`.
+fn open_output(
+    device: Option<cpal::Device>,
+) -> anyhow::Result<(AudioManager, TrackHandle, TrackHandle)> {
+    let mut manager = AudioManager::new(AudioManagerSettings {
+        backend_settings: CpalBackendSettings {
+            device,
+            config: None,
+        },
+        ..Default::default()
+    })
+    .map_err(|e| anyhow::anyhow!("Failed to open audio output: {:?}", e))?;
+    let music_track = manager.add_sub_track(TrackBuilder::default())?;
+    let sfx_track = manager.add_sub_track(TrackBuilder::default())?;
+    Ok((manager, music_track, sfx_track))
+}
+
+fn device_name(device: &cpal::Device) -> Option<String> {
+    device.description().ok().map(|d| d.name().to_string())
+}
+
+fn default_output_device_name() -> Option<String> {
+    cpal::default_host()
+        .default_output_device()
+        .as_ref()
+        .and_then(device_name)
+}
+
+fn output_devices() -> Vec<(String, cpal::Device)> {
+    match cpal::default_host().output_devices() {
+        Ok(devices) => devices
+            .filter_map(|device| device_name(&device).map(|name| (name, device)))
+            .collect(),
+        Err(e) => {
+            error!("Failed to list audio output devices: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn scan_devices() -> DeviceScan {
+    DeviceScan {
+        names: output_devices().into_iter().map(|(name, _)| name).collect(),
+        default: default_output_device_name(),
+    }
+}
+
 impl Audio {
     pub fn get_or_load_from_fn<F>(&mut self, id: &str, f: F) -> anyhow::Result<StaticSoundData>
     where
@@ -47,12 +165,147 @@ impl Audio {
         }
         Ok(self.cache.get(id).unwrap().clone())
     }
+
+    fn play_sfx(&mut self, files: &game_files::GameFiles, id: u8, volume: f32) {
+        let path = &format!("Legend/{}.mp3", id);
+        let Ok(data) = self.get_or_load_from_fn(path, || files.get_file(path)) else {
+            error!("Failed to load sound {}", id);
+            return;
+        };
+
+        let settings = StaticSoundSettings::new().volume(amplitude_to_db(volume));
+        if self.sfx_track.play(data.with_settings(settings)).is_err() {
+            error!("Failed to play sound {}", id);
+        }
+    }
+
+    fn play_music(&mut self, files: &game_files::GameFiles, path: &str) {
+        let data = match load_music(files, path) {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Failed to load music {}: {}", path, e);
+                return;
+            }
+        };
+
+        if let Some(mut handle) = self.music.take() {
+            handle.stop(Tween {
+                duration: Duration::from_millis(500),
+                ..Default::default()
+            });
+        }
+
+        match self.music_track.play(data.loop_region(0.0..)) {
+            Ok(handle) => {
+                self.music = Some(handle);
+                self.music_path = Some(path.to_string());
+            }
+            _ => error!("Failed to play music {}", path),
+        };
+    }
+
+    fn apply_volumes(&mut self, settings: &crate::settings_types::AudioSettings) {
+        let _ = self
+            .music_track
+            .set_volume(amplitude_to_db(settings.music_volume), Tween::default());
+        let _ = self
+            .sfx_track
+            .set_volume(amplitude_to_db(settings.sfx_volume), Tween::default());
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Silences every track at once without touching the saved volumes.
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        let volume = if muted {
+            Decibels::SILENCE
+        } else {
+            Decibels::IDENTITY
+        };
+        self.manager
+            .main_track()
+            .set_volume(volume, Tween::default());
+    }
+
+    /// Reopens the output on `device` and picks playback back up where it was.
+    fn switch_device(
+        &mut self,
+        name: Option<String>,
+        device: Option<cpal::Device>,
+        settings: &crate::settings_types::AudioSettings,
+        files: Option<&game_files::GameFiles>,
+    ) {
+        let (manager, music_track, sfx_track) = match open_output(device) {
+            Ok(output) => output,
+            Err(e) => {
+                error!("{}", e);
+                return;
+            }
+        };
+        info!(
+            "Audio output switched to {}",
+            name.as_deref().unwrap_or("unknown device")
+        );
+
+        if let Some(mut handle) = self.music.take() {
+            handle.stop(Tween::default());
+        }
+        let previous_manager = std::mem::replace(&mut self.manager, manager);
+        let previous_sfx = std::mem::replace(&mut self.sfx_track, sfx_track);
+        self.retired = Some(RetiredOutput {
+            _manager: previous_manager,
+            _sfx_track: previous_sfx,
+            linger: Timer::new(SFX_TAIL, TimerMode::Once),
+        });
+        self.music_track = music_track;
+        self.active_device = name;
+        self.apply_volumes(settings);
+        self.set_muted(self.muted);
+
+        if let (Some(path), Some(files)) = (self.music_path.take(), files) {
+            self.play_music(files, &path);
+        }
+    }
+}
+
+/// Reads a `.mus` track from the archive, skipping anything in front of the
+/// audio that would keep the decoder from recognising it.
+fn load_music(
+    files: &game_files::GameFiles,
+    path: &str,
+) -> anyhow::Result<StreamingSoundData<FromFileError>> {
+    let mut bytes = files
+        .get_file(path)
+        .ok_or_else(|| anyhow::anyhow!("not in the archive"))?;
+    let mus = MusFile::read(&bytes)?;
+    debug!(
+        "Music {}: {} Hz, {} channels, {} kbit/s, {:?}",
+        path, mus.sample_rate, mus.channels, mus.bitrate, mus.duration
+    );
+    let audio = bytes.split_off(mus.audio_offset);
+    Ok(StreamingSoundData::from_cursor(Cursor::new(audio))?)
+}
+
+/// Tiles within which effects play at full volume.
+const FULL_VOLUME_DISTANCE: f32 = 3.0;
+/// Tiles beyond which effects are inaudible.
+const SILENT_DISTANCE: f32 = 16.0;
+
+/// Volume multiplier for an effect `distance` tiles away from the player.
+pub fn distance_attenuation(distance: f32) -> f32 {
+    ((SILENT_DISTANCE - distance) / (SILENT_DISTANCE - FULL_VOLUME_DISTANCE)).clamp(0.0, 1.0)
 }
 
 pub fn play_sound(
     mut audio_events: MessageReader<AudioEvent>,
     audio: Option<ResMut<Audio>>,
     files: Res<game_files::GameFiles>,
+    settings: Res<crate::settings::Settings>,
+    local_player: Query<&Position, With<LocalPlayer>>,
+    entities: Query<(&EntityId, &Position)>,
 ) {
     let mut audio = match audio {
         Some(audio) => audio,
@@ -63,48 +316,39 @@ pub fn play_sound(
         match event {
             AudioEvent::PlaySound(sound) => match *sound {
                 packets::server::Sound::Sound(id) => {
-                    let path = &format!("Legend/{}.mp3", id);
-                    let data = audio.get_or_load_from_fn(path, || files.get_file(path));
-
-                    if let Ok(data) = data {
-                        if audio
-                            .sfx_track
-                            .play(data.with_settings(StaticSoundSettings::new()))
-                            .is_err()
-                        {
-                            error!("Failed to play sound {:?}", sound);
-                        }
-                    } else {
-                        error!("Failed to load sound {:?}", sound);
-                        continue;
-                    }
+                    let volume = settings.audio.category_volume(SoundCategory::Ambient);
+                    audio.play_sfx(&files, id, volume);
                 }
                 packets::server::Sound::Music(id) => {
-                    let path = &format!("music/{}.mus", id);
-                    let data = audio.get_or_load_from_fn(path, || files.get_file(path));
-
-                    if let Ok(data) = data {
-                        if let Some(mut handle) = audio.music.take() {
-                            handle.stop(Tween {
-                                duration: Duration::from_millis(500),
-                                ..Default::default()
-                            });
-                        }
-
-                        match audio.music_track.play(
-                            data.with_settings(StaticSoundSettings::new().loop_region(0.0..)),
-                        ) {
-                            Ok(handle) => {
-                                audio.music = Some(handle);
-                            }
-                            _ => error!("Failed to play sound {:?}", sound),
-                        };
-                    } else {
-                        error!("Failed to load music {:?}", sound);
-                        continue;
-                    }
+                    audio.play_music(&files, &format!("music/{}.mus", id));
                 }
             },
+            AudioEvent::PlayEffect {
+                sound,
+                category,
+                origin,
+            } => {
+                let source = match *origin {
+                    SoundOrigin::Entity(id) => entities
+                        .iter()
+                        .find(|(entity_id, _)| entity_id.id == id)
+                        .map(|(_, pos)| pos.to_vec2()),
+                    SoundOrigin::Tile(x, y) => Some(Vec2::new(x as f32, y as f32)),
+                    SoundOrigin::Local => None,
+                };
+                // Sources we can't place (off screen or unknown) play at full volume.
+                let attenuation = match (source, local_player.single()) {
+                    (Some(source), Ok(player)) => {
+                        distance_attenuation(source.distance(player.to_vec2()))
+                    }
+                    _ => 1.0,
+                };
+
+                let volume = settings.audio.category_volume(*category) * attenuation;
+                if volume > 0.0 {
+                    audio.play_sfx(&files, *sound, volume);
+                }
+            }
             _ => {}
         }
     }
@@ -125,12 +369,7 @@ pub fn sync_audio_settings(settings: Res<crate::settings::Settings>, audio: Opti
     };
 
     if settings.is_changed() {
-        let _ = audio
-            .music_track
-            .set_volume(amplitude_to_db(settings.audio.music_volume), Tween::default());
-        let _ = audio
-            .sfx_track
-            .set_volume(amplitude_to_db(settings.audio.sfx_volume), Tween::default());
+        audio.apply_volumes(&settings.audio);
     }
 }
 
@@ -143,10 +382,104 @@ pub fn setup_audio_settings(
         None => return,
     };
 
-    let _ = audio
-        .music_track
-        .set_volume(amplitude_to_db(settings.audio.music_volume), Tween::default());
-    let _ = audio
-        .sfx_track
-        .set_volume(amplitude_to_db(settings.audio.sfx_volume), Tween::default());
+    audio.apply_volumes(&settings.audio);
+}
+
+pub fn toggle_mute(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<UnifiedInputBindings>,
+    gamepad_query: Query<&Gamepad>,
+    gamepad_config: Res<GamepadConfig>,
+    audio: Option<ResMut<Audio>>,
+) {
+    let Some(mut audio) = audio else {
+        return;
+    };
+
+    if bindings.is_just_pressed(
+        GameAction::ToggleMute,
+        &keyboard_input,
+        Some(&gamepad_query),
+        Some(&gamepad_config),
+    ) {
+        let muted = !audio.is_muted();
+        audio.set_muted(muted);
+    }
+}
+
+/// Follows the selected output device, falling back to the system default
+/// while it is unplugged, and moves to a new default when one appears.
+/// Devices are listed on a background task; only an actual switch opens one
+/// on this thread.
+pub fn refresh_audio_device(
+    time: Res<Time>,
+    settings: Res<crate::settings::Settings>,
+    files: Option<Res<game_files::GameFiles>>,
+    audio: Option<ResMut<Audio>>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    let Some(mut audio) = audio else {
+        return;
+    };
+
+    if audio
+        .retired
+        .as_mut()
+        .is_some_and(|retired| retired.linger.tick(time.delta()).is_finished())
+    {
+        audio.retired = None;
+    }
+
+    let requested_changed = audio.requested_device != settings.audio.output_device;
+    let poll_due = audio.device_poll.tick(time.delta()).just_finished();
+    if audio.device_scan.is_none() && (poll_due || requested_changed) {
+        audio.requested_device = settings.audio.output_device.clone();
+        audio.device_scan =
+            Some(IoTaskPool::get().spawn(async_std::task::spawn_blocking(scan_devices)));
+    }
+
+    let Some(task) = audio.device_scan.as_mut() else {
+        return;
+    };
+    let Some(scan) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    audio.device_scan = None;
+
+    if scan.names != audio.devices {
+        outbound.write(UiOutbound(CoreToUi::AudioDevices {
+            devices: scan.names.clone(),
+        }));
+        audio.devices = scan.names;
+    }
+
+    let requested = audio
+        .requested_device
+        .clone()
+        .filter(|requested| audio.devices.contains(requested));
+    let name = requested.clone().or(scan.default);
+    if name.is_none() || name == audio.active_device {
+        return;
+    }
+    let device = requested.and_then(|requested| {
+        output_devices()
+            .into_iter()
+            .find(|(name, _)| *name == requested)
+            .map(|(_, device)| device)
+    });
+    audio.switch_device(name, device, &settings.audio, files.as_deref());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attenuation_falls_off_with_distance() {
+        assert_eq!(distance_attenuation(0.0), 1.0);
+        assert_eq!(distance_attenuation(FULL_VOLUME_DISTANCE), 1.0);
+        assert!(distance_attenuation(9.0) < 1.0 && distance_attenuation(9.0) > 0.0);
+        assert_eq!(distance_attenuation(SILENT_DISTANCE), 0.0);
+        assert_eq!(distance_attenuation(40.0), 0.0);
+    }
 }
@@ -0,0 +1,128 @@
+//! Checks on incoming players' chat: the word filter configured by
+//! `ChatFilterSettings` and mentions of the player's character.
+//!
+//! Words are matched whole and case-insensitively, so a listed "ass" leaves
+//! "class" alone. Filtered words are masked with asterisks and the chat log
+//! keeps the original text behind a click-to-reveal.
+
+use std::ops::Range;
+
+use packets::server::{PublicMessageType, ServerMessageType};
+
+/// Word list until the player edits it.
+pub const DEFAULT_WORDS: &[&str] = &[
+    "fuck", "fucking", "shit", "bitch", "cunt", "asshole", "bastard",
+];
+
+/// Number of [`ChatChannel`]s.
+pub const CHAT_CHANNELS: usize = 5;
+
+/// Channels the filter can be switched on for, by their index in
+/// `ChatFilterSettings::channels`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChatChannel {
+    Say = 0,
+    Shout = 1,
+    Whisper = 2,
+    Group = 3,
+    Guild = 4,
+}
+
+impl ChatChannel {
+    pub fn from_public(message_type: PublicMessageType) -> Option<Self> {
+        match message_type {
+            PublicMessageType::Normal => Some(Self::Say),
+            PublicMessageType::Shout => Some(Self::Shout),
+            PublicMessageType::Chant => None,
+        }
+    }
+
+    /// Only players' messages have a channel, system messages are never filtered.
+    pub fn from_server(message_type: ServerMessageType) -> Option<Self> {
+        match message_type {
+            ServerMessageType::Whisper => Some(Self::Whisper),
+            ServerMessageType::GroupChat => Some(Self::Group),
+            ServerMessageType::GuildChat => Some(Self::Guild),
+            _ => None,
+        }
+    }
+}
+
+/// Byte ranges of the runs of letters and digits in `text`.
+fn word_ranges(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut word_start = None;
+    text.char_indices()
+        .chain([(text.len(), ' ')])
+        .filter_map(move |(index, c)| {
+            if c.is_alphanumeric() {
+                word_start.get_or_insert(index);
+                None
+            } else {
+                word_start.take().map(|start| start..index)
+            }
+        })
+}
+
+/// `text` with every listed word masked, or `None` if none of them appear.
+pub fn mask_words(text: &str, words: &[String]) -> Option<String> {
+    let mut masked = String::with_capacity(text.len());
+    let mut copied = 0;
+    for range in word_ranges(text) {
+        let word = &text[range.clone()];
+        if words.iter().any(|w| w.eq_ignore_ascii_case(word)) {
+            masked.push_str(&text[copied..range.start]);
+            masked.extend(std::iter::repeat_n('*', word.chars().count()));
+            copied = range.end;
+        }
+    }
+    if copied == 0 {
+        return None;
+    }
+    masked.push_str(&text[copied..]);
+    Some(masked)
+}
+
+/// Whether a chat line addresses `name`. Its first word is the speaker, so a
+/// line the character said itself doesn't count.
+pub fn mentions(text: &str, name: &str) -> bool {
+    !name.is_empty()
+        && word_ranges(text)
+            .skip(1)
+            .any(|range| text[range].eq_ignore_ascii_case(name))
+}
+
+/// Splits the word list as typed in the settings, one entry per comma or line.
+pub fn parse_word_list(list: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for word in list.split([',', '\n']).map(str::trim) {
+        if !word.is_empty() && !words.iter().any(|w| w.eq_ignore_ascii_case(word)) {
+            words.push(word.to_lowercase());
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_whole_words_only() {
+        let words = parse_word_list("darn, Heck\n\n darn");
+        assert_eq!(words, ["darn", "heck"]);
+        assert_eq!(
+            mask_words("Darn it, what the HECK!", &words).as_deref(),
+            Some("**** it, what the ****!")
+        );
+        assert_eq!(mask_words("darned heckler", &words), None);
+        assert_eq!(mask_words("", &words), None);
+    }
+
+    #[test]
+    fn mentions_skip_the_speaker() {
+        assert!(mentions("Alice: anyone seen bob?", "Bob"));
+        assert!(mentions("[!Alice] Bob, heal", "Bob"));
+        assert!(!mentions("Bob: hi all", "Bob"));
+        assert!(!mentions("Alice: bobcat spotted", "Bob"));
+    }
+}
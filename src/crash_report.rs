@@ -0,0 +1,370 @@
+//! Structured crash reports.
+//!
+//! A panic hook writes a plain-text report (version, OS, GPU adapters, recent
+//! log output and the last server opcodes received) to `crash-reports/`. On the
+//! next launch the login screen offers to copy the pending report to the
+//! clipboard so it can be pasted into an issue, or, when
+//! `crash_reports.upload_url` is set, to upload it there. Nothing is sent
+//! unless the player picks upload.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::tasks::{IoTaskPool, Task};
+use futures_lite::future;
+
+use crate::resources::StorageConfig;
+use crate::settings_types::Settings;
+use crate::webui::ipc::{CoreToUi, UiToCore};
+use crate::webui::plugin::{UiInbound, UiOutbound};
+
+const MAX_LOG_LINES: usize = 200;
+const MAX_OPCODES: usize = 50;
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(20);
+
+static RECENT_LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static RECENT_OPCODES: Mutex<VecDeque<u8>> = Mutex::new(VecDeque::new());
+static GPU_ADAPTERS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Remember a server opcode for the next crash report. Called from the packet reader task.
+pub fn record_opcode(opcode: u8) {
+    if let Ok(mut opcodes) = RECENT_OPCODES.lock() {
+        if opcodes.len() == MAX_OPCODES {
+            opcodes.pop_front();
+        }
+        opcodes.push_back(opcode);
+    }
+}
+
+/// Record the adapters wgpu can see once the renderer is up.
+pub fn record_gpu_adapters(instance: &wgpu::Instance) {
+    let adapters =
+        futures_lite::future::block_on(instance.enumerate_adapters(wgpu::Backends::all()));
+    let names = adapters
+        .iter()
+        .map(|a| {
+            let info = a.get_info();
+            format!(
+                "{} ({:?}, {:?}, driver {} {})",
+                info.name, info.backend, info.device_type, info.driver, info.driver_info
+            )
+        })
+        .collect();
+    let _ = GPU_ADAPTERS.set(names);
+}
+
+/// `MakeWriter` that forwards log output to stdout and keeps the last
+/// [`MAX_LOG_LINES`] lines for crash reports.
+#[derive(Clone, Copy, Default)]
+pub struct LogTee;
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for LogTee {
+    type Writer = LogTee;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogTee
+    }
+}
+
+impl std::io::Write for LogTee {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Ok(mut lines) = RECENT_LOG.lock() {
+            for line in String::from_utf8_lossy(buf).lines() {
+                if lines.len() == MAX_LOG_LINES {
+                    lines.pop_front();
+                }
+                lines.push_back(strip_ansi(line));
+            }
+        }
+        std::io::stdout().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stdout().flush()
+    }
+}
+
+/// Install a panic hook that writes a report into `reports_dir` before
+/// deferring to the default hook.
+pub fn install_panic_hook(reports_dir: PathBuf) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = build_report(info);
+        let _ = std::fs::create_dir_all(&reports_dir);
+        let path = reports_dir.join(format!("crash-{}.txt", unix_secs()));
+        match std::fs::File::create(&path).and_then(|mut f| f.write_all(report.as_bytes())) {
+            Ok(()) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report {}: {}", path.display(), e),
+        }
+        default_hook(info);
+    }));
+}
+
+fn build_report(info: &std::panic::PanicHookInfo<'_>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Talgonite crash report");
+    let _ = writeln!(out, "Version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        out,
+        "OS: {} {} ({})",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::consts::FAMILY
+    );
+    let _ = writeln!(out, "Time: {} (unix)", unix_secs());
+    let _ = writeln!(
+        out,
+        "Thread: {}",
+        std::thread::current().name().unwrap_or("<unnamed>")
+    );
+
+    let _ = writeln!(out, "\nGPU adapters:");
+    match GPU_ADAPTERS.get() {
+        Some(adapters) if !adapters.is_empty() => {
+            for adapter in adapters {
+                let _ = writeln!(out, "  {}", adapter);
+            }
+        }
+        _ => {
+            let _ = writeln!(out, "  <not initialized>");
+        }
+    }
+
+    let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    };
+    let _ = writeln!(out, "\nPanic: {}", message);
+    if let Some(location) = info.location() {
+        let _ = writeln!(out, "Location: {}", location);
+    }
+    let _ = writeln!(
+        out,
+        "\nBacktrace:\n{}",
+        std::backtrace::Backtrace::force_capture()
+    );
+
+    let _ = writeln!(out, "Last {} server opcodes (oldest first):", MAX_OPCODES);
+    if let Ok(opcodes) = RECENT_OPCODES.try_lock() {
+        let _ = writeln!(out, "  {}", format_opcodes(opcodes.iter().copied()));
+    }
+
+    let _ = writeln!(out, "\nLast {} log lines:", MAX_LOG_LINES);
+    if let Ok(lines) = RECENT_LOG.try_lock() {
+        for line in lines.iter() {
+            let _ = writeln!(out, "  {}", line);
+        }
+    }
+    out
+}
+
+fn format_opcodes(opcodes: impl Iterator<Item = u8>) -> String {
+    opcodes
+        .map(|op| format!("{:02X}", op))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Remove terminal colour sequences emitted by the fmt subscriber.
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // CSI sequence: ESC [ params final-byte
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Newest report the user hasn't copied or dismissed yet.
+pub fn pending_report(reports_dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(reports_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension().is_some_and(|ext| ext == "txt")
+                && p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("crash-"))
+        })
+        .max()
+}
+
+/// Move a handled report into `reports_dir/seen` so it isn't offered again.
+pub fn mark_report_seen(path: &Path) {
+    let Some(parent) = path.parent() else {
+        return;
+    };
+    let seen_dir = parent.join("seen");
+    let _ = std::fs::create_dir_all(&seen_dir);
+    if let Some(name) = path.file_name() {
+        if let Err(e) = std::fs::rename(path, seen_dir.join(name)) {
+            tracing::warn!("Failed to archive crash report {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Put `text` on the system clipboard through the active Slint platform.
+pub fn copy_to_clipboard(text: &str) -> bool {
+    i_slint_core::context::with_global_context(
+        || Err(slint::PlatformError::NoPlatform),
+        |ctx| {
+            ctx.platform()
+                .set_clipboard_text(text, i_slint_core::platform::Clipboard::DefaultClipboard)
+        },
+    )
+    .is_ok()
+}
+
+fn upload_report(url: &str, report: String) -> Result<(), String> {
+    reqwest::blocking::Client::builder()
+        .timeout(UPLOAD_TIMEOUT)
+        .build()
+        .and_then(|client| {
+            client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(report)
+                .send()
+        })
+        .and_then(|r| r.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Upload the player asked for, still in flight.
+#[derive(Resource)]
+struct ReportUpload {
+    path: PathBuf,
+    task: Task<Result<(), String>>,
+}
+
+pub struct CrashReportPlugin;
+
+impl Plugin for CrashReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, offer_pending_report)
+            .add_systems(Update, (start_report_upload, finish_report_upload));
+    }
+}
+
+fn offer_pending_report(
+    storage: Res<StorageConfig>,
+    settings: Res<Settings>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    if let Some(path) = pending_report(&storage.crash_reports_dir()) {
+        tracing::info!("Found crash report from a previous run: {:?}", path);
+        outbound.write(UiOutbound(CoreToUi::CrashReportPending {
+            path: path.to_string_lossy().to_string(),
+            error: None,
+            can_upload: !settings.crash_reports.upload_url.is_empty(),
+        }));
+    }
+}
+
+fn start_report_upload(
+    mut commands: Commands,
+    mut inbound: MessageReader<UiInbound>,
+    settings: Res<Settings>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        let UiToCore::CrashReportUpload { path } = msg else {
+            continue;
+        };
+        let url = settings.crash_reports.upload_url.clone();
+        match std::fs::read_to_string(path) {
+            Ok(report) if !url.is_empty() => {
+                tracing::info!("Uploading crash report {:?} to {}", path, url);
+                let task = IoTaskPool::get().spawn(async_std::task::spawn_blocking(move || {
+                    upload_report(&url, report)
+                }));
+                commands.insert_resource(ReportUpload {
+                    path: PathBuf::from(path),
+                    task,
+                });
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("Failed to read crash report {:?}: {}", path, e);
+                outbound.write(UiOutbound(CoreToUi::CrashReportPending {
+                    path: path.clone(),
+                    error: Some(format!("Couldn't read the report: {}", e)),
+                    can_upload: true,
+                }));
+            }
+        }
+    }
+}
+
+/// Archives an uploaded report, or offers it again with the error.
+fn finish_report_upload(
+    mut commands: Commands,
+    upload: Option<ResMut<ReportUpload>>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    let Some(mut upload) = upload else {
+        return;
+    };
+    let Some(result) = future::block_on(future::poll_once(&mut upload.task)) else {
+        return;
+    };
+    commands.remove_resource::<ReportUpload>();
+    match result {
+        Ok(()) => mark_report_seen(&upload.path),
+        Err(e) => {
+            tracing::warn!("Failed to upload crash report {:?}: {}", upload.path, e);
+            outbound.write(UiOutbound(CoreToUi::CrashReportPending {
+                path: upload.path.to_string_lossy().to_string(),
+                error: Some(format!("Couldn't upload the report: {}", e)),
+                can_upload: true,
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_ansi_removes_colour_codes() {
+        assert_eq!(
+            strip_ansi("\u{1b}[32m INFO\u{1b}[0m \u{1b}[2mtalgonite\u{1b}[0m: ready"),
+            " INFO talgonite: ready"
+        );
+        assert_eq!(strip_ansi("plain"), "plain");
+    }
+
+    #[test]
+    fn opcodes_are_hex_formatted() {
+        assert_eq!(format_opcodes([0x00, 0x0A, 0xFF].into_iter()), "00 0A FF");
+    }
+}
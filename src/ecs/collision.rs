@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 use rendering::scene::map::door_data;
+use rendering::scene::map::interactables::is_door_wall;
 use rendering::scene::map::map_tile::MapTile;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Cursor;
 
 #[derive(Resource)]
@@ -160,3 +161,196 @@ pub fn can_walk_to(
     }
     true
 }
+
+/// Largest room [`enclosing_room`] fills before taking the player to be outdoors.
+const MAX_ROOM_TILES: usize = 600;
+/// Wall tiles deep that [`walls_in_front`] reaches, so thick walls open up too.
+const FRONT_WALL_DEPTH: u8 = 2;
+
+/// Whether a tile has a wall or door drawn on it.
+fn has_wall(map_collision: &MapCollisionData, x: u8, y: u8) -> bool {
+    map_collision
+        .get_walls_at(x, y)
+        .is_some_and(|(left, right)| left % 10000 > 2 || right % 10000 > 2)
+}
+
+/// The room around `start`: the tiles reachable from it without crossing a
+/// wall or a door. `None` outdoors, where the fill reaches the map edge or
+/// grows past [`MAX_ROOM_TILES`].
+pub fn enclosing_room(
+    start: (u8, u8),
+    collision_table: &WallCollisionTable,
+    map_collision: &MapCollisionData,
+) -> Option<HashSet<(u8, u8)>> {
+    let is_boundary = |x: u8, y: u8| {
+        !can_walk_to(x, y, Some(collision_table), Some(map_collision))
+            || map_collision
+                .get_walls_at(x, y)
+                .is_some_and(|(left, right)| is_door_wall(left) || is_door_wall(right))
+    };
+
+    let mut room = HashSet::from([start]);
+    let mut pending = vec![start];
+    while let Some((x, y)) = pending.pop() {
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if nx < 0
+                || ny < 0
+                || nx >= map_collision.width as i32
+                || ny >= map_collision.height as i32
+            {
+                return None;
+            }
+            let tile = (nx as u8, ny as u8);
+            if room.contains(&tile) || is_boundary(tile.0, tile.1) {
+                continue;
+            }
+            if room.len() >= MAX_ROOM_TILES {
+                return None;
+            }
+            room.insert(tile);
+            pending.push(tile);
+        }
+    }
+    Some(room)
+}
+
+/// Wall tiles drawn over `room`: the ones right after it along x, y or both,
+/// which sit below it on screen, up to [`FRONT_WALL_DEPTH`] tiles deep.
+pub fn walls_in_front(
+    room: &HashSet<(u8, u8)>,
+    map_collision: &MapCollisionData,
+) -> HashSet<(u8, u8)> {
+    let mut walls = HashSet::new();
+    for &(x, y) in room {
+        for (dx, dy) in [(1, 0), (0, 1), (1, 1)] {
+            for step in 1..=FRONT_WALL_DEPTH {
+                let (Some(wx), Some(wy)) = (x.checked_add(dx * step), y.checked_add(dy * step))
+                else {
+                    break;
+                };
+                if room.contains(&(wx, wy)) || !has_wall(map_collision, wx, wy) {
+                    break;
+                }
+                walls.insert((wx, wy));
+            }
+        }
+    }
+    walls
+}
+
+/// Where an item dropped on `target` should land. The tile itself when the
+/// server would take it, otherwise the closest open neighbour, preferring one
+/// without items on it already. `None` when there is nowhere to put it.
+pub fn choose_drop_tile(
+    target: (i32, i32),
+    collision_table: Option<&WallCollisionTable>,
+    map_collision: Option<&MapCollisionData>,
+    item_tiles: &HashSet<(u8, u8)>,
+) -> Option<(u8, u8)> {
+    let is_open = |x: i32, y: i32| -> Option<(u8, u8)> {
+        let (width, height) = map_collision.map_or((u8::MAX, u8::MAX), |m| (m.width, m.height));
+        if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+            return None;
+        }
+        let tile = (x as u8, y as u8);
+        can_walk_to(tile.0, tile.1, collision_table, map_collision).then_some(tile)
+    };
+
+    if let Some(tile) = is_open(target.0, target.1) {
+        return Some(tile);
+    }
+
+    // Cardinal neighbours first, they read as "next to" the wall that was hit
+    const NEIGHBORS: [(i32, i32); 8] = [
+        (0, -1),
+        (1, 0),
+        (0, 1),
+        (-1, 0),
+        (1, -1),
+        (1, 1),
+        (-1, 1),
+        (-1, -1),
+    ];
+    let open: Vec<(u8, u8)> = NEIGHBORS
+        .iter()
+        .filter_map(|(dx, dy)| is_open(target.0 + dx, target.1 + dy))
+        .collect();
+    open.iter()
+        .find(|tile| !item_tiles.contains(tile))
+        .or(open.first())
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map_with_wall_at(x: u8, y: u8) -> (WallCollisionTable, MapCollisionData) {
+        let mut map = MapCollisionData {
+            walls: vec![(0, 0); 25],
+            width: 5,
+            height: 5,
+            strips: vec![Vec::new(); 10],
+        };
+        map.set_walls_at(x, y, 1, 0);
+        (WallCollisionTable::from_sotp_bytes(vec![0x0F]), map)
+    }
+
+    #[test]
+    fn open_tiles_are_dropped_on_directly() {
+        let (table, map) = map_with_wall_at(2, 2);
+        let items = HashSet::from([(1_u8, 1_u8)]);
+
+        let result = choose_drop_tile((1, 1), Some(&table), Some(&map), &items);
+
+        assert_eq!(result, Some((1, 1)));
+    }
+
+    #[test]
+    fn walls_move_the_drop_to_an_empty_neighbour() {
+        let (table, map) = map_with_wall_at(2, 2);
+        let items = HashSet::from([(2_u8, 1_u8)]);
+
+        let result = choose_drop_tile((2, 2), Some(&table), Some(&map), &items);
+
+        assert_eq!(result, Some((3, 2)));
+    }
+
+    #[test]
+    fn rooms_stop_at_walls_and_hide_the_front_ones() {
+        // A 3x3 room walled in at x, y = 1 and 5 on a 7x7 map
+        let mut map = MapCollisionData {
+            walls: vec![(0, 0); 49],
+            width: 7,
+            height: 7,
+            strips: vec![Vec::new(); 14],
+        };
+        for i in 1..=5 {
+            for (x, y) in [(i, 1), (i, 5), (1, i), (5, i)] {
+                map.set_walls_at(x, y, 10, 0);
+            }
+        }
+        let mut sotp = vec![0; 10];
+        sotp[9] = 0x0F;
+        let table = WallCollisionTable::from_sotp_bytes(sotp);
+
+        let room = enclosing_room((3, 3), &table, &map).unwrap();
+        assert_eq!(room.len(), 9);
+        let front = walls_in_front(&room, &map);
+        assert!(front.contains(&(5, 3)) && front.contains(&(3, 5)) && front.contains(&(5, 5)));
+        assert!(!front.contains(&(1, 3)) && !front.contains(&(3, 1)));
+
+        // Outside the walls the fill runs into the map edge
+        assert_eq!(enclosing_room((0, 0), &table, &map), None);
+    }
+
+    #[test]
+    fn drops_outside_the_map_land_on_its_edge() {
+        let (table, map) = map_with_wall_at(2, 2);
+
+        let result = choose_drop_tile((5, 0), Some(&table), Some(&map), &HashSet::new());
+
+        assert_eq!(result, Some((4, 0)));
+    }
+}
@@ -65,6 +65,16 @@ pub struct MovementTween {
     pub duration: f32, // seconds
 }
 
+/// How often walk packets arrive for a remote entity, so its steps keep pace
+/// with the server instead of assuming a fixed walk speed.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct StepPace {
+    /// `Time::elapsed_secs` of the last walk packet.
+    pub last_step: f32,
+    /// Smoothed seconds between walk packets.
+    pub interval: f32,
+}
+
 #[derive(Debug, Clone)]
 pub enum PathTarget {
     Tile { x: u8, y: u8 },
@@ -74,6 +84,8 @@ pub enum PathTarget {
 pub struct PathfindingState {
     pub target: PathTarget,
     pub face_after: Option<(u8, u8)>,
+    /// Entity clicked once the destination is reached.
+    pub interact_after: Option<Entity>,
     pub retry_timer: Option<Timer>,
 }
 
@@ -281,7 +293,10 @@ pub struct MapPrepared {
     pub width: u8,
     pub height: u8,
     pub name: String,
+    pub check_sum: u16,
+    pub xray: bool,
     pub prepared_map: PreparedMap,
+    pub collision: crate::ecs::collision::MapCollisionData,
 }
 
 // Marker component: entity is tied to the currently loaded map and should be
@@ -407,6 +422,10 @@ impl Default for TargetingHover {
     }
 }
 
+/// Marks the creature or player under the cursor so its sprite is drawn outlined.
+#[derive(Component)]
+pub struct HoverOutline;
+
 #[derive(Component)]
 /// Displays a name above the entity when hovered.
 /// Rendered via immediate mode (cleared every frame), so no cleanup hook is required.
@@ -431,6 +450,7 @@ pub struct WorldLabel {
     pub y_offset: f32,
     pub color: glam::Vec4,
     pub is_speech: bool,
+    pub opacity: f32,
 }
 
 /// Hover label component - entity name shown on hover
@@ -454,10 +474,14 @@ impl HoverLabel {
             y_offset: -40.0,
             color: self.color,
             is_speech: false,
+            opacity: 1.0,
         }
     }
 }
 
+/// Seconds over which a speech bubble fades out before it expires.
+const SPEECH_BUBBLE_FADE_SECS: f32 = 0.5;
+
 /// Speech bubble component - normal/shout messages
 #[derive(Component, Clone)]
 pub struct SpeechBubble {
@@ -488,11 +512,12 @@ impl SpeechBubble {
                 glam::Vec4::new(1.0, 1.0, 1.0, 1.0) // White for normal
             },
             is_speech: true,
+            opacity: (self.timer.remaining_secs() / SPEECH_BUBBLE_FADE_SECS).min(1.0),
         }
     }
 }
 
-/// Chant label component - spell chants (light blue text)
+/// Chant label component - spell chants (light blue bubble)
 #[derive(Component, Clone)]
 pub struct ChantLabel {
     pub text: String,
@@ -515,7 +540,8 @@ impl ChantLabel {
             text: self.text.clone(),
             y_offset: -50.0,
             color: glam::Vec4::new(0.5, 0.7, 1.0, 1.0),
-            is_speech: false,
+            is_speech: true,
+            opacity: 1.0,
         }
     }
 }
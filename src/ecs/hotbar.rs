@@ -1,9 +1,17 @@
 use bevy::prelude::*;
 use std::collections::HashMap;
+use std::time::Duration;
 
+use crate::ecs::components::{LocalPlayer, Position, NPC};
 use crate::settings_types::CustomHotBars;
 use crate::{settings::Settings, CurrentSession};
 use crate::webui::ipc::Cooldown;
+use packets::server::VisibleEntityType;
+
+/// Tiles from the player within which a creature counts as on screen.
+const SCREEN_RADIUS: f32 = 10.0;
+/// How long the screen must be clear of hostiles before going back to the town page.
+const CALM_DELAY: Duration = Duration::from_secs(3);
 
 #[derive(Resource, Default)]
 pub struct HotbarState {
@@ -39,6 +47,68 @@ impl HotbarState {
 #[derive(Resource, Default)]
 pub struct HotbarPanelState {
     pub current_panel: HotbarPanel,
+    /// Context the page was last switched for, see `auto_switch_hotbar_panel`.
+    auto_context: Option<HotbarContext>,
+    calm_since: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HotbarContext {
+    Town,
+    Combat,
+}
+
+/// Picks the town or combat page depending on whether hostile creatures are on
+/// screen. Pages only change when the context does, so a page picked by hand
+/// stays up until the next change.
+pub fn auto_switch_hotbar_panel(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut hotbar_panel: ResMut<HotbarPanelState>,
+    player: Query<&Position, With<LocalPlayer>>,
+    npcs: Query<(&Position, &NPC)>,
+) {
+    if !settings.layout.hotbar_auto_switch {
+        if hotbar_panel.auto_context.is_some() {
+            hotbar_panel.bypass_change_detection().auto_context = None;
+        }
+        return;
+    }
+    let Ok(player) = player.single() else {
+        return;
+    };
+
+    let hostile_in_view = npcs.iter().any(|(pos, npc)| {
+        matches!(
+            npc.entity_type,
+            VisibleEntityType::Normal | VisibleEntityType::WalkThrough
+        ) && (pos.x - player.x).abs().max((pos.y - player.y).abs()) <= SCREEN_RADIUS
+    });
+    let now = time.elapsed();
+    let state = hotbar_panel.bypass_change_detection();
+    let context = if hostile_in_view {
+        state.calm_since = None;
+        HotbarContext::Combat
+    } else {
+        let calm_since = *state.calm_since.get_or_insert(now);
+        if state.auto_context == Some(HotbarContext::Combat) && now - calm_since < CALM_DELAY {
+            HotbarContext::Combat
+        } else {
+            HotbarContext::Town
+        }
+    };
+    if state.auto_context == Some(context) {
+        return;
+    }
+    state.auto_context = Some(context);
+
+    let panel = HotbarPanel::from_u8(match context {
+        HotbarContext::Town => settings.layout.hotbar_town_panel,
+        HotbarContext::Combat => settings.layout.hotbar_combat_panel,
+    });
+    if hotbar_panel.current_panel != panel {
+        hotbar_panel.current_panel = panel;
+    }
 }
 
 pub fn sync_hotbar_panel_to_settings(
@@ -9,3 +9,6 @@ pub use crate::events::{EntityClickEvent, EntityHoverEvent};
 #[derive(Resource, Default)]
 pub struct HoveredEntity(pub Option<Entity>);
 
+/// Ground tile under the mouse, which may be off the map.
+#[derive(Resource, Default, PartialEq)]
+pub struct HoveredTile(pub Option<(i32, i32)>);
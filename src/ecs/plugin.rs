@@ -23,6 +23,9 @@ impl Plugin for GamePlugin {
             .init_resource::<crate::resources::LobbyPortraits>()
             .init_resource::<crate::resources::ItemTileCounters>()
             .init_resource::<super::components::MapDoorQueue>()
+            .init_resource::<crate::resources::MapPreload>()
+            .init_resource::<crate::resources::MapTransition>()
+            .init_resource::<crate::resources::PathPreview>()
             .add_message::<super::components::MapPrepared>()
             .add_systems(
                 OnEnter(crate::app_state::AppState::InGame),
@@ -47,6 +50,8 @@ impl Plugin for GamePlugin {
                     audio::sync_audio_settings,
                     spell_casting::start_spell_cast,
                     spell_casting::update_spell_casting,
+                    spell_casting::fire_queued_spell.after(spell_casting::update_spell_casting),
+                    spell_casting::fire_queued_skill,
                     spell_casting::handle_spell_targeting
                         .after(crate::plugins::mouse_interaction::MouseInteractionSet),
                     spell_casting::update_targeting_hover,
@@ -60,8 +65,11 @@ impl Plugin for GamePlugin {
                     systems::consume_interaction_intents_system
                         .after(systems::resolve_interaction_intents_system)
                         .after(systems::player_interruption_system),
+                    crate::ecs::hotbar::auto_switch_hotbar_panel
+                        .before(crate::ecs::hotbar::sync_hotbar_panel_to_settings),
                     crate::ecs::hotbar::sync_hotbar_panel_to_settings,
                     systems::handle_public_messages,
+                    systems::raise_shout_banner,
                     systems::expire_speech_bubbles,
                     systems::expire_chant_labels,
                     systems::expire_health_bars,
@@ -73,6 +81,8 @@ impl Plugin for GamePlugin {
             .add_systems(
                 Update,
                 (
+                    systems::track_map_warps,
+                    systems::map_transition_system,
                     systems::map_system,
                     systems::handle_doors,
                     systems::spawn_entities_system,
@@ -85,6 +95,16 @@ impl Plugin for GamePlugin {
                     .run_if(in_state(crate::app_state::AppState::InGame))
                     .in_set(GameSet::Spawning),
             )
+            // === Map Preloading ===
+            // Decodes the map behind a nearby exit before the player walks through it
+            .add_systems(
+                Update,
+                (systems::preload_nearby_maps, systems::finish_map_preloads)
+                    .chain()
+                    .run_if(resource_exists::<crate::game_files::GameFiles>)
+                    .run_if(in_state(crate::app_state::AppState::InGame))
+                    .in_set(GameSet::Spawning),
+            )
             // === Movement Systems ===
             .add_systems(
                 Update,
@@ -121,6 +141,15 @@ impl Plugin for GamePlugin {
                     .run_if(in_state(crate::app_state::AppState::InGame))
                     .in_set(GameSet::Animation),
             )
+            .add_systems(
+                Update,
+                (
+                    systems::map_chunk_streaming_system,
+                    systems::interior_wall_system,
+                )
+                    .run_if(in_state(crate::app_state::AppState::InGame))
+                    .in_set(GameSet::RenderSync),
+            )
             // === Camera Systems ===
             .add_systems(
                 Update,
@@ -128,6 +157,9 @@ impl Plugin for GamePlugin {
                     systems::camera_follow_system,
                     systems::camera_position_sync,
                     systems::camera_xray_sync,
+                    systems::camera_xray_follow,
+                    systems::camera_highlight_sync,
+                    systems::path_preview_system,
                 )
                     .chain()
                     .run_if(in_state(crate::app_state::AppState::InGame))
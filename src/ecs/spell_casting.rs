@@ -1,11 +1,13 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use packets::client::{BeginChant, SpellChant, SpellUse, SpellUseArgs};
 use packets::server::SpellType;
 
 use crate::ecs::interaction::HoveredEntity;
-use crate::events::{AbilityEvent, EntityClickEvent};
+use crate::events::{AbilityEvent, EntityClickEvent, SpellCastEvent};
 use crate::network::PacketOutbox;
-use crate::webui::ipc::ActionId;
+use crate::webui::ipc::{ActionId, Cooldown};
 use crate::webui::plugin::AbilityState;
 
 use super::components::{EntityId, NPC, Player, Position, TargetingHover};
@@ -13,6 +15,44 @@ use super::components::{EntityId, NPC, Player, Position, TargetingHover};
 #[derive(Resource, Default)]
 pub struct SpellCastingState {
     pub active_cast: Option<ActiveSpellCast>,
+    /// Spell pressed near the end of the active cast, fired once it completes.
+    pub queued: Option<QueuedSpell>,
+    /// Skill pressed near the end of its cooldown, used again once it clears.
+    pub queued_skill: Option<QueuedSpell>,
+}
+
+impl SpellCastingState {
+    /// Drops the active cast along with anything queued behind it.
+    pub fn cancel(&mut self) {
+        self.active_cast = None;
+        self.queued = None;
+    }
+
+    /// Holds a skill press made `window_ms` or less before its cooldown ends.
+    /// Returns whether the press was held rather than left to go out now.
+    pub fn hold_skill(&mut self, slot: u8, cooldown: Option<&Cooldown>, window_ms: u32) -> bool {
+        let Some(cooldown) = cooldown else {
+            return false;
+        };
+        let window = Duration::from_millis(window_ms.into());
+        // A cooldown that has not ticked yet was started by this very press
+        let started_now = cooldown.time_left >= cooldown.duration;
+        if window.is_zero() || started_now || cooldown.time_left > window {
+            return false;
+        }
+
+        self.queued_skill = Some(QueuedSpell {
+            slot,
+            expires_in: (cooldown.time_left + window).as_secs_f32(),
+        });
+        true
+    }
+}
+
+pub struct QueuedSpell {
+    pub slot: u8,
+    /// Seconds left before the queued press is discarded.
+    pub expires_in: f32,
 }
 
 pub struct ActiveSpellCast {
@@ -25,6 +65,21 @@ pub struct ActiveSpellCast {
     pub target: Option<SpellTarget>,
 }
 
+impl ActiveSpellCast {
+    /// Seconds until the final chant line sends the spell.
+    pub fn remaining_secs(&self) -> f32 {
+        let lines_left = self.total_cast_lines.saturating_sub(self.current_line) as f32;
+        lines_left + (1.0 - self.time_since_last_chant).max(0.0)
+    }
+
+    /// Whether a press `window_ms` or less before the cast ends should wait for it.
+    pub fn accepts_queue(&self, window_ms: u32) -> bool {
+        window_ms > 0
+            && !self.waiting_for_target
+            && self.remaining_secs() <= window_ms as f32 / 1000.0
+    }
+}
+
 pub struct SpellTarget {
     pub entity_id: u32,
     pub position: (u16, u16),
@@ -35,6 +90,8 @@ pub fn start_spell_cast(
     mut casting_state: ResMut<SpellCastingState>,
     ability_state: Option<Res<AbilityState>>,
     outbox: Res<PacketOutbox>,
+    settings: Res<crate::settings_types::Settings>,
+    mut cast_events: MessageWriter<SpellCastEvent>,
 ) {
     for event in events.read() {
         if let AbilityEvent::UseSpell { slot } = event {
@@ -46,7 +103,20 @@ pub fn start_spell_cast(
                 continue;
             };
 
-            casting_state.active_cast = None;
+            // Close to the end of a cast, wait for it instead of interrupting it
+            let window_ms = settings.gameplay.spell_queue_window_ms;
+            if let Some(cast) = &casting_state.active_cast {
+                if cast.accepts_queue(window_ms) {
+                    let expires_in = cast.remaining_secs() + window_ms as f32 / 1000.0;
+                    casting_state.queued = Some(QueuedSpell {
+                        slot: *slot,
+                        expires_in,
+                    });
+                    continue;
+                }
+            }
+
+            casting_state.cancel();
 
             match spell.spell_type {
                 SpellType::Targeted => {
@@ -66,6 +136,7 @@ pub fn start_spell_cast(
                             source_slot: *slot,
                             args: SpellUseArgs::None,
                         });
+                        cast_events.write(SpellCastEvent { slot: *slot });
                     } else {
                         outbox.send(&BeginChant {
                             cast_line_count: spell.cast_lines,
@@ -95,6 +166,7 @@ pub fn update_spell_casting(
     ability_state: Option<Res<AbilityState>>,
     time: Res<Time>,
     outbox: Res<PacketOutbox>,
+    mut cast_events: MessageWriter<SpellCastEvent>,
 ) {
     let Some(ref mut cast) = casting_state.active_cast else {
         return;
@@ -139,18 +211,72 @@ pub fn update_spell_casting(
                 source_slot: spell.slot,
                 args,
             });
+            cast_events.write(SpellCastEvent { slot: spell.slot });
 
             casting_state.active_cast = None;
         }
     }
 }
 
+/// Fires the queued spell as soon as nothing is being cast.
+pub fn fire_queued_spell(
+    mut casting_state: ResMut<SpellCastingState>,
+    mut ability_events: MessageWriter<AbilityEvent>,
+    time: Res<Time>,
+) {
+    let casting_state = &mut *casting_state;
+    let Some(queued) = casting_state.queued.as_mut() else {
+        return;
+    };
+
+    queued.expires_in -= time.delta_secs();
+    if queued.expires_in <= 0.0 {
+        casting_state.queued = None;
+        return;
+    }
+
+    if casting_state.active_cast.is_none() {
+        let slot = queued.slot;
+        casting_state.queued = None;
+        ability_events.write(AbilityEvent::UseSpell { slot });
+    }
+}
+
+/// Uses the held skill again as soon as its cooldown has cleared.
+pub fn fire_queued_skill(
+    mut casting_state: ResMut<SpellCastingState>,
+    ability_state: Option<Res<AbilityState>>,
+    mut ability_events: MessageWriter<AbilityEvent>,
+    time: Res<Time>,
+) {
+    let Some(queued) = casting_state.queued_skill.as_mut() else {
+        return;
+    };
+
+    queued.expires_in -= time.delta_secs();
+    if queued.expires_in <= 0.0 {
+        casting_state.queued_skill = None;
+        return;
+    }
+
+    let slot = queued.slot;
+    let ready = ability_state
+        .as_ref()
+        .and_then(|state| state.skills.iter().find(|skill| skill.slot == slot))
+        .is_some_and(|skill| skill.on_cooldown.is_none());
+    if ready {
+        casting_state.queued_skill = None;
+        ability_events.write(AbilityEvent::UseSkill { slot });
+    }
+}
+
 pub fn handle_spell_targeting(
     mut events: MessageReader<EntityClickEvent>,
     mut casting_state: ResMut<SpellCastingState>,
     ability_state: Option<Res<AbilityState>>,
     query: Query<(&EntityId, &Position, Option<&Player>, Option<&NPC>)>,
     outbox: Res<PacketOutbox>,
+    mut cast_events: MessageWriter<SpellCastEvent>,
 ) {
     let Some(ref mut cast) = casting_state.active_cast else {
         return;
@@ -192,6 +318,7 @@ pub fn handle_spell_targeting(
                             target_y: target_ref.position.1,
                         },
                     });
+                    cast_events.write(SpellCastEvent { slot: spell.slot });
                     casting_state.active_cast = None;
                 } else {
                     outbox.send(&BeginChant {
@@ -248,3 +375,63 @@ pub fn update_targeting_hover(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{ActiveSpellCast, SpellCastingState};
+    use crate::webui::ipc::{ActionId, Cooldown};
+    use packets::server::SpellType;
+
+    fn chanting(total: u8, current: u8, elapsed: f32) -> ActiveSpellCast {
+        ActiveSpellCast {
+            spell_id: ActionId::from_spell(1, "ioc"),
+            spell_type: SpellType::NoTarget,
+            total_cast_lines: total,
+            current_line: current,
+            time_since_last_chant: elapsed,
+            waiting_for_target: false,
+            target: None,
+        }
+    }
+
+    #[test]
+    fn queues_only_near_the_end_of_a_cast() {
+        let early = chanting(3, 1, 0.2);
+        assert!((early.remaining_secs() - 2.8).abs() < 1e-4);
+        assert!(!early.accepts_queue(400));
+
+        let late = chanting(3, 3, 0.7);
+        assert!(late.accepts_queue(400));
+        assert!(!late.accepts_queue(0));
+
+        let mut targeting = chanting(0, 0, 0.0);
+        targeting.waiting_for_target = true;
+        assert!(!targeting.accepts_queue(1000));
+    }
+
+    fn cooling_down(duration_ms: u64, left_ms: u64) -> Cooldown {
+        Cooldown {
+            start_time: Instant::now(),
+            duration: Duration::from_millis(duration_ms),
+            time_left: Duration::from_millis(left_ms),
+        }
+    }
+
+    #[test]
+    fn holds_skills_only_near_the_end_of_a_cooldown() {
+        let mut state = SpellCastingState::default();
+
+        assert!(!state.hold_skill(2, None, 400));
+        assert!(!state.hold_skill(2, Some(&cooling_down(5000, 3000)), 400));
+        assert!(!state.hold_skill(2, Some(&cooling_down(300, 300)), 400));
+        assert!(!state.hold_skill(2, Some(&cooling_down(5000, 300)), 0));
+        assert!(state.queued_skill.is_none());
+
+        assert!(state.hold_skill(2, Some(&cooling_down(5000, 300)), 400));
+        let queued = state.queued_skill.as_ref().unwrap();
+        assert_eq!(queued.slot, 2);
+        assert!((queued.expires_in - 0.7).abs() < 1e-4);
+    }
+}
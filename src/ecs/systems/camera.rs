@@ -1,6 +1,9 @@
 //! Camera systems
 
 use super::super::components::*;
+use crate::input::{GameAction, GamepadConfig, UnifiedInputBindings};
+use crate::resources::PlayerBatchState;
+use crate::settings_types::XRayMode;
 use crate::{Camera, RendererState};
 use bevy::prelude::*;
 use tracing::debug;
@@ -59,7 +62,7 @@ pub fn camera_position_sync(
     }
 }
 
-/// Syncs the X-ray size setting to the camera shader.
+/// Syncs the X-ray size and mode settings to the camera shader.
 pub fn camera_xray_sync(
     mut camera: ResMut<Camera>,
     renderer: Res<RendererState>,
@@ -70,5 +73,63 @@ pub fn camera_xray_sync(
             &renderer.queue,
             settings.graphics.xray_size.to_shader_multiplier(),
         );
+        camera.camera.set_xray_mode(
+            &renderer.queue,
+            settings.graphics.xray_mode == XRayMode::Occluding,
+        );
+    }
+}
+
+/// Player tile and sprite bounds last sent to the X-ray shader.
+type XRayTarget = (Vec2, Option<(Vec2, Vec2)>);
+
+/// Keeps the X-ray cutout on the local player and sized to its current sprite.
+pub fn camera_xray_follow(
+    mut camera: ResMut<Camera>,
+    renderer: Res<RendererState>,
+    player_batch: Option<Res<PlayerBatchState>>,
+    player_query: Query<(&Position, &Children), With<LocalPlayer>>,
+    sprite_query: Query<&PlayerSpriteInstance>,
+    mut last: Local<Option<XRayTarget>>,
+) {
+    let Ok((position, children)) = player_query.single() else {
+        return;
+    };
+
+    let tile = Vec2::new(position.x, position.y);
+    let bounds = player_batch.and_then(|batch| {
+        batch.batch.sprite_bounds(
+            children
+                .iter()
+                .filter_map(|child| sprite_query.get(child).ok())
+                .map(|sprite| &sprite.handle),
+        )
+    });
+
+    if *last != Some((tile, bounds)) {
+        *last = Some((tile, bounds));
+        camera.camera.set_xray_target(&renderer.queue, tile, bounds);
+    }
+}
+
+/// Lights up every door and map exit on screen while the overlay key is held.
+pub fn camera_highlight_sync(
+    mut camera: ResMut<Camera>,
+    renderer: Res<RendererState>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<UnifiedInputBindings>,
+    gamepads: Query<&Gamepad>,
+    gamepad_config: Res<GamepadConfig>,
+    mut highlighted: Local<bool>,
+) {
+    let held = bindings.is_pressed(
+        GameAction::HighlightInteractables,
+        &keyboard,
+        Some(&gamepads),
+        Some(&gamepad_config),
+    );
+    if held != *highlighted {
+        *highlighted = held;
+        camera.camera.set_highlight(&renderer.queue, held);
     }
 }
@@ -1,17 +1,29 @@
 //! Chat-related systems (speech bubbles, messages)
 
 use super::super::components::*;
+use crate::chat_filter::ChatChannel;
 use crate::events::ChatEvent;
+use crate::settings_types::Settings;
+use crate::slint_support::state_bridge::SlintWindow;
 use bevy::prelude::*;
 use packets::server::PublicMessageType;
 
 const SPEECH_BUBBLE_DURATION_SECS: f32 = 3.0;
 const CHANT_DURATION_SECS: f32 = 2.0;
 
+/// The message as shown over the map, with filtered words masked.
+fn displayed_text(settings: &Settings, message_type: PublicMessageType, message: &str) -> String {
+    settings
+        .chat_filter
+        .apply(ChatChannel::from_public(message_type), message)
+        .unwrap_or_else(|| message.to_string())
+}
+
 /// Handles DisplayPublicMessage events and adds speech bubbles/chants to entities
 pub fn handle_public_messages(
     mut commands: Commands,
     mut chat_events: MessageReader<ChatEvent>,
+    settings: Res<Settings>,
     entities_query: Query<(Entity, &EntityId)>,
 ) {
     for event in chat_events.read() {
@@ -20,17 +32,20 @@ pub fn handle_public_messages(
                 .iter()
                 .find(|(_, eid)| eid.id == msg.source_id)
             {
+                let text = displayed_text(&settings, msg.message_type, &msg.message);
                 match msg.message_type {
+                    PublicMessageType::Normal | PublicMessageType::Shout
+                        if !settings.gameplay.chat_bubbles => {}
                     PublicMessageType::Normal => {
                         commands.entity(entity).insert(SpeechBubble::new(
-                            &msg.message,
+                            &text,
                             SPEECH_BUBBLE_DURATION_SECS,
                             false,
                         ));
                     }
                     PublicMessageType::Shout => {
                         commands.entity(entity).insert(SpeechBubble::new(
-                            &msg.message,
+                            &text,
                             SPEECH_BUBBLE_DURATION_SECS,
                             true,
                         ));
@@ -46,6 +61,56 @@ pub fn handle_public_messages(
     }
 }
 
+/// Splits a shout ("Name! message") into the speaker and the message. `known_name`
+/// comes from the entity table and is preferred over guessing from the text.
+fn shout_parts<'a>(message: &'a str, known_name: Option<&'a str>) -> (Option<&'a str>, &'a str) {
+    if let Some(name) = known_name {
+        let text = message
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('!'))
+            .map(str::trim_start)
+            .unwrap_or(message);
+        return (Some(name), text);
+    }
+    match message.split_once("! ") {
+        Some((name, text)) if !name.is_empty() && !name.contains(' ') => (Some(name), text),
+        _ => (None, message),
+    }
+}
+
+/// Shows shouts in the banner along the top of the screen, since the speaker is
+/// often somewhere else on the map.
+pub fn raise_shout_banner(
+    mut chat_events: MessageReader<ChatEvent>,
+    settings: Res<Settings>,
+    window: Option<Res<SlintWindow>>,
+    players_query: Query<(&EntityId, &Player)>,
+) {
+    for event in chat_events.read() {
+        let ChatEvent::PublicMessage(msg) = event else {
+            continue;
+        };
+        if msg.message_type != PublicMessageType::Shout {
+            continue;
+        }
+
+        let known_name = players_query
+            .iter()
+            .find(|(eid, _)| eid.id == msg.source_id)
+            .map(|(_, player)| player.name.as_str());
+        let message = displayed_text(&settings, msg.message_type, &msg.message);
+        let (sender, text) = shout_parts(&message, known_name);
+
+        if let Some(strong) = window.as_ref().and_then(|w| w.0.upgrade()) {
+            let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+            game_state.set_shout_sender(slint::SharedString::from(sender.unwrap_or_default()));
+            game_state.set_shout_text(slint::SharedString::from(text));
+            let counter = game_state.get_shout_counter();
+            game_state.set_shout_counter(counter.wrapping_add(1));
+        }
+    }
+}
+
 /// Removes expired speech bubbles
 pub fn expire_speech_bubbles(
     mut commands: Commands,
@@ -73,3 +138,32 @@ pub fn expire_chant_labels(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shout_prefers_the_known_speaker_name() {
+        assert_eq!(
+            shout_parts("Aeron! Selling a mold", Some("Aeron")),
+            (Some("Aeron"), "Selling a mold")
+        );
+        assert_eq!(
+            shout_parts("Need a group!", Some("Aeron")),
+            (Some("Aeron"), "Need a group!")
+        );
+    }
+
+    #[test]
+    fn shout_name_is_guessed_from_the_prefix() {
+        assert_eq!(
+            shout_parts("Aeron! Selling a mold", None),
+            (Some("Aeron"), "Selling a mold")
+        );
+        assert_eq!(
+            shout_parts("Hey you! over there", None),
+            (None, "Hey you! over there")
+        );
+    }
+}
@@ -9,7 +9,13 @@ pub fn entity_effect_system(
     mut entity_events: MessageReader<EntityEvent>,
     mut targets: Query<(Entity, &EntityId)>,
     mut commands: Commands,
+    settings: Res<crate::settings_types::Settings>,
 ) {
+    // Potato mode draws no effects
+    if settings.graphics.potato_mode {
+        entity_events.clear();
+        return;
+    }
     for event in entity_events.read() {
         let EntityEvent::Effect(anim) = event else {
             continue;
@@ -645,9 +645,11 @@ pub fn health_bar_system(
                     }
 
                     if let Some(sound_id) = packet.sound {
-                        audio_events.write(crate::events::AudioEvent::PlaySound(
-                            packets::server::Sound::Sound(sound_id),
-                        ));
+                        audio_events.write(crate::events::AudioEvent::PlayEffect {
+                            sound: sound_id,
+                            category: crate::settings_types::SoundCategory::Combat,
+                            origin: crate::events::SoundOrigin::Entity(packet.source_id),
+                        });
                     }
                     break;
                 }
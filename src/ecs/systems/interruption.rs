@@ -38,16 +38,16 @@ pub fn player_interruption_system(
     let has_interaction_intent = interaction_intents.read().next().is_some();
 
     if is_manual_move {
-        spells.active_cast = None;
+        spells.cancel();
         commands.entity(player_entity).remove::<PathfindingState>();
     }
 
     if is_new_path {
-        spells.active_cast = None;
+        spells.cancel();
     }
 
     if has_interaction_intent {
-        spells.active_cast = None;
+        spells.cancel();
         commands.entity(player_entity).remove::<PathfindingState>();
     }
 }
@@ -1,12 +1,20 @@
 //! Map loading and rendering systems
 
 use super::super::components::*;
-use crate::{Camera, MapRendererState, RendererState, events::MapEvent, game_files::GameFiles};
+use crate::ecs::collision::{MapCollisionData, WallCollisionTable, enclosing_room, walls_in_front};
+use crate::render_plugin::pip::{PipCamera, PipTarget};
+use crate::resources::{MAP_FADE_IN_SECS, MapPreload, MapTransition, StandbyMap};
+use crate::{MapRendererState, RendererState, events::MapEvent, game_files::GameFiles};
 use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
+use futures_lite::future;
 use rendering::scene::map::renderer::MapRenderer;
 use tracing::info;
 
-/// Handles map events: loading, clearing, light levels, and doors.
+/// Start preparing the map behind an exit once the player is this many tiles from it.
+const PRELOAD_RADIUS: f32 = 4.0;
+
+/// Handles map events: loading, clearing and doors.
 pub fn map_system(
     mut commands: Commands,
     mut map_events: MessageReader<MapEvent>,
@@ -14,10 +22,10 @@ pub fn map_system(
     scoped_q: Query<Entity, With<MapScoped>>,
     map_entities: Query<&GameMap>,
     renderer: Option<Res<RendererState>>,
-    mut camera: Option<ResMut<Camera>>,
     settings: Res<crate::settings::Settings>,
     mut door_queue: ResMut<MapDoorQueue>,
     mut tile_counters: ResMut<crate::resources::ItemTileCounters>,
+    mut preload: ResMut<MapPreload>,
 ) {
     let mut local_map_renderer: Option<MapRenderer> = None;
     // Track if we cleared the map this frame - if so, don't skip SetInfo even if
@@ -57,13 +65,13 @@ pub fn map_system(
                     &archive,
                     renderer.as_deref(),
                     &settings,
+                    &mut preload,
                     map_info,
                     map_bytes,
                 );
             }
-            MapEvent::SetLightLevel(kind) => {
-                handle_light_level(camera.as_deref_mut(), renderer.as_deref(), kind);
-            }
+            // Tinted together with the map's darkness, see plugins::map_ambience
+            MapEvent::SetLightLevel(_) => {}
             MapEvent::SetDoors(door_data) => {
                 door_queue.pending.extend(door_data.doors.clone());
             }
@@ -99,44 +107,57 @@ fn handle_map_set_info(
     archive: &Res<GameFiles>,
     renderer: Option<&RendererState>,
     settings: &Res<crate::settings::Settings>,
+    preload: &mut MapPreload,
     map_info: &packets::server::MapInfo,
     map_bytes: &std::sync::Arc<[u8]>,
 ) -> Option<MapRenderer> {
-    info!(
-        map_id = map_info.map_id,
-        name = %map_info.name,
-        size = map_bytes.len(),
-        "Map change: preparing map (sync)"
-    );
-
-    let archive_ref = archive.inner().archive();
-    let prepared_map = MapRenderer::prepare_map(
-        archive_ref,
-        (*map_bytes).to_vec(),
-        map_info.width,
-        map_info.height,
-        false,
-        settings.graphics.xray_size != crate::settings_types::XRaySize::Off,
-    );
-
-    // Parse collision data
-    let map_collision = crate::ecs::collision::MapCollisionData::from_map_bytes(
-        map_bytes,
-        map_info.width,
-        map_info.height,
-        &prepared_map.wall_heights,
-    );
-    commands.insert_resource(map_collision);
-
-    // Bind map to renderer
-    let local_map_renderer = if let Some(renderer) = renderer {
-        Some(MapRenderer::bind_map(
-            &renderer.device,
-            &renderer.queue,
-            prepared_map,
-        ))
+    let xray = settings.graphics.xray_size != crate::settings_types::XRaySize::Off;
+
+    let local_map_renderer = if let Some(standby) = preload.take_standby(map_info, xray) {
+        info!(
+            map_id = map_info.map_id,
+            name = %map_info.name,
+            "Map change: using preloaded map"
+        );
+        commands.insert_resource(standby.collision);
+        Some(standby.map_renderer)
     } else {
-        Some(MapRenderer::empty())
+        info!(
+            map_id = map_info.map_id,
+            name = %map_info.name,
+            size = map_bytes.len(),
+            "Map change: preparing map (sync)"
+        );
+
+        let archive_ref = archive.inner().archive();
+        let prepared_map = MapRenderer::prepare_map(
+            archive_ref,
+            (*map_bytes).to_vec(),
+            map_info.width,
+            map_info.height,
+            map_info.map_flags().snow_tileset(),
+            xray,
+        );
+
+        // Parse collision data
+        let map_collision = crate::ecs::collision::MapCollisionData::from_map_bytes(
+            map_bytes,
+            map_info.width,
+            map_info.height,
+            &prepared_map.wall_heights,
+        );
+        commands.insert_resource(map_collision);
+
+        // Bind map to renderer
+        if let Some(renderer) = renderer {
+            Some(MapRenderer::bind_map(
+                &renderer.device,
+                &renderer.queue,
+                prepared_map,
+            ))
+        } else {
+            Some(MapRenderer::empty())
+        }
     };
 
     // Spawn map entity (scoped)
@@ -157,35 +178,17 @@ fn handle_map_set_info(
     local_map_renderer
 }
 
-fn handle_light_level(
-    camera: Option<&mut Camera>,
-    renderer: Option<&RendererState>,
-    kind: &packets::server::LightLevelKind,
-) {
-    use packets::server::LightLevelKind;
-
-    tracing::info!("Setting light level to {:?}", kind);
-
-    let (r, g, b) = match kind {
-        LightLevelKind::DarkestA => (-0.02745098, -0.011764706, -0.02745098),
-        LightLevelKind::DarkerB => (-0.011764706, -0.011764706, -0.011764706),
-        LightLevelKind::DarkB => (-0.011764706, -0.011764706, -0.011764706),
-        LightLevelKind::LighterA => (-0.011764706, -0.011764706, -0.011764706),
-        LightLevelKind::LightestA => (-0.011764706, -0.011764706, -0.011764706),
-        _ => (0.0, 0.0, 0.0),
-    };
-
-    if let (Some(camera), Some(renderer)) = (camera, renderer) {
-        camera.camera.set_tint(&renderer.queue, r, g, b);
-    }
-}
-
 pub fn handle_doors(
     renderer: Option<Res<RendererState>>,
     mut map_renderer_state: Option<ResMut<MapRendererState>>,
     mut map_collision: Option<ResMut<crate::ecs::collision::MapCollisionData>>,
     mut door_queue: ResMut<MapDoorQueue>,
 ) {
+    // Before borrowing the collision data mutably, which marks it changed
+    if door_queue.pending.is_empty() {
+        return;
+    }
+
     let (Some(renderer), Some(map_state), Some(map_collision)) = (
         renderer.as_deref(),
         map_renderer_state.as_deref_mut(),
@@ -194,10 +197,6 @@ pub fn handle_doors(
         return;
     };
 
-    if door_queue.pending.is_empty() {
-        return;
-    }
-
     for door in &door_queue.pending {
         map_state.map_renderer.set_wall_toggle_state(
             &renderer.queue,
@@ -222,3 +221,243 @@ pub fn map_animation_system(
             .update_animations(&renderer_state.queue);
     }
 }
+
+/// Keeps the map chunks around the main camera and the PiP view loaded.
+pub fn map_chunk_streaming_system(
+    map_renderer_state: Option<ResMut<MapRendererState>>,
+    renderer_state: Res<RendererState>,
+    camera: Option<Res<crate::Camera>>,
+    pip_target: Option<Res<PipTarget>>,
+    pip_camera: Option<Res<PipCamera>>,
+) {
+    let (Some(mut map_state), Some(camera)) = (map_renderer_state, camera) else {
+        return;
+    };
+    let mut views = vec![camera.camera.camera.visible_bounds()];
+    if pip_target.is_some_and(|target| target.last_position.is_some())
+        && let Some(pip_camera) = pip_camera
+    {
+        views.push(pip_camera.camera.camera.visible_bounds());
+    }
+    map_state
+        .map_renderer
+        .stream_chunks(&renderer_state.device, &views);
+}
+
+/// Hides the walls in front of the room the local player stands in, so a
+/// building's interior opens up on entering and closes again on leaving.
+pub fn interior_wall_system(
+    renderer: Res<RendererState>,
+    settings: Res<crate::settings::Settings>,
+    map_renderer_state: Option<ResMut<MapRendererState>>,
+    collision_table: Option<Res<WallCollisionTable>>,
+    map_collision: Option<Res<MapCollisionData>>,
+    player_query: Query<&Position, With<LocalPlayer>>,
+    mut last_tile: Local<Option<(u8, u8)>>,
+) {
+    let (Some(mut map_state), Some(collision_table), Some(map_collision)) =
+        (map_renderer_state, collision_table, map_collision)
+    else {
+        return;
+    };
+    let Ok(position) = player_query.single() else {
+        return;
+    };
+
+    let tile = (position.x.round() as u8, position.y.round() as u8);
+    // Doors opening or closing change the collision data
+    if *last_tile == Some(tile)
+        && !settings.is_changed()
+        && !map_state.is_added()
+        && !map_collision.is_changed()
+    {
+        return;
+    }
+    *last_tile = Some(tile);
+
+    let hidden = settings
+        .graphics
+        .open_interiors
+        .then(|| enclosing_room(tile, &collision_table, &map_collision))
+        .flatten()
+        .map(|room| walls_in_front(&room, &map_collision))
+        .unwrap_or_default();
+    map_state
+        .map_renderer
+        .set_hidden_walls(&renderer.queue, &hidden);
+}
+
+/// Blacks out the world while a map change is pending and fades the next map in.
+pub fn map_transition_system(
+    time: Res<Time>,
+    mut map_events: MessageReader<MapEvent>,
+    mut transition: ResMut<MapTransition>,
+) {
+    for event in map_events.read() {
+        match event {
+            MapEvent::Clear => *transition = MapTransition::Loading { elapsed: 0.0 },
+            MapEvent::SetInfo(..) => *transition = MapTransition::FadingIn { elapsed: 0.0 },
+            _ => {}
+        }
+    }
+
+    let delta = time.delta_secs();
+    match *transition {
+        MapTransition::Idle => {}
+        MapTransition::Loading { elapsed } => {
+            *transition = MapTransition::Loading {
+                elapsed: elapsed + delta,
+            };
+        }
+        MapTransition::FadingIn { elapsed } if elapsed + delta >= MAP_FADE_IN_SECS => {
+            *transition = MapTransition::Idle;
+        }
+        MapTransition::FadingIn { elapsed } => {
+            *transition = MapTransition::FadingIn {
+                elapsed: elapsed + delta,
+            };
+        }
+    }
+}
+
+/// Learns where exits lead: the tile the player left from becomes a warp to the map they arrived on.
+pub fn track_map_warps(
+    mut map_events: MessageReader<MapEvent>,
+    map_q: Query<&GameMap>,
+    player_q: Query<&Position, With<LocalPlayer>>,
+    mut preload: ResMut<MapPreload>,
+) {
+    for event in map_events.read() {
+        match event {
+            MapEvent::Clear => preload.departed = preload.last_seen,
+            MapEvent::SetInfo(map_info, _) => {
+                // A refresh re-sends the current map, which is not a warp
+                let departed = preload.departed.take();
+                if let Some((from_map, tile)) =
+                    departed.filter(|(from_map, _)| *from_map != map_info.map_id)
+                {
+                    preload.learn_warp(from_map, tile, map_info.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let (Some(map), Ok(position)) = (map_q.iter().next(), player_q.single()) {
+        let tile = (position.x.round() as u8, position.y.round() as u8);
+        preload.last_seen = Some((map.map_id, tile));
+    }
+}
+
+/// Starts decoding the map behind the nearest known exit in the background.
+pub fn preload_nearby_maps(
+    mut commands: Commands,
+    mut preload: ResMut<MapPreload>,
+    map_q: Query<&GameMap>,
+    player_q: Query<&Position, With<LocalPlayer>>,
+    archive: Res<GameFiles>,
+    settings: Res<crate::settings::Settings>,
+    map_store: Res<crate::map_store::MapStore>,
+    storage_config: Res<crate::resources::StorageConfig>,
+    current_session: Option<Res<crate::CurrentSession>>,
+) {
+    let (Some(map), Ok(position), Some(session)) =
+        (map_q.iter().next(), player_q.single(), current_session)
+    else {
+        return;
+    };
+
+    let nearest = preload.warps.get(&map.map_id).and_then(|warps| {
+        warps
+            .iter()
+            .map(|((x, y), info)| {
+                let dist = Vec2::new(*x as f32, *y as f32).distance(position.to_vec2());
+                (dist, info)
+            })
+            .filter(|(dist, _)| *dist <= PRELOAD_RADIUS)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, info)| info.clone())
+    });
+    let Some(info) = nearest else {
+        return;
+    };
+    if preload.target == Some((info.map_id, info.check_sum)) {
+        return;
+    }
+
+    // Only the cached copy can be preloaded; a stale one waits for the regular download
+    preload.target = Some((info.map_id, info.check_sum));
+    preload.standby = None;
+    let Some(map_bytes) = map_store.get_map(&storage_config, session.server_id, info.map_id) else {
+        return;
+    };
+    if crc::crc16(&map_bytes) != info.check_sum {
+        return;
+    }
+
+    let archive = archive.inner().archive().clone();
+    let xray = settings.graphics.xray_size != crate::settings_types::XRaySize::Off;
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let prepared_map = MapRenderer::prepare_map(
+            &archive,
+            map_bytes.clone(),
+            info.width,
+            info.height,
+            info.map_flags().snow_tileset(),
+            xray,
+        );
+        let collision = crate::ecs::collision::MapCollisionData::from_map_bytes(
+            &map_bytes,
+            info.width,
+            info.height,
+            &prepared_map.wall_heights,
+        );
+        MapPrepared {
+            map_id: info.map_id,
+            width: info.width,
+            height: info.height,
+            name: info.name,
+            check_sum: info.check_sum,
+            xray,
+            prepared_map,
+            collision,
+        }
+    });
+    commands.spawn((MapLoadingTask(task), InGameScoped));
+}
+
+/// Uploads a finished background map to the GPU and holds it until the player walks in.
+pub fn finish_map_preloads(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut MapLoadingTask)>,
+    renderer: Option<Res<RendererState>>,
+    mut preload: ResMut<MapPreload>,
+) {
+    for (entity, mut task) in &mut tasks {
+        let Some(prepared) = future::block_on(future::poll_once(&mut task.0)) else {
+            continue;
+        };
+        commands.entity(entity).despawn();
+
+        // Dropped if the player has since moved on to a different exit
+        if preload.target != Some((prepared.map_id, prepared.check_sum)) {
+            continue;
+        }
+        let Some(renderer) = renderer.as_deref() else {
+            continue;
+        };
+
+        info!(map_id = prepared.map_id, name = %prepared.name, "Preloaded map behind nearby exit");
+        preload.standby = Some(StandbyMap {
+            map_id: prepared.map_id,
+            check_sum: prepared.check_sum,
+            xray: prepared.xray,
+            map_renderer: MapRenderer::bind_map(
+                &renderer.device,
+                &renderer.queue,
+                prepared.prepared_map,
+            ),
+            collision: prepared.collision,
+        });
+    }
+}
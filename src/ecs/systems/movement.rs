@@ -4,15 +4,12 @@ use super::super::animation::{AnimationBundle, AnimationMode, AnimationType};
 use super::super::components::*;
 use crate::{
     ecs::collision::{MapCollisionData, WallCollisionTable},
-    events::{AudioEvent, EntityEvent, PlayerAction},
+    events::{AudioEvent, EntityEvent, PlayerAction, SoundOrigin},
+    settings_types::SoundCategory,
 };
 use bevy::prelude::*;
 use formats::{epf::EpfAnimationType, mpf::MpfAnimationType};
-use packets::{
-    client,
-    server::{ClientWalkResponseArgs, Sound},
-    types::BodyAnimationKind,
-};
+use packets::{client, server::ClientWalkResponseArgs, types::BodyAnimationKind};
 
 /// Handles local player movement from input events.
 /// Performs collision detection against walls and other entities.
@@ -166,9 +163,67 @@ fn handle_walk_request(
     Some(start_pos)
 }
 
+/// Duration of a remote entity's step when its walk packets arrive at a normal pace.
+const REMOTE_STEP_SECS: f32 = 0.5;
+/// Fastest step for entities whose packets arrive quickly, so batched updates
+/// don't turn into sprinting.
+const MIN_REMOTE_STEP_SECS: f32 = 0.2;
+/// Weight of the newest packet interval in the smoothed pace.
+const STEP_PACE_SMOOTHING: f32 = 0.3;
+/// Entities drawn further than this many tiles from where the server says a step
+/// began are snapped there rather than slid across the map.
+const SNAP_DISTANCE: f32 = 2.5;
+
+/// Tween for a remote walk that starts from where the entity is currently drawn,
+/// so a step arriving mid-tween (or several at once) continues smoothly instead of
+/// jumping back to the tile the server reported.
+fn remote_step_tween(drawn: Vec2, old_point: Vec2, end: Vec2, step_secs: f32) -> MovementTween {
+    if drawn.distance(old_point) > SNAP_DISTANCE {
+        return MovementTween {
+            start: old_point,
+            end,
+            elapsed: 0.0,
+            duration: step_secs,
+        };
+    }
+
+    // Partial steps keep the usual speed; when behind, catch up within one step
+    // instead of extrapolating past the last known tile.
+    let distance = drawn.distance(end);
+    MovementTween {
+        start: drawn,
+        end,
+        elapsed: 0.0,
+        duration: step_secs * distance.min(1.0),
+    }
+}
+
+/// Updates an entity's smoothed walk packet interval and returns the step duration to use.
+fn next_step_secs(pace: Option<&mut StepPace>, now: f32) -> (f32, Option<StepPace>) {
+    match pace {
+        Some(pace) => {
+            let interval = now - pace.last_step;
+            pace.interval += (interval - pace.interval) * STEP_PACE_SMOOTHING;
+            pace.last_step = now;
+            (
+                pace.interval.clamp(MIN_REMOTE_STEP_SECS, REMOTE_STEP_SECS),
+                None,
+            )
+        }
+        None => (
+            REMOTE_STEP_SECS,
+            Some(StepPace {
+                last_step: now,
+                interval: REMOTE_STEP_SECS,
+            }),
+        ),
+    }
+}
+
 /// Handles creature movement events from the server.
 pub fn entity_motion_system(
     mut commands: Commands,
+    time: Res<Time>,
     mut entity_events: MessageReader<EntityEvent>,
     mut moved_query: Query<
         (
@@ -180,6 +235,7 @@ pub fn entity_motion_system(
         ),
         Without<LocalPlayer>,
     >,
+    mut pace_query: Query<(&Position, Option<&mut StepPace>), Without<LocalPlayer>>,
 ) {
     for event in entity_events.read() {
         match event {
@@ -200,12 +256,20 @@ pub fn entity_motion_system(
                     let start_pos = Vec2::new(evt.old_point.0 as f32, evt.old_point.1 as f32);
                     let end_pos = start_pos + delta;
 
-                    commands.entity(entity).insert(MovementTween {
-                        start: start_pos,
-                        end: end_pos,
-                        elapsed: 0.0,
-                        duration: 0.5,
-                    });
+                    let tween = match pace_query.get_mut(entity) {
+                        Ok((position, pace)) => {
+                            let (step_secs, new_pace) =
+                                next_step_secs(pace.map(Mut::into_inner), time.elapsed_secs());
+                            if let Some(new_pace) = new_pace {
+                                commands.entity(entity).insert(new_pace);
+                            }
+                            remote_step_tween(position.to_vec2(), start_pos, end_pos, step_secs)
+                        }
+                        Err(_) => {
+                            remote_step_tween(start_pos, start_pos, end_pos, REMOTE_STEP_SECS)
+                        }
+                    };
+                    commands.entity(entity).insert(tween);
 
                     if let Some(instance) = instance {
                         if let Some(walk) = instance.instance.get_animation(MpfAnimationType::Walk)
@@ -288,7 +352,11 @@ pub fn player_animation_start_system(
         };
 
         if let Some(sound) = anim.sound {
-            audio_events.write(AudioEvent::PlaySound(Sound::Sound(sound)));
+            audio_events.write(AudioEvent::PlayEffect {
+                sound,
+                category: SoundCategory::Combat,
+                origin: SoundOrigin::Entity(anim.source_id),
+            });
         }
 
         // Handle player animations
@@ -564,3 +632,67 @@ pub fn player_reconciliation_system(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_step_continues_from_drawn_position() {
+        let tween = remote_step_tween(
+            Vec2::new(4.5, 3.0),
+            Vec2::new(5.0, 3.0),
+            Vec2::new(6.0, 3.0),
+            0.5,
+        );
+
+        assert_eq!(tween.start, Vec2::new(4.5, 3.0));
+        assert_eq!(tween.end, Vec2::new(6.0, 3.0));
+        assert_eq!(tween.duration, 0.5);
+    }
+
+    #[test]
+    fn remote_step_shortens_partial_steps() {
+        let tween = remote_step_tween(
+            Vec2::new(5.5, 3.0),
+            Vec2::new(5.0, 3.0),
+            Vec2::new(6.0, 3.0),
+            0.5,
+        );
+
+        assert_eq!(tween.duration, 0.25);
+    }
+
+    #[test]
+    fn remote_step_snaps_when_far_behind() {
+        let tween = remote_step_tween(
+            Vec2::new(1.0, 3.0),
+            Vec2::new(5.0, 3.0),
+            Vec2::new(6.0, 3.0),
+            0.5,
+        );
+
+        assert_eq!(tween.start, Vec2::new(5.0, 3.0));
+    }
+
+    #[test]
+    fn step_pace_follows_packet_interval_within_limits() {
+        let mut pace = StepPace {
+            last_step: 0.0,
+            interval: REMOTE_STEP_SECS,
+        };
+
+        for step in 1..=20 {
+            next_step_secs(Some(&mut pace), step as f32 * 0.3);
+        }
+        let (fast, _) = next_step_secs(Some(&mut pace), 6.3);
+        assert!((fast - 0.3).abs() < 0.01);
+
+        // Batched packets arrive together, but steps never get shorter than the floor
+        for _ in 0..20 {
+            next_step_secs(Some(&mut pace), 6.3);
+        }
+        let (batched, _) = next_step_secs(Some(&mut pace), 6.3);
+        assert_eq!(batched, MIN_REMOTE_STEP_SECS);
+    }
+}
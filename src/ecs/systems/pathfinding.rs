@@ -4,15 +4,20 @@ use std::collections::HashSet;
 
 use crate::ecs::collision::{MapCollisionData, WallCollisionTable, can_walk_to};
 use crate::ecs::components::{
-    Direction, GameMap, ItemSprite, LocalPlayer, MovementTween, NPC, PathTarget, PathfindingState,
-    Player, Position, occupied_tile,
+    Direction, EntityId, GameMap, ItemSprite, LocalPlayer, MovementTween, NPC, PathTarget,
+    PathfindingState, Player, Position, occupied_tile,
 };
+use crate::ecs::interaction::HoveredTile;
 use crate::ecs::spell_casting::SpellCastingState;
 use crate::events::{
     ClickSource, EntityClickEvent, InputSource, InteractionIntentAction, InteractionIntentEvent,
     InteractionTargetKind, PlayerAction, TileClickEvent,
 };
+use crate::input::{GameAction, GamepadConfig, UnifiedInputBindings};
+use crate::network::PacketOutbox;
 use crate::plugins::input::InputTimer;
+use crate::resources::PathPreview;
+use packets::client::Click;
 
 const STEP_COST_CLEAR: u32 = 2;
 const STEP_COST_NEAR_ENTITY: u32 = 3;
@@ -66,10 +71,85 @@ pub fn pathfinding_target_system(
             continue;
         };
 
-        insert_pathfinding_target(&mut commands, player_entity, destination, None);
+        insert_pathfinding_target(&mut commands, player_entity, destination, None, None);
     }
 }
 
+/// Works out the path a right-click on the hovered tile would walk, while the
+/// preview key is held. Only recomputed when the player or the hovered tile moves.
+pub fn path_preview_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<UnifiedInputBindings>,
+    gamepads: Query<&Gamepad>,
+    gamepad_config: Res<GamepadConfig>,
+    hovered_tile: Res<HoveredTile>,
+    player_query: Query<&Position, With<LocalPlayer>>,
+    map_query: Query<&GameMap>,
+    collision_table: Option<Res<WallCollisionTable>>,
+    map_collision: Option<Res<MapCollisionData>>,
+    entity_positions: Query<
+        (&Position, Option<&MovementTween>),
+        (Or<(With<NPC>, With<Player>)>, Without<LocalPlayer>),
+    >,
+    mut preview: ResMut<PathPreview>,
+) {
+    let held = bindings.is_pressed(
+        GameAction::PreviewPath,
+        &keyboard,
+        Some(&gamepads),
+        Some(&gamepad_config),
+    );
+    let (true, Some(target), Ok(player_pos), Ok(map)) = (
+        held,
+        hovered_tile.0,
+        player_query.single(),
+        map_query.single(),
+    ) else {
+        preview.set_if_neq(PathPreview::default());
+        return;
+    };
+    let start = (player_pos.x.round() as u8, player_pos.y.round() as u8);
+    if preview.target == Some(target) && preview.start == start {
+        return;
+    }
+
+    let goal = (
+        target.0.clamp(0, map.width as i32 - 1) as u8,
+        target.1.clamp(0, map.height as i32 - 1) as u8,
+    );
+    let occupied_tiles = collect_occupied_tiles(&entity_positions);
+    let path = if goal == start {
+        vec![start]
+    } else {
+        resolve_walk_target(
+            start,
+            goal,
+            map.width,
+            map.height,
+            collision_table.as_deref(),
+            map_collision.as_deref(),
+            &occupied_tiles,
+        )
+        .and_then(|destination| {
+            find_path(
+                start,
+                destination,
+                map.width,
+                map.height,
+                collision_table.as_deref(),
+                map_collision.as_deref(),
+                &occupied_tiles,
+            )
+        })
+        .unwrap_or_default()
+    };
+    *preview = PathPreview {
+        start,
+        target: Some(target),
+        path,
+    };
+}
+
 pub fn resolve_interaction_intents_system(
     spell_casting: Res<SpellCastingState>,
     mut entity_clicks: MessageReader<EntityClickEvent>,
@@ -211,9 +291,10 @@ pub fn consume_interaction_intents_system(
                     continue;
                 }
 
-                insert_pathfinding_target(&mut commands, player_entity, destination, None);
+                insert_pathfinding_target(&mut commands, player_entity, destination, None, None);
             }
-            InteractionIntentAction::ApproachAndFace => {
+            InteractionIntentAction::ApproachAndFace
+            | InteractionIntentAction::ApproachAndInteract => {
                 let Some(destination) = choose_best_approach_tile(
                     start,
                     (target_x, target_y),
@@ -226,11 +307,17 @@ pub fn consume_interaction_intents_system(
                     continue;
                 };
 
+                let interact_after = match event.action {
+                    InteractionIntentAction::ApproachAndInteract => event.target_entity,
+                    _ => None,
+                };
+
                 insert_pathfinding_target(
                     &mut commands,
                     player_entity,
                     destination,
                     Some((target_x, target_y)),
+                    interact_after,
                 );
             }
         }
@@ -260,6 +347,8 @@ pub fn pathfinding_execution_system(
     >,
     mut player_actions: MessageWriter<PlayerAction>,
     spell_casting: Res<SpellCastingState>,
+    entity_ids: Query<&EntityId>,
+    outbox: Res<PacketOutbox>,
 ) {
     let Ok((player_entity, player_pos, mut player_direction, tween, mut pathfinding)) =
         player_query.single_mut()
@@ -299,6 +388,7 @@ pub fn pathfinding_execution_system(
         y: target_y,
     } = pathfinding.target;
     let face_after = pathfinding.face_after;
+    let interact_after = pathfinding.interact_after;
     let start_x = player_pos.x.round() as u8;
     let start_y = player_pos.y.round() as u8;
 
@@ -315,6 +405,10 @@ pub fn pathfinding_execution_system(
             }
         }
 
+        if let Some(entity_id) = interact_after.and_then(|entity| entity_ids.get(entity).ok()) {
+            outbox.send(&Click::TargetEntity(entity_id.id));
+        }
+
         commands.entity(player_entity).remove::<PathfindingState>();
         return;
     }
@@ -532,6 +626,7 @@ fn insert_pathfinding_target(
     player_entity: Entity,
     destination: (u8, u8),
     face_after: Option<(u8, u8)>,
+    interact_after: Option<Entity>,
 ) {
     commands.entity(player_entity).insert(PathfindingState {
         target: PathTarget::Tile {
@@ -539,6 +634,7 @@ fn insert_pathfinding_target(
             y: destination.1,
         },
         face_after,
+        interact_after,
         retry_timer: None,
     });
 }
@@ -266,13 +266,14 @@ pub fn sync_players_to_renderer(
         &Player,
         &EntityId,
         Option<&TargetingHover>,
+        Has<HoverOutline>,
     )>,
     mut store_state: ResMut<PlayerAssetStoreState>,
     batch_state: Res<PlayerBatchState>,
 ) {
     let mut sprites_to_add = Vec::new();
     for (sprite_entity, child_of, sprite) in added_sprites.iter() {
-        if let Ok((position, direction, player, entity_id, targeting_hover)) =
+        if let Ok((position, direction, player, entity_id, targeting_hover, hovered)) =
             player_query.get(child_of.parent())
         {
             sprites_to_add.push((
@@ -284,6 +285,7 @@ pub fn sync_players_to_renderer(
                 player,
                 entity_id,
                 targeting_hover,
+                hovered,
             ));
         }
     }
@@ -297,6 +299,7 @@ pub fn sync_players_to_renderer(
         player,
         entity_id,
         targeting_hover,
+        hovered,
     ) in sprites_to_add
     {
         let gender = if player.is_male {
@@ -320,7 +323,7 @@ pub fn sync_players_to_renderer(
             position.x,
             position.y,
             entity_id.id,
-            InstanceFlag::None,
+            outline_flag(hovered, targeting_hover.is_some()),
             tint,
         );
 
@@ -342,6 +345,7 @@ pub fn update_player_sprites(
         &Direction,
         Option<&Animation>,
         Option<&TargetingHover>,
+        Has<HoverOutline>,
         &Children,
         &EntityId,
     )>,
@@ -352,21 +356,30 @@ pub fn update_player_sprites(
             Changed<Direction>,
             Changed<Animation>,
             Changed<TargetingHover>,
+            Changed<HoverOutline>,
         )>,
     >,
     mut removed_hovers: RemovedComponents<TargetingHover>,
+    mut removed_outlines: RemovedComponents<HoverOutline>,
     children_query: Query<(&PlayerSprite, &PlayerSpriteInstance)>,
 ) {
     let mut to_update = changed_query
         .iter()
         .collect::<std::collections::HashSet<_>>();
-    for entity in removed_hovers.read() {
+    for entity in removed_hovers.read().chain(removed_outlines.read()) {
         to_update.insert(entity);
     }
 
     for entity in to_update {
-        if let Ok((position, direction, animation, targeting_hover, children, _entity_id)) =
-            parent_query.get(entity)
+        if let Ok((
+            position,
+            direction,
+            animation,
+            targeting_hover,
+            hovered,
+            children,
+            _entity_id,
+        )) = parent_query.get(entity)
         {
             let (anim_type, frame_index) = match animation {
                 Some(anim) if anim.mode == AnimationMode::Finished => (EpfAnimationType::Idle, 0),
@@ -378,6 +391,7 @@ pub fn update_player_sprites(
             };
 
             let tint = targeting_hover.map(|t| t.tint).unwrap_or(Vec3::ZERO);
+            let flags = outline_flag(hovered, targeting_hover.is_some());
 
             for child_entity in children.iter() {
                 if let Ok((sprite, sprite_instance)) = children_query.get(child_entity) {
@@ -400,7 +414,7 @@ pub fn update_player_sprites(
                             sprite.color,
                             at,
                             fi,
-                            InstanceFlag::None,
+                            flags,
                             tint,
                         ) {
                             if at.is_emote() {
@@ -427,6 +441,17 @@ pub fn update_player_sprites(
     }
 }
 
+/// Outline for a sprite, preferring the spell-targeting outline over plain hover.
+fn outline_flag(hovered: bool, targeting: bool) -> InstanceFlag {
+    if targeting {
+        InstanceFlag::Target
+    } else if hovered {
+        InstanceFlag::Hover
+    } else {
+        InstanceFlag::None
+    }
+}
+
 /// Syncs creature positions and animations to the GPU.
 pub fn creature_movement_sync(
     renderer: Res<RendererState>,
@@ -436,6 +461,7 @@ pub fn creature_movement_sync(
         &Direction,
         &Animation,
         Option<&TargetingHover>,
+        Has<HoverOutline>,
         &EntityId,
     )>,
     changed_query: Query<
@@ -445,9 +471,11 @@ pub fn creature_movement_sync(
             Changed<Direction>,
             Changed<Animation>,
             Changed<TargetingHover>,
+            Changed<HoverOutline>,
         )>,
     >,
     mut removed_hovers: RemovedComponents<TargetingHover>,
+    mut removed_outlines: RemovedComponents<HoverOutline>,
     creatures_store: Res<CreatureAssetStoreState>,
     creatures_batch: Res<CreatureBatchState>,
 ) {
@@ -456,12 +484,14 @@ pub fn creature_movement_sync(
     let mut to_update = changed_query
         .iter()
         .collect::<std::collections::HashSet<_>>();
-    for entity in removed_hovers.read() {
+    for entity in removed_hovers.read().chain(removed_outlines.read()) {
         to_update.insert(entity);
     }
 
     for entity in to_update {
-        if let Ok((creature, pos, dir, anim, targeting_hover, _entity_id)) = query.get(entity) {
+        if let Ok((creature, pos, dir, anim, targeting_hover, hovered, _entity_id)) =
+            query.get(entity)
+        {
             let (actual_anim_type, actual_frame) = if anim.mode == AnimationMode::Finished {
                 (MpfAnimationType::Standing, 0)
             } else if let AnimationType::Creature(at) = anim.anim_type {
@@ -481,6 +511,7 @@ pub fn creature_movement_sync(
                     mpf_anim,
                     actual_frame,
                     *dir as u8,
+                    outline_flag(hovered, targeting_hover.is_some()),
                     tint,
                 );
             }
@@ -30,9 +30,24 @@ pub enum EntityEvent {
 #[derive(Debug, Clone, Message)]
 pub enum AudioEvent {
     PlaySound(server::Sound),
+    /// A sound effect heard from somewhere in the world, quieter the further away it is.
+    PlayEffect {
+        sound: u8,
+        category: crate::settings_types::SoundCategory,
+        origin: SoundOrigin,
+    },
     SetVolume(f32),
 }
 
+/// Where in the world a sound effect comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundOrigin {
+    Entity(u32),
+    Tile(u16, u16),
+    /// The client's own alerts, heard at full volume.
+    Local,
+}
+
 #[derive(Debug, Clone, Message)]
 pub enum InventoryEvent {
     // Inbound
@@ -62,6 +77,12 @@ pub enum AbilityEvent {
     UseSpell { slot: u8 },
 }
 
+/// A spell sent to the server, once chanting and targeting are done.
+#[derive(Debug, Clone, Message)]
+pub struct SpellCastEvent {
+    pub slot: u8,
+}
+
 #[derive(Debug, Clone, Message)]
 pub enum ChatEvent {
     ServerMessage(server::ServerMessage),
@@ -119,8 +140,13 @@ pub enum SessionEvent {
 #[derive(Debug, Clone, Message)]
 pub enum NetworkEvent {
     Packet(server::Codes, Vec<u8>),
+    /// Opcode the protocol definitions don't know about, with its raw payload.
+    UnknownPacket(u8, Vec<u8>),
     Connected,
     Disconnected,
+    /// The server went quiet for longer than its heartbeats allow, raised
+    /// before the socket itself gives up.
+    TimedOut,
 }
 
 // === Interaction Events ===
@@ -157,6 +183,8 @@ pub enum InteractionTargetKind {
 pub enum InteractionIntentAction {
     WalkToTile,
     ApproachAndFace,
+    /// Approach and face the target, then click it on arrival.
+    ApproachAndInteract,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -166,6 +194,16 @@ pub enum WorldContextAction {
     ViewProfile { entity: Entity, is_self: bool },
     PickUpItem { tile_x: i32, tile_y: i32 },
     SpeakToNpc { entity: Entity },
+    InviteToGroup { name: String },
+    NameCreature { sprite: u16 },
+    MarkPet { entity: Entity, sprite: u16 },
+    StartSpellTimer { name: String, sprite: u16, secs: u32 },
+    PromptSpellTimer { name: String, sprite: u16 },
+    StopSpellTimer { name: String },
+    ToggleItemConfirm { name: String },
+    ToggleAutoPickup { name: String, sprite: u16 },
+    PinTile { tile_x: u16, tile_y: u16 },
+    RemoveMapPin { tile_x: u16, tile_y: u16 },
     InteractWalls {
         walls: Vec<(i32, i32, bool)>,
     },
@@ -0,0 +1,226 @@
+//! Extension point for community plugins.
+//!
+//! A plugin is compiled into a custom build of the client. It implements
+//! [`ClientPlugin`], is added to a [`ClientPlugins`] registry, and the registry
+//! is handed to [`crate::main_with_plugins`] in place of
+//! [`crate::main_with_storage`]:
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use talgonite_lib::ext::{AppState, ChatEvent, ClientPlugin, ClientPlugins, Notify};
+//!
+//! struct BossAlerts;
+//!
+//! impl ClientPlugin for BossAlerts {
+//!     fn name(&self) -> &'static str {
+//!         "boss-alerts"
+//!     }
+//!
+//!     fn build(&self, app: &mut App) {
+//!         app.add_systems(Update, alert.run_if(in_state(AppState::InGame)));
+//!     }
+//! }
+//!
+//! fn alert(mut chat: MessageReader<ChatEvent>, mut notify: MessageWriter<Notify>) {
+//!     for event in chat.read() {
+//!         if let ChatEvent::ServerMessage(msg) = event {
+//!             if msg.message.contains("has awoken") {
+//!                 notify.write(Notify::new(msg.message.clone()));
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let plugins = ClientPlugins::new().add(BossAlerts);
+//!     talgonite_lib::main_with_plugins(data_dir(), plugins);
+//! }
+//! ```
+//!
+//! Everything re-exported from this module is kept source compatible across
+//! patch releases. Plugins can reach the rest of the crate, but other modules
+//! change without notice.
+//!
+//! # Messages
+//!
+//! Read with `MessageReader`:
+//!
+//! - [`ChatEvent`] - server and public chat, before the chat panel sees it
+//! - [`EntityEvent`] - entities appearing, moving, animating and taking damage
+//! - [`MapEvent`] - map changes, doors and light levels
+//! - [`SessionEvent`] - dialogs, menus, profiles and the world list
+//! - [`AbilityEvent`], [`InventoryEvent`] - skill, spell and item panes and cooldowns
+//! - [`NetworkEvent`] - raw packets, connects and disconnects
+//!
+//! Write with `MessageWriter`:
+//!
+//! - [`Notify`] - show a toast over the game view
+//! - [`AudioEvent`] - play a sound effect
+//!
+//! # Resources and components
+//!
+//! - [`CurrentSession`] - present while logged in
+//! - [`StorageConfig`] - data directories; use [`StorageConfig::plugin_dir`]
+//!   for a plugin's own files
+//! - [`ClientPluginNames`] - every plugin the client was built with
+//! - [`LocalPlayer`], [`Player`], [`NPC`], [`EntityId`], [`Position`] - world
+//!   entities, queried in [`AppState::InGame`]
+//! - [`InGameScoped`] - insert on a plugin's own entities to have them
+//!   despawned on logout
+
+use bevy::prelude::*;
+use game_ui::CoreToUi;
+
+pub use crate::CurrentSession;
+pub use crate::app_state::AppState;
+pub use crate::ecs::components::{EntityId, InGameScoped, LocalPlayer, NPC, Player, Position};
+pub use crate::events::{
+    AbilityEvent, AudioEvent, ChatEvent, EntityEvent, InventoryEvent, MapEvent, NetworkEvent,
+    SessionEvent, SoundOrigin,
+};
+pub use crate::resources::StorageConfig;
+pub use crate::settings_types::SoundCategory;
+
+use crate::webui::plugin::UiOutbound;
+
+/// A plugin built into the client. Like a Bevy [`Plugin`], but named so the
+/// client can report which ones it was built with.
+pub trait ClientPlugin: Send + Sync + 'static {
+    /// Short, unique, kebab-case name. Also names the plugin's data directory.
+    fn name(&self) -> &'static str;
+
+    fn build(&self, app: &mut App);
+}
+
+/// Plugins to build into the client, in the order they were added.
+#[derive(Default)]
+pub struct ClientPlugins {
+    plugins: Vec<Box<dyn ClientPlugin>>,
+}
+
+impl ClientPlugins {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(mut self, plugin: impl ClientPlugin) -> Self {
+        self.plugins.push(Box::new(plugin));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Builds every plugin into `app` after the core plugins. A name that is
+    /// already taken skips the plugin rather than sharing its data directory.
+    pub fn build(self, app: &mut App) {
+        let mut names = Vec::new();
+        for plugin in self.plugins {
+            let name = plugin.name();
+            if names.contains(&name) {
+                warn!("Skipping client plugin {}: name already in use", name);
+                continue;
+            }
+            info!("Loading client plugin {}", name);
+            plugin.build(app);
+            names.push(name);
+        }
+        app.insert_resource(ClientPluginNames(names));
+    }
+}
+
+/// Names of the plugins the client was built with.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct ClientPluginNames(pub Vec<&'static str>);
+
+/// Shows a toast over the game view.
+#[derive(Message, Debug, Clone)]
+pub struct Notify {
+    pub text: String,
+}
+
+impl Notify {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { text: text.into() }
+    }
+}
+
+/// Core side of the extension API, always built so plugins can rely on it.
+pub struct ExtPlugin;
+
+impl Plugin for ExtPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<Notify>()
+            .init_resource::<ClientPluginNames>()
+            .add_systems(Update, forward_notifications);
+    }
+}
+
+fn forward_notifications(
+    mut notifications: MessageReader<Notify>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    for notify in notifications.read() {
+        outbound.write(UiOutbound(CoreToUi::Toast {
+            text: notify.text.clone(),
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Marker(&'static str);
+
+    #[derive(Resource, Default)]
+    struct Built(Vec<&'static str>);
+
+    impl ClientPlugin for Marker {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn build(&self, app: &mut App) {
+            app.world_mut()
+                .get_resource_or_insert_with(Built::default)
+                .0
+                .push(self.0);
+        }
+    }
+
+    #[test]
+    fn builds_plugins_in_order_and_skips_duplicate_names() {
+        let mut app = App::new();
+        ClientPlugins::new()
+            .add(Marker("timers"))
+            .add(Marker("boss-alerts"))
+            .add(Marker("timers"))
+            .build(&mut app);
+
+        assert_eq!(app.world().resource::<Built>().0, ["timers", "boss-alerts"]);
+        assert_eq!(
+            app.world().resource::<ClientPluginNames>().0,
+            ["timers", "boss-alerts"]
+        );
+    }
+
+    #[test]
+    fn notifications_become_toasts() {
+        let mut app = App::new();
+        app.add_message::<UiOutbound>().add_plugins(ExtPlugin);
+        app.world_mut().write_message(Notify::new("Boss is up"));
+        app.update();
+
+        let outbound = app.world().resource::<Messages<UiOutbound>>();
+        let toasts: Vec<_> = outbound
+            .iter_current_update_messages()
+            .filter_map(|UiOutbound(msg)| match msg {
+                CoreToUi::Toast { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(toasts, ["Boss is up"]);
+    }
+}
@@ -1,12 +1,14 @@
 use bevy::prelude::*;
 
 pub use game_ui::slint_types::{
-    ChatMessage, ContextMenuEntry, ContextMenuState, Cooldown, DragDropState,
+    AbilityStatRow, ArchiveBrowserRow, BankRow, ChatMessage, ClassicSkin, CombatLogRow, ContextMenuEntry, ContextMenuState, Cooldown, CreatureNamePrompt,
+    DeathRecapRow, DialogConfirmPrompt, DialogHistoryRow, DragDropState, EntityInspectorRow,
     EquipmentSlotData, GameState, GroupInviteNotification, GroupMember, HotbarEntry,
-    InputBridge, InstallerState, InventoryItem, LegendMarkData, LobbyState, LoginBridge,
-    LoginState, MainWindow, MenuEntry, NpcDialogData, NpcDialogState, PlatformState,
-    ProfileData, SavedLoginItem, ServerItem, SettingsState, Skill, SlotPanelType, Spell,
-    WorldLabel, WorldListMemberUi, WorldMapNode,
+    HudWindowRect, InputBridge, InstallerState, InventoryItem, ItemConfirmPrompt, ItemLinkInfo, KeyBindingEntry,
+    KeyBindingSection, LegendMarkData, LobbyState, LoginBridge, LoginState, MainWindow, MapPinMarker, MapPinPrompt, MenuEntry,
+    NewsItem, NotificationRow, NpcDialogData, NpcDialogState, PathPoint, PetFrameData, PlatformState, ProfileData, SavedLoginItem, ServerItem,
+    ServerSubscriptionItem, SessionSummaryRow, SettingsState, Skill, SlotPanelType, Spell, SpellTimerChip, SpellTimerPrompt,
+    SplitStackPrompt, TelegraphTile, ThreatArrow, WorldLabel, WorldListMemberUi, WorldMapNode,
 };
 
 #[cfg(target_os = "android")]
@@ -16,8 +18,11 @@ use slint::ComponentHandle;
 
 pub mod app_state;
 pub mod audio;
+pub mod chat_filter;
+pub mod crash_report;
 pub mod ecs;
 pub mod events;
+pub mod ext;
 pub mod game_files;
 pub mod input;
 pub mod map_store;
@@ -30,15 +35,16 @@ pub mod rich_text;
 pub mod session;
 pub mod session_prelogin;
 pub mod settings;
+pub mod settings_migration;
 pub mod settings_types;
 pub mod slint_plugin;
 pub mod slint_support;
 pub mod webui;
 
 pub use resources::{
-    Camera, CreatureAssetStoreState, CreatureBatchState, EffectManagerState, ItemAssetStoreState,
-    ItemBatchState, MapRendererState, PlayerAssetStoreState, PlayerBatchState, PlayerPortraitState,
-    RendererState, StorageConfig, WindowSurface,
+    Camera, CreatureAssetStoreState, CreatureBatchState, EffectManagerState, FrameCompositorState,
+    ItemAssetStoreState, ItemBatchState, MapRendererState, PlayerAssetStoreState, PlayerBatchState,
+    PlayerPortraitState, RendererState, StorageConfig, WindowSurface,
 };
 
 #[derive(Resource)]
@@ -57,6 +63,7 @@ impl Plugin for CoreEventsPlugin {
             .add_message::<events::AudioEvent>()
             .add_message::<events::InventoryEvent>()
             .add_message::<events::AbilityEvent>()
+            .add_message::<events::SpellCastEvent>()
             .add_message::<events::ChatEvent>()
             .add_message::<events::PlayerAction>()
             .add_message::<events::SessionEvent>()
@@ -101,10 +108,18 @@ impl Plugin for CorePlugin {
 }
 
 pub fn main_with_storage(storage_root: std::path::PathBuf) {
+    main_with_plugins(storage_root, ext::ClientPlugins::new());
+}
+
+/// Runs the client with community plugins built in, see [`ext`].
+pub fn main_with_plugins(storage_root: std::path::PathBuf, plugins: ext::ClientPlugins) {
     init();
 
+    let storage = resources::StorageConfig::new(storage_root);
+    crash_report::install_panic_hook(storage.crash_reports_dir());
+
     let mut app = App::new();
-    app.insert_resource(resources::StorageConfig::new(storage_root))
+    app.insert_resource(storage)
         .add_message::<webui::plugin::UiOutbound>()
         .add_plugins(MinimalPlugins)
         .add_plugins(bevy::input::InputPlugin)
@@ -114,10 +129,53 @@ pub fn main_with_storage(storage_root: std::path::PathBuf) {
             session::runtime::SessionRuntimePlugin,
             plugins::installer::InstallerPlugin,
             plugins::mouse_interaction::MouseInteractionPlugin,
+            plugins::diagnostics::DiagnosticsPlugin,
+            plugins::debug_overlay::DebugOverlayPlugin,
+            plugins::stream_output::StreamOutputPlugin,
+            plugins::idle::IdlePlugin,
+            plugins::clock::ClockPlugin,
+            plugins::creature_names::CreatureNamesPlugin,
+            plugins::effect_sounds::EffectSoundsPlugin,
+            crash_report::CrashReportPlugin,
             webui::plugin::UiBridgePlugin,
             slint_plugin::SlintBridgePlugin,
         ))
-        .insert_resource(audio::Audio::default());
+        .add_plugins((
+            audio::AudioPlugin,
+            plugins::server_status::ServerStatusPlugin,
+            plugins::server_registry::ServerRegistryPlugin,
+            plugins::server_news::ServerNewsPlugin,
+            plugins::spell_timers::SpellTimersPlugin,
+            plugins::item_confirm::ItemConfirmPlugin,
+            plugins::dialog_confirm::DialogConfirmPlugin,
+            plugins::dialog_advance::DialogAdvancePlugin,
+            plugins::menu_shortcuts::MenuShortcutsPlugin,
+            plugins::auto_pickup::AutoPickupPlugin,
+            plugins::threat_indicators::ThreatIndicatorsPlugin,
+            plugins::attack_telegraphs::AttackTelegraphsPlugin,
+            plugins::map_pins::MapPinsPlugin,
+            plugins::anti_idle::AntiIdlePlugin,
+            plugins::pets::PetsPlugin,
+        ))
+        .add_plugins(plugins::bank::BankPlugin)
+        .add_plugins((
+            plugins::combat_log::CombatLogPlugin,
+            plugins::dialog_history::DialogHistoryPlugin,
+            plugins::death_recap::DeathRecapPlugin,
+            plugins::ability_stats::AbilityStatsPlugin,
+            plugins::world_cursor::WorldCursorPlugin,
+            plugins::notifications::NotificationsPlugin,
+            plugins::session_summary::SessionSummaryPlugin,
+            plugins::potato_mode::PotatoModePlugin,
+            plugins::texture_memory::TextureMemoryPlugin,
+            plugins::frame_capture::FrameCapturePlugin,
+            plugins::entity_inspector::EntityInspectorPlugin,
+            plugins::archive_browser::ArchiveBrowserPlugin,
+            plugins::map_ambience::MapAmbiencePlugin,
+            plugins::debug_console::DebugConsolePlugin,
+            ext::ExtPlugin,
+        ));
+    plugins.build(&mut app);
 
     // Attach Slint UI and hand off control of the rendering notifier to the plugin.
     let slint_app = slint_plugin::attach_slint_ui(app);
@@ -151,6 +209,7 @@ fn init() {
 
     let subscriber = tracing_subscriber::fmt()
         .with_env_filter(filter)
+        .with_writer(crash_report::LogTee)
         .without_time()
         .compact()
         .finish();
@@ -167,4 +167,110 @@ impl MetafileStore {
         );
         true
     }
+
+    /// Description of a spell from the class (`SClass*`) metafiles.
+    fn spell_description(&self, spell_name: &str) -> Option<&str> {
+        self.metafiles
+            .iter()
+            .filter(|(name, _)| name.starts_with("SClass"))
+            .flat_map(|(_, meta)| {
+                meta.entries
+                    .iter()
+                    .skip_while(|e| e.name != "Spell")
+                    .take_while(|e| e.name != "Spell_End")
+            })
+            .find(|e| e.name.trim().eq_ignore_ascii_case(spell_name))
+            .and_then(|e| e.fields.last())
+            .map(String::as_str)
+    }
+
+    /// Mana cost of a spell from the class metafiles. Those carry no dedicated
+    /// cost field, so the cost is read from the spell description.
+    pub fn spell_mana_cost(&self, spell_name: &str) -> Option<u32> {
+        self.spell_description(spell_name).and_then(parse_mana_cost)
+    }
+
+    /// How long a spell lasts in seconds, when its description says.
+    pub fn spell_duration_secs(&self, spell_name: &str) -> Option<u32> {
+        self.spell_description(spell_name)
+            .and_then(parse_duration_secs)
+    }
+}
+
+/// Find a cost such as "Mana: 30", "Mana cost 30" or "costs 30 MP" in free text.
+fn parse_mana_cost(description: &str) -> Option<u32> {
+    let words: Vec<String> = description
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_ascii_lowercase())
+        .collect();
+    words
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| *w == "mana" || *w == "mp")
+        .find_map(|(i, _)| {
+            let after = words[i + 1..].iter().take(2).find_map(|w| w.parse().ok());
+            after.or_else(|| i.checked_sub(1).and_then(|j| words[j].parse().ok()))
+        })
+}
+
+/// Find a duration such as "Duration: 30s", "lasts 2 minutes" or
+/// "for 45 seconds" in free text.
+fn parse_duration_secs(description: &str) -> Option<u32> {
+    let words: Vec<String> = description
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_ascii_lowercase())
+        .collect();
+    let unit_secs = |word: &str| match word {
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(1),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(60),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(3600),
+        _ => None,
+    };
+    // A number glued to its unit, e.g. "30s", or followed by one.
+    let amount = |i: usize| -> Option<u32> {
+        let word = &words[i];
+        let digits = word
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(word.len());
+        let count: u32 = word[..digits].parse().ok()?;
+        let unit = if digits < word.len() {
+            unit_secs(&word[digits..])?
+        } else {
+            unit_secs(words.get(i + 1)?)?
+        };
+        count.checked_mul(unit)
+    };
+
+    // Prefer a duration right after a cue word, then any number with a unit.
+    words
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| matches!(w.as_str(), "duration" | "lasts" | "last" | "for"))
+        .find_map(|(cue, _)| (cue + 1..words.len().min(cue + 4)).find_map(amount))
+        .or_else(|| (0..words.len()).find_map(amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_duration_secs, parse_mana_cost};
+
+    #[test]
+    fn mana_cost_from_description() {
+        assert_eq!(parse_mana_cost("Heals a target. Mana: 30"), Some(30));
+        assert_eq!(parse_mana_cost("Mana cost 120, lines 2"), Some(120));
+        assert_eq!(parse_mana_cost("Costs 45 MP to cast"), Some(45));
+        assert_eq!(parse_mana_cost("Restores your mana over time"), None);
+    }
+    #[test]
+    fn duration_from_description() {
+        assert_eq!(parse_duration_secs("Armor up. Duration: 30s"), Some(30));
+        assert_eq!(parse_duration_secs("Lasts 2 minutes. Mana: 50"), Some(120));
+        assert_eq!(
+            parse_duration_secs("Blinds a target for 45 seconds"),
+            Some(45)
+        );
+        assert_eq!(parse_duration_secs("Strikes 3 times, mana 20"), None);
+    }
 }
@@ -1,18 +1,295 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
 use bevy::ecs::resource::Resource;
+use game_types::RateLimits;
 use packets::ToBytes;
+use packets::client::Codes;
 
 #[derive(Resource, Clone)]
-pub struct PacketOutbox(pub async_channel::Sender<Vec<u8>>);
+pub struct PacketOutbox {
+    tx: async_channel::Sender<Vec<u8>>,
+    /// Set by the writer task while the rate limiter is holding packets back.
+    throttled: Arc<AtomicBool>,
+}
 
 impl Default for PacketOutbox {
     fn default() -> Self {
         let (tx, _rx) = async_channel::unbounded();
-        Self(tx)
+        Self::new(tx)
     }
 }
 
 impl PacketOutbox {
+    pub fn new(tx: async_channel::Sender<Vec<u8>>) -> Self {
+        Self {
+            tx,
+            throttled: Arc::default(),
+        }
+    }
+
     pub fn send<T: ToBytes>(&self, packet: &T) {
-        let _ = self.0.try_send(packet.to_bytes());
+        let _ = self.tx.try_send(packet.to_bytes());
+    }
+
+    /// Flag the writer task raises while packets wait on the rate limiter.
+    pub fn throttled_flag(&self) -> Arc<AtomicBool> {
+        self.throttled.clone()
+    }
+
+    pub fn is_throttled(&self) -> bool {
+        self.throttled.load(Ordering::Relaxed)
+    }
+}
+
+/// Kinds of outgoing packet limited separately, see `RateLimits`. The
+/// discriminant indexes `RateLimiter`'s buckets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketClass {
+    Movement,
+    Action,
+    Chat,
+}
+
+impl PacketClass {
+    pub const ALL: [PacketClass; 3] = [Self::Movement, Self::Action, Self::Chat];
+}
+
+fn is_any<const N: usize>(opcode: u8, codes: [Codes; N]) -> bool {
+    codes.into_iter().any(|code| code as u8 == opcode)
+}
+
+/// Rate-limited class of a client packet, `None` for packets that are never held back.
+pub fn packet_class(opcode: u8) -> Option<PacketClass> {
+    if is_any(opcode, [Codes::ClientWalk, Codes::Turn]) {
+        Some(PacketClass::Movement)
+    } else if is_any(opcode, [Codes::PublicMessage, Codes::Whisper, Codes::Emote]) {
+        Some(PacketClass::Chat)
+    } else if is_any(
+        opcode,
+        [
+            Codes::Pickup,
+            Codes::ItemDrop,
+            Codes::SpellUse,
+            Codes::Spacebar,
+            Codes::ItemUse,
+            Codes::GoldDrop,
+            Codes::ItemDroppedOnCreature,
+            Codes::GoldDroppedOnCreature,
+            Codes::SwapSlot,
+            Codes::RefreshRequest,
+            Codes::SkillUse,
+            Codes::Click,
+            Codes::Unequip,
+            Codes::BeginChant,
+            Codes::Chant,
+        ],
+    ) {
+        Some(PacketClass::Action)
+    } else {
+        None
+    }
+}
+
+/// Token bucket per packet class. Each holds up to a second's worth of
+/// packets and refills at the configured rate. Packets over the limit wait
+/// in a queue of their own class, so they never hold up other classes or
+/// the unlimited packets (heartbeats, keepalives) behind them.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: RateLimits,
+    /// Tokens left per class and when they were last topped up.
+    buckets: [(f32, Duration); 3],
+    /// Packets waiting on their class's bucket, oldest first.
+    held: [VecDeque<Vec<u8>>; 3],
+    started: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        let full = |rate: u16| (f32::from(rate), Duration::ZERO);
+        Self {
+            buckets: [
+                full(limits.movement),
+                full(limits.actions),
+                full(limits.chat),
+            ],
+            held: Default::default(),
+            limits,
+            started: Instant::now(),
+        }
+    }
+
+    /// Time since the limiter was created, the clock its buckets run on.
+    pub fn now(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Returns `packet` when it may go out now, otherwise queues it behind
+    /// the held packets of its class.
+    pub fn admit(&mut self, packet: Vec<u8>, now: Duration) -> Option<Vec<u8>> {
+        let opcode = packet.first().copied().unwrap_or_default();
+        let Some(class) = packet_class(opcode) else {
+            return Some(packet);
+        };
+        let held = &self.held[class as usize];
+        if held.is_empty() && self.delay(opcode, now).is_zero() {
+            return Some(packet);
+        }
+        self.held[class as usize].push_back(packet);
+        None
+    }
+
+    /// Held packets whose turn has come, in order within each class.
+    pub fn release(&mut self, now: Duration) -> Vec<Vec<u8>> {
+        let mut ready = Vec::new();
+        for class in PacketClass::ALL {
+            while !self.held[class as usize].is_empty() && self.take_token(class, now) {
+                ready.extend(self.held[class as usize].pop_front());
+            }
+        }
+        ready
+    }
+
+    /// How long until the next held packet may go out, `None` when none are held.
+    pub fn next_release(&mut self, now: Duration) -> Option<Duration> {
+        let mut next = None;
+        for class in PacketClass::ALL {
+            if !self.held[class as usize].is_empty() {
+                let wait = self.wait(class, now);
+                next = Some(next.map_or(wait, |next: Duration| next.min(wait)));
+            }
+        }
+        next
+    }
+
+    pub fn is_holding(&self) -> bool {
+        self.held.iter().any(|held| !held.is_empty())
+    }
+
+    /// How long a packet with `opcode` has to wait at `now`. When it can go
+    /// out straight away its token is taken.
+    pub fn delay(&mut self, opcode: u8, now: Duration) -> Duration {
+        let Some(class) = packet_class(opcode) else {
+            return Duration::ZERO;
+        };
+        let wait = self.wait(class, now);
+        if wait.is_zero() {
+            self.take_token(class, now);
+        }
+        wait
+    }
+
+    fn take_token(&mut self, class: PacketClass, now: Duration) -> bool {
+        if !self.wait(class, now).is_zero() {
+            return false;
+        }
+        if self.rate(class) > 0 {
+            self.buckets[class as usize].0 -= 1.0;
+        }
+        true
+    }
+
+    fn rate(&self, class: PacketClass) -> u16 {
+        match class {
+            PacketClass::Movement => self.limits.movement,
+            PacketClass::Action => self.limits.actions,
+            PacketClass::Chat => self.limits.chat,
+        }
+    }
+
+    /// Tops up the class's bucket to `now` and returns how long until it
+    /// holds a token, without taking one.
+    fn wait(&mut self, class: PacketClass, now: Duration) -> Duration {
+        let rate = self.rate(class);
+        if rate == 0 {
+            return Duration::ZERO;
+        }
+
+        let rate = f32::from(rate);
+        let (tokens, topped_up) = &mut self.buckets[class as usize];
+        *tokens = (*tokens + now.saturating_sub(*topped_up).as_secs_f32() * rate).min(rate);
+        *topped_up = now;
+        if *tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f32((1.0 - *tokens) / rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bursts_over_the_limit_wait() {
+        let mut limiter = RateLimiter::new(RateLimits {
+            movement: 0,
+            actions: 2,
+            chat: 1,
+        });
+        let start = Duration::from_secs(5);
+        let spell = Codes::SpellUse as u8;
+
+        assert!(limiter.delay(spell, start).is_zero());
+        assert!(limiter.delay(spell, start).is_zero());
+        let wait = limiter.delay(spell, start);
+        assert!((wait.as_secs_f32() - 0.5).abs() < 1e-3);
+        assert!(
+            limiter
+                .delay(spell, start + wait + Duration::from_millis(1))
+                .is_zero()
+        );
+
+        // Classes are limited separately and unlimited ones never wait
+        assert!(limiter.delay(Codes::Whisper as u8, start).is_zero());
+        for _ in 0..50 {
+            assert!(limiter.delay(Codes::ClientWalk as u8, start).is_zero());
+            assert!(limiter.delay(Codes::HeartBeat as u8, start).is_zero());
+        }
+    }
+
+    #[test]
+    fn held_packets_dont_block_other_classes() {
+        let mut limiter = RateLimiter::new(RateLimits {
+            movement: 0,
+            actions: 10,
+            chat: 1,
+        });
+        let start = Duration::from_secs(5);
+        let whisper = |n: u8| vec![Codes::Whisper as u8, n];
+
+        assert!(limiter.admit(whisper(1), start).is_some());
+        assert!(limiter.admit(whisper(2), start).is_none());
+        assert!(limiter.admit(whisper(3), start).is_none());
+        assert!(limiter.is_holding());
+
+        // Walks and heartbeats pass the held chat straight away
+        let walk = vec![Codes::ClientWalk as u8];
+        assert_eq!(limiter.admit(walk.clone(), start), Some(walk));
+        let beat = vec![Codes::HeartBeat as u8];
+        assert_eq!(limiter.admit(beat.clone(), start), Some(beat));
+
+        let wait = limiter.next_release(start).unwrap();
+        assert!((wait.as_secs_f32() - 1.0).abs() < 1e-3);
+        assert!(limiter.release(start).is_empty());
+        assert_eq!(limiter.release(start + wait), [whisper(2)]);
+        assert_eq!(limiter.release(start + wait * 2), [whisper(3)]);
+        assert!(!limiter.is_holding());
+        assert_eq!(limiter.next_release(start + wait * 2), None);
+    }
+
+    #[test]
+    fn packets_are_classed_by_opcode() {
+        assert_eq!(packet_class(Codes::Turn as u8), Some(PacketClass::Movement));
+        assert_eq!(packet_class(Codes::Emote as u8), Some(PacketClass::Chat));
+        assert_eq!(
+            packet_class(Codes::SkillUse as u8),
+            Some(PacketClass::Action)
+        );
+        assert_eq!(packet_class(Codes::SynchronizeTicks as u8), None);
     }
 }
@@ -0,0 +1,268 @@
+//! How often each skill and spell is used this session and how often it works.
+//!
+//! A skill counts as used when it is pressed and a spell when it is sent,
+//! after any chanting and targeting. The server doesn't confirm abilities, so
+//! a use counts as a success unless a status message that reads like a failure
+//! ("failed", "cannot", "not", ...) arrives within `RESULT_WINDOW`. A skill
+//! going on cooldown confirms it straight away.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_ui::UiToCore;
+use packets::server::ServerMessageType;
+
+use crate::app_state::AppState;
+use crate::events::{AbilityEvent, ChatEvent, SpellCastEvent};
+use crate::webui::plugin::{AbilityState, UiInbound};
+
+const RESULT_WINDOW: Duration = Duration::from_millis(1200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbilityKind {
+    Skill,
+    Spell,
+}
+
+impl AbilityKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AbilityKind::Skill => "Skill",
+            AbilityKind::Spell => "Spell",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbilityStat {
+    pub kind: AbilityKind,
+    pub name: String,
+    pub uses: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PendingUse {
+    index: usize,
+    at: Duration,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct AbilityStats {
+    stats: Vec<AbilityStat>,
+    /// Latest use still waiting to hear whether it worked.
+    pending: Option<PendingUse>,
+}
+
+impl AbilityStats {
+    /// Abilities in the order they were first used.
+    pub fn stats(&self) -> &[AbilityStat] {
+        &self.stats
+    }
+
+    /// Uses of the most used ability, for scaling the heatmap.
+    pub fn most_uses(&self) -> u32 {
+        self.stats.iter().map(|stat| stat.uses).max().unwrap_or(0)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn used(&mut self, kind: AbilityKind, name: &str, now: Duration) {
+        self.resolve(true);
+        let index = match self
+            .stats
+            .iter()
+            .position(|stat| stat.kind == kind && stat.name == name)
+        {
+            Some(index) => index,
+            None => {
+                self.stats.push(AbilityStat {
+                    kind,
+                    name: name.to_owned(),
+                    uses: 0,
+                    succeeded: 0,
+                    failed: 0,
+                });
+                self.stats.len() - 1
+            }
+        };
+        self.stats[index].uses += 1;
+        self.pending = Some(PendingUse { index, at: now });
+    }
+
+    fn resolve(&mut self, succeeded: bool) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+        let stat = &mut self.stats[pending.index];
+        if succeeded {
+            stat.succeeded += 1;
+        } else {
+            stat.failed += 1;
+        }
+    }
+
+    fn skill_cooldown(&mut self, name: &str) {
+        if self.pending.is_some_and(|pending| {
+            let stat = &self.stats[pending.index];
+            stat.kind == AbilityKind::Skill && stat.name == name
+        }) {
+            self.resolve(true);
+        }
+    }
+
+    fn status_message(&mut self, text: &str) {
+        if self.pending.is_some() && is_failure_message(text) {
+            self.resolve(false);
+        }
+    }
+
+    fn waiting_since(&self) -> Option<Duration> {
+        self.pending.map(|pending| pending.at)
+    }
+}
+
+fn is_failure_message(text: &str) -> bool {
+    let text = text.to_lowercase();
+    text.contains("fail")
+        || text.contains("cannot")
+        || text.contains("can't")
+        || text.contains("too far")
+        || text
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| matches!(word, "not" | "already" | "miss" | "missed"))
+}
+
+pub struct AbilityStatsPlugin;
+
+impl Plugin for AbilityStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AbilityStats>()
+            .add_systems(OnExit(AppState::InGame), clear_ability_stats)
+            .add_systems(
+                Update,
+                (track_ability_stats, handle_ability_stats_input)
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn clear_ability_stats(mut stats: ResMut<AbilityStats>) {
+    stats.clear();
+}
+
+fn track_ability_stats(
+    time: Res<Time>,
+    abilities: Option<Res<AbilityState>>,
+    mut ability_events: MessageReader<AbilityEvent>,
+    mut cast_events: MessageReader<SpellCastEvent>,
+    mut chat_events: MessageReader<ChatEvent>,
+    mut stats: ResMut<AbilityStats>,
+) {
+    let now = time.elapsed();
+    let skill_name = |slot: u8| {
+        abilities
+            .as_ref()?
+            .skills
+            .iter()
+            .find(|skill| skill.slot == slot)
+            .map(|skill| skill.name.clone())
+    };
+
+    for event in ability_events.read() {
+        match event {
+            AbilityEvent::UseSkill { slot } => {
+                if let Some(name) = skill_name(*slot) {
+                    stats.used(AbilityKind::Skill, &name, now);
+                }
+            }
+            AbilityEvent::SkillCooldown { slot, .. } => {
+                if let Some(name) = skill_name(*slot) {
+                    stats.skill_cooldown(&name);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for SpellCastEvent { slot } in cast_events.read() {
+        let name = abilities
+            .as_ref()
+            .and_then(|abilities| abilities.spells.iter().find(|spell| spell.slot == *slot))
+            .map(|spell| spell.panel_name.clone());
+        if let Some(name) = name {
+            stats.used(AbilityKind::Spell, &name, now);
+        }
+    }
+
+    for event in chat_events.read() {
+        if let ChatEvent::ServerMessage(msg) = event
+            && matches!(
+                msg.message_type,
+                ServerMessageType::OrangeBar1
+                    | ServerMessageType::OrangeBar2
+                    | ServerMessageType::OrangeBar3
+                    | ServerMessageType::OrangeBar5
+                    | ServerMessageType::ActiveMessage
+            )
+        {
+            stats.status_message(&msg.message);
+        }
+    }
+
+    // Nothing said it failed, so it worked
+    if stats
+        .waiting_since()
+        .is_some_and(|at| now.saturating_sub(at) >= RESULT_WINDOW)
+    {
+        stats.resolve(true);
+    }
+}
+
+fn handle_ability_stats_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut stats: ResMut<AbilityStats>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        if let UiToCore::AbilityStatsReset = msg {
+            stats.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uses_resolve_from_messages_and_cooldowns() {
+        let mut stats = AbilityStats::default();
+        let now = Duration::ZERO;
+
+        stats.used(AbilityKind::Skill, "Assail", now);
+        stats.skill_cooldown("Assail");
+        stats.used(AbilityKind::Spell, "ard srad", now);
+        stats.status_message("Spell failed.");
+        stats.used(AbilityKind::Spell, "ard srad", now);
+        stats.status_message("You feel stronger.");
+        stats.used(AbilityKind::Skill, "Assail", now);
+
+        let assail = &stats.stats()[0];
+        assert_eq!((assail.uses, assail.succeeded, assail.failed), (2, 1, 0));
+        let srad = &stats.stats()[1];
+        assert_eq!((srad.uses, srad.succeeded, srad.failed), (2, 1, 1));
+        assert_eq!(stats.most_uses(), 2);
+    }
+
+    #[test]
+    fn failure_messages() {
+        assert!(is_failure_message("You cannot do that here."));
+        assert!(is_failure_message("Not enough mana"));
+        assert!(is_failure_message("That spell is already active"));
+        assert!(!is_failure_message("Nothing happens... yet"));
+        assert!(!is_failure_message("You cast Beag Ioc."));
+    }
+}
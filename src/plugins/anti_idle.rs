@@ -0,0 +1,206 @@
+//! Opt-in anti-idle for servers that disconnect characters left standing.
+//!
+//! Once the player has gone `IDLE_AFTER` without input (see `plugins::idle`),
+//! the character turns to face the way it already faces every 90 to 180
+//! seconds. It switches itself off after `gameplay.anti_idle_max_minutes` of
+//! idling, and as soon as a whisper arrives so nobody is left talking to an
+//! empty chair. A banner stays up the whole time it is on.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_ui::CoreToUi;
+use packets::server::ServerMessageType;
+
+use crate::app_state::AppState;
+use crate::ecs::components::{Direction, LocalPlayer};
+use crate::events::ChatEvent;
+use crate::network::PacketOutbox;
+use crate::plugins::idle::SocialStatusState;
+use crate::settings_types::Settings;
+use crate::webui::plugin::UiOutbound;
+
+const IDLE_AFTER: Duration = Duration::from_secs(60);
+const MIN_INTERVAL_SECS: u64 = 90;
+const MAX_INTERVAL_SECS: u64 = 180;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    Wait,
+    Turn,
+    Expired,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct AntiIdleState {
+    /// When anti-idle started covering the current stretch of idling.
+    covering_since: Option<Duration>,
+    next_turn: Option<Duration>,
+}
+
+impl AntiIdleState {
+    fn step(
+        &mut self,
+        now: Duration,
+        idle_for: Duration,
+        limit: Option<Duration>,
+        interval: impl Fn() -> Duration,
+    ) -> Step {
+        if idle_for < IDLE_AFTER {
+            *self = Self::default();
+            return Step::Wait;
+        }
+        let since = *self.covering_since.get_or_insert(now);
+        if limit.is_some_and(|limit| now.saturating_sub(since) >= limit) {
+            *self = Self::default();
+            return Step::Expired;
+        }
+        let next = *self.next_turn.get_or_insert_with(|| now + interval());
+        if now < next {
+            return Step::Wait;
+        }
+        self.next_turn = Some(now + interval());
+        Step::Turn
+    }
+
+    /// Whole minutes left before the limit, while covering idling.
+    fn minutes_left(&self, now: Duration, limit: Duration) -> Option<u64> {
+        let since = self.covering_since?;
+        let left = limit.saturating_sub(now.saturating_sub(since));
+        Some(left.as_secs().div_ceil(60))
+    }
+}
+
+/// Banner text while anti-idle is on, empty otherwise.
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct AntiIdleStatus(pub String);
+
+pub struct AntiIdlePlugin;
+
+impl Plugin for AntiIdlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AntiIdleState>()
+            .init_resource::<AntiIdleStatus>()
+            .add_systems(
+                Update,
+                run_anti_idle
+                    .after(crate::plugins::input::InputPumpSet)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_anti_idle);
+    }
+}
+
+fn reset_anti_idle(mut state: ResMut<AntiIdleState>, mut status: ResMut<AntiIdleStatus>) {
+    *state = AntiIdleState::default();
+    status.set_if_neq(AntiIdleStatus::default());
+}
+
+fn random_interval() -> Duration {
+    Duration::from_secs(rand::random_range(MIN_INTERVAL_SECS..=MAX_INTERVAL_SECS))
+}
+
+fn run_anti_idle(
+    time: Res<Time>,
+    mut settings: ResMut<Settings>,
+    social: Res<SocialStatusState>,
+    outbox: Option<Res<PacketOutbox>>,
+    player: Query<&Direction, With<LocalPlayer>>,
+    mut chat_events: MessageReader<ChatEvent>,
+    mut outbound: MessageWriter<UiOutbound>,
+    mut state: ResMut<AntiIdleState>,
+    mut status: ResMut<AntiIdleStatus>,
+) {
+    let mut whispered = false;
+    for event in chat_events.read() {
+        if let ChatEvent::ServerMessage(pkt) = event {
+            whispered |= pkt.message_type == ServerMessageType::Whisper;
+        }
+    }
+    if !settings.gameplay.anti_idle {
+        *state = AntiIdleState::default();
+        status.set_if_neq(AntiIdleStatus::default());
+        return;
+    }
+
+    let now = time.elapsed();
+    let limit = (settings.gameplay.anti_idle_max_minutes > 0)
+        .then(|| Duration::from_secs(u64::from(settings.gameplay.anti_idle_max_minutes) * 60));
+    let step = state.step(now, social.idle_for(), limit, random_interval);
+
+    let stopped = if whispered {
+        Some("Anti-idle turned off: you were whispered")
+    } else if step == Step::Expired {
+        Some("Anti-idle turned off: time limit reached")
+    } else {
+        None
+    };
+    if let Some(text) = stopped {
+        settings.gameplay.anti_idle = false;
+        *state = AntiIdleState::default();
+        status.set_if_neq(AntiIdleStatus::default());
+        outbound.write(UiOutbound(settings.to_sync_message()));
+        outbound.write(UiOutbound(CoreToUi::Toast { text: text.into() }));
+        return;
+    }
+
+    if step == Step::Turn
+        && let (Some(outbox), Ok(direction)) = (outbox, player.single())
+    {
+        outbox.send(&packets::client::Turn {
+            direction: (*direction).into(),
+        });
+    }
+
+    let text = match limit.and_then(|limit| state.minutes_left(now, limit)) {
+        Some(minutes) => format!("Anti-idle on, stops in {} min", minutes),
+        None => "Anti-idle on".to_string(),
+    };
+    status.set_if_neq(AntiIdleStatus(text));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(100);
+
+    #[test]
+    fn turns_only_while_idle() {
+        let mut state = AntiIdleState::default();
+        let start = Duration::from_secs(1000);
+        assert_eq!(
+            state.step(start, Duration::from_secs(5), None, || INTERVAL),
+            Step::Wait
+        );
+        assert_eq!(state.step(start, IDLE_AFTER, None, || INTERVAL), Step::Wait);
+        assert_eq!(
+            state.step(start + INTERVAL, IDLE_AFTER + INTERVAL, None, || INTERVAL),
+            Step::Turn
+        );
+        assert_eq!(
+            state.step(start + INTERVAL, IDLE_AFTER + INTERVAL, None, || INTERVAL),
+            Step::Wait
+        );
+
+        // Input starts the wait over
+        state.step(start + INTERVAL, Duration::ZERO, None, || INTERVAL);
+        assert_eq!(state.next_turn, None);
+    }
+
+    #[test]
+    fn switches_off_at_the_limit() {
+        let mut state = AntiIdleState::default();
+        let limit = Some(Duration::from_secs(30 * 60));
+        let start = Duration::from_secs(1000);
+        state.step(start, IDLE_AFTER, limit, || INTERVAL);
+        assert_eq!(
+            state.minutes_left(start + Duration::from_secs(90), limit.unwrap()),
+            Some(29)
+        );
+        assert_eq!(
+            state.step(start + limit.unwrap(), IDLE_AFTER, limit, || INTERVAL),
+            Step::Expired
+        );
+    }
+}
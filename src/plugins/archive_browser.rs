@@ -0,0 +1,567 @@
+//! Developer window for browsing `data.arx`, previewing palettized sprites
+//! with the palette the renderer would pick, to track down wrong colors.
+//!
+//! Toggled with [`GameAction::ArchiveBrowser`] while `graphics.debug_overlay`
+//! is enabled, like the entity inspector. The palette row is chosen the way
+//! the item and player renderers choose it, and the panel shows which palette
+//! table range matched. The preview is shaded on the CPU exactly as
+//! `fs_main` in `shader.wgsl` does: palette lookup, then the dye row mixed in
+//! by its alpha. The row and dye can be overridden to compare.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use bevy::prelude::*;
+use formats::epf::{EpfAnimation, EpfImage};
+use formats::mpf::MpfFile;
+use game_ui::UiToCore;
+use rangemap::RangeMap;
+
+use crate::app_state::AppState;
+use crate::game_files::GameFiles;
+use crate::input::{GameAction, GamepadConfig, UnifiedInputBindings};
+use crate::resources::StorageConfig;
+use crate::settings_types::Settings;
+use crate::slint_support::state_bridge::SlintWindow;
+use crate::webui::plugin::UiInbound;
+
+const MAX_ROWS: usize = 200;
+const ITEMS_PER_FILE: u16 = 266;
+const DYE_PALETTE: &str = "Legend/color0.ktx2";
+/// One RGBA palette per row of a palette texture.
+const PALETTE_ROW_BYTES: usize = 256 * 4;
+/// Letters with a `khanpal/pal<letter>.ktx2`, in the order the player
+/// renderer stacks them.
+const KHAN_PALETTES: [char; 11] = ['b', 'c', 'e', 'f', 'h', 'i', 'l', 'm', 'p', 'u', 'w'];
+
+#[derive(Resource, Default)]
+pub struct ArchiveBrowserState {
+    pub visible: bool,
+    /// Listed when the browser is first opened, as `(path, size)`.
+    entries: Option<Vec<(String, u64)>>,
+    query: String,
+    selected: Option<String>,
+    frame: usize,
+    dye: u8,
+    row_override: Option<usize>,
+    /// Loaded palette tables by path, `None` when the archive lacks one.
+    tables: HashMap<String, Option<RangeMap<u16, u16>>>,
+    list_dirty: bool,
+    preview_dirty: bool,
+}
+
+/// Where the palette for a sprite comes from, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteMatch {
+    /// Palette texture, one palette per row.
+    pub palette: String,
+    pub row: usize,
+    /// Row of `Legend/color0.ktx2` mixed over the palette, as the shader
+    /// does for player equipment.
+    pub dye: Option<usize>,
+    /// The table and range that matched, when a table was used.
+    pub table: Option<(String, Range<u16>)>,
+    /// Readable summary of how the row was picked.
+    pub reason: String,
+}
+
+/// Picks the palette the renderer would use for `frame` of the sprite at
+/// `path`. `load_table` fetches palette tables from the archive.
+pub fn resolve_palette(
+    path: &str,
+    frame: usize,
+    dye: u8,
+    mpf_palette: Option<u8>,
+    mut load_table: impl FnMut(&str) -> Option<RangeMap<u16, u16>>,
+) -> Option<PaletteMatch> {
+    let mut lookup = |table: &str, id: u16| {
+        let map = load_table(table)?;
+        let (range, row) = map.get_key_value(&id)?;
+        Some((*row as usize, (table.to_string(), range.clone())))
+    };
+
+    if let Some(sheet) = path
+        .strip_prefix("Legend/item")
+        .and_then(|rest| rest.strip_suffix(".epf.bin"))
+        .and_then(|number| number.parse::<u16>().ok())
+    {
+        let sprite = (sheet.max(1) - 1) * ITEMS_PER_FILE + frame as u16 + 1;
+        let found = lookup("Legend/item.tbl.bin", sprite);
+        return Some(PaletteMatch {
+            palette: "Legend/item.ktx2".into(),
+            row: found.as_ref().map_or(0, |(row, _)| *row),
+            dye: None,
+            reason: match &found {
+                Some((row, (table, range))) => format!(
+                    "item {} in {} {}..{}, row {}",
+                    sprite, table, range.start, range.end, row
+                ),
+                None => format!("item {} not in Legend/item.tbl.bin, row 0", sprite),
+            },
+            table: found.map(|(_, table)| table),
+        });
+    }
+
+    if path.starts_with("hades/mns") && path.ends_with(".mpf.bin") {
+        let row = mpf_palette.unwrap_or_default() as usize;
+        return Some(PaletteMatch {
+            palette: "hades/mns.ktx2".into(),
+            row,
+            dye: None,
+            table: None,
+            reason: format!("palette number {} from the MPF header", row),
+        });
+    }
+
+    let (dir, file) = path.strip_prefix("khan/")?.split_once('/')?;
+    let number: u16 = file.strip_suffix(".epfanim")?.parse().ok()?;
+    if dir == "em" {
+        return Some(body_palette(dye, "emote"));
+    }
+    let mut chars = dir.chars();
+    let (gender, prefix) = (chars.next()?, chars.next()?);
+    if chars.next().is_some() {
+        return None;
+    }
+    if matches!(prefix, 'm' | 'o') {
+        return Some(body_palette(
+            dye,
+            if prefix == 'm' { "body" } else { "face" },
+        ));
+    }
+
+    // See PlayerSpriteKey::prefix_for_palette and PlayerPalettes::get_palette_params
+    let sprite = number + if matches!(prefix, 'j' | 'i') { 1000 } else { 0 };
+    let palette_letter = match prefix {
+        's' => 'w',
+        'n' => 'b',
+        'g' => 'c',
+        // Pieces without a palette of their own sample the first one
+        other if !KHAN_PALETTES.contains(&other) => KHAN_PALETTES[0],
+        other => other,
+    };
+    let gendered = format!("khanpal/pal{}_{}.tbl.bin", prefix, gender_suffix(gender));
+    let base = format!("khanpal/pal{}.tbl.bin", prefix);
+    let found = lookup(&gendered, sprite).or_else(|| lookup(&base, sprite));
+    Some(PaletteMatch {
+        palette: format!("khanpal/pal{}.ktx2", palette_letter),
+        row: found.as_ref().map_or(0, |(row, _)| *row),
+        dye: Some(dye as usize),
+        reason: match &found {
+            Some((row, (table, range))) => format!(
+                "sprite {} in {} {}..{}, row {}",
+                sprite, table, range.start, range.end, row
+            ),
+            None => format!("sprite {} not in {} or {}, row 0", sprite, gendered, base),
+        },
+        table: found.map(|(_, table)| table),
+    })
+}
+
+fn gender_suffix(gender: char) -> char {
+    if gender == 'w' { 'f' } else { 'm' }
+}
+
+/// Bodies, faces and emotes use the dye color as the palette row instead.
+fn body_palette(dye: u8, kind: &str) -> PaletteMatch {
+    PaletteMatch {
+        palette: "khanpal/palm.ktx2".into(),
+        row: dye as usize,
+        dye: None,
+        table: None,
+        reason: format!("{} color {} is the palette row", kind, dye),
+    }
+}
+
+/// Colors palette indices as `fs_main` does. Index 0 is transparent.
+pub fn shade(indices: &[u8], palette: &[u8], dye: Option<&[u8]>) -> Vec<u8> {
+    let mut rgba = Vec::with_capacity(indices.len() * 4);
+    for &index in indices {
+        if index == 0 {
+            rgba.extend_from_slice(&[0; 4]);
+            continue;
+        }
+        let i = index as usize * 4;
+        let mut color = [palette[i], palette[i + 1], palette[i + 2], palette[i + 3]];
+        if let Some(dye) = dye {
+            let alpha = dye[i + 3] as u32;
+            for (c, d) in color.iter_mut().zip(&dye[i..i + 4]) {
+                *c = ((*c as u32 * (255 - alpha) + *d as u32 * alpha + 127) / 255) as u8;
+            }
+        }
+        rgba.extend_from_slice(&color);
+    }
+    rgba
+}
+
+/// A sprite frame as palette indices.
+struct Frame {
+    width: u32,
+    height: u32,
+    indices: Vec<u8>,
+}
+
+/// Every frame of an `.epf.bin`, `.mpf.bin` or `.epfanim`, and the MPF
+/// palette number.
+fn decode_frames(path: &str, bytes: &[u8]) -> anyhow::Result<(Vec<Frame>, Option<u8>)> {
+    let config = bincode::config::standard();
+    let epf_frames = |image: &EpfImage| {
+        image
+            .frames
+            .iter()
+            .map(|f| Frame {
+                width: f.right.saturating_sub(f.left) as u32,
+                height: f.bottom.saturating_sub(f.top) as u32,
+                indices: f.data.clone(),
+            })
+            .collect::<Vec<_>>()
+    };
+    if path.ends_with(".epf.bin") {
+        let (image, _): (EpfImage, _) = bincode::decode_from_slice(bytes, config)?;
+        Ok((epf_frames(&image), None))
+    } else if path.ends_with(".epfanim") {
+        let (animations, _): (Vec<EpfAnimation>, _) = bincode::decode_from_slice(bytes, config)?;
+        let frames = animations
+            .iter()
+            .flat_map(|a| epf_frames(&a.image))
+            .collect();
+        Ok((frames, None))
+    } else if path.ends_with(".mpf.bin") {
+        let (mpf, _): (MpfFile, _) = bincode::decode_from_slice(bytes, config)?;
+        let frames = mpf
+            .frames
+            .iter()
+            .map(|f| Frame {
+                width: (f.right - f.left).max(0) as u32,
+                height: (f.bottom - f.top).max(0) as u32,
+                indices: f.data.clone(),
+            })
+            .collect();
+        Ok((frames, Some(mpf.palette_number)))
+    } else {
+        anyhow::bail!("Only .epf.bin, .mpf.bin and .epfanim sprites can be previewed")
+    }
+}
+
+fn palette_row(game_files: &GameFiles, path: &str, row: usize) -> anyhow::Result<Vec<u8>> {
+    let bytes = game_files
+        .get_file(path)
+        .ok_or_else(|| anyhow::anyhow!("{} not found", path))?;
+    let (_, _, data) = rendering::texture::Texture::load_ktx2(&bytes)?;
+    data.get(row * PALETTE_ROW_BYTES..(row + 1) * PALETTE_ROW_BYTES)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} has no row {} ({} rows)",
+                path,
+                row,
+                data.len() / PALETTE_ROW_BYTES
+            )
+        })
+}
+
+pub struct ArchiveBrowserPlugin;
+
+impl Plugin for ArchiveBrowserPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ArchiveBrowserState>()
+            .add_systems(
+                Update,
+                (
+                    (toggle_archive_browser, handle_archive_browser_input),
+                    (refresh_archive_list, refresh_archive_preview).run_if(browser_visible),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_archive_browser);
+    }
+}
+
+fn browser_visible(state: Res<ArchiveBrowserState>) -> bool {
+    state.visible
+}
+
+fn reset_archive_browser(mut state: ResMut<ArchiveBrowserState>) {
+    *state = ArchiveBrowserState::default();
+}
+
+/// Shows or hides the browser, also used by the debug console.
+pub(crate) fn set_browser_visible(
+    state: &mut ArchiveBrowserState,
+    window: Option<&SlintWindow>,
+    visible: bool,
+) {
+    state.visible = visible;
+    state.list_dirty = true;
+    state.preview_dirty = true;
+    if let Some(strong) = window.and_then(|w| w.0.upgrade()) {
+        let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+        game_state.set_show_archive_browser(visible);
+    }
+}
+
+fn toggle_archive_browser(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<UnifiedInputBindings>,
+    gamepad_query: Query<&Gamepad>,
+    gamepad_config: Res<GamepadConfig>,
+    settings: Res<Settings>,
+    mut state: ResMut<ArchiveBrowserState>,
+    window: Option<Res<SlintWindow>>,
+) {
+    if !settings.graphics.debug_overlay {
+        if state.visible {
+            set_browser_visible(&mut state, window.as_deref(), false);
+        }
+    } else if bindings.is_just_pressed(
+        GameAction::ArchiveBrowser,
+        &keyboard_input,
+        Some(&gamepad_query),
+        Some(&gamepad_config),
+    ) {
+        let visible = !state.visible;
+        set_browser_visible(&mut state, window.as_deref(), visible);
+    }
+}
+
+fn handle_archive_browser_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut state: ResMut<ArchiveBrowserState>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::ArchiveBrowserSearch { query } => {
+                state.query = query.clone();
+                state.list_dirty = true;
+            }
+            UiToCore::ArchiveBrowserSelect { path } => {
+                state.selected = Some(path.clone());
+                state.frame = 0;
+                state.preview_dirty = true;
+            }
+            UiToCore::ArchiveBrowserView { frame, dye, row } => {
+                state.frame = *frame;
+                state.dye = *dye;
+                state.row_override = *row;
+                state.preview_dirty = true;
+            }
+            UiToCore::ArchiveBrowserClose => {
+                state.visible = false;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn refresh_archive_list(
+    mut state: ResMut<ArchiveBrowserState>,
+    storage: Res<StorageConfig>,
+    window: Option<Res<SlintWindow>>,
+) {
+    if !state.list_dirty {
+        return;
+    }
+    state.list_dirty = false;
+    let Some(strong) = window.and_then(|w| w.0.upgrade()) else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+
+    if state.entries.is_none() {
+        match installer::archive::list(&storage.data_arx_path()) {
+            Ok(entries) => {
+                let mut entries: Vec<(String, u64)> =
+                    entries.into_iter().map(|e| (e.path, e.size)).collect();
+                entries.sort();
+                state.entries = Some(entries);
+            }
+            Err(e) => {
+                game_state
+                    .set_archive_browser_summary(format!("Failed to list archive: {}", e).into());
+                return;
+            }
+        }
+    }
+
+    let query = state.query.trim().to_lowercase();
+    let entries = state.entries.as_deref().unwrap_or_default();
+    let matching: Vec<&(String, u64)> = entries
+        .iter()
+        .filter(|(path, _)| path.to_lowercase().contains(&query))
+        .collect();
+    let rows: Vec<crate::ArchiveBrowserRow> = matching
+        .iter()
+        .take(MAX_ROWS)
+        .map(|(path, size)| crate::ArchiveBrowserRow {
+            path: slint::SharedString::from(path.as_str()),
+            size: slint::SharedString::from(format!("{:.1} KiB", *size as f64 / 1024.0)),
+        })
+        .collect();
+    game_state.set_archive_browser_summary(slint::SharedString::from(
+        if matching.len() > rows.len() {
+            format!("Showing {} of {} files", rows.len(), matching.len())
+        } else {
+            format!("{} files", matching.len())
+        },
+    ));
+    game_state.set_archive_browser_entries(slint::ModelRc::new(slint::VecModel::from(rows)));
+}
+
+fn refresh_archive_preview(
+    mut state: ResMut<ArchiveBrowserState>,
+    game_files: Option<Res<GameFiles>>,
+    window: Option<Res<SlintWindow>>,
+) {
+    if !state.preview_dirty {
+        return;
+    }
+    state.preview_dirty = false;
+    let (Some(strong), Some(game_files)) = (window.and_then(|w| w.0.upgrade()), game_files) else {
+        return;
+    };
+    let Some(path) = state.selected.clone() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    game_state.set_archive_browser_selected(slint::SharedString::from(path.as_str()));
+
+    let result = preview(&mut state, &game_files, &path);
+    let (image, frames, palette, error) = match result {
+        Ok((image, frames, palette)) => (image, frames, palette, String::new()),
+        Err(e) => (slint::Image::default(), 0, String::new(), e.to_string()),
+    };
+    game_state.set_archive_browser_preview(image);
+    game_state.set_archive_browser_frame_count(frames as i32);
+    game_state.set_archive_browser_palette(palette.into());
+    game_state.set_archive_browser_error(error.into());
+}
+
+/// The shaded frame, how many frames there are, and a description of the
+/// palette used.
+fn preview(
+    state: &mut ArchiveBrowserState,
+    game_files: &GameFiles,
+    path: &str,
+) -> anyhow::Result<(slint::Image, usize, String)> {
+    let bytes = game_files
+        .get_file(path)
+        .ok_or_else(|| anyhow::anyhow!("{} not found", path))?;
+    let (frames, mpf_palette) = decode_frames(path, &bytes)?;
+    let frame_index = state.frame.min(frames.len().saturating_sub(1));
+    let frame = frames
+        .get(frame_index)
+        .ok_or_else(|| anyhow::anyhow!("{} has no frames", path))?;
+
+    let tables = &mut state.tables;
+    let found = resolve_palette(path, frame_index, state.dye, mpf_palette, |table| {
+        tables
+            .entry(table.to_string())
+            .or_insert_with(|| {
+                let data = game_files.get_file(table)?;
+                bincode::serde::decode_from_slice(&data, bincode::config::standard())
+                    .ok()
+                    .map(|(map, _)| map)
+            })
+            .clone()
+    });
+
+    let (palette, dye, mut description) = match &found {
+        Some(found) => {
+            let row = state.row_override.unwrap_or(found.row);
+            let palette = palette_row(game_files, &found.palette, row)?;
+            let dye = found
+                .dye
+                .map(|dye| palette_row(game_files, DYE_PALETTE, dye))
+                .transpose()?;
+            let mut description = format!("{} row {}: {}", found.palette, row, found.reason);
+            if let Some(dye) = found.dye {
+                description.push_str(&format!(", dye {} from {}", dye, DYE_PALETTE));
+            }
+            (palette, dye, description)
+        }
+        None => (
+            (0..=255u8).flat_map(|v| [v, v, v, 255]).collect(),
+            None,
+            "No palette rule for this path, shown as a grey ramp".to_string(),
+        ),
+    };
+    if found.is_some() && state.row_override.is_some() {
+        description.push_str(" (row overridden)");
+    }
+
+    let (w, h) = (frame.width, frame.height);
+    if w == 0 || h == 0 || frame.indices.len() < (w * h) as usize {
+        anyhow::bail!("Frame {} is empty or truncated", frame_index);
+    }
+    let rgba = shade(&frame.indices[..(w * h) as usize], &palette, dye.as_deref());
+    let mut buffer = slint::SharedPixelBuffer::<slint::Rgba8Pixel>::new(w, h);
+    buffer
+        .make_mut_slice()
+        .copy_from_slice(bytemuck::cast_slice(&rgba));
+    Ok((slint::Image::from_rgba8(buffer), frames.len(), description))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_comes_from_the_matching_table_range() {
+        let mut items = RangeMap::new();
+        items.insert(270..300, 14);
+        let mut female = RangeMap::new();
+        female.insert(1005..1010, 3);
+        let tables = |path: &str| match path {
+            "Legend/item.tbl.bin" => Some(items.clone()),
+            "khanpal/pali_f.tbl.bin" => Some(female.clone()),
+            _ => None,
+        };
+
+        // Second sheet, frame 10 is item 277
+        let found = resolve_palette("Legend/item002.epf.bin", 10, 0, None, tables).unwrap();
+        assert_eq!(
+            (found.palette.as_str(), found.row),
+            ("Legend/item.ktx2", 14)
+        );
+        assert_eq!(found.table, Some(("Legend/item.tbl.bin".into(), 270..300)));
+
+        let found = resolve_palette("khan/wi/007.epfanim", 0, 9, None, tables).unwrap();
+        assert_eq!(
+            (found.palette.as_str(), found.row),
+            ("khanpal/pali.ktx2", 3)
+        );
+        assert_eq!(found.dye, Some(9));
+
+        let found = resolve_palette("khan/ms/002.epfanim", 0, 9, None, tables).unwrap();
+        assert_eq!(
+            (found.palette.as_str(), found.row),
+            ("khanpal/palw.ktx2", 0)
+        );
+        assert_eq!(found.table, None);
+
+        let found = resolve_palette("khan/mm/001.epfanim", 0, 4, None, tables).unwrap();
+        assert_eq!((found.row, found.dye), (4, None));
+
+        let found = resolve_palette("hades/mns012.mpf.bin", 0, 0, Some(7), tables).unwrap();
+        assert_eq!(found.row, 7);
+        assert!(resolve_palette("seo/tile.ktx2", 0, 0, None, tables).is_none());
+    }
+
+    #[test]
+    fn dye_is_mixed_in_by_its_alpha() {
+        let mut palette = vec![0u8; PALETTE_ROW_BYTES];
+        palette[4..8].copy_from_slice(&[200, 100, 0, 255]);
+        let mut dye = vec![0u8; PALETTE_ROW_BYTES];
+        dye[4..8].copy_from_slice(&[0, 100, 200, 255]);
+
+        assert_eq!(
+            shade(&[0, 1], &palette, None),
+            [0, 0, 0, 0, 200, 100, 0, 255]
+        );
+        // A transparent dye entry leaves the palette color alone
+        let clear = vec![0u8; PALETTE_ROW_BYTES];
+        assert_eq!(shade(&[1], &palette, Some(&clear)), [200, 100, 0, 255]);
+        assert_eq!(shade(&[1], &palette, Some(&dye)), [0, 100, 200, 255]);
+    }
+}
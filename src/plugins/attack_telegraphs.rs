@@ -0,0 +1,239 @@
+//! Flashes the tiles a creature is about to hit, so the player can step out
+//! of the way.
+//!
+//! When a creature plays an attack animation while facing the player, the
+//! tiles from it up to the player are marked: the one in front of it for a
+//! swing, the whole line for something that shoots from further away. The
+//! server says nothing about where an attack lands, so this is a guess from
+//! the animation and the creature's facing, and is off unless switched on
+//! with `GraphicsSettings::attack_telegraphs`.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use packets::server::VisibleEntityType;
+use packets::types::BodyAnimationKind;
+
+use crate::app_state::AppState;
+use crate::ecs::components::{Direction, EntityId, LocalPlayer, NPC, Position};
+use crate::events::EntityEvent;
+
+/// How long a telegraph stays up, fading out.
+const TELEGRAPH_TIME: Duration = Duration::from_millis(700);
+/// Furthest a creature in line with the player is taken to be aiming at them.
+const TELEGRAPH_REACH: i32 = 8;
+
+pub fn is_attack(kind: BodyAnimationKind) -> bool {
+    matches!(
+        kind,
+        BodyAnimationKind::Assail
+            | BodyAnimationKind::TwoHandAtk
+            | BodyAnimationKind::Kick
+            | BodyAnimationKind::Punch
+            | BodyAnimationKind::RoundHouseKick
+            | BodyAnimationKind::Stab
+            | BodyAnimationKind::DoubleStab
+            | BodyAnimationKind::Swipe
+            | BodyAnimationKind::HeavySwipe
+            | BodyAnimationKind::JumpAttack
+            | BodyAnimationKind::BowShot
+            | BodyAnimationKind::HeavyBowShot
+            | BodyAnimationKind::LongBowShot
+    )
+}
+
+/// Tiles from in front of a creature at `from` facing `facing` up to the
+/// player at `player`, or `None` when the player isn't in line within reach.
+pub fn attack_path(
+    from: (i32, i32),
+    facing: Direction,
+    player: (i32, i32),
+) -> Option<Vec<(i32, i32)>> {
+    let (dx, dy) = facing.delta();
+    let (dx, dy) = (dx as i32, dy as i32);
+    let offset = (player.0 - from.0, player.1 - from.1);
+    // Steps along the facing, with no sideways offset
+    let steps = if dx != 0 {
+        (offset.1 == 0).then_some(offset.0 * dx)?
+    } else {
+        (offset.0 == 0).then_some(offset.1 * dy)?
+    };
+    if !(1..=TELEGRAPH_REACH).contains(&steps) {
+        return None;
+    }
+    Some(
+        (1..=steps)
+            .map(|step| (from.0 + dx * step, from.1 + dy * step))
+            .collect(),
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TelegraphTile {
+    pub x: i32,
+    pub y: i32,
+    /// 1 when the attack starts, fading to 0.
+    pub strength: f32,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct AttackTelegraphs {
+    /// When each attack started and the tiles it covers.
+    attacks: Vec<(Duration, Vec<(i32, i32)>)>,
+}
+
+impl AttackTelegraphs {
+    fn record(&mut self, tiles: Vec<(i32, i32)>, now: Duration) {
+        self.attacks.push((now, tiles));
+    }
+
+    /// Every flashing tile once, at the strength of its latest attack.
+    fn tiles(&self, now: Duration) -> Vec<TelegraphTile> {
+        let mut tiles: Vec<TelegraphTile> = Vec::new();
+        for (started, attack) in &self.attacks {
+            let age = now.saturating_sub(*started);
+            if age >= TELEGRAPH_TIME {
+                continue;
+            }
+            let strength = 1.0 - age.as_secs_f32() / TELEGRAPH_TIME.as_secs_f32();
+            for &(x, y) in attack {
+                match tiles.iter_mut().find(|tile| (tile.x, tile.y) == (x, y)) {
+                    Some(tile) => tile.strength = tile.strength.max(strength),
+                    None => tiles.push(TelegraphTile { x, y, strength }),
+                }
+            }
+        }
+        tiles
+    }
+
+    fn forget_old(&mut self, now: Duration) {
+        self.attacks
+            .retain(|(started, _)| now.saturating_sub(*started) < TELEGRAPH_TIME);
+    }
+}
+
+/// Tiles for the UI, rebuilt every frame while any are flashing.
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct TelegraphTiles(pub Vec<TelegraphTile>);
+
+pub struct AttackTelegraphsPlugin;
+
+impl Plugin for AttackTelegraphsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttackTelegraphs>()
+            .init_resource::<TelegraphTiles>()
+            .add_systems(
+                Update,
+                (record_attacks, update_telegraph_tiles)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_telegraphs);
+    }
+}
+
+fn reset_telegraphs(mut telegraphs: ResMut<AttackTelegraphs>, mut tiles: ResMut<TelegraphTiles>) {
+    *telegraphs = AttackTelegraphs::default();
+    tiles.set_if_neq(TelegraphTiles::default());
+}
+
+fn record_attacks(
+    mut entity_events: MessageReader<EntityEvent>,
+    time: Res<Time>,
+    settings: Res<crate::settings::Settings>,
+    player: Query<&Position, With<LocalPlayer>>,
+    creatures: Query<(&EntityId, &NPC, &Position, &Direction)>,
+    mut telegraphs: ResMut<AttackTelegraphs>,
+) {
+    if !settings.graphics.attack_telegraphs {
+        entity_events.clear();
+        return;
+    }
+    let Ok(player_pos) = player.single() else {
+        return;
+    };
+    let player_tile = (player_pos.x.round() as i32, player_pos.y.round() as i32);
+
+    for event in entity_events.read() {
+        let EntityEvent::Animate(anim) = event else {
+            continue;
+        };
+        if !is_attack(anim.kind) {
+            continue;
+        }
+        let Some((_, _, pos, direction)) = creatures.iter().find(|(id, npc, ..)| {
+            id.id == anim.source_id
+                && matches!(
+                    npc.entity_type,
+                    VisibleEntityType::Normal | VisibleEntityType::WalkThrough
+                )
+        }) else {
+            continue;
+        };
+        let from = (pos.x.round() as i32, pos.y.round() as i32);
+        if let Some(tiles) = attack_path(from, *direction, player_tile) {
+            telegraphs.record(tiles, time.elapsed());
+        }
+    }
+}
+
+fn update_telegraph_tiles(
+    time: Res<Time>,
+    mut telegraphs: ResMut<AttackTelegraphs>,
+    mut tiles: ResMut<TelegraphTiles>,
+) {
+    let now = time.elapsed();
+    if !telegraphs.attacks.is_empty() {
+        telegraphs.forget_old(now);
+    }
+    tiles.set_if_neq(TelegraphTiles(telegraphs.tiles(now)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_run_up_to_a_player_in_line() {
+        assert_eq!(
+            attack_path((5, 5), Direction::Right, (6, 5)),
+            Some(vec![(6, 5)])
+        );
+        assert_eq!(
+            attack_path((5, 5), Direction::Up, (5, 2)),
+            Some(vec![(5, 4), (5, 3), (5, 2)])
+        );
+        // Behind, beside or too far away
+        assert_eq!(attack_path((5, 5), Direction::Up, (5, 6)), None);
+        assert_eq!(attack_path((5, 5), Direction::Right, (6, 6)), None);
+        assert_eq!(attack_path((0, 0), Direction::Down, (0, 20)), None);
+    }
+
+    #[test]
+    fn overlapping_attacks_flash_each_tile_once() {
+        let mut telegraphs = AttackTelegraphs::default();
+        telegraphs.record(vec![(1, 0), (2, 0)], Duration::ZERO);
+        telegraphs.record(vec![(2, 0)], TELEGRAPH_TIME / 2);
+
+        let tiles = telegraphs.tiles(TELEGRAPH_TIME / 2);
+        assert_eq!(tiles.len(), 2);
+        assert_eq!(
+            tiles[1],
+            TelegraphTile {
+                x: 2,
+                y: 0,
+                strength: 1.0
+            }
+        );
+
+        telegraphs.forget_old(TELEGRAPH_TIME);
+        assert_eq!(
+            telegraphs.tiles(TELEGRAPH_TIME),
+            vec![TelegraphTile {
+                x: 2,
+                y: 0,
+                strength: 0.5
+            }]
+        );
+    }
+}
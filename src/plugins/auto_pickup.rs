@@ -0,0 +1,141 @@
+//! Automatic pickup of loot that lands on the player's tile.
+//!
+//! Ground items only carry a sprite, so the filter matches gold piles by their
+//! sprites and listed items by the sprite they had in the inventory when they
+//! were added (right-click an inventory item). Pickups go out at most one per
+//! `PICKUP_INTERVAL`, and an item the server didn't hand over isn't asked for
+//! again until `RETRY_AFTER` has passed, so standing on a pile someone else
+//! owns doesn't flood the server with requests.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use packets::client::Pickup;
+
+use crate::app_state::AppState;
+use crate::ecs::components::{EntityId, ItemSprite, LocalPlayer, Position};
+use crate::network::PacketOutbox;
+use crate::settings_types::{GameplaySettings, Settings};
+
+/// Item sprites the common server implementations use for gold piles.
+const GOLD_SPRITES: std::ops::RangeInclusive<u16> = 137..=142;
+const PICKUP_INTERVAL: Duration = Duration::from_millis(400);
+const RETRY_AFTER: Duration = Duration::from_secs(5);
+
+#[derive(Resource, Default, Debug)]
+pub struct AutoPickupState {
+    last_sent: Option<Duration>,
+    /// Item ids already asked for, with when.
+    requested: HashMap<u32, Duration>,
+}
+
+impl AutoPickupState {
+    /// Whether a pickup for `id` may go out at `now`.
+    fn ready(&self, id: u32, now: Duration) -> bool {
+        let spaced = self
+            .last_sent
+            .is_none_or(|last| now.saturating_sub(last) >= PICKUP_INTERVAL);
+        let retried = self
+            .requested
+            .get(&id)
+            .is_none_or(|&at| now.saturating_sub(at) >= RETRY_AFTER);
+        spaced && retried
+    }
+
+    fn record(&mut self, id: u32, now: Duration) {
+        self.last_sent = Some(now);
+        self.requested.insert(id, now);
+        self.requested
+            .retain(|_, at| now.saturating_sub(*at) < RETRY_AFTER);
+    }
+}
+
+/// Whether the loot filter takes an item with this ground sprite.
+pub fn wants_item(gameplay: &GameplaySettings, sprite: u16) -> bool {
+    (gameplay.auto_pickup_gold && GOLD_SPRITES.contains(&sprite))
+        || gameplay
+            .auto_pickup_items
+            .iter()
+            .any(|item| item.sprite == sprite)
+}
+
+pub struct AutoPickupPlugin;
+
+impl Plugin for AutoPickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutoPickupState>()
+            .add_systems(Update, auto_pickup_items.run_if(in_state(AppState::InGame)))
+            .add_systems(OnExit(AppState::InGame), reset_auto_pickup);
+    }
+}
+
+fn reset_auto_pickup(mut state: ResMut<AutoPickupState>) {
+    *state = AutoPickupState::default();
+}
+
+fn auto_pickup_items(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    outbox: Option<Res<PacketOutbox>>,
+    player: Query<&Position, With<LocalPlayer>>,
+    items: Query<(&Position, &EntityId, &ItemSprite)>,
+    mut state: ResMut<AutoPickupState>,
+) {
+    if !settings.gameplay.auto_pickup {
+        return;
+    }
+    let (Some(outbox), Ok(player_pos)) = (outbox, player.single()) else {
+        return;
+    };
+    let now = time.elapsed();
+    let player_tile = (player_pos.x.round() as u16, player_pos.y.round() as u16);
+
+    let next = items.iter().find(|(pos, id, sprite)| {
+        (pos.x.round() as u16, pos.y.round() as u16) == player_tile
+            && wants_item(&settings.gameplay, sprite.id)
+            && state.ready(id.id, now)
+    });
+    if let Some((_, id, _)) = next {
+        outbox.send(&Pickup {
+            destination_slot: 0,
+            source_point: player_tile,
+        });
+        state.record(id.id, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings_types::AutoPickupItem;
+
+    #[test]
+    fn filter_takes_gold_and_listed_sprites() {
+        let mut gameplay = Settings::default().gameplay;
+        gameplay.auto_pickup_items.push(AutoPickupItem {
+            name: "Wolf's Fur".into(),
+            sprite: 55,
+        });
+
+        assert!(wants_item(&gameplay, 140));
+        assert!(wants_item(&gameplay, 55));
+        assert!(!wants_item(&gameplay, 56));
+
+        gameplay.auto_pickup_gold = false;
+        assert!(!wants_item(&gameplay, 140));
+    }
+
+    #[test]
+    fn pickups_are_spaced_and_not_repeated() {
+        let mut state = AutoPickupState::default();
+        let start = Duration::from_secs(10);
+        assert!(state.ready(1, start));
+        state.record(1, start);
+
+        assert!(!state.ready(2, start + Duration::from_millis(100)));
+        assert!(state.ready(2, start + PICKUP_INTERVAL));
+        assert!(!state.ready(1, start + PICKUP_INTERVAL));
+        assert!(state.ready(1, start + RETRY_AFTER));
+    }
+}
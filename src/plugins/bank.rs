@@ -0,0 +1,374 @@
+//! Bank viewer: the items on a banker's withdraw list, searchable and
+//! sortable, with "withdraw all" for items kept in several stacks.
+//!
+//! A withdraw list is an item menu reached through an option mentioning
+//! "withdraw", or whose text does. Entries with the same name are added up,
+//! counting the amount the server puts in each entry's price, or one for
+//! entries without one. The last list seen stays browsable after the menu
+//! closes. Withdrawing all of an item picks its first entry, answers the
+//! amount prompt with that entry's amount, and repeats as the list comes
+//! back until the item is gone from it.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_ui::{CoreToUi, UiToCore};
+use packets::client::{MenuInteraction, MenuInteractionArgs};
+use packets::server::{DisplayDialog, DisplayMenuPayload, ItemInfo};
+use packets::types::EntityType;
+
+use crate::app_state::AppState;
+use crate::events::SessionEvent;
+use crate::network::PacketOutbox;
+use crate::webui::plugin::{ActiveMenuContext, UiInbound, UiOutbound};
+
+/// How long to wait for the banker between withdrawals.
+const WITHDRAW_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether an option or menu text leads to a withdraw list.
+pub fn is_withdraw_text(text: &str) -> bool {
+    text.to_lowercase().contains("withdraw")
+}
+
+/// Amount held in one entry of a withdraw list.
+fn entry_amount(item: &ItemInfo) -> u32 {
+    item.cost.max(1) as u32
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BankItem {
+    pub name: String,
+    pub sprite: u16,
+    pub color: u8,
+    /// Amount across every entry with this name.
+    pub total: u32,
+    /// How many entries the item is split over.
+    pub stacks: u32,
+}
+
+/// One row per item name, in the order the banker lists them.
+pub fn group_items(items: &[ItemInfo]) -> Vec<BankItem> {
+    let mut grouped: Vec<BankItem> = Vec::new();
+    for item in items {
+        match grouped.iter_mut().find(|row| row.name == item.name) {
+            Some(row) => {
+                row.total += entry_amount(item);
+                row.stacks += 1;
+            }
+            None => grouped.push(BankItem {
+                name: item.name.clone(),
+                sprite: item.sprite,
+                color: item.color,
+                total: entry_amount(item),
+                stacks: 1,
+            }),
+        }
+    }
+    grouped
+}
+
+/// The banker whose menu is on screen, to answer it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Banker {
+    entity_type: EntityType,
+    entity_id: u32,
+    pursuit_id: u16,
+}
+
+impl Banker {
+    fn send(&self, topics: Vec<String>, outbox: &PacketOutbox) {
+        outbox.send(&MenuInteraction {
+            entity_type: self.entity_type,
+            entity_id: self.entity_id,
+            pursuit_id: self.pursuit_id,
+            args: MenuInteractionArgs::Topics(topics),
+        });
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct BankContents {
+    pub npc: String,
+    items: Vec<BankItem>,
+    /// Entries of the last list as sent, to pick from.
+    entries: Vec<ItemInfo>,
+    /// Local wall-clock time of the last list, "HH:MM".
+    pub updated: String,
+    /// Matched against item names; blank shows everything.
+    pub search: String,
+    pub sort_by_amount: bool,
+    /// Withdraw list on screen, when it is.
+    banker: Option<Banker>,
+    /// Item being withdrawn in full.
+    pub withdrawing: Option<String>,
+}
+
+impl BankContents {
+    pub fn is_open(&self) -> bool {
+        self.banker.is_some()
+    }
+
+    pub fn item_count(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Items matching the search, in the chosen order.
+    pub fn view(&self) -> Vec<&BankItem> {
+        let search = self.search.trim().to_lowercase();
+        let mut items: Vec<&BankItem> = self
+            .items
+            .iter()
+            .filter(|item| search.is_empty() || item.name.to_lowercase().contains(&search))
+            .collect();
+        if self.sort_by_amount {
+            items.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.name.cmp(&b.name)));
+        } else {
+            items.sort_by_key(|item| item.name.to_lowercase());
+        }
+        items
+    }
+
+    fn first_entry(&self, name: &str) -> Option<&ItemInfo> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+}
+
+/// Withdraw-all in progress.
+struct Withdrawal {
+    name: String,
+    /// Amount of the entry picked last, for the amount prompt.
+    amount: u32,
+    /// Entries left to pick, so a banker that never empties can't loop.
+    picks_left: usize,
+    deadline: Duration,
+}
+
+#[derive(Resource, Default)]
+struct BankWithdrawal(Option<Withdrawal>);
+
+/// Last option picked from an NPC menu, to tell a withdraw list from a shop.
+#[derive(Resource, Default)]
+struct LastPickedOption(String);
+
+pub struct BankPlugin;
+
+impl Plugin for BankPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BankContents>()
+            .init_resource::<BankWithdrawal>()
+            .init_resource::<LastPickedOption>()
+            .add_systems(OnExit(AppState::InGame), reset_bank)
+            .add_systems(
+                Update,
+                (handle_bank_input, track_bank_menus)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn reset_bank(
+    mut commands: Commands,
+    mut withdrawal: ResMut<BankWithdrawal>,
+    mut last_picked: ResMut<LastPickedOption>,
+) {
+    commands.insert_resource(BankContents::default());
+    withdrawal.0 = None;
+    last_picked.0.clear();
+}
+
+fn handle_bank_input(
+    mut inbound: MessageReader<UiInbound>,
+    time: Res<Time>,
+    menu_ctx: Res<ActiveMenuContext>,
+    outbox: Res<PacketOutbox>,
+    mut bank: ResMut<BankContents>,
+    mut withdrawal: ResMut<BankWithdrawal>,
+    mut last_picked: ResMut<LastPickedOption>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::MenuSelect { id, .. } => {
+                if let Some(text) = menu_ctx.option_text(*id) {
+                    last_picked.0 = text.to_owned();
+                }
+            }
+            UiToCore::MenuClose => {
+                bank.banker = None;
+                withdrawal.0 = None;
+                bank.withdrawing = None;
+            }
+            UiToCore::BankSearch { text } => bank.search = text.clone(),
+            UiToCore::BankSort { by_amount } => bank.sort_by_amount = *by_amount,
+            UiToCore::BankWithdrawAll { name } => {
+                let Some(banker) = bank.banker.filter(|_| withdrawal.0.is_none()) else {
+                    continue;
+                };
+                let Some(entry) = bank.first_entry(name) else {
+                    continue;
+                };
+                let amount = entry_amount(entry);
+                let picks_left = bank.entries.iter().filter(|e| &e.name == name).count();
+                banker.send(vec![name.clone()], &outbox);
+                withdrawal.0 = Some(Withdrawal {
+                    name: name.clone(),
+                    amount,
+                    picks_left: picks_left - 1,
+                    deadline: time.elapsed() + WITHDRAW_TIMEOUT,
+                });
+                bank.withdrawing = Some(name.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn track_bank_menus(
+    mut session_events: MessageReader<SessionEvent>,
+    time: Res<Time>,
+    outbox: Res<PacketOutbox>,
+    last_picked: Res<LastPickedOption>,
+    mut outbound: MessageWriter<UiOutbound>,
+    mut bank: ResMut<BankContents>,
+    mut withdrawal: ResMut<BankWithdrawal>,
+) {
+    let now = time.elapsed();
+    let mut stopped: Option<String> = None;
+
+    for event in session_events.read() {
+        match event {
+            SessionEvent::DisplayMenu(pkt) => {
+                let banker = Banker {
+                    entity_type: pkt.header.entity_type,
+                    entity_id: pkt.header.source_id,
+                    pursuit_id: 0,
+                };
+                let from_banker = bank
+                    .banker
+                    .is_some_and(|current| current.entity_id == banker.entity_id);
+
+                match &pkt.payload {
+                    DisplayMenuPayload::ShowItems { pursuit_id, items }
+                        if from_banker
+                            || is_withdraw_text(&last_picked.0)
+                            || is_withdraw_text(&pkt.header.text) =>
+                    {
+                        let banker = Banker {
+                            pursuit_id: *pursuit_id,
+                            ..banker
+                        };
+                        bank.banker = Some(banker);
+                        bank.npc = pkt.header.name.clone();
+                        bank.items = group_items(items);
+                        bank.entries = items.clone();
+                        bank.updated = chrono::Local::now().format("%H:%M").to_string();
+
+                        // The list came back after a withdrawal: next entry, or done
+                        if let Some(current) = withdrawal.0.as_mut() {
+                            match bank.entries.iter().find(|e| e.name == current.name) {
+                                Some(entry) if current.picks_left > 0 => {
+                                    current.amount = entry_amount(entry);
+                                    current.picks_left -= 1;
+                                    current.deadline = now + WITHDRAW_TIMEOUT;
+                                    banker.send(vec![current.name.clone()], &outbox);
+                                }
+                                Some(_) => {
+                                    stopped = Some(format!("Stopped withdrawing {}", current.name));
+                                }
+                                None => stopped = Some(format!("Withdrew all {}", current.name)),
+                            }
+                        }
+                    }
+                    DisplayMenuPayload::TextEntry { pursuit_id }
+                    | DisplayMenuPayload::TextEntryWithArgs { pursuit_id, .. }
+                        if from_banker && withdrawal.0.is_some() =>
+                    {
+                        let Some(current) = withdrawal.0.as_mut() else {
+                            continue;
+                        };
+                        let mut topics = Vec::new();
+                        if let DisplayMenuPayload::TextEntryWithArgs { args, .. } = &pkt.payload
+                            && !args.is_empty()
+                        {
+                            topics.push(args.clone());
+                        }
+                        topics.push(current.amount.to_string());
+                        Banker {
+                            pursuit_id: *pursuit_id,
+                            ..banker
+                        }
+                        .send(topics, &outbox);
+                        current.deadline = now + WITHDRAW_TIMEOUT;
+                    }
+                    _ => {
+                        bank.banker = None;
+                        if let Some(current) = &withdrawal.0 {
+                            stopped = Some(format!("Stopped withdrawing {}", current.name));
+                        }
+                    }
+                }
+            }
+            SessionEvent::DisplayDialog(DisplayDialog::Show { .. } | DisplayDialog::Close) => {
+                bank.banker = None;
+                if let Some(current) = &withdrawal.0 {
+                    stopped = Some(format!("Stopped withdrawing {}", current.name));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(current) = &withdrawal.0
+        && stopped.is_none()
+        && now > current.deadline
+    {
+        stopped = Some(format!(
+            "Stopped withdrawing {}: the banker didn't answer",
+            current.name
+        ));
+    }
+    if let Some(text) = stopped {
+        withdrawal.0 = None;
+        bank.withdrawing = None;
+        outbound.write(UiOutbound(CoreToUi::Toast { text }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, cost: i32) -> ItemInfo {
+        ItemInfo {
+            sprite: 1,
+            color: 0,
+            cost,
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn stacks_are_added_up_and_sorted() {
+        let mut bank = BankContents {
+            items: group_items(&[
+                item("Wolf Fur", 40),
+                item("Apple", 3),
+                item("Wolf Fur", 60),
+                item("Leather Tunic", 0),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(bank.items[0].total, 100);
+        assert_eq!(bank.items[0].stacks, 2);
+        assert_eq!(bank.items[2].total, 1);
+
+        let names = |bank: &BankContents| -> Vec<String> {
+            bank.view().iter().map(|item| item.name.clone()).collect()
+        };
+        assert_eq!(names(&bank), ["Apple", "Leather Tunic", "Wolf Fur"]);
+        bank.sort_by_amount = true;
+        assert_eq!(names(&bank), ["Wolf Fur", "Apple", "Leather Tunic"]);
+        bank.search = "FUR".to_owned();
+        assert_eq!(names(&bank), ["Wolf Fur"]);
+    }
+}
@@ -0,0 +1,194 @@
+//! HUD clock (local time, Temuairan date, time online) and the event banner
+//! raised by server announcements such as double experience weekends.
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+
+use crate::app_state::AppState;
+use crate::events::ChatEvent;
+use crate::slint_support::state_bridge::SlintWindow;
+
+/// One Temuairan day ("sun") passes per real hour.
+const SECS_PER_SUN: u64 = 60 * 60;
+const SUNS_PER_MOON: u64 = 28;
+const MOONS_PER_YEAR: u64 = 13;
+/// Unix time at which Deoch year 1 began on the client-side calendar.
+const CALENDAR_EPOCH: u64 = 946_684_800;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Date on the in-game calendar, derived from wall-clock time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemuairDate {
+    pub year: u64,
+    pub moon: u64,
+    pub sun: u64,
+}
+
+impl TemuairDate {
+    pub fn from_unix(secs: u64) -> Self {
+        let suns = secs.saturating_sub(CALENDAR_EPOCH) / SECS_PER_SUN;
+        let moons = suns / SUNS_PER_MOON;
+        Self {
+            year: moons / MOONS_PER_YEAR + 1,
+            moon: moons % MOONS_PER_YEAR + 1,
+            sun: suns % SUNS_PER_MOON + 1,
+        }
+    }
+}
+
+impl std::fmt::Display for TemuairDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Deoch {}, {} Moon, {} Sun",
+            self.year,
+            ordinal(self.moon),
+            ordinal(self.sun)
+        )
+    }
+}
+
+fn ordinal(n: u64) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// Pick out server announcements worth pinning, e.g. "Double experience is active this weekend!".
+pub fn event_banner_text(message: &str) -> Option<String> {
+    let lower = message.to_lowercase();
+    let bonus = ["double", "2x", "bonus", "triple", "3x"]
+        .iter()
+        .any(|k| lower.contains(k));
+    let reward = ["exp", "xp", "experience", "gold", "drop", "ability"]
+        .iter()
+        .any(|k| lower.contains(k));
+    (bonus && reward).then(|| message.trim().to_string())
+}
+
+#[derive(Resource, Default)]
+struct ClockState {
+    entered_at: Option<Instant>,
+    last_refresh: Option<Instant>,
+}
+
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClockState>()
+            .add_systems(OnEnter(AppState::InGame), start_clock)
+            .add_systems(
+                Update,
+                (update_clock, raise_event_banner).run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), stop_clock);
+    }
+}
+
+fn start_clock(mut state: ResMut<ClockState>) {
+    state.entered_at = Some(Instant::now());
+    state.last_refresh = None;
+}
+
+fn stop_clock(mut state: ResMut<ClockState>) {
+    *state = ClockState::default();
+}
+
+fn update_clock(mut state: ResMut<ClockState>, window: Option<Res<SlintWindow>>) {
+    if state
+        .last_refresh
+        .is_some_and(|last| last.elapsed() < REFRESH_INTERVAL)
+    {
+        return;
+    }
+    state.last_refresh = Some(Instant::now());
+
+    let Some(strong) = window.as_ref().and_then(|w| w.0.upgrade()) else {
+        return;
+    };
+
+    let now = chrono::Local::now();
+    let time = if i_slint_core::date_time::use_24_hour_format() {
+        now.format("%H:%M").to_string()
+    } else {
+        now.format("%-I:%M %p").to_string()
+    };
+    let date = TemuairDate::from_unix(now.timestamp().max(0) as u64);
+    let online = state.entered_at.map(|t| t.elapsed().as_secs()).unwrap_or(0);
+
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    game_state.set_clock_time(slint::SharedString::from(time));
+    game_state.set_clock_date(slint::SharedString::from(date.to_string()));
+    game_state.set_clock_online(slint::SharedString::from(format!(
+        "Online {}h {:02}m",
+        online / 3600,
+        online / 60 % 60
+    )));
+}
+
+fn raise_event_banner(mut chat_events: MessageReader<ChatEvent>, window: Option<Res<SlintWindow>>) {
+    use packets::server::ServerMessageType;
+
+    for event in chat_events.read() {
+        let ChatEvent::ServerMessage(pkt) = event else {
+            continue;
+        };
+        // Player whispers and group/guild chat can't be server announcements
+        if matches!(
+            pkt.message_type,
+            ServerMessageType::Whisper
+                | ServerMessageType::GroupChat
+                | ServerMessageType::GuildChat
+        ) {
+            continue;
+        }
+        let Some(text) = event_banner_text(&pkt.message) else {
+            continue;
+        };
+        if let Some(strong) = window.as_ref().and_then(|w| w.0.upgrade()) {
+            let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+            game_state.set_event_banner_text(slint::SharedString::from(text));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calendar_starts_at_first_sun() {
+        assert_eq!(
+            TemuairDate::from_unix(CALENDAR_EPOCH),
+            TemuairDate {
+                year: 1,
+                moon: 1,
+                sun: 1
+            }
+        );
+        let next_year = CALENDAR_EPOCH + SECS_PER_SUN * SUNS_PER_MOON * MOONS_PER_YEAR;
+        assert_eq!(TemuairDate::from_unix(next_year).year, 2);
+        assert_eq!(
+            TemuairDate::from_unix(CALENDAR_EPOCH + SECS_PER_SUN * 22).to_string(),
+            "Deoch 1, 1st Moon, 23rd Sun"
+        );
+    }
+
+    #[test]
+    fn banner_needs_bonus_and_reward() {
+        assert_eq!(
+            event_banner_text(" Double experience is active this weekend! ").as_deref(),
+            Some("Double experience is active this weekend!")
+        );
+        assert!(event_banner_text("You feel a double strike").is_none());
+        assert!(event_banner_text("You gained 500 experience").is_none());
+    }
+}
@@ -0,0 +1,436 @@
+//! Combat log of casts, hits, heals and status messages.
+//!
+//! Entries are pieced together from what the server already sends: effect
+//! animations become casts (named after the spell when it is our own cast),
+//! health bar changes become hits and heals, and
+//! action bar messages are kept as status lines. Health bars don't say who
+//! caused them, so a change is credited to the last caster whose effect landed
+//! on the same target moments before.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_ui::{CoreToUi, UiToCore};
+use packets::server::{Animation, ServerMessageType};
+
+use crate::app_state::AppState;
+use crate::ecs::components::{EntityId, HoverName, LocalPlayer, NPC, Player};
+use crate::events::{ChatEvent, EntityEvent, SpellCastEvent};
+use crate::resources::StorageConfig;
+use crate::webui::plugin::{AbilityState, UiInbound, UiOutbound};
+
+/// Oldest entries are dropped past this many.
+const MAX_ENTRIES: usize = 1000;
+/// How long after an effect lands a health change is still credited to its caster.
+const CREDIT_WINDOW: Duration = Duration::from_millis(1500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CombatLogKind {
+    Cast,
+    Hit,
+    Heal,
+    Status,
+}
+
+impl CombatLogKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CombatLogKind::Cast => "Cast",
+            CombatLogKind::Hit => "Hit",
+            CombatLogKind::Heal => "Heal",
+            CombatLogKind::Status => "Status",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CombatLogEntry {
+    /// Local wall-clock time, "HH:MM:SS".
+    pub time: String,
+    pub kind: CombatLogKind,
+    pub source: String,
+    pub target: String,
+    pub text: String,
+    /// Entity the entry is about, when there is one.
+    pub target_id: Option<u32>,
+    /// Effect of the cast a hit or heal is credited to.
+    pub effect: Option<u16>,
+}
+
+impl std::fmt::Display for CombatLogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {:<6}", self.time, self.kind.label())?;
+        match (self.source.is_empty(), self.target.is_empty()) {
+            (false, false) => write!(f, " {} -> {}:", self.source, self.target)?,
+            (false, true) => write!(f, " {}:", self.source)?,
+            (true, false) => write!(f, " {}:", self.target)?,
+            (true, true) => {}
+        }
+        write!(f, " {}", self.text)
+    }
+}
+
+/// Source and target name filters; blank matches everything.
+#[derive(Debug, Clone, Default)]
+pub struct CombatLogFilter {
+    pub source: String,
+    pub target: String,
+}
+
+impl CombatLogFilter {
+    pub fn matches(&self, entry: &CombatLogEntry) -> bool {
+        fn contains(haystack: &str, needle: &str) -> bool {
+            let needle = needle.trim();
+            needle.is_empty() || haystack.to_lowercase().contains(&needle.to_lowercase())
+        }
+        contains(&entry.source, &self.source) && contains(&entry.target, &self.target)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Caster {
+    name: String,
+    effect: u16,
+    at: Duration,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct CombatLog {
+    entries: VecDeque<CombatLogEntry>,
+    pub filter: CombatLogFilter,
+    /// Last health percentage seen per entity id.
+    health: HashMap<u32, u8>,
+    /// Last caster whose effect landed on each entity id.
+    last_caster: HashMap<u32, Caster>,
+    /// Spell we last sent and when, naming the effects it lands.
+    own_spell: Option<(String, Duration)>,
+}
+
+impl CombatLog {
+    /// Entries passing the current filter, oldest first.
+    pub fn filtered(&self) -> impl Iterator<Item = &CombatLogEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| self.filter.matches(entry))
+    }
+
+    /// The last `count` hits on `target_id`, oldest first, regardless of the filter.
+    pub fn last_hits_on(&self, target_id: u32, count: usize) -> Vec<&CombatLogEntry> {
+        let mut hits: Vec<_> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.kind == CombatLogKind::Hit && entry.target_id == Some(target_id))
+            .take(count)
+            .collect();
+        hits.reverse();
+        hits
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn push(&mut self, entry: CombatLogEntry) {
+        if self.entries.len() >= MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    fn entry(kind: CombatLogKind, source: &str, target: &str, text: String) -> CombatLogEntry {
+        CombatLogEntry {
+            time: chrono::Local::now().format("%H:%M:%S").to_string(),
+            kind,
+            source: source.to_owned(),
+            target: target.to_owned(),
+            text,
+            target_id: None,
+            effect: None,
+        }
+    }
+
+    fn record_own_spell(&mut self, now: Duration, name: &str) {
+        self.own_spell = Some((name.to_owned(), now));
+    }
+
+    /// Logs an effect landing on `target_id`. Our own casts are named after the
+    /// spell we just sent, anything else only by its effect number.
+    fn record_effect(
+        &mut self,
+        now: Duration,
+        target_id: u32,
+        source: &str,
+        target: &str,
+        effect: u16,
+        own: bool,
+    ) {
+        let spell = self
+            .own_spell
+            .as_ref()
+            .filter(|(_, at)| own && now.saturating_sub(*at) <= CREDIT_WINDOW)
+            .map(|(name, _)| name.clone());
+        let text = spell.unwrap_or_else(|| format!("effect {}", effect));
+        self.push(CombatLogEntry {
+            target_id: Some(target_id),
+            effect: Some(effect),
+            ..Self::entry(CombatLogKind::Cast, source, target, text)
+        });
+        self.last_caster.insert(
+            target_id,
+            Caster {
+                name: source.to_owned(),
+                effect,
+                at: now,
+            },
+        );
+    }
+
+    fn record_health(&mut self, now: Duration, target_id: u32, target: &str, percent: u8) {
+        let previous = self.health.insert(target_id, percent);
+        let caster = self
+            .last_caster
+            .get(&target_id)
+            .filter(|caster| now.saturating_sub(caster.at) <= CREDIT_WINDOW);
+        let source = caster
+            .map(|caster| caster.name.as_str())
+            .unwrap_or_default();
+
+        let (kind, text) = match previous {
+            Some(previous) if percent < previous => (
+                CombatLogKind::Hit,
+                format!("{}% (-{}%)", percent, previous - percent),
+            ),
+            Some(previous) if percent > previous => (
+                CombatLogKind::Heal,
+                format!("{}% (+{}%)", percent, percent - previous),
+            ),
+            None if percent < 100 => (CombatLogKind::Hit, format!("{}%", percent)),
+            _ => return,
+        };
+        let entry = CombatLogEntry {
+            target_id: Some(target_id),
+            effect: caster.map(|caster| caster.effect),
+            ..Self::entry(kind, source, target, text)
+        };
+        self.push(entry);
+    }
+
+    fn record_status(&mut self, text: &str) {
+        self.push(Self::entry(CombatLogKind::Status, "", "", text.to_owned()));
+    }
+
+    /// Writes the entries passing the filter to a timestamped file in `dir`.
+    fn export(&self, dir: &std::path::Path) -> std::io::Result<std::path::PathBuf> {
+        let path = dir.join(format!(
+            "combat-log-{}.txt",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let contents: String = self
+            .filtered()
+            .map(|entry| format!("{}\n", entry))
+            .collect();
+        std::fs::write(&path, contents)?;
+        Ok(path)
+    }
+}
+
+pub struct CombatLogPlugin;
+
+impl Plugin for CombatLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CombatLog>()
+            .add_systems(OnExit(AppState::InGame), clear_combat_log)
+            .add_systems(
+                Update,
+                (record_combat_log, handle_combat_log_input).run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn clear_combat_log(mut commands: Commands) {
+    commands.insert_resource(CombatLog::default());
+}
+
+fn record_combat_log(
+    mut entity_events: MessageReader<EntityEvent>,
+    mut chat_events: MessageReader<ChatEvent>,
+    mut cast_events: MessageReader<SpellCastEvent>,
+    time: Res<Time>,
+    abilities: Option<Res<AbilityState>>,
+    entities: Query<(&EntityId, Option<&Player>, Option<&NPC>, Option<&HoverName>)>,
+    local_player: Query<&EntityId, With<LocalPlayer>>,
+    mut log: ResMut<CombatLog>,
+) {
+    let name_of = |id: u32| {
+        entities
+            .iter()
+            .find(|(entity_id, ..)| entity_id.id == id)
+            .and_then(|(_, player, npc, hover_name)| {
+                let name = player
+                    .map(|p| p.name.as_str())
+                    .or(hover_name.map(|h| h.name.as_str()))
+                    .or(npc.map(|n| n.name.as_str()))?;
+                (!name.is_empty()).then(|| name.to_owned())
+            })
+            .unwrap_or_else(|| format!("#{}", id))
+    };
+    let now = time.elapsed();
+    let local_id = local_player.iter().next().map(|id| id.id);
+
+    for SpellCastEvent { slot } in cast_events.read() {
+        let spell = abilities
+            .as_ref()
+            .and_then(|abilities| abilities.spells.iter().find(|spell| spell.slot == *slot));
+        if let Some(spell) = spell {
+            let name = crate::webui::plugin::spell_base_name(&spell.panel_name);
+            log.record_own_spell(now, name);
+        }
+    }
+
+    for event in entity_events.read() {
+        match event {
+            EntityEvent::Effect(Animation::Source {
+                target_id,
+                source_id,
+                target_animation,
+                source_animation,
+                ..
+            }) => {
+                let Some(effect) = target_animation.or(*source_animation) else {
+                    continue;
+                };
+                log.record_effect(
+                    now,
+                    *target_id,
+                    &name_of(*source_id),
+                    &name_of(*target_id),
+                    effect,
+                    Some(*source_id) == local_id,
+                );
+            }
+            EntityEvent::HealthBar(bar) => {
+                log.record_health(
+                    now,
+                    bar.source_id,
+                    &name_of(bar.source_id),
+                    bar.health_percent,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    for event in chat_events.read() {
+        let ChatEvent::ServerMessage(msg) = event else {
+            continue;
+        };
+        if matches!(
+            msg.message_type,
+            ServerMessageType::OrangeBar1
+                | ServerMessageType::OrangeBar2
+                | ServerMessageType::OrangeBar3
+                | ServerMessageType::OrangeBar5
+                | ServerMessageType::ActiveMessage
+        ) && !msg.message.trim().is_empty()
+        {
+            log.record_status(msg.message.trim());
+        }
+    }
+}
+
+fn handle_combat_log_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut outbound: MessageWriter<UiOutbound>,
+    storage: Res<StorageConfig>,
+    mut log: ResMut<CombatLog>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::CombatLogFilter { source, target } => {
+                log.filter = CombatLogFilter {
+                    source: source.clone(),
+                    target: target.clone(),
+                };
+            }
+            UiToCore::CombatLogClear => log.clear(),
+            UiToCore::CombatLogExport => {
+                let text = match log.export(&storage.combat_logs_dir()) {
+                    Ok(path) => format!("Combat log saved to {}", path.display()),
+                    Err(e) => {
+                        error!("Failed to export combat log: {}", e);
+                        format!("Couldn't save the combat log: {}", e)
+                    }
+                };
+                outbound.write(UiOutbound(CoreToUi::Toast { text }));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn health_changes_are_credited_to_recent_casters() {
+        let mut log = CombatLog::default();
+        log.record_health(Duration::ZERO, 7, "Kobold", 100);
+        log.record_effect(Duration::from_secs(1), 7, "Aisling", "Kobold", 12, false);
+        log.record_health(Duration::from_secs(2), 7, "Kobold", 70);
+        log.record_health(Duration::from_secs(10), 7, "Kobold", 80);
+
+        let entries: Vec<_> = log.filtered().collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1].kind, CombatLogKind::Hit);
+        assert_eq!(entries[1].source, "Aisling");
+        assert_eq!(entries[1].text, "70% (-30%)");
+        assert_eq!(entries[2].kind, CombatLogKind::Heal);
+        assert_eq!(entries[2].source, "");
+
+        log.filter.source = "aisl".to_owned();
+        assert_eq!(log.filtered().count(), 2);
+    }
+
+    #[test]
+    fn last_hits_on_a_target() {
+        let mut log = CombatLog::default();
+        log.record_health(Duration::ZERO, 1, "Aisling", 100);
+        for (i, percent) in [90, 80, 70].into_iter().enumerate() {
+            log.record_effect(
+                Duration::from_secs(i as u64),
+                1,
+                "Kobold",
+                "Aisling",
+                5,
+                false,
+            );
+            log.record_health(Duration::from_secs(i as u64), 1, "Aisling", percent);
+            log.record_health(Duration::from_secs(i as u64), 2, "Kobold", percent);
+        }
+        log.record_health(Duration::from_secs(10), 1, "Aisling", 50);
+
+        let hits = log.last_hits_on(1, 3);
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].text, "80% (-10%)");
+        assert_eq!(
+            (hits[1].source.as_str(), hits[1].effect),
+            ("Kobold", Some(5))
+        );
+        assert_eq!((hits[2].source.as_str(), hits[2].effect), ("", None));
+    }
+
+    #[test]
+    fn own_casts_are_named_after_the_spell() {
+        let mut log = CombatLog::default();
+        log.record_own_spell(Duration::from_secs(1), "beag srad");
+        log.record_effect(Duration::from_secs(1), 7, "Aisling", "Kobold", 12, true);
+        log.record_effect(Duration::from_secs(1), 8, "Kobold", "Aisling", 5, false);
+        log.record_effect(Duration::from_secs(5), 7, "Aisling", "Kobold", 12, true);
+
+        let texts: Vec<_> = log.filtered().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(texts, ["beag srad", "effect 5", "effect 12"]);
+    }
+}
@@ -0,0 +1,165 @@
+//! Per-server names for creatures the server only identifies by sprite.
+//!
+//! Monsters usually arrive without a name, so the client keeps its own table
+//! in `creature_names.json` inside the server's data directory. The file is
+//! plain JSON keyed by sprite id so players can share and edit it by hand, and
+//! unknown creatures can be named in game from the world context menu.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use game_ui::UiToCore;
+
+use crate::app_state::AppState;
+use crate::ecs::components::{CreatureSprite, HoverName, NPC};
+use crate::resources::StorageConfig;
+use crate::webui::plugin::UiInbound;
+
+/// Creature names by sprite id for the current server.
+#[derive(Resource, Default, Debug)]
+pub struct CreatureNames {
+    path: Option<PathBuf>,
+    names: BTreeMap<u16, String>,
+}
+
+impl CreatureNames {
+    pub fn load(path: PathBuf) -> Self {
+        let names = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring malformed {}: {}", path.display(), e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+
+        Self {
+            path: Some(path),
+            names,
+        }
+    }
+
+    pub fn get(&self, sprite: u16) -> Option<&str> {
+        self.names.get(&sprite).map(String::as_str)
+    }
+
+    /// Name shown for a creature the server sent no name for.
+    pub fn display_name(&self, sprite: u16) -> String {
+        self.get(sprite)
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("Unknown creature #{}", sprite))
+    }
+
+    /// Names a sprite, or forgets it when `name` is blank, and saves the file.
+    pub fn set(&mut self, sprite: u16, name: &str) {
+        let name = name.trim();
+        if name.is_empty() {
+            self.names.remove(&sprite);
+        } else {
+            self.names.insert(sprite, name.to_owned());
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        match serde_json::to_string_pretty(&self.names) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    error!("Failed to save {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => error!("Failed to serialize creature names: {}", e),
+        }
+    }
+}
+
+/// Sprite of the creature currently being named in the UI, if any.
+#[derive(Resource, Default, Debug)]
+pub struct PendingCreatureName(pub Option<u16>);
+
+pub struct CreatureNamesPlugin;
+
+impl Plugin for CreatureNamesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CreatureNames>()
+            .init_resource::<PendingCreatureName>()
+            .add_systems(OnEnter(AppState::InGame), load_creature_names)
+            .add_systems(OnExit(AppState::InGame), clear_creature_names)
+            .add_systems(
+                Update,
+                (handle_creature_name_prompt, apply_creature_names)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn load_creature_names(
+    mut commands: Commands,
+    storage: Res<StorageConfig>,
+    session: Option<Res<crate::CurrentSession>>,
+) {
+    let Some(session) = session else {
+        return;
+    };
+    commands.insert_resource(CreatureNames::load(
+        storage.server_creature_names_path(session.server_id),
+    ));
+}
+
+fn clear_creature_names(mut commands: Commands) {
+    commands.insert_resource(CreatureNames::default());
+    commands.insert_resource(PendingCreatureName::default());
+}
+
+fn handle_creature_name_prompt(
+    mut inbound: MessageReader<UiInbound>,
+    mut names: ResMut<CreatureNames>,
+    mut pending: ResMut<PendingCreatureName>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::CreatureNameSubmit { sprite, name } => {
+                names.set(*sprite, name);
+                pending.0 = None;
+            }
+            UiToCore::CreatureNameCancel => {
+                pending.0 = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Fills in hover names for unnamed creatures as they spawn and whenever the table changes.
+fn apply_creature_names(
+    names: Res<CreatureNames>,
+    mut creatures: Query<(&NPC, &CreatureSprite, &mut HoverName)>,
+) {
+    let refresh_all = names.is_changed();
+    for (npc, sprite, mut hover_name) in creatures.iter_mut() {
+        if !npc.name.is_empty() || !(refresh_all || hover_name.is_added()) {
+            continue;
+        }
+        hover_name.name = names.display_name(sprite.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_name_forgets_sprite() {
+        let mut names = CreatureNames::default();
+        names.set(42, "  Kobold  ");
+        assert_eq!(names.get(42), Some("Kobold"));
+
+        names.set(42, " ");
+        assert_eq!(names.get(42), None);
+        assert_eq!(names.display_name(42), "Unknown creature #42");
+    }
+}
@@ -0,0 +1,145 @@
+//! Recap of what hit the player last, shown when they die.
+//!
+//! Built from the combat log a moment after health reaches zero, so the health
+//! bar for the killing blow has arrived too. Amounts are in percent of maximum
+//! health, which is all health bars carry.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::app_state::AppState;
+use crate::ecs::components::{EntityId, LocalPlayer};
+use crate::plugins::combat_log::{CombatLog, CombatLogEntry};
+use crate::resources::PlayerAttributes;
+
+const RECAP_HITS: usize = 10;
+const RECAP_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeathRecapRow {
+    pub time: String,
+    pub source: String,
+    pub amount: String,
+    pub ability: String,
+}
+
+impl DeathRecapRow {
+    fn from_entry(entry: &CombatLogEntry) -> Self {
+        // Hit text is "NN% (-MM%)", or just "NN%" for the first bar seen
+        let amount = entry
+            .text
+            .split_once('(')
+            .map(|(_, change)| change.trim_end_matches(')').to_owned())
+            .unwrap_or_else(|| entry.text.clone());
+        Self {
+            time: entry.time.clone(),
+            source: if entry.source.is_empty() {
+                "Unknown".to_owned()
+            } else {
+                entry.source.clone()
+            },
+            amount,
+            ability: entry
+                .effect
+                .map(|effect| format!("Effect {}", effect))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct DeathRecap {
+    /// Deaths so far this session, 0 until there is a recap to show.
+    pub deaths: u32,
+    /// Hits leading up to the latest death, oldest first.
+    pub rows: Vec<DeathRecapRow>,
+}
+
+#[derive(Resource, Default, Debug)]
+struct DeathWatch {
+    alive: bool,
+    died_at: Option<Duration>,
+}
+
+pub struct DeathRecapPlugin;
+
+impl Plugin for DeathRecapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DeathRecap>()
+            .init_resource::<DeathWatch>()
+            .add_systems(Update, watch_for_death.run_if(in_state(AppState::InGame)))
+            .add_systems(OnExit(AppState::InGame), reset_death_recap);
+    }
+}
+
+fn reset_death_recap(mut recap: ResMut<DeathRecap>, mut watch: ResMut<DeathWatch>) {
+    *watch = DeathWatch::default();
+    recap.set_if_neq(DeathRecap::default());
+}
+
+fn watch_for_death(
+    time: Res<Time>,
+    attributes: Res<PlayerAttributes>,
+    log: Res<CombatLog>,
+    player: Query<&EntityId, With<LocalPlayer>>,
+    mut watch: ResMut<DeathWatch>,
+    mut recap: ResMut<DeathRecap>,
+) {
+    let now = time.elapsed();
+    if attributes.is_changed() && attributes.max_hp > 0 {
+        if watch.alive && attributes.current_hp == 0 {
+            watch.died_at = Some(now);
+        }
+        watch.alive = attributes.current_hp > 0;
+    }
+
+    let Some(died_at) = watch.died_at else {
+        return;
+    };
+    if now.saturating_sub(died_at) < RECAP_DELAY {
+        return;
+    }
+    watch.died_at = None;
+    let Ok(player_id) = player.single() else {
+        return;
+    };
+    recap.deaths += 1;
+    recap.rows = log
+        .last_hits_on(player_id.id, RECAP_HITS)
+        .into_iter()
+        .map(DeathRecapRow::from_entry)
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::combat_log::CombatLogKind;
+
+    #[test]
+    fn rows_show_the_change_and_its_source() {
+        let entry = CombatLogEntry {
+            time: "12:00:00".into(),
+            kind: CombatLogKind::Hit,
+            source: "Kobold".into(),
+            target: "Aisling".into(),
+            text: "40% (-25%)".into(),
+            target_id: Some(1),
+            effect: Some(7),
+        };
+        let row = DeathRecapRow::from_entry(&entry);
+        assert_eq!(row.amount, "-25%");
+        assert_eq!(row.ability, "Effect 7");
+
+        let row = DeathRecapRow::from_entry(&CombatLogEntry {
+            source: String::new(),
+            text: "60%".into(),
+            effect: None,
+            ..entry
+        });
+        assert_eq!(row.source, "Unknown");
+        assert_eq!(row.amount, "60%");
+        assert_eq!(row.ability, "");
+    }
+}
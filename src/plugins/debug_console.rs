@@ -0,0 +1,615 @@
+//! Optional developer console on a local TCP port, for scripted UI tests and
+//! poking at a live client.
+//!
+//! While `debug_console.enabled` is set, a background thread listens on
+//! `127.0.0.1:<port>`. A client first sends the session token, which is
+//! written to `debug_console.token` in the data directory whenever the
+//! console starts; a connection that sends anything else, or anything shaped
+//! like an HTTP request, is dropped, so a web page posting to the port can't
+//! drive the client. After that each line is one command and gets one line
+//! back. A line starting with `{` is a JSON-RPC 2.0 request with
+//! positional params, answered in kind; anything else is read as a command
+//! followed by whitespace separated arguments, which is easier from telnet.
+//! Commands run on the main thread between frames:
+//!
+//! - `state`: app state, map, player position and vitals
+//! - `entities [search]`: entities as the entity inspector lists them
+//! - `packet <opcode> [hex payload]`: feeds a server packet to the event
+//!   pipeline as if it came off the socket
+//! - `overlay [on|off]`, `inspector [on|off]` and `archive [on|off]`: shows
+//!   the debug windows, toggling them without an argument
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use packets::server;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::app_state::AppState;
+use crate::ecs::components::{EntityId, GameMap, LocalPlayer, Position};
+use crate::events::NetworkEvent;
+use crate::plugins::archive_browser::{ArchiveBrowserState, set_browser_visible};
+use crate::plugins::debug_overlay::{DebugOverlayState, set_overlay_visible};
+use crate::plugins::entity_inspector::{
+    EntityInspectorState, inspect_entities, set_inspector_visible,
+};
+use crate::resources::{PlayerAttributes, StorageConfig};
+use crate::settings_types::Settings;
+use crate::slint_support::state_bridge::SlintWindow;
+
+/// How long a client waits for the game to answer, e.g. while loading a map.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const PARSE_ERROR: i64 = -32700;
+/// Holds the token a client must send first, in the data directory.
+const TOKEN_FILE: &str = "debug_console.token";
+const TOKEN_LEN: usize = 16;
+
+struct ConsoleRequest {
+    line: String,
+    reply: Sender<String>,
+}
+
+struct ConsoleServer {
+    port: u16,
+    token_path: PathBuf,
+    requests: Receiver<ConsoleRequest>,
+    /// Dropping this stops the server thread.
+    _stop: Sender<()>,
+}
+
+#[derive(Resource, Default)]
+pub struct DebugConsole {
+    server: Option<ConsoleServer>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Command {
+    /// JSON-RPC request id, `None` for plain text commands.
+    id: Option<Value>,
+    method: String,
+    args: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+#[derive(Debug, PartialEq)]
+struct CommandError {
+    code: i64,
+    message: String,
+}
+
+impl CommandError {
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.into(),
+        }
+    }
+}
+
+fn parse_command(line: &str) -> Result<Command, CommandError> {
+    let line = line.trim();
+    if line.starts_with('{') {
+        let request: RpcRequest = serde_json::from_str(line).map_err(|e| CommandError {
+            code: PARSE_ERROR,
+            message: e.to_string(),
+        })?;
+        let args = request
+            .params
+            .into_iter()
+            .map(|param| match param {
+                Value::String(s) => s,
+                other => other.to_string(),
+            })
+            .collect();
+        return Ok(Command {
+            id: Some(request.id),
+            method: request.method,
+            args,
+        });
+    }
+
+    let mut words = line.split_whitespace().map(str::to_owned);
+    Ok(Command {
+        id: None,
+        method: words.next().unwrap_or_default(),
+        args: words.collect(),
+    })
+}
+
+fn format_reply(id: Option<Value>, result: Result<Value, CommandError>) -> String {
+    match (id, result) {
+        (Some(id), Ok(result)) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        (Some(id), Err(e)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": e.code, "message": e.message },
+        }),
+        (None, Ok(result)) => return result.to_string(),
+        (None, Err(e)) => return format!("error: {}", e.message),
+    }
+    .to_string()
+}
+
+/// Opcode in decimal or `0x` hex, then the payload as hex digits.
+fn parse_packet(args: &[String]) -> Result<(u8, Vec<u8>), CommandError> {
+    let opcode = args
+        .first()
+        .ok_or_else(|| CommandError::invalid_params("usage: packet <opcode> [hex payload]"))?;
+    let opcode = match opcode.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => opcode.parse(),
+    }
+    .map_err(|_| CommandError::invalid_params(format!("bad opcode {}", opcode)))?;
+
+    let hex: String = args[1..].concat();
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(CommandError::invalid_params("payload is not hex"));
+    }
+    if hex.len() % 2 != 0 {
+        return Err(CommandError::invalid_params("odd number of hex digits"));
+    }
+    let payload = hex
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| (hex_digit(pair[0]) << 4) | hex_digit(pair[1]))
+        .collect();
+    Ok((opcode, payload))
+}
+
+/// A request or header line of an HTTP request, which any web page can make a
+/// browser send to a local port.
+fn looks_like_http(line: &str) -> bool {
+    let line = line.trim();
+    let request_line = line
+        .rsplit_once(' ')
+        .is_some_and(|(_, version)| version.starts_with("HTTP/"));
+    let header = line.split_once(':').is_some_and(|(name, _)| {
+        !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    });
+    request_line || header
+}
+
+/// Fresh random token for this console session, as hex.
+fn new_token() -> Option<String> {
+    let mut bytes = [0u8; TOKEN_LEN];
+    SystemRandom::new().fill(&mut bytes).ok()?;
+    Some(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Writes the token readable by the current user only.
+fn write_token(path: &Path, token: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut out = options.open(path)?;
+    #[cfg(unix)]
+    out.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    writeln!(out, "{}", token)
+}
+
+/// Value of an ASCII hex digit, already checked to be one.
+fn hex_digit(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        _ => digit - b'A' + 10,
+    }
+}
+
+/// `on`/`off`, or the opposite of `current` without an argument.
+fn parse_switch(args: &[String], current: bool) -> Result<bool, CommandError> {
+    match args.first().map(String::as_str) {
+        None => Ok(!current),
+        Some("on" | "true" | "1") => Ok(true),
+        Some("off" | "false" | "0") => Ok(false),
+        Some(other) => Err(CommandError::invalid_params(format!(
+            "expected on or off, got {}",
+            other
+        ))),
+    }
+}
+
+pub struct DebugConsolePlugin;
+
+impl Plugin for DebugConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugConsole>().add_systems(
+            Update,
+            (
+                sync_console_server,
+                serve_console_requests.run_if(console_running),
+            )
+                .chain(),
+        );
+    }
+}
+
+fn console_running(console: Res<DebugConsole>) -> bool {
+    console.server.is_some()
+}
+
+/// Binds the console on the configured port when it's switched on, and
+/// drops the listener when it's switched off or the port changes. Every
+/// start writes a new session token.
+fn sync_console_server(
+    settings: Res<Settings>,
+    storage: Res<StorageConfig>,
+    mut console: ResMut<DebugConsole>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let wanted = settings
+        .debug_console
+        .enabled
+        .then_some(settings.debug_console.port);
+    if console.server.as_ref().map(|server| server.port) == wanted {
+        return;
+    }
+    if let Some(old) = console.server.take() {
+        let _ = std::fs::remove_file(&old.token_path);
+    }
+
+    let Some(port) = wanted else {
+        tracing::info!("Debug console disabled");
+        return;
+    };
+    let Some(token) = new_token() else {
+        tracing::error!("Debug console disabled: no randomness available for its token");
+        return;
+    };
+    let token_path = storage.root.join(TOKEN_FILE);
+    if let Err(e) = write_token(&token_path, &token) {
+        tracing::error!(
+            "Failed to write debug console token {:?}: {}",
+            token_path,
+            e
+        );
+        return;
+    }
+    match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+        Ok(listener) => {
+            let (requests_tx, requests) = crossbeam_channel::unbounded();
+            let (stop, stop_rx) = crossbeam_channel::bounded(0);
+            let token: Arc<str> = token.into();
+            let spawned = std::thread::Builder::new()
+                .name("debug-console".to_string())
+                .spawn(move || run_server(listener, token, requests_tx, stop_rx));
+            match spawned {
+                Ok(_) => {
+                    tracing::info!(
+                        "Debug console listening on 127.0.0.1:{}, token in {:?}",
+                        port,
+                        token_path
+                    );
+                    console.server = Some(ConsoleServer {
+                        port,
+                        token_path,
+                        requests,
+                        _stop: stop,
+                    });
+                }
+                Err(e) => tracing::error!("Failed to start debug console thread: {}", e),
+            }
+        }
+        Err(e) => tracing::error!("Failed to bind debug console on port {}: {}", port, e),
+    }
+}
+
+fn serve_console_requests(world: &mut World) {
+    let requests: Vec<ConsoleRequest> = match &world.resource::<DebugConsole>().server {
+        Some(server) => server.requests.try_iter().collect(),
+        None => return,
+    };
+    for request in requests {
+        let reply = match parse_command(&request.line) {
+            Ok(command) => {
+                let result = run_command(world, &command);
+                format_reply(command.id, result)
+            }
+            Err(e) => format_reply(Some(Value::Null), Err(e)),
+        };
+        let _ = request.reply.send(reply);
+    }
+}
+
+fn run_command(world: &mut World, command: &Command) -> Result<Value, CommandError> {
+    match command.method.as_str() {
+        "state" => Ok(game_state(world)),
+        "entities" => {
+            let (rows, matched) = inspect_entities(world, &command.args.join(" "));
+            let rows: Vec<Value> = rows
+                .iter()
+                .map(|row| {
+                    json!({
+                        "entity": row.entity.to_string(),
+                        "server_id": row.server_id,
+                        "name": row.name,
+                        "position": row.position.map(|pos| [pos.x, pos.y]),
+                        "sprite": row.sprite,
+                        "animation": row.animation,
+                        "components": row.components,
+                    })
+                })
+                .collect();
+            Ok(json!({ "matched": matched, "entities": rows }))
+        }
+        "packet" => {
+            let (opcode, payload) = parse_packet(&command.args)?;
+            let len = payload.len();
+            world.write_message(match server::Codes::try_from(opcode) {
+                Ok(code) => NetworkEvent::Packet(code, payload),
+                Err(_) => NetworkEvent::UnknownPacket(opcode, payload),
+            });
+            tracing::info!(
+                "Debug console injected packet {:#04x} ({} bytes)",
+                opcode,
+                len
+            );
+            Ok(json!({ "opcode": opcode, "bytes": len }))
+        }
+        "overlay" => {
+            let visible =
+                parse_switch(&command.args, world.resource::<DebugOverlayState>().visible)?;
+            let window = world.get_resource::<SlintWindow>().cloned();
+            let mut state = world.resource_mut::<DebugOverlayState>();
+            set_overlay_visible(&mut state, window.as_ref(), visible);
+            Ok(json!({ "visible": visible }))
+        }
+        "inspector" => {
+            let visible = parse_switch(
+                &command.args,
+                world.resource::<EntityInspectorState>().visible,
+            )?;
+            let window = world.get_resource::<SlintWindow>().cloned();
+            let mut state = world.resource_mut::<EntityInspectorState>();
+            set_inspector_visible(&mut state, window.as_ref(), visible);
+            Ok(json!({ "visible": visible }))
+        }
+        "archive" => {
+            let visible = parse_switch(
+                &command.args,
+                world.resource::<ArchiveBrowserState>().visible,
+            )?;
+            let window = world.get_resource::<SlintWindow>().cloned();
+            let mut state = world.resource_mut::<ArchiveBrowserState>();
+            set_browser_visible(&mut state, window.as_ref(), visible);
+            Ok(json!({ "visible": visible }))
+        }
+        "help" | "" => Ok(json!([
+            "state",
+            "entities [search]",
+            "packet <opcode> [hex payload]",
+            "overlay [on|off]",
+            "inspector [on|off]",
+            "archive [on|off]",
+        ])),
+        other => Err(CommandError {
+            code: METHOD_NOT_FOUND,
+            message: format!("unknown command {}, try help", other),
+        }),
+    }
+}
+
+fn game_state(world: &mut World) -> Value {
+    let app_state = format!("{:?}", world.resource::<State<AppState>>().get());
+    let map = world
+        .query::<&GameMap>()
+        .iter(world)
+        .next()
+        .map(|map| json!({ "id": map.map_id, "name": map.name }));
+    let player = world
+        .query_filtered::<(&EntityId, &Position), With<LocalPlayer>>()
+        .iter(world)
+        .next()
+        .map(|(id, pos)| json!({ "id": id.id, "x": pos.x, "y": pos.y }));
+    let vitals = world.get_resource::<PlayerAttributes>().map(|attrs| {
+        json!({
+            "hp": attrs.current_hp,
+            "max_hp": attrs.max_hp,
+            "mp": attrs.current_mp,
+            "max_mp": attrs.max_mp,
+        })
+    });
+    let entities = world.entities().len();
+
+    json!({
+        "app_state": app_state,
+        "map": map,
+        "player": player,
+        "vitals": vitals,
+        "entities": entities,
+    })
+}
+
+fn run_server(
+    listener: TcpListener,
+    token: Arc<str>,
+    requests: Sender<ConsoleRequest>,
+    stop: Receiver<()>,
+) {
+    if let Err(e) = listener.set_nonblocking(true) {
+        tracing::error!("Debug console listener setup failed: {}", e);
+        return;
+    }
+
+    loop {
+        match stop.recv_timeout(Duration::from_millis(100)) {
+            Err(RecvTimeoutError::Timeout) => {}
+            Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        while let Ok((stream, addr)) = listener.accept() {
+            let requests = requests.clone();
+            let token = token.clone();
+            let spawned = std::thread::Builder::new()
+                .name("debug-console-client".to_string())
+                .spawn(move || serve_client(stream, &token, requests));
+            match spawned {
+                Ok(_) => tracing::info!("Debug console client connected from {}", addr),
+                Err(e) => tracing::error!("Failed to start debug console client thread: {}", e),
+            }
+        }
+    }
+    tracing::debug!("Debug console server stopped");
+}
+
+fn serve_client(stream: TcpStream, token: &str, requests: Sender<ConsoleRequest>) {
+    let Ok(mut writer) = stream
+        .set_nonblocking(false)
+        .and_then(|_| stream.try_clone())
+    else {
+        return;
+    };
+    let mut lines = BufReader::new(stream).lines();
+    match lines.next() {
+        Some(Ok(line)) if line.trim() == token => {}
+        _ => {
+            tracing::warn!("Debug console client dropped: it didn't send the session token");
+            let _ = writeln!(writer, "error: send the token from {} first", TOKEN_FILE);
+            return;
+        }
+    }
+    for line in lines {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if looks_like_http(&line) {
+            tracing::warn!("Debug console client dropped: it sent an HTTP request");
+            break;
+        }
+        let (reply_tx, reply_rx) = crossbeam_channel::bounded(1);
+        if requests
+            .send(ConsoleRequest {
+                line,
+                reply: reply_tx,
+            })
+            .is_err()
+        {
+            break;
+        }
+        let reply = reply_rx
+            .recv_timeout(REPLY_TIMEOUT)
+            .unwrap_or_else(|_| "error: the game did not answer".to_owned());
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_and_json_rpc_commands_parse_alike() {
+        let text = parse_command("entities 4102\r\n").unwrap();
+        assert_eq!(text.id, None);
+        assert_eq!(text.method, "entities");
+        assert_eq!(text.args, ["4102"]);
+
+        let rpc =
+            parse_command(r#"{"jsonrpc":"2.0","id":7,"method":"packet","params":[10,"01ff"]}"#)
+                .unwrap();
+        assert_eq!(rpc.id, Some(json!(7)));
+        assert_eq!(rpc.method, "packet");
+        assert_eq!(rpc.args, ["10", "01ff"]);
+
+        assert_eq!(parse_command("{nope").unwrap_err().code, PARSE_ERROR);
+        let reply: Value =
+            serde_json::from_str(&format_reply(Some(json!(7)), Ok(json!(true)))).unwrap();
+        assert_eq!(reply, json!({ "jsonrpc": "2.0", "id": 7, "result": true }));
+        assert_eq!(
+            format_reply(None, Err(CommandError::invalid_params("bad"))),
+            "error: bad"
+        );
+    }
+
+    #[test]
+    fn packets_take_decimal_or_hex_opcodes() {
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            parse_packet(&args(&["0x0A", "01ff", "10"])),
+            Ok((0x0A, vec![0x01, 0xff, 0x10]))
+        );
+        assert_eq!(parse_packet(&args(&["51"])), Ok((51, vec![])));
+        assert!(parse_packet(&args(&["300"])).is_err());
+        assert!(parse_packet(&args(&["1", "abc"])).is_err());
+        assert!(parse_packet(&args(&["1", "aéa"])).is_err());
+        assert!(parse_packet(&args(&["1", "+1"])).is_err());
+        assert!(parse_packet(&[]).is_err());
+
+        assert_eq!(parse_switch(&args(&["on"]), false), Ok(true));
+        assert_eq!(parse_switch(&[], true), Ok(false));
+    }
+
+    /// Runs a client sending `input` against `serve_client`, returning what
+    /// reached the game and what the client got back.
+    fn serve_input(input: &str) -> (Vec<String>, String) {
+        use std::io::Read;
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        client.write_all(input.as_bytes()).unwrap();
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+
+        let (requests_tx, requests) = crossbeam_channel::unbounded();
+        let game = std::thread::spawn(move || {
+            requests
+                .iter()
+                .map(|request: ConsoleRequest| {
+                    let _ = request.reply.send("ok".to_owned());
+                    request.line
+                })
+                .collect::<Vec<_>>()
+        });
+        serve_client(stream, "secret", requests_tx);
+        let mut reply = String::new();
+        let _ = client.read_to_string(&mut reply);
+        (game.join().unwrap(), reply)
+    }
+
+    #[test]
+    fn http_shaped_connections_are_refused() {
+        let (lines, _) = serve_input(
+            "POST / HTTP/1.1\r\nHost: 127.0.0.1:7777\r\nContent-Type: text/plain\r\n\r\nsecret\npacket 10 00\n",
+        );
+        assert!(lines.is_empty());
+
+        let (lines, _) = serve_input("secret\nstate\nHost: 127.0.0.1\npacket 10 00\n");
+        assert_eq!(lines, ["state"]);
+
+        let (lines, reply) = serve_input("state\n");
+        assert!(lines.is_empty());
+        assert!(reply.starts_with("error:"));
+
+        assert!(looks_like_http("GET /favicon.ico HTTP/1.1"));
+        assert!(!looks_like_http(
+            r#"{"jsonrpc":"2.0","id":1,"method":"state"}"#
+        ));
+        assert!(!looks_like_http("entities Wolf"));
+    }
+}
@@ -0,0 +1,222 @@
+//! Developer overlay with frame timing per [`GameSet`], entity counts,
+//! texture atlas usage and texture memory.
+//!
+//! The overlay is toggled with [`GameAction::ToggleDebugOverlay`] but only when
+//! `graphics.debug_overlay` is enabled in settings, so it also works in release
+//! builds without being discoverable by accident.
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+
+use crate::app_state::AppState;
+use crate::ecs::systems::GameSet;
+use crate::input::{GameAction, GamepadConfig, UnifiedInputBindings};
+use crate::plugins::texture_memory::{TextureMemory, megabytes};
+use crate::settings_types::Settings;
+use crate::slint_support::state_bridge::SlintWindow;
+
+const GAME_SETS: [GameSet; 7] = [
+    GameSet::EventProcessing,
+    GameSet::Spawning,
+    GameSet::Movement,
+    GameSet::Physics,
+    GameSet::Animation,
+    GameSet::Camera,
+    GameSet::RenderSync,
+];
+
+/// Weight of the newest sample in the exponential moving averages.
+const SMOOTHING: f32 = 0.1;
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Resource, Default)]
+pub struct DebugOverlayState {
+    pub visible: bool,
+    last_refresh: Option<Instant>,
+    frame_ms: f32,
+}
+
+/// Smoothed wall time spent in each [`GameSet`], measured by marker systems
+/// placed directly before and after the set.
+#[derive(Resource, Default)]
+pub struct GameSetTimings {
+    started: [Option<Instant>; GAME_SETS.len()],
+    average_us: [f32; GAME_SETS.len()],
+}
+
+impl GameSetTimings {
+    fn begin(&mut self, idx: usize) {
+        self.started[idx] = Some(Instant::now());
+    }
+
+    fn end(&mut self, idx: usize) {
+        if let Some(start) = self.started[idx].take() {
+            let sample = start.elapsed().as_secs_f32() * 1_000_000.0;
+            self.average_us[idx] += (sample - self.average_us[idx]) * SMOOTHING;
+        }
+    }
+}
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlayState>()
+            .init_resource::<GameSetTimings>()
+            .add_systems(
+                Update,
+                (toggle_debug_overlay, update_debug_overlay)
+                    .chain()
+                    .after(GameSet::RenderSync)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_debug_overlay);
+
+        for (idx, set) in GAME_SETS.iter().enumerate() {
+            let mut begin = (move |mut timings: ResMut<GameSetTimings>| timings.begin(idx))
+                .before(set.clone())
+                .run_if(overlay_visible);
+            let mut end = (move |mut timings: ResMut<GameSetTimings>| timings.end(idx))
+                .after(set.clone())
+                .run_if(overlay_visible);
+            if idx > 0 {
+                begin = begin.after(GAME_SETS[idx - 1].clone());
+            }
+            if idx + 1 < GAME_SETS.len() {
+                end = end.before(GAME_SETS[idx + 1].clone());
+            }
+            app.add_systems(Update, (begin, end));
+        }
+    }
+}
+
+fn overlay_visible(state: Res<DebugOverlayState>) -> bool {
+    state.visible
+}
+
+/// Shows or hides the overlay, also used by the debug console.
+pub(crate) fn set_overlay_visible(
+    state: &mut DebugOverlayState,
+    window: Option<&SlintWindow>,
+    visible: bool,
+) {
+    state.visible = visible;
+    state.last_refresh = None;
+    if let Some(strong) = window.and_then(|w| w.0.upgrade()) {
+        let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+        game_state.set_show_debug_overlay(visible);
+    }
+}
+
+fn toggle_debug_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<UnifiedInputBindings>,
+    gamepad_query: Query<&Gamepad>,
+    gamepad_config: Res<GamepadConfig>,
+    settings: Res<Settings>,
+    mut state: ResMut<DebugOverlayState>,
+    window: Option<Res<SlintWindow>>,
+) {
+    if !settings.graphics.debug_overlay {
+        if state.visible {
+            set_overlay_visible(&mut state, window.as_deref(), false);
+        }
+    } else if bindings.is_just_pressed(
+        GameAction::ToggleDebugOverlay,
+        &keyboard_input,
+        Some(&gamepad_query),
+        Some(&gamepad_config),
+    ) {
+        let visible = !state.visible;
+        set_overlay_visible(&mut state, window.as_deref(), visible);
+    }
+}
+
+fn update_debug_overlay(
+    time: Res<Time>,
+    mut state: ResMut<DebugOverlayState>,
+    timings: Res<GameSetTimings>,
+    entities: Query<Entity>,
+    creatures: Option<Res<crate::CreatureAssetStoreState>>,
+    players: Option<Res<crate::PlayerAssetStoreState>>,
+    items: Option<Res<crate::ItemAssetStoreState>>,
+    effects: Option<Res<crate::EffectManagerState>>,
+    texture_memory: Res<TextureMemory>,
+    settings: Res<Settings>,
+    window: Option<Res<SlintWindow>>,
+) {
+    if !state.visible {
+        return;
+    }
+
+    let frame_ms = time.delta_secs() * 1000.0;
+    state.frame_ms += (frame_ms - state.frame_ms) * SMOOTHING;
+
+    if state
+        .last_refresh
+        .is_some_and(|last| last.elapsed() < REFRESH_INTERVAL)
+    {
+        return;
+    }
+    state.last_refresh = Some(Instant::now());
+
+    let Some(strong) = window.as_ref().and_then(|w| w.0.upgrade()) else {
+        return;
+    };
+
+    let mut text = format!(
+        "{:.1} fps ({:.2} ms)\nentities: {}\n",
+        if state.frame_ms > 0.0 { 1000.0 / state.frame_ms } else { 0.0 },
+        state.frame_ms,
+        entities.iter().count()
+    );
+
+    for (set, us) in GAME_SETS.iter().zip(timings.average_us.iter()) {
+        text.push_str(&format!("{:?}: {:.0} us\n", set, us));
+    }
+
+    let atlases = [
+        ("creatures", creatures.map(|s| s.store.atlas_stats())),
+        ("players", players.map(|s| s.store.atlas_stats())),
+        ("items", items.map(|s| s.store.atlas_stats())),
+        ("effects", effects.map(|s| s.effect_manager.atlas_stats())),
+    ];
+    for (name, stats) in atlases {
+        if let Some(stats) = stats {
+            text.push_str(&format!(
+                "atlas {}: {:.1}% ({}K / {}K texels)\n",
+                name,
+                stats.percent_used(),
+                stats.used / 1024,
+                stats.total / 1024
+            ));
+        }
+    }
+
+    let budget_mb = settings.graphics.texture_budget_mb;
+    text.push_str(&format!(
+        "textures: {} in use, {} allocated, budget {}\n",
+        megabytes(texture_memory.in_use()),
+        megabytes(texture_memory.allocated()),
+        if budget_mb == 0 {
+            "off".to_owned()
+        } else {
+            format!("{} MB", budget_mb)
+        }
+    ));
+    text.push_str(&format!(
+        "  map {}, preload {}, frames {}\n",
+        megabytes(texture_memory.map),
+        megabytes(texture_memory.standby_map),
+        megabytes(texture_memory.back_buffers)
+    ));
+
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    game_state.set_debug_overlay_text(slint::SharedString::from(text.trim_end()));
+}
+
+fn reset_debug_overlay(mut state: ResMut<DebugOverlayState>, mut timings: ResMut<GameSetTimings>) {
+    *state = DebugOverlayState::default();
+    *timings = GameSetTimings::default();
+}
@@ -0,0 +1,261 @@
+//! Capture of server packets the client doesn't understand.
+//!
+//! Opcodes that are unknown to the protocol definitions, or known but not yet
+//! handled by the session runtime, are hexdumped to a per-session log under
+//! `diagnostics/` together with a frequency summary, so users can attach the
+//! files to bug reports.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use packets::server;
+
+use crate::app_state::AppState;
+use crate::resources::StorageConfig;
+use crate::webui::ipc::CoreToUi;
+use crate::webui::plugin::UiOutbound;
+
+/// Number of payload samples written per opcode before only counting further packets.
+const MAX_SAMPLES_PER_OPCODE: u32 = 16;
+
+/// Raw payload of a server packet that no system consumed.
+#[derive(Message, Debug, Clone)]
+pub struct UnhandledPacket {
+    pub opcode: u8,
+    /// `None` when the opcode isn't part of [`server::Codes`] at all.
+    pub code: Option<server::Codes>,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct OpcodeStats {
+    code: Option<server::Codes>,
+    count: u32,
+    total_bytes: usize,
+}
+
+/// Per-session capture state. The log file is only created once the first
+/// unhandled packet arrives.
+#[derive(Resource, Default)]
+pub struct UnhandledPacketCapture {
+    log: Option<(PathBuf, File)>,
+    stats: BTreeMap<u8, OpcodeStats>,
+    stats_dirty: bool,
+    notified: bool,
+}
+
+impl UnhandledPacketCapture {
+    pub fn log_path(&self) -> Option<&std::path::Path> {
+        self.log.as_ref().map(|(path, _)| path.as_path())
+    }
+
+    fn stats_path(&self) -> Option<PathBuf> {
+        self.log_path().map(|p| p.with_extension("stats.txt"))
+    }
+
+    fn open_log(&mut self, storage: &StorageConfig) -> Option<&mut File> {
+        if self.log.is_none() {
+            let started = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            let path = storage
+                .diagnostics_dir()
+                .join(format!("unhandled-{}.log", started));
+            match OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(file) => {
+                    tracing::info!("Capturing unhandled packets to {:?}", path);
+                    self.log = Some((path, file));
+                }
+                Err(e) => {
+                    tracing::error!("Failed to create packet capture {:?}: {}", path, e);
+                    return None;
+                }
+            }
+        }
+        self.log.as_mut().map(|(_, file)| file)
+    }
+
+    fn record(&mut self, storage: &StorageConfig, packet: &UnhandledPacket) {
+        let stats = self.stats.entry(packet.opcode).or_default();
+        stats.code = packet.code;
+        stats.count += 1;
+        stats.total_bytes += packet.payload.len();
+        let sample_index = stats.count;
+        self.stats_dirty = true;
+
+        if sample_index > MAX_SAMPLES_PER_OPCODE {
+            return;
+        }
+
+        let entry = format!(
+            "opcode 0x{:02X} ({}) sample {}/{}, {} bytes\n{}\n",
+            packet.opcode,
+            opcode_name(packet.opcode, packet.code),
+            sample_index,
+            MAX_SAMPLES_PER_OPCODE,
+            packet.payload.len(),
+            hexdump(&packet.payload)
+        );
+        if let Some(file) = self.open_log(storage) {
+            if let Err(e) = file.write_all(entry.as_bytes()) {
+                tracing::error!("Failed to write packet capture: {}", e);
+            }
+        }
+    }
+
+    fn write_stats(&mut self) {
+        if !self.stats_dirty {
+            return;
+        }
+        self.stats_dirty = false;
+        let Some(path) = self.stats_path() else {
+            return;
+        };
+
+        let mut sorted: Vec<(&u8, &OpcodeStats)> = self.stats.iter().collect();
+        sorted.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(b.0)));
+
+        let mut out = String::from("opcode  count     bytes  name\n");
+        for (opcode, stats) in sorted {
+            let _ = writeln!(
+                out,
+                "0x{:02X}  {:>7}  {:>8}  {}",
+                opcode,
+                stats.count,
+                stats.total_bytes,
+                opcode_name(*opcode, stats.code)
+            );
+        }
+        if let Err(e) = std::fs::write(&path, out) {
+            tracing::error!("Failed to write packet stats {:?}: {}", path, e);
+        }
+    }
+}
+
+pub struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UnhandledPacketCapture>()
+            .add_message::<UnhandledPacket>()
+            .add_systems(
+                Update,
+                (capture_unhandled_packets, flush_capture_stats)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), finish_capture_session);
+    }
+}
+
+fn capture_unhandled_packets(
+    mut packets: MessageReader<UnhandledPacket>,
+    mut capture: ResMut<UnhandledPacketCapture>,
+    storage: Res<StorageConfig>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    for packet in packets.read() {
+        capture.record(&storage, packet);
+    }
+
+    if capture.notified {
+        return;
+    }
+    if let Some(path) = capture.log_path() {
+        let text = format!(
+            "Received packets this client can't handle yet. They were saved to {} - please attach it to bug reports.",
+            path.display()
+        );
+        outbound.write(UiOutbound(CoreToUi::Toast { text }));
+        capture.notified = true;
+    }
+}
+
+fn flush_capture_stats(
+    time: Res<Time>,
+    mut timer: Local<Timer>,
+    mut capture: ResMut<UnhandledPacketCapture>,
+) {
+    if timer.duration().is_zero() {
+        *timer = Timer::from_seconds(5.0, TimerMode::Repeating);
+    }
+
+    if timer.tick(time.delta()).just_finished() {
+        capture.write_stats();
+    }
+}
+
+fn finish_capture_session(mut capture: ResMut<UnhandledPacketCapture>) {
+    capture.write_stats();
+    *capture = UnhandledPacketCapture::default();
+}
+
+fn opcode_name(opcode: u8, code: Option<server::Codes>) -> String {
+    match code {
+        Some(code) => format!("{:?}", code),
+        None => format!("Unknown{}", opcode),
+    }
+}
+
+/// Classic 16 bytes per row hexdump with an ASCII gutter.
+pub fn hexdump(data: &[u8]) -> String {
+    let mut out = String::new();
+    for (row, chunk) in data.chunks(16).enumerate() {
+        let _ = write!(out, "{:08x}  ", row * 16);
+        for i in 0..16 {
+            match chunk.get(i) {
+                Some(b) => {
+                    let _ = write!(out, "{:02x} ", b);
+                }
+                None => out.push_str("   "),
+            }
+            if i == 7 {
+                out.push(' ');
+            }
+        }
+        out.push_str(" |");
+        for b in chunk {
+            out.push(if b.is_ascii_graphic() || *b == b' ' {
+                *b as char
+            } else {
+                '.'
+            });
+        }
+        out.push_str("|\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hexdump_pads_partial_rows() {
+        let dump = hexdump(b"Hello\x00\x01");
+        assert_eq!(
+            dump,
+            "00000000  48 65 6c 6c 6f 00 01                              |Hello..|\n"
+        );
+    }
+
+    #[test]
+    fn hexdump_splits_rows_at_sixteen_bytes() {
+        let data: Vec<u8> = (0u8..20).collect();
+        let dump = hexdump(&data);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("00000000  00 01 02 03 04 05 06 07  08 09"));
+        assert!(lines[1].starts_with("00000010  10 11 12 13"));
+    }
+
+    #[test]
+    fn hexdump_empty_payload_is_empty() {
+        assert_eq!(hexdump(&[]), "");
+    }
+}
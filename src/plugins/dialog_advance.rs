@@ -0,0 +1,170 @@
+//! Quicker paging through long NPC dialogs.
+//!
+//! Space turns to the next page, "Skip" keeps turning pages until one asks
+//! something of the player, and NPCs listed in
+//! `GameplaySettings::auto_next_npcs` are always skipped through that way,
+//! for quest turn-ins heard a hundred times. Pages with options or a text
+//! field always stop, so nothing is ever answered on the player's behalf.
+
+use bevy::prelude::*;
+use game_ui::UiToCore;
+use packets::client::{DialogInteraction, DialogInteractionArgs};
+use packets::server::{DisplayDialog, DisplayDialogPayload};
+
+use crate::app_state::AppState;
+use crate::events::SessionEvent;
+use crate::network::PacketOutbox;
+use crate::settings_types::Settings;
+use crate::webui::plugin::{ActiveMenuContext, UiInbound, send_menu_selection};
+
+/// Whether a page is only text to read, with another page after it.
+pub fn is_plain_page(has_next: bool, payload: &DisplayDialogPayload) -> bool {
+    has_next
+        && matches!(
+            payload,
+            DisplayDialogPayload::Normal | DisplayDialogPayload::Speak
+        )
+}
+
+pub fn is_auto_next(npc: &str, npcs: &[String]) -> bool {
+    !npc.is_empty() && npcs.iter().any(|name| name.eq_ignore_ascii_case(npc))
+}
+
+/// The dialog page on screen, for the UI.
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct DialogAdvance {
+    pub npc: String,
+    pub has_next: bool,
+    /// Whether `npc` is in `GameplaySettings::auto_next_npcs`.
+    pub auto_next: bool,
+}
+
+/// Entity whose pages are being skipped through.
+#[derive(Resource, Default)]
+struct FastForward(Option<u32>);
+
+pub struct DialogAdvancePlugin;
+
+impl Plugin for DialogAdvancePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DialogAdvance>()
+            .init_resource::<FastForward>()
+            .add_systems(OnExit(AppState::InGame), reset_dialog_advance)
+            .add_systems(
+                Update,
+                (advance_dialogs, handle_dialog_advance_input).run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn reset_dialog_advance(mut advance: ResMut<DialogAdvance>, mut fast_forward: ResMut<FastForward>) {
+    advance.set_if_neq(DialogAdvance::default());
+    fast_forward.0 = None;
+}
+
+fn advance_dialogs(
+    mut session_events: MessageReader<SessionEvent>,
+    settings: Res<Settings>,
+    outbox: Res<PacketOutbox>,
+    mut advance: ResMut<DialogAdvance>,
+    mut fast_forward: ResMut<FastForward>,
+) {
+    for event in session_events.read() {
+        match event {
+            SessionEvent::DisplayDialog(DisplayDialog::Show { header, payload }) => {
+                let auto_next = is_auto_next(&header.name, &settings.gameplay.auto_next_npcs);
+                let plain = is_plain_page(header.has_next_button, payload);
+                advance.set_if_neq(DialogAdvance {
+                    npc: header.name.clone(),
+                    has_next: plain,
+                    auto_next,
+                });
+
+                if plain && (auto_next || fast_forward.0 == Some(header.source_id)) {
+                    fast_forward.0 = Some(header.source_id);
+                    outbox.send(&DialogInteraction {
+                        entity_type: header.entity_type,
+                        entity_id: header.source_id,
+                        pursuit_id: header.pursuit_id,
+                        dialog_id: header.dialog_id.wrapping_add(1),
+                        args: DialogInteractionArgs::None,
+                    });
+                } else {
+                    fast_forward.0 = None;
+                }
+            }
+            SessionEvent::DisplayDialog(DisplayDialog::Close) => {
+                advance.set_if_neq(DialogAdvance::default());
+                fast_forward.0 = None;
+            }
+            SessionEvent::DisplayMenu(pkt) => {
+                let auto_next = is_auto_next(&pkt.header.name, &settings.gameplay.auto_next_npcs);
+                advance.set_if_neq(DialogAdvance {
+                    npc: pkt.header.name.clone(),
+                    has_next: false,
+                    auto_next,
+                });
+                fast_forward.0 = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn handle_dialog_advance_input(
+    mut inbound: MessageReader<UiInbound>,
+    menu_ctx: Res<ActiveMenuContext>,
+    outbox: Res<PacketOutbox>,
+    mut settings: ResMut<Settings>,
+    mut advance: ResMut<DialogAdvance>,
+    mut fast_forward: ResMut<FastForward>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::DialogNext | UiToCore::DialogSkip => {
+                let Some(dialog_id) = menu_ctx.dialog_id.filter(|_| advance.has_next) else {
+                    continue;
+                };
+                if matches!(msg, UiToCore::DialogSkip) {
+                    fast_forward.0 = Some(menu_ctx.entity_id);
+                }
+                send_menu_selection(&menu_ctx, dialog_id as i32 + 1, "Next", &outbox);
+            }
+            UiToCore::DialogAutoNext { enabled } => {
+                if advance.npc.is_empty() {
+                    continue;
+                }
+                let npc = advance.npc.clone();
+                let npcs = &mut settings.gameplay.auto_next_npcs;
+                npcs.retain(|name| !name.eq_ignore_ascii_case(&npc));
+                if *enabled {
+                    npcs.push(npc);
+                }
+                advance.auto_next = *enabled;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_plain_pages_of_listed_npcs_turn_themselves() {
+        assert!(is_plain_page(true, &DisplayDialogPayload::Normal));
+        assert!(!is_plain_page(false, &DisplayDialogPayload::Normal));
+        assert!(!is_plain_page(
+            true,
+            &DisplayDialogPayload::DialogMenu {
+                options: vec!["Yes".into()]
+            }
+        ));
+
+        let npcs = vec!["Aoife".to_owned()];
+        assert!(is_auto_next("aoife", &npcs));
+        assert!(!is_auto_next("Riona", &npcs));
+        assert!(!is_auto_next("", &npcs));
+    }
+}
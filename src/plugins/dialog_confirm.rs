@@ -0,0 +1,101 @@
+//! Confirmation before picking a dangerous NPC menu or dialog option.
+//!
+//! Options whose text contains an entry of
+//! `GameplaySettings::dangerous_dialog_options`, such as renouncing a faith,
+//! are held back until the player confirms them, so a misclick in a long
+//! conversation can't undo hours of progress.
+
+use bevy::prelude::*;
+use game_ui::UiToCore;
+
+use crate::app_state::AppState;
+use crate::network::PacketOutbox;
+use crate::webui::plugin::{ActiveMenuContext, UiInbound, send_menu_selection};
+
+/// Option list until the player edits it.
+pub const DEFAULT_DANGEROUS_OPTIONS: &[&str] = &["Renounce", "Desecrate", "Forget"];
+
+/// Whether `option` contains one of the listed phrases, ignoring case.
+pub fn is_dangerous(option: &str, phrases: &[String]) -> bool {
+    let option = option.to_lowercase();
+    phrases
+        .iter()
+        .any(|phrase| option.contains(&phrase.to_lowercase()))
+}
+
+/// Splits the option list as typed in the settings, one entry per comma or line.
+pub fn parse_option_list(list: &str) -> Vec<String> {
+    let mut phrases: Vec<String> = Vec::new();
+    for phrase in list.split([',', '\n']).map(str::trim) {
+        if !phrase.is_empty() && !phrases.iter().any(|p| p.eq_ignore_ascii_case(phrase)) {
+            phrases.push(phrase.to_string());
+        }
+    }
+    phrases
+}
+
+#[derive(Clone)]
+pub struct DialogConfirmRequest {
+    pub option: String,
+    pub id: i32,
+    pub name: String,
+    /// The menu the option was picked from, in case another opens meanwhile.
+    pub menu: ActiveMenuContext,
+}
+
+/// Option waiting on the confirmation dialog.
+#[derive(Resource, Default)]
+pub struct PendingDialogConfirm(pub Option<DialogConfirmRequest>);
+
+pub struct DialogConfirmPlugin;
+
+impl Plugin for DialogConfirmPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingDialogConfirm>()
+            .add_systems(OnExit(AppState::InGame), clear_dialog_confirm)
+            .add_systems(
+                Update,
+                handle_dialog_confirm_input.run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn clear_dialog_confirm(mut pending: ResMut<PendingDialogConfirm>) {
+    pending.0 = None;
+}
+
+fn handle_dialog_confirm_input(
+    mut inbound: MessageReader<UiInbound>,
+    outbox: Res<PacketOutbox>,
+    mut pending: ResMut<PendingDialogConfirm>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::DialogConfirmAccept => {
+                if let Some(request) = pending.0.take() {
+                    send_menu_selection(&request.menu, request.id, &request.name, &outbox);
+                }
+            }
+            UiToCore::DialogConfirmCancel => {
+                pending.0 = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listed_phrases_match_anywhere_in_an_option() {
+        let phrases = parse_option_list("Renounce your Faith,\n desecrate , renounce your faith");
+        assert_eq!(phrases, ["Renounce your Faith", "desecrate"]);
+
+        assert!(is_dangerous("I wish to renounce your faith.", &phrases));
+        assert!(is_dangerous("Desecrate the altar", &phrases));
+        assert!(!is_dangerous("Worship", &phrases));
+        assert!(!is_dangerous("Renounce", &phrases));
+    }
+}
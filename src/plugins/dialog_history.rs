@@ -0,0 +1,158 @@
+//! Conversation history: every NPC dialog and menu seen this session.
+//!
+//! Dialog pages vanish once closed or clicked past, taking quest hints with
+//! them. Each page is kept here with the NPC's name, its text and the options
+//! it offered, and can be read back in the conversation history window.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use game_ui::UiToCore;
+use packets::server::{DisplayDialog, DisplayDialogPayload, DisplayMenuPayload};
+
+use crate::app_state::AppState;
+use crate::events::SessionEvent;
+use crate::webui::plugin::UiInbound;
+
+/// Oldest entries are dropped past this many.
+const MAX_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialogHistoryEntry {
+    /// Local wall-clock time, "HH:MM:SS".
+    pub time: String,
+    pub npc: String,
+    pub text: String,
+    pub options: Vec<String>,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct DialogHistory {
+    entries: VecDeque<DialogHistoryEntry>,
+    /// Matched against NPC names and dialog text; blank shows everything.
+    pub search: String,
+}
+
+impl DialogHistory {
+    /// Entries matching the search, oldest first.
+    pub fn filtered(&self) -> impl Iterator<Item = &DialogHistoryEntry> {
+        let search = self.search.trim().to_lowercase();
+        self.entries.iter().filter(move |entry| {
+            search.is_empty()
+                || entry.npc.to_lowercase().contains(&search)
+                || entry.text.to_lowercase().contains(&search)
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Adds a page, unless it is blank or the same as the last one, as when
+    /// the server re-sends a dialog after a refresh.
+    fn record(&mut self, npc: &str, text: &str, options: Vec<String>) {
+        let text = text.trim();
+        if text.is_empty() && options.is_empty() {
+            return;
+        }
+        if self
+            .entries
+            .back()
+            .is_some_and(|last| last.npc == npc && last.text == text && last.options == options)
+        {
+            return;
+        }
+        if self.entries.len() >= MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(DialogHistoryEntry {
+            time: chrono::Local::now().format("%H:%M:%S").to_string(),
+            npc: npc.to_owned(),
+            text: text.to_owned(),
+            options,
+        });
+    }
+}
+
+pub struct DialogHistoryPlugin;
+
+impl Plugin for DialogHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DialogHistory>()
+            .add_systems(OnExit(AppState::InGame), clear_dialog_history)
+            .add_systems(
+                Update,
+                (record_dialog_history, handle_dialog_history_input)
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn clear_dialog_history(mut commands: Commands) {
+    commands.insert_resource(DialogHistory::default());
+}
+
+fn record_dialog_history(
+    mut session_events: MessageReader<SessionEvent>,
+    mut history: ResMut<DialogHistory>,
+) {
+    for event in session_events.read() {
+        match event {
+            SessionEvent::DisplayDialog(DisplayDialog::Show { header, payload }) => {
+                let options = match payload {
+                    DisplayDialogPayload::DialogMenu { options }
+                    | DisplayDialogPayload::CreatureMenu { options } => options.clone(),
+                    _ => Vec::new(),
+                };
+                history.record(&header.name, &header.text, options);
+            }
+            SessionEvent::DisplayMenu(pkt) => {
+                let options = match &pkt.payload {
+                    DisplayMenuPayload::Menu { options }
+                    | DisplayMenuPayload::MenuWithArgs { options, .. } => {
+                        options.iter().map(|(text, _)| text.clone()).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                history.record(&pkt.header.name, &pkt.header.text, options);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn handle_dialog_history_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut history: ResMut<DialogHistory>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::DialogHistorySearch { text } => history.search = text.clone(),
+            UiToCore::DialogHistoryClear => history.clear(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_pages_are_kept_once_and_searchable() {
+        let mut history = DialogHistory::default();
+        history.record("Aoife", "Bring me three wolf pelts.", vec!["Accept".into()]);
+        history.record("Aoife", "Bring me three wolf pelts.", vec!["Accept".into()]);
+        history.record("Aoife", "  ", Vec::new());
+        history.record("Riona", "The crypt lies east of Mileth.", Vec::new());
+        assert_eq!(history.filtered().count(), 2);
+
+        history.search = "CRYPT".to_owned();
+        let found: Vec<_> = history.filtered().collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].npc, "Riona");
+
+        history.search = "aoife".to_owned();
+        assert_eq!(history.filtered().next().unwrap().options, ["Accept"]);
+    }
+}
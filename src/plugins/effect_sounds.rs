@@ -0,0 +1,129 @@
+//! Per-server sounds for spell and effect animations.
+//!
+//! Animation packets only carry an effect id, so the client keeps its own
+//! effect id → sound id table in `effect_sounds.json` inside the server's data
+//! directory. Mapped effects play through the spells volume, attenuated by
+//! how far they are from the player.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::app_state::AppState;
+use crate::events::{AudioEvent, EntityEvent, SoundOrigin};
+use crate::resources::StorageConfig;
+use crate::settings_types::SoundCategory;
+
+/// Sound ids by effect id for the current server.
+#[derive(Resource, Default, Debug)]
+pub struct EffectSounds {
+    sounds: BTreeMap<u16, u8>,
+}
+
+impl EffectSounds {
+    pub fn load(path: PathBuf) -> Self {
+        let sounds = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring malformed {}: {}", path.display(), e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+
+        Self { sounds }
+    }
+
+    pub fn get(&self, effect_id: u16) -> Option<u8> {
+        self.sounds.get(&effect_id).copied()
+    }
+}
+
+pub struct EffectSoundsPlugin;
+
+impl Plugin for EffectSoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectSounds>()
+            .add_systems(OnEnter(AppState::InGame), load_effect_sounds)
+            .add_systems(OnExit(AppState::InGame), clear_effect_sounds)
+            .add_systems(
+                Update,
+                play_effect_sounds.run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn load_effect_sounds(
+    mut commands: Commands,
+    storage: Res<StorageConfig>,
+    session: Option<Res<crate::CurrentSession>>,
+) {
+    let Some(session) = session else {
+        return;
+    };
+    commands.insert_resource(EffectSounds::load(
+        storage.server_effect_sounds_path(session.server_id),
+    ));
+}
+
+fn clear_effect_sounds(mut commands: Commands) {
+    commands.insert_resource(EffectSounds::default());
+}
+
+fn play_effect_sounds(
+    mut entity_events: MessageReader<EntityEvent>,
+    mut audio_events: MessageWriter<AudioEvent>,
+    sounds: Res<EffectSounds>,
+) {
+    for event in entity_events.read() {
+        let EntityEvent::Effect(anim) = event else {
+            continue;
+        };
+
+        let mut play = |effect_id: u16, origin: SoundOrigin| {
+            if let Some(sound) = sounds.get(effect_id) {
+                audio_events.write(AudioEvent::PlayEffect {
+                    sound,
+                    category: SoundCategory::Spells,
+                    origin,
+                });
+            }
+        };
+
+        match *anim {
+            packets::server::Animation::Target {
+                target_animation,
+                target_point: (x, y),
+                ..
+            } => play(target_animation, SoundOrigin::Tile(x, y)),
+            packets::server::Animation::Source {
+                target_id,
+                source_id,
+                target_animation,
+                source_animation,
+                ..
+            } => {
+                if let Some(effect_id) = target_animation {
+                    play(effect_id, SoundOrigin::Entity(target_id));
+                }
+                if let Some(effect_id) = source_animation {
+                    play(effect_id, SoundOrigin::Entity(source_id));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_effect_sound_table() {
+        let sounds: BTreeMap<u16, u8> = serde_json::from_str(r#"{"12": 3, "250": 41}"#).unwrap();
+        let sounds = EffectSounds { sounds };
+        assert_eq!(sounds.get(12), Some(3));
+        assert_eq!(sounds.get(250), Some(41));
+        assert_eq!(sounds.get(13), None);
+    }
+}
@@ -0,0 +1,296 @@
+//! Developer window listing the ECS entities with their components, for
+//! tracking down sprites that exist on the server but never show up.
+//!
+//! Toggled with [`GameAction::EntityInspector`] while `graphics.debug_overlay`
+//! is enabled, like the debug overlay. The search box matches server ids, and
+//! names for convenience. Entities with a server id are listed first, and the
+//! list is cut off at `MAX_ROWS`.
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use game_ui::UiToCore;
+
+use crate::app_state::AppState;
+use crate::ecs::animation::Animation;
+use crate::ecs::components::{
+    CreatureInstance, CreatureLoadRequested, CreatureLoading, CreatureSprite, EntityId, ItemSprite,
+    NPC, Player, Position,
+};
+use crate::input::{GameAction, GamepadConfig, UnifiedInputBindings};
+use crate::settings_types::Settings;
+use crate::slint_support::state_bridge::SlintWindow;
+use crate::webui::plugin::UiInbound;
+
+const MAX_ROWS: usize = 200;
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Resource, Default)]
+pub struct EntityInspectorState {
+    pub visible: bool,
+    query: String,
+    last_refresh: Option<Instant>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectedEntity {
+    pub entity: Entity,
+    pub server_id: Option<u32>,
+    pub name: String,
+    pub position: Option<Position>,
+    /// Sprite id and whether the renderer has it.
+    pub sprite: String,
+    pub animation: String,
+    pub components: Vec<String>,
+}
+
+impl InspectedEntity {
+    fn new(world: &World, entity: Entity) -> Self {
+        let entity_ref = world.entity(entity);
+        let name = entity_ref
+            .get::<NPC>()
+            .map(|npc| npc.name.clone())
+            .or_else(|| entity_ref.get::<Player>().map(|p| p.name.clone()))
+            .unwrap_or_default();
+
+        let sprite = if let Some(sprite) = entity_ref.get::<CreatureSprite>() {
+            let state = if entity_ref.contains::<CreatureInstance>() {
+                "drawn"
+            } else if entity_ref.contains::<CreatureLoading>()
+                || entity_ref.contains::<CreatureLoadRequested>()
+            {
+                "loading"
+            } else {
+                "not drawn"
+            };
+            format!("creature {} ({})", sprite.id, state)
+        } else if let Some(sprite) = entity_ref.get::<ItemSprite>() {
+            format!("item {} color {}", sprite.id, sprite.color)
+        } else {
+            String::new()
+        };
+
+        let animation = entity_ref
+            .get::<Animation>()
+            .map(|anim| {
+                format!(
+                    "{:?} frame {}/{}",
+                    anim.anim_type, anim.current_frame, anim.end_index
+                )
+            })
+            .unwrap_or_default();
+
+        let mut components: Vec<String> = world
+            .inspect_entity(entity)
+            .map(|infos| {
+                infos
+                    .map(|info| info.name().shortname().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        components.sort();
+
+        Self {
+            entity,
+            server_id: entity_ref.get::<EntityId>().map(|id| id.id),
+            name,
+            position: entity_ref.get::<Position>().copied(),
+            sprite,
+            animation,
+            components,
+        }
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        let query = query.trim();
+        query.is_empty()
+            || self
+                .server_id
+                .is_some_and(|id| id.to_string().contains(query))
+            || self.name.to_lowercase().contains(&query.to_lowercase())
+    }
+}
+
+/// Entities matching the search, server entities first by id. Also returns
+/// how many matched before the list was cut off.
+pub fn inspect_entities(world: &mut World, query: &str) -> (Vec<InspectedEntity>, usize) {
+    let entities: Vec<Entity> = world.query::<Entity>().iter(world).collect();
+    let mut rows: Vec<InspectedEntity> = entities
+        .into_iter()
+        .map(|entity| InspectedEntity::new(world, entity))
+        .filter(|row| row.matches(query))
+        .collect();
+    rows.sort_by_key(|row| (row.server_id.is_none(), row.server_id, row.entity));
+    let matched = rows.len();
+    rows.truncate(MAX_ROWS);
+    (rows, matched)
+}
+
+pub struct EntityInspectorPlugin;
+
+impl Plugin for EntityInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityInspectorState>()
+            .add_systems(
+                Update,
+                (
+                    (toggle_entity_inspector, handle_entity_inspector_input),
+                    refresh_entity_inspector.run_if(inspector_visible),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_entity_inspector);
+    }
+}
+
+fn inspector_visible(state: Res<EntityInspectorState>) -> bool {
+    state.visible
+}
+
+fn reset_entity_inspector(mut state: ResMut<EntityInspectorState>) {
+    *state = EntityInspectorState::default();
+}
+
+/// Shows or hides the inspector, also used by the debug console.
+pub(crate) fn set_inspector_visible(
+    state: &mut EntityInspectorState,
+    window: Option<&SlintWindow>,
+    visible: bool,
+) {
+    state.visible = visible;
+    state.last_refresh = None;
+    if let Some(strong) = window.and_then(|w| w.0.upgrade()) {
+        let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+        game_state.set_show_entity_inspector(visible);
+    }
+}
+
+fn toggle_entity_inspector(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<UnifiedInputBindings>,
+    gamepad_query: Query<&Gamepad>,
+    gamepad_config: Res<GamepadConfig>,
+    settings: Res<Settings>,
+    mut state: ResMut<EntityInspectorState>,
+    window: Option<Res<SlintWindow>>,
+) {
+    if !settings.graphics.debug_overlay {
+        if state.visible {
+            set_inspector_visible(&mut state, window.as_deref(), false);
+        }
+    } else if bindings.is_just_pressed(
+        GameAction::EntityInspector,
+        &keyboard_input,
+        Some(&gamepad_query),
+        Some(&gamepad_config),
+    ) {
+        let visible = !state.visible;
+        set_inspector_visible(&mut state, window.as_deref(), visible);
+    }
+}
+
+fn handle_entity_inspector_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut state: ResMut<EntityInspectorState>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::EntityInspectorSearch { query } => {
+                state.query = query.clone();
+                state.last_refresh = None;
+            }
+            UiToCore::EntityInspectorClose => {
+                state.visible = false;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn refresh_entity_inspector(world: &mut World) {
+    let query = {
+        let mut state = world.resource_mut::<EntityInspectorState>();
+        if state
+            .last_refresh
+            .is_some_and(|last| last.elapsed() < REFRESH_INTERVAL)
+        {
+            return;
+        }
+        state.last_refresh = Some(Instant::now());
+        state.query.clone()
+    };
+    let Some(strong) = world
+        .get_resource::<SlintWindow>()
+        .and_then(|w| w.0.upgrade())
+    else {
+        return;
+    };
+
+    let (rows, matched) = inspect_entities(world, &query);
+    let rows: Vec<crate::EntityInspectorRow> = rows
+        .iter()
+        .map(|row| crate::EntityInspectorRow {
+            entity: slint::SharedString::from(format!("{}", row.entity)),
+            server_id: slint::SharedString::from(
+                row.server_id.map(|id| id.to_string()).unwrap_or_default(),
+            ),
+            name: slint::SharedString::from(row.name.as_str()),
+            position: slint::SharedString::from(
+                row.position
+                    .map(|pos| format!("{:.1}, {:.1}", pos.x, pos.y))
+                    .unwrap_or_default(),
+            ),
+            sprite: slint::SharedString::from(row.sprite.as_str()),
+            animation: slint::SharedString::from(row.animation.as_str()),
+            components: slint::SharedString::from(row.components.join(", ")),
+        })
+        .collect();
+
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    game_state.set_entity_inspector_summary(slint::SharedString::from(if matched > rows.len() {
+        format!("Showing {} of {} entities", rows.len(), matched)
+    } else {
+        format!("{} entities", matched)
+    }));
+    game_state.set_entity_inspector(slint::ModelRc::new(slint::VecModel::from(rows)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_matches_server_ids_and_names() {
+        let mut world = World::new();
+        world.spawn((
+            EntityId { id: 4102 },
+            Position::new(3.0, 7.0),
+            CreatureSprite { id: 12 },
+        ));
+        world.spawn((
+            EntityId { id: 77 },
+            Player {
+                name: "Aisling".into(),
+                is_male: true,
+            },
+        ));
+        world.spawn(Position::new(0.0, 0.0));
+
+        let (rows, matched) = inspect_entities(&mut world, "");
+        assert_eq!(matched, 3);
+        assert_eq!(rows[0].server_id, Some(77));
+        assert_eq!(rows[1].server_id, Some(4102));
+        assert_eq!(rows[2].server_id, None);
+
+        let (rows, _) = inspect_entities(&mut world, "410");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].sprite, "creature 12 (not drawn)");
+        assert_eq!(rows[0].position, Some(Position::new(3.0, 7.0)));
+        assert_eq!(rows[0].components.len(), 3);
+
+        let (rows, _) = inspect_entities(&mut world, "aisl");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].name, "Aisling");
+    }
+}
@@ -0,0 +1,214 @@
+//! Dumps the instance buffers of the current frame and the camera state to a
+//! JSON file in the diagnostics directory, for attaching to rendering bug
+//! reports.
+//!
+//! Like the debug overlay, [`GameAction::CaptureFrame`] only does anything
+//! while `graphics.debug_overlay` is enabled. Instances are read back from the
+//! GPU, so the file shows what was actually drawn rather than what the ECS
+//! meant to draw. Freed slots are left out.
+
+use bevy::prelude::*;
+use game_ui::CoreToUi;
+use rendering::InstanceRaw;
+use rendering::scene::CameraState;
+use serde::Serialize;
+
+use crate::app_state::AppState;
+use crate::input::{GameAction, GamepadConfig, UnifiedInputBindings};
+use crate::resources::StorageConfig;
+use crate::settings_types::Settings;
+use crate::webui::plugin::UiOutbound;
+use crate::{
+    Camera, CreatureBatchState, EffectManagerState, ItemBatchState, MapRendererState,
+    PlayerBatchState, RendererState,
+};
+
+#[derive(Serialize)]
+struct FrameCapture {
+    camera: CameraCapture,
+    batches: Vec<BatchCapture>,
+}
+
+#[derive(Serialize)]
+struct CameraCapture {
+    width: f32,
+    height: f32,
+    position: [f32; 2],
+    zoom: f32,
+    view_proj: [[f32; 4]; 4],
+    tint: [f32; 4],
+    highlight: f32,
+    xray_size: f32,
+    xray_mode: u32,
+}
+
+impl CameraCapture {
+    fn new(state: &CameraState) -> Self {
+        let uniform = state.uniform();
+        Self {
+            width: state.camera.width,
+            height: state.camera.height,
+            position: state.camera.position.to_array(),
+            zoom: state.camera.zoom,
+            view_proj: uniform.view_proj,
+            tint: uniform.tint,
+            highlight: uniform.highlight,
+            xray_size: uniform.xray_size,
+            xray_mode: uniform.xray_mode,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BatchCapture {
+    name: String,
+    instances: Vec<InstanceCapture>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct InstanceCapture {
+    /// Slot in the instance buffer.
+    index: usize,
+    position: [f32; 3],
+    tex_min: [f32; 2],
+    tex_max: [f32; 2],
+    sprite_size: [f32; 2],
+    palette_offset: f32,
+    dye_v_offset: f32,
+    flags: u32,
+    tint: [f32; 3],
+}
+
+impl BatchCapture {
+    fn new(name: impl Into<String>, raw: &[InstanceRaw]) -> Self {
+        let instances = raw
+            .iter()
+            .enumerate()
+            // Freed slots are reset to the default, which has no size
+            .filter(|(_, raw)| raw.sprite_size != [0.0; 2])
+            .map(|(index, raw)| InstanceCapture {
+                index,
+                position: raw.position,
+                tex_min: raw.tex_min,
+                tex_max: raw.tex_max,
+                sprite_size: raw.sprite_size,
+                palette_offset: raw.palette_offset,
+                dye_v_offset: raw.dye_v_offset,
+                flags: raw.flags,
+                tint: raw.tint,
+            })
+            .collect();
+        Self {
+            name: name.into(),
+            instances,
+        }
+    }
+}
+
+pub struct FrameCapturePlugin;
+
+impl Plugin for FrameCapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, capture_frame.run_if(in_state(AppState::InGame)));
+    }
+}
+
+fn capture_frame(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<UnifiedInputBindings>,
+    gamepad_query: Query<&Gamepad>,
+    gamepad_config: Res<GamepadConfig>,
+    settings: Res<Settings>,
+    storage: Res<StorageConfig>,
+    renderer: Option<Res<RendererState>>,
+    camera: Option<Res<Camera>>,
+    map: Option<Res<MapRendererState>>,
+    creatures: Option<Res<CreatureBatchState>>,
+    players: Option<Res<PlayerBatchState>>,
+    items: Option<Res<ItemBatchState>>,
+    effects: Option<Res<EffectManagerState>>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    if !settings.graphics.debug_overlay
+        || !bindings.is_just_pressed(
+            GameAction::CaptureFrame,
+            &keyboard_input,
+            Some(&gamepad_query),
+            Some(&gamepad_config),
+        )
+    {
+        return;
+    }
+    let (Some(renderer), Some(camera)) = (renderer, camera) else {
+        return;
+    };
+    let (device, queue) = (&renderer.device, &renderer.queue);
+
+    let mut batches = Vec::new();
+    if let Some(map) = &map {
+        // Only loaded chunks have a buffer to read back
+        for (i, batch) in map.map_renderer.instance_batches().iter().enumerate() {
+            for (j, chunk) in batch.loaded().enumerate() {
+                batches.push(BatchCapture::new(
+                    format!("map {} chunk {}", i, j),
+                    &chunk.read_back(device, queue),
+                ));
+            }
+        }
+    }
+    let shared = [
+        ("items", items.as_ref().map(|s| s.batch.instances())),
+        ("creatures", creatures.as_ref().map(|s| s.batch.instances())),
+        ("players", players.as_ref().map(|s| s.batch.instances())),
+        (
+            "effects",
+            effects.as_ref().map(|s| s.effect_manager.instances()),
+        ),
+    ];
+    for (name, batch) in shared {
+        if let Some(batch) = batch {
+            batches.push(BatchCapture::new(name, &batch.read_back(device, queue)));
+        }
+    }
+
+    let capture = FrameCapture {
+        camera: CameraCapture::new(&camera.camera),
+        batches,
+    };
+    let path = storage.diagnostics_dir().join(format!(
+        "frame-{}.json",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let result = serde_json::to_string_pretty(&capture)
+        .map_err(std::io::Error::from)
+        .and_then(|json| std::fs::write(&path, json));
+    let text = match result {
+        Ok(()) => format!("Frame captured to {}", path.display()),
+        Err(e) => {
+            error!("Failed to write frame capture: {}", e);
+            format!("Couldn't save the frame capture: {}", e)
+        }
+    };
+    outbound.write(UiOutbound(CoreToUi::Toast { text }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn freed_slots_are_skipped() {
+        let drawn = InstanceRaw {
+            position: [1.0, 2.0, 0.5],
+            sprite_size: [28.0, 14.0],
+            flags: 2,
+            ..Default::default()
+        };
+        let batch = BatchCapture::new("items", &[InstanceRaw::default(), drawn]);
+
+        assert_eq!(batch.instances.len(), 1);
+        assert_eq!(batch.instances[0].index, 1);
+        assert_eq!(batch.instances[0].position, [1.0, 2.0, 0.5]);
+        assert_eq!(batch.instances[0].flags, 2);
+    }
+}
@@ -0,0 +1,135 @@
+//! Social status picked on the profile, and automatic Daydreaming after a
+//! period without input.
+//!
+//! After `gameplay.idle_daydream_minutes` without keyboard, mouse or gamepad
+//! input the client switches the social status to Daydreaming and restores the
+//! previous status on the next input. A value of 0 disables the timeout.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_ui::UiToCore;
+use packets::types::SocialStatus;
+
+use crate::app_state::AppState;
+use crate::network::PacketOutbox;
+use crate::settings_types::Settings;
+use crate::webui::plugin::{CursorPosition, UiInbound};
+
+/// Social status the client last sent to the server.
+#[derive(Resource, Debug)]
+pub struct SocialStatusState {
+    pub status: SocialStatus,
+    /// Status to restore once the player is active again, set while auto-daydreaming.
+    pub restore_to: Option<SocialStatus>,
+    idle_for: Duration,
+}
+
+impl Default for SocialStatusState {
+    fn default() -> Self {
+        Self {
+            status: SocialStatus::Awake,
+            restore_to: None,
+            idle_for: Duration::ZERO,
+        }
+    }
+}
+
+impl SocialStatusState {
+    pub fn set(&mut self, status: SocialStatus, outbox: &PacketOutbox) {
+        if self.status == status {
+            return;
+        }
+        outbox.send(&packets::client::SocialStatus {
+            social_status: status.into(),
+        });
+        self.status = status;
+    }
+
+    /// Time since the last keyboard, mouse or gamepad input.
+    pub fn idle_for(&self) -> Duration {
+        self.idle_for
+    }
+}
+
+pub struct IdlePlugin;
+
+impl Plugin for IdlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SocialStatusState>()
+            .add_systems(
+                Update,
+                (
+                    track_idle_status.after(crate::plugins::input::InputPumpSet),
+                    handle_social_status_input,
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_social_status);
+    }
+}
+
+fn track_idle_status(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorPosition>,
+    gamepads: Query<&Gamepad>,
+    outbox: Option<Res<PacketOutbox>>,
+    mut state: ResMut<SocialStatusState>,
+) {
+    let active = keyboard.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+        || cursor.is_changed()
+        || gamepads
+            .iter()
+            .any(|g| g.get_just_pressed().next().is_some());
+
+    if active {
+        state.idle_for = Duration::ZERO;
+        if let (Some(previous), Some(out)) = (state.restore_to.take(), outbox.as_ref()) {
+            tracing::info!("Input after idle, restoring social status {:?}", previous);
+            state.set(previous, out);
+        }
+        return;
+    }
+
+    state.idle_for += time.delta();
+    let minutes = settings.gameplay.idle_daydream_minutes;
+    if minutes == 0
+        || state.restore_to.is_some()
+        || state.status == SocialStatus::DayDreaming
+        || state.idle_for < Duration::from_secs(u64::from(minutes) * 60)
+    {
+        return;
+    }
+
+    if let Some(out) = outbox.as_ref() {
+        tracing::info!("Idle for {} minutes, switching to Daydreaming", minutes);
+        state.restore_to = Some(state.status);
+        state.set(SocialStatus::DayDreaming, out);
+    }
+}
+
+fn handle_social_status_input(
+    mut inbound: MessageReader<UiInbound>,
+    outbox: Option<Res<PacketOutbox>>,
+    mut state: ResMut<SocialStatusState>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        let UiToCore::SocialStatusChange { status } = msg else {
+            continue;
+        };
+        let (Ok(status), Some(out)) = (SocialStatus::try_from(*status), outbox.as_ref()) else {
+            continue;
+        };
+        // Picked by hand, so coming back from idle keeps it
+        state.restore_to = None;
+        state.set(status, out);
+    }
+}
+
+fn reset_social_status(mut state: ResMut<SocialStatusState>) {
+    *state = SocialStatusState::default();
+}
@@ -9,7 +9,7 @@ use crate::{
         UnifiedInputBindings, gamepad_rebinding_system, sync_rebinding_state_from_slint,
     },
     network::PacketOutbox,
-    settings_types::Settings,
+    settings_types::{DirectionPriority, Settings},
 };
 use bevy::prelude::*;
 use game_types::SlotPanelType;
@@ -42,10 +42,12 @@ impl Plugin for InputPlugin {
                     resolve_android_touch_events_system,
                     crate::slint_support::input_bridge::pump_slint_scroll_events_system,
                     pump_double_clicks_system,
+                    update_input_bindings_system,
                 )
                     .chain()
                     .in_set(InputPumpSet),
             )
+            .add_systems(OnExit(AppState::InGame), release_toggled_actions)
             .add_systems(
                 Update,
                 (
@@ -61,6 +63,21 @@ impl Plugin for InputPlugin {
     }
 }
 
+/// Applies this frame's mouse buttons and toggle presses to the bindings.
+fn update_input_bindings_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_query: Query<&Gamepad>,
+    gamepad_config: Res<GamepadConfig>,
+    mut bindings: ResMut<UnifiedInputBindings>,
+) {
+    bindings.update(&keyboard, &mouse, Some(&gamepad_query), Some(&gamepad_config));
+}
+
+fn release_toggled_actions(mut bindings: ResMut<UnifiedInputBindings>) {
+    bindings.release_toggles();
+}
+
 pub fn pump_double_clicks_system(
     queue: Res<crate::slint_support::input_bridge::SlintDoubleClickQueue>,
     mut events: MessageWriter<crate::slint_plugin::SlintDoubleClickEvent>,
@@ -202,11 +219,25 @@ fn resolve_android_touch_events_system(
     }
 }
 
+const WALK_COOLDOWN_SECS: f32 = 0.5;
+/// How long a movement key pressed mid-step is remembered.
+const MOVE_BUFFER_SECS: f32 = 0.25;
+
+/// Movement actions in their fixed priority order.
+const MOVE_DIRECTIONS: [(GameAction, Direction); 4] = [
+    (GameAction::MoveUp, Direction::Up),
+    (GameAction::MoveDown, Direction::Down),
+    (GameAction::MoveLeft, Direction::Left),
+    (GameAction::MoveRight, Direction::Right),
+];
+
 #[derive(Resource)]
 pub struct InputTimer {
-    walk_cd: Timer,            // gates actual movement (walk)
-    primed: bool,              // first walk allowed immediately
-    turn_grace: Option<Timer>, // suppress walking right after a facing change
+    walk_cd: Timer,                       // gates actual movement (walk)
+    primed: bool,                         // first walk allowed immediately
+    turn_grace: Option<Timer>,            // a facing change only turns unless held past this
+    held: Vec<Direction>,                 // held movement keys, oldest press first
+    buffered: Option<(Direction, Timer)>, // press made while a step was playing out
 }
 
 impl Default for InputTimer {
@@ -215,6 +246,8 @@ impl Default for InputTimer {
             walk_cd: Timer::from_seconds(0.0, TimerMode::Once), // finished immediately
             primed: true,
             turn_grace: None,
+            held: Vec::new(),
+            buffered: None,
         }
     }
 }
@@ -225,6 +258,17 @@ impl InputTimer {
     }
 }
 
+/// The direction to walk in while `held` (in press order) are down.
+fn held_direction(held: &[Direction], priority: DirectionPriority) -> Option<Direction> {
+    match priority {
+        DirectionPriority::Fixed => MOVE_DIRECTIONS
+            .iter()
+            .map(|(_, direction)| *direction)
+            .find(|direction| held.contains(direction)),
+        DirectionPriority::LastPressed => held.last().copied(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -239,6 +283,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fixed_priority_ignores_press_order() {
+        let held = [Direction::Right, Direction::Up];
+        assert_eq!(
+            held_direction(&held, DirectionPriority::Fixed),
+            Some(Direction::Up)
+        );
+    }
+
+    #[test]
+    fn last_pressed_direction_wins() {
+        let held = [Direction::Up, Direction::Right];
+        assert_eq!(
+            held_direction(&held, DirectionPriority::LastPressed),
+            Some(Direction::Right)
+        );
+        assert_eq!(held_direction(&[], DirectionPriority::LastPressed), None);
+    }
+
     #[test]
     fn short_press_resolves_to_left_click() {
         let mut state = AndroidTouchInputState::default();
@@ -320,6 +383,7 @@ pub fn input_handling_system(
     mut inventory_events: MessageWriter<crate::events::InventoryEvent>,
     mut ability_events: MessageWriter<crate::events::AbilityEvent>,
     mut spell_casting: ResMut<SpellCastingState>,
+    settings: Res<Settings>,
 ) {
     let bindings = unified_bindings;
 
@@ -340,7 +404,7 @@ pub fn input_handling_system(
         Some(&gamepad_config),
     ) {
         tracing::info!("Basic attack triggered");
-        spell_casting.active_cast = None;
+        spell_casting.cancel();
         outbox.send(&Spacebar);
     }
 
@@ -381,6 +445,22 @@ pub fn input_handling_system(
         ) {
             game_state.set_show_spells(!game_state.get_show_spells());
         }
+        if bindings.is_just_pressed(
+            GameAction::CombatLog,
+            &keyboard_input,
+            Some(&gamepad_query),
+            Some(&gamepad_config),
+        ) {
+            game_state.set_show_combat_log(!game_state.get_show_combat_log());
+        }
+        if bindings.is_just_pressed(
+            GameAction::DialogHistory,
+            &keyboard_input,
+            Some(&gamepad_query),
+            Some(&gamepad_config),
+        ) {
+            game_state.set_show_dialog_history(!game_state.get_show_dialog_history());
+        }
         if bindings.is_just_pressed(
             GameAction::Settings,
             &keyboard_input,
@@ -390,6 +470,14 @@ pub fn input_handling_system(
             let settings_state = slint::ComponentHandle::global::<crate::SettingsState>(&strong);
             settings_state.set_show_game_menu(!settings_state.get_show_game_menu());
         }
+        if bindings.is_just_pressed(
+            GameAction::ToggleUi,
+            &keyboard_input,
+            Some(&gamepad_query),
+            Some(&gamepad_config),
+        ) {
+            game_state.set_ui_hidden(!game_state.get_ui_hidden());
+        }
     }
 
     // Panel switching
@@ -499,80 +587,106 @@ pub fn input_handling_system(
         }
     }
 
-    let movement_actions = [
-        GameAction::MoveUp,
-        GameAction::MoveDown,
-        GameAction::MoveLeft,
-        GameAction::MoveRight,
-    ];
-
     if let Ok((_, mut current_direction, active_tween)) = player_query.single_mut() {
         input_timer.walk_cd.tick(time.delta());
         if let Some(grace) = input_timer.turn_grace.as_mut() {
             grace.tick(time.delta());
         }
+        if let Some((_, buffer)) = input_timer.buffered.as_mut() {
+            buffer.tick(time.delta());
+        }
 
-        if active_tween.is_some() {
-            return;
+        let mut any_just_pressed = false;
+        for (action, direction) in MOVE_DIRECTIONS {
+            if bindings.is_just_pressed(
+                action,
+                &keyboard_input,
+                Some(&gamepad_query),
+                Some(&gamepad_config),
+            ) {
+                any_just_pressed = true;
+                input_timer.held.retain(|held| *held != direction);
+                input_timer.held.push(direction);
+                if active_tween.is_some() {
+                    input_timer.buffered = Some((
+                        direction,
+                        Timer::from_seconds(MOVE_BUFFER_SECS, TimerMode::Once),
+                    ));
+                }
+            } else if bindings.is_pressed(
+                action,
+                &keyboard_input,
+                Some(&gamepad_query),
+                Some(&gamepad_config),
+            ) {
+                // Held since before this handler saw it, e.g. across a map load
+                if !input_timer.held.contains(&direction) {
+                    input_timer.held.push(direction);
+                }
+            } else {
+                input_timer.held.retain(|held| *held != direction);
+            }
         }
 
-        let any_pressed = bindings.any_pressed(
-            &movement_actions,
-            &keyboard_input,
-            Some(&gamepad_query),
-            Some(&gamepad_config),
-        );
-        let any_just_pressed = bindings.any_just_pressed(
-            &movement_actions,
-            &keyboard_input,
-            Some(&gamepad_query),
-            Some(&gamepad_config),
-        );
-        if !any_pressed {
+        if active_tween.is_some() {
             return;
         }
 
-        let pressed_direction = if bindings.is_pressed(
-            GameAction::MoveUp,
-            &keyboard_input,
-            Some(&gamepad_query),
-            Some(&gamepad_config),
-        ) {
-            Some(Direction::Up)
-        } else if bindings.is_pressed(
-            GameAction::MoveDown,
-            &keyboard_input,
-            Some(&gamepad_query),
-            Some(&gamepad_config),
-        ) {
-            Some(Direction::Down)
-        } else if bindings.is_pressed(
-            GameAction::MoveLeft,
-            &keyboard_input,
-            Some(&gamepad_query),
-            Some(&gamepad_config),
-        ) {
-            Some(Direction::Left)
-        } else if bindings.is_pressed(
-            GameAction::MoveRight,
-            &keyboard_input,
-            Some(&gamepad_query),
-            Some(&gamepad_config),
-        ) {
-            Some(Direction::Right)
+        // A press made near the end of a step walks once it finishes, even if the key
+        // was already released, so quick alternating presses don't drop steps.
+        if input_timer
+            .buffered
+            .as_ref()
+            .is_some_and(|(_, buffer)| buffer.is_finished())
+        {
+            input_timer.buffered = None;
+        }
+        let buffered = if input_timer.walk_cd.is_finished() {
+            input_timer.buffered.take()
         } else {
             None
         };
+        if let Some((direction, _)) = buffered {
+            if *current_direction != direction {
+                player_actions.write(PlayerAction::Turn {
+                    direction,
+                    source: InputSource::Manual,
+                });
+                *current_direction = direction;
+                // Same tap-to-turn rule as a press made standing still: the
+                // walk only follows if the key is still held past the grace
+                if settings.input.turn_tap_ms > 0 {
+                    input_timer.turn_grace = Some(Timer::new(
+                        Duration::from_millis(settings.input.turn_tap_ms as u64),
+                        TimerMode::Once,
+                    ));
+                    return;
+                }
+            }
+            input_timer.turn_grace = None;
+            player_actions.write(PlayerAction::Walk {
+                direction,
+                source: InputSource::Manual,
+            });
+            input_timer.walk_cd = Timer::from_seconds(WALK_COOLDOWN_SECS, TimerMode::Once);
+            input_timer.primed = false;
+            return;
+        }
+
+        let pressed_direction =
+            held_direction(&input_timer.held, settings.input.direction_priority);
 
         if let Some(new_direction) = pressed_direction {
-            const WALK_COOLDOWN_SECS: f32 = 0.5;
             if *current_direction != new_direction {
                 player_actions.write(PlayerAction::Turn {
                     direction: new_direction,
                     source: InputSource::Manual,
                 });
                 *current_direction = new_direction;
-                input_timer.turn_grace = Some(Timer::from_seconds(0.12, TimerMode::Once));
+                input_timer.turn_grace = Some(Timer::new(
+                    Duration::from_millis(settings.input.turn_tap_ms as u64),
+                    TimerMode::Once,
+                ));
             }
 
             if *current_direction == new_direction {
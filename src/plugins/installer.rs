@@ -2,10 +2,11 @@ use std::{path::PathBuf, sync::Arc, thread};
 
 use bevy::prelude::*;
 use crossbeam_channel::{Receiver, Sender, unbounded};
-use installer::InstallProgress;
+use installer::{InstallOptions, InstallProgress, InstallSource, InstallerProfile};
 use tracing::debug;
 
 use crate::app_state::AppState;
+use crate::settings_types::{InstallerSettings, Settings};
 
 #[derive(Message, Debug, Clone)]
 pub struct InstallerProgressEvent {
@@ -54,11 +55,30 @@ impl Plugin for InstallerPlugin {
 #[derive(Resource, Default)]
 struct MaybeStartedInstaller(bool);
 
+fn install_options(settings: &InstallerSettings) -> InstallOptions {
+    let mut options = InstallOptions::default();
+    match InstallerProfile::find(&settings.version) {
+        Some(profile) => options.profile = profile,
+        None => tracing::warn!(
+            "Unknown installer version {}, using {}",
+            settings.version,
+            options.profile.version
+        ),
+    }
+    options.sources = settings
+        .sources
+        .iter()
+        .map(|source| InstallSource::parse(source))
+        .collect();
+    options
+}
+
 fn start_installer_once(
     mut commands: Commands,
     mut maybe_started: ResMut<MaybeStartedInstaller>,
     storage_config: Res<crate::resources::StorageConfig>,
     config: Option<Res<InstallerConfig>>,
+    settings: Res<Settings>,
 ) {
     if maybe_started.0 {
         return;
@@ -76,11 +96,12 @@ fn start_installer_once(
     ) = unbounded();
 
     let proxy = Arc::new(ProgressProxy { tx });
+    let options = install_options(&settings.installer);
 
     // Spawn a background thread to run the blocking installer
     thread::spawn(move || {
         // Use the external workspace crate named `installer`, avoiding module name clash.
-        let result = ::installer::install(&arx_path, Some(proxy.clone()));
+        let result = ::installer::install_with(&arx_path, &options, Some(proxy.clone()));
         match result {
             Ok(()) => {
                 proxy.report(1.0, "Install complete".to_string());
@@ -0,0 +1,154 @@
+//! Confirmation before using or dropping flagged items.
+//!
+//! Right-clicking an inventory item offers to flag it (and to add it to the
+//! auto-pickup list, see `plugins::auto_pickup`). Flagged items are kept
+//! by name in the character profile and ask before an ItemUse or a drop goes
+//! out, so a stray double-click doesn't spend or throw away something rare.
+
+use bevy::prelude::*;
+use game_ui::{CoreToUi, UiToCore, WorldContextMenuEntryUi};
+use packets::client;
+
+use crate::CurrentSession;
+use crate::app_state::AppState;
+use crate::events::{WorldContextAction, WorldContextMenuEntry};
+use crate::network::PacketOutbox;
+use crate::settings_types::Settings;
+use crate::webui::plugin::{
+    ActiveWorldContextMenu, InventoryState, PendingSplitStack, SplitStackTarget, UiInbound,
+    UiOutbound, drop_item,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum GuardedAction {
+    Use,
+    Drop(SplitStackTarget),
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemConfirmRequest {
+    pub slot: u8,
+    pub item_name: String,
+    pub sprite: u16,
+    pub action: GuardedAction,
+}
+
+/// Use or drop waiting on the confirmation dialog.
+#[derive(Resource, Default, Debug)]
+pub struct PendingItemConfirm(pub Option<ItemConfirmRequest>);
+
+/// Whether the current character flagged `item_name`.
+pub fn needs_confirm(
+    settings: &Settings,
+    session: Option<&CurrentSession>,
+    item_name: &str,
+) -> bool {
+    session.is_some_and(|session| {
+        settings.item_needs_confirm(session.server_id, &session.username, item_name)
+    })
+}
+
+pub struct ItemConfirmPlugin;
+
+impl Plugin for ItemConfirmPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingItemConfirm>()
+            .add_systems(OnExit(AppState::InGame), clear_item_confirm)
+            .add_systems(
+                Update,
+                handle_item_confirm_input.run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn clear_item_confirm(mut pending: ResMut<PendingItemConfirm>) {
+    pending.0 = None;
+}
+
+fn handle_item_confirm_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut outbound: MessageWriter<UiOutbound>,
+    inv_state: Res<InventoryState>,
+    settings: Res<Settings>,
+    session: Option<Res<CurrentSession>>,
+    outbox: Res<PacketOutbox>,
+    mut pending: ResMut<PendingItemConfirm>,
+    mut split_stack: ResMut<PendingSplitStack>,
+    mut world_context_menu: ResMut<ActiveWorldContextMenu>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::ItemContextMenu { slot, x, y } => {
+                let Some(item) = inv_state.0.iter().find(|item| item.slot == *slot) else {
+                    continue;
+                };
+                let confirm_text = if needs_confirm(&settings, session.as_deref(), &item.name) {
+                    "Stop asking before use or drop"
+                } else {
+                    "Ask before use or drop"
+                };
+                let pickup_text = if settings.gameplay.auto_picks_up(&item.name) {
+                    "Stop picking up automatically"
+                } else {
+                    "Always pick up"
+                };
+                let entries = vec![
+                    WorldContextMenuEntry {
+                        id: 0,
+                        text: confirm_text.to_owned(),
+                        action: WorldContextAction::ToggleItemConfirm {
+                            name: item.name.clone(),
+                        },
+                    },
+                    WorldContextMenuEntry {
+                        id: 1,
+                        text: pickup_text.to_owned(),
+                        action: WorldContextAction::ToggleAutoPickup {
+                            name: item.name.clone(),
+                            sprite: item.sprite,
+                        },
+                    },
+                ];
+
+                world_context_menu.title = item.name.clone();
+                world_context_menu.entries = entries.clone();
+                outbound.write(UiOutbound(CoreToUi::ShowWorldContextMenu {
+                    title: item.name.clone(),
+                    x: *x,
+                    y: *y,
+                    anchor_width: 1.0,
+                    anchor_height: 1.0,
+                    entries: entries
+                        .into_iter()
+                        .map(|entry| WorldContextMenuEntryUi {
+                            id: entry.id,
+                            text: entry.text,
+                        })
+                        .collect(),
+                }));
+            }
+            UiToCore::ItemConfirmAccept => {
+                let Some(request) = pending.0.take() else {
+                    continue;
+                };
+                match request.action {
+                    GuardedAction::Use => outbox.send(&client::ItemUse {
+                        source_slot: request.slot,
+                    }),
+                    GuardedAction::Drop(target) => {
+                        // The item may have moved or gone while the dialog was up
+                        if let Some(item) = inv_state.0.iter().find(|item| {
+                            item.slot == request.slot && item.name == request.item_name
+                        }) {
+                            drop_item(item, target, &mut split_stack, &outbox);
+                        }
+                    }
+                }
+            }
+            UiToCore::ItemConfirmCancel => {
+                pending.0 = None;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Map ambience from the map's flags and the server's light level: the
+//! screen tint, falling weather and HUD badges for the map's restrictions.
+//!
+//! Weather only falls on maps flagged for snow or rain, so indoor maps stay
+//! clear. Dark maps such as caves are tinted on top of the light level.
+//! Potato mode drops both the tint and the weather.
+
+use bevy::prelude::*;
+use packets::server::{LightLevelKind, MapFlags, Weather};
+
+use crate::app_state::AppState;
+use crate::events::MapEvent;
+use crate::settings_types::Settings;
+use crate::slint_support::state_bridge::SlintWindow;
+use crate::{Camera, RendererState};
+
+/// Added to the light level tint on dark maps.
+const DARK_MAP_TINT: [f32; 3] = [-0.12, -0.12, -0.09];
+
+#[derive(Resource, Default)]
+pub struct MapAmbience {
+    pub flags: MapFlags,
+    light_level: Option<LightLevelKind>,
+}
+
+impl MapAmbience {
+    /// Screen tint to draw with. The light level is kept while potato mode is
+    /// on, so turning it off brings the current tint straight back.
+    pub fn tint(&self, potato_mode: bool) -> [f32; 3] {
+        if potato_mode {
+            return [0.0, 0.0, 0.0];
+        }
+        ambient_tint(self.light_level, self.flags.is_dark())
+    }
+}
+
+/// Color added to every pixel for the light level, darker on dark maps.
+pub fn ambient_tint(light_level: Option<LightLevelKind>, dark: bool) -> [f32; 3] {
+    let light = match light_level {
+        Some(LightLevelKind::DarkestA) => [-0.02745098, -0.011764706, -0.02745098],
+        Some(
+            LightLevelKind::DarkerB
+            | LightLevelKind::DarkB
+            | LightLevelKind::LighterA
+            | LightLevelKind::LightestA,
+        ) => [-0.011764706, -0.011764706, -0.011764706],
+        _ => [0.0, 0.0, 0.0],
+    };
+    if !dark {
+        return light;
+    }
+    [
+        light[0] + DARK_MAP_TINT[0],
+        light[1] + DARK_MAP_TINT[1],
+        light[2] + DARK_MAP_TINT[2],
+    ]
+}
+
+pub struct MapAmbiencePlugin;
+
+impl Plugin for MapAmbiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapAmbience>()
+            .add_systems(
+                Update,
+                (track_map_ambience, apply_map_ambience)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_map_ambience);
+    }
+}
+
+fn reset_map_ambience(mut ambience: ResMut<MapAmbience>) {
+    *ambience = MapAmbience::default();
+}
+
+fn track_map_ambience(mut map_events: MessageReader<MapEvent>, mut ambience: ResMut<MapAmbience>) {
+    for event in map_events.read() {
+        match event {
+            MapEvent::SetInfo(info, _) => ambience.flags = info.map_flags(),
+            MapEvent::SetLightLevel(kind) => {
+                tracing::info!("Setting light level to {:?}", kind);
+                ambience.light_level = Some(*kind);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn apply_map_ambience(
+    ambience: Res<MapAmbience>,
+    settings: Res<Settings>,
+    camera: Option<ResMut<Camera>>,
+    renderer: Option<Res<RendererState>>,
+    window: Option<Res<SlintWindow>>,
+) {
+    if !ambience.is_changed() && !settings.is_changed() {
+        return;
+    }
+    let potato_mode = settings.graphics.potato_mode;
+    let flags = ambience.flags;
+
+    let [r, g, b] = ambience.tint(potato_mode);
+    if let (Some(mut camera), Some(renderer)) = (camera, renderer) {
+        camera.camera.set_tint(&renderer.queue, r, g, b);
+    }
+
+    let Some(strong) = window.and_then(|w| w.0.upgrade()) else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    let weather = match flags.weather() {
+        Weather::Snow => "snow",
+        Weather::Rain => "rain",
+        Weather::Clear => "",
+    };
+    game_state.set_map_weather(weather.into());
+    game_state.set_show_weather(!potato_mode && !weather.is_empty());
+    game_state.set_map_dark(flags.is_dark());
+    game_state.set_map_no_tab_map(flags.no_tab_map());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dark_maps_darken_the_light_level() {
+        assert_eq!(ambient_tint(None, false), [0.0, 0.0, 0.0]);
+        assert_eq!(ambient_tint(None, true), DARK_MAP_TINT);
+
+        let light = ambient_tint(Some(LightLevelKind::DarkB), false);
+        let dark = ambient_tint(Some(LightLevelKind::DarkB), true);
+        assert!(light[0] < 0.0);
+        assert!((dark[0] - (light[0] + DARK_MAP_TINT[0])).abs() < 1e-6);
+    }
+
+    #[test]
+    fn leaving_potato_mode_restores_the_light_level() {
+        let ambience = MapAmbience {
+            flags: MapFlags::default(),
+            light_level: Some(LightLevelKind::DarkestA),
+        };
+        assert_eq!(ambience.tint(true), [0.0, 0.0, 0.0]);
+        assert_eq!(
+            ambience.tint(false),
+            ambient_tint(Some(LightLevelKind::DarkestA), false)
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! Named pins on map tiles, kept per server and map.
+//!
+//! Pins are stored beside the cached map files as `lodNNN.pins.json` in the
+//! server's maps directory and drawn over their tiles in the world. They are
+//! placed and removed from the world context menu. To share hunting spots, the
+//! settings export every pin on the server to one file in the `map-pins`
+//! folder, and import merges every file found there into the current server,
+//! leaving tiles that already have a pin alone.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use game_ui::{CoreToUi, UiToCore};
+use serde::{Deserialize, Serialize};
+
+use crate::CurrentSession;
+use crate::app_state::AppState;
+use crate::ecs::components::GameMap;
+use crate::resources::StorageConfig;
+use crate::webui::plugin::{UiInbound, UiOutbound};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapPin {
+    pub x: u16,
+    pub y: u16,
+    pub name: String,
+}
+
+/// Contents of an exported pin file: pins by map id.
+type SharedPins = BTreeMap<u16, Vec<MapPin>>;
+
+/// Pins on the map the player is on.
+#[derive(Resource, Default, Debug)]
+pub struct MapPins {
+    path: Option<PathBuf>,
+    map_id: Option<u16>,
+    pins: Vec<MapPin>,
+}
+
+impl MapPins {
+    pub fn load(path: PathBuf, map_id: u16) -> Self {
+        Self {
+            pins: read_pins(&path),
+            path: Some(path),
+            map_id: Some(map_id),
+        }
+    }
+
+    pub fn map_id(&self) -> Option<u16> {
+        self.map_id
+    }
+
+    pub fn pins(&self) -> &[MapPin] {
+        &self.pins
+    }
+
+    pub fn at(&self, x: u16, y: u16) -> Option<&MapPin> {
+        self.pins.iter().find(|pin| pin.x == x && pin.y == y)
+    }
+
+    /// Pins a tile, or removes its pin when `name` is blank, and saves the file.
+    pub fn set(&mut self, x: u16, y: u16, name: &str) {
+        let name = name.trim();
+        self.pins.retain(|pin| pin.x != x || pin.y != y);
+        if !name.is_empty() {
+            self.pins.push(MapPin {
+                x,
+                y,
+                name: name.to_owned(),
+            });
+        }
+        if let Some(path) = &self.path
+            && let Err(e) = write_pins(path, &self.pins)
+        {
+            error!("Failed to save {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn read_pins(path: &Path) -> Vec<MapPin> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Ignoring malformed {}: {}", path.display(), e);
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn write_pins(path: &Path, pins: &[MapPin]) -> std::io::Result<()> {
+    if pins.is_empty() {
+        return match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    std::fs::write(path, serde_json::to_string_pretty(pins)?)
+}
+
+/// Map id of a `lodNNN.pins.json` file name.
+fn pins_file_map_id(file_name: &str) -> Option<u16> {
+    file_name
+        .strip_prefix("lod")?
+        .strip_suffix(".pins.json")?
+        .parse()
+        .ok()
+}
+
+/// Adds the pins on tiles `pins` has none on yet, returning how many were added.
+fn merge_pins(pins: &mut Vec<MapPin>, incoming: Vec<MapPin>) -> usize {
+    let before = pins.len();
+    for pin in incoming {
+        if !pin.name.trim().is_empty() && !pins.iter().any(|p| p.x == pin.x && p.y == pin.y) {
+            pins.push(pin);
+        }
+    }
+    pins.len() - before
+}
+
+/// Writes every pin in `maps_dir` to a timestamped file in `export_dir`.
+fn export_pins(
+    maps_dir: &Path,
+    export_dir: &Path,
+    server_id: u32,
+) -> std::io::Result<(PathBuf, usize)> {
+    let mut shared = SharedPins::new();
+    for entry in std::fs::read_dir(maps_dir)? {
+        let path = entry?.path();
+        let Some(map_id) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(pins_file_map_id)
+        else {
+            continue;
+        };
+        let pins = read_pins(&path);
+        if !pins.is_empty() {
+            shared.insert(map_id, pins);
+        }
+    }
+
+    let count = shared.values().map(Vec::len).sum();
+    let path = export_dir.join(format!(
+        "pins-{}-{}.json",
+        server_id,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, serde_json::to_string_pretty(&shared)?)?;
+    Ok((path, count))
+}
+
+/// Merges every pin file in `import_dir` into the pin files in `maps_dir`.
+fn import_pins(maps_dir: &Path, import_dir: &Path) -> std::io::Result<usize> {
+    let mut added = 0;
+    for entry in std::fs::read_dir(import_dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let shared: SharedPins = match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()))
+        {
+            Ok(shared) => shared,
+            Err(e) => {
+                warn!("Skipping pin file {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        for (map_id, incoming) in shared {
+            let map_path = maps_dir.join(format!("lod{:03}.pins.json", map_id));
+            let mut pins = read_pins(&map_path);
+            let merged = merge_pins(&mut pins, incoming);
+            if merged > 0 {
+                write_pins(&map_path, &pins)?;
+                added += merged;
+            }
+        }
+    }
+    Ok(added)
+}
+
+/// Tile a pin is being named for in the UI, if any.
+#[derive(Resource, Default, Debug)]
+pub struct PendingMapPin(pub Option<(u16, u16)>);
+
+pub struct MapPinsPlugin;
+
+impl Plugin for MapPinsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapPins>()
+            .init_resource::<PendingMapPin>()
+            .add_systems(OnExit(AppState::InGame), clear_map_pins)
+            .add_systems(
+                Update,
+                (load_map_pins, handle_map_pin_input)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn clear_map_pins(mut commands: Commands) {
+    commands.insert_resource(MapPins::default());
+    commands.insert_resource(PendingMapPin::default());
+}
+
+fn load_map_pins(
+    storage: Res<StorageConfig>,
+    session: Option<Res<CurrentSession>>,
+    map_query: Query<&GameMap, Changed<GameMap>>,
+    mut pins: ResMut<MapPins>,
+    mut pending: ResMut<PendingMapPin>,
+) {
+    let (Some(session), Some(map)) = (session, map_query.iter().next()) else {
+        return;
+    };
+    if pins.map_id() == Some(map.map_id) {
+        return;
+    }
+    *pins = MapPins::load(
+        storage.server_map_pins_path(session.server_id, map.map_id),
+        map.map_id,
+    );
+    pending.0 = None;
+}
+
+fn handle_map_pin_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut outbound: MessageWriter<UiOutbound>,
+    storage: Res<StorageConfig>,
+    session: Option<Res<CurrentSession>>,
+    mut pins: ResMut<MapPins>,
+    mut pending: ResMut<PendingMapPin>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::MapPinSubmit { x, y, name } => {
+                if !name.trim().is_empty() {
+                    pins.set(*x, *y, name);
+                }
+                pending.0 = None;
+            }
+            UiToCore::MapPinCancel => {
+                pending.0 = None;
+            }
+            UiToCore::MapPinsExport => {
+                let Some(session) = session.as_deref() else {
+                    continue;
+                };
+                let text = match export_pins(
+                    &storage.server_maps_dir(session.server_id),
+                    &storage.map_pins_dir(),
+                    session.server_id,
+                ) {
+                    Ok((path, count)) => format!("Saved {} pins to {}", count, path.display()),
+                    Err(e) => {
+                        error!("Failed to export map pins: {}", e);
+                        format!("Couldn't export map pins: {}", e)
+                    }
+                };
+                outbound.write(UiOutbound(CoreToUi::Toast { text }));
+            }
+            UiToCore::MapPinsImport => {
+                let Some(session) = session.as_deref() else {
+                    continue;
+                };
+                let text = match import_pins(
+                    &storage.server_maps_dir(session.server_id),
+                    &storage.map_pins_dir(),
+                ) {
+                    Ok(added) => {
+                        // Pick up anything added to the map we're on
+                        if let Some(map_id) = pins.map_id() {
+                            *pins = MapPins::load(
+                                storage.server_map_pins_path(session.server_id, map_id),
+                                map_id,
+                            );
+                        }
+                        format!("Imported {} new pins", added)
+                    }
+                    Err(e) => {
+                        error!("Failed to import map pins: {}", e);
+                        format!("Couldn't import map pins: {}", e)
+                    }
+                };
+                outbound.write(UiOutbound(CoreToUi::Toast { text }));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(x: u16, y: u16, name: &str) -> MapPin {
+        MapPin {
+            x,
+            y,
+            name: name.into(),
+        }
+    }
+
+    #[test]
+    fn blank_name_removes_pin() {
+        let mut pins = MapPins::default();
+        pins.set(3, 4, " Wolves ");
+        pins.set(3, 4, "Kobolds");
+        assert_eq!(pins.pins(), [pin(3, 4, "Kobolds")]);
+
+        pins.set(3, 4, "");
+        assert_eq!(pins.at(3, 4), None);
+    }
+
+    #[test]
+    fn merging_keeps_existing_pins() {
+        let mut pins = vec![pin(1, 1, "Mine")];
+        let added = merge_pins(
+            &mut pins,
+            vec![pin(1, 1, "Theirs"), pin(2, 2, "Spot"), pin(3, 3, " ")],
+        );
+        assert_eq!(added, 1);
+        assert_eq!(pins, [pin(1, 1, "Mine"), pin(2, 2, "Spot")]);
+    }
+
+    #[test]
+    fn pin_files_are_named_by_map() {
+        assert_eq!(pins_file_map_id("lod500.pins.json"), Some(500));
+        assert_eq!(pins_file_map_id("lod007.pins.json"), Some(7));
+        assert_eq!(pins_file_map_id("lod500.map"), None);
+    }
+}
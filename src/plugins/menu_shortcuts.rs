@@ -0,0 +1,365 @@
+//! Favorite NPC menus, reached with one key.
+//!
+//! The options picked since an NPC's first menu opened are remembered, and
+//! the shortcut button on the dialog saves them for that NPC. Pressing the
+//! favorite menu key next to the NPC then clicks it and picks the same
+//! options again as each menu arrives, turning plain text pages on the way,
+//! so the bank deposit screen is one key away. Options are looked up by
+//! their text first and by their recorded id after, and the replay stops
+//! when neither is on the menu.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_ui::{CoreToUi, UiToCore};
+use packets::client::Click;
+use packets::server::DisplayDialog;
+
+use crate::app_state::AppState;
+use crate::ecs::components::{EntityId, LocalPlayer, NPC, Position};
+use crate::events::SessionEvent;
+use crate::input::{GameAction, GamepadConfig, UnifiedInputBindings};
+use crate::network::PacketOutbox;
+use crate::settings_types::{MenuShortcut, MenuShortcutStep, Settings};
+use crate::webui::plugin::{
+    ActiveMenuContext, ActiveWindowType, UiInbound, UiOutbound, send_menu_selection,
+};
+
+/// Furthest an NPC can be, in tiles, for the key to reach it.
+const SHORTCUT_REACH: f32 = 2.0;
+/// How long to wait for each menu before giving up.
+const REPLAY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Most text pages turned in one replay, in case a dialog loops.
+const MAX_PAGES: usize = 20;
+
+/// The option on a menu matching a recorded step, as its id and text.
+pub fn pick_option<'a>(
+    step: &MenuShortcutStep,
+    options: &'a [(i32, String)],
+) -> Option<(i32, &'a str)> {
+    options
+        .iter()
+        .find(|(_, text)| text.eq_ignore_ascii_case(&step.text))
+        .or_else(|| options.iter().find(|(id, _)| *id == step.id))
+        .map(|(id, text)| (*id, text.as_str()))
+}
+
+/// Options picked with the NPC whose menus are on screen.
+#[derive(Resource, Default, Debug)]
+pub struct MenuShortcutRecorder {
+    entity_id: Option<u32>,
+    npc: String,
+    steps: Vec<MenuShortcutStep>,
+    open: bool,
+}
+
+impl MenuShortcutRecorder {
+    /// A menu from `entity_id` came up; one from another NPC, or after the
+    /// dialog was closed, starts over.
+    fn screen_opened(&mut self, entity_id: u32, npc: &str) {
+        if !self.open || self.entity_id != Some(entity_id) {
+            self.entity_id = Some(entity_id);
+            self.npc = npc.to_owned();
+            self.steps.clear();
+        }
+        self.open = true;
+    }
+
+    fn closed(&mut self) {
+        self.open = false;
+    }
+
+    fn picked(&mut self, text: &str, id: i32) {
+        if self.open {
+            self.steps.push(MenuShortcutStep {
+                text: text.to_owned(),
+                id,
+            });
+        }
+    }
+
+    /// The options picked so far, once there are any.
+    fn shortcut(&self) -> Option<MenuShortcut> {
+        (self.open && !self.steps.is_empty() && !self.npc.is_empty()).then(|| MenuShortcut {
+            npc: self.npc.clone(),
+            steps: self.steps.clone(),
+        })
+    }
+}
+
+/// For the shortcut button on the dialog.
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct MenuShortcutStatus {
+    pub can_save: bool,
+    pub saved: bool,
+}
+
+struct Replay {
+    entity_id: u32,
+    steps: Vec<MenuShortcutStep>,
+    next: usize,
+    pages: usize,
+    deadline: Duration,
+}
+
+#[derive(Resource, Default)]
+struct ShortcutReplay(Option<Replay>);
+
+pub struct MenuShortcutsPlugin;
+
+impl Plugin for MenuShortcutsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuShortcutRecorder>()
+            .init_resource::<MenuShortcutStatus>()
+            .init_resource::<ShortcutReplay>()
+            .add_systems(OnExit(AppState::InGame), reset_menu_shortcuts)
+            .add_systems(
+                Update,
+                (
+                    record_menu_picks,
+                    handle_menu_shortcut_input,
+                    start_shortcut,
+                    replay_shortcut,
+                    update_shortcut_status,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn reset_menu_shortcuts(
+    mut recorder: ResMut<MenuShortcutRecorder>,
+    mut status: ResMut<MenuShortcutStatus>,
+    mut replay: ResMut<ShortcutReplay>,
+) {
+    *recorder = MenuShortcutRecorder::default();
+    status.set_if_neq(MenuShortcutStatus::default());
+    replay.0 = None;
+}
+
+fn record_menu_picks(
+    mut session_events: MessageReader<SessionEvent>,
+    mut inbound: MessageReader<UiInbound>,
+    menu_ctx: Res<ActiveMenuContext>,
+    mut recorder: ResMut<MenuShortcutRecorder>,
+) {
+    for event in session_events.read() {
+        match event {
+            SessionEvent::DisplayDialog(DisplayDialog::Show { header, .. }) => {
+                recorder.screen_opened(header.source_id, &header.name);
+            }
+            SessionEvent::DisplayDialog(DisplayDialog::Close) => {
+                recorder.closed();
+            }
+            SessionEvent::DisplayMenu(pkt) => {
+                recorder.screen_opened(pkt.header.source_id, &pkt.header.name);
+            }
+            _ => {}
+        }
+    }
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            // Only real options: page turns and shop picks aren't part of the way there
+            UiToCore::MenuSelect { id, .. } => {
+                if let Some(text) = menu_ctx.option_text(*id) {
+                    recorder.picked(text, *id);
+                }
+            }
+            UiToCore::MenuClose => recorder.closed(),
+            _ => {}
+        }
+    }
+}
+
+fn handle_menu_shortcut_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut outbound: MessageWriter<UiOutbound>,
+    recorder: Res<MenuShortcutRecorder>,
+    mut settings: ResMut<Settings>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        if !matches!(msg, UiToCore::MenuShortcutToggle) {
+            continue;
+        }
+        let Some(shortcut) = recorder.shortcut() else {
+            continue;
+        };
+        let shortcuts = &mut settings.gameplay.menu_shortcuts;
+        let text = if shortcuts.contains(&shortcut) {
+            shortcuts.retain(|saved| saved != &shortcut);
+            format!("Removed the menu shortcut for {}", shortcut.npc)
+        } else {
+            shortcuts.retain(|saved| !saved.npc.eq_ignore_ascii_case(&shortcut.npc));
+            let text = format!(
+                "Menu shortcut saved: press the Favorite NPC Menu key next to {}",
+                shortcut.npc
+            );
+            shortcuts.push(shortcut);
+            text
+        };
+        outbound.write(UiOutbound(CoreToUi::Toast { text }));
+    }
+}
+
+fn start_shortcut(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    bindings: Res<UnifiedInputBindings>,
+    gamepad_query: Query<&Gamepad>,
+    gamepad_config: Res<GamepadConfig>,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    outbox: Res<PacketOutbox>,
+    player: Query<&Position, With<LocalPlayer>>,
+    npcs: Query<(&EntityId, &NPC, &Position)>,
+    mut outbound: MessageWriter<UiOutbound>,
+    mut replay: ResMut<ShortcutReplay>,
+) {
+    if !bindings.is_just_pressed(
+        GameAction::MenuShortcut,
+        &keyboard_input,
+        Some(&gamepad_query),
+        Some(&gamepad_config),
+    ) {
+        return;
+    }
+    let Ok(player_pos) = player.single() else {
+        return;
+    };
+
+    let nearest = npcs
+        .iter()
+        .filter_map(|(id, npc, pos)| {
+            let shortcut = settings.gameplay.menu_shortcuts.iter().find(|shortcut| {
+                !shortcut.steps.is_empty() && shortcut.npc.eq_ignore_ascii_case(&npc.name)
+            })?;
+            let distance = (pos.x - player_pos.x).abs() + (pos.y - player_pos.y).abs();
+            (distance <= SHORTCUT_REACH).then_some((id.id, shortcut, distance))
+        })
+        .min_by(|a, b| a.2.total_cmp(&b.2));
+    let Some((entity_id, shortcut, _)) = nearest else {
+        outbound.write(UiOutbound(CoreToUi::Toast {
+            text: "No NPC with a menu shortcut next to you".into(),
+        }));
+        return;
+    };
+
+    outbox.send(&Click::TargetEntity(entity_id));
+    replay.0 = Some(Replay {
+        entity_id,
+        steps: shortcut.steps.clone(),
+        next: 0,
+        pages: 0,
+        deadline: time.elapsed() + REPLAY_TIMEOUT,
+    });
+}
+
+fn replay_shortcut(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    outbox: Res<PacketOutbox>,
+    menu_ctx: Res<ActiveMenuContext>,
+    mut recorder: ResMut<MenuShortcutRecorder>,
+    mut outbound: MessageWriter<UiOutbound>,
+    mut replay: ResMut<ShortcutReplay>,
+) {
+    let Some(state) = replay.0.as_mut() else {
+        return;
+    };
+    let now = time.elapsed();
+    if now > state.deadline {
+        replay.0 = None;
+        outbound.write(UiOutbound(CoreToUi::Toast {
+            text: "Menu shortcut stopped: the NPC didn't answer".into(),
+        }));
+        return;
+    }
+    if !menu_ctx.is_changed()
+        || menu_ctx.window_type == ActiveWindowType::None
+        || menu_ctx.entity_id != state.entity_id
+    {
+        return;
+    }
+    state.deadline = now + REPLAY_TIMEOUT;
+
+    let step = &state.steps[state.next];
+    if let Some((id, text)) = pick_option(step, &menu_ctx.options) {
+        send_menu_selection(&menu_ctx, id, text, &outbox);
+        recorder.picked(text, id);
+        state.next += 1;
+        if state.next == state.steps.len() {
+            replay.0 = None;
+        }
+    } else if menu_ctx.has_next && state.pages < MAX_PAGES {
+        state.pages += 1;
+        // Pages of auto-next NPCs are already being turned
+        let npc = &recorder.npc;
+        if !crate::plugins::dialog_advance::is_auto_next(npc, &settings.gameplay.auto_next_npcs)
+            && let Some(dialog_id) = menu_ctx.dialog_id
+        {
+            send_menu_selection(&menu_ctx, dialog_id as i32 + 1, "Next", &outbox);
+        }
+    } else {
+        let text = format!(
+            "Menu shortcut stopped: \"{}\" isn't on this menu",
+            step.text
+        );
+        replay.0 = None;
+        outbound.write(UiOutbound(CoreToUi::Toast { text }));
+    }
+}
+
+fn update_shortcut_status(
+    recorder: Res<MenuShortcutRecorder>,
+    settings: Res<Settings>,
+    mut status: ResMut<MenuShortcutStatus>,
+) {
+    if !recorder.is_changed() && !settings.is_changed() {
+        return;
+    }
+    let shortcut = recorder.shortcut();
+    status.set_if_neq(MenuShortcutStatus {
+        can_save: shortcut.is_some(),
+        saved: shortcut
+            .is_some_and(|shortcut| settings.gameplay.menu_shortcuts.contains(&shortcut)),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_are_recorded_per_npc_and_found_again_by_text_or_id() {
+        let mut recorder = MenuShortcutRecorder::default();
+        recorder.screen_opened(7, "Banker");
+        recorder.picked("Bank", 3);
+        recorder.screen_opened(7, "Banker");
+        recorder.picked("Deposit Item", 100_001);
+        let shortcut = recorder.shortcut().unwrap();
+        assert_eq!(shortcut.npc, "Banker");
+        assert_eq!(shortcut.steps.len(), 2);
+
+        // Another NPC's menu starts over, and so does reopening after a close
+        recorder.screen_opened(8, "Guard");
+        assert_eq!(recorder.shortcut(), None);
+        recorder.closed();
+        recorder.picked("Leave", 1);
+        assert_eq!(recorder.shortcut(), None);
+
+        let options = vec![
+            (100_000, "Withdraw Item".to_owned()),
+            (100_001, "deposit item".to_owned()),
+        ];
+        assert_eq!(
+            pick_option(&shortcut.steps[1], &options),
+            Some((100_001, "deposit item"))
+        );
+        let renamed = vec![(3, "Banking".to_owned())];
+        assert_eq!(
+            pick_option(&shortcut.steps[0], &renamed),
+            Some((3, "Banking"))
+        );
+        assert_eq!(pick_option(&shortcut.steps[0], &options), None);
+    }
+}
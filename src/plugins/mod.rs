@@ -1,4 +1,40 @@
 // pub mod cursor;
+pub mod ability_stats;
+pub mod anti_idle;
+pub mod archive_browser;
+pub mod attack_telegraphs;
+pub mod auto_pickup;
+pub mod bank;
+pub mod clock;
+pub mod combat_log;
+pub mod creature_names;
+pub mod death_recap;
+pub mod debug_console;
+pub mod debug_overlay;
+pub mod dialog_advance;
+pub mod dialog_confirm;
+pub mod dialog_history;
+pub mod diagnostics;
+pub mod effect_sounds;
+pub mod entity_inspector;
+pub mod frame_capture;
+pub mod idle;
 pub mod input;
 pub mod installer;
+pub mod item_confirm;
+pub mod map_ambience;
+pub mod map_pins;
+pub mod menu_shortcuts;
 pub mod mouse_interaction;
+pub mod notifications;
+pub mod pets;
+pub mod potato_mode;
+pub mod server_news;
+pub mod server_registry;
+pub mod server_status;
+pub mod session_summary;
+pub mod spell_timers;
+pub mod stream_output;
+pub mod texture_memory;
+pub mod threat_indicators;
+pub mod world_cursor;
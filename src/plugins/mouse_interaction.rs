@@ -4,21 +4,28 @@ use rendering::scene::utils::screen_to_iso_tile;
 use std::cmp::Ordering;
 
 use crate::app_state::AppState;
-use crate::ecs::components::{EntityId, Hitbox, ItemSprite, LocalPlayer, NPC, Player, Position};
+use crate::ecs::components::{
+    CreatureSprite, EntityId, Hitbox, ItemSprite, LocalPlayer, NPC, Player, Position,
+};
 use crate::ecs::interaction::HoveredEntity;
 use crate::ecs::spell_casting::SpellCastingState;
 use crate::events::{
-    ClickSource, EntityClickEvent, EntityHoverEvent, ResolvedPointerClickEvent, TileClickEvent,
+    ClickSource, EntityClickEvent, EntityHoverEvent, InteractionIntentAction,
+    InteractionIntentEvent, InteractionTargetKind, ResolvedPointerClickEvent, TileClickEvent,
     WallClickEvent, WorldContextAction, WorldContextMenuEntry,
 };
 use crate::network::PacketOutbox;
+use crate::plugins::map_pins::MapPins;
 use crate::resources::ZoomState;
 use crate::slint_plugin::{ShowSelfProfileEvent, SlintDoubleClickEvent};
-use crate::webui::plugin::{ActiveWorldContextMenu, CursorPosition, UiOutbound};
+use crate::webui::plugin::{ActiveWorldContextMenu, CursorPosition, GroupState, UiOutbound};
 use crate::{Camera, WindowSurface};
 use game_ui::{CoreToUi, WorldContextMenuEntryUi};
 use packets::client::{Click, Pickup, SelfProfileRequest};
 
+/// NPCs further than this many tiles (Manhattan) are walked up to before clicking.
+const NPC_CLICK_RANGE: i32 = 10;
+
 #[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct MouseInteractionSet;
 
@@ -29,6 +36,7 @@ impl Plugin for MouseInteractionPlugin {
         // Note: EntityHoverEvent and EntityClickEvent are registered in CoreEventsPlugin
         app.insert_resource(InteractionState::default())
             .init_resource::<HoveredEntity>()
+            .init_resource::<crate::ecs::interaction::HoveredTile>()
             .add_systems(
                 Update,
                 (
@@ -72,10 +80,23 @@ struct SceneEntityHit {
 #[derive(Clone)]
 enum SceneEntityHitKind {
     Player { name: String, is_self: bool },
-    Npc { name: String },
+    Npc {
+        name: String,
+        /// Sprite of a creature the server sent no name for.
+        unnamed_sprite: Option<u16>,
+    },
     Item,
 }
 
+#[derive(bevy::ecs::system::SystemParam)]
+struct DesktopContextMenu<'w> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    world_context_menu: ResMut<'w, ActiveWorldContextMenu>,
+    ui_outbound: MessageWriter<'w, UiOutbound>,
+    map_pins: Res<'w, MapPins>,
+    group: Res<'w, GroupState>,
+}
+
 fn mouse_interaction_system(
     cursor: Res<CursorPosition>,
     buttons: Res<ButtonInput<MouseButton>>,
@@ -85,6 +106,7 @@ fn mouse_interaction_system(
     collision_table: Option<Res<crate::ecs::collision::WallCollisionTable>>,
     mut interaction_state: ResMut<InteractionState>,
     mut hovered_entity: ResMut<HoveredEntity>,
+    mut hovered_tile: ResMut<crate::ecs::interaction::HoveredTile>,
     entity_query: Query<(
         Entity,
         &Position,
@@ -93,12 +115,14 @@ fn mouse_interaction_system(
         Option<&NPC>,
         Option<&ItemSprite>,
         Option<&LocalPlayer>,
+        Option<&CreatureSprite>,
     )>,
     mut hover_events: MessageWriter<EntityHoverEvent>,
     mut click_events: MessageWriter<EntityClickEvent>,
     mut tile_click_events: MessageWriter<TileClickEvent>,
     mut wall_click_events: MessageWriter<WallClickEvent>,
     map_collision: Option<Res<crate::ecs::collision::MapCollisionData>>,
+    mut context_menu: DesktopContextMenu,
 ) {
     let Some(hit_result) = hit_test_scene(
         &camera,
@@ -114,6 +138,10 @@ fn mouse_interaction_system(
 
     let current_entity = hit_result.top_entity;
     hovered_entity.0 = current_entity;
+    hovered_tile.set_if_neq(crate::ecs::interaction::HoveredTile(Some((
+        hit_result.ground_x,
+        hit_result.ground_y,
+    ))));
 
     if current_entity != interaction_state.last_entity {
         if let Some(entity) = current_entity {
@@ -135,6 +163,23 @@ fn mouse_interaction_system(
     }
 
     if buttons.just_pressed(MouseButton::Right) {
+        // Shift+right-click opens the same menu as an Android long-press
+        let shift = context_menu
+            .keyboard
+            .any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        if shift
+            && show_world_context_menu(
+                &hit_result,
+                &context_menu.map_pins,
+                &context_menu.group,
+                (cursor.x, cursor.y),
+                &mut context_menu.world_context_menu,
+                &mut context_menu.ui_outbound,
+            )
+        {
+            return;
+        }
+
         emit_scene_click(
             &hit_result,
             MouseButton::Right,
@@ -161,6 +206,7 @@ fn handle_resolved_pointer_clicks(
         Option<&NPC>,
         Option<&ItemSprite>,
         Option<&LocalPlayer>,
+        Option<&CreatureSprite>,
     )>,
     mut click_events: MessageWriter<EntityClickEvent>,
     mut tile_click_events: MessageWriter<TileClickEvent>,
@@ -169,6 +215,8 @@ fn handle_resolved_pointer_clicks(
     spell_casting: Res<SpellCastingState>,
     mut ui_outbound: MessageWriter<UiOutbound>,
     mut world_context_menu: ResMut<ActiveWorldContextMenu>,
+    map_pins: Res<MapPins>,
+    group: Res<GroupState>,
 ) {
     let is_waiting_for_target = spell_casting
         .active_cast
@@ -195,6 +243,8 @@ fn handle_resolved_pointer_clicks(
 
             if show_world_context_menu(
                 &hit_result,
+                &map_pins,
+                &group,
                 event.position,
                 &mut world_context_menu,
                 &mut ui_outbound,
@@ -293,6 +343,8 @@ fn handle_entity_clicks(
         Option<&LocalPlayer>,
         Option<&ItemSprite>,
     )>,
+    player_query: Query<&Position, With<LocalPlayer>>,
+    mut interaction_intents: MessageWriter<InteractionIntentEvent>,
     outbox: Res<PacketOutbox>,
 ) {
     let is_waiting_for_target = spell_casting
@@ -324,7 +376,26 @@ fn handle_entity_clicks(
                         profile_events.write(ShowSelfProfileEvent::OtherRequested);
                         outbox.send(&Click::TargetEntity(entity_id.id));
                     } else if npc.is_some() {
-                        outbox.send(&Click::TargetEntity(entity_id.id));
+                        let tile_x = position.x.round() as i32;
+                        let tile_y = position.y.round() as i32;
+                        let out_of_range = player_query.single().is_ok_and(|player_pos| {
+                            (player_pos.x.round() as i32 - tile_x).abs()
+                                + (player_pos.y.round() as i32 - tile_y).abs()
+                                > NPC_CLICK_RANGE
+                        });
+
+                        if out_of_range {
+                            interaction_intents.write(InteractionIntentEvent {
+                                source: event.source,
+                                target_kind: InteractionTargetKind::Actor,
+                                target_entity: Some(event.entity),
+                                tile_x,
+                                tile_y,
+                                action: InteractionIntentAction::ApproachAndInteract,
+                            });
+                        } else {
+                            outbox.send(&Click::TargetEntity(entity_id.id));
+                        }
                     }
                 } else {
                     // Single click for players/NPCs is now ignored here to prevent
@@ -361,6 +432,7 @@ fn hit_test_scene(
         Option<&NPC>,
         Option<&ItemSprite>,
         Option<&LocalPlayer>,
+        Option<&CreatureSprite>,
     )>,
     map_collision: Option<&crate::ecs::collision::MapCollisionData>,
     pointer_position: (f32, f32),
@@ -403,7 +475,9 @@ fn hit_test_scene(
     }
 
     let mut hits = Vec::new();
-    for (entity, pos, hitbox, player, npc, item, local_player) in entity_query.iter() {
+    for (entity, pos, hitbox, player, npc, item, local_player, creature_sprite) in
+        entity_query.iter()
+    {
         let Some(hb) = hitbox else {
             continue;
         };
@@ -424,6 +498,9 @@ fn hit_test_scene(
             } else if let Some(npc) = npc {
                 Some(SceneEntityHitKind::Npc {
                     name: npc.name.clone(),
+                    unnamed_sprite: creature_sprite
+                        .filter(|_| npc.name.is_empty())
+                        .map(|sprite| sprite.id),
                 })
             } else if item.is_some() {
                 Some(SceneEntityHitKind::Item)
@@ -539,11 +616,13 @@ fn emit_scene_click(
 
 fn show_world_context_menu(
     hit_result: &SceneHitResult,
+    map_pins: &MapPins,
+    group: &GroupState,
     pointer_position: (f32, f32),
     world_context_menu: &mut ResMut<ActiveWorldContextMenu>,
     ui_outbound: &mut MessageWriter<UiOutbound>,
 ) -> bool {
-    let (title, entries) = build_world_context_entries(hit_result);
+    let (title, entries) = build_world_context_entries(hit_result, map_pins, group);
     if entries.is_empty() {
         return false;
     }
@@ -571,6 +650,8 @@ fn show_world_context_menu(
 
 fn build_world_context_entries(
     hit_result: &SceneHitResult,
+    map_pins: &MapPins,
+    group: &GroupState,
 ) -> (String, Vec<WorldContextMenuEntry>) {
     let mut title = String::new();
     let mut entries = Vec::new();
@@ -602,8 +683,20 @@ fn build_world_context_entries(
                         is_self: *is_self,
                     },
                 );
+
+                // Inviting a member again would kick them, the packet toggles
+                if !is_self && !group.is_member(name) {
+                    push_world_context_entry(
+                        &mut entries,
+                        "Invite to group",
+                        WorldContextAction::InviteToGroup { name: name.clone() },
+                    );
+                }
             }
-            SceneEntityHitKind::Npc { name } => {
+            SceneEntityHitKind::Npc {
+                name,
+                unnamed_sprite,
+            } => {
                 if title.is_empty() && hit_result.top_entity == Some(hit.entity) {
                     title = name.clone();
                 }
@@ -613,6 +706,22 @@ fn build_world_context_entries(
                     format!("Speak to {}", name),
                     WorldContextAction::SpeakToNpc { entity: hit.entity },
                 );
+
+                if let Some(sprite) = unnamed_sprite {
+                    push_world_context_entry(
+                        &mut entries,
+                        "Name this creature",
+                        WorldContextAction::NameCreature { sprite: *sprite },
+                    );
+                    push_world_context_entry(
+                        &mut entries,
+                        "Mark as your pet",
+                        WorldContextAction::MarkPet {
+                            entity: hit.entity,
+                            sprite: *sprite,
+                        },
+                    );
+                }
             }
             SceneEntityHitKind::Item => {
                 push_world_context_entry(
@@ -648,6 +757,25 @@ fn build_world_context_entries(
         );
     }
 
+    if let (Some(_), Ok(tile_x), Ok(tile_y)) = (
+        map_pins.map_id(),
+        u16::try_from(hit_result.ground_x),
+        u16::try_from(hit_result.ground_y),
+    ) {
+        match map_pins.at(tile_x, tile_y) {
+            Some(pin) => push_world_context_entry(
+                &mut entries,
+                format!("Remove pin \"{}\"", pin.name),
+                WorldContextAction::RemoveMapPin { tile_x, tile_y },
+            ),
+            None => push_world_context_entry(
+                &mut entries,
+                "Pin this tile",
+                WorldContextAction::PinTile { tile_x, tile_y },
+            ),
+        }
+    }
+
     (title, entries)
 }
 
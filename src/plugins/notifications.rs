@@ -0,0 +1,447 @@
+//! Notification cards with a severity, dismissed after a few seconds and kept
+//! in a history drawer for the session.
+//!
+//! Anything can raise one with `CoreToUi::Notify`. Besides those, this plugin
+//! announces friends coming online (by polling the world list), equipment
+//! dropping below the durability warning and losing the connection. Whispers
+//! are only shown while the chat is hidden, since they are in the chat log
+//! otherwise.
+//!
+//! What gets through also depends on the social status: each one has a
+//! `NotificationPolicy` in the settings, by default Do Not Disturb only lets
+//! whispers through and silences group invites.
+
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_ui::{CoreToUi, NotifyKind, NotifySeverity, UiToCore};
+use packets::types::EquipmentSlot;
+
+use crate::app_state::AppState;
+use crate::events::{AudioEvent, NetworkEvent, SessionEvent, SoundOrigin};
+use crate::plugins::idle::SocialStatusState;
+use crate::settings_types::{Settings, SoundCategory};
+use crate::slint_support::profile_bridge::is_low_durability;
+use crate::webui::plugin::{EquipmentState, UiInbound, UiOutbound};
+
+/// Cards on screen at once; older ones are dismissed early to make room.
+const MAX_ACTIVE: usize = 4;
+/// Oldest history entries are dropped past this many.
+const MAX_HISTORY: usize = 100;
+/// How often the world list is requested to spot friends logging in.
+const FRIEND_POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// Chime for an incoming group invite, the same alert spell timers use.
+const GROUP_INVITE_SOUND: u8 = 1;
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: u32,
+    pub severity: NotifySeverity,
+    pub kind: NotifyKind,
+    pub text: String,
+    /// Local wall-clock time, "HH:MM".
+    pub time: String,
+    expires_at: Duration,
+}
+
+/// How long a card stays up; the more serious, the longer.
+fn lifetime(severity: NotifySeverity) -> Duration {
+    match severity {
+        NotifySeverity::Info | NotifySeverity::Success => Duration::from_secs(5),
+        NotifySeverity::Warning => Duration::from_secs(8),
+        NotifySeverity::Error => Duration::from_secs(12),
+    }
+}
+
+pub fn kind_label(kind: NotifyKind) -> &'static str {
+    match kind {
+        NotifyKind::General => "Notice",
+        NotifyKind::Whisper => "Whisper",
+        NotifyKind::Friend => "Friend",
+        NotifyKind::Durability => "Durability",
+        NotifyKind::Connection => "Connection",
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct NotificationCenter {
+    next_id: u32,
+    /// On screen, oldest first.
+    active: VecDeque<Notification>,
+    /// Everything shown this session, oldest first.
+    history: VecDeque<Notification>,
+    /// History entries added since the drawer was last opened.
+    pub unread: u32,
+    /// The chat log isn't visible, so whispers need a card.
+    pub chat_hidden: bool,
+}
+
+impl NotificationCenter {
+    pub fn active(&self) -> impl Iterator<Item = &Notification> {
+        self.active.iter()
+    }
+
+    pub fn history(&self) -> impl Iterator<Item = &Notification> {
+        self.history.iter()
+    }
+
+    /// Whether a notification of `kind` gets a card at all.
+    fn wants(&self, kind: NotifyKind) -> bool {
+        kind != NotifyKind::Whisper || self.chat_hidden || cfg!(target_os = "android")
+    }
+
+    fn push(&mut self, now: Duration, severity: NotifySeverity, kind: NotifyKind, text: String) {
+        let notification = Notification {
+            id: self.next_id,
+            severity,
+            kind,
+            text,
+            time: chrono::Local::now().format("%H:%M").to_string(),
+            expires_at: now + lifetime(severity),
+        };
+        self.next_id = self.next_id.wrapping_add(1);
+
+        if self.history.len() >= MAX_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(notification.clone());
+        self.unread = self.unread.saturating_add(1);
+
+        if self.active.len() >= MAX_ACTIVE {
+            self.active.pop_front();
+        }
+        self.active.push_back(notification);
+    }
+
+    pub fn dismiss(&mut self, id: u32) {
+        self.active.retain(|n| n.id != id);
+    }
+
+    fn has_expired(&self, now: Duration) -> bool {
+        self.active.iter().any(|n| n.expires_at <= now)
+    }
+
+    fn expire(&mut self, now: Duration) {
+        self.active.retain(|n| n.expires_at > now);
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.unread = 0;
+    }
+}
+
+/// Characters seen on the last world list, lowercased; `None` until the first
+/// list arrives so friends already online aren't announced at login.
+#[derive(Resource, Default, Debug)]
+struct OnlineNames(Option<HashSet<String>>);
+
+impl OnlineNames {
+    /// Records the latest world list and returns the friends who weren't on the last one.
+    fn update<'a>(
+        &mut self,
+        names: impl Iterator<Item = &'a str>,
+        is_friend: impl Fn(&str) -> bool,
+    ) -> Vec<String> {
+        let mut online = HashSet::new();
+        let mut arrived = Vec::new();
+        for name in names {
+            let key = name.to_lowercase();
+            if let Some(previous) = &self.0 {
+                if !previous.contains(&key) && is_friend(name) {
+                    arrived.push(name.to_owned());
+                }
+            }
+            online.insert(key);
+        }
+        self.0 = Some(online);
+        arrived
+    }
+}
+
+/// Equipment slots already warned about, until the item is repaired or swapped.
+#[derive(Resource, Default, Debug)]
+struct WornSlots(HashSet<EquipmentSlot>);
+
+pub struct NotificationsPlugin;
+
+impl Plugin for NotificationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NotificationCenter>()
+            .init_resource::<OnlineNames>()
+            .init_resource::<WornSlots>()
+            .add_systems(OnExit(AppState::InGame), reset_notifications)
+            .add_systems(
+                Update,
+                (
+                    (
+                        notify_friend_logins,
+                        notify_low_durability,
+                        notify_disconnect,
+                        poll_world_list,
+                        play_group_invite_sound,
+                    ),
+                    collect_notifications,
+                    handle_notification_input,
+                    expire_notifications,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn reset_notifications(mut commands: Commands) {
+    commands.insert_resource(NotificationCenter::default());
+    commands.insert_resource(OnlineNames::default());
+    commands.insert_resource(WornSlots::default());
+}
+
+fn notify(
+    outbound: &mut MessageWriter<UiOutbound>,
+    severity: NotifySeverity,
+    kind: NotifyKind,
+    text: String,
+) {
+    outbound.write(UiOutbound(CoreToUi::Notify {
+        severity,
+        kind,
+        text,
+    }));
+}
+
+fn notify_friend_logins(
+    mut session_events: MessageReader<SessionEvent>,
+    mut outbound: MessageWriter<UiOutbound>,
+    settings: Res<Settings>,
+    mut online: ResMut<OnlineNames>,
+) {
+    for event in session_events.read() {
+        let SessionEvent::WorldList(list) = event else {
+            continue;
+        };
+        let arrived = online.update(list.country_list.iter().map(|m| m.name.as_str()), |name| {
+            settings.gameplay.is_friend(name)
+        });
+        for name in arrived {
+            notify(
+                &mut outbound,
+                NotifySeverity::Success,
+                NotifyKind::Friend,
+                format!("{} is online", name),
+            );
+        }
+    }
+}
+
+/// Asks for the world list now and then while there are friends to look out for.
+fn poll_world_list(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    outbox: Option<Res<crate::network::PacketOutbox>>,
+    mut last_poll: Local<Option<Duration>>,
+) {
+    let (Some(outbox), false) = (outbox, settings.gameplay.friends.is_empty()) else {
+        return;
+    };
+    let now = time.elapsed();
+    if last_poll.is_some_and(|at| now.saturating_sub(at) < FRIEND_POLL_INTERVAL) {
+        return;
+    }
+    *last_poll = Some(now);
+    outbox.send(&packets::client::WorldListRequest);
+}
+
+fn notify_low_durability(
+    equipment: Res<EquipmentState>,
+    settings: Res<Settings>,
+    mut outbound: MessageWriter<UiOutbound>,
+    mut worn: ResMut<WornSlots>,
+) {
+    if !equipment.is_changed() {
+        return;
+    }
+    let threshold = settings.gameplay.durability_warning_percent;
+    worn.0.retain(|slot| {
+        equipment.0.get(slot).is_some_and(|item| {
+            is_low_durability(item.current_durability, item.max_durability, threshold)
+        })
+    });
+    for (slot, item) in &equipment.0 {
+        if !is_low_durability(item.current_durability, item.max_durability, threshold)
+            || !worn.0.insert(*slot)
+        {
+            continue;
+        }
+        notify(
+            &mut outbound,
+            NotifySeverity::Warning,
+            NotifyKind::Durability,
+            format!(
+                "{} is at {}% durability",
+                item.name,
+                u64::from(item.current_durability) * 100 / u64::from(item.max_durability)
+            ),
+        );
+    }
+}
+
+fn notify_disconnect(
+    mut net_events: MessageReader<NetworkEvent>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    for event in net_events.read() {
+        let text = match event {
+            NetworkEvent::Disconnected => "Disconnected from the server",
+            NetworkEvent::TimedOut => "The server stopped responding, the connection timed out",
+            _ => continue,
+        };
+        notify(
+            &mut outbound,
+            NotifySeverity::Error,
+            NotifyKind::Connection,
+            text.to_string(),
+        );
+    }
+}
+
+fn play_group_invite_sound(
+    mut session_events: MessageReader<SessionEvent>,
+    mut audio_events: MessageWriter<AudioEvent>,
+    settings: Res<Settings>,
+    status: Res<SocialStatusState>,
+) {
+    let invited = session_events.read().any(|event| {
+        matches!(
+            event,
+            SessionEvent::GroupInvite(packets::server::DisplayGroupInvite::Invite { .. })
+        )
+    });
+    if invited
+        && settings
+            .gameplay
+            .notification_policy(status.status)
+            .plays_sounds()
+    {
+        audio_events.write(AudioEvent::PlayEffect {
+            sound: GROUP_INVITE_SOUND,
+            category: SoundCategory::Ambient,
+            origin: SoundOrigin::Local,
+        });
+    }
+}
+
+fn collect_notifications(
+    mut outbound: MessageReader<UiOutbound>,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    status: Res<SocialStatusState>,
+    mut center: ResMut<NotificationCenter>,
+) {
+    let now = time.elapsed();
+    let policy = settings.gameplay.notification_policy(status.status);
+    for UiOutbound(msg) in outbound.read() {
+        if let CoreToUi::Notify {
+            severity,
+            kind,
+            text,
+        } = msg
+        {
+            if center.wants(*kind) && policy.shows(*kind == NotifyKind::Whisper) {
+                center.push(now, *severity, *kind, text.clone());
+            }
+        }
+    }
+}
+
+fn handle_notification_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut center: ResMut<NotificationCenter>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::UiHiddenChange { hidden } => center.chat_hidden = *hidden,
+            UiToCore::NotificationDismiss { id } => center.dismiss(*id),
+            UiToCore::NotificationHistoryOpened => center.unread = 0,
+            UiToCore::NotificationHistoryClear => center.clear_history(),
+            _ => {}
+        }
+    }
+}
+
+fn expire_notifications(time: Res<Time>, mut center: ResMut<NotificationCenter>) {
+    // Checked first so the HUD isn't resynced every frame
+    let now = time.elapsed();
+    if center.has_expired(now) {
+        center.expire(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cards_expire_but_stay_in_history() {
+        let mut center = NotificationCenter::default();
+        for i in 0..6 {
+            center.push(
+                Duration::ZERO,
+                NotifySeverity::Info,
+                NotifyKind::General,
+                format!("{}", i),
+            );
+        }
+        center.push(
+            Duration::ZERO,
+            NotifySeverity::Error,
+            NotifyKind::Connection,
+            "lost".to_string(),
+        );
+        assert_eq!(center.active().count(), MAX_ACTIVE);
+        assert_eq!(center.history().count(), 7);
+        assert_eq!(center.unread, 7);
+
+        center.expire(Duration::from_secs(6));
+        let active: Vec<_> = center.active().map(|n| n.text.as_str()).collect();
+        assert_eq!(active, ["lost"]);
+
+        center.chat_hidden = true;
+        assert!(center.wants(NotifyKind::Whisper));
+    }
+
+    #[test]
+    fn do_not_disturb_only_lets_whispers_through() {
+        use packets::types::SocialStatus;
+
+        let gameplay = Settings::default().gameplay;
+        let dnd = gameplay.notification_policy(SocialStatus::DoNotDisturb);
+        assert!(dnd.shows(true));
+        assert!(!dnd.shows(false));
+        assert!(!dnd.plays_sounds());
+        assert!(
+            gameplay
+                .notification_policy(SocialStatus::Awake)
+                .plays_sounds()
+        );
+    }
+
+    #[test]
+    fn friends_are_announced_once_they_appear() {
+        let mut online = OnlineNames::default();
+        let is_friend = |name: &str| name.eq_ignore_ascii_case("Kedian");
+
+        assert!(
+            online
+                .update(["Kedian", "Ailla"].into_iter(), is_friend)
+                .is_empty()
+        );
+        assert!(online.update(["Ailla"].into_iter(), is_friend).is_empty());
+        assert_eq!(
+            online.update(["Ailla", "Kedian"].into_iter(), is_friend),
+            ["Kedian"]
+        );
+        assert!(online.update(["KEDIAN"].into_iter(), is_friend).is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+//! The player's pet or summon, shown in a frame next to the player's own.
+//!
+//! Servers don't say who owns a creature, so a summon is recognised by timing:
+//! the first creature to appear next to the player within `SUMMON_WINDOW` of
+//! casting a spell named like a summon. Any unnamed creature can also be
+//! marked as the pet from the world context menu. Its health comes from the
+//! health bars the server sends as it takes damage.
+//!
+//! The protocol has no pet commands, so the frame can only cast a spell that
+//! is waiting for a target on the pet, or let it go.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_ui::UiToCore;
+use packets::server::VisibleEntityType;
+
+use crate::app_state::AppState;
+use crate::ecs::components::{CreatureSprite, EntityId, LocalPlayer, NPC, Position};
+use crate::ecs::spell_casting::SpellCastingState;
+use crate::events::{ClickSource, EntityClickEvent, EntityEvent, SpellCastEvent};
+use crate::webui::plugin::{AbilityState, UiInbound};
+
+/// How long after a summon is cast a creature appearing nearby counts as it.
+const SUMMON_WINDOW: Duration = Duration::from_secs(3);
+/// Tiles from the player a summon appears within.
+const SUMMON_RADIUS: f32 = 3.0;
+/// Words in a spell's name that mark it as a summon.
+const SUMMON_WORDS: &[&str] = &["summon", "familiar"];
+
+pub fn is_summon_spell(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SUMMON_WORDS.iter().any(|word| name.contains(word))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pet {
+    pub id: u32,
+    pub sprite: u16,
+    /// Unknown until the pet first takes damage.
+    pub health_percent: Option<u8>,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct PetTracker {
+    pet: Option<Pet>,
+    /// When the player last cast a summon that hasn't shown up yet.
+    summoned_at: Option<Duration>,
+}
+
+impl PetTracker {
+    pub fn pet(&self) -> Option<&Pet> {
+        self.pet.as_ref()
+    }
+
+    pub fn is_pet(&self, id: u32) -> bool {
+        self.pet.as_ref().is_some_and(|pet| pet.id == id)
+    }
+
+    pub fn mark(&mut self, id: u32, sprite: u16) {
+        self.pet = Some(Pet {
+            id,
+            sprite,
+            health_percent: None,
+        });
+        self.summoned_at = None;
+    }
+
+    pub fn release(&mut self) {
+        self.pet = None;
+    }
+
+    fn summoned(&mut self, now: Duration) {
+        self.summoned_at = Some(now);
+    }
+
+    fn awaiting_summon(&self, now: Duration) -> bool {
+        self.summoned_at
+            .is_some_and(|at| now.saturating_sub(at) <= SUMMON_WINDOW)
+    }
+
+    /// Takes a creature that appeared `distance` tiles from the player as the
+    /// summon just cast, if there is one. Returns whether it did.
+    fn creature_appeared(&mut self, id: u32, sprite: u16, distance: f32, now: Duration) -> bool {
+        if !self.awaiting_summon(now) || distance > SUMMON_RADIUS {
+            return false;
+        }
+        self.mark(id, sprite);
+        true
+    }
+}
+
+pub struct PetsPlugin;
+
+impl Plugin for PetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PetTracker>()
+            .add_systems(
+                Update,
+                (
+                    record_summon_casts,
+                    detect_summons,
+                    track_pet_health,
+                    handle_pet_input,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_pet);
+    }
+}
+
+fn reset_pet(mut commands: Commands) {
+    commands.insert_resource(PetTracker::default());
+}
+
+fn record_summon_casts(
+    time: Res<Time>,
+    abilities: Option<Res<AbilityState>>,
+    mut cast_events: MessageReader<SpellCastEvent>,
+    mut tracker: ResMut<PetTracker>,
+) {
+    for SpellCastEvent { slot } in cast_events.read() {
+        let is_summon = abilities
+            .as_ref()
+            .and_then(|abilities| abilities.spells.iter().find(|spell| spell.slot == *slot))
+            .is_some_and(|spell| is_summon_spell(&spell.panel_name));
+        if is_summon {
+            tracker.summoned(time.elapsed());
+        }
+    }
+}
+
+fn detect_summons(
+    time: Res<Time>,
+    player: Query<&Position, With<LocalPlayer>>,
+    creatures: Query<(&EntityId, &NPC, &Position, &CreatureSprite), Added<NPC>>,
+    mut tracker: ResMut<PetTracker>,
+) {
+    let now = time.elapsed();
+    if !tracker.awaiting_summon(now) {
+        return;
+    }
+    let Ok(player_pos) = player.single() else {
+        return;
+    };
+    for (id, npc, pos, sprite) in creatures.iter() {
+        if !matches!(
+            npc.entity_type,
+            VisibleEntityType::Normal | VisibleEntityType::WalkThrough
+        ) {
+            continue;
+        }
+        let distance = pos.to_vec2().distance(player_pos.to_vec2());
+        if tracker.creature_appeared(id.id, sprite.id, distance, now) {
+            break;
+        }
+    }
+}
+
+fn track_pet_health(
+    mut entity_events: MessageReader<EntityEvent>,
+    mut tracker: ResMut<PetTracker>,
+) {
+    for event in entity_events.read() {
+        if let EntityEvent::HealthBar(bar) = event
+            && tracker.is_pet(bar.source_id)
+            && let Some(pet) = tracker.pet.as_mut()
+        {
+            pet.health_percent = Some(bar.health_percent);
+        }
+    }
+}
+
+fn handle_pet_input(
+    mut inbound: MessageReader<UiInbound>,
+    spell_casting: Res<SpellCastingState>,
+    creatures: Query<(Entity, &EntityId, &Position), With<NPC>>,
+    mut clicks: MessageWriter<EntityClickEvent>,
+    mut tracker: ResMut<PetTracker>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::PetFrameClick => {
+                let waiting_for_target = spell_casting
+                    .active_cast
+                    .as_ref()
+                    .is_some_and(|cast| cast.waiting_for_target);
+                if !waiting_for_target {
+                    continue;
+                }
+                // Same as clicking the pet in the world, which casts the spell on it
+                if let Some((entity, _, pos)) =
+                    creatures.iter().find(|(_, id, _)| tracker.is_pet(id.id))
+                {
+                    clicks.write(EntityClickEvent {
+                        entity,
+                        ground_tile_x: pos.x.round() as i32,
+                        ground_tile_y: pos.y.round() as i32,
+                        button: MouseButton::Left,
+                        source: ClickSource::DesktopMouse,
+                        is_double_click: false,
+                    });
+                }
+            }
+            UiToCore::PetRelease => tracker.release(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summons_are_the_next_creature_nearby() {
+        assert!(is_summon_spell("Summon Wolf (Lev 3/100)"));
+        assert!(!is_summon_spell("ard ioc"));
+
+        let mut tracker = PetTracker::default();
+        let cast = Duration::from_secs(10);
+        assert!(!tracker.creature_appeared(7, 100, 1.0, cast));
+
+        tracker.summoned(cast);
+        assert!(!tracker.creature_appeared(7, 100, 6.0, cast));
+        assert!(!tracker.creature_appeared(8, 100, 1.0, cast + Duration::from_secs(5)));
+        assert!(tracker.creature_appeared(9, 100, 1.0, cast + Duration::from_secs(1)));
+        assert!(tracker.is_pet(9));
+
+        // Only the first one, the next creature to walk up isn't another summon
+        assert!(!tracker.creature_appeared(10, 100, 1.0, cast + Duration::from_secs(2)));
+    }
+}
@@ -0,0 +1,129 @@
+//! Potato mode: a graphics preset for old or integrated GPUs.
+//!
+//! Spell effects are not drawn, scaling switches to whole pixels, which skips
+//! the sharpening pass, and map ambience drops its tint and weather (see
+//! `map_ambience`). The effect atlas is created at startup, so its smaller
+//! size only applies after a restart.
+//!
+//! When every adapter the system reports looks weak, the player is pointed at
+//! the setting once per profile.
+
+use bevy::prelude::*;
+use game_ui::CoreToUi;
+use rendering::scene::effects::EFFECT_ATLAS_SIZE;
+
+use crate::app_state::AppState;
+use crate::settings_types::{RenderScaling, Settings};
+use crate::webui::plugin::UiOutbound;
+
+/// Effect atlas side in potato mode, a sixteenth of the usual memory.
+const POTATO_EFFECT_ATLAS_SIZE: u32 = 512;
+
+/// Integrated GPUs with textures no bigger than this are old enough to
+/// struggle with the full renderer.
+const WEAK_MAX_TEXTURE_SIZE: u32 = 8192;
+
+/// Set when the GPU looks too weak for the full renderer, with a description
+/// of the best adapter found.
+#[derive(Resource, Debug, Clone)]
+pub struct WeakGpu(pub String);
+
+pub struct PotatoModePlugin;
+
+impl Plugin for PotatoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            suggest_potato_mode.run_if(in_state(AppState::InGame)),
+        );
+    }
+}
+
+/// Turns potato mode on or off. Turning it on also applies the rest of the
+/// preset.
+pub fn set_potato_mode(settings: &mut Settings, enabled: bool) {
+    settings.graphics.potato_mode = enabled;
+    if enabled {
+        settings.graphics.render_scaling = RenderScaling::Integer;
+    }
+}
+
+/// Side of the effect atlas to create for the current settings.
+pub fn effect_atlas_size(settings: &Settings) -> u32 {
+    if settings.graphics.potato_mode {
+        POTATO_EFFECT_ATLAS_SIZE
+    } else {
+        EFFECT_ATLAS_SIZE
+    }
+}
+
+fn is_weak_adapter(device_type: wgpu::DeviceType, max_texture_dimension_2d: u32) -> bool {
+    match device_type {
+        wgpu::DeviceType::Cpu => true,
+        wgpu::DeviceType::IntegratedGpu => max_texture_dimension_2d <= WEAK_MAX_TEXTURE_SIZE,
+        _ => false,
+    }
+}
+
+/// Describes the best adapter when every adapter available is weak, or
+/// returns `None` if any of them can run the full renderer.
+pub fn weak_gpu_reason(instance: &wgpu::Instance) -> Option<String> {
+    let adapters =
+        futures_lite::future::block_on(instance.enumerate_adapters(wgpu::Backends::all()));
+    let mut weakest = None;
+    for adapter in &adapters {
+        let info = adapter.get_info();
+        if !is_weak_adapter(info.device_type, adapter.limits().max_texture_dimension_2d) {
+            return None;
+        }
+        weakest.get_or_insert_with(|| format!("{} ({:?})", info.name, info.device_type));
+    }
+    weakest
+}
+
+fn suggest_potato_mode(
+    weak_gpu: Option<Res<WeakGpu>>,
+    mut settings: ResMut<Settings>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    let Some(weak_gpu) = weak_gpu else {
+        return;
+    };
+    if settings.graphics.potato_mode || settings.graphics.potato_mode_suggested {
+        return;
+    }
+    settings.graphics.potato_mode_suggested = true;
+    tracing::info!("Suggesting potato mode for {}", weak_gpu.0);
+    outbound.write(UiOutbound(CoreToUi::Toast {
+        text: format!(
+            "{} may struggle with the game. Try Settings → Graphics → Potato mode.",
+            weak_gpu.0
+        ),
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_cpus_and_old_integrated_gpus_are_weak() {
+        assert!(is_weak_adapter(wgpu::DeviceType::Cpu, 16384));
+        assert!(is_weak_adapter(wgpu::DeviceType::IntegratedGpu, 8192));
+        assert!(!is_weak_adapter(wgpu::DeviceType::IntegratedGpu, 16384));
+        assert!(!is_weak_adapter(wgpu::DeviceType::DiscreteGpu, 4096));
+    }
+
+    #[test]
+    fn preset_switches_to_integer_scaling() {
+        let mut settings = Settings::default();
+        set_potato_mode(&mut settings, true);
+        assert!(settings.graphics.potato_mode);
+        assert_eq!(settings.graphics.render_scaling, RenderScaling::Integer);
+        assert_eq!(effect_atlas_size(&settings), POTATO_EFFECT_ATLAS_SIZE);
+
+        set_potato_mode(&mut settings, false);
+        assert!(!settings.graphics.potato_mode);
+        assert_eq!(effect_atlas_size(&settings), EFFECT_ATLAS_SIZE);
+    }
+}
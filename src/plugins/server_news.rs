@@ -0,0 +1,340 @@
+//! News pane on the login screen.
+//!
+//! A server entry may name a news feed, either JSON (`{"items": [{"title",
+//! "date", "body"}]}` or a bare array of items) or an RSS/Atom feed. The feed of
+//! the selected server is fetched while the player is on the login screen and
+//! cached per server, so the last news shown stays readable offline.
+
+use std::io::Read;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::tasks::{IoTaskPool, Task};
+use futures_lite::future;
+use game_ui::{CoreToUi, ServerNewsItemUi};
+use serde::{Deserialize, Serialize};
+
+use crate::app_state::AppState;
+use crate::resources::StorageConfig;
+use crate::settings_types::Settings;
+use crate::webui::plugin::UiOutbound;
+
+/// Cached news younger than this isn't fetched again when switching servers.
+const NEWS_REFRESH: Duration = Duration::from_secs(30 * 60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_FEED_BYTES: u64 = 1024 * 1024;
+const MAX_ITEMS: usize = 10;
+/// Item bodies are cut to this many characters.
+const MAX_BODY_CHARS: usize = 400;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct NewsItem {
+    #[serde(default)]
+    title: String,
+    #[serde(default, alias = "published", alias = "pubDate")]
+    date: String,
+    #[serde(default, alias = "summary", alias = "content")]
+    body: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonFeed {
+    Items { items: Vec<NewsItem> },
+    List(Vec<NewsItem>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NewsCache {
+    url: String,
+    /// Unix seconds.
+    fetched_at: u64,
+    items: Vec<NewsItem>,
+}
+
+/// Drops markup from feed text and squeezes its whitespace.
+fn plain_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                out.push(' ');
+            }
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn parse_xml_feed(text: &str) -> Result<Vec<NewsItem>, String> {
+    let doc = roxmltree::Document::parse(text).map_err(|e| e.to_string())?;
+    let child_text = |node: roxmltree::Node, names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| node.children().find(|c| c.has_tag_name(*name)))
+            .map(|c| c.descendants().filter_map(|d| d.text()).collect::<String>())
+            .unwrap_or_default()
+    };
+    Ok(doc
+        .descendants()
+        .filter(|n| n.has_tag_name("item") || n.has_tag_name("entry"))
+        .map(|n| NewsItem {
+            title: child_text(n, &["title"]),
+            date: child_text(n, &["pubDate", "published", "updated"]),
+            body: child_text(n, &["description", "summary", "content"]),
+        })
+        .collect())
+}
+
+/// Parses a JSON or RSS/Atom feed, newest items first as the feed orders them.
+fn parse_feed(text: &str) -> Result<Vec<NewsItem>, String> {
+    let items = if text.trim_start().starts_with('<') {
+        parse_xml_feed(text)?
+    } else {
+        match serde_json::from_str(text).map_err(|e| format!("not a news feed: {}", e))? {
+            JsonFeed::Items { items } | JsonFeed::List(items) => items,
+        }
+    };
+    Ok(items
+        .into_iter()
+        .map(|item| NewsItem {
+            title: plain_text(&item.title),
+            date: plain_text(&item.date),
+            body: plain_text(&item.body)
+                .chars()
+                .take(MAX_BODY_CHARS)
+                .collect(),
+        })
+        .filter(|item| !item.title.is_empty() || !item.body.is_empty())
+        .take(MAX_ITEMS)
+        .collect())
+}
+
+fn fetch_feed(url: &str) -> Result<Vec<NewsItem>, String> {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .and_then(|client| client.get(url).send())
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    response
+        .take(MAX_FEED_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    parse_feed(&String::from_utf8_lossy(&bytes))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn load_cache(path: &std::path::Path, url: &str) -> Option<NewsCache> {
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str::<NewsCache>(&text)
+        .ok()
+        .filter(|cache| cache.url == url)
+}
+
+fn news_message(items: &[NewsItem], status: String) -> UiOutbound {
+    UiOutbound(CoreToUi::ServerNews {
+        items: items
+            .iter()
+            .map(|item| ServerNewsItemUi {
+                title: item.title.clone(),
+                date: item.date.clone(),
+                body: item.body.clone(),
+            })
+            .collect(),
+        status,
+    })
+}
+
+#[derive(Resource)]
+struct ServerNews {
+    /// Server id and feed URL currently shown.
+    shown: Option<(u32, String)>,
+    task: Option<Task<Result<Vec<NewsItem>, String>>>,
+    timer: Timer,
+}
+
+impl Default for ServerNews {
+    fn default() -> Self {
+        Self {
+            shown: None,
+            task: None,
+            timer: Timer::new(NEWS_REFRESH, TimerMode::Repeating),
+        }
+    }
+}
+
+impl ServerNews {
+    fn fetch(&mut self, url: &str) {
+        let url = url.to_owned();
+        self.task = Some(
+            IoTaskPool::get().spawn(async_std::task::spawn_blocking(move || fetch_feed(&url))),
+        );
+    }
+}
+
+pub struct ServerNewsPlugin;
+
+impl Plugin for ServerNewsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ServerNews>()
+            .add_systems(OnEnter(AppState::MainMenu), reset_server_news)
+            .add_systems(
+                Update,
+                (select_news_feed, collect_news)
+                    .chain()
+                    .run_if(in_state(AppState::MainMenu)),
+            );
+    }
+}
+
+fn reset_server_news(mut commands: Commands) {
+    commands.insert_resource(ServerNews::default());
+}
+
+fn select_news_feed(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    storage: Res<StorageConfig>,
+    mut news: ResMut<ServerNews>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    let current = settings
+        .gameplay
+        .current_server_id
+        .and_then(|id| settings.servers.iter().find(|s| s.id == id))
+        .or(settings.servers.first())
+        .and_then(|s| Some((s.id, s.news_url.clone()?)));
+
+    if current == news.shown {
+        if let Some((_, url)) = current
+            && news.timer.tick(time.delta()).just_finished()
+            && news.task.is_none()
+        {
+            news.fetch(&url);
+        }
+        return;
+    }
+
+    news.shown = current.clone();
+    news.task = None;
+    news.timer.reset();
+    let Some((server_id, url)) = current else {
+        outbound.write(news_message(&[], String::new()));
+        return;
+    };
+    match load_cache(&storage.server_news_path(server_id), &url) {
+        Some(cache) => {
+            outbound.write(news_message(&cache.items, String::new()));
+            if unix_now().saturating_sub(cache.fetched_at) >= NEWS_REFRESH.as_secs() {
+                news.fetch(&url);
+            }
+        }
+        None => {
+            outbound.write(news_message(&[], "Loading news...".to_owned()));
+            news.fetch(&url);
+        }
+    }
+}
+
+fn collect_news(
+    storage: Res<StorageConfig>,
+    mut news: ResMut<ServerNews>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    let Some(task) = news.task.as_mut() else {
+        return;
+    };
+    let Some(result) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    news.task = None;
+    let Some((server_id, url)) = news.shown.clone() else {
+        return;
+    };
+    let path = storage.server_news_path(server_id);
+
+    match result {
+        Ok(items) => {
+            let cache = NewsCache {
+                url,
+                fetched_at: unix_now(),
+                items,
+            };
+            if let Ok(json) = serde_json::to_string_pretty(&cache) {
+                let _ = std::fs::write(&path, json);
+            }
+            outbound.write(news_message(&cache.items, String::new()));
+        }
+        Err(e) => {
+            warn!("Failed to fetch news from {}: {}", url, e);
+            let message = match load_cache(&path, &url) {
+                Some(cache) => {
+                    let saved = chrono::DateTime::from_timestamp(cache.fetched_at as i64, 0)
+                        .map(|t| {
+                            t.with_timezone(&chrono::Local)
+                                .format("%b %-d, %H:%M")
+                                .to_string()
+                        })
+                        .unwrap_or_default();
+                    news_message(
+                        &cache.items,
+                        format!("Offline, showing news from {}", saved),
+                    )
+                }
+                None => news_message(&[], "News is unavailable right now.".to_owned()),
+            };
+            outbound.write(message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_and_rss_feeds_parse_to_plain_items() {
+        let json = r#"{"items": [
+            {"title": "Patch 1.2", "date": "2026-10-01", "body": "<p>New <b>quests</b></p>"},
+            {"summary": "untitled notes"}
+        ]}"#;
+        assert_eq!(
+            parse_feed(json).unwrap(),
+            vec![
+                NewsItem {
+                    title: "Patch 1.2".to_owned(),
+                    date: "2026-10-01".to_owned(),
+                    body: "New quests".to_owned(),
+                },
+                NewsItem {
+                    title: String::new(),
+                    date: String::new(),
+                    body: "untitled notes".to_owned(),
+                },
+            ]
+        );
+
+        let rss = r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>Shard</title>
+              <item><title>Maintenance</title><pubDate>Tue, 13 Oct 2026 04:00:00 GMT</pubDate>
+                <description>&lt;p&gt;Down for an hour&lt;/p&gt;</description></item>
+            </channel></rss>"#;
+        let items = parse_feed(rss).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Maintenance");
+        assert_eq!(items[0].date, "Tue, 13 Oct 2026 04:00:00 GMT");
+        assert_eq!(items[0].body, "Down for an hour");
+    }
+}
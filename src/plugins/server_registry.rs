@@ -0,0 +1,412 @@
+//! Community server lists the player subscribes to by URL.
+//!
+//! A subscription points at a JSON manifest:
+//!
+//! ```json
+//! {
+//!   "name": "Temuair Shards",
+//!   "servers": [
+//!     { "name": "Loures", "address": "loures.example.org", "port": 2610,
+//!       "protocol": "7.41", "icon": "https://example.org/loures.png",
+//!       "news": "https://example.org/loures/news.rss" }
+//!   ]
+//! }
+//! ```
+//!
+//! Manifests are fetched while the player is on the login screen and again
+//! every few hours. Servers they list are merged into the server list tagged
+//! with the manifest URL, so a refresh replaces them without touching servers
+//! the player added by hand. A failed refresh keeps the servers from the one
+//! before.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::tasks::{IoTaskPool, Task};
+use futures_lite::future;
+use game_ui::{CoreToUi, ServerSubscriptionUi, UiToCore};
+use network::connect::{DEFAULT_PORT, join_host_port, split_host_port};
+use serde::Deserialize;
+
+use crate::app_state::AppState;
+use crate::resources::StorageConfig;
+use crate::settings_types::{ServerEntry, ServerSubscription, Settings};
+use crate::webui::plugin::{UiInbound, UiOutbound, next_id};
+
+/// How often subscribed manifests are fetched again.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Manifests and icons larger than this are refused.
+const MAX_DOWNLOAD_BYTES: u64 = 512 * 1024;
+/// Servers past this many in one manifest are ignored.
+const MAX_MANIFEST_SERVERS: usize = 64;
+
+#[derive(Debug, Deserialize)]
+struct ServerManifest {
+    #[serde(default)]
+    name: String,
+    servers: Vec<ManifestServer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestServer {
+    name: String,
+    address: String,
+    #[serde(default)]
+    port: Option<u16>,
+    #[serde(default)]
+    protocol: Option<String>,
+    #[serde(default)]
+    icon: Option<String>,
+    #[serde(default)]
+    news: Option<String>,
+}
+
+impl ManifestServer {
+    fn full_address(&self) -> String {
+        match split_host_port(&self.address) {
+            (_, Some(_)) => self.address.trim().to_owned(),
+            (host, None) => join_host_port(&host, self.port.unwrap_or(DEFAULT_PORT)),
+        }
+    }
+}
+
+fn parse_manifest(text: &str) -> Result<ServerManifest, String> {
+    let mut manifest: ServerManifest =
+        serde_json::from_str(text).map_err(|e| format!("not a server list: {}", e))?;
+    manifest
+        .servers
+        .retain(|s| !s.name.trim().is_empty() && !s.address.trim().is_empty());
+    manifest.servers.truncate(MAX_MANIFEST_SERVERS);
+    if manifest.servers.is_empty() {
+        return Err("the list has no servers".to_owned());
+    }
+    Ok(manifest)
+}
+
+/// Replaces the servers imported from `source` with the ones in `manifest`.
+/// Entries keep their id when the name or address still matches, so saved
+/// logins, per-server data and connection tuning stay attached across refreshes.
+fn merge_manifest(servers: &mut Vec<ServerEntry>, source: &str, manifest: &ServerManifest) {
+    let mut previous = Vec::new();
+    servers.retain(|s| {
+        if s.source.as_deref() == Some(source) {
+            previous.push(s.clone());
+            false
+        } else {
+            true
+        }
+    });
+
+    for server in &manifest.servers {
+        let name = server.name.trim().to_owned();
+        let address = server.full_address();
+        let (id, connection) = match previous
+            .iter()
+            .position(|p| p.name == name || p.address == address)
+        {
+            Some(index) => {
+                let kept = previous.remove(index);
+                (kept.id, kept.connection)
+            }
+            None => (
+                next_id(servers.iter().chain(previous.iter()).map(|s| s.id)),
+                Default::default(),
+            ),
+        };
+        servers.push(ServerEntry {
+            id,
+            name,
+            address,
+            protocol: server.protocol.clone().filter(|p| !p.trim().is_empty()),
+            icon: server.icon.clone().filter(|i| !i.trim().is_empty()),
+            news_url: server.news.clone().filter(|n| !n.trim().is_empty()),
+            source: Some(source.to_owned()),
+            connection,
+        });
+    }
+}
+
+fn download(client: &reqwest::blocking::Client, url: &str) -> Result<Vec<u8>, String> {
+    let response = client
+        .get(url)
+        .send()
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    response
+        .take(MAX_DOWNLOAD_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    if bytes.len() as u64 > MAX_DOWNLOAD_BYTES {
+        return Err("download is too large".to_owned());
+    }
+    Ok(bytes)
+}
+
+/// Fetches a manifest and caches the icons it names. Runs on a blocking thread.
+fn fetch_manifest(url: &str, storage: &StorageConfig) -> Result<ServerManifest, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let bytes = download(&client, url)?;
+    let manifest = parse_manifest(&String::from_utf8_lossy(&bytes))?;
+
+    for icon in manifest.servers.iter().filter_map(|s| s.icon.as_deref()) {
+        let path = storage.server_icon_path(icon);
+        if path.exists() {
+            continue;
+        }
+        match download(&client, icon) {
+            Ok(bytes) => {
+                let _ = std::fs::write(&path, bytes);
+            }
+            Err(e) => warn!("Failed to fetch server icon {}: {}", icon, e),
+        }
+    }
+    Ok(manifest)
+}
+
+#[derive(Resource)]
+struct ServerRegistry {
+    timer: Timer,
+    tasks: Vec<(String, Task<Result<ServerManifest, String>>)>,
+    /// Last refresh error per subscription URL.
+    errors: HashMap<String, String>,
+}
+
+impl Default for ServerRegistry {
+    fn default() -> Self {
+        let mut timer = Timer::new(REFRESH_INTERVAL, TimerMode::Repeating);
+        timer.set_elapsed(REFRESH_INTERVAL);
+        Self {
+            timer,
+            tasks: Vec::new(),
+            errors: HashMap::new(),
+        }
+    }
+}
+
+impl ServerRegistry {
+    fn fetch(&mut self, url: &str, storage: &StorageConfig) {
+        if self.tasks.iter().any(|(u, _)| u == url) {
+            return;
+        }
+        let (task_url, storage) = (url.to_owned(), storage.clone());
+        let task = IoTaskPool::get().spawn(async_std::task::spawn_blocking(move || {
+            fetch_manifest(&task_url, &storage)
+        }));
+        self.tasks.push((url.to_owned(), task));
+    }
+
+    fn to_message(&self, settings: &Settings) -> CoreToUi {
+        CoreToUi::ServerSubscriptions {
+            subscriptions: settings
+                .server_subscriptions
+                .iter()
+                .map(|sub| ServerSubscriptionUi {
+                    url: sub.url.clone(),
+                    name: sub.name.clone(),
+                    server_count: settings
+                        .servers
+                        .iter()
+                        .filter(|s| s.source.as_deref() == Some(sub.url.as_str()))
+                        .count() as u32,
+                    error: self.errors.get(&sub.url).cloned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub struct ServerRegistryPlugin;
+
+impl Plugin for ServerRegistryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ServerRegistry>()
+            .add_systems(OnEnter(AppState::MainMenu), send_subscriptions)
+            .add_systems(
+                Update,
+                (
+                    handle_subscription_input,
+                    refresh_subscriptions,
+                    collect_manifests,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::MainMenu)),
+            );
+    }
+}
+
+fn send_subscriptions(
+    settings: Res<Settings>,
+    registry: Res<ServerRegistry>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    outbound.write(UiOutbound(registry.to_message(&settings)));
+}
+
+fn handle_subscription_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut outbound: MessageWriter<UiOutbound>,
+    storage: Res<StorageConfig>,
+    mut settings: ResMut<Settings>,
+    mut registry: ResMut<ServerRegistry>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::ServerSubscriptionAdd { url } => {
+                let url = url.trim();
+                if !(url.starts_with("https://") || url.starts_with("http://")) {
+                    warn!("Ignoring server list subscription to {}", url);
+                    continue;
+                }
+                if !settings.server_subscriptions.iter().any(|s| s.url == url) {
+                    settings.server_subscriptions.push(ServerSubscription {
+                        url: url.to_owned(),
+                        name: url.to_owned(),
+                    });
+                }
+                registry.fetch(url, &storage);
+                outbound.write(UiOutbound(registry.to_message(&settings)));
+            }
+            UiToCore::ServerSubscriptionRemove { url } => {
+                settings.server_subscriptions.retain(|s| s.url != *url);
+                settings
+                    .servers
+                    .retain(|s| s.source.as_deref() != Some(url.as_str()));
+                registry.tasks.retain(|(u, _)| u != url);
+                registry.errors.remove(url);
+                if let Some(id) = settings.gameplay.current_server_id
+                    && !settings.servers.iter().any(|s| s.id == id)
+                {
+                    settings.gameplay.current_server_id = settings.servers.first().map(|s| s.id);
+                }
+                outbound.write(UiOutbound(settings.to_snapshot_message(None)));
+                outbound.write(UiOutbound(registry.to_message(&settings)));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn refresh_subscriptions(
+    time: Res<Time>,
+    storage: Res<StorageConfig>,
+    settings: Res<Settings>,
+    mut registry: ResMut<ServerRegistry>,
+) {
+    if !registry.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    for sub in &settings.server_subscriptions {
+        registry.fetch(&sub.url, &storage);
+    }
+}
+
+fn collect_manifests(
+    mut settings: ResMut<Settings>,
+    mut registry: ResMut<ServerRegistry>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    let mut finished = Vec::new();
+    registry.tasks.retain_mut(|(url, task)| {
+        let Some(result) = future::block_on(future::poll_once(task)) else {
+            return true;
+        };
+        finished.push((url.clone(), result));
+        false
+    });
+    if finished.is_empty() {
+        return;
+    }
+
+    for (url, result) in finished {
+        match result {
+            Ok(manifest) => {
+                registry.errors.remove(&url);
+                let settings = &mut *settings;
+                if let Some(sub) = settings
+                    .server_subscriptions
+                    .iter_mut()
+                    .find(|s| s.url == url)
+                    && !manifest.name.trim().is_empty()
+                {
+                    sub.name = manifest.name.trim().to_owned();
+                }
+                merge_manifest(&mut settings.servers, &url, &manifest);
+                if settings.gameplay.current_server_id.is_none() {
+                    settings.gameplay.current_server_id = settings.servers.first().map(|s| s.id);
+                }
+            }
+            Err(e) => {
+                warn!("Failed to refresh server list {}: {}", url, e);
+                registry.errors.insert(url, e);
+            }
+        }
+    }
+    outbound.write(UiOutbound(settings.to_snapshot_message(None)));
+    outbound.write(UiOutbound(registry.to_message(&settings)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "https://example.org/servers.json";
+
+    fn entry(id: u32, name: &str, source: Option<&str>) -> ServerEntry {
+        ServerEntry {
+            id,
+            name: name.to_owned(),
+            address: format!("{}.example.org:2610", name.to_lowercase()),
+            protocol: None,
+            icon: None,
+            news_url: None,
+            source: source.map(str::to_owned),
+            connection: Default::default(),
+        }
+    }
+
+    #[test]
+    fn refresh_replaces_imported_servers_and_keeps_their_ids() {
+        let mut servers = vec![
+            entry(1, "Mine", None),
+            entry(2, "Loures", Some(SOURCE)),
+            entry(3, "Gone", Some(SOURCE)),
+        ];
+        let manifest = parse_manifest(
+            r#"{"name": "Shards", "servers": [
+                {"name": "Loures", "address": "loures.example.org", "port": 2611, "protocol": "7.41"},
+                {"name": "Mileth", "address": "mileth.example.org:2612"},
+                {"name": "", "address": "nameless.example.org"}
+            ]}"#,
+        )
+        .unwrap();
+        merge_manifest(&mut servers, SOURCE, &manifest);
+
+        let summary: Vec<_> = servers
+            .iter()
+            .map(|s| (s.id, s.name.as_str(), s.address.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "Mine", "mine.example.org:2610"),
+                (2, "Loures", "loures.example.org:2611"),
+                (4, "Mileth", "mileth.example.org:2612"),
+            ]
+        );
+        assert_eq!(servers[1].protocol.as_deref(), Some("7.41"));
+        assert_eq!(servers[0].source, None);
+    }
+
+    #[test]
+    fn empty_or_malformed_manifests_are_rejected() {
+        assert!(parse_manifest(r#"{"servers": []}"#).is_err());
+        assert!(parse_manifest("<html>").is_err());
+    }
+}
@@ -0,0 +1,127 @@
+//! Lobby probes for the server list.
+//!
+//! While the player is on the login screen, each configured server's lobby is
+//! connected to in the background. A server counts as online once it sends its
+//! greeting packet, and its latency is the time the TCP connect took.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use async_std::sync::Arc;
+use bevy::prelude::*;
+use bevy::tasks::{IoTaskPool, Task};
+use futures_lite::future;
+use game_ui::{CoreToUi, ServerStatus};
+use network::packet::PacketDecoder;
+
+use crate::app_state::AppState;
+use crate::settings_types::Settings;
+use crate::webui::plugin::UiOutbound;
+
+/// How often the server list is probed again.
+const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+/// How long a lobby gets to send its greeting before it counts as offline.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Latest probe result per server id.
+#[derive(Resource, Default, Debug)]
+pub struct ServerStatuses(pub HashMap<u32, ServerStatus>);
+
+#[derive(Resource)]
+struct ServerProbes {
+    timer: Timer,
+    /// Addresses probed last round, to re-probe as soon as the list is edited.
+    addresses: Vec<(u32, String)>,
+    tasks: Vec<(u32, Task<ServerStatus>)>,
+}
+
+impl Default for ServerProbes {
+    fn default() -> Self {
+        let mut timer = Timer::new(PROBE_INTERVAL, TimerMode::Repeating);
+        timer.set_elapsed(PROBE_INTERVAL);
+        Self {
+            timer,
+            addresses: Vec::new(),
+            tasks: Vec::new(),
+        }
+    }
+}
+
+pub struct ServerStatusPlugin;
+
+impl Plugin for ServerStatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ServerStatuses>()
+            .init_resource::<ServerProbes>()
+            .add_systems(
+                Update,
+                (start_server_probes, collect_server_probes)
+                    .chain()
+                    .run_if(in_state(AppState::MainMenu)),
+            );
+    }
+}
+
+fn start_server_probes(time: Res<Time>, settings: Res<Settings>, mut probes: ResMut<ServerProbes>) {
+    let addresses: Vec<(u32, String)> = settings
+        .servers
+        .iter()
+        .map(|s| (s.id, s.address.clone()))
+        .collect();
+    let list_changed = addresses != probes.addresses;
+    if !probes.timer.tick(time.delta()).just_finished() && !list_changed {
+        return;
+    }
+    if !probes.tasks.is_empty() && !list_changed {
+        return;
+    }
+
+    probes.tasks = addresses
+        .iter()
+        .map(|(id, address)| {
+            let address = address.clone();
+            (*id, IoTaskPool::get().spawn(probe_server(address)))
+        })
+        .collect();
+    probes.addresses = addresses;
+}
+
+fn collect_server_probes(
+    mut probes: ResMut<ServerProbes>,
+    mut statuses: ResMut<ServerStatuses>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    probes.tasks.retain_mut(|(server_id, task)| {
+        let Some(status) = future::block_on(future::poll_once(task)) else {
+            return true;
+        };
+        statuses.0.insert(*server_id, status);
+        outbound.write(UiOutbound(CoreToUi::ServerStatus {
+            server_id: *server_id,
+            status,
+        }));
+        false
+    });
+}
+
+async fn probe_server(address: String) -> ServerStatus {
+    let probe = async_std::io::timeout(PROBE_TIMEOUT, async {
+        let (host, port) = network::connect::resolve_server(&address).await;
+        let started = Instant::now();
+        let stream = network::connect::connect(&host, port).await?;
+        let latency = started.elapsed();
+
+        let packet = PacketDecoder::new(Arc::new(stream)).read().await?;
+        if packet.first() != Some(&0x7E) {
+            return Err(std::io::Error::other("unexpected lobby greeting"));
+        }
+        Ok(latency)
+    });
+
+    match probe.await {
+        Ok(latency) => ServerStatus::Online {
+            latency_ms: latency.as_millis() as u32,
+        },
+        Err(_) => ServerStatus::Offline,
+    }
+}
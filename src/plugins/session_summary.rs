@@ -0,0 +1,286 @@
+//! Summary of a play session, shown on the login screen after logging out.
+//!
+//! Time played, experience and gold gained, kills, deaths and whispers are
+//! tallied while in game and added to the character's lifetime totals, which
+//! are saved with the rest of their profile. The server never says who killed
+//! what, so a kill is a creature the player hit in the last `KILL_WINDOW`
+//! disappearing; one that walks off screen right after a hit is counted too.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_ui::{CoreToUi, SessionSummaryRowUi};
+use packets::server::{Animation, ServerMessageType};
+
+use crate::CurrentSession;
+use crate::app_state::AppState;
+use crate::ecs::components::{Direction, EntityId, LocalPlayer, NPC, Position};
+use crate::events::{ChatEvent, EntityEvent};
+use crate::resources::PlayerAttributes;
+use crate::settings_types::{SessionTotals, Settings};
+use crate::webui::plugin::UiOutbound;
+
+const KILL_WINDOW: Duration = Duration::from_secs(3);
+
+#[derive(Resource, Default, Debug)]
+pub struct SessionTracker {
+    started: Option<Duration>,
+    /// Server id and name of the character being played.
+    character: Option<(u32, String)>,
+    /// First and latest experience and gold reported this session.
+    exp_gold: Option<((u32, u32), (u32, u32))>,
+    kills: u32,
+    deaths: u32,
+    whispers: u32,
+    /// When the player last hit each creature id.
+    recent_hits: HashMap<u32, Duration>,
+    alive: bool,
+}
+
+impl SessionTracker {
+    fn hit(&mut self, id: u32, now: Duration) {
+        self.recent_hits.insert(id, now);
+    }
+
+    fn creature_removed(&mut self, id: u32, now: Duration) {
+        if self
+            .recent_hits
+            .remove(&id)
+            .is_some_and(|at| now.saturating_sub(at) <= KILL_WINDOW)
+        {
+            self.kills += 1;
+        }
+    }
+
+    fn forget_old_hits(&mut self, now: Duration) {
+        self.recent_hits
+            .retain(|_, at| now.saturating_sub(*at) <= KILL_WINDOW);
+    }
+
+    fn health(&mut self, current_hp: u32) {
+        if self.alive && current_hp == 0 {
+            self.deaths += 1;
+        }
+        self.alive = current_hp > 0;
+    }
+
+    fn exp_gold(&mut self, latest: (u32, u32)) {
+        let first = self.exp_gold.map_or(latest, |(first, _)| first);
+        self.exp_gold = Some((first, latest));
+    }
+
+    fn totals(&self, now: Duration) -> SessionTotals {
+        let (experience, gold) = self
+            .exp_gold
+            .map(|(first, last)| {
+                (
+                    i64::from(last.0) - i64::from(first.0),
+                    i64::from(last.1) - i64::from(first.1),
+                )
+            })
+            .unwrap_or_default();
+        SessionTotals {
+            sessions: 1,
+            seconds_played: self
+                .started
+                .map_or(0, |started| now.saturating_sub(started).as_secs()),
+            experience,
+            gold,
+            kills: self.kills,
+            deaths: self.deaths,
+            whispers: self.whispers,
+        }
+    }
+}
+
+pub struct SessionSummaryPlugin;
+
+impl Plugin for SessionSummaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionTracker>()
+            .add_systems(OnEnter(AppState::InGame), start_session)
+            .add_systems(Update, track_session.run_if(in_state(AppState::InGame)))
+            .add_systems(OnExit(AppState::InGame), finish_session);
+    }
+}
+
+fn start_session(time: Res<Time>, mut tracker: ResMut<SessionTracker>) {
+    *tracker = SessionTracker {
+        started: Some(time.elapsed()),
+        ..default()
+    };
+}
+
+fn track_session(
+    time: Res<Time>,
+    session: Option<Res<CurrentSession>>,
+    attributes: Res<PlayerAttributes>,
+    mut chat_events: MessageReader<ChatEvent>,
+    mut entity_events: MessageReader<EntityEvent>,
+    player: Query<(&EntityId, &Position, &Direction), With<LocalPlayer>>,
+    creatures: Query<(&EntityId, &Position), With<NPC>>,
+    mut tracker: ResMut<SessionTracker>,
+) {
+    let now = time.elapsed();
+    if tracker.character.is_none()
+        && let Some(session) = session
+    {
+        tracker.character = Some((session.server_id, session.username.clone()));
+    }
+
+    for event in chat_events.read() {
+        if let ChatEvent::ServerMessage(pkt) = event
+            && pkt.message_type == ServerMessageType::Whisper
+        {
+            tracker.whispers += 1;
+        }
+    }
+
+    if attributes.is_changed() && attributes.max_hp > 0 {
+        tracker.health(attributes.current_hp);
+        if let Some(exp_gold) = attributes.exp_gold {
+            tracker.exp_gold(exp_gold);
+        }
+    }
+
+    let player = player.single().ok();
+    for event in entity_events.read() {
+        match event {
+            EntityEvent::Effect(Animation::Source {
+                target_id,
+                source_id,
+                ..
+            }) if player.is_some_and(|(id, ..)| id.id == *source_id) => {
+                if creatures.iter().any(|(id, _)| id.id == *target_id) {
+                    tracker.hit(*target_id, now);
+                }
+            }
+            // Melee hits carry no source, so take a creature losing health
+            // in front of the player as hit by them
+            EntityEvent::HealthBar(bar) => {
+                let Some((_, player_pos, direction)) = player else {
+                    continue;
+                };
+                let facing = player_pos.to_vec2() + direction.vec2_delta();
+                if creatures.iter().any(|(id, pos)| {
+                    id.id == bar.source_id && pos.to_vec2().distance_squared(facing) < 0.25
+                }) {
+                    tracker.hit(bar.source_id, now);
+                }
+            }
+            EntityEvent::Remove(remove) => {
+                tracker.creature_removed(remove.source_id, now);
+            }
+            _ => {}
+        }
+    }
+    tracker.forget_old_hits(now);
+}
+
+fn format_duration(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+fn summary_rows(session: &SessionTotals, lifetime: &SessionTotals) -> Vec<SessionSummaryRowUi> {
+    let row = |label: &str, session: String, lifetime: String| SessionSummaryRowUi {
+        label: label.to_owned(),
+        session,
+        lifetime,
+    };
+    vec![
+        row(
+            "Time played",
+            format_duration(session.seconds_played),
+            format_duration(lifetime.seconds_played),
+        ),
+        row(
+            "Experience",
+            format!("{:+}", session.experience),
+            lifetime.experience.to_string(),
+        ),
+        row(
+            "Gold",
+            format!("{:+}", session.gold),
+            lifetime.gold.to_string(),
+        ),
+        row(
+            "Kills",
+            session.kills.to_string(),
+            lifetime.kills.to_string(),
+        ),
+        row(
+            "Deaths",
+            session.deaths.to_string(),
+            lifetime.deaths.to_string(),
+        ),
+        row(
+            "Whispers received",
+            session.whispers.to_string(),
+            lifetime.whispers.to_string(),
+        ),
+    ]
+}
+
+fn finish_session(
+    time: Res<Time>,
+    mut settings: ResMut<Settings>,
+    mut tracker: ResMut<SessionTracker>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    let tracker = std::mem::take(&mut *tracker);
+    let Some((server_id, username)) = tracker.character.as_ref() else {
+        return;
+    };
+    let session = tracker.totals(time.elapsed());
+    let lifetime = settings.add_session_stats(*server_id, username, &session);
+    outbound.write(UiOutbound(CoreToUi::SessionSummary {
+        character: username.clone(),
+        rows: summary_rows(&session, &lifetime),
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tallies_kills_deaths_and_gains() {
+        let mut tracker = SessionTracker {
+            started: Some(Duration::from_secs(10)),
+            ..default()
+        };
+        let now = Duration::from_secs(100);
+
+        tracker.hit(1, now);
+        tracker.hit(2, now);
+        tracker.creature_removed(1, now + KILL_WINDOW);
+        tracker.creature_removed(2, now + KILL_WINDOW * 2);
+        tracker.creature_removed(3, now);
+
+        tracker.health(100);
+        tracker.health(0);
+        tracker.health(0);
+        tracker.health(50);
+
+        tracker.exp_gold((1000, 50));
+        tracker.exp_gold((1500, 20));
+
+        let totals = tracker.totals(Duration::from_secs(130));
+        assert_eq!(totals.seconds_played, 120);
+        assert_eq!((totals.kills, totals.deaths), (1, 1));
+        assert_eq!((totals.experience, totals.gold), (500, -30));
+    }
+
+    #[test]
+    fn durations_read_as_hours_and_minutes() {
+        assert_eq!(format_duration(59), "0m");
+        assert_eq!(format_duration(45 * 60), "45m");
+        assert_eq!(format_duration(2 * 3600 + 5 * 60), "2h 05m");
+    }
+}
@@ -0,0 +1,431 @@
+//! Countdown timers for spell durations.
+//!
+//! Buffs and debuffs aren't tracked yet, so players can time their spells by
+//! hand: right-clicking a spell in the spells panel offers to start a timer
+//! with the duration from the spell's metafile description, or one the player
+//! entered. Entered durations and the expiry sound are kept in
+//! `spell_timers.json` inside the server's data directory. Running timers show
+//! as countdown chips in the HUD and play the expiry sound when they run out.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use game_ui::{CoreToUi, UiToCore, WorldContextMenuEntryUi};
+
+use crate::app_state::AppState;
+use crate::events::{AudioEvent, SoundOrigin, WorldContextAction, WorldContextMenuEntry};
+use crate::metafile_store::MetafileStore;
+use crate::resources::StorageConfig;
+use crate::settings_types::SoundCategory;
+use crate::webui::plugin::{
+    AbilityState, ActiveWorldContextMenu, UiInbound, UiOutbound, spell_base_name,
+};
+
+/// Sound played when a timer runs out, unless `spell_timers.json` names another.
+const DEFAULT_EXPIRY_SOUND: u8 = 1;
+/// Longest duration a timer accepts.
+const MAX_TIMER_SECS: u32 = 2 * 60 * 60;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(default)]
+struct SpellTimerFile {
+    expiry_sound: u8,
+    /// Durations entered by the player in seconds, by spell name.
+    durations: BTreeMap<String, u32>,
+}
+
+impl Default for SpellTimerFile {
+    fn default() -> Self {
+        Self {
+            expiry_sound: DEFAULT_EXPIRY_SOUND,
+            durations: BTreeMap::new(),
+        }
+    }
+}
+
+/// Player-entered spell durations and the expiry sound for the current server.
+#[derive(Resource, Default, Debug)]
+pub struct SpellTimerConfig {
+    path: Option<PathBuf>,
+    file: SpellTimerFile,
+}
+
+impl SpellTimerConfig {
+    pub fn load(path: PathBuf) -> Self {
+        let file = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring malformed {}: {}", path.display(), e);
+                SpellTimerFile::default()
+            }),
+            Err(_) => SpellTimerFile::default(),
+        };
+
+        Self {
+            path: Some(path),
+            file,
+        }
+    }
+
+    pub fn duration(&self, spell: &str) -> Option<u32> {
+        self.file.durations.get(spell).copied()
+    }
+
+    pub fn expiry_sound(&self) -> u8 {
+        self.file.expiry_sound
+    }
+
+    /// Remembers the duration entered for a spell and saves the file.
+    pub fn set_duration(&mut self, spell: &str, secs: u32) {
+        self.file.durations.insert(spell.to_owned(), secs);
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        match serde_json::to_string_pretty(&self.file) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    error!("Failed to save {}: {}", path.display(), e);
+                }
+            }
+            Err(e) => error!("Failed to serialize spell timers: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SpellTimer {
+    pub id: u32,
+    pub name: String,
+    pub sprite: u16,
+    pub total: Duration,
+    pub remaining: Duration,
+}
+
+impl SpellTimer {
+    /// Whole seconds left, rounded up so a timer never shows 0:00 while running.
+    pub fn remaining_secs(&self) -> u32 {
+        self.remaining.as_secs_f32().ceil() as u32
+    }
+}
+
+/// Running spell timers, in the order they were started.
+#[derive(Resource, Default, Debug)]
+pub struct SpellTimers {
+    timers: Vec<SpellTimer>,
+    next_id: u32,
+}
+
+impl SpellTimers {
+    pub fn iter(&self) -> impl Iterator<Item = &SpellTimer> {
+        self.timers.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    pub fn is_running(&self, spell: &str) -> bool {
+        self.timers.iter().any(|timer| timer.name == spell)
+    }
+
+    /// Starts a timer for a spell, restarting it if one is already running.
+    pub fn start(&mut self, spell: &str, sprite: u16, secs: u32) {
+        self.stop(spell);
+        let total = Duration::from_secs(secs.into());
+        self.next_id = self.next_id.wrapping_add(1);
+        self.timers.push(SpellTimer {
+            id: self.next_id,
+            name: spell.to_owned(),
+            sprite,
+            total,
+            remaining: total,
+        });
+    }
+
+    pub fn stop(&mut self, spell: &str) {
+        self.timers.retain(|timer| timer.name != spell);
+    }
+
+    pub fn cancel(&mut self, id: u32) {
+        self.timers.retain(|timer| timer.id != id);
+    }
+
+    /// Advances every timer and removes the ones that ran out.
+    fn tick(&mut self, delta: Duration) -> Vec<SpellTimer> {
+        let mut expired = Vec::new();
+        self.timers.retain_mut(|timer| {
+            timer.remaining = timer.remaining.saturating_sub(delta);
+            if timer.remaining.is_zero() {
+                expired.push(timer.clone());
+                false
+            } else {
+                true
+            }
+        });
+        expired
+    }
+}
+
+/// Spell waiting for a duration in the UI, if any.
+#[derive(Resource, Default, Debug)]
+pub struct PendingSpellTimer(pub Option<PendingSpell>);
+
+#[derive(Debug, Clone)]
+pub struct PendingSpell {
+    pub name: String,
+    pub sprite: u16,
+}
+
+pub struct SpellTimersPlugin;
+
+impl Plugin for SpellTimersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpellTimerConfig>()
+            .init_resource::<SpellTimers>()
+            .init_resource::<PendingSpellTimer>()
+            .add_systems(OnEnter(AppState::InGame), load_spell_timer_config)
+            .add_systems(OnExit(AppState::InGame), clear_spell_timers)
+            .add_systems(
+                Update,
+                (handle_spell_timer_input, tick_spell_timers)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn load_spell_timer_config(
+    mut commands: Commands,
+    storage: Res<StorageConfig>,
+    session: Option<Res<crate::CurrentSession>>,
+) {
+    let Some(session) = session else {
+        return;
+    };
+    commands.insert_resource(SpellTimerConfig::load(
+        storage.server_spell_timers_path(session.server_id),
+    ));
+}
+
+fn clear_spell_timers(mut commands: Commands) {
+    commands.insert_resource(SpellTimerConfig::default());
+    commands.insert_resource(SpellTimers::default());
+    commands.insert_resource(PendingSpellTimer::default());
+}
+
+fn handle_spell_timer_input(
+    mut inbound: MessageReader<UiInbound>,
+    mut outbound: MessageWriter<UiOutbound>,
+    ability_state: Res<AbilityState>,
+    metafiles: Res<MetafileStore>,
+    mut config: ResMut<SpellTimerConfig>,
+    mut timers: ResMut<SpellTimers>,
+    mut pending: ResMut<PendingSpellTimer>,
+    mut world_context_menu: ResMut<ActiveWorldContextMenu>,
+) {
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::SpellContextMenu { slot, x, y } => {
+                let Some(spell) = ability_state.spells.iter().find(|s| s.slot == *slot) else {
+                    continue;
+                };
+                let name = spell_base_name(&spell.panel_name);
+                let duration = config
+                    .duration(name)
+                    .or_else(|| metafiles.spell_duration_secs(name));
+                let entries =
+                    spell_timer_entries(name, spell.sprite, duration, timers.is_running(name));
+
+                world_context_menu.title = name.to_owned();
+                world_context_menu.entries = entries.clone();
+                outbound.write(UiOutbound(CoreToUi::ShowWorldContextMenu {
+                    title: name.to_owned(),
+                    x: *x,
+                    y: *y,
+                    anchor_width: 1.0,
+                    anchor_height: 1.0,
+                    entries: entries
+                        .into_iter()
+                        .map(|entry| WorldContextMenuEntryUi {
+                            id: entry.id,
+                            text: entry.text,
+                        })
+                        .collect(),
+                }));
+            }
+            UiToCore::SpellTimerSubmit { duration } => {
+                let Some(spell) = pending.0.clone() else {
+                    continue;
+                };
+                let Some(secs) = parse_duration_input(duration) else {
+                    outbound.write(UiOutbound(CoreToUi::Toast {
+                        text: "Enter a duration like 90, 1:30 or 2m".to_owned(),
+                    }));
+                    continue;
+                };
+                config.set_duration(&spell.name, secs);
+                timers.start(&spell.name, spell.sprite, secs);
+                pending.0 = None;
+            }
+            UiToCore::SpellTimerPromptCancel => {
+                pending.0 = None;
+            }
+            UiToCore::SpellTimerCancel { id } => {
+                timers.cancel(*id);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn spell_timer_entries(
+    name: &str,
+    sprite: u16,
+    duration: Option<u32>,
+    running: bool,
+) -> Vec<WorldContextMenuEntry> {
+    let mut actions = Vec::new();
+    let prompt = WorldContextAction::PromptSpellTimer {
+        name: name.to_owned(),
+        sprite,
+    };
+    match duration {
+        Some(secs) => {
+            let verb = if running { "Restart" } else { "Start" };
+            actions.push((
+                format!("{} duration timer ({})", verb, format_countdown(secs)),
+                WorldContextAction::StartSpellTimer {
+                    name: name.to_owned(),
+                    sprite,
+                    secs,
+                },
+            ));
+            actions.push(("Set timer duration...".to_owned(), prompt));
+        }
+        None => actions.push(("Start duration timer...".to_owned(), prompt)),
+    }
+    if running {
+        actions.push((
+            "Stop timer".to_owned(),
+            WorldContextAction::StopSpellTimer {
+                name: name.to_owned(),
+            },
+        ));
+    }
+
+    actions
+        .into_iter()
+        .enumerate()
+        .map(|(id, (text, action))| WorldContextMenuEntry {
+            id: id as i32,
+            text,
+            action,
+        })
+        .collect()
+}
+
+fn tick_spell_timers(
+    time: Res<Time>,
+    config: Res<SpellTimerConfig>,
+    mut timers: ResMut<SpellTimers>,
+    mut audio_events: MessageWriter<AudioEvent>,
+) {
+    // Leave the resource untouched while idle so the HUD isn't resynced.
+    if timers.is_empty() {
+        return;
+    }
+    if !timers.tick(time.delta()).is_empty() {
+        audio_events.write(AudioEvent::PlayEffect {
+            sound: config.expiry_sound(),
+            category: SoundCategory::Spells,
+            origin: SoundOrigin::Local,
+        });
+    }
+}
+
+/// Reads a duration typed by the player: plain seconds ("90"), minutes and
+/// seconds ("1:30"), or units ("2m", "1m30s", "1h").
+fn parse_duration_input(text: &str) -> Option<u32> {
+    let text = text.trim().to_ascii_lowercase();
+    let secs = if let Some((minutes, seconds)) = text.split_once(':') {
+        let minutes: u32 = minutes.trim().parse().ok()?;
+        let seconds: u32 = seconds.trim().parse().ok()?;
+        if seconds >= 60 {
+            return None;
+        }
+        minutes.checked_mul(60)?.checked_add(seconds)?
+    } else if let Ok(seconds) = text.parse() {
+        seconds
+    } else {
+        let mut total = 0u32;
+        let mut number = String::new();
+        for c in text.chars().filter(|c| !c.is_whitespace()) {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            let unit = match c {
+                'h' => 3600,
+                'm' => 60,
+                's' => 1,
+                _ => return None,
+            };
+            let count: u32 = number.parse().ok()?;
+            total = total.checked_add(count.checked_mul(unit)?)?;
+            number.clear();
+        }
+        if !number.is_empty() {
+            return None;
+        }
+        total
+    };
+    (1..=MAX_TIMER_SECS).contains(&secs).then_some(secs)
+}
+
+/// Formats seconds as "1:05", or "1:02:05" from an hour up.
+pub fn format_countdown(secs: u32) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restarting_a_spell_replaces_its_timer() {
+        let mut timers = SpellTimers::default();
+        timers.start("armachd", 12, 30);
+        timers.start("beag ioc", 3, 5);
+        timers.start("armachd", 12, 30);
+        assert_eq!(timers.iter().count(), 2);
+
+        let expired = timers.tick(Duration::from_secs(5));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].name, "beag ioc");
+        let armachd = timers.iter().next().unwrap();
+        assert_eq!(armachd.remaining_secs(), 25);
+    }
+
+    #[test]
+    fn duration_input_forms() {
+        assert_eq!(parse_duration_input("90"), Some(90));
+        assert_eq!(parse_duration_input(" 1:30 "), Some(90));
+        assert_eq!(parse_duration_input("2m"), Some(120));
+        assert_eq!(parse_duration_input("1m 30s"), Some(90));
+        assert_eq!(parse_duration_input("0"), None);
+        assert_eq!(parse_duration_input("1:75"), None);
+        assert_eq!(parse_duration_input("soon"), None);
+        assert_eq!(format_countdown(65), "1:05");
+        assert_eq!(format_countdown(3725), "1:02:05");
+    }
+}
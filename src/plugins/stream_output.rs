@@ -0,0 +1,309 @@
+//! Optional local WebSocket feed of game state for OBS browser-source overlays.
+//!
+//! While `stream_output.enabled` is set, a background thread listens on
+//! `127.0.0.1:<port>` and pushes JSON text frames to every connected client:
+//! a `state` message (HP/MP, map name, kill counter) whenever one of those
+//! changes, and a `chat` message per chat line. New clients receive the latest
+//! state right after the handshake. The feed is push-only; frames sent by
+//! clients are ignored.
+
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use serde::Serialize;
+use tungstenite::{Message, WebSocket};
+
+use crate::app_state::AppState;
+use crate::ecs::components::{CreatureSprite, EntityId, GameMap, HealthBar};
+use crate::ecs::systems::GameSet;
+use crate::events::{ChatEvent, EntityEvent};
+use crate::resources::PlayerAttributes;
+use crate::settings_types::Settings;
+
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+struct FeedState {
+    hp: u32,
+    max_hp: u32,
+    mp: u32,
+    max_mp: u32,
+    map: String,
+    kills: u32,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FeedMessage<'a> {
+    State(&'a FeedState),
+    Chat {
+        channel: &'static str,
+        text: &'a str,
+    },
+}
+
+enum Outgoing {
+    /// Replayed to clients that connect later.
+    State(String),
+    Event(String),
+}
+
+#[derive(Resource, Default)]
+pub struct StreamOutput {
+    feed: Option<(u16, Sender<Outgoing>)>,
+    last_state: Option<FeedState>,
+    kills: u32,
+}
+
+impl StreamOutput {
+    fn send(&self, message: &FeedMessage) {
+        let Some((_, feed)) = &self.feed else {
+            return;
+        };
+        match serde_json::to_string(message) {
+            Ok(json) => {
+                let _ = feed.send(match message {
+                    FeedMessage::State(_) => Outgoing::State(json),
+                    FeedMessage::Chat { .. } => Outgoing::Event(json),
+                });
+            }
+            Err(e) => tracing::error!("Failed to serialize stream feed message: {}", e),
+        }
+    }
+}
+
+pub struct StreamOutputPlugin;
+
+impl Plugin for StreamOutputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StreamOutput>()
+            .add_systems(Update, sync_stream_server)
+            .add_systems(
+                Update,
+                count_kills
+                    .in_set(GameSet::EventProcessing)
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (publish_state, publish_chat)
+                    .after(GameSet::RenderSync)
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(feed_running),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_stream_state);
+    }
+}
+
+fn feed_running(output: Res<StreamOutput>) -> bool {
+    output.feed.is_some()
+}
+
+/// Start, stop or move the server to follow the settings.
+fn sync_stream_server(settings: Res<Settings>, mut output: ResMut<StreamOutput>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let wanted = settings
+        .stream_output
+        .enabled
+        .then_some(settings.stream_output.port);
+    if output.feed.as_ref().map(|(port, _)| *port) == wanted {
+        return;
+    }
+
+    // Dropping the sender stops the server thread.
+    output.feed = None;
+    output.last_state = None;
+
+    let Some(port) = wanted else {
+        tracing::info!("Stream output disabled");
+        return;
+    };
+    match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+        Ok(listener) => {
+            let (tx, rx) = crossbeam_channel::unbounded();
+            let spawned = std::thread::Builder::new()
+                .name("stream-output".to_string())
+                .spawn(move || run_server(listener, rx));
+            match spawned {
+                Ok(_) => {
+                    tracing::info!("Stream output listening on ws://127.0.0.1:{}", port);
+                    output.feed = Some((port, tx));
+                }
+                Err(e) => tracing::error!("Failed to start stream output thread: {}", e),
+            }
+        }
+        Err(e) => tracing::error!("Failed to bind stream output on port {}: {}", port, e),
+    }
+}
+
+/// A creature that leaves view with an empty health bar counts as a kill.
+fn count_kills(
+    mut entity_events: MessageReader<EntityEvent>,
+    creatures: Query<(&EntityId, &HealthBar), With<CreatureSprite>>,
+    mut output: ResMut<StreamOutput>,
+) {
+    for event in entity_events.read() {
+        if let EntityEvent::Remove(remove) = event {
+            if creatures
+                .iter()
+                .any(|(id, bar)| id.id == remove.source_id && bar.percent == 0)
+            {
+                output.kills += 1;
+            }
+        }
+    }
+}
+
+fn publish_state(
+    attrs: Res<PlayerAttributes>,
+    map_query: Query<&GameMap>,
+    mut output: ResMut<StreamOutput>,
+) {
+    let state = FeedState {
+        hp: attrs.current_hp,
+        max_hp: attrs.max_hp,
+        mp: attrs.current_mp,
+        max_mp: attrs.max_mp,
+        map: map_query
+            .iter()
+            .next()
+            .map(|m| m.name.clone())
+            .unwrap_or_default(),
+        kills: output.kills,
+    };
+    if output.last_state.as_ref() == Some(&state) {
+        return;
+    }
+    output.send(&FeedMessage::State(&state));
+    output.last_state = Some(state);
+}
+
+fn publish_chat(
+    mut chat_events: MessageReader<ChatEvent>,
+    settings: Res<Settings>,
+    output: Res<StreamOutput>,
+) {
+    use packets::server::{PublicMessageType, ServerMessageType};
+
+    for event in chat_events.read() {
+        let (channel, text) = match event {
+            ChatEvent::ServerMessage(pkt) => {
+                let channel = match pkt.message_type {
+                    // Whispers stay private while streamer mode is on
+                    ServerMessageType::Whisper if settings.gameplay.streamer_mode => continue,
+                    ServerMessageType::Whisper => "whisper",
+                    ServerMessageType::GroupChat => "group",
+                    ServerMessageType::GuildChat => "guild",
+                    ServerMessageType::OrangeBar1
+                    | ServerMessageType::OrangeBar2
+                    | ServerMessageType::OrangeBar3
+                    | ServerMessageType::OrangeBar5
+                    | ServerMessageType::ActiveMessage
+                    | ServerMessageType::AdminMessage => "system",
+                    _ => continue,
+                };
+                (channel, pkt.message.as_str())
+            }
+            ChatEvent::PublicMessage(pkt) => {
+                let channel = match pkt.message_type {
+                    PublicMessageType::Normal => "say",
+                    PublicMessageType::Shout => "shout",
+                    PublicMessageType::Chant => continue,
+                };
+                (channel, pkt.message.as_str())
+            }
+            _ => continue,
+        };
+        output.send(&FeedMessage::Chat { channel, text });
+    }
+}
+
+fn reset_stream_state(mut output: ResMut<StreamOutput>) {
+    output.kills = 0;
+    output.last_state = None;
+    output.send(&FeedMessage::State(&FeedState::default()));
+}
+
+fn run_server(listener: TcpListener, feed: Receiver<Outgoing>) {
+    if let Err(e) = listener.set_nonblocking(true) {
+        tracing::error!("Stream output listener setup failed: {}", e);
+        return;
+    }
+
+    let mut clients: Vec<WebSocket<TcpStream>> = Vec::new();
+    let mut last_state: Option<String> = None;
+    loop {
+        match feed.recv_timeout(Duration::from_millis(50)) {
+            Ok(outgoing) => {
+                let json = match outgoing {
+                    Outgoing::State(json) => {
+                        last_state = Some(json.clone());
+                        json
+                    }
+                    Outgoing::Event(json) => json,
+                };
+                clients.retain_mut(|client| client.send(Message::text(json.clone())).is_ok());
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        while let Ok((stream, addr)) = listener.accept() {
+            match accept_client(stream, last_state.as_deref()) {
+                Ok(client) => {
+                    tracing::info!("Stream output client connected from {}", addr);
+                    clients.push(client);
+                }
+                Err(e) => tracing::debug!("Stream output handshake with {} failed: {}", addr, e),
+            }
+        }
+    }
+    tracing::debug!("Stream output server stopped");
+}
+
+/// Completes the WebSocket handshake and sends the latest state, if any.
+fn accept_client(stream: TcpStream, state: Option<&str>) -> anyhow::Result<WebSocket<TcpStream>> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(1)))?;
+    let mut client = tungstenite::accept(stream).map_err(|e| anyhow::anyhow!("{}", e))?;
+    if let Some(state) = state {
+        client.send(Message::text(state))?;
+    }
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn new_clients_get_the_latest_state() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            accept_client(stream, Some(r#"{"type":"state"}"#)).unwrap();
+        });
+
+        let mut client = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        client
+            .write_all(
+                b"GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+                  Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                  Sec-WebSocket-Version: 13\r\n\r\n",
+            )
+            .unwrap();
+        server.join().unwrap();
+
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).unwrap();
+        let response = String::from_utf8_lossy(&response);
+        assert!(response.contains("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+        assert!(response.ends_with(r#"{"type":"state"}"#));
+    }
+}
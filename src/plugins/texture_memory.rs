@@ -0,0 +1,163 @@
+//! GPU memory taken by the game's textures, and keeping it under the budget
+//! set in the graphics settings.
+//!
+//! Sprite atlases are allocated up front at a fixed size, so what counts
+//! against the budget is the atlas space holding sprites, plus the map
+//! textures and the frame buffers. Creatures, players and items free their
+//! sheets as soon as nothing shows them. Effect sheets and a preloaded map
+//! are kept for reuse, so those are dropped first when over budget, before an
+//! atlas runs out of space.
+
+use bevy::prelude::*;
+
+use crate::resources::{MapPreload, WindowSurface};
+use crate::settings_types::Settings;
+use crate::{
+    CreatureAssetStoreState, EffectManagerState, ItemAssetStoreState, MapRendererState,
+    PlayerAssetStoreState,
+};
+
+/// Frame buffers the renderer rotates with Slint, see `frame_exchange`.
+const BACK_BUFFERS: u64 = 3;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+/// How often to retry evicting while over budget and usage stays the same.
+const RETRY_SECS: f32 = 1.0;
+
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq)]
+pub struct TextureMemory {
+    /// Atlas space holding sprites.
+    pub atlas_used: u64,
+    /// Atlas space allocated, empty or not.
+    pub atlas_total: u64,
+    pub map: u64,
+    /// Map preloaded behind a nearby exit.
+    pub standby_map: u64,
+    pub back_buffers: u64,
+}
+
+impl TextureMemory {
+    /// Bytes counted against the budget.
+    pub fn in_use(&self) -> u64 {
+        self.atlas_used + self.map + self.standby_map + self.back_buffers
+    }
+
+    /// Bytes allocated on the GPU, including empty atlas space.
+    pub fn allocated(&self) -> u64 {
+        self.atlas_total + self.map + self.standby_map + self.back_buffers
+    }
+
+    pub fn over_budget(&self, budget_mb: u32) -> bool {
+        budget_mb > 0 && self.in_use() > u64::from(budget_mb) * BYTES_PER_MB
+    }
+}
+
+/// Formats a byte count as megabytes for display.
+pub fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / BYTES_PER_MB as f64)
+}
+
+pub struct TextureMemoryPlugin;
+
+impl Plugin for TextureMemoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TextureMemory>().add_systems(
+            Update,
+            (track_texture_memory, enforce_texture_budget).chain(),
+        );
+    }
+}
+
+fn track_texture_memory(
+    creatures: Option<Res<CreatureAssetStoreState>>,
+    players: Option<Res<PlayerAssetStoreState>>,
+    items: Option<Res<ItemAssetStoreState>>,
+    effects: Option<Res<EffectManagerState>>,
+    map: Option<Res<MapRendererState>>,
+    preload: Res<MapPreload>,
+    window_surface: Option<NonSend<WindowSurface>>,
+    mut memory: ResMut<TextureMemory>,
+) {
+    let atlases = [
+        creatures.map(|s| s.store.atlas_stats()),
+        players.map(|s| s.store.atlas_stats()),
+        items.map(|s| s.store.atlas_stats()),
+        effects.map(|s| s.effect_manager.atlas_stats()),
+    ];
+    let atlases = atlases.iter().flatten();
+
+    memory.set_if_neq(TextureMemory {
+        atlas_used: atlases.clone().map(|stats| stats.used_bytes()).sum(),
+        atlas_total: atlases.map(|stats| stats.total_bytes()).sum(),
+        map: map.map_or(0, |s| s.map_renderer.texture_bytes()),
+        standby_map: preload
+            .standby
+            .as_ref()
+            .map_or(0, |standby| standby.map_renderer.texture_bytes()),
+        back_buffers: window_surface.map_or(0, |surface| {
+            BACK_BUFFERS * u64::from(surface.output_width) * u64::from(surface.output_height) * 4
+        }),
+    });
+}
+
+fn enforce_texture_budget(
+    time: Res<Time>,
+    mut retry: Local<Timer>,
+    settings: Res<Settings>,
+    memory: Res<TextureMemory>,
+    effects: Option<ResMut<EffectManagerState>>,
+    mut preload: ResMut<MapPreload>,
+) {
+    if retry.duration().is_zero() {
+        *retry = Timer::from_seconds(RETRY_SECS, TimerMode::Repeating);
+    }
+    // Effect sheets fall out of use without changing the usage, so with
+    // nothing freed last time, look again only now and then
+    let retry_due = retry.tick(time.delta()).just_finished();
+    if !memory.is_changed() && !settings.is_changed() && !retry_due {
+        return;
+    }
+    if !memory.over_budget(settings.graphics.texture_budget_mb) {
+        return;
+    }
+
+    if let Some(mut effects) = effects {
+        let dropped = effects.effect_manager.evict_unused();
+        if dropped > 0 {
+            debug!(
+                "Dropped {} effect sheets, {} of textures in use",
+                dropped,
+                megabytes(memory.in_use())
+            );
+            return;
+        }
+    }
+
+    if preload.standby.take().is_some() {
+        info!(
+            "Dropped the preloaded map, {} of textures in use",
+            megabytes(memory.in_use())
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_counts_used_space_only() {
+        let memory = TextureMemory {
+            atlas_used: 40 * BYTES_PER_MB,
+            atlas_total: 200 * BYTES_PER_MB,
+            map: 10 * BYTES_PER_MB,
+            standby_map: 10 * BYTES_PER_MB,
+            back_buffers: 0,
+        };
+        assert_eq!(memory.in_use(), 60 * BYTES_PER_MB);
+        assert_eq!(memory.allocated(), 220 * BYTES_PER_MB);
+        assert!(memory.over_budget(50));
+        assert!(!memory.over_budget(64));
+        assert!(!memory.over_budget(0));
+        assert_eq!(megabytes(memory.in_use()), "60.0 MB");
+    }
+}
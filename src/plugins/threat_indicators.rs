@@ -0,0 +1,210 @@
+//! Arrows at the edge of the view pointing at off-screen creatures that just
+//! went after the player.
+//!
+//! A creature counts as a threat when one of its effects lands on the player,
+//! or when the player's health drops while it stands next to them facing them
+//! (melee hits carry no source). Threats are remembered for `THREAT_MEMORY` and
+//! their arrows fade out over that time.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use packets::server::{Animation, VisibleEntityType};
+
+use crate::app_state::AppState;
+use crate::ecs::components::{Direction, EntityId, LocalPlayer, NPC, Position};
+use crate::events::EntityEvent;
+use crate::resources::ZoomState;
+
+const THREAT_MEMORY: Duration = Duration::from_secs(8);
+/// Fraction of the view kept between an arrow and the screen edge.
+const EDGE_INSET: f32 = 0.06;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThreatArrow {
+    /// Position as a fraction of the view's width and height.
+    pub x: f32,
+    pub y: f32,
+    /// Clockwise from pointing right, in degrees.
+    pub angle: f32,
+    /// 1 for a fresh threat, fading to 0 as it is forgotten.
+    pub strength: f32,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct ThreatTracker {
+    /// When each creature id last threatened the player.
+    threats: HashMap<u32, Duration>,
+    player_health: Option<u8>,
+}
+
+impl ThreatTracker {
+    fn record(&mut self, id: u32, now: Duration) {
+        self.threats.insert(id, now);
+    }
+
+    fn strength(&self, id: u32, now: Duration) -> Option<f32> {
+        let age = now.saturating_sub(*self.threats.get(&id)?);
+        (age < THREAT_MEMORY).then(|| 1.0 - age.as_secs_f32() / THREAT_MEMORY.as_secs_f32())
+    }
+
+    fn forget_old(&mut self, now: Duration) {
+        self.threats
+            .retain(|_, at| now.saturating_sub(*at) < THREAT_MEMORY);
+    }
+}
+
+/// Arrows for the UI, rebuilt every frame while there are threats about.
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct ThreatArrows(pub Vec<ThreatArrow>);
+
+pub struct ThreatIndicatorsPlugin;
+
+impl Plugin for ThreatIndicatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ThreatTracker>()
+            .init_resource::<ThreatArrows>()
+            .add_systems(
+                Update,
+                (record_threats, update_threat_arrows)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), reset_threats);
+    }
+}
+
+fn reset_threats(mut tracker: ResMut<ThreatTracker>, mut arrows: ResMut<ThreatArrows>) {
+    *tracker = ThreatTracker::default();
+    arrows.set_if_neq(ThreatArrows::default());
+}
+
+fn is_hostile(npc: &NPC) -> bool {
+    matches!(
+        npc.entity_type,
+        VisibleEntityType::Normal | VisibleEntityType::WalkThrough
+    )
+}
+
+fn record_threats(
+    mut entity_events: MessageReader<EntityEvent>,
+    time: Res<Time>,
+    player: Query<(&EntityId, &Position), With<LocalPlayer>>,
+    creatures: Query<(&EntityId, &NPC, &Position, &Direction)>,
+    mut tracker: ResMut<ThreatTracker>,
+) {
+    let Ok((player_id, player_pos)) = player.single() else {
+        return;
+    };
+    let now = time.elapsed();
+
+    for event in entity_events.read() {
+        match event {
+            EntityEvent::Effect(Animation::Source {
+                target_id,
+                source_id,
+                ..
+            }) if *target_id == player_id.id && *source_id != player_id.id => {
+                if creatures
+                    .iter()
+                    .any(|(id, npc, ..)| id.id == *source_id && is_hostile(npc))
+                {
+                    tracker.record(*source_id, now);
+                }
+            }
+            EntityEvent::HealthBar(bar) if bar.source_id == player_id.id => {
+                let previous = tracker.player_health.replace(bar.health_percent);
+                if previous.is_none_or(|previous| bar.health_percent >= previous) {
+                    continue;
+                }
+                for (id, npc, pos, direction) in creatures.iter() {
+                    let facing = pos.to_vec2() + direction.vec2_delta();
+                    if is_hostile(npc) && facing.distance_squared(player_pos.to_vec2()) < 0.25 {
+                        tracker.record(id.id, now);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    tracker.forget_old(now);
+}
+
+/// Where the arrow for something `offset` render pixels from the view centre
+/// goes, or `None` while it is on screen.
+pub fn edge_arrow(offset: Vec2, view: Vec2) -> Option<(Vec2, f32)> {
+    let half = view / 2.0;
+    if offset.x.abs() <= half.x && offset.y.abs() <= half.y {
+        return None;
+    }
+    // Scale the offset back until it sits on the inset border of the view
+    let limit = half * (1.0 - 2.0 * EDGE_INSET);
+    let scale = (limit.x / offset.x.abs()).min(limit.y / offset.y.abs());
+    let position = (offset * scale + half) / view;
+    Some((position, offset.y.atan2(offset.x).to_degrees()))
+}
+
+fn update_threat_arrows(
+    time: Res<Time>,
+    camera: Res<crate::Camera>,
+    zoom_state: Res<ZoomState>,
+    tracker: Res<ThreatTracker>,
+    creatures: Query<(&EntityId, &Position), With<NPC>>,
+    mut arrows: ResMut<ThreatArrows>,
+) {
+    let now = time.elapsed();
+    let view = Vec2::new(
+        zoom_state.render_size.0 as f32,
+        zoom_state.render_size.1 as f32,
+    );
+    let cam_pos = camera.camera.position();
+    let zoom = camera.camera.zoom();
+
+    let next: Vec<ThreatArrow> = creatures
+        .iter()
+        .filter_map(|(id, pos)| {
+            let strength = tracker.strength(id.id, now)?;
+            let world = rendering::scene::get_isometric_coordinate(pos.x, pos.y);
+            let (position, angle) = edge_arrow((world - cam_pos) * zoom, view)?;
+            Some(ThreatArrow {
+                x: position.x,
+                y: position.y,
+                angle,
+                strength,
+            })
+        })
+        .collect();
+    arrows.set_if_neq(ThreatArrows(next));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrows_only_show_for_creatures_off_screen() {
+        let view = Vec2::new(800.0, 600.0);
+        assert_eq!(edge_arrow(Vec2::new(100.0, -100.0), view), None);
+
+        let (position, angle) = edge_arrow(Vec2::new(1600.0, 0.0), view).unwrap();
+        assert!((position.x - (1.0 - EDGE_INSET)).abs() < 1e-5);
+        assert!((position.y - 0.5).abs() < 1e-5);
+        assert!(angle.abs() < 1e-5);
+
+        let (position, angle) = edge_arrow(Vec2::new(0.0, -900.0), view).unwrap();
+        assert!((position.y - EDGE_INSET).abs() < 1e-5);
+        assert!((angle + 90.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn threats_fade_and_are_forgotten() {
+        let mut tracker = ThreatTracker::default();
+        tracker.record(7, Duration::ZERO);
+
+        assert_eq!(tracker.strength(7, Duration::ZERO), Some(1.0));
+        assert_eq!(tracker.strength(7, THREAT_MEMORY / 2), Some(0.5));
+        tracker.forget_old(THREAT_MEMORY);
+        assert_eq!(tracker.strength(7, THREAT_MEMORY), None);
+    }
+}
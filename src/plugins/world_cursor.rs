@@ -0,0 +1,88 @@
+//! Game cursor sprites over the world: normal, attack, talk and item, picked from
+//! whatever is under the pointer. The UI draws the sprite above everything else
+//! and hides the OS cursor while the pointer is over the world.
+
+use bevy::prelude::*;
+use packets::server::VisibleEntityType;
+
+use crate::app_state::AppState;
+use crate::ecs::components::{ItemSprite, NPC};
+use crate::ecs::interaction::HoveredEntity;
+use crate::plugins::mouse_interaction::MouseInteractionSet;
+
+/// Cursor for the hovered target. The discriminant is the frame in the cursor sheet.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldCursor {
+    #[default]
+    Normal = 0,
+    Attack = 1,
+    Talk = 2,
+    Item = 3,
+}
+
+impl WorldCursor {
+    fn for_target(npc: Option<&NPC>, item: bool) -> Self {
+        match npc {
+            Some(npc) if npc.entity_type == VisibleEntityType::Merchant => WorldCursor::Talk,
+            Some(_) => WorldCursor::Attack,
+            None if item => WorldCursor::Item,
+            None => WorldCursor::Normal,
+        }
+    }
+}
+
+pub struct WorldCursorPlugin;
+
+impl Plugin for WorldCursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldCursor>()
+            .add_systems(OnExit(AppState::InGame), reset_world_cursor)
+            .add_systems(
+                Update,
+                update_world_cursor
+                    .after(MouseInteractionSet)
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+fn update_world_cursor(
+    hovered: Res<HoveredEntity>,
+    targets: Query<(Option<&NPC>, Has<ItemSprite>)>,
+    mut cursor: ResMut<WorldCursor>,
+) {
+    let next = hovered
+        .0
+        .and_then(|entity| targets.get(entity).ok())
+        .map_or(WorldCursor::Normal, |(npc, item)| {
+            WorldCursor::for_target(npc, item)
+        });
+    cursor.set_if_neq(next);
+}
+
+fn reset_world_cursor(mut cursor: ResMut<WorldCursor>) {
+    cursor.set_if_neq(WorldCursor::Normal);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merchants_talk_and_monsters_attack() {
+        let npc = |entity_type| NPC {
+            name: String::new(),
+            entity_type,
+        };
+        assert_eq!(
+            WorldCursor::for_target(Some(&npc(VisibleEntityType::Merchant)), false),
+            WorldCursor::Talk
+        );
+        assert_eq!(
+            WorldCursor::for_target(Some(&npc(VisibleEntityType::Normal)), false),
+            WorldCursor::Attack
+        );
+        assert_eq!(WorldCursor::for_target(None, true), WorldCursor::Item);
+        assert_eq!(WorldCursor::for_target(None, false), WorldCursor::Normal);
+    }
+}
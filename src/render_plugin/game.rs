@@ -1,15 +1,18 @@
 use crate::app_state::AppState;
-use crate::slint_support::frame_exchange::{BackBufferPool, ControlMessage, FrameChannels};
+use crate::resources::MapTransition;
+use crate::slint_support::frame_exchange::{
+    BackBufferPool, ControlMessage, FrameChannels, PipBufferPool,
+};
 use crate::{
-    Camera, CreatureAssetStoreState, CreatureBatchState, EffectManagerState, ItemAssetStoreState,
-    ItemBatchState, MapRendererState, PlayerAssetStoreState, PlayerBatchState, RendererState,
-    WindowSurface, game_files,
+    Camera, CreatureAssetStoreState, CreatureBatchState, EffectManagerState, FrameCompositorState,
+    ItemAssetStoreState, ItemBatchState, MapRendererState, PlayerAssetStoreState, PlayerBatchState,
+    RendererState, WindowSurface, game_files,
 };
 use async_std::task::block_on;
 use bevy::prelude::*;
 use rendering::scene::{EffectManager, creatures, items, players};
 
-use crate::ecs::components::HoverName;
+use crate::ecs::components::{HoverName, HoverOutline, LocalPlayer, NPC, Player};
 use crate::ecs::interaction::HoveredEntity;
 
 pub struct GameWorldRenderPlugin;
@@ -27,7 +30,13 @@ impl Plugin for GameWorldRenderPlugin {
             )
             .add_systems(
                 PostUpdate,
-                update_hover_labels.run_if(in_state(AppState::InGame)),
+                (update_hover_labels, update_hover_outline).run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                PostUpdate,
+                sync_frame_grade
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(resource_exists::<FrameCompositorState>),
             )
             .add_systems(
                 Last,
@@ -73,6 +82,7 @@ fn init_render_managers_after_gamefiles(
     existing_items: Option<Res<ItemAssetStoreState>>,
     existing_effects: Option<Res<EffectManagerState>>,
     _existing_portrait: Option<Res<crate::resources::PlayerPortraitState>>,
+    settings: Res<crate::settings_types::Settings>,
 ) {
     let (files, renderer, camera) = match (files, renderer, camera) {
         (Some(f), Some(r), Some(c)) => (f, r, c),
@@ -187,6 +197,7 @@ fn init_render_managers_after_gamefiles(
                 &renderer.queue,
                 &files.inner().archive(),
                 &camera.camera.bind_group_layout,
+                crate::plugins::potato_mode::effect_atlas_size(&settings),
             ),
         });
     }
@@ -215,6 +226,8 @@ pub struct PendingResize {
     pub width: u32,
     pub height: u32,
     pub scale: f32,
+    pub output_width: u32,
+    pub output_height: u32,
     pub dirty: bool,
 }
 
@@ -233,6 +246,8 @@ fn apply_pending_resize(
     window_surface.width = pending.width;
     window_surface.height = pending.height;
     window_surface.scale_factor = pending.scale;
+    window_surface.output_width = pending.output_width.max(pending.width);
+    window_surface.output_height = pending.output_height.max(pending.height);
 
     let RendererState { device, scene, .. } = &mut *renderer_state;
     scene.resize_depth_texture(device, pending.width, pending.height);
@@ -243,7 +258,7 @@ fn apply_pending_resize(
         pending.scale,
     );
 
-    // Reallocate pool textures to new resolution so next frame can render immediately
+    // Reallocate pool textures to the published frame size so next frame can render immediately
     pool.0.clear();
     for label in ["Back Buffer", "Inflight Buffer", "Front Seed"] {
         let tex = renderer_state
@@ -251,8 +266,8 @@ fn apply_pending_resize(
             .create_texture(&wgpu::TextureDescriptor {
                 label: Some(label),
                 size: wgpu::Extent3d {
-                    width: pending.width,
-                    height: pending.height,
+                    width: window_surface.output_width,
+                    height: window_surface.output_height,
                     depth_or_array_layers: 1,
                 },
                 mip_level_count: 1,
@@ -282,7 +297,9 @@ fn draw_frame(
     effect_manager_state: Option<Res<EffectManagerState>>,
     channels: Res<FrameChannels>,
     mut pool: ResMut<BackBufferPool>,
+    mut pip_pool: ResMut<PipBufferPool>,
     mut pending: ResMut<PendingResize>,
+    compositor: Option<ResMut<FrameCompositorState>>,
 ) {
     if window_surface.width == 0 || window_surface.height == 0 {
         return;
@@ -295,28 +312,38 @@ fn draw_frame(
                 width,
                 height,
                 scale,
+                output_width,
+                output_height,
             } => {
                 pending.width = width;
                 pending.height = height;
                 pending.scale = scale;
+                pending.output_width = output_width;
+                pending.output_height = output_height;
                 pending.dirty = true;
                 return;
             }
             ControlMessage::ReleaseFrontBufferTexture { texture } => {
                 // Discard textures that no longer match the current surface size.
-                if texture.width() == window_surface.width
-                    && texture.height() == window_surface.height
+                if texture.width() == window_surface.output_width
+                    && texture.height() == window_surface.output_height
                 {
                     pool.0.push(texture);
                 }
             }
+            ControlMessage::ReleasePipTexture { texture } => {
+                pip_pool.0.push(texture);
+            }
         }
     }
 
     // Acquire a back buffer from the pool (provided by UI via ReleaseFrontBufferTexture)
     let back = loop {
         match pool.0.pop() {
-            Some(t) if t.width() == window_surface.width && t.height() == window_surface.height => {
+            Some(t)
+                if t.width() == window_surface.output_width
+                    && t.height() == window_surface.output_height =>
+            {
                 break t;
             }
             Some(_) => {
@@ -329,8 +356,8 @@ fn draw_frame(
                     .create_texture(&wgpu::TextureDescriptor {
                         label: Some("GameFrameFallback"),
                         size: wgpu::Extent3d {
-                            width: window_surface.width,
-                            height: window_surface.height,
+                            width: window_surface.output_width,
+                            height: window_surface.output_height,
                             depth_or_array_layers: 1,
                         },
                         mip_level_count: 1,
@@ -347,7 +374,22 @@ fn draw_frame(
             }
         }
     };
-    let view = back.create_view(&wgpu::TextureViewDescriptor::default());
+    let output_view = back.create_view(&wgpu::TextureViewDescriptor::default());
+
+    // Fractional zoom and gamma render offscreen first, then composite into the frame
+    let mut compositor = compositor
+        .filter(|state| window_surface.needs_upscale() || state.compositor.adjusts_color());
+    let view = match compositor.as_deref_mut() {
+        Some(state) => state
+            .compositor
+            .source_view(
+                &render_hardware.device,
+                window_surface.width,
+                window_surface.height,
+            )
+            .clone(),
+        None => output_view.clone(),
+    };
 
     let mut encoder = render_hardware
         .device
@@ -384,7 +426,8 @@ fn draw_frame(
         render_pass.set_pipeline(&render_hardware.scene.pipeline);
         render_pass.set_bind_group(1, &camera.camera.camera_bind_group, &[]);
         if let Some(m) = map_renderer_state {
-            m.map_renderer.render(&mut render_pass);
+            let view = camera.camera.camera.visible_bounds();
+            m.map_renderer.render(&mut render_pass, view);
         }
         if let Some(im) = &item_batch_state {
             im.batch.render(&mut render_pass);
@@ -401,6 +444,10 @@ fn draw_frame(
         }
     }
 
+    if let Some(state) = &compositor {
+        state.compositor.render(&mut encoder, &output_view);
+    }
+
     render_hardware.queue.submit([encoder.finish()]);
 
     // Publish only the newest completed frame; recycle any unpublished older one.
@@ -417,6 +464,50 @@ fn draw_frame(
     }
 }
 
+/// Pushes the brightness setting and the map transition fade to the final composite pass.
+fn sync_frame_grade(
+    mut compositor: ResMut<FrameCompositorState>,
+    renderer: Res<RendererState>,
+    settings: Res<crate::settings_types::Settings>,
+    transition: Res<MapTransition>,
+) {
+    if settings.is_changed() {
+        compositor
+            .compositor
+            .set_gamma(&renderer.queue, settings.graphics.gamma.clamp(0.5, 2.0));
+    }
+    if transition.is_changed() {
+        compositor
+            .compositor
+            .set_fade(&renderer.queue, transition.fade());
+    }
+}
+
+/// Other players and creatures, the entities that get a hover outline.
+type OutlinableFilter = (Or<(With<Player>, With<NPC>)>, Without<LocalPlayer>);
+
+/// Outlines the hovered creature or player and clears the outline once the cursor moves off it.
+fn update_hover_outline(
+    mut commands: Commands,
+    hovered_entity: Res<HoveredEntity>,
+    outlinable: Query<(), OutlinableFilter>,
+    outlined: Query<Entity, With<HoverOutline>>,
+) {
+    let target = hovered_entity
+        .0
+        .filter(|entity| outlinable.contains(*entity));
+
+    for entity in outlined.iter() {
+        if Some(entity) != target {
+            commands.entity(entity).remove::<HoverOutline>();
+        }
+    }
+
+    if let Some(entity) = target.filter(|entity| !outlined.contains(*entity)) {
+        commands.entity(entity).insert(HoverOutline);
+    }
+}
+
 /// Track which entity currently has a hover label so we can remove it when hover changes.
 #[derive(Resource, Default)]
 pub struct CurrentHoverLabel(pub Option<Entity>);
@@ -1,14 +1,20 @@
 use bevy::prelude::*;
 
 use self::game::GameWorldRenderPlugin;
+use self::pip::PipRenderPlugin;
 
 pub mod game;
+pub mod pip;
+#[cfg(feature = "shader-hot-reload")]
+pub mod shader_reload;
 // pub mod minimap;
 
 pub struct GameRenderPlugin;
 
 impl Plugin for GameRenderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(GameWorldRenderPlugin);
+        app.add_plugins((GameWorldRenderPlugin, PipRenderPlugin));
+        #[cfg(feature = "shader-hot-reload")]
+        app.add_plugins(shader_reload::ShaderReloadPlugin);
     }
 }
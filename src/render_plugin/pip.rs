@@ -0,0 +1,224 @@
+//! Picture-in-picture view of a group member.
+//!
+//! A second camera is centered on the watched member's last known position and the
+//! scene is drawn again into a small texture, which is handed to Slint through
+//! `FrameChannels::latest_pip_buffer` alongside the main frame.
+
+use bevy::prelude::*;
+use rendering::scene::CameraState;
+
+use crate::app_state::AppState;
+use crate::ecs::components::{LocalPlayer, Player, Position};
+use crate::events::MapEvent;
+use crate::slint_support::frame_exchange::{FrameChannels, PipBufferPool};
+use crate::webui::plugin::GroupState;
+use crate::{
+    CreatureBatchState, EffectManagerState, ItemBatchState, MapRendererState, PlayerBatchState,
+    RendererState,
+};
+
+/// Size of the PiP render target in pixels.
+pub const PIP_WIDTH: u32 = 320;
+pub const PIP_HEIGHT: u32 = 240;
+
+/// Group member shown in the PiP view, if any.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct PipTarget {
+    pub name: Option<String>,
+    /// Tile position where the member was last seen on the current map.
+    pub last_position: Option<Vec2>,
+}
+
+impl PipTarget {
+    pub fn watch(&mut self, name: Option<String>) {
+        self.name = name;
+        self.last_position = None;
+    }
+}
+
+/// Second camera and its depth buffer, created the first time a member is in view.
+#[derive(Resource)]
+pub struct PipCamera {
+    pub camera: CameraState,
+    pub depth_texture: rendering::texture::Texture,
+}
+
+pub struct PipRenderPlugin;
+
+impl Plugin for PipRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PipTarget>()
+            .add_systems(
+                Update,
+                track_pip_target.run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Last,
+                draw_pip_frame
+                    .run_if(in_state(AppState::InGame))
+                    .run_if(resource_exists::<RendererState>)
+                    .run_if(resource_exists::<FrameChannels>),
+            )
+            .add_systems(OnExit(AppState::InGame), clear_pip_target);
+    }
+}
+
+fn clear_pip_target(mut target: ResMut<PipTarget>) {
+    target.watch(None);
+}
+
+/// Follows the watched member while they are on screen and forgets them on map change.
+fn track_pip_target(
+    mut target: ResMut<PipTarget>,
+    group: Res<GroupState>,
+    mut map_events: MessageReader<MapEvent>,
+    players: Query<(&Player, &Position), Without<LocalPlayer>>,
+) {
+    let changed_map = map_events
+        .read()
+        .any(|e| matches!(e, MapEvent::Clear | MapEvent::SetInfo(..)));
+
+    let Some(name) = target.name.clone() else {
+        return;
+    };
+
+    // Stop watching once they leave the group
+    if !group.members.iter().any(|(m, _)| m.eq_ignore_ascii_case(&name)) {
+        target.watch(None);
+        return;
+    }
+
+    if changed_map && target.last_position.is_some() {
+        target.last_position = None;
+    }
+
+    if let Some((_, pos)) = players
+        .iter()
+        .find(|(player, _)| player.name.eq_ignore_ascii_case(&name))
+    {
+        let seen = Vec2::new(pos.x, pos.y);
+        if target.last_position != Some(seen) {
+            target.last_position = Some(seen);
+        }
+    }
+}
+
+fn draw_pip_frame(
+    mut commands: Commands,
+    target: Res<PipTarget>,
+    renderer: Res<RendererState>,
+    pip_camera: Option<ResMut<PipCamera>>,
+    map_renderer_state: Option<Res<MapRendererState>>,
+    creature_batch_state: Option<Res<CreatureBatchState>>,
+    item_batch_state: Option<Res<ItemBatchState>>,
+    player_batch_state: Option<Res<PlayerBatchState>>,
+    effect_manager_state: Option<Res<EffectManagerState>>,
+    channels: Res<FrameChannels>,
+    mut pool: ResMut<PipBufferPool>,
+) {
+    let Some(position) = target.last_position else {
+        return;
+    };
+
+    let Some(mut pip_camera) = pip_camera else {
+        commands.insert_resource(PipCamera {
+            camera: CameraState::new(
+                glam::UVec2::new(PIP_WIDTH, PIP_HEIGHT),
+                &renderer.device,
+                1.0,
+            ),
+            depth_texture: rendering::texture::Texture::create_depth_texture(
+                &renderer.device,
+                PIP_WIDTH,
+                PIP_HEIGHT,
+                "pip_depth",
+            ),
+        });
+        return;
+    };
+    pip_camera
+        .camera
+        .set_position(&renderer.queue, position.x, position.y);
+
+    let target_texture = pool.0.pop().unwrap_or_else(|| {
+        renderer.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("PipFrame"),
+            size: wgpu::Extent3d {
+                width: PIP_WIDTH,
+                height: PIP_HEIGHT,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    });
+    let view = target_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut encoder = renderer
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("PiP Render Encoder"),
+        });
+
+    {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("PiP Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &pip_camera.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+        render_pass.set_stencil_reference(0);
+        render_pass.set_pipeline(&renderer.scene.pipeline);
+        render_pass.set_bind_group(1, &pip_camera.camera.camera_bind_group, &[]);
+        if let Some(m) = map_renderer_state {
+            let view = pip_camera.camera.camera.visible_bounds();
+            m.map_renderer.render(&mut render_pass, view);
+        }
+        if let Some(im) = &item_batch_state {
+            im.batch.render(&mut render_pass);
+        }
+        if let Some(cm) = creature_batch_state {
+            cm.batch.render(&mut render_pass);
+        }
+        if let Some(pb) = &player_batch_state {
+            pb.batch.render(&mut render_pass);
+        }
+        if let Some(em) = &effect_manager_state {
+            em.effect_manager
+                .render(&mut render_pass, &pip_camera.camera.camera_bind_group);
+        }
+    }
+
+    renderer.queue.submit([encoder.finish()]);
+
+    // Keep only the newest frame for Slint; an unclaimed older one goes straight back
+    let mut latest_pip_buffer = channels
+        .latest_pip_buffer
+        .lock()
+        .expect("latest pip buffer mutex poisoned");
+    if let Some(stale_texture) = latest_pip_buffer.replace(target_texture) {
+        pool.0.push(stale_texture);
+    }
+}
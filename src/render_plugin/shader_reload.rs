@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use rendering::shaders::{self, ShaderWatcher};
+
+use crate::{EffectManagerState, FrameCompositorState, RendererState};
+
+/// How often the shader files are checked for changes.
+const POLL_INTERVAL_SECS: f32 = 0.5;
+
+/// Rebuilds the render pipelines when a shader under `rendering/src/shaders` is saved,
+/// so shader work doesn't need a restart and a fresh login.
+pub struct ShaderReloadPlugin;
+
+impl Plugin for ShaderReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            reload_changed_shaders.run_if(resource_exists::<RendererState>),
+        );
+    }
+}
+
+fn reload_changed_shaders(
+    time: Res<Time>,
+    mut since_poll: Local<f32>,
+    mut watcher: Local<ShaderWatcher>,
+    mut renderer: ResMut<RendererState>,
+    mut effects: Option<ResMut<EffectManagerState>>,
+    mut compositor: Option<ResMut<FrameCompositorState>>,
+) {
+    *since_poll += time.delta_secs();
+    if *since_poll < POLL_INTERVAL_SECS {
+        return;
+    }
+    *since_poll = 0.0;
+
+    for name in watcher.changed() {
+        let source = match shaders::load(name) {
+            Ok(source) => source,
+            Err(e) => {
+                tracing::error!("Not reloading {name}:\n{e}");
+                continue;
+            }
+        };
+
+        let RendererState { device, scene, .. } = &mut *renderer;
+        match name {
+            shaders::SCENE => scene.reload_pipeline(device, &source),
+            shaders::EFFECT => {
+                if let Some(effects) = effects.as_deref_mut() {
+                    effects.effect_manager.reload_pipeline(device, &source);
+                }
+            }
+            shaders::COMPOSITE => {
+                if let Some(compositor) = compositor.as_deref_mut() {
+                    compositor.compositor.reload_pipeline(device, &source);
+                }
+            }
+            _ => {}
+        }
+        tracing::info!("Reloaded shader {name}");
+    }
+}
@@ -9,6 +9,8 @@ pub struct PlayerAttributes {
     pub max_hp: u32,
     pub current_mp: u32,
     pub max_mp: u32,
+    /// Total experience and gold, once the server has sent them.
+    pub exp_gold: Option<(u32, u32)>,
 }
 
 #[derive(Resource, Clone, Debug)]
@@ -29,6 +31,10 @@ impl StorageConfig {
         self.root.join("settings.toml")
     }
 
+    pub fn credential_vault_path(&self) -> std::path::PathBuf {
+        self.root.join("credentials.vault")
+    }
+
     pub fn server_dir(&self, server_id: u32) -> std::path::PathBuf {
         let path = self.root.join("servers").join(server_id.to_string());
         let _ = std::fs::create_dir_all(&path);
@@ -53,6 +59,38 @@ impl StorageConfig {
         path
     }
 
+    pub fn diagnostics_dir(&self) -> std::path::PathBuf {
+        let path = self.root.join("diagnostics");
+        let _ = std::fs::create_dir_all(&path);
+        path
+    }
+
+    /// Directory for a community plugin's own files, see `ext::ClientPlugin::name`.
+    pub fn plugin_dir(&self, name: &str) -> std::path::PathBuf {
+        let path = self.root.join("plugins").join(name);
+        let _ = std::fs::create_dir_all(&path);
+        path
+    }
+
+    pub fn crash_reports_dir(&self) -> std::path::PathBuf {
+        let path = self.root.join("crash-reports");
+        let _ = std::fs::create_dir_all(&path);
+        path
+    }
+
+    pub fn combat_logs_dir(&self) -> std::path::PathBuf {
+        let path = self.root.join("combat-logs");
+        let _ = std::fs::create_dir_all(&path);
+        path
+    }
+
+    /// Cached copy of a community server list's icon, keyed by its URL.
+    pub fn server_icon_path(&self, url: &str) -> std::path::PathBuf {
+        let dir = self.root.join("server-icons");
+        let _ = std::fs::create_dir_all(&dir);
+        dir.join(format!("{:x}.png", md5::compute(url.as_bytes())))
+    }
+
     pub fn server_character_settings_path(&self, server_id: u32, username: &str) -> std::path::PathBuf {
         self.server_characters_dir(server_id).join(format!("{}.toml", username))
     }
@@ -60,6 +98,35 @@ impl StorageConfig {
     pub fn server_map_path(&self, server_id: u32, map_id: u16) -> std::path::PathBuf {
         self.server_maps_dir(server_id).join(format!("lod{:03}.map", map_id))
     }
+
+    /// Pins placed on a map, kept beside its cached map file.
+    pub fn server_map_pins_path(&self, server_id: u32, map_id: u16) -> std::path::PathBuf {
+        self.server_maps_dir(server_id)
+            .join(format!("lod{:03}.pins.json", map_id))
+    }
+
+    /// Exported pin files, and where shared ones are dropped to be imported.
+    pub fn map_pins_dir(&self) -> std::path::PathBuf {
+        let path = self.root.join("map-pins");
+        let _ = std::fs::create_dir_all(&path);
+        path
+    }
+
+    pub fn server_creature_names_path(&self, server_id: u32) -> std::path::PathBuf {
+        self.server_dir(server_id).join("creature_names.json")
+    }
+
+    pub fn server_effect_sounds_path(&self, server_id: u32) -> std::path::PathBuf {
+        self.server_dir(server_id).join("effect_sounds.json")
+    }
+
+    pub fn server_news_path(&self, server_id: u32) -> std::path::PathBuf {
+        self.server_dir(server_id).join("news.json")
+    }
+
+    pub fn server_spell_timers_path(&self, server_id: u32) -> std::path::PathBuf {
+        self.server_dir(server_id).join("spell_timers.json")
+    }
 }
 
 #[derive(Resource)]
@@ -69,11 +136,44 @@ pub struct RendererState {
     pub scene: Scene,
 }
 
+/// Walking path to the hovered tile, shown while the preview key is held.
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct PathPreview {
+    pub start: (u8, u8),
+    pub target: Option<(i32, i32)>,
+    /// Tiles from the player to the target, empty when it can't be reached.
+    pub path: Vec<(u8, u8)>,
+}
+
+impl PathPreview {
+    /// Walking steps and straight-line tile distance, e.g. "7 steps, 5 tiles".
+    pub fn label(&self) -> String {
+        let Some(target) = self.target else {
+            return String::new();
+        };
+        let tiles = (target.0 - self.start.0 as i32).abs() + (target.1 - self.start.1 as i32).abs();
+        let tiles = format!("{} {}", tiles, if tiles == 1 { "tile" } else { "tiles" });
+        match self.path.len() {
+            0 => format!("No path, {}", tiles),
+            len => {
+                let steps = len - 1;
+                let unit = if steps == 1 { "step" } else { "steps" };
+                format!("{} {}, {}", steps, unit, tiles)
+            }
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct Camera {
     pub camera: CameraState,
 }
 
+#[derive(Resource)]
+pub struct FrameCompositorState {
+    pub compositor: rendering::FrameCompositor,
+}
+
 #[derive(Resource)]
 pub struct MapRendererState {
     pub map_renderer: MapRenderer,
@@ -125,6 +225,93 @@ impl ItemTileCounters {
     }
 }
 
+/// Destination of each exit tile, keyed by the map the exit leaves.
+type MapWarps = std::collections::HashMap<u16, Vec<((u8, u8), packets::server::MapInfo)>>;
+
+/// Map exits learned by walking through them, and the map behind the nearest one
+/// prepared ahead of time so entering it doesn't stall on decoding.
+#[derive(Resource, Default)]
+pub struct MapPreload {
+    pub warps: MapWarps,
+    /// Map and tile the local player was last seen on.
+    pub last_seen: Option<(u16, (u8, u8))>,
+    /// Where the player stood when the current map change began.
+    pub departed: Option<(u16, (u8, u8))>,
+    /// Map id and checksum being prepared or held in `standby`.
+    pub target: Option<(u16, u16)>,
+    pub standby: Option<StandbyMap>,
+}
+
+pub struct StandbyMap {
+    pub map_id: u16,
+    pub check_sum: u16,
+    pub xray: bool,
+    pub map_renderer: MapRenderer,
+    pub collision: crate::ecs::collision::MapCollisionData,
+}
+
+impl MapPreload {
+    pub fn learn_warp(
+        &mut self,
+        from_map: u16,
+        tile: (u8, u8),
+        destination: packets::server::MapInfo,
+    ) {
+        let warps = self.warps.entry(from_map).or_default();
+        match warps.iter_mut().find(|(t, _)| *t == tile) {
+            Some(warp) => warp.1 = destination,
+            None => warps.push((tile, destination)),
+        }
+    }
+
+    /// Hands over the standby map if it is the one being entered; any other standby is dropped.
+    pub fn take_standby(
+        &mut self,
+        map_info: &packets::server::MapInfo,
+        xray: bool,
+    ) -> Option<StandbyMap> {
+        self.target = None;
+        self.standby.take().filter(|standby| {
+            standby.map_id == map_info.map_id
+                && standby.check_sum == map_info.check_sum
+                && standby.xray == xray
+        })
+    }
+}
+
+/// Seconds the world takes to fade back in once a new map is shown.
+pub const MAP_FADE_IN_SECS: f32 = 0.35;
+/// How long a map change may stay black before the loading indicator appears.
+pub const MAP_LOADING_INDICATOR_DELAY_SECS: f32 = 0.4;
+
+/// Covers a map change: black while waiting for the next map, then a short fade-in.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq)]
+pub enum MapTransition {
+    #[default]
+    Idle,
+    Loading {
+        elapsed: f32,
+    },
+    FadingIn {
+        elapsed: f32,
+    },
+}
+
+impl MapTransition {
+    /// Brightness of the world frame, 0.0 is black.
+    pub fn fade(&self) -> f32 {
+        match self {
+            MapTransition::Idle => 1.0,
+            MapTransition::Loading { .. } => 0.0,
+            MapTransition::FadingIn { elapsed } => (elapsed / MAP_FADE_IN_SECS).clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn shows_loading_indicator(&self) -> bool {
+        matches!(self, MapTransition::Loading { elapsed } if *elapsed >= MAP_LOADING_INDICATOR_DELAY_SECS)
+    }
+}
+
 #[derive(Resource)]
 pub struct EffectManagerState {
     pub effect_manager: EffectManager,
@@ -162,6 +349,16 @@ pub struct WindowSurface {
     pub width: u32,
     pub height: u32,
     pub scale_factor: f32,
+    /// Size of the frames handed to Slint; larger than `width`/`height` when the
+    /// renderer upscales the frame itself.
+    pub output_width: u32,
+    pub output_height: u32,
+}
+
+impl WindowSurface {
+    pub fn needs_upscale(&self) -> bool {
+        self.output_width != self.width || self.output_height != self.height
+    }
 }
 
 #[derive(Resource)]
@@ -173,6 +370,9 @@ pub struct ZoomState {
     pub camera_zoom: f32,
     pub is_pixel_perfect: bool,
     pub high_quality_scaling: bool,
+    pub render_scaling: crate::settings_types::RenderScaling,
+    /// How many display pixels each rendered pixel covers.
+    pub upscale: f32,
 }
 
 impl ZoomState {
@@ -184,6 +384,7 @@ impl ZoomState {
         dpi_scale: f32,
         zoom: f32,
         high_quality_scaling: bool,
+        render_scaling: crate::settings_types::RenderScaling,
     ) -> Self {
         let initial_zoom = if zoom == 1.0 {
             Self::compute_initial_zoom(display_h)
@@ -199,6 +400,8 @@ impl ZoomState {
             camera_zoom: 1.0,
             is_pixel_perfect: true,
             high_quality_scaling,
+            render_scaling,
+            upscale: 1.0,
         };
         state.recalculate();
         state
@@ -229,24 +432,38 @@ impl ZoomState {
         self.recalculate();
     }
 
+    pub fn set_render_scaling(&mut self, render_scaling: crate::settings_types::RenderScaling) {
+        self.render_scaling = render_scaling;
+        self.recalculate();
+    }
+
     pub fn cursor_to_render_scale(&self) -> f32 {
-        if self.high_quality_scaling {
-            self.dpi_scale
-        } else {
-            self.dpi_scale / self.user_zoom.max(1.0)
-        }
+        self.dpi_scale / self.upscale
     }
 
     pub fn display_scale(&self) -> f32 {
-        if self.high_quality_scaling {
-            1.0
-        } else if self.is_pixel_perfect {
-            self.user_zoom
+        if self.is_pixel_perfect || self.sharpens_upscale() {
+            self.upscale
         } else {
             1.0
         }
     }
 
+    /// Whether the renderer scales the frame up itself with the sharpened filter,
+    /// instead of leaving a fractional stretch to Slint.
+    pub fn sharpens_upscale(&self) -> bool {
+        !self.is_pixel_perfect && self.upscale > 1.0
+    }
+
+    /// Size of the frames handed to Slint.
+    pub fn output_size(&self) -> (u32, u32) {
+        if self.sharpens_upscale() {
+            self.display_size
+        } else {
+            self.render_size
+        }
+    }
+
     fn recalculate(&mut self) {
         let zoom = self.user_zoom.clamp(0.1, 5.0);
 
@@ -255,20 +472,29 @@ impl ZoomState {
         if self.high_quality_scaling {
             self.render_size = self.display_size;
             self.camera_zoom = zoom;
+            self.upscale = 1.0;
             self.is_pixel_perfect = true; // Always native, so no interest in "blowing up" pixel-perfectly
         } else if zoom < 1.0 {
             self.render_size = self.display_size;
             self.camera_zoom = zoom;
+            self.upscale = 1.0;
             self.is_pixel_perfect = false;
         } else {
-            let render_w = ((self.display_size.0 as f32 / zoom).round() as u32).max(MIN_RENDER_DIM);
+            // Crisp pixels snap to the nearest whole multiple and show a little more or less of the map
+            let upscale = match self.render_scaling {
+                crate::settings_types::RenderScaling::Integer => zoom.round().max(1.0),
+                crate::settings_types::RenderScaling::Smooth => zoom,
+            };
+            let render_w =
+                ((self.display_size.0 as f32 / upscale).round() as u32).max(MIN_RENDER_DIM);
             let render_h =
-                ((self.display_size.1 as f32 / zoom).round() as u32).max(MIN_RENDER_DIM / 2);
+                ((self.display_size.1 as f32 / upscale).round() as u32).max(MIN_RENDER_DIM / 2);
 
             self.render_size = (render_w, render_h);
             self.camera_zoom = 1.0;
+            self.upscale = upscale;
 
-            let frac = zoom.fract();
+            let frac = upscale.fract();
             self.is_pixel_perfect = frac < 0.01 || frac > 0.99;
         }
     }
@@ -50,6 +50,38 @@ impl RichText {
     }
 }
 
+/// Opening marker of an item link token, `[item:Name]`. The chat input builds
+/// the same token when an inventory item is shift-clicked.
+const ITEM_LINK_OPEN: &str = "[item:";
+
+/// Replace `[item:Name]` tokens with `[Name]` and return the linked item names.
+pub fn extract_item_links(input: &str) -> (String, Vec<String>) {
+    let mut text = String::with_capacity(input.len());
+    let mut links = Vec::new();
+    let mut rest = input;
+
+    while let Some(start) = rest.find(ITEM_LINK_OPEN) {
+        let after = &rest[start + ITEM_LINK_OPEN.len()..];
+        let Some(end) = after.find(']') else {
+            break;
+        };
+        let name = after[..end].trim();
+        text.push_str(&rest[..start]);
+        if name.is_empty() {
+            text.push_str(&rest[start..start + ITEM_LINK_OPEN.len() + end + 1]);
+        } else {
+            text.push('[');
+            text.push_str(name);
+            text.push(']');
+            links.push(name.to_string());
+        }
+        rest = &after[end + 1..];
+    }
+    text.push_str(rest);
+
+    (text, links)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,6 +111,17 @@ mod tests {
         assert_eq!(rich.to_plain_string(), "");
     }
 
+    #[test]
+    fn test_extract_item_links() {
+        let (text, links) = extract_item_links("Selling [item:Battle Axe] cheap, [item:] too");
+        assert_eq!(text, "Selling [Battle Axe] cheap, [item:] too");
+        assert_eq!(links, vec!["Battle Axe".to_string()]);
+
+        let (text, links) = extract_item_links("no links [item:unterminated");
+        assert_eq!(text, "no links [item:unterminated");
+        assert!(links.is_empty());
+    }
+
     #[test]
     fn test_parse_only_color() {
         let rich = RichText::parse("{=r");
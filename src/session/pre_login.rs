@@ -1,3 +1,5 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddrV4};
+
 use anyhow::anyhow;
 use async_std::net::TcpStream;
 use async_std::sync::Arc;
@@ -19,10 +21,79 @@ pub struct PreLoginSession {
     decoder: PacketDecoder,
     decrypter: PacketDecrypter,
     sender: EncryptedSender,
+    /// Host the lobby was reached at, for redirects to unroutable addresses.
+    origin_host: String,
 }
 
 pub use game_ui::LoginError;
 
+/// Where to connect for a redirect to `addr`.
+///
+/// Servers behind NAT often hand out their loopback or LAN address, which only
+/// works from the same network. When the lobby itself was reached from outside,
+/// the redirect goes to that host instead, keeping the redirect's port.
+pub fn redirect_target(addr: SocketAddrV4, origin_host: &str) -> String {
+    let unroutable = |ip: &Ipv4Addr| ip.is_loopback() || ip.is_private() || ip.is_unspecified();
+    let origin_is_local = origin_host.eq_ignore_ascii_case("localhost")
+        || origin_host.parse::<IpAddr>().is_ok_and(|ip| match ip {
+            IpAddr::V4(ip) => unroutable(&ip),
+            IpAddr::V6(ip) => ip.is_loopback() || ip.is_unspecified(),
+        });
+    if unroutable(addr.ip()) && !origin_is_local {
+        network::connect::join_host_port(origin_host, addr.port())
+    } else {
+        addr.to_string()
+    }
+}
+
+/// Hands the session token from `redirect` to the game server it points at.
+///
+/// Used for the login server's handoff and for later moves between game
+/// servers, which use the per-character key table.
+pub async fn follow_redirect(
+    redirect: &server::Redirect,
+    origin_host: &str,
+) -> anyhow::Result<(DecryptedReceiver, EncryptedSender)> {
+    let target = redirect_target(redirect.addr, origin_host);
+    tracing::info!("Redirected to {} as {}", target, redirect.name);
+    let stream = TcpStream::connect(&target).await?;
+    stream.set_nodelay(true).ok();
+    let stream = Arc::new(stream);
+    let mut encoder = PacketEncoder::new(stream.clone());
+
+    encoder
+        .write(
+            &client::ClientRedirected {
+                seed: redirect.seed,
+                key: redirect.key.clone(),
+                name: redirect.name.clone(),
+                id: redirect.id,
+            }
+            .to_bytes(),
+        )
+        .await?;
+    encoder.flush().await?;
+
+    Ok((
+        DecryptedReceiver::new(
+            PacketDecoder::new(stream),
+            PacketDecrypter::new_with_special_key_table(
+                redirect.key.clone(),
+                redirect.seed,
+                &redirect.name,
+            ),
+        ),
+        EncryptedSender::new(
+            encoder,
+            PacketEncrypter::new_with_special_key_table(
+                redirect.key.clone(),
+                redirect.seed,
+                &redirect.name,
+            ),
+        ),
+    ))
+}
+
 impl PreLoginSession {
     pub async fn new(server_address: &str, server_port: u16) -> anyhow::Result<Self> {
         tracing::info!(
@@ -30,8 +101,7 @@ impl PreLoginSession {
             server_address,
             server_port
         );
-        let connection_string = format!("{}:{}", server_address, server_port);
-        let stream = TcpStream::connect(&connection_string).await?;
+        let stream = network::connect::connect(server_address, server_port).await?;
         stream.set_nodelay(true).ok();
         tracing::info!("Connected to lobby server.");
         let stream = Arc::new(stream);
@@ -84,7 +154,7 @@ impl PreLoginSession {
             id: redirect.id,
         };
 
-        let stream = TcpStream::connect(redirect.addr).await?;
+        let stream = TcpStream::connect(redirect_target(redirect.addr, server_address)).await?;
         stream.set_nodelay(true).ok();
         let stream = Arc::new(stream);
         let mut decoder = PacketDecoder::new(stream.clone());
@@ -104,6 +174,7 @@ impl PreLoginSession {
             decoder,
             decrypter: PacketDecrypter::new(redirect.key, redirect.seed),
             sender,
+            origin_host: server_address.to_owned(),
         })
     }
 
@@ -140,54 +211,28 @@ impl PreLoginSession {
         };
 
         if login_response.msg_type != LoginMessageType::Confirm {
+            // There is no dedicated message type for a full server, so go by its wording.
+            if is_server_full_message(&login_response.msg) {
+                return Err(LoginError::ServerFull(login_response.msg));
+            }
             return Err(LoginError::Response(login_response.msg_type));
         }
 
-        let packet = self
-            .decoder
-            .read()
-            .await
-            .map_err(|_| LoginError::Network("Failed to read redirect packet".to_string()))?;
-        assert_eq!(packet[0], server::Codes::Redirect as u8);
-
-        let redirect = match server::Redirect::try_from_bytes(&packet[1..]) {
-            Ok(r) => r,
-            Err(_) => return Err(LoginError::Unknown),
-        };
-
-        let redirect_response = client::ClientRedirected {
-            seed: redirect.seed,
-            key: redirect.key.clone(),
-            name: redirect.name.clone(),
-            id: redirect.id,
+        // Notices can arrive ahead of the handoff, only the redirect matters here.
+        let redirect = loop {
+            let packet =
+                self.decoder.read().await.map_err(|_| {
+                    LoginError::Network("Failed to read redirect packet".to_string())
+                })?;
+            if packet[0] == server::Codes::Redirect as u8 {
+                break server::Redirect::try_from_bytes(&packet[1..])
+                    .map_err(|_| LoginError::Unknown)?;
+            }
         };
 
-        let stream = TcpStream::connect(redirect.addr).await.unwrap();
-        stream.set_nodelay(true).ok();
-        let stream = Arc::new(stream);
-        let mut encoder = PacketEncoder::new(stream.clone());
-
-        encoder.write(&redirect_response.to_bytes()).await.unwrap();
-        encoder.flush().await.unwrap();
-
-        Ok((
-            DecryptedReceiver::new(
-                PacketDecoder::new(stream),
-                PacketDecrypter::new_with_special_key_table(
-                    redirect.key.clone(),
-                    redirect.seed,
-                    &redirect.name,
-                ),
-            ),
-            EncryptedSender::new(
-                encoder,
-                PacketEncrypter::new_with_special_key_table(
-                    redirect.key,
-                    redirect.seed,
-                    &redirect.name,
-                ),
-            ),
-        ))
+        follow_redirect(&redirect, &self.origin_host)
+            .await
+            .map_err(|e| LoginError::Network(format!("Couldn't reach the game server: {}", e)))
     }
 
     pub async fn create_character(
@@ -225,3 +270,40 @@ impl PreLoginSession {
         Ok(())
     }
 }
+
+fn is_server_full_message(msg: &str) -> bool {
+    let msg = msg.to_ascii_lowercase();
+    msg.contains("too many players")
+        || msg
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|word| matches!(word, "full" | "queue" | "queued" | "capacity"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_server_full_messages() {
+        assert!(is_server_full_message("The server is FULL, try later."));
+        assert!(is_server_full_message("You are #12 in the login queue."));
+        assert!(!is_server_full_message("Incorrect password."));
+        assert!(!is_server_full_message("Character created successfully."));
+    }
+
+    #[test]
+    fn redirects_to_private_addresses_reuse_the_lobby_host() {
+        let lan: SocketAddrV4 = "192.168.1.20:2615".parse().unwrap();
+        let public: SocketAddrV4 = "203.0.113.7:2615".parse().unwrap();
+        assert_eq!(
+            redirect_target(lan, "play.example.org"),
+            "play.example.org:2615"
+        );
+        assert_eq!(redirect_target(lan, "192.168.1.20"), "192.168.1.20:2615");
+        assert_eq!(redirect_target(lan, "localhost"), "192.168.1.20:2615");
+        assert_eq!(
+            redirect_target(public, "play.example.org"),
+            "203.0.113.7:2615"
+        );
+    }
+}
@@ -4,7 +4,7 @@ use crate::app_state::AppState;
 use bevy::tasks::Task;
 use crc::crc16;
 use std::any::type_name;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::events::{
     AbilityEvent, AudioEvent, ChatEvent, EntityEvent, InventoryEvent, MapEvent, NetworkEvent,
@@ -34,6 +34,66 @@ impl Default for NetSessionState {
     }
 }
 
+/// How often an unprompted tick sync goes out to keep idle connections open.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(20);
+/// Servers send heartbeats well within this, so silence means the link is gone.
+const SERVER_SILENCE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Keeps an idle connection alive and notices when the server stops talking.
+#[derive(Resource)]
+pub struct ConnectionWatchdog {
+    last_received: Instant,
+    last_keepalive: Instant,
+    /// Last tick count the server reported, and when.
+    server_ticks: Option<(u32, Instant)>,
+    timed_out: bool,
+}
+
+impl Default for ConnectionWatchdog {
+    fn default() -> Self {
+        Self::started_at(Instant::now())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum WatchdogStep {
+    Wait,
+    SendKeepalive,
+    TimedOut,
+}
+
+impl ConnectionWatchdog {
+    fn started_at(now: Instant) -> Self {
+        Self {
+            last_received: now,
+            last_keepalive: now,
+            server_ticks: None,
+            timed_out: false,
+        }
+    }
+
+    /// Anything from the server shows the link is up, even after a stall.
+    fn received(&mut self, now: Instant) {
+        self.last_received = now;
+        self.timed_out = false;
+    }
+
+    fn step(&mut self, now: Instant) -> WatchdogStep {
+        if self.timed_out {
+            return WatchdogStep::Wait;
+        }
+        if now.saturating_duration_since(self.last_received) >= SERVER_SILENCE_TIMEOUT {
+            self.timed_out = true;
+            return WatchdogStep::TimedOut;
+        }
+        if now.saturating_duration_since(self.last_keepalive) >= KEEPALIVE_INTERVAL {
+            self.last_keepalive = now;
+            return WatchdogStep::SendKeepalive;
+        }
+        WatchdogStep::Wait
+    }
+}
+
 #[derive(Debug)]
 enum MapDownloadState {
     None,
@@ -54,13 +114,20 @@ pub struct SessionRuntimePlugin;
 impl Plugin for SessionRuntimePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<NetSessionState>()
+            .init_resource::<ConnectionWatchdog>()
             .init_resource::<crate::network::PacketOutbox>()
             .init_resource::<crate::resources::PlayerAttributes>()
             .add_systems(
                 PreUpdate,
                 drain_net_events.run_if(in_state(AppState::InGame)),
             )
-            .add_systems(Update, (process_net_packets, send_client_actions));
+            .add_systems(Update, (process_net_packets, send_client_actions))
+            .add_systems(
+                Update,
+                (track_server_traffic, keep_connection_alive)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            );
     }
 }
 
@@ -71,6 +138,61 @@ fn drain_net_events(rx: Res<NetEventRx>, mut writer: MessageWriter<NetworkEvent>
     }
 }
 
+fn track_server_traffic(
+    mut net_events: MessageReader<NetworkEvent>,
+    mut watchdog: ResMut<ConnectionWatchdog>,
+) {
+    for evt in net_events.read() {
+        match evt {
+            NetworkEvent::Connected => *watchdog = ConnectionWatchdog::default(),
+            NetworkEvent::Packet(code, data) => {
+                watchdog.received(Instant::now());
+                if *code == server::Codes::SynchronizeTicksResponse
+                    && let Some(q) = parse_packet::<server::SynchronizeTicksResponse>(data)
+                {
+                    watchdog.server_ticks = Some((q.ticks as u32, Instant::now()));
+                }
+            }
+            NetworkEvent::UnknownPacket(..) => watchdog.received(Instant::now()),
+            NetworkEvent::Disconnected | NetworkEvent::TimedOut => watchdog.timed_out = true,
+        }
+    }
+}
+
+// Sends tick syncs while idle; once the server goes quiet, raises TimedOut and
+// drops the session, back to the login screen
+fn keep_connection_alive(
+    mut watchdog: ResMut<ConnectionWatchdog>,
+    session: Res<NetSessionState>,
+    outbox: Res<crate::network::PacketOutbox>,
+    mut net_events: MessageWriter<NetworkEvent>,
+    mut outbound: MessageWriter<crate::webui::plugin::UiOutbound>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    match watchdog.step(Instant::now()) {
+        WatchdogStep::Wait => {}
+        WatchdogStep::SendKeepalive => {
+            if let Some((ticks, at)) = watchdog.server_ticks {
+                outbox.send(&client::SynchronizeTicks {
+                    server_ticks: ticks.wrapping_add(at.elapsed().as_millis() as u32),
+                    client_ticks: session.start_time.elapsed().as_millis() as u32,
+                });
+            }
+        }
+        WatchdogStep::TimedOut => {
+            net_events.write(NetworkEvent::TimedOut);
+            // Leaving the game despawns the socket tasks, closing the connection
+            outbound.write(crate::webui::plugin::UiOutbound(
+                game_ui::CoreToUi::ConnectionLost {
+                    reason: "The server stopped responding, so the connection was closed."
+                        .to_string(),
+                },
+            ));
+            next_state.set(AppState::MainMenu);
+        }
+    }
+}
+
 // Marker component for the spawned TCP receive loop task
 #[derive(Component)]
 #[allow(dead_code)]
@@ -93,6 +215,7 @@ fn process_net_packets(
     mut metafile_store: ResMut<crate::metafile_store::MetafileStore>,
     current_session: Option<Res<crate::CurrentSession>>,
     storage_config: Res<crate::resources::StorageConfig>,
+    mut unhandled: MessageWriter<crate::plugins::diagnostics::UnhandledPacket>,
 ) {
     let Some(current_session) = current_session else {
         return;
@@ -109,6 +232,17 @@ fn process_net_packets(
             NetworkEvent::Disconnected => {
                 tracing::warn!("Network disconnected");
             }
+            NetworkEvent::TimedOut => {
+                tracing::warn!("Server stopped responding");
+            }
+            NetworkEvent::UnknownPacket(opcode, data) => {
+                tracing::warn!(opcode, len = data.len(), "Unknown opcode");
+                unhandled.write(crate::plugins::diagnostics::UnhandledPacket {
+                    opcode: *opcode,
+                    code: None,
+                    payload: data.clone(),
+                });
+            }
             NetworkEvent::Packet(code, data) => match code {
                 &server::Codes::HeartBeatResponse => {
                     if let Some(query) = parse_packet::<server::HeartBeatResponse>(data) {
@@ -248,6 +382,9 @@ fn process_net_packets(
                             player_attrs.max_hp = primary.maximum_hp;
                             player_attrs.max_mp = primary.maximum_mp;
                         }
+                        if let Some(exp_gold) = &attrs.exp_gold {
+                            player_attrs.exp_gold = Some((exp_gold.total_exp, exp_gold.gold));
+                        }
                     }
                 }
                 &server::Codes::Equipment => {
@@ -352,6 +489,11 @@ fn process_net_packets(
                 }
                 e => {
                     tracing::warn!(?e, "Unhandled game event");
+                    unhandled.write(crate::plugins::diagnostics::UnhandledPacket {
+                        opcode: *e as u8,
+                        code: Some(*e),
+                        payload: data.clone(),
+                    });
                 }
             },
         }
@@ -364,6 +506,12 @@ fn send_client_actions(
     mut inventory_events: MessageReader<InventoryEvent>,
     mut ability_events: MessageReader<AbilityEvent>,
     outbox: Res<crate::network::PacketOutbox>,
+    inv_state: Res<crate::webui::plugin::InventoryState>,
+    settings: Res<crate::settings_types::Settings>,
+    current_session: Option<Res<crate::CurrentSession>>,
+    mut item_confirm: ResMut<crate::plugins::item_confirm::PendingItemConfirm>,
+    mut casting_state: ResMut<crate::ecs::spell_casting::SpellCastingState>,
+    abilities: Option<Res<crate::webui::plugin::AbilityState>>,
 ) {
     for e in chat_events.read() {
         match e {
@@ -394,6 +542,21 @@ fn send_client_actions(
                 });
             }
             InventoryEvent::Use { slot } => {
+                use crate::plugins::item_confirm::{
+                    GuardedAction, ItemConfirmRequest, needs_confirm,
+                };
+                // Flagged items wait for the confirmation dialog instead
+                if let Some(item) = inv_state.0.iter().find(|item| item.slot == *slot)
+                    && needs_confirm(&settings, current_session.as_deref(), &item.name)
+                {
+                    item_confirm.0 = Some(ItemConfirmRequest {
+                        slot: *slot,
+                        item_name: item.name.clone(),
+                        sprite: item.sprite,
+                        action: GuardedAction::Use,
+                    });
+                    continue;
+                }
                 outbox.send(&client::ItemUse { source_slot: *slot });
             }
             InventoryEvent::Unequip { slot } => {
@@ -414,7 +577,15 @@ fn send_client_actions(
     for e in ability_events.read() {
         match e {
             AbilityEvent::UseSkill { slot } => {
-                outbox.send(&client::SkillUse { source_slot: *slot });
+                // Close to the end of a cooldown, hold the press until it clears
+                let cooldown = abilities
+                    .as_ref()
+                    .and_then(|abilities| abilities.skills.iter().find(|s| s.slot == *slot))
+                    .and_then(|skill| skill.on_cooldown.as_ref());
+                let window_ms = settings.gameplay.spell_queue_window_ms;
+                if !casting_state.hold_skill(*slot, cooldown, window_ms) {
+                    outbox.send(&client::SkillUse { source_slot: *slot });
+                }
             }
 
             AbilityEvent::UseSpell { .. }
@@ -633,3 +804,35 @@ fn handle_metadata(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchdog_keeps_alive_times_out_and_recovers() {
+        let start = Instant::now();
+        let mut watchdog = ConnectionWatchdog::started_at(start);
+        assert_eq!(watchdog.step(start), WatchdogStep::Wait);
+
+        // Quiet but within the timeout: a keepalive every interval
+        let later = start + KEEPALIVE_INTERVAL;
+        assert_eq!(watchdog.step(later), WatchdogStep::SendKeepalive);
+        assert_eq!(watchdog.step(later), WatchdogStep::Wait);
+
+        let silent = start + SERVER_SILENCE_TIMEOUT;
+        assert_eq!(watchdog.step(silent), WatchdogStep::TimedOut);
+        assert_eq!(watchdog.step(silent), WatchdogStep::Wait);
+
+        // Traffic after a stall switches the watchdog back on
+        watchdog.received(silent);
+        assert_eq!(
+            watchdog.step(silent + KEEPALIVE_INTERVAL),
+            WatchdogStep::SendKeepalive
+        );
+        assert_eq!(
+            watchdog.step(silent + SERVER_SILENCE_TIMEOUT),
+            WatchdogStep::TimedOut
+        );
+    }
+}
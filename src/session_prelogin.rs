@@ -6,3 +6,4 @@ mod pre_login_impl;
 
 pub use pre_login_impl::LoginError;
 pub use pre_login_impl::PreLoginSession;
+pub use pre_login_impl::follow_redirect;
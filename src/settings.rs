@@ -1,36 +1,51 @@
+use crate::settings_migration::{self, SETTINGS_VERSION, SettingsRecovery};
 pub use crate::settings_types::*;
+use crate::webui::plugin::UiOutbound;
 use bevy::prelude::*;
+use game_ui::CoreToUi;
 use std::fs;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 impl Settings {
-    pub fn load_from_root(root: &std::path::Path) -> Self {
+    /// Loads `settings.toml` and every saved character profile under `root`.
+    /// A settings file that can't be read leaves defaults in place and is
+    /// returned as a [`SettingsRecovery`] instead of being overwritten.
+    pub fn load_from_root(root: &std::path::Path) -> (Self, Option<SettingsRecovery>) {
         let path = root.join("settings.toml");
-        let mut settings = if path.exists() {
-            match fs::read_to_string(&path) {
-                Ok(content) => match toml::from_str::<Settings>(&content) {
-                    Ok(s) => {
-                        info!("Loaded global settings from {:?}", path);
-                        s
+        let mut recovery = None;
+        let mut settings = match fs::read_to_string(&path) {
+            Ok(content) => match settings_migration::parse(&content) {
+                Ok(parsed) => {
+                    info!("Loaded global settings from {:?}", path);
+                    if parsed.version != SETTINGS_VERSION {
+                        migrate_file(&path, parsed.version, &parsed.settings);
+                    } else if let Err(e) =
+                        fs::write(settings_migration::last_good_backup_path(&path), &content)
+                    {
+                        warn!("Failed to back up settings.toml: {}", e);
                     }
-                    Err(e) => {
-                        error!("Failed to parse settings.toml: {}", e);
-                        Settings::default()
-                    }
-                },
+                    parsed.settings
+                }
                 Err(e) => {
-                    error!("Failed to read settings.toml: {}", e);
+                    error!("Failed to parse settings.toml: {}", e);
+                    recovery = Some(recovery_for(&path, e));
                     Settings::default()
                 }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                info!("Creating default settings at {:?}", path);
+                let default_settings = Settings::default();
+                // Create a temporary config for the initial save if necessary, 
+                // but we can just use the path version for internal load.
+                let default_content = toml::to_string_pretty(&default_settings).unwrap();
+                let _ = fs::write(&path, default_content);
+                default_settings
+            }
+            Err(e) => {
+                error!("Failed to read settings.toml: {}", e);
+                recovery = Some(recovery_for(&path, e.to_string()));
+                Settings::default()
             }
-        } else {
-            info!("Creating default settings at {:?}", path);
-            let default_settings = Settings::default();
-            // Create a temporary config for the initial save if necessary, 
-            // but we can just use the path version for internal load.
-            let default_content = toml::to_string_pretty(&default_settings).unwrap();
-            let _ = fs::write(&path, default_content);
-            default_settings
         };
 
         // Load profiles from servers/{server_id}/characters/
@@ -48,7 +63,7 @@ impl Settings {
             }
         }
 
-        settings
+        (settings, recovery)
     }
 
     pub fn save_to_root(&self, config: &crate::resources::StorageConfig) {
@@ -85,6 +100,9 @@ impl Settings {
                     bars: hotbars,
                     current_panel: current_hotbar_panel,
                 },
+                windows: self.get_window_layout(cred.server_id, &cred.username),
+                confirm_items: self.get_confirm_items(cred.server_id, &cred.username),
+                lifetime: self.get_lifetime_stats(cred.server_id, &cred.username),
             };
 
             let profile_path = config.server_characters_dir(cred.server_id)
@@ -101,6 +119,44 @@ impl Settings {
         }
     }
 
+    /// Resolves a pending [`SettingsRecovery`] with either defaults or the backup,
+    /// keeping the character profiles that were loaded alongside the defaults.
+    /// The unreadable file is moved aside rather than overwritten.
+    pub fn recover(
+        &mut self,
+        recovery: &SettingsRecovery,
+        restore_backup: bool,
+        config: &crate::resources::StorageConfig,
+    ) {
+        let path = config.settings_path();
+        let corrupt = settings_migration::corrupt_path(&path);
+        if let Err(e) = fs::rename(&path, &corrupt) {
+            warn!("Failed to move unreadable settings to {:?}: {}", corrupt, e);
+        }
+
+        let mut recovered = Settings::default();
+        if let (true, Some(backup)) = (restore_backup, &recovery.backup) {
+            match fs::read_to_string(backup)
+                .map_err(|e| e.to_string())
+                .and_then(|content| settings_migration::parse(&content))
+            {
+                Ok(parsed) => {
+                    info!("Restored settings from {:?}", backup);
+                    recovered = parsed.settings;
+                }
+                Err(e) => error!("Failed to restore settings from {:?}: {}", backup, e),
+            }
+        }
+
+        recovered.saved_credentials = std::mem::take(&mut self.saved_credentials);
+        recovered.hotbars = std::mem::take(&mut self.hotbars);
+        recovered.window_layouts = std::mem::take(&mut self.window_layouts);
+        recovered.confirm_items = std::mem::take(&mut self.confirm_items);
+        recovered.lifetime_stats = std::mem::take(&mut self.lifetime_stats);
+        *self = recovered;
+        self.save_to_root(config);
+    }
+
     pub fn remove_credential(&mut self, id: &str, config: &crate::resources::StorageConfig) {
         if let Some(idx) = self.saved_credentials.iter().position(|c| c.id == id) {
             let cred = self.saved_credentials.remove(idx);
@@ -150,19 +206,41 @@ impl Plugin for SettingsPlugin {
             .get_resource::<crate::resources::StorageConfig>()
             .expect("StorageConfig resource missing during SettingsPlugin::build");
 
-        let settings = Settings::load_from_root(&storage_config.root);
+        let (settings, recovery) = Settings::load_from_root(&storage_config.root);
         app.insert_resource(settings);
+        if let Some(recovery) = recovery {
+            app.insert_resource(recovery);
+        }
         app.insert_resource(SettingsSaveTimer(Timer::from_seconds(1.0, TimerMode::Once)));
+        app.add_systems(Startup, offer_settings_recovery);
         app.add_systems(Update, save_settings_on_change);
     }
 }
 
+fn offer_settings_recovery(
+    recovery: Option<Res<SettingsRecovery>>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    if let Some(recovery) = recovery {
+        outbound.write(UiOutbound(CoreToUi::SettingsRecoveryPending {
+            error: recovery.error.clone(),
+            has_backup: recovery.backup.is_some(),
+        }));
+    }
+}
+
 fn save_settings_on_change(
     settings: Res<Settings>,
     storage_config: Res<crate::resources::StorageConfig>,
     mut timer: ResMut<SettingsSaveTimer>,
     time: Res<Time>,
+    recovery: Option<Res<SettingsRecovery>>,
 ) {
+    // Keep the unreadable file on disk until the player decides what replaces it.
+    if recovery.is_some() {
+        return;
+    }
+
     if settings.is_changed() {
         timer.0.reset();
     }
@@ -174,6 +252,40 @@ fn save_settings_on_change(
     }
 }
 
+/// Copies a file written with another schema version aside, then replaces it
+/// with its migrated form. The original is left untouched if the copy fails.
+fn migrate_file(path: &std::path::Path, version: u32, settings: &Settings) {
+    let backup = settings_migration::migration_backup_path(path, version);
+    if let Err(e) = fs::copy(path, &backup) {
+        error!("Failed to back up settings.toml before migrating: {}", e);
+        return;
+    }
+    info!(
+        "Migrating settings from version {} to {}, previous file kept at {:?}",
+        version, SETTINGS_VERSION, backup
+    );
+    match toml::to_string_pretty(settings) {
+        Ok(content) => {
+            if let Err(e) = fs::write(path, content) {
+                error!("Failed to write migrated settings.toml: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to serialize migrated settings: {}", e),
+    }
+}
+
+fn recovery_for(path: &std::path::Path, error: String) -> SettingsRecovery {
+    let backup = settings_migration::last_good_backup_path(path);
+    let backup_usable = fs::read_to_string(&backup)
+        .map_err(|e| e.to_string())
+        .and_then(|content| settings_migration::parse(&content))
+        .is_ok();
+    SettingsRecovery {
+        error,
+        backup: backup_usable.then_some(backup),
+    }
+}
+
 fn load_profiles_from_dir(dir: &std::path::Path, settings: &mut Settings) {
     if let Ok(char_files) = fs::read_dir(dir) {
         for char_file in char_files.flatten() {
@@ -187,7 +299,14 @@ fn load_profiles_from_dir(dir: &std::path::Path, settings: &mut Settings) {
                             last_used: profile.last_used,
                             preview: profile.preview,
                         });
-                        settings.hotbars.insert(profile.id, profile.hotbars);
+                        settings.hotbars.insert(profile.id.clone(), profile.hotbars);
+                        settings
+                            .window_layouts
+                            .insert(profile.id.clone(), profile.windows);
+                        settings
+                            .confirm_items
+                            .insert(profile.id.clone(), profile.confirm_items);
+                        settings.lifetime_stats.insert(profile.id, profile.lifetime);
                     }
                 }
             }
@@ -0,0 +1,196 @@
+//! Versioned `settings.toml` schema.
+//!
+//! The file records the schema `version` it was written with. Older files are
+//! raised one version at a time by the steps in [`MIGRATIONS`] before they are
+//! deserialized, so a schema change never silently falls back to defaults.
+//! `settings::SettingsPlugin` copies a file aside before its migrated form
+//! replaces it, and raises a [`SettingsRecovery`] when a file can't be read at
+//! all.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::Resource;
+use toml::Table;
+
+use crate::settings_types::Settings;
+
+/// Schema version written by this build.
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` raises a version `n` table to version `n + 1`.
+const MIGRATIONS: &[fn(&mut Table)] = &[fill_missing_sections];
+
+/// A `settings.toml` that couldn't be loaded. Defaults are in use and nothing is
+/// saved until the player picks defaults or the backup.
+#[derive(Resource, Debug, Clone)]
+pub struct SettingsRecovery {
+    pub error: String,
+    /// Last settings file that loaded cleanly, if it still parses.
+    pub backup: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct ParsedSettings {
+    pub settings: Settings,
+    /// Version the file was written with, before migrating.
+    pub version: u32,
+}
+
+/// Parses and migrates the contents of a `settings.toml`.
+pub fn parse(content: &str) -> Result<ParsedSettings, String> {
+    let mut table: Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    let version = match table.get("version") {
+        None => 0,
+        Some(value) => value
+            .as_integer()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| format!("invalid settings version {}", value))?,
+    };
+
+    for migrate in MIGRATIONS.iter().skip(version as usize) {
+        migrate(&mut table);
+    }
+    // Files from a newer build load as far as this one understands them and
+    // are written back as this version.
+    if version != SETTINGS_VERSION {
+        table.insert("version".into(), i64::from(SETTINGS_VERSION).into());
+    }
+
+    let settings = table
+        .try_into()
+        .map_err(|e: toml::de::Error| e.to_string())?;
+    Ok(ParsedSettings { settings, version })
+}
+
+/// Where a file written with `version` is copied before being replaced.
+pub fn migration_backup_path(settings_path: &Path, version: u32) -> PathBuf {
+    settings_path.with_extension(format!("v{}.toml.bak", version))
+}
+
+/// Copy of the last settings file that loaded without migrating.
+pub fn last_good_backup_path(settings_path: &Path) -> PathBuf {
+    settings_path.with_extension("toml.bak")
+}
+
+/// Where an unreadable settings file is moved once the player has chosen a recovery.
+pub fn corrupt_path(settings_path: &Path) -> PathBuf {
+    settings_path.with_extension("toml.corrupt")
+}
+
+/// Version 0 files predate the `version` key. Sections added since then were
+/// required, so a file missing any of them failed to load and was replaced by
+/// defaults. Missing keys now take their default values instead.
+fn fill_missing_sections(table: &mut Table) {
+    let defaults = Table::try_from(Settings::default()).expect("default settings serialize");
+    fill_missing(table, defaults);
+}
+
+fn fill_missing(table: &mut Table, defaults: Table) {
+    for (key, default) in defaults {
+        match (table.get_mut(&key), default) {
+            (None, default) => {
+                table.insert(key, default);
+            }
+            (Some(toml::Value::Table(existing)), toml::Value::Table(default)) => {
+                fill_missing(existing, default);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings_types::KeyBindings;
+    use game_types::GameAction;
+
+    #[test]
+    fn current_settings_round_trip() {
+        let content = toml::to_string_pretty(&Settings::default()).unwrap();
+        let parsed = parse(&content).unwrap();
+        assert_eq!(parsed.version, SETTINGS_VERSION);
+        assert_eq!(parsed.settings.version, SETTINGS_VERSION);
+    }
+
+    #[test]
+    fn unversioned_file_keeps_values_and_gains_missing_sections() {
+        let content = r#"
+            servers = []
+
+            [audio]
+            music_volume = 0.25
+            sfx_volume = 0.5
+        "#;
+        let parsed = parse(content).unwrap();
+        assert_eq!(parsed.version, 0);
+        assert_eq!(parsed.settings.version, SETTINGS_VERSION);
+        assert_eq!(parsed.settings.audio.music_volume, 0.25);
+        assert!(parsed.settings.servers.is_empty());
+        assert_eq!(
+            parsed.settings.graphics.scale,
+            Settings::default().graphics.scale
+        );
+    }
+
+    #[test]
+    fn hotbar_layout_is_clamped_on_load() {
+        let parsed = parse("[layout]\nhotbar_rows = 0\nhotbar_icon_size = 500").unwrap();
+        assert_eq!(parsed.settings.layout.hotbar_rows, 1);
+        assert_eq!(parsed.settings.layout.hotbar_icon_size, 64);
+    }
+
+    #[test]
+    fn unreadable_files_are_errors() {
+        assert!(parse("[audio\nmusic_volume = 1").is_err());
+        assert!(parse("version = \"two\"").is_err());
+        assert!(parse("version = 1\n[audio]\nmusic_volume = \"loud\"").is_err());
+    }
+
+    #[test]
+    fn key_bindings_load_by_action_id() {
+        let content = r#"
+            [key_bindings]
+            move_up = "KeyW"
+            refresh = ["F5", "Ctrl+KeyR"]
+            retired_action = "KeyZ"
+        "#;
+        let settings = parse(content).unwrap().settings;
+        let bindings = &settings.key_bindings;
+        assert_eq!(bindings.get(GameAction::MoveUp)[0], "KeyW");
+        assert_eq!(bindings.get(GameAction::Refresh)[1], "Ctrl+KeyR");
+        assert_eq!(bindings.get(GameAction::ToggleMute)[0], "KeyM");
+
+        let saved = toml::to_string_pretty(&settings).unwrap();
+        assert!(saved.contains("move_up = \"KeyW\""));
+        assert!(!saved.contains("retired_action"));
+    }
+
+    #[test]
+    fn rebinding_a_key_takes_it_from_other_actions() {
+        let mut bindings = KeyBindings::default();
+        bindings.rebind(GameAction::BasicAttack, 1, "KeyI");
+        assert_eq!(bindings.get(GameAction::BasicAttack)[1], "KeyI");
+        assert_eq!(bindings.get(GameAction::Inventory)[0], "");
+
+        // Otherwise the default layout couldn't be rebuilt from the controls screen
+        let mut defaults: Vec<&str> = GameAction::ALL.iter().map(|a| a.default_key()).collect();
+        defaults.sort_unstable();
+        defaults.dedup();
+        assert_eq!(defaults.len(), GameAction::ALL.len());
+    }
+
+    #[test]
+    fn backup_paths_sit_next_to_settings() {
+        let path = Path::new("/data/settings.toml");
+        assert_eq!(
+            migration_backup_path(path, 0),
+            Path::new("/data/settings.v0.toml.bak")
+        );
+        assert_eq!(
+            last_good_backup_path(path),
+            Path::new("/data/settings.toml.bak")
+        );
+        assert_eq!(corrupt_path(path), Path::new("/data/settings.toml.corrupt"));
+    }
+}
@@ -2,10 +2,14 @@ use bevy::prelude::Resource;
 use game_ui::{CoreToUi, LoginError};
 
 pub use game_types::{
-    CharacterPreview, CustomHotBarSlot, CustomHotBars, KeyBindings, SavedCredential,
-    SavedCredentialPublic, ServerEntry, XRaySize,
+    CharacterPreview, CustomHotBarSlot, CustomHotBars, DirectionPriority, GpuBackend,
+    GpuPreference, KeyBindings, NotificationPolicy, PressMode, RenderScaling, SavedCredential,
+    SavedCredentialPublic, ServerEntry, ServerSubscription, SoundCategory, WindowGeometry,
+    XRayMode, XRaySize,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::chat_filter::{CHAT_CHANNELS, ChatChannel, DEFAULT_WORDS, mask_words};
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
 pub struct HotbarData {
@@ -15,40 +19,460 @@ pub struct HotbarData {
     pub current_panel: i32,
 }
 
+/// Floating HUD window geometry keyed by window id ("chat", "inventory", ...).
+/// Windows without an entry use their default placement.
+pub type WindowLayout = BTreeMap<String, WindowGeometry>;
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct AudioSettings {
     pub music_volume: f32,
     pub sfx_volume: f32,
+    /// Per-category multipliers applied on top of `sfx_volume`.
+    #[serde(default = "default_full_volume")]
+    pub combat_volume: f32,
+    #[serde(default = "default_full_volume")]
+    pub spell_volume: f32,
+    #[serde(default = "default_full_volume")]
+    pub ambient_volume: f32,
+    /// Output device name, or `None` to follow the system default.
+    #[serde(default)]
+    pub output_device: Option<String>,
+}
+
+impl AudioSettings {
+    pub fn category_volume(&self, category: SoundCategory) -> f32 {
+        match category {
+            SoundCategory::Combat => self.combat_volume,
+            SoundCategory::Spells => self.spell_volume,
+            SoundCategory::Ambient => self.ambient_volume,
+        }
+    }
+
+    pub fn set_category_volume(&mut self, category: SoundCategory, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        match category {
+            SoundCategory::Combat => self.combat_volume = volume,
+            SoundCategory::Spells => self.spell_volume = volume,
+            SoundCategory::Ambient => self.ambient_volume = volume,
+        }
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct GraphicsSettings {
     pub xray_size: XRaySize,
+    #[serde(default)]
+    pub xray_mode: XRayMode,
+    /// Hides the walls in front of the room the player stands in, see
+    /// `ecs::systems::interior_wall_system`.
+    #[serde(default = "default_true")]
+    pub open_interiors: bool,
+    /// Flashes the tiles a creature is about to attack, see
+    /// `plugins::attack_telegraphs`.
+    #[serde(default)]
+    pub attack_telegraphs: bool,
     pub scale: f32,
     #[serde(default = "default_true")]
     pub high_quality_scaling: bool,
+    #[serde(default)]
+    pub render_scaling: RenderScaling,
+    /// Brightness curve applied to the final frame, 1.0 leaves colours untouched.
+    #[serde(default = "default_gamma")]
+    pub gamma: f32,
+    /// Allows the developer overlay hotkey, also in release builds.
+    #[serde(default)]
+    pub debug_overlay: bool,
+    /// Low-spec mode for weak GPUs, see `plugins::potato_mode`.
+    #[serde(default)]
+    pub potato_mode: bool,
+    /// Whether potato mode has already been suggested for a weak GPU.
+    #[serde(default)]
+    pub potato_mode_suggested: bool,
+    /// Graphics API to render with, read once at startup.
+    #[serde(default)]
+    pub gpu_backend: GpuBackend,
+    /// Kind of GPU to render on, read once at startup.
+    #[serde(default)]
+    pub gpu_preference: GpuPreference,
+    /// Texture memory in use above which unused sheets are dropped, 0 for no
+    /// limit. See `plugins::texture_memory`.
+    #[serde(default = "default_texture_budget_mb")]
+    pub texture_budget_mb: u32,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_gamma() -> f32 {
+    1.0
+}
+
+fn default_full_volume() -> f32 {
+    1.0
+}
+
+fn default_texture_budget_mb() -> u32 {
+    256
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct GameplaySettings {
     pub current_server_id: Option<u32>,
+    /// Masks saved usernames, whispers and server addresses for streaming.
+    #[serde(default)]
+    pub streamer_mode: bool,
+    /// Minutes without input before switching to Daydreaming, 0 disables it.
+    #[serde(default = "default_idle_daydream_minutes")]
+    pub idle_daydream_minutes: u32,
+    /// Keep the character from being kicked for idling, see `plugins::anti_idle`.
+    #[serde(default)]
+    pub anti_idle: bool,
+    /// Minutes of idling anti-idle covers before switching itself off, 0 for no limit.
+    #[serde(default = "default_anti_idle_max_minutes")]
+    pub anti_idle_max_minutes: u32,
+    /// Warn when an equipped item falls below this durability percentage, 0 disables it.
+    #[serde(default = "default_durability_warning_percent")]
+    pub durability_warning_percent: u32,
+    /// How long before a cast or skill cooldown finishes a press is queued, 0 disables queueing.
+    #[serde(default = "default_spell_queue_window_ms")]
+    pub spell_queue_window_ms: u32,
+    /// Show public chat as bubbles above the speaker as well as in the chat log.
+    #[serde(default = "default_chat_bubbles")]
+    pub chat_bubbles: bool,
+    /// Highlight chat lines naming the character and collect them in the
+    /// chat log's Mentions tab.
+    #[serde(default = "default_true")]
+    pub mention_highlight: bool,
+    #[serde(default = "default_true")]
+    pub mention_sound: bool,
+    /// Characters whose logins are announced, matched case-insensitively.
+    #[serde(default)]
+    pub friends: Vec<String>,
+    /// Notifications let through per social status, indexed by its protocol value.
+    #[serde(default = "default_notification_policies")]
+    pub notification_policies: [NotificationPolicy; 8],
+    /// Pick up matching items that land on the player's tile, see `plugins::auto_pickup`.
+    #[serde(default)]
+    pub auto_pickup: bool,
+    #[serde(default = "default_true")]
+    pub auto_pickup_gold: bool,
+    /// Items always picked up, matched by their ground sprite.
+    #[serde(default)]
+    pub auto_pickup_items: Vec<AutoPickupItem>,
+    /// NPC options that ask before being picked, matched as parts of the
+    /// option text, see `plugins::dialog_confirm`.
+    #[serde(default = "default_dangerous_dialog_options")]
+    pub dangerous_dialog_options: Vec<String>,
+    /// NPCs whose text pages are clicked through automatically, by name,
+    /// see `plugins::dialog_advance`.
+    #[serde(default)]
+    pub auto_next_npcs: Vec<String>,
+    /// Menu option chains replayed with the favorite NPC menu key, one per
+    /// NPC, see `plugins::menu_shortcuts`.
+    #[serde(default)]
+    pub menu_shortcuts: Vec<MenuShortcut>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AutoPickupItem {
+    pub name: String,
+    pub sprite: u16,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MenuShortcut {
+    pub npc: String,
+    pub steps: Vec<MenuShortcutStep>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MenuShortcutStep {
+    /// Option text, looked for first when replaying.
+    pub text: String,
+    /// Id the option was picked with: its pursuit id, or its place in a dialog.
+    pub id: i32,
+}
+
+fn default_idle_daydream_minutes() -> u32 {
+    10
+}
+
+fn default_anti_idle_max_minutes() -> u32 {
+    60
+}
+
+fn default_durability_warning_percent() -> u32 {
+    20
+}
+
+fn default_spell_queue_window_ms() -> u32 {
+    400
+}
+
+fn default_chat_bubbles() -> bool {
+    true
+}
+
+fn default_dangerous_dialog_options() -> Vec<String> {
+    crate::plugins::dialog_confirm::DEFAULT_DANGEROUS_OPTIONS
+        .iter()
+        .map(|option| option.to_string())
+        .collect()
+}
+
+fn default_notification_policies() -> [NotificationPolicy; 8] {
+    let mut policies = [NotificationPolicy::All; 8];
+    policies[packets::types::SocialStatus::DoNotDisturb as usize] =
+        NotificationPolicy::WhispersOnly;
+    policies
+}
+
+impl GameplaySettings {
+    pub fn is_friend(&self, name: &str) -> bool {
+        self.friends.iter().any(|f| f.eq_ignore_ascii_case(name))
+    }
+
+    pub fn notification_policy(&self, status: packets::types::SocialStatus) -> NotificationPolicy {
+        self.notification_policies
+            .get(status as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn auto_picks_up(&self, name: &str) -> bool {
+        self.auto_pickup_items
+            .iter()
+            .any(|item| item.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Adds the item to the auto-pickup list, or removes it if it is already
+    /// there. Returns whether it is now picked up automatically.
+    pub fn toggle_auto_pickup(&mut self, name: &str, sprite: u16) -> bool {
+        let before = self.auto_pickup_items.len();
+        self.auto_pickup_items
+            .retain(|item| !item.name.eq_ignore_ascii_case(name));
+        if self.auto_pickup_items.len() == before {
+            self.auto_pickup_items.push(AutoPickupItem {
+                name: name.to_owned(),
+                sprite,
+            });
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Adds `name` to the friends list, or removes it if it is already there.
+    pub fn toggle_friend(&mut self, name: &str) {
+        let before = self.friends.len();
+        self.friends.retain(|f| !f.eq_ignore_ascii_case(name));
+        if self.friends.len() == before {
+            self.friends.push(name.to_owned());
+        }
+    }
+}
+
+/// Local WebSocket feed for stream overlays, see `plugins::stream_output`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct StreamOutputSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for StreamOutputSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7373,
+        }
+    }
+}
+
+/// Where the game assets are installed from. Only set by editing the file,
+/// for when the default download stops working.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct InstallerSettings {
+    /// Client version, one of `installer::PROFILES`.
+    pub version: String,
+    /// Installer URLs or local paths, tried before the built-in mirrors.
+    pub sources: Vec<String>,
+}
+
+impl Default for InstallerSettings {
+    fn default() -> Self {
+        Self {
+            version: "741".to_string(),
+            sources: Vec::new(),
+        }
+    }
+}
+
+/// Local developer console, see `plugins::debug_console`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct DebugConsoleSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for DebugConsoleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7374,
+        }
+    }
+}
+
+/// Where crash reports can be sent, see `crash_report`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CrashReportSettings {
+    /// Reports are POSTed here as plain text when the player chooses to
+    /// upload one. Empty hides the upload button.
+    pub upload_url: String,
+}
+
+pub const MIN_HOTBAR_ROWS: u32 = 1;
+pub const MAX_HOTBAR_ROWS: u32 = 3;
+pub const MIN_HOTBAR_ICON_SIZE: u32 = 32;
+pub const MAX_HOTBAR_ICON_SIZE: u32 = 64;
+
+/// HUD arrangement. The hotbar is docked bottom-right until it is dragged.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LayoutSettings {
+    /// 1 to 3 rows of slots; out of range values in the file are clamped.
+    #[serde(deserialize_with = "deserialize_hotbar_rows")]
+    pub hotbar_rows: u32,
+    /// Slot size in logical pixels, 32 to 64.
+    #[serde(deserialize_with = "deserialize_hotbar_icon_size")]
+    pub hotbar_icon_size: u32,
+    /// Top-left corner in logical pixels, `None` while docked.
+    pub hotbar_position: Option<(f32, f32)>,
+    /// Original client arrangement: chat and hotbar in a bottom panel drawn from
+    /// the gui sheets, inventory and abilities docked on the right.
+    pub classic_hud: bool,
+    /// Switch to `hotbar_combat_panel` while hostiles are on screen and back to
+    /// `hotbar_town_panel` once they're gone.
+    pub hotbar_auto_switch: bool,
+    /// Hotbar pages as `HotbarPanel` values.
+    pub hotbar_town_panel: u8,
+    pub hotbar_combat_panel: u8,
+}
+
+fn deserialize_hotbar_rows<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+    let rows = <u32 as serde::Deserialize>::deserialize(d)?;
+    Ok(rows.clamp(MIN_HOTBAR_ROWS, MAX_HOTBAR_ROWS))
+}
+
+fn deserialize_hotbar_icon_size<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+    let size = <u32 as serde::Deserialize>::deserialize(d)?;
+    Ok(size.clamp(MIN_HOTBAR_ICON_SIZE, MAX_HOTBAR_ICON_SIZE))
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            hotbar_rows: 1,
+            hotbar_icon_size: 44,
+            hotbar_position: None,
+            classic_hud: false,
+            hotbar_auto_switch: false,
+            hotbar_town_panel: 0,
+            hotbar_combat_panel: 3,
+        }
+    }
+}
+
+/// Masks listed words in players' chat, see `chat_filter`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ChatFilterSettings {
+    pub enabled: bool,
+    /// Matched as whole words, case-insensitively.
+    pub words: Vec<String>,
+    /// Whether the filter applies, indexed by `ChatChannel`.
+    pub channels: [bool; CHAT_CHANNELS],
+}
+
+impl Default for ChatFilterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            words: DEFAULT_WORDS.iter().map(|w| w.to_string()).collect(),
+            channels: [true; CHAT_CHANNELS],
+        }
+    }
+}
+
+impl ChatFilterSettings {
+    /// The masked text, if the filter is on for `channel` and caught something.
+    pub fn apply(&self, channel: Option<ChatChannel>, text: &str) -> Option<String> {
+        let channel = channel?;
+        if !self.enabled || !self.channels[channel as usize] {
+            return None;
+        }
+        mask_words(text, &self.words)
+    }
+}
+
+/// Keyboard and gamepad behaviour that isn't a key binding.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct InputSettings {
+    /// Pressing a new direction only turns the character unless the key is still held
+    /// after this many milliseconds, 0 turns and walks at once.
+    pub turn_tap_ms: u32,
+    pub direction_priority: DirectionPriority,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self {
+            turn_tap_ms: 120,
+            direction_priority: DirectionPriority::Fixed,
+        }
+    }
 }
 
 #[derive(Resource, serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct Settings {
+    /// Schema version, see `settings_migration`.
+    #[serde(default)]
+    pub version: u32,
     pub audio: AudioSettings,
     pub graphics: GraphicsSettings,
     pub gameplay: GameplaySettings,
+    #[serde(default)]
+    pub stream_output: StreamOutputSettings,
+    #[serde(default)]
+    pub debug_console: DebugConsoleSettings,
+    #[serde(default)]
+    pub crash_reports: CrashReportSettings,
+    #[serde(default)]
+    pub installer: InstallerSettings,
+    #[serde(default)]
+    pub layout: LayoutSettings,
+    #[serde(default)]
+    pub input: InputSettings,
+    #[serde(default)]
+    pub chat_filter: ChatFilterSettings,
     pub key_bindings: KeyBindings,
     pub servers: Vec<ServerEntry>,
+    #[serde(default)]
+    pub server_subscriptions: Vec<ServerSubscription>,
     #[serde(skip)]
     pub saved_credentials: Vec<SavedCredential>,
     #[serde(skip)]
     pub hotbars: HashMap<String, HotbarData>,
+    #[serde(skip)]
+    pub window_layouts: HashMap<String, WindowLayout>,
+    #[serde(skip)]
+    pub confirm_items: HashMap<String, BTreeSet<String>>,
+    #[serde(skip)]
+    pub lifetime_stats: HashMap<String, SessionTotals>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -61,31 +485,118 @@ pub struct CharacterProfile {
     pub preview: Option<CharacterPreview>,
     #[serde(default)]
     pub hotbars: HotbarData,
+    #[serde(default)]
+    pub windows: WindowLayout,
+    /// Item names that ask before being used or dropped.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub confirm_items: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "SessionTotals::is_empty")]
+    pub lifetime: SessionTotals,
+}
+
+/// What a character did in one session or, summed up, over all of them. See
+/// `plugins::session_summary`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct SessionTotals {
+    pub sessions: u32,
+    pub seconds_played: u64,
+    /// Experience and gold can go down, from deaths and spending.
+    pub experience: i64,
+    pub gold: i64,
+    pub kills: u32,
+    pub deaths: u32,
+    pub whispers: u32,
+}
+
+impl SessionTotals {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn add(&mut self, other: &SessionTotals) {
+        self.sessions += other.sessions;
+        self.seconds_played += other.seconds_played;
+        self.experience += other.experience;
+        self.gold += other.gold;
+        self.kills += other.kills;
+        self.deaths += other.deaths;
+        self.whispers += other.whispers;
+    }
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: crate::settings_migration::SETTINGS_VERSION,
             audio: AudioSettings {
                 music_volume: 0.5,
                 sfx_volume: 0.7,
+                combat_volume: 1.0,
+                spell_volume: 1.0,
+                ambient_volume: 1.0,
+                output_device: None,
             },
             graphics: GraphicsSettings {
                 xray_size: XRaySize::Medium,
+                xray_mode: XRayMode::Radius,
+                open_interiors: true,
+                attack_telegraphs: false,
                 scale: 1.0,
                 high_quality_scaling: true,
+                render_scaling: RenderScaling::Smooth,
+                gamma: 1.0,
+                debug_overlay: false,
+                potato_mode: false,
+                potato_mode_suggested: false,
+                gpu_backend: GpuBackend::Auto,
+                gpu_preference: GpuPreference::Auto,
+                texture_budget_mb: default_texture_budget_mb(),
             },
             gameplay: GameplaySettings {
                 current_server_id: Some(1),
+                streamer_mode: false,
+                idle_daydream_minutes: default_idle_daydream_minutes(),
+                anti_idle: false,
+                anti_idle_max_minutes: default_anti_idle_max_minutes(),
+                durability_warning_percent: default_durability_warning_percent(),
+                spell_queue_window_ms: default_spell_queue_window_ms(),
+                chat_bubbles: default_chat_bubbles(),
+                mention_highlight: true,
+                mention_sound: true,
+                friends: Vec::new(),
+                notification_policies: default_notification_policies(),
+                auto_pickup: false,
+                auto_pickup_gold: true,
+                auto_pickup_items: Vec::new(),
+                dangerous_dialog_options: default_dangerous_dialog_options(),
+                auto_next_npcs: Vec::new(),
+                menu_shortcuts: Vec::new(),
             },
+            stream_output: StreamOutputSettings::default(),
+            debug_console: DebugConsoleSettings::default(),
+            crash_reports: CrashReportSettings::default(),
+            installer: InstallerSettings::default(),
+            layout: LayoutSettings::default(),
+            input: InputSettings::default(),
+            chat_filter: ChatFilterSettings::default(),
             key_bindings: KeyBindings::default(),
             servers: vec![ServerEntry {
                 id: 1,
                 name: "DA Official".to_string(),
                 address: "da0.kru.com:2610".to_string(),
+                protocol: None,
+                icon: None,
+                news_url: None,
+                source: None,
+                connection: Default::default(),
             }],
+            server_subscriptions: vec![],
             saved_credentials: vec![],
             hotbars: HashMap::new(),
+            window_layouts: HashMap::new(),
+            confirm_items: HashMap::new(),
+            lifetime_stats: HashMap::new(),
         }
     }
 }
@@ -114,12 +625,126 @@ impl Settings {
         self.hotbars.entry(key).or_default().current_panel = panel;
     }
 
+    pub fn get_window_layout(&self, server_id: u32, username: &str) -> WindowLayout {
+        let key = format!("{}:{}", server_id, username);
+        self.window_layouts.get(&key).cloned().unwrap_or_default()
+    }
+
+    pub fn set_window_geometry(
+        &mut self,
+        server_id: u32,
+        username: &str,
+        id: &str,
+        geometry: WindowGeometry,
+    ) {
+        let key = format!("{}:{}", server_id, username);
+        self.window_layouts
+            .entry(key)
+            .or_default()
+            .insert(id.to_string(), geometry);
+    }
+
+    pub fn clear_window_layout(&mut self, server_id: u32, username: &str) {
+        let key = format!("{}:{}", server_id, username);
+        self.window_layouts.remove(&key);
+    }
+
+    pub fn get_lifetime_stats(&self, server_id: u32, username: &str) -> SessionTotals {
+        let key = format!("{}:{}", server_id, username);
+        self.lifetime_stats.get(&key).copied().unwrap_or_default()
+    }
+
+    /// Adds a finished session to the character's lifetime totals and returns them.
+    pub fn add_session_stats(
+        &mut self,
+        server_id: u32,
+        username: &str,
+        session: &SessionTotals,
+    ) -> SessionTotals {
+        let key = format!("{}:{}", server_id, username);
+        let lifetime = self.lifetime_stats.entry(key).or_default();
+        lifetime.add(session);
+        *lifetime
+    }
+
+    pub fn get_confirm_items(&self, server_id: u32, username: &str) -> BTreeSet<String> {
+        let key = format!("{}:{}", server_id, username);
+        self.confirm_items.get(&key).cloned().unwrap_or_default()
+    }
+
+    pub fn item_needs_confirm(&self, server_id: u32, username: &str, item_name: &str) -> bool {
+        let key = format!("{}:{}", server_id, username);
+        self.confirm_items
+            .get(&key)
+            .is_some_and(|items| items.contains(item_name))
+    }
+
+    /// Flags or unflags an item, returning whether it now asks for confirmation.
+    pub fn toggle_item_confirm(&mut self, server_id: u32, username: &str, item_name: &str) -> bool {
+        let key = format!("{}:{}", server_id, username);
+        let items = self.confirm_items.entry(key).or_default();
+        if items.remove(item_name) {
+            false
+        } else {
+            items.insert(item_name.to_string());
+            true
+        }
+    }
+
     pub fn to_sync_message(&self) -> CoreToUi {
         CoreToUi::SettingsSync {
             xray_size: self.graphics.xray_size as u8,
+            xray_mode: self.graphics.xray_mode as u8,
+            open_interiors: self.graphics.open_interiors,
+            attack_telegraphs: self.graphics.attack_telegraphs,
             sfx_volume: self.audio.sfx_volume,
             music_volume: self.audio.music_volume,
+            combat_volume: self.audio.combat_volume,
+            spell_volume: self.audio.spell_volume,
+            ambient_volume: self.audio.ambient_volume,
+            audio_device: self.audio.output_device.clone().unwrap_or_default(),
             scale: self.graphics.scale,
+            render_scaling: self.graphics.render_scaling as u8,
+            gamma: self.graphics.gamma,
+            debug_overlay: self.graphics.debug_overlay,
+            potato_mode: self.graphics.potato_mode,
+            gpu_backend: self.graphics.gpu_backend as u8,
+            gpu_preference: self.graphics.gpu_preference as u8,
+            texture_budget_mb: self.graphics.texture_budget_mb,
+            streamer_mode: self.gameplay.streamer_mode,
+            stream_output_enabled: self.stream_output.enabled,
+            stream_output_port: self.stream_output.port,
+            debug_console_enabled: self.debug_console.enabled,
+            debug_console_port: self.debug_console.port,
+            idle_daydream_minutes: self.gameplay.idle_daydream_minutes,
+            anti_idle: self.gameplay.anti_idle,
+            anti_idle_max_minutes: self.gameplay.anti_idle_max_minutes,
+            durability_warning_percent: self.gameplay.durability_warning_percent,
+            spell_queue_window_ms: self.gameplay.spell_queue_window_ms,
+            chat_bubbles: self.gameplay.chat_bubbles,
+            mention_highlight: self.gameplay.mention_highlight,
+            mention_sound: self.gameplay.mention_sound,
+            chat_filter: self.chat_filter.enabled,
+            chat_filter_words: self.chat_filter.words.join(", "),
+            chat_filter_channels: self.chat_filter.channels.to_vec(),
+            auto_pickup: self.gameplay.auto_pickup,
+            auto_pickup_gold: self.gameplay.auto_pickup_gold,
+            dangerous_dialog_options: self.gameplay.dangerous_dialog_options.join(", "),
+            notification_policies: self
+                .gameplay
+                .notification_policies
+                .iter()
+                .map(|policy| *policy as u8)
+                .collect(),
+            hotbar_rows: self.layout.hotbar_rows,
+            hotbar_icon_size: self.layout.hotbar_icon_size,
+            hotbar_position: self.layout.hotbar_position,
+            classic_hud: self.layout.classic_hud,
+            hotbar_auto_switch: self.layout.hotbar_auto_switch,
+            hotbar_town_panel: self.layout.hotbar_town_panel,
+            hotbar_combat_panel: self.layout.hotbar_combat_panel,
+            turn_tap_ms: self.input.turn_tap_ms,
+            direction_priority: self.input.direction_priority as u8,
             key_bindings: (&self.key_bindings).into(),
         }
     }
@@ -140,6 +765,7 @@ impl Settings {
                 })
                 .collect(),
             login_error,
+            streamer_mode: self.gameplay.streamer_mode,
         }
     }
 }
@@ -6,10 +6,19 @@
 use bevy::prelude::*;
 
 use crate::app_state::AppState;
+use crate::slint_support::chat_history::{ChatHistory, apply_chat_window_requests};
 use crate::slint_support::state_bridge::{
-    SlintUiChannels, apply_core_to_slint, drain_slint_inbound, sync_group_to_slint,
-    sync_installer_to_slint, sync_map_name_to_slint, sync_settings_to_slint,
-    sync_world_labels_to_slint,
+    SlintUiChannels, apply_core_to_slint, drain_slint_inbound, sync_ability_stats_to_slint,
+    sync_anti_idle_to_slint, sync_bank_to_slint, sync_classic_skin_to_slint,
+    sync_combat_log_to_slint, sync_creature_name_to_slint, sync_death_recap_to_slint,
+    sync_dialog_advance_to_slint, sync_dialog_confirm_to_slint, sync_dialog_history_to_slint,
+    sync_group_to_slint, sync_installer_to_slint, sync_item_confirm_to_slint,
+    sync_location_to_slint, sync_map_loading_to_slint, sync_map_name_to_slint,
+    sync_map_pins_to_slint, sync_menu_shortcut_to_slint, sync_notifications_to_slint,
+    sync_outbox_throttle_to_slint, sync_path_preview_to_slint, sync_pet_frame_to_slint,
+    sync_pip_to_slint, sync_settings_to_slint, sync_social_status_to_slint,
+    sync_spell_timers_to_slint, sync_split_stack_to_slint, sync_telegraphs_to_slint,
+    sync_threat_arrows_to_slint, sync_world_cursor_to_slint, sync_world_labels_to_slint,
 };
 use crate::slint_support::{handle_show_self_profile, sync_profile_to_slint};
 
@@ -29,6 +38,7 @@ impl Plugin for SlintBridgePlugin {
             .insert_resource(SlintUiChannels::default())
             .add_message::<SlintDoubleClickEvent>()
             .add_message::<ShowSelfProfileEvent>()
+            .init_resource::<ChatHistory>()
             .add_systems(PreUpdate, drain_slint_inbound)
             .add_systems(
                 OnEnter(AppState::MainMenu),
@@ -58,6 +68,42 @@ impl Plugin for SlintBridgePlugin {
                     sync_group_to_slint
                         .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
                         .run_if(in_state(AppState::InGame)),
+                    sync_split_stack_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_item_confirm_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_dialog_confirm_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_creature_name_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_pip_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_spell_timers_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_combat_log_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_classic_skin_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_world_cursor_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_notifications_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_social_status_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    apply_chat_window_requests
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
                 ),
             )
             .add_systems(
@@ -69,6 +115,50 @@ impl Plugin for SlintBridgePlugin {
                     sync_map_name_to_slint
                         .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
                         .run_if(in_state(AppState::InGame)),
+                    sync_map_loading_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_threat_arrows_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_pet_frame_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_location_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_path_preview_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_telegraphs_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_map_pins_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_anti_idle_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>),
+                    sync_outbox_throttle_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_death_recap_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_ability_stats_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_dialog_history_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_dialog_advance_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_menu_shortcut_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
+                    sync_bank_to_slint
+                        .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
+                        .run_if(in_state(AppState::InGame)),
                     sync_installer_to_slint
                         .run_if(resource_exists::<crate::slint_support::state_bridge::SlintWindow>)
                         .run_if(in_state(AppState::Installing)),
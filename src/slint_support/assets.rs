@@ -52,18 +52,12 @@ impl SlintAssetLoader {
                         let filename = spf_name.to_lowercase().replace(".spf", ".0.ktx2");
                         let full_path = format!("npc/npcbase/{}", filename);
 
-                        if let Some(bytes) = game_files.get_file(&full_path) {
-                            let (w, h, data) = rendering::texture::Texture::load_ktx2(&bytes)
-                                .map_err(|e| e.to_string())?;
-                            let mut pixel_buffer = SharedPixelBuffer::<Rgba8Pixel>::new(w, h);
-                            pixel_buffer
-                                .make_mut_slice()
-                                .copy_from_slice(bytemuck::cast_slice(&data));
+                        if let Ok(image) = Self::load_spf_frame(game_files, &full_path) {
                             debug!(
                                 "NPC portrait {} (name: {}) loaded from metafile path {}",
                                 sprite_id, npc_name, full_path
                             );
-                            return Ok(Image::from_rgba8(pixel_buffer));
+                            return Ok(image);
                         }
                     }
                 }
@@ -193,6 +187,20 @@ impl SlintAssetLoader {
         SlintAssetLoader::decode_epf_to_slint_with_palette(game_files, &epf_path, 0, &palette_rgba)
     }
 
+    /// Loads one SPF frame as the installer extracted it (`<sheet>.<frame>.ktx2`).
+    pub fn load_spf_frame(game_files: &GameFiles, path: &str) -> Result<Image, String> {
+        let bytes = game_files
+            .get_file(path)
+            .ok_or_else(|| format!("SPF frame not found: {}", path))?;
+        let (w, h, data) =
+            rendering::texture::Texture::load_ktx2(&bytes).map_err(|e| e.to_string())?;
+        let mut pixel_buffer = SharedPixelBuffer::<Rgba8Pixel>::new(w, h);
+        pixel_buffer
+            .make_mut_slice()
+            .copy_from_slice(bytemuck::cast_slice(&data));
+        Ok(Image::from_rgba8(pixel_buffer))
+    }
+
     fn decode_epf_to_slint(
         game_files: &GameFiles,
         epf_path: &str,
@@ -287,3 +295,38 @@ impl SlintAssetLoader {
         Ok(Image::from_rgba8(pixel_buffer))
     }
 }
+
+/// Decodes a PNG from disk, e.g. a cached server list icon. Anything unreadable
+/// is left to the caller's placeholder.
+pub fn load_png_image(path: &std::path::Path) -> Option<Image> {
+    let file = std::io::BufReader::new(std::fs::File::open(path).ok()?);
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let mut buf = vec![0; reader.output_buffer_size()?];
+    let info = reader.next_frame(&mut buf).ok()?;
+
+    let mut pixel_buffer = SharedPixelBuffer::<Rgba8Pixel>::new(info.width, info.height);
+    let pixels = pixel_buffer.make_mut_slice();
+    let channels = info.color_type.samples();
+    for (pixel, src) in pixels.iter_mut().zip(buf.chunks_exact(channels)) {
+        *pixel = match *src {
+            [v] => Rgba8Pixel {
+                r: v,
+                g: v,
+                b: v,
+                a: 255,
+            },
+            [v, a] => Rgba8Pixel {
+                r: v,
+                g: v,
+                b: v,
+                a,
+            },
+            [r, g, b] => Rgba8Pixel { r, g, b, a: 255 },
+            [r, g, b, a] => Rgba8Pixel { r, g, b, a },
+            _ => return None,
+        };
+    }
+    Some(Image::from_rgba8(pixel_buffer))
+}
@@ -71,6 +71,32 @@ pub fn wire_game_callbacks(slint_app: &MainWindow, tx: Sender<UiToCore>) {
         });
     }
 
+    // Dialog paging
+    {
+        let tx = tx.clone();
+        npc_dialog.on_next_request(move || {
+            let _ = tx.send(UiToCore::DialogNext);
+        });
+    }
+    {
+        let tx = tx.clone();
+        npc_dialog.on_skip_request(move || {
+            let _ = tx.send(UiToCore::DialogSkip);
+        });
+    }
+    {
+        let tx = tx.clone();
+        npc_dialog.on_auto_next_toggled(move |enabled| {
+            let _ = tx.send(UiToCore::DialogAutoNext { enabled });
+        });
+    }
+    {
+        let tx = tx.clone();
+        npc_dialog.on_shortcut_toggled(move || {
+            let _ = tx.send(UiToCore::MenuShortcutToggle);
+        });
+    }
+
     // Unequip
     {
         let tx = tx.clone();
@@ -200,6 +226,357 @@ pub fn wire_game_callbacks(slint_app: &MainWindow, tx: Sender<UiToCore>) {
         });
     }
 
+    // Split-stack dialog
+    {
+        let tx = tx.clone();
+        game_state.on_split_stack_confirm(move |count| {
+            let _ = tx.send(UiToCore::SplitStackConfirm {
+                count: count.max(1) as u32,
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_split_stack_cancel(move || {
+            let _ = tx.send(UiToCore::SplitStackCancel);
+        });
+    }
+
+    // Flagged item confirmation
+    {
+        let tx = tx.clone();
+        game_state.on_item_context_menu(move |slot, x, y| {
+            let _ = tx.send(UiToCore::ItemContextMenu {
+                slot: slot as u8,
+                x,
+                y,
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_item_confirm_accept(move || {
+            let _ = tx.send(UiToCore::ItemConfirmAccept);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_item_confirm_cancel(move || {
+            let _ = tx.send(UiToCore::ItemConfirmCancel);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_dialog_confirm_accept(move || {
+            let _ = tx.send(UiToCore::DialogConfirmAccept);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_dialog_confirm_cancel(move || {
+            let _ = tx.send(UiToCore::DialogConfirmCancel);
+        });
+    }
+
+    // Location readout click
+    {
+        let tx = tx.clone();
+        game_state.on_copy_location(move |text| {
+            let _ = tx.send(UiToCore::CopyLocation {
+                text: text.to_string(),
+            });
+        });
+    }
+
+    // Creature name prompt
+    {
+        let tx = tx.clone();
+        game_state.on_creature_name_confirm(move |sprite, name| {
+            let _ = tx.send(UiToCore::CreatureNameSubmit {
+                sprite: sprite as u16,
+                name: name.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_creature_name_cancel(move || {
+            let _ = tx.send(UiToCore::CreatureNameCancel);
+        });
+    }
+
+    // Map pin prompt
+    {
+        let tx = tx.clone();
+        game_state.on_map_pin_confirm(move |x, y, name| {
+            let _ = tx.send(UiToCore::MapPinSubmit {
+                x: x as u16,
+                y: y as u16,
+                name: name.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_map_pin_cancel(move || {
+            let _ = tx.send(UiToCore::MapPinCancel);
+        });
+    }
+
+    // Spell timers
+    {
+        let tx = tx.clone();
+        game_state.on_spell_context_menu(move |slot, x, y| {
+            let _ = tx.send(UiToCore::SpellContextMenu {
+                slot: slot as u8,
+                x,
+                y,
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_spell_timer_confirm(move |duration| {
+            let _ = tx.send(UiToCore::SpellTimerSubmit {
+                duration: duration.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_spell_timer_prompt_cancel(move || {
+            let _ = tx.send(UiToCore::SpellTimerPromptCancel);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_spell_timer_cancel(move |id| {
+            let _ = tx.send(UiToCore::SpellTimerCancel { id: id as u32 });
+        });
+    }
+
+    // Combat log
+    {
+        let tx = tx.clone();
+        game_state.on_combat_log_filter(move |source, target| {
+            let _ = tx.send(UiToCore::CombatLogFilter {
+                source: source.to_string(),
+                target: target.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_entity_inspector_search(move |query| {
+            let _ = tx.send(UiToCore::EntityInspectorSearch {
+                query: query.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_entity_inspector_close(move || {
+            let _ = tx.send(UiToCore::EntityInspectorClose);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_archive_browser_search(move |query| {
+            let _ = tx.send(UiToCore::ArchiveBrowserSearch {
+                query: query.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_archive_browser_select(move |path| {
+            let _ = tx.send(UiToCore::ArchiveBrowserSelect {
+                path: path.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_archive_browser_view(move |frame, dye, row| {
+            let _ = tx.send(UiToCore::ArchiveBrowserView {
+                frame: frame.max(0) as usize,
+                dye: dye.clamp(0, 255) as u8,
+                row: usize::try_from(row).ok(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_archive_browser_close(move || {
+            let _ = tx.send(UiToCore::ArchiveBrowserClose);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_combat_log_clear(move || {
+            let _ = tx.send(UiToCore::CombatLogClear);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_combat_log_export(move || {
+            let _ = tx.send(UiToCore::CombatLogExport);
+        });
+    }
+
+    // Conversation history
+    {
+        let tx = tx.clone();
+        game_state.on_dialog_history_search(move |text| {
+            let _ = tx.send(UiToCore::DialogHistorySearch {
+                text: text.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_dialog_history_clear(move || {
+            let _ = tx.send(UiToCore::DialogHistoryClear);
+        });
+    }
+
+    // Bank viewer
+    {
+        let tx = tx.clone();
+        game_state.on_bank_search(move |text| {
+            let _ = tx.send(UiToCore::BankSearch {
+                text: text.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_bank_sort(move |by_amount| {
+            let _ = tx.send(UiToCore::BankSort { by_amount });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_bank_withdraw_all(move |name| {
+            let _ = tx.send(UiToCore::BankWithdrawAll {
+                name: name.to_string(),
+            });
+        });
+    }
+
+    // Ability statistics
+    {
+        let tx = tx.clone();
+        game_state.on_ability_stats_reset(move || {
+            let _ = tx.send(UiToCore::AbilityStatsReset);
+        });
+    }
+
+    // Notifications
+    {
+        let tx = tx.clone();
+        game_state.on_ui_hidden_changed(move |hidden| {
+            let _ = tx.send(UiToCore::UiHiddenChange { hidden });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_notification_dismiss(move |id| {
+            let _ = tx.send(UiToCore::NotificationDismiss { id: id as u32 });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_notification_history_opened(move || {
+            let _ = tx.send(UiToCore::NotificationHistoryOpened);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_notification_history_clear(move || {
+            let _ = tx.send(UiToCore::NotificationHistoryClear);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_set_social_status(move |status| {
+            let _ = tx.send(UiToCore::SocialStatusChange {
+                status: status as u8,
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_toggle_friend(move |name| {
+            let _ = tx.send(UiToCore::FriendToggle {
+                name: name.to_string(),
+            });
+        });
+    }
+
+    // === Pet frame callbacks ===
+    {
+        let tx = tx.clone();
+        game_state.on_pet_frame_clicked(move || {
+            let _ = tx.send(UiToCore::PetFrameClick);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_pet_release(move || {
+            let _ = tx.send(UiToCore::PetRelease);
+        });
+    }
+
+    // Chat item link clicked
+    {
+        let tx = tx.clone();
+        game_state.on_show_item_link(move |name: slint::SharedString| {
+            let _ = tx.send(UiToCore::ShowItemLink {
+                name: name.to_string(),
+            });
+        });
+    }
+
+    // === Chat history callbacks ===
+    {
+        let tx = tx.clone();
+        game_state.on_chat_load_older(move || {
+            let _ = tx.send(UiToCore::ChatLoadOlder);
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_chat_scrolled_back(move |scrolled_back| {
+            let _ = tx.send(UiToCore::ChatScrolledBack { scrolled_back });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_chat_reveal(move |row| {
+            let _ = tx.send(UiToCore::ChatReveal { row: row as usize });
+        });
+    }
+
+    // === HUD window callbacks ===
+    {
+        let tx = tx.clone();
+        game_state.on_window_geometry_changed(move |id: slint::SharedString, rect| {
+            let _ = tx.send(UiToCore::WindowGeometryChange {
+                id: id.to_string(),
+                geometry: crate::slint_support::window_layout::to_geometry(&rect),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        game_state.on_watch_group_member(move |name: slint::SharedString| {
+            let _ = tx.send(UiToCore::PipWatch {
+                name: (!name.is_empty()).then(|| name.to_string()),
+            });
+        });
+    }
+
     // === Group callbacks ===
     {
         let tx = tx.clone();
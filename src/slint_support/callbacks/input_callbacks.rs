@@ -1,7 +1,11 @@
 //! Input-related callback wiring for Slint UI.
 
+use std::cell::Cell;
+use std::rc::Rc;
 use std::sync::Arc;
 
+use bevy::input::keyboard::KeyCode;
+use i_slint_core::items::PointerEventButton;
 use slint::ComponentHandle;
 
 use crate::slint_support::input_bridge::{
@@ -58,9 +62,22 @@ pub fn wire_input_callbacks(
         });
     }
 
-    // Rebind key event
+    // Text field focused: the field now gets the key releases, so drop held keys
+    {
+        let queue = Arc::clone(key_event_queue);
+        input_bridge.on_text_input_focused(move || {
+            if let Ok(mut guard) = queue.lock() {
+                guard.push_back(QueuedKeyEvent::release_all());
+            }
+        });
+    }
+
+    // Rebind key event. A modifier press waits for the rest of the chord and
+    // binds on its own only if it is released first.
+    let pending_modifier = Rc::new(Cell::new(None::<KeyCode>));
     {
         let slint_app_weak = slint_app.as_weak();
+        let pending_modifier = Rc::clone(&pending_modifier);
         input_bridge.on_rebind_key_event(move |event| {
             let Some(strong) = slint_app_weak.upgrade() else {
                 return false;
@@ -69,30 +86,72 @@ pub fn wire_input_callbacks(
             let settings_global = strong.global::<SettingsState>();
 
             if event.text.as_str() == "\u{1b}" {
+                pending_modifier.set(None);
                 settings_global.set_is_rebinding(false);
                 settings_global.set_rebinding_action(slint::SharedString::from(""));
                 return true;
             }
 
-            if let Some(code) = input_bridge::slint_key_to_keycode(&event) {
-                let mut key_string = String::new();
-
-                if event.modifiers.control {
-                    key_string.push_str("Ctrl+");
-                }
-                if event.modifiers.shift {
-                    key_string.push_str("Shift+");
-                }
-                if event.modifiers.alt {
-                    key_string.push_str("Alt+");
-                }
-
-                key_string.push_str(&input_bridge::keycode_to_string(code));
-                settings_global.invoke_rebind_key(slint::SharedString::from(key_string));
+            let Some(code) = input_bridge::slint_key_to_keycode(&event) else {
+                return false;
+            };
+            if input_bridge::is_modifier_key(code) {
+                pending_modifier.set(Some(code));
                 return true;
             }
 
-            false
+            pending_modifier.set(None);
+            let key = input_bridge::binding_string(
+                &input_bridge::keycode_to_string(code),
+                &event.modifiers,
+            );
+            settings_global.invoke_rebind_key(slint::SharedString::from(key));
+            true
+        });
+    }
+    {
+        let slint_app_weak = slint_app.as_weak();
+        let pending_modifier = Rc::clone(&pending_modifier);
+        input_bridge.on_rebind_key_released(move |event| {
+            let Some(strong) = slint_app_weak.upgrade() else {
+                return false;
+            };
+            let Some(code) = input_bridge::slint_key_to_keycode(&event) else {
+                return false;
+            };
+            if pending_modifier.get() != Some(code) {
+                return false;
+            }
+
+            pending_modifier.set(None);
+            let key = input_bridge::binding_string(
+                &input_bridge::keycode_to_string(code),
+                &event.modifiers,
+            );
+            strong
+                .global::<SettingsState>()
+                .invoke_rebind_key(slint::SharedString::from(key));
+            true
+        });
+    }
+
+    // Side mouse buttons pressed over the binding being captured
+    {
+        let slint_app_weak = slint_app.as_weak();
+        input_bridge.on_rebind_pointer_event(move |event| {
+            let Some(strong) = slint_app_weak.upgrade() else {
+                return;
+            };
+            let key = match event.button {
+                PointerEventButton::Back => "Mouse4",
+                PointerEventButton::Forward => "Mouse5",
+                _ => return,
+            };
+            pending_modifier.set(None);
+            let key = input_bridge::binding_string(key, &event.modifiers);
+            strong
+                .global::<SettingsState>()
+                .invoke_rebind_key(slint::SharedString::from(key));
         });
     }
 
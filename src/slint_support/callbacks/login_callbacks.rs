@@ -50,10 +50,11 @@ pub fn wire_login_callbacks(slint_app: &MainWindow, tx: Sender<UiToCore>) {
     // Add server
     {
         let tx = tx.clone();
-        login_bridge.on_add_server(move |name, address| {
+        login_bridge.on_add_server(move |name, address, news_url| {
             let server = ServerNoId {
                 name: name.to_string(),
                 address: address.to_string(),
+                news_url: news_url.to_string(),
             };
             let _ = tx.send(UiToCore::ServersAdd { server });
         });
@@ -62,11 +63,12 @@ pub fn wire_login_callbacks(slint_app: &MainWindow, tx: Sender<UiToCore>) {
     // Edit server
     {
         let tx = tx.clone();
-        login_bridge.on_edit_server(move |id, name, address| {
+        login_bridge.on_edit_server(move |id, name, address, news_url| {
             let server = ServerWithId {
                 id: id as u32,
                 name: name.to_string(),
                 address: address.to_string(),
+                news_url: news_url.to_string(),
             };
             let _ = tx.send(UiToCore::ServersEdit { server });
         });
@@ -80,6 +82,82 @@ pub fn wire_login_callbacks(slint_app: &MainWindow, tx: Sender<UiToCore>) {
         });
     }
 
+    // Community server lists
+    {
+        let tx = tx.clone();
+        login_bridge.on_add_server_subscription(move |url| {
+            let _ = tx.send(UiToCore::ServerSubscriptionAdd {
+                url: url.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        login_bridge.on_remove_server_subscription(move |url| {
+            let _ = tx.send(UiToCore::ServerSubscriptionRemove {
+                url: url.to_string(),
+            });
+        });
+    }
+
+    // Crash report from a previous run
+    {
+        let tx = tx.clone();
+        login_bridge.on_copy_crash_report(move |path| {
+            let _ = tx.send(UiToCore::CrashReportCopy {
+                path: path.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        login_bridge.on_upload_crash_report(move |path| {
+            let _ = tx.send(UiToCore::CrashReportUpload {
+                path: path.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        login_bridge.on_dismiss_crash_report(move |path| {
+            let _ = tx.send(UiToCore::CrashReportDismiss {
+                path: path.to_string(),
+            });
+        });
+    }
+
+    // Unreadable settings.toml
+    {
+        let tx = tx.clone();
+        login_bridge.on_recover_settings(move |restore_backup| {
+            let _ = tx.send(UiToCore::SettingsRecover { restore_backup });
+        });
+    }
+
+    // Stop waiting on a full server
+    {
+        let tx = tx.clone();
+        login_bridge.on_cancel_login_retry(move || {
+            let _ = tx.send(UiToCore::LoginRetryCancel);
+        });
+    }
+
+    // Master password for the encrypted credential store
+    {
+        let tx = tx.clone();
+        login_bridge.on_vault_unlock(move |password| {
+            let _ = tx.send(UiToCore::VaultUnlock {
+                password: password.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        login_bridge.on_vault_cancel(move || {
+            let _ = tx.send(UiToCore::VaultCancel);
+        });
+    }
+
     // Request snapshot (on MainWindow, not login bridge)
     {
         let tx = tx.clone();
@@ -20,6 +20,30 @@ pub fn wire_settings_callbacks(slint_app: &MainWindow, tx: Sender<UiToCore>) {
         });
     }
 
+    // X-ray mode changed
+    {
+        let tx = tx.clone();
+        settings_state.on_xray_mode_changed(move |mode| {
+            let _ = tx.send(UiToCore::XRayModeChange { mode: mode as u8 });
+        });
+    }
+
+    // Open interiors toggled
+    {
+        let tx = tx.clone();
+        settings_state.on_open_interiors_changed(move |enabled| {
+            let _ = tx.send(UiToCore::OpenInteriorsChange { enabled });
+        });
+    }
+
+    // Attack telegraphs toggled
+    {
+        let tx = tx.clone();
+        settings_state.on_attack_telegraphs_changed(move |enabled| {
+            let _ = tx.send(UiToCore::AttackTelegraphsChange { enabled });
+        });
+    }
+
     // SFX volume changed
     {
         let tx = tx.clone();
@@ -31,6 +55,27 @@ pub fn wire_settings_callbacks(slint_app: &MainWindow, tx: Sender<UiToCore>) {
         });
     }
 
+    // Per-category sound volume changed
+    {
+        let tx = tx.clone();
+        settings_state.on_category_volume_changed(move |category, vol| {
+            let _ = tx.send(UiToCore::CategoryVolumeChange {
+                category: category as u8,
+                volume: vol,
+            });
+        });
+    }
+
+    // Audio output device changed
+    {
+        let tx = tx.clone();
+        settings_state.on_audio_device_changed(move |device| {
+            let _ = tx.send(UiToCore::AudioDeviceChange {
+                device: device.to_string(),
+            });
+        });
+    }
+
     // Music volume changed
     {
         let tx = tx.clone();
@@ -50,6 +95,270 @@ pub fn wire_settings_callbacks(slint_app: &MainWindow, tx: Sender<UiToCore>) {
         });
     }
 
+    // Render scaling filter changed
+    {
+        let tx = tx.clone();
+        settings_state.on_render_scaling_changed(move |mode| {
+            let _ = tx.send(UiToCore::RenderScalingChange { mode: mode as u8 });
+        });
+    }
+
+    // Brightness (gamma) changed
+    {
+        let tx = tx.clone();
+        settings_state.on_gamma_changed(move |gamma| {
+            let _ = tx.send(UiToCore::GammaChange { gamma });
+        });
+    }
+
+    // Debug overlay allowed
+    {
+        let tx = tx.clone();
+        settings_state.on_debug_overlay_enabled_changed(move |enabled| {
+            let _ = tx.send(UiToCore::DebugOverlayChange { enabled });
+        });
+    }
+
+    // Potato mode
+    {
+        let tx = tx.clone();
+        settings_state.on_potato_mode_changed(move |enabled| {
+            let _ = tx.send(UiToCore::PotatoModeChange { enabled });
+        });
+    }
+
+    // GPU adapter
+    {
+        let tx = tx.clone();
+        settings_state.on_gpu_adapter_changed(move |backend, preference| {
+            let _ = tx.send(UiToCore::GpuAdapterChange {
+                backend: backend as u8,
+                preference: preference as u8,
+            });
+        });
+    }
+
+    // Texture memory budget
+    {
+        let tx = tx.clone();
+        settings_state.on_texture_budget_changed(move |mb| {
+            let _ = tx.send(UiToCore::TextureBudgetChange {
+                mb: mb.clamp(0, 1024) as u32,
+            });
+        });
+    }
+
+    // Streamer mode
+    {
+        let tx = tx.clone();
+        settings_state.on_streamer_mode_changed(move |enabled| {
+            let _ = tx.send(UiToCore::StreamerModeChange { enabled });
+        });
+    }
+
+    // Stream overlay feed
+    {
+        let tx = tx.clone();
+        settings_state.on_stream_output_enabled_changed(move |enabled| {
+            let _ = tx.send(UiToCore::StreamOutputChange { enabled });
+        });
+    }
+
+    // Developer console
+    {
+        let tx = tx.clone();
+        settings_state.on_debug_console_enabled_changed(move |enabled| {
+            let _ = tx.send(UiToCore::DebugConsoleChange { enabled });
+        });
+    }
+
+    // Idle timeout before Daydreaming
+    {
+        let tx = tx.clone();
+        settings_state.on_idle_daydream_minutes_changed(move |minutes| {
+            let _ = tx.send(UiToCore::IdleDaydreamChange {
+                minutes: minutes.max(0) as u32,
+            });
+        });
+    }
+
+    // Anti-idle
+    {
+        let tx = tx.clone();
+        settings_state.on_anti_idle_changed(move |enabled, max_minutes| {
+            let _ = tx.send(UiToCore::AntiIdleChange {
+                enabled,
+                max_minutes: max_minutes.max(0) as u32,
+            });
+        });
+    }
+
+    // Low durability warning threshold
+    {
+        let tx = tx.clone();
+        settings_state.on_durability_warning_percent_changed(move |percent| {
+            let _ = tx.send(UiToCore::DurabilityWarningChange {
+                percent: percent.clamp(0, 100) as u32,
+            });
+        });
+    }
+
+    // Spell queue window
+    {
+        let tx = tx.clone();
+        settings_state.on_spell_queue_window_changed(move |ms| {
+            let _ = tx.send(UiToCore::SpellQueueWindowChange {
+                ms: ms.clamp(0, 1000) as u32,
+            });
+        });
+    }
+
+    // Tap-to-turn threshold
+    {
+        let tx = tx.clone();
+        settings_state.on_turn_tap_changed(move |ms| {
+            let _ = tx.send(UiToCore::TurnTapChange {
+                ms: ms.clamp(0, 500) as u32,
+            });
+        });
+    }
+
+    // Direction priority
+    {
+        let tx = tx.clone();
+        settings_state.on_direction_priority_changed(move |mode| {
+            let _ = tx.send(UiToCore::DirectionPriorityChange { mode: mode as u8 });
+        });
+    }
+
+    // Chat bubbles
+    {
+        let tx = tx.clone();
+        settings_state.on_chat_bubbles_changed(move |enabled| {
+            let _ = tx.send(UiToCore::ChatBubblesChange { enabled });
+        });
+    }
+
+    // Mentions
+    {
+        let tx = tx.clone();
+        settings_state.on_mention_alerts_changed(move |highlight, sound| {
+            let _ = tx.send(UiToCore::MentionAlertsChange { highlight, sound });
+        });
+    }
+
+    // Chat filter
+    {
+        let tx = tx.clone();
+        settings_state.on_chat_filter_changed(move |enabled, words| {
+            let _ = tx.send(UiToCore::ChatFilterChange {
+                enabled,
+                words: words.to_string(),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        settings_state.on_chat_filter_channel_changed(move |channel, enabled| {
+            let _ = tx.send(UiToCore::ChatFilterChannelChange {
+                channel: channel as u8,
+                enabled,
+            });
+        });
+    }
+
+    // Auto-pickup
+    {
+        let tx = tx.clone();
+        settings_state.on_auto_pickup_changed(move |enabled, gold| {
+            let _ = tx.send(UiToCore::AutoPickupChange { enabled, gold });
+        });
+    }
+
+    // Dangerous NPC options
+    {
+        let tx = tx.clone();
+        settings_state.on_dangerous_dialog_options_changed(move |options| {
+            let _ = tx.send(UiToCore::DangerousDialogOptionsChange {
+                options: options.to_string(),
+            });
+        });
+    }
+
+    // Map pin sharing
+    {
+        let tx = tx.clone();
+        settings_state.on_map_pins_export(move || {
+            let _ = tx.send(UiToCore::MapPinsExport);
+        });
+    }
+    {
+        let tx = tx.clone();
+        settings_state.on_map_pins_import(move || {
+            let _ = tx.send(UiToCore::MapPinsImport);
+        });
+    }
+
+    // Hotbar rows, size and position
+    {
+        let tx = tx.clone();
+        settings_state.on_hotbar_layout_changed(move |rows, icon_size| {
+            let _ = tx.send(UiToCore::HotbarLayoutChange {
+                rows: rows.max(1) as u32,
+                icon_size: icon_size.max(0) as u32,
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        settings_state.on_hotbar_moved(move |x, y| {
+            let _ = tx.send(UiToCore::HotbarMoved {
+                position: Some((x, y)),
+            });
+        });
+    }
+    {
+        let tx = tx.clone();
+        settings_state.on_hotbar_dock(move || {
+            let _ = tx.send(UiToCore::HotbarMoved { position: None });
+        });
+    }
+    {
+        let tx = tx.clone();
+        settings_state.on_hotbar_auto_switch_changed(move |enabled, town, combat| {
+            let _ = tx.send(UiToCore::HotbarAutoSwitchChange {
+                enabled,
+                town_panel: town.clamp(0, 5) as u8,
+                combat_panel: combat.clamp(0, 5) as u8,
+            });
+        });
+    }
+
+    // Notification policy per social status
+    {
+        let tx = tx.clone();
+        settings_state.on_notification_policy_changed(move |status, policy| {
+            let _ = tx.send(UiToCore::NotificationPolicyChange {
+                status: status as u8,
+                policy: policy as u8,
+            });
+        });
+    }
+
+    // Classic HUD
+    {
+        let tx = tx.clone();
+        settings_state.on_classic_hud_changed(move |enabled| {
+            let _ = tx.send(UiToCore::ClassicHudChange { enabled });
+        });
+    }
+    {
+        let tx = tx.clone();
+        settings_state.on_reset_window_layout(move || {
+            let _ = tx.send(UiToCore::WindowLayoutReset);
+        });
+    }
+
     // Start rebind
     {
         let slint_app_weak = slint_app.as_weak();
@@ -95,6 +404,17 @@ pub fn wire_settings_callbacks(slint_app: &MainWindow, tx: Sender<UiToCore>) {
         });
     }
 
+    // Hold or toggle
+    {
+        let tx = tx.clone();
+        settings_state.on_key_press_mode_changed(move |action, mode| {
+            let _ = tx.send(UiToCore::KeyPressModeChange {
+                action: action.to_string(),
+                mode: mode as u8,
+            });
+        });
+    }
+
     // Cancel rebind
     {
         let slint_app_weak = slint_app.as_weak();
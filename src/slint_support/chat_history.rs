@@ -0,0 +1,234 @@
+//! Chat history behind the chat log.
+//!
+//! The bridge keeps the last [`CHAT_HISTORY_LINES`] lines in a ring buffer,
+//! while the `chat-messages` model only holds a window of them: the newest
+//! [`CHAT_WINDOW_LINES`] while the log follows the bottom, growing a page at
+//! a time as the player scrolls up. The chat log's `ListView` then only
+//! creates the rows on screen, so a long session stays cheap to render.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use slint::{Model, VecModel};
+
+use super::model_diff::{as_vec_model, ensure_vec_model};
+use super::state_bridge::{SlintWindow, parse_color_hex};
+use crate::webui::ipc::UiToCore;
+use crate::webui::plugin::UiInbound;
+
+/// Lines kept for scrolling back.
+pub const CHAT_HISTORY_LINES: usize = 5000;
+/// Lines in the model while the log follows the bottom.
+pub const CHAT_WINDOW_LINES: usize = 200;
+/// Older lines added to the model each time the log is scrolled to its top.
+const CHAT_PAGE_LINES: usize = 200;
+/// Lines kept in the chat log's Mentions tab.
+const MENTION_LINES: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct ChatLine {
+    pub text: String,
+    /// Hex color, e.g. `#d0d0d0`.
+    pub color: String,
+    /// Shown instead of the text until clicked.
+    pub concealed: Option<String>,
+    pub links: Vec<String>,
+    /// Names the player's character.
+    pub mention: bool,
+}
+
+impl ChatLine {
+    fn to_row(&self) -> crate::ChatMessage {
+        let links: Vec<slint::SharedString> =
+            self.links.iter().map(|l| l.as_str().into()).collect();
+        crate::ChatMessage {
+            text: self.text.as_str().into(),
+            color: parse_color_hex(&self.color),
+            concealed: self.concealed.is_some(),
+            concealed_text: self.concealed.as_deref().unwrap_or_default().into(),
+            links: slint::ModelRc::new(VecModel::from(links)),
+            mention: self.mention,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ChatHistory {
+    lines: VecDeque<ChatLine>,
+    /// Id of `lines[0]`; ids keep counting up as old lines are dropped.
+    first_id: usize,
+    /// Id of the line in the model's first row.
+    window_start: usize,
+    /// The log is scrolled up, so the window isn't trimmed to the newest lines.
+    scrolled_back: bool,
+}
+
+impl ChatHistory {
+    fn end_id(&self) -> usize {
+        self.first_id + self.lines.len()
+    }
+
+    /// Whether there are lines before the window to load.
+    pub fn has_older(&self) -> bool {
+        self.window_start > self.first_id
+    }
+
+    pub fn append(&mut self, model: &VecModel<crate::ChatMessage>, new_lines: Vec<ChatLine>) {
+        for line in new_lines {
+            model.push(line.to_row());
+            self.lines.push_back(line);
+        }
+        while self.lines.len() > CHAT_HISTORY_LINES {
+            self.lines.pop_front();
+            self.first_id += 1;
+        }
+        // Rows whose lines fell out of the history
+        while self.window_start < self.first_id && model.row_count() > 0 {
+            model.remove(0);
+            self.window_start += 1;
+        }
+        if !self.scrolled_back {
+            self.trim(model);
+        }
+    }
+
+    /// Puts the page of lines before the window at the top of the model.
+    pub fn load_older(&mut self, model: &VecModel<crate::ChatMessage>) {
+        let start = self
+            .window_start
+            .saturating_sub(CHAT_PAGE_LINES)
+            .max(self.first_id);
+        for (row, id) in (start..self.window_start).enumerate() {
+            model.insert(row, self.lines[id - self.first_id].to_row());
+        }
+        self.window_start = start;
+    }
+
+    pub fn set_scrolled_back(&mut self, model: &VecModel<crate::ChatMessage>, scrolled_back: bool) {
+        self.scrolled_back = scrolled_back;
+        if !scrolled_back {
+            self.trim(model);
+        }
+    }
+
+    /// Keeps a click-to-reveal line revealed when its row is rebuilt.
+    pub fn reveal(&mut self, row: usize) {
+        let id = self.window_start + row;
+        if let Some(line) = id
+            .checked_sub(self.first_id)
+            .and_then(|index| self.lines.get_mut(index))
+        {
+            line.concealed = None;
+        }
+    }
+
+    /// Drops rows off the top until only the newest window is left.
+    fn trim(&mut self, model: &VecModel<crate::ChatMessage>) {
+        let keep_from = self.end_id().saturating_sub(CHAT_WINDOW_LINES);
+        while self.window_start < keep_from && model.row_count() > 0 {
+            model.remove(0);
+            self.window_start += 1;
+        }
+    }
+}
+
+/// The chat model, replaced by an empty `VecModel` if it isn't one yet.
+pub fn chat_model(game_state: &crate::GameState) -> slint::ModelRc<crate::ChatMessage> {
+    ensure_vec_model(game_state.get_chat_messages(), |model| {
+        game_state.set_chat_messages(model)
+    })
+}
+
+/// Copies the lines naming the character to the Mentions tab and bumps its
+/// counter, which flashes the chat log.
+pub fn append_mentions(game_state: &crate::GameState, lines: &[ChatLine]) {
+    let model = ensure_vec_model(game_state.get_chat_mentions(), |model| {
+        game_state.set_chat_mentions(model)
+    });
+    let Some(mentions) = as_vec_model(&model) else {
+        return;
+    };
+    let mut added = 0;
+    for line in lines.iter().filter(|line| line.mention) {
+        mentions.push(line.to_row());
+        added += 1;
+    }
+    if added == 0 {
+        return;
+    }
+    while mentions.row_count() > MENTION_LINES {
+        mentions.remove(0);
+    }
+    let counter = game_state.get_chat_mention_counter();
+    game_state.set_chat_mention_counter(counter.wrapping_add(added));
+}
+
+/// Scrolling and reveal requests from the chat log.
+pub fn apply_chat_window_requests(
+    mut inbound: MessageReader<UiInbound>,
+    mut history: ResMut<ChatHistory>,
+    win: Res<SlintWindow>,
+) {
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    for UiInbound(msg) in inbound.read() {
+        match msg {
+            UiToCore::ChatLoadOlder => {
+                let model = chat_model(&game_state);
+                if let Some(chat) = as_vec_model(&model) {
+                    history.load_older(chat);
+                }
+            }
+            UiToCore::ChatScrolledBack { scrolled_back } => {
+                let model = chat_model(&game_state);
+                if let Some(chat) = as_vec_model(&model) {
+                    history.set_scrolled_back(chat, *scrolled_back);
+                }
+            }
+            UiToCore::ChatReveal { row } => history.reveal(*row),
+            _ => continue,
+        }
+        game_state.set_chat_has_older(history.has_older());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(n: usize) -> ChatLine {
+        ChatLine {
+            text: n.to_string(),
+            color: "#d0d0d0".to_string(),
+            concealed: None,
+            links: Vec::new(),
+            mention: false,
+        }
+    }
+
+    fn texts(model: &VecModel<crate::ChatMessage>) -> Vec<String> {
+        model.iter().map(|row| row.text.to_string()).collect()
+    }
+
+    #[test]
+    fn window_follows_the_bottom_and_pages_back() {
+        let model = VecModel::default();
+        let mut history = ChatHistory::default();
+        history.append(&model, (0..CHAT_WINDOW_LINES + 50).map(line).collect());
+        assert_eq!(model.row_count(), CHAT_WINDOW_LINES);
+        assert_eq!(texts(&model)[0], "50");
+        assert!(history.has_older());
+
+        history.set_scrolled_back(&model, true);
+        history.load_older(&model);
+        assert_eq!(model.row_count(), CHAT_WINDOW_LINES + 50);
+        assert_eq!(texts(&model)[0], "0");
+        assert!(!history.has_older());
+
+        history.set_scrolled_back(&model, false);
+        assert_eq!(model.row_count(), CHAT_WINDOW_LINES);
+        assert_eq!(texts(&model)[0], "50");
+    }
+}
@@ -0,0 +1,129 @@
+//! Graphics API and adapter selection, and the diagnostics shown under
+//! Settings → Graphics.
+//!
+//! Slint creates the wgpu instance and picks the adapter before the window
+//! exists, so the choice is handed over as [`WGPUSettings`] and changes apply
+//! on the next launch. A backend with no adapters falls back to the defaults
+//! instead of failing to start. Slint doesn't say which adapter it picked;
+//! asking the instance again with the same preference gives the same answer.
+
+use slint::wgpu_28::WGPUSettings;
+
+use crate::settings_types::{GpuBackend, GpuPreference};
+
+/// Format the game frame is rendered in before Slint composites it.
+pub const FRAME_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+fn backends(backend: GpuBackend) -> Option<wgpu::Backends> {
+    match backend {
+        GpuBackend::Auto => None,
+        GpuBackend::Vulkan => Some(wgpu::Backends::VULKAN),
+        GpuBackend::Dx12 => Some(wgpu::Backends::DX12),
+        GpuBackend::Metal => Some(wgpu::Backends::METAL),
+        GpuBackend::Gl => Some(wgpu::Backends::GL),
+    }
+}
+
+fn power_preference(preference: GpuPreference) -> wgpu::PowerPreference {
+    match preference {
+        GpuPreference::Auto => wgpu::PowerPreference::None,
+        GpuPreference::Integrated => wgpu::PowerPreference::LowPower,
+        GpuPreference::Discrete => wgpu::PowerPreference::HighPerformance,
+    }
+}
+
+fn has_adapter(backends: wgpu::Backends) -> bool {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+    !futures_lite::future::block_on(instance.enumerate_adapters(backends)).is_empty()
+}
+
+/// Settings for Slint's wgpu renderer with the chosen backend and GPU kind.
+pub fn wgpu_settings(backend: GpuBackend, preference: GpuPreference) -> WGPUSettings {
+    let mut wgpu_settings = WGPUSettings::default();
+    wgpu_settings.device_required_features = wgpu::Features::IMMEDIATES;
+    wgpu_settings.device_required_limits.max_immediate_size = 16;
+    wgpu_settings.power_preference = power_preference(preference);
+
+    if let Some(backends) = backends(backend) {
+        if has_adapter(backends) {
+            wgpu_settings.backends = backends;
+        } else {
+            tracing::warn!(
+                "No {:?} adapters found, using the default backends",
+                backend
+            );
+        }
+    }
+    wgpu_settings
+}
+
+/// Adapter, limits and formats of the GPU the game renders with, for display.
+pub fn gpu_diagnostics(
+    instance: &wgpu::Instance,
+    device: &wgpu::Device,
+    preference: GpuPreference,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    let adapter =
+        futures_lite::future::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: power_preference(preference),
+            ..Default::default()
+        }));
+    match adapter {
+        Ok(adapter) => {
+            let info = adapter.get_info();
+            lines.push(format!(
+                "Adapter: {} ({:?}, {:?})",
+                info.name, info.device_type, info.backend
+            ));
+            lines.push(format!("Driver: {} {}", info.driver, info.driver_info));
+        }
+        Err(err) => lines.push(format!("Adapter: unknown ({})", err)),
+    }
+
+    let limits = device.limits();
+    lines.push(format!(
+        "Max texture size: {}",
+        limits.max_texture_dimension_2d
+    ));
+    lines.push(format!(
+        "Max buffer size: {} MB",
+        limits.max_buffer_size / (1024 * 1024)
+    ));
+    lines.push(format!("Max bind groups: {}", limits.max_bind_groups));
+    lines.push(format!("Frame format: {:?}", FRAME_FORMAT));
+
+    let adapters =
+        futures_lite::future::block_on(instance.enumerate_adapters(wgpu::Backends::all()));
+    for adapter in adapters {
+        let info = adapter.get_info();
+        lines.push(format!(
+            "Available: {} ({:?}, {:?})",
+            info.name, info.device_type, info.backend
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_map_to_wgpu_options() {
+        assert_eq!(backends(GpuBackend::Auto), None);
+        assert_eq!(backends(GpuBackend::Gl), Some(wgpu::Backends::GL));
+        assert_eq!(
+            power_preference(GpuPreference::Discrete),
+            wgpu::PowerPreference::HighPerformance
+        );
+        assert_eq!(
+            power_preference(GpuPreference::Integrated),
+            wgpu::PowerPreference::LowPower
+        );
+    }
+}
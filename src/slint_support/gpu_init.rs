@@ -4,8 +4,8 @@ use bevy::prelude::*;
 use rendering::scene::Scene;
 
 use crate::resources::ZoomState;
-use crate::slint_support::frame_exchange::{BackBufferPool, FrameChannels};
-use crate::{Camera, RendererState, WindowSurface};
+use crate::slint_support::frame_exchange::{BackBufferPool, FrameChannels, PipBufferPool};
+use crate::{Camera, FrameCompositorState, RendererState, WindowSurface};
 
 use super::SlintGpuReady;
 
@@ -39,6 +39,9 @@ pub fn initialize_gpu_world(
         window.scale_factor(),
     );
 
+    world.insert_resource(FrameCompositorState {
+        compositor: rendering::FrameCompositor::new(device, texture_format),
+    });
     world.insert_resource(RendererState {
         device: device.clone(),
         queue: queue.clone(),
@@ -49,24 +52,34 @@ pub fn initialize_gpu_world(
         width: size.width,
         height: size.height,
         scale_factor: window.scale_factor(),
+        output_width: size.width,
+        output_height: size.height,
     });
     world.insert_resource(Camera { camera });
-    let (initial_zoom, high_quality_scaling) = world
+    let (initial_zoom, high_quality_scaling, render_scaling) = world
         .get_resource::<crate::settings_types::Settings>()
-        .map(|s| (s.graphics.scale, s.graphics.high_quality_scaling))
-        .unwrap_or((1.0, true));
+        .map(|s| {
+            (
+                s.graphics.scale,
+                s.graphics.high_quality_scaling,
+                s.graphics.render_scaling,
+            )
+        })
+        .unwrap_or((1.0, true, Default::default()));
     world.insert_resource(ZoomState::new(
         size.width,
         size.height,
         window.scale_factor(),
         initial_zoom,
         high_quality_scaling,
+        render_scaling,
     ));
     // Initialize frame channels & an empty pool (textures allocated lazily after notifier knows desired size)
     if !world.contains_resource::<FrameChannels>() {
         world.insert_resource(FrameChannels::new());
     }
     world.init_resource::<BackBufferPool>();
+    world.init_resource::<PipBufferPool>();
     // Seed frame buffers and channels will be handled from main.rs after this call.
     if let Some(mut ready) = world.get_resource_mut::<SlintGpuReady>() {
         ready.0 = true;
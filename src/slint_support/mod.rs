@@ -1,11 +1,15 @@
 pub mod assets;
 pub mod callbacks;
+pub mod chat_history;
 pub mod frame_exchange;
+pub mod gpu_adapter;
 pub mod gpu_init;
 pub mod input_bridge;
+pub mod model_diff;
 pub mod profile_bridge;
 pub mod rendering_notifier;
 pub mod state_bridge;
+pub mod window_layout;
 
 // Re-exports for convenience
 pub use gpu_init::initialize_gpu_world;
@@ -13,7 +17,7 @@ pub use profile_bridge::{ShowSelfProfileEvent, handle_show_self_profile, sync_pr
 
 use bevy::prelude::*;
 use slint::ComponentHandle;
-use slint::wgpu_28::{WGPUConfiguration, WGPUSettings};
+use slint::wgpu_28::WGPUConfiguration;
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -57,9 +61,9 @@ pub struct SlintDoubleClickEvent(pub f32, pub f32);
 /// drive updates from Slint's rendering callbacks.
 pub fn attach_slint_ui(mut app: App) -> MainWindow {
     // Configure WGPU for Slint backend
-    let mut wgpu_settings = WGPUSettings::default();
-    wgpu_settings.device_required_features = wgpu::Features::IMMEDIATES;
-    wgpu_settings.device_required_limits.max_immediate_size = 16;
+    let graphics = &app.world().resource::<crate::settings::Settings>().graphics;
+    let (gpu_backend, gpu_preference) = (graphics.gpu_backend, graphics.gpu_preference);
+    let wgpu_settings = gpu_adapter::wgpu_settings(gpu_backend, gpu_preference);
 
     slint::BackendSelector::new()
         .require_wgpu_28(WGPUConfiguration::Automatic(wgpu_settings))
@@ -124,7 +128,13 @@ pub fn attach_slint_ui(mut app: App) -> MainWindow {
             let mut app = app_for_notifier.borrow_mut();
             match rendering_state {
                 slint::RenderingState::RenderingSetup => {
-                    if let slint::GraphicsAPI::WGPU28 { device, queue, .. } = graphics_api {
+                    if let slint::GraphicsAPI::WGPU28 {
+                        instance,
+                        device,
+                        queue,
+                        ..
+                    } = graphics_api
+                    {
                         let Some(strong) = slint_app_handle.upgrade() else {
                             return;
                         };
@@ -135,7 +145,7 @@ pub fn attach_slint_ui(mut app: App) -> MainWindow {
                             &device,
                             &queue,
                             window,
-                            wgpu::TextureFormat::Rgba8Unorm,
+                            gpu_adapter::FRAME_FORMAT,
                         );
                         let size = window.size();
 
@@ -146,6 +156,18 @@ pub fn attach_slint_ui(mut app: App) -> MainWindow {
                             size.height,
                         );
 
+                        crate::crash_report::record_gpu_adapters(instance);
+                        let lines = gpu_adapter::gpu_diagnostics(instance, device, gpu_preference);
+                        app.world_mut()
+                            .write_message(crate::webui::plugin::UiOutbound(
+                                crate::webui::ipc::CoreToUi::GpuDiagnostics { lines },
+                            ));
+                        if let Some(reason) = crate::plugins::potato_mode::weak_gpu_reason(instance)
+                        {
+                            app.world_mut()
+                                .insert_resource(crate::plugins::potato_mode::WeakGpu(reason));
+                        }
+
                         tracing::info!("WGPU Rendering setup complete (Slint -> Bevy bridge)");
 
                         // One update so startup systems that depend on GPU can initialize.
@@ -172,6 +194,7 @@ pub fn attach_slint_ui(mut app: App) -> MainWindow {
                         |w| w.get_texture(),
                         |w, img| w.set_texture(img),
                     );
+                    rendering_notifier::exchange_pip_texture(&mut app, &strong);
                 }
                 _ => {}
             }
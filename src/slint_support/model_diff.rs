@@ -0,0 +1,140 @@
+//! Row diffing for list properties.
+//!
+//! Replacing a list model makes Slint recreate every row, which resets scroll
+//! positions and flickers long lists. Patching the existing `VecModel` only
+//! notifies the rows that changed, so appending a chat line or renaming one
+//! world list entry leaves the rest alone.
+
+use slint::{Model, ModelRc, VecModel};
+
+/// Brings `model` in line with `rows`, touching only the rows that differ.
+/// Returns whether anything changed, or hands `rows` back when `model` isn't
+/// a `VecModel` and has to be replaced.
+pub fn patch_rows<T: Clone + PartialEq + 'static>(
+    model: &ModelRc<T>,
+    rows: Vec<T>,
+) -> Result<bool, Vec<T>> {
+    let Some(vec_model) = as_vec_model(model) else {
+        return Err(rows);
+    };
+    let old_len = vec_model.row_count();
+    let new_len = rows.len();
+
+    // Lines trimmed off the front while new ones were appended, like the chat log
+    if vec_model.row_data(0).as_ref() != rows.first()
+        && let Some(shift) = front_shift(vec_model, &rows)
+    {
+        for _ in 0..shift {
+            vec_model.remove(0);
+        }
+        vec_model.extend(rows.into_iter().skip(old_len - shift));
+        return Ok(true);
+    }
+
+    // Rows kept at either end, e.g. appended lines or lines removed from the front
+    let prefix = (0..old_len.min(new_len))
+        .take_while(|&i| vec_model.row_data(i).as_ref() == Some(&rows[i]))
+        .count();
+    let suffix = (0..old_len.min(new_len) - prefix)
+        .take_while(|&i| {
+            vec_model.row_data(old_len - 1 - i).as_ref() == Some(&rows[new_len - 1 - i])
+        })
+        .count();
+    if prefix == old_len && prefix == new_len {
+        return Ok(false);
+    }
+
+    let old_middle = old_len - prefix - suffix;
+    let new_middle = new_len - prefix - suffix;
+    let mut middle = rows.into_iter().skip(prefix).take(new_middle);
+    for (row, value) in (prefix..prefix + old_middle.min(new_middle)).zip(middle.by_ref()) {
+        if vec_model.row_data(row).as_ref() != Some(&value) {
+            vec_model.set_row_data(row, value);
+        }
+    }
+    let at = prefix + old_middle.min(new_middle);
+    for _ in new_middle..old_middle {
+        vec_model.remove(at);
+    }
+    for (offset, value) in middle.enumerate() {
+        vec_model.insert(at + offset, value);
+    }
+    Ok(true)
+}
+
+/// The `VecModel` behind `model`, if that's what it is.
+pub fn as_vec_model<T: 'static>(model: &ModelRc<T>) -> Option<&VecModel<T>> {
+    model.as_any().downcast_ref::<VecModel<T>>()
+}
+
+/// `current`, or an empty `VecModel` passed to `set` in its place if it isn't
+/// one, for lists that are edited row by row.
+pub fn ensure_vec_model<T: Clone + 'static>(
+    current: ModelRc<T>,
+    set: impl FnOnce(ModelRc<T>),
+) -> ModelRc<T> {
+    if as_vec_model(&current).is_some() {
+        return current;
+    }
+    let model = ModelRc::new(VecModel::<T>::default());
+    set(model.clone());
+    model
+}
+
+/// Rows to drop from the front of `model` so the rest lines up with the start
+/// of `rows`, when that's the only difference besides rows added at the end.
+fn front_shift<T: Clone + PartialEq + 'static>(model: &VecModel<T>, rows: &[T]) -> Option<usize> {
+    let old_len = model.row_count();
+    let first = rows.first()?;
+    let shift = (1..old_len).find(|&k| model.row_data(k).as_ref() == Some(first))?;
+    let kept = old_len - shift;
+    (kept <= rows.len()
+        && (shift..old_len).all(|k| model.row_data(k).as_ref() == Some(&rows[k - shift])))
+    .then_some(shift)
+}
+
+/// Sets a list property from `rows`, patching the current model when it can.
+/// Returns whether the list changed.
+pub fn sync_rows<T: Clone + PartialEq + 'static>(
+    current: ModelRc<T>,
+    rows: Vec<T>,
+    set: impl FnOnce(ModelRc<T>),
+) -> bool {
+    match patch_rows(&current, rows) {
+        Ok(changed) => changed,
+        Err(rows) => {
+            set(ModelRc::new(VecModel::from(rows)));
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(rows: &[i32]) -> ModelRc<i32> {
+        ModelRc::new(VecModel::from(rows.to_vec()))
+    }
+
+    #[test]
+    fn patches_ends_and_middle_in_place() {
+        let m = model(&[1, 2, 3, 4]);
+        // Trimmed off the front and appended, like the chat log
+        assert_eq!(patch_rows(&m, vec![2, 3, 4, 5, 6]), Ok(true));
+        assert_eq!(m.iter().collect::<Vec<_>>(), [2, 3, 4, 5, 6]);
+
+        assert_eq!(patch_rows(&m, vec![2, 9, 4, 5, 6]), Ok(true));
+        assert_eq!(m.iter().collect::<Vec<_>>(), [2, 9, 4, 5, 6]);
+
+        assert_eq!(patch_rows(&m, vec![2, 9, 4, 5, 6]), Ok(false));
+        assert_eq!(patch_rows(&m, vec![]), Ok(true));
+        assert_eq!(m.row_count(), 0);
+    }
+
+    #[test]
+    fn hands_rows_back_for_other_models() {
+        let m: ModelRc<i32> = ModelRc::default();
+        assert_eq!(patch_rows(&m, vec![1]), Err(vec![1]));
+    }
+}
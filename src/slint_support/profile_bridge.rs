@@ -30,6 +30,19 @@ pub fn legend_mark_color(color_str: &str) -> slint::Color {
     }
 }
 
+/// Whether durability has fallen below `threshold_percent` of max. Items without
+/// durability and a threshold of 0 never count as low.
+pub fn is_low_durability(
+    current_durability: u32,
+    max_durability: u32,
+    threshold_percent: u32,
+) -> bool {
+    max_durability > 0
+        && threshold_percent > 0
+        && u64::from(current_durability) * 100
+            < u64::from(max_durability) * u64::from(threshold_percent)
+}
+
 /// Build equipment slot data from item info.
 pub fn build_equipment_slot(
     asset_loader: &crate::slint_support::assets::SlintAssetLoader,
@@ -38,6 +51,7 @@ pub fn build_equipment_slot(
     name: Option<&str>,
     current_durability: u32,
     max_durability: u32,
+    warning_percent: u32,
 ) -> EquipmentSlotData {
     let durability_percent = if max_durability > 0 {
         current_durability as f32 / max_durability as f32
@@ -51,6 +65,7 @@ pub fn build_equipment_slot(
         durability_percent,
         current_durability: current_durability as i32,
         max_durability: max_durability as i32,
+        low_durability: is_low_durability(current_durability, max_durability, warning_percent),
     }
 }
 
@@ -61,6 +76,7 @@ pub fn sync_profile_to_slint(
     game_files: Res<crate::game_files::GameFiles>,
     eq_state: Res<crate::webui::plugin::EquipmentState>,
     profile_state: Res<crate::webui::plugin::PlayerProfileState>,
+    settings: Res<crate::settings_types::Settings>,
     mut portrait_state: ResMut<crate::resources::ProfilePortraitState>,
     renderer: Res<RendererState>,
     mut last_portrait_version: Local<u32>,
@@ -90,7 +106,7 @@ pub fn sync_profile_to_slint(
         *last_portrait_version = portrait_state.version;
     }
 
-    if profile_state.is_changed() || portrait_image.is_some() {
+    if profile_state.is_changed() || settings.is_changed() || portrait_image.is_some() {
         let game_state = slint::ComponentHandle::global::<GameState>(&strong);
         let mut profile = game_state.get_profile();
 
@@ -126,7 +142,15 @@ pub fn sync_profile_to_slint(
         let make_slot = |slot_type: EquipmentSlot| {
             if is_other_player {
                 if let Some(item) = profile_state.equipment.get(&slot_type) {
-                    return build_equipment_slot(asset_loader, &game_files, item.sprite, None, 0, 0);
+                    return build_equipment_slot(
+                        asset_loader,
+                        &game_files,
+                        item.sprite,
+                        None,
+                        0,
+                        0,
+                        0,
+                    );
                 }
             } else if let Some(item) = eq_state.0.get(&slot_type) {
                 return build_equipment_slot(
@@ -136,6 +160,7 @@ pub fn sync_profile_to_slint(
                     Some(&item.name),
                     item.current_durability,
                     item.max_durability,
+                    settings.gameplay.durability_warning_percent,
                 );
             }
             EquipmentSlotData::default()
@@ -172,6 +197,7 @@ pub fn handle_show_self_profile(
     game_files: Res<crate::game_files::GameFiles>,
     eq_state: Res<crate::webui::plugin::EquipmentState>,
     mut profile_state: ResMut<crate::webui::plugin::PlayerProfileState>,
+    settings: Res<crate::settings_types::Settings>,
     mut portrait_state: ResMut<crate::resources::ProfilePortraitState>,
 ) {
     let Some(strong) = win.0.upgrade() else {
@@ -262,7 +288,15 @@ pub fn handle_show_self_profile(
             // Try to get from profile_state first (set for other players' profiles)
             if is_other_player {
                 if let Some(item) = profile_state.equipment.get(&slot_type) {
-                    return build_equipment_slot(asset_loader, &game_files, item.sprite, None, 0, 0);
+                    return build_equipment_slot(
+                        asset_loader,
+                        &game_files,
+                        item.sprite,
+                        None,
+                        0,
+                        0,
+                        0,
+                    );
                 }
                 return EquipmentSlotData::default();
             }
@@ -276,6 +310,7 @@ pub fn handle_show_self_profile(
                     Some(&item.name),
                     item.current_durability,
                     item.max_durability,
+                    settings.gameplay.durability_warning_percent,
                 );
             }
 
@@ -306,3 +341,16 @@ pub fn handle_show_self_profile(
         tracing::info!("Showing self profile panel");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_low_durability;
+
+    #[test]
+    fn low_durability_threshold() {
+        assert!(is_low_durability(19, 100, 20));
+        assert!(!is_low_durability(20, 100, 20));
+        assert!(!is_low_durability(0, 100, 0));
+        assert!(!is_low_durability(0, 0, 20));
+    }
+}
@@ -33,13 +33,26 @@ pub fn handle_before_rendering<W: slint::ComponentHandle>(
         }
     }
 
-    let (render_size, is_pixel_perfect, camera_zoom) = app
+    let (render_size, output_size, pixelated, camera_zoom) = app
         .world()
         .get_resource::<ZoomState>()
-        .map(|zs| (zs.render_size, zs.is_pixel_perfect, zs.camera_zoom))
-        .unwrap_or(((display_width, display_height), true, 1.0));
+        .map(|zs| {
+            (
+                zs.render_size,
+                zs.output_size(),
+                zs.is_pixel_perfect || zs.sharpens_upscale(),
+                zs.camera_zoom,
+            )
+        })
+        .unwrap_or((
+            (display_width, display_height),
+            (display_width, display_height),
+            true,
+            1.0,
+        ));
 
-    set_pixelated_filtering(slint_app, is_pixel_perfect);
+    // Frames the renderer already scaled to the display are shown 1:1
+    set_pixelated_filtering(slint_app, pixelated);
 
     if let Some(ch) = app.world().get_resource::<FrameChannels>() {
         let in_game = app
@@ -55,6 +68,8 @@ pub fn handle_before_rendering<W: slint::ComponentHandle>(
                 .map(|surface| {
                     surface.width != render_size.0
                         || surface.height != render_size.1
+                        || surface.output_width != output_size.0
+                        || surface.output_height != output_size.1
                         || (surface.scale_factor - camera_zoom).abs() > 0.001
                 })
                 .unwrap_or(true);
@@ -77,6 +92,8 @@ pub fn handle_before_rendering<W: slint::ComponentHandle>(
                     width: render_size.0,
                     height: render_size.1,
                     scale: camera_zoom,
+                    output_width: output_size.0,
+                    output_height: output_size.1,
                 });
             }
         }
@@ -101,6 +118,31 @@ pub fn handle_before_rendering<W: slint::ComponentHandle>(
     }
 }
 
+/// Hand the newest PiP frame to Slint and send the one it replaces back to the pool.
+pub fn exchange_pip_texture(app: &mut App, slint_app: &crate::MainWindow) {
+    let Some(ch) = app.world().get_resource::<FrameChannels>() else {
+        return;
+    };
+
+    let new_texture = ch
+        .latest_pip_buffer
+        .lock()
+        .expect("latest pip buffer mutex poisoned")
+        .take();
+
+    if let Some(new_texture) = new_texture {
+        let game_state = slint::ComponentHandle::global::<crate::GameState>(slint_app);
+        if let Some(old) = game_state.get_pip_texture().to_wgpu_28_texture() {
+            let _ = ch
+                .control_tx
+                .try_send(ControlMessage::ReleasePipTexture { texture: old });
+        }
+        if let Ok(image) = new_texture.try_into() {
+            game_state.set_pip_texture(image);
+        }
+    }
+}
+
 /// Seed the back buffer pool with initial textures.
 pub fn seed_back_buffers(app: &mut App, device: &wgpu::Device, width: u32, height: u32) {
     // Grab control sender clone without holding a mutable borrow to the World
@@ -1,10 +1,14 @@
 use crate::{
     resources::{PlayerPortraitState, RendererState, ZoomState},
-    slint_support::assets::SlintAssetLoader,
+    slint_support::{
+        assets::SlintAssetLoader,
+        chat_history::{ChatHistory, ChatLine, append_mentions, chat_model},
+        model_diff::{as_vec_model, sync_rows},
+    },
 };
 use bevy::prelude::*;
-use game_types::SlotPanelType;
-use game_ui::{ActionId, LoginError};
+use game_types::{GameAction, SlotPanelType};
+use game_ui::{ActionId, KeyBindingsUi, LoginError, ServerStatus};
 use slint::Model;
 
 pub fn sync_portrait_to_slint(
@@ -80,7 +84,11 @@ pub fn sync_lobby_portraits_to_slint(
         li.push(crate::SavedLoginItem {
             id: slint::SharedString::from(l.id.as_str()),
             server_id: l.server_id as i32,
-            username: slint::SharedString::from(l.username.as_str()),
+            username: if settings.gameplay.streamer_mode {
+                slint::SharedString::from(mask_username(&l.username))
+            } else {
+                slint::SharedString::from(l.username.as_str())
+            },
             last_used: l.last_used as i32,
             preview,
         });
@@ -95,7 +103,57 @@ pub fn sync_lobby_portraits_to_slint(
     *last_version = portraits.version;
 }
 
-fn parse_color_hex(hex: &str) -> slint::Brush {
+/// Shown in place of server addresses while streamer mode is on.
+const REDACTED_ADDRESS: &str = "••••••••";
+
+/// Keep the first letter so characters stay distinguishable without giving the name away.
+fn mask_username(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => std::iter::once(first).chain(chars.map(|_| '•')).collect(),
+        None => String::new(),
+    }
+}
+
+/// Status code and latency for a server list row: 0 = checking, 1 = online, 2 = offline.
+fn server_status_fields(status: Option<&ServerStatus>) -> (i32, i32) {
+    match status {
+        None => (0, 0),
+        Some(ServerStatus::Online { latency_ms }) => (1, *latency_ms as i32),
+        Some(ServerStatus::Offline) => (2, 0),
+    }
+}
+
+/// Groups bindings under their controls-screen headings, keeping their order.
+fn key_binding_sections(bindings: &KeyBindingsUi) -> slint::ModelRc<crate::KeyBindingSection> {
+    let mut sections: Vec<(game_types::ActionSection, Vec<crate::KeyBindingEntry>)> = Vec::new();
+    for binding in &bindings.0 {
+        let entry = crate::KeyBindingEntry {
+            action_id: binding.action_id.as_str().into(),
+            label: binding.label.as_str().into(),
+            key: binding.keys[0].as_str().into(),
+            key_2: binding.keys[1].as_str().into(),
+            can_toggle: binding.can_toggle,
+            toggle: binding.toggle,
+        };
+        match sections.last_mut() {
+            Some((section, entries)) if *section == binding.section => entries.push(entry),
+            _ => sections.push((binding.section, vec![entry])),
+        }
+    }
+
+    let sections: Vec<crate::KeyBindingSection> = sections
+        .into_iter()
+        .map(|(section, entries)| crate::KeyBindingSection {
+            section_id: section.id().into(),
+            title: section.title().into(),
+            bindings: slint::ModelRc::new(slint::VecModel::from(entries)),
+        })
+        .collect();
+    slint::ModelRc::new(slint::VecModel::from(sections))
+}
+
+pub(super) fn parse_color_hex(hex: &str) -> slint::Brush {
     let hex = hex.trim_start_matches('#');
     let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(208);
     let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(208);
@@ -109,11 +167,12 @@ pub struct SlintWindow(pub slint::Weak<crate::MainWindow>);
 #[derive(Resource)]
 pub struct SlintAssetLoaderRes(pub SlintAssetLoader);
 
-pub fn show_prelogin_ui(win: Res<SlintWindow>) {
+pub fn show_prelogin_ui(win: Res<SlintWindow>, mut chat_history: ResMut<ChatHistory>) {
     let Some(strong) = win.0.upgrade() else {
         return;
     };
     reset_game_state_for_main_menu(&strong);
+    *chat_history = ChatHistory::default();
     strong.set_show_prelogin(true);
     let settings_state = slint::ComponentHandle::global::<crate::SettingsState>(&strong);
     settings_state.set_show_settings(false);
@@ -170,8 +229,26 @@ fn reset_game_state_for_main_menu(window: &crate::MainWindow) {
 
     game_state.set_world_labels(empty_model());
     game_state.set_chat_messages(empty_model());
+    game_state.set_chat_has_older(false);
+    game_state.set_chat_mentions(empty_model());
+    game_state.set_chat_mention_counter(0);
     game_state.set_action_bar_messages(empty_model());
     game_state.set_action_bar_update_counter(0);
+    game_state.set_toast_text(slint::SharedString::from(""));
+    game_state.set_toast_counter(0);
+    game_state.set_show_debug_overlay(false);
+    game_state.set_ui_hidden(false);
+    game_state.set_debug_overlay_text(slint::SharedString::from(""));
+    game_state.set_clock_time(slint::SharedString::from(""));
+    game_state.set_clock_date(slint::SharedString::from(""));
+    game_state.set_clock_online(slint::SharedString::from(""));
+    game_state.set_event_banner_text(slint::SharedString::from(""));
+    game_state.set_shout_sender(slint::SharedString::default());
+    game_state.set_shout_text(slint::SharedString::default());
+    game_state.set_item_link(crate::ItemLinkInfo::default());
+    game_state.set_pending_chat_link(slint::SharedString::default());
+    game_state.set_pip_target(slint::SharedString::default());
+    game_state.set_pip_in_view(false);
     game_state.set_last_whisper_target(slint::SharedString::from(""));
 
     game_state.set_world_map_nodes(empty_model());
@@ -190,6 +267,24 @@ fn reset_game_state_for_main_menu(window: &crate::MainWindow) {
     game_state.set_show_skills(false);
     game_state.set_show_spells(false);
 
+    game_state.set_show_combat_log(false);
+    game_state.set_combat_log(empty_model());
+    game_state.set_show_dialog_history(false);
+    game_state.set_dialog_history(empty_model());
+    game_state.set_show_bank(false);
+    game_state.set_bank_open(false);
+    game_state.set_bank_rows(empty_model());
+    game_state.set_bank_summary(slint::SharedString::new());
+    game_state.set_bank_withdrawing(slint::SharedString::new());
+    game_state.set_ability_stats(empty_model());
+    game_state.set_show_death_recap(false);
+    game_state.set_death_recap(empty_model());
+
+    game_state.set_notifications(empty_model());
+    game_state.set_notification_history(empty_model());
+    game_state.set_notification_unread(0);
+    game_state.set_show_notification_history(false);
+
     game_state.set_show_world_list(false);
     game_state.set_world_list_loading(false);
     game_state.set_world_list_members(empty_model());
@@ -210,6 +305,37 @@ fn reset_game_state_for_main_menu(window: &crate::MainWindow) {
     game_state.set_group_invite(crate::GroupInviteNotification::default());
 }
 
+/// MP the hotbar was last built with and the known mana costs of the spells
+/// on it, so it is only rebuilt when MP crosses one of them.
+#[derive(Default)]
+pub struct HotbarMana {
+    mp: u32,
+    costs: Vec<u32>,
+}
+
+impl HotbarMana {
+    fn crossed(&self, mp: u32) -> bool {
+        self.costs
+            .iter()
+            .any(|&cost| (self.mp >= cost) != (mp >= cost))
+    }
+}
+
+/// Tooltip lines of a hotbar slot, one per line. A `None` mana cost is a spell
+/// whose cost couldn't be found, not a free one.
+fn hotbar_description(mana_cost: Option<u32>, cast_lines: u8, quantity: u32) -> String {
+    let lines = [
+        match mana_cost {
+            Some(0) => None,
+            Some(cost) => Some(format!("Mana: {}", cost)),
+            None => Some("Mana: unknown".to_owned()),
+        },
+        (cast_lines > 0).then(|| format!("Lines: {}", cast_lines)),
+        (quantity > 1).then(|| format!("Quantity: {}", quantity)),
+    ];
+    lines.into_iter().flatten().collect::<Vec<_>>().join("\n")
+}
+
 pub fn apply_core_to_slint(
     mut reader: MessageReader<crate::webui::plugin::UiOutbound>,
     win: Res<SlintWindow>,
@@ -222,6 +348,11 @@ pub fn apply_core_to_slint(
     hotbar_panel: Res<crate::ecs::hotbar::HotbarPanelState>,
     lobby_portraits: Res<crate::resources::LobbyPortraits>,
     world_list: Res<crate::webui::plugin::WorldListState>,
+    player_attrs: Res<crate::resources::PlayerAttributes>,
+    server_statuses: Res<crate::plugins::server_status::ServerStatuses>,
+    storage: Res<crate::resources::StorageConfig>,
+    mut chat_history: ResMut<ChatHistory>,
+    mut hotbar_mana: Local<HotbarMana>,
 ) {
     let Some(strong) = win.0.upgrade() else {
         return;
@@ -232,13 +363,6 @@ pub fn apply_core_to_slint(
     if inventory.is_changed() {
         let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
 
-        if game_state.get_inventory().row_count() != 60 {
-            game_state.set_inventory(slint::ModelRc::new(slint::VecModel::from(
-                vec![crate::InventoryItem::default(); 60],
-            )));
-        }
-
-        let inventory_state = game_state.get_inventory();
         let mut slint_items: Vec<crate::InventoryItem> = (1..=60)
             .map(|i| crate::InventoryItem {
                 slot: i,
@@ -257,34 +381,26 @@ pub fn apply_core_to_slint(
             };
         }
 
-        for (idx, item) in slint_items.into_iter().enumerate() {
-            if let Some(m) = inventory_state.row_data(idx) {
-                if m != item {
-                    inventory_state.set_row_data(idx, item);
-                    hotbar_dirty = true;
-                }
-            }
-        }
+        hotbar_dirty |= sync_rows(game_state.get_inventory(), slint_items, |model| {
+            game_state.set_inventory(model)
+        });
     }
 
     if ability.is_changed() {
         let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
 
-        if game_state.get_skills().row_count() != ability.skills.len() {
-            game_state.set_skills(slint::ModelRc::new(slint::VecModel::from(
-                vec![crate::Skill::default(); ability.skills.len()],
-            )));
-        }
-        let skills_state = game_state.get_skills();
-        let mut si = 0;
+        let mut skills = Vec::with_capacity(ability.skills.len());
         for s in &ability.skills {
             let icon = asset_loader
                 .load_skill_icon(&game_files, s.sprite)
                 .unwrap_or_default();
+            let proficiency = crate::webui::plugin::ability_proficiency(&s.name);
             let skill = crate::Skill {
                 name: slint::SharedString::from(s.name.as_str()),
                 icon,
                 slot: s.slot as i32,
+                has_progress: proficiency.is_some(),
+                progress: proficiency.map_or(0.0, |(cur, max)| cur as f32 / max as f32),
                 cooldown: match &s.on_cooldown {
                     Some(cd) => crate::Cooldown {
                         time_left: cd.time_left.as_millis() as i64,
@@ -294,45 +410,40 @@ pub fn apply_core_to_slint(
                 },
             };
 
-            if let Some(m) = skills_state.row_data(si) {
-                if m != skill {
-                    skills_state.set_row_data(si, skill);
-                    hotbar_dirty = true;
-                }
-            }
-            si += 1;
+            skills.push(skill);
         }
+        hotbar_dirty |= sync_rows(game_state.get_skills(), skills, |model| {
+            game_state.set_skills(model)
+        });
 
         // Update Spells
-        if game_state.get_spells().row_count() != ability.spells.len() {
-            game_state.set_spells(slint::ModelRc::new(slint::VecModel::from(
-                vec![crate::Spell::default(); ability.spells.len()],
-            )));
-        }
-        let spells_state = game_state.get_spells();
-        let mut spi = 0;
+        let mut spells = Vec::with_capacity(ability.spells.len());
         for s in &ability.spells {
             let icon = asset_loader
                 .load_spell_icon(&game_files, s.sprite)
                 .unwrap_or_default();
+            let proficiency = crate::webui::plugin::ability_proficiency(&s.panel_name);
             let spell = crate::Spell {
                 name: slint::SharedString::from(s.panel_name.as_str()),
                 icon,
                 slot: s.slot as i32,
                 prompt: slint::SharedString::from(s.prompt.as_str()),
+                has_progress: proficiency.is_some(),
+                progress: proficiency.map_or(0.0, |(cur, max)| cur as f32 / max as f32),
             };
 
-            if let Some(m) = spells_state.row_data(spi) {
-                if m != spell {
-                    spells_state.set_row_data(spi, spell);
-                    hotbar_dirty = true;
-                }
-            }
-            spi += 1;
+            spells.push(spell);
         }
+        hotbar_dirty |= sync_rows(game_state.get_spells(), spells, |model| {
+            game_state.set_spells(model)
+        });
     }
 
-    if hotbar.is_changed() {
+    // MP decides which hotbar spells are affordable
+    if hotbar.is_changed()
+        || metafile_store.is_changed()
+        || hotbar_mana.crossed(player_attrs.current_mp)
+    {
         hotbar_dirty = true;
     }
 
@@ -343,6 +454,7 @@ pub fn apply_core_to_slint(
                 current_server_id,
                 logins,
                 login_error,
+                streamer_mode,
             } => {
                 let mut si: Vec<crate::ServerItem> = Vec::with_capacity(servers.len());
                 let mut _selected_index: i32 = -1;
@@ -354,10 +466,30 @@ pub fn apply_core_to_slint(
                     {
                         _selected_index = idx as i32;
                     }
+                    let (status, latency_ms) = server_status_fields(server_statuses.0.get(&s.id));
                     si.push(crate::ServerItem {
                         id: s.id as i32,
                         name: slint::SharedString::from(s.name.as_str()),
                         address: slint::SharedString::from(s.address.as_str()),
+                        display_address: if *streamer_mode {
+                            slint::SharedString::from(REDACTED_ADDRESS)
+                        } else {
+                            slint::SharedString::from(s.address.as_str())
+                        },
+                        status,
+                        latency_ms,
+                        protocol: s.protocol.as_deref().unwrap_or_default().into(),
+                        icon: s
+                            .icon
+                            .as_ref()
+                            .and_then(|url| {
+                                crate::slint_support::assets::load_png_image(
+                                    &storage.server_icon_path(url),
+                                )
+                            })
+                            .unwrap_or_default(),
+                        imported: s.source.is_some(),
+                        news_url: s.news_url.as_deref().unwrap_or_default().into(),
                     });
                 }
                 // Default to first server if none selected
@@ -381,7 +513,11 @@ pub fn apply_core_to_slint(
                     li.push(crate::SavedLoginItem {
                         id: slint::SharedString::from(l.id.as_str()),
                         server_id: l.server_id as i32,
-                        username: slint::SharedString::from(l.username.as_str()),
+                        username: if *streamer_mode {
+                            slint::SharedString::from(mask_username(&l.username))
+                        } else {
+                            slint::SharedString::from(l.username.as_str())
+                        },
                         last_used: l.last_used as i32,
                         preview,
                     });
@@ -412,42 +548,98 @@ pub fn apply_core_to_slint(
                 }));
                 if login_error.is_some() {
                     login_state.set_is_submitting(false);
+                    login_state.set_queue_seconds(-1);
+                }
+            }
+            crate::webui::ipc::CoreToUi::ServerNews { items, status } => {
+                let rows: Vec<crate::NewsItem> = items
+                    .iter()
+                    .map(|item| crate::NewsItem {
+                        title: item.title.as_str().into(),
+                        date: item.date.as_str().into(),
+                        body: item.body.as_str().into(),
+                    })
+                    .collect();
+                let lobby_state = slint::ComponentHandle::global::<crate::LobbyState>(&strong);
+                lobby_state.set_news(slint::ModelRc::new(slint::VecModel::from(rows)));
+                lobby_state.set_news_status(status.as_str().into());
+            }
+            crate::webui::ipc::CoreToUi::ServerSubscriptions { subscriptions } => {
+                let rows: Vec<crate::ServerSubscriptionItem> = subscriptions
+                    .iter()
+                    .map(|sub| crate::ServerSubscriptionItem {
+                        url: sub.url.as_str().into(),
+                        name: sub.name.as_str().into(),
+                        server_count: sub.server_count as i32,
+                        error: sub.error.as_deref().unwrap_or_default().into(),
+                    })
+                    .collect();
+                slint::ComponentHandle::global::<crate::LobbyState>(&strong)
+                    .set_server_subscriptions(slint::ModelRc::new(slint::VecModel::from(rows)));
+            }
+            crate::webui::ipc::CoreToUi::ServerStatus { server_id, status } => {
+                let lobby_state = slint::ComponentHandle::global::<crate::LobbyState>(&strong);
+                let servers = lobby_state.get_servers();
+                let (status, latency_ms) = server_status_fields(Some(status));
+                for row in 0..servers.row_count() {
+                    let Some(mut item) = servers.row_data(row) else {
+                        continue;
+                    };
+                    if item.id == *server_id as i32 {
+                        item.status = status;
+                        item.latency_ms = latency_ms;
+                        servers.set_row_data(row, item);
+                    }
                 }
             }
+            crate::webui::ipc::CoreToUi::LoginQueue {
+                message,
+                seconds_left,
+            } => {
+                let login_state = slint::ComponentHandle::global::<crate::LoginState>(&strong);
+                login_state.set_queue_message(slint::SharedString::from(message.as_str()));
+                login_state.set_queue_seconds(*seconds_left as i32);
+            }
+            crate::webui::ipc::CoreToUi::VaultPrompt { create, error } => {
+                let login_state = slint::ComponentHandle::global::<crate::LoginState>(&strong);
+                login_state.set_is_submitting(false);
+                login_state.set_vault_create(*create);
+                login_state.set_vault_error(error.as_deref().unwrap_or_default().into());
+                login_state.set_vault_prompt_visible(true);
+            }
             crate::webui::ipc::CoreToUi::EnteredGame => {
                 let login_state = slint::ComponentHandle::global::<crate::LoginState>(&strong);
                 login_state.set_login_error_code(-1);
+                login_state.set_queue_seconds(-1);
                 strong.set_show_prelogin(false);
                 login_state.set_is_submitting(false);
             }
             crate::webui::ipc::CoreToUi::ChatAppend { entries } => {
                 let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
 
-                let existing_chat = game_state.get_chat_messages();
-                let mut chat_messages: Vec<crate::ChatMessage> = existing_chat.iter().collect();
-
+                let mut chat_lines = Vec::new();
                 let existing_action = game_state.get_action_bar_messages();
                 let mut action_bar_messages: Vec<slint::SharedString> =
                     existing_action.iter().collect();
 
                 let mut action_bar_updated = false;
                 for entry in entries.iter() {
+                    let (text, links) = crate::rich_text::extract_item_links(&entry.text);
                     if entry.show_in_message_box {
-                        let color_str = entry
-                            .color
-                            .as_ref()
-                            .map(|s| s.as_str())
-                            .unwrap_or("#d0d0d0");
-                        let color = parse_color_hex(color_str);
-
-                        chat_messages.push(crate::ChatMessage {
-                            text: slint::SharedString::from(entry.text.as_str()),
-                            color,
+                        chat_lines.push(ChatLine {
+                            text: text.clone(),
+                            color: entry.color.as_deref().unwrap_or("#d0d0d0").to_string(),
+                            concealed: entry
+                                .concealed
+                                .as_deref()
+                                .map(|cover| crate::rich_text::extract_item_links(cover).0),
+                            links,
+                            mention: entry.mention,
                         });
                     }
 
                     if entry.show_in_action_bar {
-                        action_bar_messages.push(slint::SharedString::from(entry.text.as_str()));
+                        action_bar_messages.push(slint::SharedString::from(text.as_str()));
                         while action_bar_messages.len() > 4 {
                             action_bar_messages.remove(0);
                         }
@@ -455,11 +647,15 @@ pub fn apply_core_to_slint(
                     }
                 }
 
-                let chat_model = std::rc::Rc::new(slint::VecModel::from(chat_messages));
-                game_state.set_chat_messages(chat_model.clone().into());
-
-                let action_model = std::rc::Rc::new(slint::VecModel::from(action_bar_messages));
-                game_state.set_action_bar_messages(action_model.clone().into());
+                append_mentions(&game_state, &chat_lines);
+                let chat = chat_model(&game_state);
+                if let Some(chat) = as_vec_model(&chat) {
+                    chat_history.append(chat, chat_lines);
+                }
+                game_state.set_chat_has_older(chat_history.has_older());
+                sync_rows(existing_action, action_bar_messages, |model| {
+                    game_state.set_action_bar_messages(model)
+                });
 
                 if action_bar_updated {
                     let counter = game_state.get_action_bar_update_counter();
@@ -635,55 +831,212 @@ pub fn apply_core_to_slint(
             }
             crate::webui::ipc::CoreToUi::SettingsSync {
                 xray_size,
+                xray_mode,
+                open_interiors,
+                attack_telegraphs,
                 sfx_volume,
                 music_volume,
+                combat_volume,
+                spell_volume,
+                ambient_volume,
+                audio_device,
                 scale,
+                render_scaling,
+                gamma,
+                debug_overlay,
+                potato_mode,
+                gpu_backend,
+                gpu_preference,
+                texture_budget_mb,
+                streamer_mode,
+                stream_output_enabled,
+                stream_output_port,
+                debug_console_enabled,
+                debug_console_port,
+                idle_daydream_minutes,
+                anti_idle,
+                anti_idle_max_minutes,
+                durability_warning_percent,
+                spell_queue_window_ms,
+                chat_bubbles,
+                mention_highlight,
+                mention_sound,
+                chat_filter,
+                chat_filter_words,
+                chat_filter_channels,
+                auto_pickup,
+                auto_pickup_gold,
+                dangerous_dialog_options,
+                notification_policies,
+                hotbar_rows,
+                hotbar_icon_size,
+                hotbar_position,
+                classic_hud,
+                hotbar_auto_switch,
+                hotbar_town_panel,
+                hotbar_combat_panel,
+                turn_tap_ms,
+                direction_priority,
                 key_bindings,
             } => {
                 let settings_state =
                     slint::ComponentHandle::global::<crate::SettingsState>(&strong);
-                macro_rules! set_keys {
-                   ($field:ident) => {
-                       paste::paste! {
-                           settings_state.[<set_key_ $field>](slint::SharedString::from(key_bindings.$field[0].as_str()));
-                           settings_state.[<set_key_ $field _2>](slint::SharedString::from(key_bindings.$field[1].as_str()));
-                       }
-                   };
-                }
-
                 settings_state.set_xray_size(*xray_size as i32);
+                settings_state.set_xray_mode(*xray_mode as i32);
+                settings_state.set_open_interiors(*open_interiors);
+                settings_state.set_attack_telegraphs(*attack_telegraphs);
                 settings_state.set_sfx_volume(*sfx_volume);
                 settings_state.set_music_volume(*music_volume);
+                settings_state.set_combat_volume(*combat_volume);
+                settings_state.set_spell_volume(*spell_volume);
+                settings_state.set_ambient_volume(*ambient_volume);
+                settings_state.set_audio_device(slint::SharedString::from(audio_device.as_str()));
                 settings_state.set_scale(*scale);
-
-                set_keys!(move_up);
-                set_keys!(move_down);
-                set_keys!(move_left);
-                set_keys!(move_right);
-                set_keys!(inventory);
-                set_keys!(skills);
-                set_keys!(spells);
-                set_keys!(settings);
-                set_keys!(refresh);
-                set_keys!(basic_attack);
-                set_keys!(hotbar_slot_1);
-                set_keys!(hotbar_slot_2);
-                set_keys!(hotbar_slot_3);
-                set_keys!(hotbar_slot_4);
-                set_keys!(hotbar_slot_5);
-                set_keys!(hotbar_slot_6);
-                set_keys!(hotbar_slot_7);
-                set_keys!(hotbar_slot_8);
-                set_keys!(hotbar_slot_9);
-                set_keys!(hotbar_slot_10);
-                set_keys!(hotbar_slot_11);
-                set_keys!(hotbar_slot_12);
-                set_keys!(switch_to_inventory);
-                set_keys!(switch_to_skills);
-                set_keys!(switch_to_spells);
-                set_keys!(switch_to_hotbar_1);
-                set_keys!(switch_to_hotbar_2);
-                set_keys!(switch_to_hotbar_3);
+                settings_state.set_render_scaling(*render_scaling as i32);
+                settings_state.set_gamma(*gamma);
+                settings_state.set_debug_overlay_enabled(*debug_overlay);
+                settings_state.set_potato_mode(*potato_mode);
+                settings_state.set_gpu_backend(*gpu_backend as i32);
+                settings_state.set_gpu_preference(*gpu_preference as i32);
+                settings_state.set_texture_budget_mb(*texture_budget_mb as i32);
+                settings_state.set_streamer_mode(*streamer_mode);
+                settings_state.set_stream_output_enabled(*stream_output_enabled);
+                settings_state.set_stream_output_port(*stream_output_port as i32);
+                settings_state.set_debug_console_enabled(*debug_console_enabled);
+                settings_state.set_debug_console_port(*debug_console_port as i32);
+                settings_state.set_idle_daydream_minutes(*idle_daydream_minutes as i32);
+                settings_state.set_anti_idle(*anti_idle);
+                settings_state.set_anti_idle_max_minutes(*anti_idle_max_minutes as i32);
+                settings_state
+                    .set_durability_warning_percent(*durability_warning_percent as i32);
+                settings_state.set_spell_queue_window_ms(*spell_queue_window_ms as i32);
+                settings_state.set_chat_bubbles(*chat_bubbles);
+                settings_state.set_mention_highlight(*mention_highlight);
+                settings_state.set_mention_sound(*mention_sound);
+                settings_state.set_chat_filter(*chat_filter);
+                settings_state.set_chat_filter_words(chat_filter_words.as_str().into());
+                settings_state.set_chat_filter_channels(slint::ModelRc::new(
+                    slint::VecModel::from(chat_filter_channels.clone()),
+                ));
+                settings_state.set_auto_pickup(*auto_pickup);
+                settings_state.set_auto_pickup_gold(*auto_pickup_gold);
+                settings_state
+                    .set_dangerous_dialog_options(dangerous_dialog_options.as_str().into());
+                let policies: Vec<i32> = notification_policies.iter().map(|&p| p as i32).collect();
+                settings_state.set_notification_policies(slint::ModelRc::new(
+                    slint::VecModel::from(policies),
+                ));
+                settings_state.set_hotbar_rows(*hotbar_rows as i32);
+                settings_state.set_turn_tap_ms(*turn_tap_ms as i32);
+                settings_state.set_direction_priority(*direction_priority as i32);
+                settings_state.set_hotbar_icon_size(*hotbar_icon_size as i32);
+                settings_state.set_hotbar_docked(hotbar_position.is_none());
+                if let Some((x, y)) = hotbar_position {
+                    settings_state.set_hotbar_x(*x);
+                    settings_state.set_hotbar_y(*y);
+                }
+                settings_state.set_classic_hud(*classic_hud);
+                settings_state.set_hotbar_auto_switch(*hotbar_auto_switch);
+                settings_state.set_hotbar_town_panel(*hotbar_town_panel as i32);
+                settings_state.set_hotbar_combat_panel(*hotbar_combat_panel as i32);
+
+                settings_state.set_key_binding_sections(key_binding_sections(key_bindings));
+                let primary_key = |action| {
+                    slint::SharedString::from(
+                        key_bindings
+                            .keys(action)
+                            .map_or("", |keys| keys[0].as_str()),
+                    )
+                };
+                settings_state.set_key_toggle_ui(primary_key(GameAction::ToggleUi));
+                settings_state
+                    .set_key_toggle_debug_overlay(primary_key(GameAction::ToggleDebugOverlay));
+                settings_state.set_key_entity_inspector(primary_key(GameAction::EntityInspector));
+                settings_state.set_key_capture_frame(primary_key(GameAction::CaptureFrame));
+                settings_state.set_key_archive_browser(primary_key(GameAction::ArchiveBrowser));
+            }
+            crate::webui::ipc::CoreToUi::CrashReportPending {
+                path,
+                error,
+                can_upload,
+            } => {
+                let login_state = slint::ComponentHandle::global::<crate::LoginState>(&strong);
+                login_state.set_crash_report_path(slint::SharedString::from(path.as_str()));
+                login_state.set_crash_report_error(slint::SharedString::from(
+                    error.as_deref().unwrap_or_default(),
+                ));
+                login_state.set_crash_report_can_upload(*can_upload);
+            }
+            crate::webui::ipc::CoreToUi::SettingsRecoveryPending { error, has_backup } => {
+                let login_state = slint::ComponentHandle::global::<crate::LoginState>(&strong);
+                login_state.set_settings_error(slint::SharedString::from(error.as_str()));
+                login_state.set_settings_has_backup(*has_backup);
+            }
+            crate::webui::ipc::CoreToUi::SessionSummary { character, rows } => {
+                let login_state = slint::ComponentHandle::global::<crate::LoginState>(&strong);
+                let slint_rows: Vec<crate::SessionSummaryRow> = rows
+                    .iter()
+                    .map(|row| crate::SessionSummaryRow {
+                        label: slint::SharedString::from(row.label.as_str()),
+                        session: slint::SharedString::from(row.session.as_str()),
+                        lifetime: slint::SharedString::from(row.lifetime.as_str()),
+                    })
+                    .collect();
+                login_state
+                    .set_session_summary(slint::ModelRc::new(slint::VecModel::from(slint_rows)));
+                login_state
+                    .set_session_summary_character(slint::SharedString::from(character.as_str()));
+            }
+            crate::webui::ipc::CoreToUi::ConnectionLost { reason } => {
+                let login_state = slint::ComponentHandle::global::<crate::LoginState>(&strong);
+                login_state.set_connection_lost(slint::SharedString::from(reason.as_str()));
+            }
+            crate::webui::ipc::CoreToUi::Toast { text } => {
+                let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+                game_state.set_toast_text(slint::SharedString::from(text.as_str()));
+                let counter = game_state.get_toast_counter();
+                game_state.set_toast_counter(counter.wrapping_add(1));
+            }
+            // Collected by `plugins::notifications`, see `sync_notifications_to_slint`
+            crate::webui::ipc::CoreToUi::Notify { .. } => {}
+            crate::webui::ipc::CoreToUi::ItemLinkTooltip {
+                name,
+                sprite,
+                details,
+            } => {
+                let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+                game_state.set_item_link(crate::ItemLinkInfo {
+                    visible: true,
+                    name: slint::SharedString::from(name.as_str()),
+                    icon: sprite
+                        .and_then(|s| asset_loader.load_item_icon(&game_files, s).ok())
+                        .unwrap_or_default(),
+                    details: slint::SharedString::from(details.as_str()),
+                });
+            }
+            crate::webui::ipc::CoreToUi::WindowLayoutSync { windows } => {
+                let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+                crate::slint_support::window_layout::apply_window_layout(&game_state, windows);
+            }
+            crate::webui::ipc::CoreToUi::AudioDevices { devices } => {
+                let settings_state =
+                    slint::ComponentHandle::global::<crate::SettingsState>(&strong);
+                let devices: Vec<slint::SharedString> = devices
+                    .iter()
+                    .map(|d| slint::SharedString::from(d.as_str()))
+                    .collect();
+                settings_state
+                    .set_audio_devices(slint::ModelRc::new(slint::VecModel::from(devices)));
+            }
+            crate::webui::ipc::CoreToUi::GpuDiagnostics { lines } => {
+                let settings_state =
+                    slint::ComponentHandle::global::<crate::SettingsState>(&strong);
+                let lines: Vec<slint::SharedString> = lines
+                    .iter()
+                    .map(|line| slint::SharedString::from(line.as_str()))
+                    .collect();
+                settings_state
+                    .set_gpu_diagnostics(slint::ModelRc::new(slint::VecModel::from(lines)));
             }
         }
     }
@@ -699,6 +1052,8 @@ pub fn apply_core_to_slint(
 
         let hotbar_state = game_state.get_hotbar();
         let mut entry_idx = 0;
+        hotbar_mana.mp = player_attrs.current_mp;
+        hotbar_mana.costs.clear();
 
         for bar in &hotbar.config.bars {
             for slot in bar {
@@ -710,6 +1065,8 @@ pub fn apply_core_to_slint(
                     let mut enabled = false;
                     let mut sprite = action_id.sprite();
                     let mut cooldown = None;
+                    let mut mana_cost = Some(0);
+                    let mut cast_lines = 0;
 
                     let mut name = slint::SharedString::default();
 
@@ -746,6 +1103,10 @@ pub fn apply_core_to_slint(
                                 name = slint::SharedString::from(spell.panel_name.as_str());
                                 enabled = true;
                                 cooldown = hotbar.cooldowns.get(&slot.action_id).cloned();
+                                cast_lines = spell.cast_lines;
+                                let base_name =
+                                    crate::webui::plugin::spell_base_name(&spell.panel_name);
+                                mana_cost = metafile_store.spell_mana_cost(base_name);
                             }
                         }
                         _ => {}
@@ -759,11 +1120,19 @@ pub fn apply_core_to_slint(
                     }
                     .unwrap_or_default();
 
+                    // Spells of unknown cost are never dimmed, so MP can't change them
+                    if let Some(cost) = mana_cost.filter(|&cost| cost > 0) {
+                        hotbar_mana.costs.push(cost);
+                    }
                     crate::HotbarEntry {
                         name,
                         icon,
                         quantity: quantity as i32,
                         enabled,
+                        description: slint::SharedString::from(hotbar_description(
+                            mana_cost, cast_lines, quantity,
+                        )),
+                        affordable: mana_cost.is_none_or(|cost| player_attrs.current_mp >= cost),
                         cooldown: match cooldown {
                             Some(cd) => crate::Cooldown {
                                 time_left: cd.time_left.as_millis() as i64,
@@ -801,11 +1170,15 @@ pub fn apply_core_to_slint(
                 class: slint::SharedString::from(m.class.as_str()),
                 color: slint::Color::from_argb_f32(m.color[3], m.color[0], m.color[1], m.color[2]),
                 is_master: m.is_master,
+                is_friend: m.is_friend,
             });
         }
 
-        game_state
-            .set_world_list_members(slint::ModelRc::new(slint::VecModel::from(slint_members)));
+        sync_rows(
+            game_state.get_world_list_members(),
+            slint_members,
+            |model| game_state.set_world_list_members(model),
+        );
         game_state.set_world_list_count(world_list.filtered.len() as i32);
         if let Some(raw) = &world_list.raw {
             game_state.set_world_list_total_count(raw.world_member_count as i32);
@@ -923,6 +1296,7 @@ pub fn sync_world_labels_to_slint(
                     color_a: label.color.w,
                     is_speech: label.is_speech,
                     health_percent: final_hp,
+                    opacity: label.opacity,
                 });
             };
 
@@ -951,12 +1325,105 @@ pub fn sync_world_labels_to_slint(
                 color_a: 1.0,
                 is_speech: false,
                 health_percent: hp,
+                opacity: 1.0,
             });
         }
     }
 
-    let model = slint::VecModel::from(slint_labels);
-    game_state.set_world_labels(slint::ModelRc::new(model));
+    sync_rows(game_state.get_world_labels(), slint_labels, |model| {
+        game_state.set_world_labels(model)
+    });
+}
+
+pub fn sync_threat_arrows_to_slint(
+    win: Res<SlintWindow>,
+    arrows: Res<crate::plugins::threat_indicators::ThreatArrows>,
+) {
+    if !arrows.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    let rows: Vec<crate::ThreatArrow> = arrows
+        .0
+        .iter()
+        .map(|arrow| crate::ThreatArrow {
+            x: arrow.x,
+            y: arrow.y,
+            angle: arrow.angle,
+            strength: arrow.strength,
+        })
+        .collect();
+    game_state.set_threat_arrows(slint::ModelRc::new(slint::VecModel::from(rows)));
+}
+
+/// Pet frame next to the player's, shown while a pet is tracked and dimmed
+/// when it's out of sight.
+pub fn sync_pet_frame_to_slint(
+    win: Res<SlintWindow>,
+    pets: Res<crate::plugins::pets::PetTracker>,
+    creature_names: Res<crate::plugins::creature_names::CreatureNames>,
+    entity_ids: Query<&crate::ecs::components::EntityId>,
+) {
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    let frame = match pets.pet() {
+        Some(pet) => crate::PetFrameData {
+            visible: true,
+            name: creature_names.get(pet.sprite).unwrap_or("Pet").into(),
+            health_percent: pet.health_percent.map_or(-1, i32::from),
+            in_view: entity_ids.iter().any(|id| id.id == pet.id),
+        },
+        None => crate::PetFrameData::default(),
+    };
+    if game_state.get_pet() != frame {
+        game_state.set_pet(frame);
+    }
+}
+
+pub fn sync_anti_idle_to_slint(
+    win: Res<SlintWindow>,
+    status: Res<crate::plugins::anti_idle::AntiIdleStatus>,
+) {
+    if !status.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    game_state.set_anti_idle_status(slint::SharedString::from(status.0.as_str()));
+}
+
+/// Shows the throttling notice while the rate limiter holds packets back,
+/// and for a moment after so it doesn't flicker between packets.
+pub fn sync_outbox_throttle_to_slint(
+    win: Res<SlintWindow>,
+    time: Res<Time>,
+    outbox: Option<Res<crate::network::PacketOutbox>>,
+    mut last_throttled: Local<Option<std::time::Duration>>,
+    mut shown: Local<bool>,
+) {
+    const LINGER: std::time::Duration = std::time::Duration::from_secs(1);
+
+    let now = time.elapsed();
+    if outbox.is_some_and(|outbox| outbox.is_throttled()) {
+        *last_throttled = Some(now);
+    }
+    let throttled = last_throttled.is_some_and(|at| now.saturating_sub(at) < LINGER);
+    if throttled == *shown {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    *shown = throttled;
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    game_state.set_outbox_throttled(throttled);
 }
 
 pub fn sync_map_name_to_slint(
@@ -973,6 +1440,118 @@ pub fn sync_map_name_to_slint(
     }
 }
 
+pub fn sync_path_preview_to_slint(
+    win: Res<SlintWindow>,
+    preview: Res<crate::resources::PathPreview>,
+) {
+    if !preview.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    let points: Vec<crate::PathPoint> = preview
+        .path
+        .iter()
+        .map(|&(x, y)| {
+            let world = rendering::scene::get_isometric_coordinate(x as f32, y as f32);
+            crate::PathPoint {
+                world_x: world.x,
+                world_y: world.y,
+            }
+        })
+        .collect();
+    game_state.set_path_preview(slint::ModelRc::new(slint::VecModel::from(points)));
+    if let Some((x, y)) = preview.target {
+        let world = rendering::scene::get_isometric_coordinate(x as f32, y as f32);
+        game_state.set_path_preview_x(world.x);
+        game_state.set_path_preview_y(world.y);
+    }
+    game_state.set_path_preview_text(preview.label().into());
+}
+
+pub fn sync_telegraphs_to_slint(
+    win: Res<SlintWindow>,
+    tiles: Res<crate::plugins::attack_telegraphs::TelegraphTiles>,
+) {
+    if !tiles.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    let rows: Vec<crate::TelegraphTile> = tiles
+        .0
+        .iter()
+        .map(|tile| {
+            let world = rendering::scene::get_isometric_coordinate(tile.x as f32, tile.y as f32);
+            crate::TelegraphTile {
+                world_x: world.x,
+                world_y: world.y,
+                strength: tile.strength,
+            }
+        })
+        .collect();
+    game_state.set_telegraph_tiles(slint::ModelRc::new(slint::VecModel::from(rows)));
+}
+
+/// Map, tile and facing of the local player for the HUD's location readout.
+pub fn sync_location_to_slint(
+    win: Res<SlintWindow>,
+    player: Query<
+        (
+            &crate::ecs::components::Position,
+            &crate::ecs::components::Direction,
+        ),
+        With<crate::ecs::components::LocalPlayer>,
+    >,
+    map_query: Query<&crate::ecs::components::GameMap>,
+) {
+    use crate::ecs::components::Direction;
+
+    let (Ok((pos, direction)), Some(map)) = (player.single(), map_query.iter().next()) else {
+        return;
+    };
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let facing = match direction {
+        Direction::Up => "North",
+        Direction::Right => "East",
+        Direction::Down => "South",
+        Direction::Left => "West",
+    };
+    let text = format!(
+        "{} ({}) {}, {} {}",
+        map.name,
+        map.map_id,
+        pos.x.round() as i32,
+        pos.y.round() as i32,
+        facing
+    );
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    if game_state.get_location_text() != text.as_str() {
+        game_state.set_location_text(text.into());
+    }
+}
+
+/// Show the loading indicator while a map change is slow.
+pub fn sync_map_loading_to_slint(
+    win: Res<SlintWindow>,
+    transition: Res<crate::resources::MapTransition>,
+) {
+    let loading = transition.shows_loading_indicator();
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    if game_state.get_map_loading() != loading {
+        game_state.set_map_loading(loading);
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Group state → Slint
 // ---------------------------------------------------------------------------
@@ -1022,7 +1601,9 @@ pub fn sync_group_to_slint(
             is_leader: i == 0,
         })
         .collect();
-    game_state.set_group_members(slint::ModelRc::new(slint::VecModel::from(members)));
+    sync_rows(game_state.get_group_members(), members, |model| {
+        game_state.set_group_members(model)
+    });
 
     if let Some(invite) = &group_state.pending_invite {
         game_state.set_group_invite(crate::GroupInviteNotification {
@@ -1030,6 +1611,7 @@ pub fn sync_group_to_slint(
             source_name: slint::SharedString::from(invite.source_name.as_str()),
             group_name: slint::SharedString::from(invite.group_name.as_str()),
             group_note: slint::SharedString::from(invite.group_note.as_str()),
+            timeout_secs: crate::webui::plugin::GROUP_INVITE_TIMEOUT_SECS,
         });
     } else {
         let mut gi = game_state.get_group_invite();
@@ -1040,6 +1622,593 @@ pub fn sync_group_to_slint(
     }
 }
 
+pub fn sync_split_stack_to_slint(
+    win: Res<SlintWindow>,
+    asset_loader: Res<SlintAssetLoaderRes>,
+    game_files: Res<crate::game_files::GameFiles>,
+    split_stack: Res<crate::webui::plugin::PendingSplitStack>,
+) {
+    use crate::webui::plugin::SplitStackTarget;
+
+    if !split_stack.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+
+    if let Some(request) = &split_stack.0 {
+        game_state.set_split_stack(crate::SplitStackPrompt {
+            visible: true,
+            item_name: slint::SharedString::from(request.item_name.as_str()),
+            icon: asset_loader
+                .0
+                .load_item_icon(&game_files, request.sprite)
+                .unwrap_or_default(),
+            max: request.max as i32,
+            action: slint::SharedString::from(match request.target {
+                SplitStackTarget::Ground { .. } => "Drop",
+                SplitStackTarget::Creature { .. } => "Give",
+            }),
+        });
+    } else {
+        let mut prompt = game_state.get_split_stack();
+        if prompt.visible {
+            prompt.visible = false;
+            game_state.set_split_stack(prompt);
+        }
+    }
+}
+
+pub fn sync_item_confirm_to_slint(
+    win: Res<SlintWindow>,
+    asset_loader: Res<SlintAssetLoaderRes>,
+    game_files: Res<crate::game_files::GameFiles>,
+    item_confirm: Res<crate::plugins::item_confirm::PendingItemConfirm>,
+) {
+    use crate::plugins::item_confirm::GuardedAction;
+    use crate::webui::plugin::SplitStackTarget;
+
+    if !item_confirm.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+
+    if let Some(request) = &item_confirm.0 {
+        game_state.set_item_confirm(crate::ItemConfirmPrompt {
+            visible: true,
+            item_name: slint::SharedString::from(request.item_name.as_str()),
+            icon: asset_loader
+                .0
+                .load_item_icon(&game_files, request.sprite)
+                .unwrap_or_default(),
+            action: slint::SharedString::from(match request.action {
+                GuardedAction::Use => "Use",
+                GuardedAction::Drop(SplitStackTarget::Ground { .. }) => "Drop",
+                GuardedAction::Drop(SplitStackTarget::Creature { .. }) => "Give",
+            }),
+        });
+    } else {
+        let mut prompt = game_state.get_item_confirm();
+        if prompt.visible {
+            prompt.visible = false;
+            game_state.set_item_confirm(prompt);
+        }
+    }
+}
+
+pub fn sync_dialog_confirm_to_slint(
+    win: Res<SlintWindow>,
+    dialog_confirm: Res<crate::plugins::dialog_confirm::PendingDialogConfirm>,
+) {
+    if !dialog_confirm.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    game_state.set_dialog_confirm(match &dialog_confirm.0 {
+        Some(request) => crate::DialogConfirmPrompt {
+            visible: true,
+            option: request.option.as_str().into(),
+        },
+        None => crate::DialogConfirmPrompt::default(),
+    });
+}
+
+pub fn sync_pip_to_slint(
+    win: Res<SlintWindow>,
+    target: Res<crate::render_plugin::pip::PipTarget>,
+) {
+    if !target.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+
+    game_state.set_pip_target(slint::SharedString::from(
+        target.name.as_deref().unwrap_or_default(),
+    ));
+    game_state.set_pip_in_view(target.last_position.is_some());
+}
+
+pub fn sync_creature_name_to_slint(
+    win: Res<SlintWindow>,
+    names: Res<crate::plugins::creature_names::CreatureNames>,
+    pending: Res<crate::plugins::creature_names::PendingCreatureName>,
+) {
+    if !pending.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+
+    if let Some(sprite) = pending.0 {
+        game_state.set_creature_name(crate::CreatureNamePrompt {
+            visible: true,
+            sprite: sprite as i32,
+            name: slint::SharedString::from(names.get(sprite).unwrap_or_default()),
+        });
+    } else {
+        let mut prompt = game_state.get_creature_name();
+        if prompt.visible {
+            prompt.visible = false;
+            game_state.set_creature_name(prompt);
+        }
+    }
+}
+
+pub fn sync_map_pins_to_slint(
+    win: Res<SlintWindow>,
+    pins: Res<crate::plugins::map_pins::MapPins>,
+    pending: Res<crate::plugins::map_pins::PendingMapPin>,
+) {
+    if !pins.is_changed() && !pending.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+
+    if pins.is_changed() {
+        let markers: Vec<crate::MapPinMarker> = pins
+            .pins()
+            .iter()
+            .map(|pin| {
+                let world = rendering::scene::get_isometric_coordinate(pin.x as f32, pin.y as f32);
+                crate::MapPinMarker {
+                    name: slint::SharedString::from(pin.name.as_str()),
+                    world_x: world.x,
+                    world_y: world.y,
+                }
+            })
+            .collect();
+        game_state.set_map_pins(slint::ModelRc::new(slint::VecModel::from(markers)));
+    }
+
+    if pending.is_changed() {
+        let (x, y) = pending.0.unwrap_or_default();
+        game_state.set_map_pin_prompt(crate::MapPinPrompt {
+            visible: pending.0.is_some(),
+            x: x as i32,
+            y: y as i32,
+        });
+    }
+}
+
+pub fn sync_spell_timers_to_slint(
+    win: Res<SlintWindow>,
+    asset_loader: Res<SlintAssetLoaderRes>,
+    game_files: Res<crate::game_files::GameFiles>,
+    timers: Res<crate::plugins::spell_timers::SpellTimers>,
+    config: Res<crate::plugins::spell_timers::SpellTimerConfig>,
+    pending: Res<crate::plugins::spell_timers::PendingSpellTimer>,
+) {
+    use crate::plugins::spell_timers::format_countdown;
+
+    if !timers.is_changed() && !pending.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+
+    if pending.is_changed() {
+        if let Some(spell) = &pending.0 {
+            game_state.set_spell_timer_prompt(crate::SpellTimerPrompt {
+                visible: true,
+                name: slint::SharedString::from(spell.name.as_str()),
+                duration: config
+                    .duration(&spell.name)
+                    .map(format_countdown)
+                    .unwrap_or_default()
+                    .into(),
+            });
+        } else {
+            let mut prompt = game_state.get_spell_timer_prompt();
+            if prompt.visible {
+                prompt.visible = false;
+                game_state.set_spell_timer_prompt(prompt);
+            }
+        }
+    }
+
+    if !timers.is_changed() {
+        return;
+    }
+    let progress = |timer: &crate::plugins::spell_timers::SpellTimer| {
+        timer.remaining.as_secs_f32() / timer.total.as_secs_f32().max(1.0)
+    };
+    let chips = game_state.get_spell_timers();
+    let same_timers = chips.row_count() == timers.iter().count()
+        && chips
+            .iter()
+            .zip(timers.iter())
+            .all(|(chip, timer)| chip.id == timer.id as i32);
+
+    // Icons are only decoded when timers start or stop; ticking just updates the text.
+    if !same_timers {
+        let chips: Vec<crate::SpellTimerChip> = timers
+            .iter()
+            .map(|timer| crate::SpellTimerChip {
+                id: timer.id as i32,
+                name: slint::SharedString::from(timer.name.as_str()),
+                icon: asset_loader
+                    .0
+                    .load_spell_icon(&game_files, timer.sprite)
+                    .unwrap_or_default(),
+                time_left: format_countdown(timer.remaining_secs()).into(),
+                progress: progress(timer),
+            })
+            .collect();
+        game_state.set_spell_timers(slint::ModelRc::new(slint::VecModel::from(chips)));
+        return;
+    }
+    for (row, timer) in timers.iter().enumerate() {
+        let Some(mut chip) = chips.row_data(row) else {
+            continue;
+        };
+        let time_left = format_countdown(timer.remaining_secs());
+        if chip.time_left != time_left.as_str() {
+            chip.time_left = time_left.into();
+            chip.progress = progress(timer);
+            chips.set_row_data(row, chip);
+        }
+    }
+}
+
+/// Fills the combat log window with the filtered entries, only while it is open.
+pub fn sync_combat_log_to_slint(
+    win: Res<SlintWindow>,
+    log: Res<crate::plugins::combat_log::CombatLog>,
+    mut stale: Local<bool>,
+) {
+    use crate::plugins::combat_log::CombatLogKind;
+
+    if log.is_changed() {
+        *stale = true;
+    }
+    if !*stale {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    if !game_state.get_show_combat_log() {
+        return;
+    }
+    *stale = false;
+
+    let rows: Vec<crate::CombatLogRow> = log
+        .filtered()
+        .map(|entry| crate::CombatLogRow {
+            time: slint::SharedString::from(entry.time.as_str()),
+            kind: slint::SharedString::from(entry.kind.label()),
+            color: match entry.kind {
+                CombatLogKind::Cast => slint::Color::from_rgb_u8(0x93, 0xc5, 0xfd),
+                CombatLogKind::Hit => slint::Color::from_rgb_u8(0xfc, 0xa5, 0xa5),
+                CombatLogKind::Heal => slint::Color::from_rgb_u8(0x86, 0xef, 0xac),
+                CombatLogKind::Status => slint::Color::from_rgb_u8(0xff, 0x98, 0x00),
+            },
+            source: slint::SharedString::from(entry.source.as_str()),
+            target: slint::SharedString::from(entry.target.as_str()),
+            text: slint::SharedString::from(entry.text.as_str()),
+        })
+        .collect();
+    game_state.set_combat_log(slint::ModelRc::new(slint::VecModel::from(rows)));
+}
+
+/// Shows the dialog paging controls for the page on screen.
+pub fn sync_dialog_advance_to_slint(
+    win: Res<SlintWindow>,
+    advance: Res<crate::plugins::dialog_advance::DialogAdvance>,
+) {
+    if !advance.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let npc_dialog = slint::ComponentHandle::global::<crate::NpcDialogState>(&strong);
+    npc_dialog.set_has_next(advance.has_next);
+    npc_dialog.set_auto_next(advance.auto_next);
+}
+
+/// Shows whether the NPC menu on screen can be, or is, saved as a shortcut.
+pub fn sync_menu_shortcut_to_slint(
+    win: Res<SlintWindow>,
+    status: Res<crate::plugins::menu_shortcuts::MenuShortcutStatus>,
+) {
+    if !status.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let npc_dialog = slint::ComponentHandle::global::<crate::NpcDialogState>(&strong);
+    npc_dialog.set_shortcut_can_save(status.can_save);
+    npc_dialog.set_shortcut_saved(status.saved);
+}
+
+/// Fills the conversation history window with the matching entries, only while it is open.
+pub fn sync_dialog_history_to_slint(
+    win: Res<SlintWindow>,
+    history: Res<crate::plugins::dialog_history::DialogHistory>,
+    mut stale: Local<bool>,
+) {
+    if history.is_changed() {
+        *stale = true;
+    }
+    if !*stale {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    if !game_state.get_show_dialog_history() {
+        return;
+    }
+    *stale = false;
+
+    let rows: Vec<crate::DialogHistoryRow> = history
+        .filtered()
+        .map(|entry| crate::DialogHistoryRow {
+            time: slint::SharedString::from(entry.time.as_str()),
+            npc: slint::SharedString::from(entry.npc.as_str()),
+            text: slint::SharedString::from(entry.text.as_str()),
+            options: slint::SharedString::from(entry.options.join(" · ")),
+        })
+        .collect();
+    game_state.set_dialog_history(slint::ModelRc::new(slint::VecModel::from(rows)));
+}
+
+/// Mirrors the bank viewer; the item rows are only rebuilt while its window is open.
+pub fn sync_bank_to_slint(
+    win: Res<SlintWindow>,
+    bank: Res<crate::plugins::bank::BankContents>,
+    mut stale: Local<bool>,
+) {
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    if bank.is_changed() {
+        *stale = true;
+        game_state.set_bank_open(bank.is_open());
+        game_state.set_bank_withdrawing(slint::SharedString::from(
+            bank.withdrawing.as_deref().unwrap_or_default(),
+        ));
+        let summary = if bank.npc.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "{} items at {}, as of {}",
+                bank.item_count(),
+                bank.npc,
+                bank.updated
+            )
+        };
+        game_state.set_bank_summary(slint::SharedString::from(summary));
+    }
+    if !*stale || !game_state.get_show_bank() {
+        return;
+    }
+    *stale = false;
+
+    let rows: Vec<crate::BankRow> = bank
+        .view()
+        .into_iter()
+        .map(|item| crate::BankRow {
+            name: slint::SharedString::from(item.name.as_str()),
+            total: item.total as i32,
+            stacks: item.stacks as i32,
+        })
+        .collect();
+    game_state.set_bank_rows(slint::ModelRc::new(slint::VecModel::from(rows)));
+}
+
+/// Fills the statistics tab of the actions window with this session's ability use.
+pub fn sync_ability_stats_to_slint(
+    win: Res<SlintWindow>,
+    stats: Res<crate::plugins::ability_stats::AbilityStats>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    let most_uses = stats.most_uses().max(1) as f32;
+    let rows: Vec<crate::AbilityStatRow> = stats
+        .stats()
+        .iter()
+        .map(|stat| crate::AbilityStatRow {
+            name: slint::SharedString::from(stat.name.as_str()),
+            kind: slint::SharedString::from(stat.kind.label()),
+            uses: stat.uses as i32,
+            succeeded: stat.succeeded as i32,
+            failed: stat.failed as i32,
+            heat: stat.uses as f32 / most_uses,
+        })
+        .collect();
+    game_state.set_ability_stats(slint::ModelRc::new(slint::VecModel::from(rows)));
+}
+
+/// Opens the death recap with the hits leading up to each new death.
+pub fn sync_death_recap_to_slint(
+    win: Res<SlintWindow>,
+    recap: Res<crate::plugins::death_recap::DeathRecap>,
+) {
+    if !recap.is_changed() || recap.deaths == 0 {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    let rows: Vec<crate::DeathRecapRow> = recap
+        .rows
+        .iter()
+        .map(|row| crate::DeathRecapRow {
+            time: slint::SharedString::from(row.time.as_str()),
+            source: slint::SharedString::from(row.source.as_str()),
+            amount: slint::SharedString::from(row.amount.as_str()),
+            ability: slint::SharedString::from(row.ability.as_str()),
+        })
+        .collect();
+    game_state.set_death_recap(slint::ModelRc::new(slint::VecModel::from(rows)));
+    game_state.set_show_death_recap(true);
+}
+
+/// Keeps the profile's status picker on the status last sent to the server.
+pub fn sync_social_status_to_slint(
+    win: Res<SlintWindow>,
+    state: Res<crate::plugins::idle::SocialStatusState>,
+) {
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+    let status = state.status as i32;
+    if game_state.get_social_status() != status {
+        game_state.set_social_status(status);
+    }
+}
+
+/// Shows the active notification cards and fills the history drawer.
+pub fn sync_notifications_to_slint(
+    win: Res<SlintWindow>,
+    center: Res<crate::plugins::notifications::NotificationCenter>,
+) {
+    use crate::plugins::notifications::{Notification, kind_label};
+    use crate::webui::ipc::NotifySeverity;
+
+    if !center.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+
+    let row = |n: &Notification| crate::NotificationRow {
+        id: n.id as i32,
+        time: slint::SharedString::from(n.time.as_str()),
+        label: slint::SharedString::from(kind_label(n.kind)),
+        text: slint::SharedString::from(n.text.as_str()),
+        color: match n.severity {
+            NotifySeverity::Info => slint::Color::from_rgb_u8(0x93, 0xc5, 0xfd),
+            NotifySeverity::Success => slint::Color::from_rgb_u8(0x86, 0xef, 0xac),
+            NotifySeverity::Warning => slint::Color::from_rgb_u8(0xff, 0x98, 0x00),
+            NotifySeverity::Error => slint::Color::from_rgb_u8(0xfc, 0xa5, 0xa5),
+        },
+    };
+    let active: Vec<crate::NotificationRow> = center.active().map(row).collect();
+    // Newest first in the drawer
+    let history: Vec<crate::NotificationRow> = center.history().rev().map(row).collect();
+    game_state.set_notifications(slint::ModelRc::new(slint::VecModel::from(active)));
+    game_state.set_notification_history(slint::ModelRc::new(slint::VecModel::from(history)));
+    game_state.set_notification_unread(center.unread as i32);
+}
+
+/// Gui sheet frames behind the classic HUD: the bottom panel and the slot strip on its right.
+const CLASSIC_BOTTOM_PANEL: &str = "setoa/_nbtmpnl.0.ktx2";
+const CLASSIC_SLOT_PANEL: &str = "setoa/_nslot.0.ktx2";
+
+/// Decodes the classic HUD art the first time the classic layout is switched on.
+/// Missing frames leave the images empty and the panel falls back to theme colors.
+pub fn sync_classic_skin_to_slint(
+    win: Res<SlintWindow>,
+    game_files: Res<crate::game_files::GameFiles>,
+    settings: Res<crate::settings_types::Settings>,
+    mut loaded: Local<bool>,
+) {
+    if *loaded || !settings.layout.classic_hud {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let skin = slint::ComponentHandle::global::<crate::ClassicSkin>(&strong);
+    let load = |path| {
+        SlintAssetLoader::load_spf_frame(&game_files, path)
+            .inspect_err(|e| warn!("Classic HUD art unavailable: {}", e))
+            .unwrap_or_default()
+    };
+    skin.set_bottom_panel(load(CLASSIC_BOTTOM_PANEL));
+    skin.set_slot_panel(load(CLASSIC_SLOT_PANEL));
+    *loaded = true;
+}
+
+/// Cursor sheet, one frame per `WorldCursor` in discriminant order.
+const CURSOR_SHEET: &str = "setoa/mouse";
+const CURSOR_FRAMES: usize = 4;
+
+/// Hands the cursor sprites to the UI once, then the cursor to show whenever it changes.
+pub fn sync_world_cursor_to_slint(
+    win: Res<SlintWindow>,
+    game_files: Res<crate::game_files::GameFiles>,
+    cursor: Res<crate::plugins::world_cursor::WorldCursor>,
+    mut loaded: Local<bool>,
+) {
+    if *loaded && !cursor.is_changed() {
+        return;
+    }
+    let Some(strong) = win.0.upgrade() else {
+        return;
+    };
+    let game_state = slint::ComponentHandle::global::<crate::GameState>(&strong);
+
+    if !*loaded {
+        // Stops at the first missing frame; the UI keeps the OS cursor for those
+        let frames: Vec<slint::Image> = (0..CURSOR_FRAMES)
+            .map_while(|frame| {
+                let path = format!("{}.{}.ktx2", CURSOR_SHEET, frame);
+                SlintAssetLoader::load_spf_frame(&game_files, &path)
+                    .inspect_err(|e| warn!("Game cursor unavailable: {}", e))
+                    .ok()
+            })
+            .collect();
+        game_state.set_cursor_frames(slint::ModelRc::new(slint::VecModel::from(frames)));
+        *loaded = true;
+    }
+    game_state.set_cursor_kind(*cursor as i32);
+}
+
 pub fn sync_settings_to_slint(
     win: Res<SlintWindow>,
     settings: Res<crate::settings_types::Settings>,
@@ -1057,6 +2226,9 @@ pub fn sync_settings_to_slint(
     // Sync settings to slint
     settings_state.set_music_volume(settings.audio.music_volume);
     settings_state.set_sfx_volume(settings.audio.sfx_volume);
+    settings_state.set_combat_volume(settings.audio.combat_volume);
+    settings_state.set_spell_volume(settings.audio.spell_volume);
+    settings_state.set_ambient_volume(settings.audio.ambient_volume);
     settings_state.set_scale(settings.graphics.scale);
 }
 
@@ -0,0 +1,65 @@
+//! Floating HUD windows - maps saved geometry onto the per-window `GameState` properties.
+//!
+//! The Slint side owns dragging and resizing; Rust only restores the layout at
+//! login and persists whatever the UI reports back.
+
+use game_types::WindowGeometry;
+
+use crate::{GameState, HudWindowRect};
+
+/// Ids of the windows that can be moved and resized.
+pub const HUD_WINDOWS: [&str; 9] = [
+    "chat",
+    "inventory",
+    "actions",
+    "group",
+    "map",
+    "pip",
+    "combat_log",
+    "dialog_history",
+    "bank",
+];
+
+/// Resets every HUD window to its default placement, then applies the saved entries.
+pub fn apply_window_layout(game_state: &GameState, windows: &[(String, WindowGeometry)]) {
+    for id in HUD_WINDOWS {
+        set_window_rect(game_state, id, HudWindowRect::default());
+    }
+    for (id, geometry) in windows {
+        set_window_rect(game_state, id, to_rect(geometry));
+    }
+}
+
+pub fn to_rect(geometry: &WindowGeometry) -> HudWindowRect {
+    HudWindowRect {
+        custom: true,
+        x: geometry.x,
+        y: geometry.y,
+        width: geometry.width,
+        height: geometry.height,
+    }
+}
+
+pub fn to_geometry(rect: &HudWindowRect) -> WindowGeometry {
+    WindowGeometry {
+        x: rect.x,
+        y: rect.y,
+        width: rect.width,
+        height: rect.height,
+    }
+}
+
+fn set_window_rect(game_state: &GameState, id: &str, rect: HudWindowRect) {
+    match id {
+        "chat" => game_state.set_chat_window(rect),
+        "inventory" => game_state.set_inventory_window(rect),
+        "actions" => game_state.set_actions_window(rect),
+        "group" => game_state.set_group_window(rect),
+        "map" => game_state.set_map_window(rect),
+        "pip" => game_state.set_pip_window(rect),
+        "combat_log" => game_state.set_combat_log_window(rect),
+        "dialog_history" => game_state.set_dialog_history_window(rect),
+        "bank" => game_state.set_bank_window(rect),
+        _ => tracing::warn!("Ignoring layout for unknown HUD window {:?}", id),
+    }
+}
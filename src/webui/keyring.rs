@@ -1,22 +1,45 @@
 use anyhow::{Result, anyhow};
 
+use super::vault::CredentialVault;
+
 const SERVICE: &str = "talgonite";
 
-pub fn set_password(cred_id: &str, password: &str) -> Result<()> {
+/// Whether the system keyring answers at all; a missing entry still counts.
+pub fn probe() -> bool {
+    match keyring::Entry::new(SERVICE, "probe").and_then(|entry| entry.get_password()) {
+        Ok(_) | Err(keyring::Error::NoEntry) => true,
+        Err(e) => {
+            tracing::info!("keyring probe failed: {}", e);
+            false
+        }
+    }
+}
+
+pub fn set_password(vault: &mut CredentialVault, cred_id: &str, password: &str) -> Result<()> {
+    if !vault.keyring_available {
+        return vault.set(cred_id, password);
+    }
     keyring::Entry::new(SERVICE, cred_id)
         .map_err(|e| anyhow!("keyring error: {}", e))?
         .set_password(password)
         .map_err(|e| anyhow!("failed to store password: {}", e))
 }
 
-pub fn get_password(cred_id: &str) -> Result<String> {
+pub fn get_password(vault: &CredentialVault, cred_id: &str) -> Result<String> {
+    if !vault.keyring_available {
+        return vault.get(cred_id);
+    }
     keyring::Entry::new(SERVICE, cred_id)
         .map_err(|e| anyhow!("keyring error: {}", e))?
         .get_password()
         .map_err(|e| anyhow!("password not found: {}", e))
 }
 
-pub fn delete_password(cred_id: &str) -> Result<()> {
+pub fn delete_password(vault: &mut CredentialVault, cred_id: &str) -> Result<()> {
+    vault.remove(cred_id)?;
+    if !vault.keyring_available {
+        return Ok(());
+    }
     let entry = keyring::Entry::new(SERVICE, cred_id)
         .map_err(|e| anyhow!("keyring error: {}", e))?;
     match entry.delete_credential() {
@@ -3,3 +3,4 @@ pub mod state;
 pub mod plugin;
 pub mod settings;
 pub mod keyring;
+pub mod vault;
@@ -3,23 +3,26 @@ use std::time::Instant;
 use bevy::input::ButtonInput;
 use bevy::input::mouse::MouseButton;
 use bevy::prelude::*;
-use bevy::tasks::{IoTaskPool, Task};
+use bevy::tasks::{AsyncComputeTaskPool, IoTaskPool, Task};
 use futures_lite::future;
 use game_types::SlotPanelType;
 pub use game_ui::CursorPosition;
 use game_ui::{
     ActionId, ChatEntryUi, Cooldown, CoreToUi, InventoryItemUi, KeyboardEdges, LoginError,
-    MenuEntryUi, SkillUi, SpellUi, UiToCore, WorldListFilter, WorldListMemberUi, WorldMapNodeUi,
+    MenuEntryUi, NotifyKind, NotifySeverity, SkillUi, SpellUi, UiToCore, WorldListFilter,
+    WorldListMemberUi, WorldMapNodeUi,
 };
 use packets::client;
 use packets::server::display_menu::DisplayMenuPayload;
 use packets::types::{EntityType, MenuType};
 
 use crate::app_state::AppState;
+use crate::chat_filter::{ChatChannel, mentions};
 use crate::events::{
     AbilityEvent, ChatEvent, InteractionIntentAction, InteractionIntentEvent,
     InteractionTargetKind, InventoryEvent, SessionEvent, WorldContextMenuEntry,
 };
+use crate::plugins::item_confirm::GuardedAction;
 use crate::render_plugin::game::WebUi;
 use crate::rich_text::RichText;
 use crate::slint_plugin::ShowSelfProfileEvent;
@@ -27,6 +30,7 @@ use rendering::scene::utils::screen_to_iso_tile;
 
 use super::keyring;
 use super::settings::{SavedCredential, SavedCredentialPublic, ServerEntry, SettingsFile};
+use super::vault::{CredentialVault, DerivedKey, PendingLogin};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ActiveWindowType {
@@ -37,7 +41,7 @@ pub enum ActiveWindowType {
     Info,
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone)]
 pub struct ActiveMenuContext {
     pub window_type: ActiveWindowType,
     pub entity_type: Option<EntityType>,
@@ -48,6 +52,20 @@ pub struct ActiveMenuContext {
     pub menu_type: Option<MenuType>,
     pub args: String,
     pub dialog_id: Option<u16>,
+    /// Text of the options on screen by the id the UI sends back, for
+    /// checking against `GameplaySettings::dangerous_dialog_options`.
+    pub options: Vec<(i32, String)>,
+    /// Dialog page with a Next button.
+    pub has_next: bool,
+}
+
+impl ActiveMenuContext {
+    pub fn option_text(&self, id: i32) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|(option_id, _)| *option_id == id)
+            .map(|(_, text)| text.as_str())
+    }
 }
 
 #[derive(Resource, Default, Debug, Clone)]
@@ -74,6 +92,7 @@ impl Plugin for UiBridgePlugin {
             .init_resource::<EquipmentState>()
             .init_resource::<PlayerProfileState>()
             .init_resource::<GroupState>()
+            .init_resource::<PendingSplitStack>()
             .init_resource::<crate::ecs::hotbar::HotbarState>()
             .init_resource::<crate::ecs::hotbar::HotbarPanelState>()
             .init_resource::<ActiveMenuContext>()
@@ -82,6 +101,7 @@ impl Plugin for UiBridgePlugin {
             .init_resource::<ButtonInput<KeyCode>>()
             .init_resource::<ButtonInput<MouseButton>>()
             .init_resource::<KeyboardEdges>()
+            .init_resource::<CredentialVault>()
             .add_systems(PreUpdate, handle_input_bridge)
             .add_systems(
                 Update,
@@ -96,11 +116,15 @@ impl Plugin for UiBridgePlugin {
                     handle_ui_inbound_ingame.run_if(in_state(AppState::InGame)),
                     handle_login_tasks,
                     handle_login_results,
+                    tick_login_retry,
+                    finish_vault_unlock,
+                    resume_vault_login,
                     update_skill_cooldowns,
                     sync_settings_to_ui,
                 ),
             )
             .add_systems(Last, (clear_input_edges, clear_just_input))
+            .add_systems(OnExit(AppState::InGame), clear_split_stack)
             .add_systems(Update, emit_snapshot_on_state_change);
     }
 }
@@ -155,6 +179,14 @@ struct InteractionResources<'w, 's> {
             Option<&'static crate::ecs::components::LocalPlayer>,
         ),
     >,
+    ground_items: Query<
+        'w,
+        's,
+        &'static crate::ecs::components::Position,
+        With<crate::ecs::components::ItemSprite>,
+    >,
+    collision_table: Option<Res<'w, crate::ecs::collision::WallCollisionTable>>,
+    map_collision: Option<Res<'w, crate::ecs::collision::MapCollisionData>>,
 }
 
 #[derive(bevy::ecs::system::SystemParam)]
@@ -162,6 +194,11 @@ struct UiStateResources<'w> {
     inv_state: Res<'w, InventoryState>,
     ability_state: Res<'w, AbilityState>,
     world_list_state: ResMut<'w, WorldListState>,
+    split_stack: ResMut<'w, PendingSplitStack>,
+    item_confirm: ResMut<'w, crate::plugins::item_confirm::PendingItemConfirm>,
+    dialog_confirm: ResMut<'w, crate::plugins::dialog_confirm::PendingDialogConfirm>,
+    eq_state: Res<'w, EquipmentState>,
+    pip_target: ResMut<'w, crate::render_plugin::pip::PipTarget>,
 }
 
 #[derive(bevy::ecs::system::SystemParam)]
@@ -175,6 +212,12 @@ struct WorldContextResources<'w, 's> {
     world_context_menu: ResMut<'w, ActiveWorldContextMenu>,
     interaction_intents: MessageWriter<'w, InteractionIntentEvent>,
     profile_events: MessageWriter<'w, ShowSelfProfileEvent>,
+    pending_creature_name: ResMut<'w, crate::plugins::creature_names::PendingCreatureName>,
+    pending_map_pin: ResMut<'w, crate::plugins::map_pins::PendingMapPin>,
+    map_pins: ResMut<'w, crate::plugins::map_pins::MapPins>,
+    spell_timers: ResMut<'w, crate::plugins::spell_timers::SpellTimers>,
+    pending_spell_timer: ResMut<'w, crate::plugins::spell_timers::PendingSpellTimer>,
+    pets: ResMut<'w, crate::plugins::pets::PetTracker>,
     entity_ids: Query<
         'w,
         's,
@@ -207,6 +250,11 @@ fn handle_ui_inbound_ingame(
     let inv_state = ui_state.inv_state;
     let ability_state = ui_state.ability_state;
     let mut world_list_state = ui_state.world_list_state;
+    let mut split_stack = ui_state.split_stack;
+    let mut item_confirm = ui_state.item_confirm;
+    let mut dialog_confirm = ui_state.dialog_confirm;
+    let eq_state = ui_state.eq_state;
+    let mut pip_target = ui_state.pip_target;
     let mut input_bindings = bindings.input_bindings;
     let mut unified_bindings = bindings.unified_bindings;
     let mut zoom_state = interaction_res.zoom_state;
@@ -216,6 +264,14 @@ fn handle_ui_inbound_ingame(
             UiToCore::InputKeyboard { .. } | UiToCore::InputPointer { .. } => {
                 // Handled by handle_input_bridge
             }
+            UiToCore::CopyLocation { text } => {
+                let text = if crate::crash_report::copy_to_clipboard(text) {
+                    format!("Copied {}", text)
+                } else {
+                    "Clipboard unavailable".to_string()
+                };
+                outbound.write(UiOutbound(CoreToUi::Toast { text }));
+            }
             UiToCore::WorldContextMenuSelect { id } => {
                 let selected = world_context
                     .world_context_menu
@@ -281,6 +337,63 @@ fn handle_ui_inbound_ingame(
                             outbox.send(&packets::client::Click::TargetEntity(entity_id.id));
                         }
                     }
+                    crate::events::WorldContextAction::InviteToGroup { name } => {
+                        outbox.send(&packets::client::GroupInvite::Request { name: name.clone() });
+                        outbound.write(UiOutbound(CoreToUi::Toast {
+                            text: format!("Invited {} to the group", name),
+                        }));
+                    }
+                    crate::events::WorldContextAction::NameCreature { sprite } => {
+                        world_context.pending_creature_name.0 = Some(sprite);
+                    }
+                    crate::events::WorldContextAction::MarkPet { entity, sprite } => {
+                        if let Ok((entity_id, _)) = world_context.entity_ids.get(entity) {
+                            world_context.pets.mark(entity_id.id, sprite);
+                            outbound.write(UiOutbound(CoreToUi::Toast {
+                                text: "Marked as your pet".to_string(),
+                            }));
+                        }
+                    }
+                    crate::events::WorldContextAction::StartSpellTimer { name, sprite, secs } => {
+                        world_context.spell_timers.start(&name, sprite, secs);
+                    }
+                    crate::events::WorldContextAction::PromptSpellTimer { name, sprite } => {
+                        world_context.pending_spell_timer.0 =
+                            Some(crate::plugins::spell_timers::PendingSpell { name, sprite });
+                    }
+                    crate::events::WorldContextAction::StopSpellTimer { name } => {
+                        world_context.spell_timers.stop(&name);
+                    }
+                    crate::events::WorldContextAction::ToggleItemConfirm { name } => {
+                        let flagged = settings.toggle_item_confirm(
+                            session.server_id,
+                            &session.username,
+                            &name,
+                        );
+                        outbound.write(UiOutbound(CoreToUi::Toast {
+                            text: if flagged {
+                                format!("{} will ask before being used or dropped", name)
+                            } else {
+                                format!("{} no longer asks before use or drop", name)
+                            },
+                        }));
+                    }
+                    crate::events::WorldContextAction::ToggleAutoPickup { name, sprite } => {
+                        let enabled = settings.gameplay.toggle_auto_pickup(&name, sprite);
+                        outbound.write(UiOutbound(CoreToUi::Toast {
+                            text: if enabled {
+                                format!("{} will be picked up automatically", name)
+                            } else {
+                                format!("{} is no longer picked up automatically", name)
+                            },
+                        }));
+                    }
+                    crate::events::WorldContextAction::PinTile { tile_x, tile_y } => {
+                        world_context.pending_map_pin.0 = Some((tile_x, tile_y));
+                    }
+                    crate::events::WorldContextAction::RemoveMapPin { tile_x, tile_y } => {
+                        world_context.map_pins.set(tile_x, tile_y, "");
+                    }
                     crate::events::WorldContextAction::InteractWalls { walls } => {
                         for (tile_x, tile_y, is_right) in walls {
                             outbox.send(&packets::client::Click::TargetWall {
@@ -316,68 +429,21 @@ fn handle_ui_inbound_ingame(
                     continue;
                 }
 
-                if let Some(dialog_id) = menu_ctx.dialog_id {
-                    if let Some(entity_type) = menu_ctx.entity_type {
-                        let mut final_dialog_id = *id;
-                        let args = if *id == dialog_id as i32 {
-                            packets::client::DialogInteractionArgs::TextResponse {
-                                args: vec![name.clone()],
-                            }
-                        } else if *id >= 100_000 {
-                            final_dialog_id = dialog_id as i32 + 1;
-                            packets::client::DialogInteractionArgs::MenuResponse {
-                                option: (*id - 100_000 + 1) as u8,
-                            }
-                        } else {
-                            packets::client::DialogInteractionArgs::None
-                        };
-
-                        outbox.send(&packets::client::DialogInteraction {
-                            entity_type,
-                            entity_id: menu_ctx.entity_id,
-                            pursuit_id: menu_ctx.pursuit_id.unwrap_or(0),
-                            dialog_id: final_dialog_id as u16,
-                            args,
-                        });
-                    }
-                    continue;
-                }
-
-                let is_slot_interaction = matches!(
-                    menu_ctx.menu_type,
-                    Some(MenuType::ShowPlayerItems)
-                        | Some(MenuType::ShowPlayerSpells)
-                        | Some(MenuType::ShowPlayerSkills)
-                );
-
-                let args = if is_slot_interaction {
-                    packets::client::MenuInteractionArgs::Slot(*id as u8)
-                } else {
-                    let mut topics = Vec::new();
-                    if !menu_ctx.args.is_empty() {
-                        topics.push(menu_ctx.args.clone());
-                    }
-                    if !name.is_empty() {
-                        topics.push(name.clone());
-                    }
-
-                    if topics.is_empty() {
-                        packets::client::MenuInteractionArgs::Slot(0)
-                    } else {
-                        packets::client::MenuInteractionArgs::Topics(topics)
-                    }
-                };
-
-                if let Some(entity_type) = menu_ctx.entity_type {
-                    outbox.send(&packets::client::MenuInteraction {
-                        entity_type,
-                        entity_id: menu_ctx.entity_id,
-                        pursuit_id: menu_ctx.pursuit_id.unwrap_or(*id as _),
-                        args,
+                if let Some(option) = menu_ctx.option_text(*id)
+                    && crate::plugins::dialog_confirm::is_dangerous(
+                        option,
+                        &settings.gameplay.dangerous_dialog_options,
+                    )
+                {
+                    dialog_confirm.0 = Some(crate::plugins::dialog_confirm::DialogConfirmRequest {
+                        option: option.to_string(),
+                        id: *id,
+                        name: name.clone(),
+                        menu: menu_ctx.clone(),
                     });
-                } else {
-                    tracing::warn!("MenuSelect with no entity_type in context");
+                    continue;
                 }
+                send_menu_selection(&menu_ctx, *id, name, &outbox);
             }
             UiToCore::MenuClose => {
                 if let Some(dialog_id) = menu_ctx.dialog_id {
@@ -431,143 +497,177 @@ fn handle_ui_inbound_ingame(
                     settings.audio.music_volume = *music_vol;
                 }
             }
+            UiToCore::CategoryVolumeChange { category, volume } => {
+                settings.audio.set_category_volume(
+                    crate::settings_types::SoundCategory::from_u8(*category),
+                    *volume,
+                );
+            }
+            UiToCore::AudioDeviceChange { device } => {
+                settings.audio.output_device = (!device.is_empty()).then(|| device.clone());
+            }
             UiToCore::ScaleChange { scale } => {
                 settings.graphics.scale = *scale;
                 zoom_state.set_zoom(*scale);
             }
+            UiToCore::RenderScalingChange { mode } => {
+                settings.graphics.render_scaling =
+                    crate::settings_types::RenderScaling::from_u8(*mode);
+                zoom_state.set_render_scaling(settings.graphics.render_scaling);
+            }
+            UiToCore::GammaChange { gamma } => {
+                settings.graphics.gamma = gamma.clamp(0.5, 2.0);
+            }
+            UiToCore::XRayModeChange { mode } => {
+                settings.graphics.xray_mode = crate::settings_types::XRayMode::from_u8(*mode);
+            }
+            UiToCore::OpenInteriorsChange { enabled } => {
+                settings.graphics.open_interiors = *enabled;
+            }
+            UiToCore::AttackTelegraphsChange { enabled } => {
+                settings.graphics.attack_telegraphs = *enabled;
+            }
+            UiToCore::DebugOverlayChange { enabled } => {
+                settings.graphics.debug_overlay = *enabled;
+            }
+            UiToCore::PotatoModeChange { enabled } => {
+                crate::plugins::potato_mode::set_potato_mode(&mut settings, *enabled);
+                zoom_state.set_render_scaling(settings.graphics.render_scaling);
+            }
+            UiToCore::GpuAdapterChange {
+                backend,
+                preference,
+            } => {
+                settings.graphics.gpu_backend =
+                    crate::settings_types::GpuBackend::from_u8(*backend);
+                settings.graphics.gpu_preference =
+                    crate::settings_types::GpuPreference::from_u8(*preference);
+            }
+            UiToCore::TextureBudgetChange { mb } => {
+                settings.graphics.texture_budget_mb = *mb;
+            }
+            UiToCore::StreamerModeChange { enabled } => {
+                settings.gameplay.streamer_mode = *enabled;
+                outbound.write(UiOutbound(settings.to_snapshot_message(None)));
+            }
+            UiToCore::StreamOutputChange { enabled } => {
+                settings.stream_output.enabled = *enabled;
+            }
+            UiToCore::DebugConsoleChange { enabled } => {
+                settings.debug_console.enabled = *enabled;
+            }
+            UiToCore::IdleDaydreamChange { minutes } => {
+                settings.gameplay.idle_daydream_minutes = *minutes;
+            }
+            UiToCore::AntiIdleChange {
+                enabled,
+                max_minutes,
+            } => {
+                settings.gameplay.anti_idle = *enabled;
+                settings.gameplay.anti_idle_max_minutes = *max_minutes;
+            }
+            UiToCore::DurabilityWarningChange { percent } => {
+                settings.gameplay.durability_warning_percent = *percent;
+            }
+            UiToCore::SpellQueueWindowChange { ms } => {
+                settings.gameplay.spell_queue_window_ms = *ms;
+            }
+            UiToCore::ChatBubblesChange { enabled } => {
+                settings.gameplay.chat_bubbles = *enabled;
+            }
+            UiToCore::MentionAlertsChange { highlight, sound } => {
+                settings.gameplay.mention_highlight = *highlight;
+                settings.gameplay.mention_sound = *sound;
+            }
+            UiToCore::ChatFilterChange { enabled, words } => {
+                settings.chat_filter.enabled = *enabled;
+                settings.chat_filter.words = crate::chat_filter::parse_word_list(words);
+            }
+            UiToCore::ChatFilterChannelChange { channel, enabled } => {
+                if let Some(slot) = settings.chat_filter.channels.get_mut(*channel as usize) {
+                    *slot = *enabled;
+                }
+            }
+            UiToCore::AutoPickupChange { enabled, gold } => {
+                settings.gameplay.auto_pickup = *enabled;
+                settings.gameplay.auto_pickup_gold = *gold;
+            }
+            UiToCore::DangerousDialogOptionsChange { options } => {
+                settings.gameplay.dangerous_dialog_options =
+                    crate::plugins::dialog_confirm::parse_option_list(options);
+            }
+            UiToCore::HotbarLayoutChange { rows, icon_size } => {
+                use crate::settings_types::{
+                    MAX_HOTBAR_ICON_SIZE, MAX_HOTBAR_ROWS, MIN_HOTBAR_ICON_SIZE, MIN_HOTBAR_ROWS,
+                };
+                settings.layout.hotbar_rows = (*rows).clamp(MIN_HOTBAR_ROWS, MAX_HOTBAR_ROWS);
+                settings.layout.hotbar_icon_size =
+                    (*icon_size).clamp(MIN_HOTBAR_ICON_SIZE, MAX_HOTBAR_ICON_SIZE);
+            }
+            UiToCore::HotbarMoved { position } => {
+                settings.layout.hotbar_position = *position;
+            }
+            UiToCore::ClassicHudChange { enabled } => {
+                settings.layout.classic_hud = *enabled;
+            }
+            UiToCore::HotbarAutoSwitchChange {
+                enabled,
+                town_panel,
+                combat_panel,
+            } => {
+                settings.layout.hotbar_auto_switch = *enabled;
+                settings.layout.hotbar_town_panel = (*town_panel).min(5);
+                settings.layout.hotbar_combat_panel = (*combat_panel).min(5);
+            }
+            UiToCore::NotificationPolicyChange { status, policy } => {
+                if let Some(slot) = settings
+                    .gameplay
+                    .notification_policies
+                    .get_mut(*status as usize)
+                {
+                    *slot = crate::settings_types::NotificationPolicy::from_u8(*policy);
+                }
+            }
+            UiToCore::TurnTapChange { ms } => {
+                settings.input.turn_tap_ms = (*ms).min(500);
+            }
+            UiToCore::DirectionPriorityChange { mode } => {
+                settings.input.direction_priority =
+                    crate::settings_types::DirectionPriority::from_u8(*mode);
+            }
             UiToCore::RebindKey {
                 action,
                 new_key,
                 index,
             } => {
-                use crate::input::{InputBindings, UnifiedInputBindings};
-                let index = *index;
-
-                // Conflict detection: if new_key is already bound to another action, clear that action's binding at that index
-                macro_rules! check_conflict {
-                    ($field:ident) => {
-                        for k in settings.key_bindings.$field.iter_mut() {
-                            if !new_key.is_empty() && k == new_key {
-                                *k = "".to_string();
-                            }
-                        }
-                    };
-                }
+                use crate::input::{GameAction, InputBindings, UnifiedInputBindings};
+                if let Some(action) = GameAction::from_action_id(action) {
+                    settings.key_bindings.rebind(action, *index, new_key);
 
-                check_conflict!(move_up);
-                check_conflict!(move_down);
-                check_conflict!(move_left);
-                check_conflict!(move_right);
-                check_conflict!(inventory);
-                check_conflict!(skills);
-                check_conflict!(spells);
-                check_conflict!(settings);
-                check_conflict!(refresh);
-                check_conflict!(basic_attack);
-                check_conflict!(hotbar_slot_1);
-                check_conflict!(hotbar_slot_2);
-                check_conflict!(hotbar_slot_3);
-                check_conflict!(hotbar_slot_4);
-                check_conflict!(hotbar_slot_5);
-                check_conflict!(hotbar_slot_6);
-                check_conflict!(hotbar_slot_7);
-                check_conflict!(hotbar_slot_8);
-                check_conflict!(hotbar_slot_9);
-                check_conflict!(hotbar_slot_10);
-                check_conflict!(hotbar_slot_11);
-                check_conflict!(hotbar_slot_12);
-                check_conflict!(switch_to_inventory);
-                check_conflict!(switch_to_skills);
-                check_conflict!(switch_to_spells);
-                check_conflict!(switch_to_hotbar_1);
-                check_conflict!(switch_to_hotbar_2);
-                check_conflict!(switch_to_hotbar_3);
-
-                macro_rules! set_field {
-                    ($field:ident) => {
-                        if action == stringify!($field) {
-                            settings.key_bindings.$field[index] = new_key.clone();
-                        }
-                    };
+                    // Refresh the runtime bindings from the updated settings
+                    *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
+                    *input_bindings = InputBindings::from_settings(&settings.key_bindings);
                 }
-
-                set_field!(move_up);
-                set_field!(move_down);
-                set_field!(move_left);
-                set_field!(move_right);
-                set_field!(inventory);
-                set_field!(skills);
-                set_field!(spells);
-                set_field!(settings);
-                set_field!(refresh);
-                set_field!(basic_attack);
-                set_field!(hotbar_slot_1);
-                set_field!(hotbar_slot_2);
-                set_field!(hotbar_slot_3);
-                set_field!(hotbar_slot_4);
-                set_field!(hotbar_slot_5);
-                set_field!(hotbar_slot_6);
-                set_field!(hotbar_slot_7);
-                set_field!(hotbar_slot_8);
-                set_field!(hotbar_slot_9);
-                set_field!(hotbar_slot_10);
-                set_field!(hotbar_slot_11);
-                set_field!(hotbar_slot_12);
-                set_field!(switch_to_inventory);
-                set_field!(switch_to_skills);
-                set_field!(switch_to_spells);
-                set_field!(switch_to_hotbar_1);
-                set_field!(switch_to_hotbar_2);
-                set_field!(switch_to_hotbar_3);
-
-                // Refresh the runtime bindings from the updated settings
-                *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
-                *input_bindings = InputBindings::from_settings(&settings.key_bindings);
             }
             UiToCore::UnbindKey { action, index } => {
-                use crate::input::{InputBindings, UnifiedInputBindings};
-                let index = *index;
+                use crate::input::{GameAction, InputBindings, UnifiedInputBindings};
+                if let Some(action) = GameAction::from_action_id(action) {
+                    settings.key_bindings.unbind(action, *index);
 
-                macro_rules! clear_field {
-                    ($field:ident) => {
-                        if action == stringify!($field) {
-                            settings.key_bindings.$field[index] = "".to_string();
-                        }
-                    };
+                    // Refresh the runtime bindings from the updated settings
+                    *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
+                    *input_bindings = InputBindings::from_settings(&settings.key_bindings);
                 }
+            }
+            UiToCore::KeyPressModeChange { action, mode } => {
+                use crate::input::{GameAction, InputBindings, UnifiedInputBindings};
+                if let Some(action) = GameAction::from_action_id(action).filter(|a| a.is_held()) {
+                    settings.key_bindings.get_mut(action).mode =
+                        crate::settings_types::PressMode::from_u8(*mode);
 
-                clear_field!(move_up);
-                clear_field!(move_down);
-                clear_field!(move_left);
-                clear_field!(move_right);
-                clear_field!(inventory);
-                clear_field!(skills);
-                clear_field!(spells);
-                clear_field!(settings);
-                clear_field!(refresh);
-                clear_field!(basic_attack);
-                clear_field!(hotbar_slot_1);
-                clear_field!(hotbar_slot_2);
-                clear_field!(hotbar_slot_3);
-                clear_field!(hotbar_slot_4);
-                clear_field!(hotbar_slot_5);
-                clear_field!(hotbar_slot_6);
-                clear_field!(hotbar_slot_7);
-                clear_field!(hotbar_slot_8);
-                clear_field!(hotbar_slot_9);
-                clear_field!(hotbar_slot_10);
-                clear_field!(hotbar_slot_11);
-                clear_field!(hotbar_slot_12);
-                clear_field!(switch_to_inventory);
-                clear_field!(switch_to_skills);
-                clear_field!(switch_to_spells);
-                clear_field!(switch_to_hotbar_1);
-                clear_field!(switch_to_hotbar_2);
-                clear_field!(switch_to_hotbar_3);
-
-                // Refresh the runtime bindings from the updated settings
-                *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
-                *input_bindings = InputBindings::from_settings(&settings.key_bindings);
+                    *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
+                    *input_bindings = InputBindings::from_settings(&settings.key_bindings);
+                }
             }
             UiToCore::ExitApplication => {
                 let _ = slint::quit_event_loop();
@@ -586,6 +686,10 @@ fn handle_ui_inbound_ingame(
                 world_list_state.filter = filter.clone();
                 world_list_state.version = world_list_state.version.wrapping_add(1);
             }
+            UiToCore::FriendToggle { name } => {
+                settings.gameplay.toggle_friend(name);
+                world_list_state.version = world_list_state.version.wrapping_add(1);
+            }
             UiToCore::Unequip { slot } => {
                 inventory_events.write(InventoryEvent::Unequip { slot: *slot });
             }
@@ -795,35 +899,54 @@ fn handle_ui_inbound_ingame(
                     if matches!(src_category, SlotPanelType::Item) {
                         if let Some(item) = inv_state.0.iter().find(|i| i.slot == *src_index as u8)
                         {
-                            if let Some((_, entity_id, is_creature, _, _)) = hits.first() {
-                                if *is_creature {
-                                    if let Some(eid) = entity_id {
-                                        outbox.send(&client::ItemDroppedOnCreature {
-                                            source_slot: item.slot,
-                                            target_id: eid.id,
-                                            count: 1, // Only drop 1 as requested
-                                        });
+                            let target = match hits.first() {
+                                Some((_, Some(eid), true, _, _)) => {
+                                    Some(SplitStackTarget::Creature { id: eid.id })
+                                }
+                                // Creature without a server id can't receive items
+                                Some((_, None, true, _, _)) => None,
+                                _ => {
+                                    // The server silently refuses drops onto walls
+                                    // and off the map, so nudge those to a free tile
+                                    let item_tiles = interaction_res
+                                        .ground_items
+                                        .iter()
+                                        .map(|pos| (pos.x.round() as u8, pos.y.round() as u8))
+                                        .collect();
+                                    let tile = crate::ecs::collision::choose_drop_tile(
+                                        tile_i,
+                                        interaction_res.collision_table.as_deref(),
+                                        interaction_res.map_collision.as_deref(),
+                                        &item_tiles,
+                                    );
+                                    if tile.is_none() {
+                                        outbound.write(UiOutbound(CoreToUi::Toast {
+                                            text: "There's no room to drop that there".to_string(),
+                                        }));
                                     }
+                                    tile.map(|(x, y)| SplitStackTarget::Ground {
+                                        x: x as u16,
+                                        y: y as u16,
+                                    })
+                                }
+                            };
+
+                            if let Some(target) = target {
+                                if crate::plugins::item_confirm::needs_confirm(
+                                    &settings,
+                                    Some(&*session),
+                                    &item.name,
+                                ) {
+                                    item_confirm.0 =
+                                        Some(crate::plugins::item_confirm::ItemConfirmRequest {
+                                            slot: item.slot,
+                                            item_name: item.name.clone(),
+                                            sprite: item.sprite,
+                                            action: GuardedAction::Drop(target),
+                                        });
                                 } else {
-                                    outbox.send(&client::ItemDrop {
-                                        source_slot: item.slot,
-                                        destination_point: (
-                                            tile_i.0.max(0) as u16,
-                                            tile_i.1.max(0) as u16,
-                                        ),
-                                        count: 1, // Only drop 1 as requested
-                                    });
+                                    drop_item(item, target, &mut split_stack, &outbox);
                                 }
-                            } else {
-                                // Drop on empty tile
-                                outbox.send(&client::ItemDrop {
-                                    source_slot: item.slot,
-                                    destination_point: (
-                                        tile_i.0.max(0) as u16,
-                                        tile_i.1.max(0) as u16,
-                                    ),
-                                    count: 1, // Only drop 1 as requested
-                                });
                             }
                         }
                     }
@@ -872,6 +995,63 @@ fn handle_ui_inbound_ingame(
             UiToCore::SendGroupInvite { name } => {
                 outbox.send(&packets::client::GroupInvite::Request { name: name.clone() });
             }
+            UiToCore::SplitStackConfirm { count } => {
+                if let Some(request) = split_stack.0.take() {
+                    let count = (*count).clamp(1, request.max);
+                    request.target.send(&outbox, request.source_slot, count);
+                }
+            }
+            UiToCore::SplitStackCancel => {
+                split_stack.0 = None;
+            }
+            UiToCore::ShowItemLink { name } => {
+                // Only our own items carry details; anything else shows just the name
+                let mut sprite = None;
+                let mut details = Vec::new();
+                if let Some(item) = inv_state
+                    .0
+                    .iter()
+                    .find(|i| i.name.eq_ignore_ascii_case(name))
+                {
+                    sprite = Some(item.sprite);
+                    if item.stackable {
+                        details.push(format!("Count: {}", item.count));
+                    }
+                    if item.max_durability > 0 {
+                        details.push(format!(
+                            "Durability: {} / {}",
+                            item.current_durability, item.max_durability
+                        ));
+                    }
+                } else if let Some(item) =
+                    eq_state.0.values().find(|e| e.name.eq_ignore_ascii_case(name))
+                {
+                    sprite = Some(item.sprite);
+                    if item.max_durability > 0 {
+                        details.push(format!(
+                            "Durability: {} / {}",
+                            item.current_durability, item.max_durability
+                        ));
+                    }
+                }
+                outbound.write(UiOutbound(CoreToUi::ItemLinkTooltip {
+                    name: name.clone(),
+                    sprite,
+                    details: details.join("\n"),
+                }));
+            }
+            UiToCore::WindowGeometryChange { id, geometry } => {
+                settings.set_window_geometry(session.server_id, &session.username, id, *geometry);
+            }
+            UiToCore::WindowLayoutReset => {
+                settings.clear_window_layout(session.server_id, &session.username);
+                outbound.write(UiOutbound(CoreToUi::WindowLayoutSync {
+                    windows: Vec::new(),
+                }));
+            }
+            UiToCore::PipWatch { name } => {
+                pip_target.watch(name.clone());
+            }
             UiToCore::RespondGroupInvite { accept, source_name } => {
                 if *accept {
                     outbox.send(&packets::client::GroupInvite::Forced {
@@ -903,6 +1083,8 @@ fn handle_ui_inbound_login(
     mut commands: Commands,
     storage_config: Res<crate::resources::StorageConfig>,
     bindings: InputBindingResources,
+    settings_recovery: Option<Res<crate::settings_migration::SettingsRecovery>>,
+    mut vault: ResMut<CredentialVault>,
 ) {
     let mut input_bindings = bindings.input_bindings;
     let mut unified_bindings = bindings.unified_bindings;
@@ -918,6 +1100,48 @@ fn handle_ui_inbound_login(
                 outbound.write(UiOutbound(settings.to_snapshot_message(None)));
                 outbound.write(UiOutbound(settings.to_sync_message()));
             }
+            UiToCore::LoginRetryCancel => {
+                commands.remove_resource::<LoginRetry>();
+            }
+            UiToCore::CrashReportCopy { path: report_path } => {
+                let path = std::path::Path::new(report_path);
+                let error = match std::fs::read_to_string(path) {
+                    Ok(report) if crate::crash_report::copy_to_clipboard(&report) => None,
+                    Ok(_) => {
+                        tracing::warn!("Clipboard unavailable, crash report left at {:?}", path);
+                        Some("The clipboard isn't available. Open the file above to copy the report.".to_string())
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to read crash report {:?}: {}", path, e);
+                        Some(format!("Couldn't read the report: {}", e))
+                    }
+                };
+                // Only a copied report counts as handled; otherwise ask again
+                match error {
+                    None => crate::crash_report::mark_report_seen(path),
+                    Some(error) => {
+                        outbound.write(UiOutbound(CoreToUi::CrashReportPending {
+                            path: report_path.clone(),
+                            error: Some(error),
+                            can_upload: !settings.crash_reports.upload_url.is_empty(),
+                        }));
+                    }
+                }
+            }
+            UiToCore::CrashReportDismiss { path } => {
+                crate::crash_report::mark_report_seen(std::path::Path::new(path));
+            }
+            UiToCore::SettingsRecover { restore_backup } => {
+                let Some(recovery) = settings_recovery.as_deref() else {
+                    continue;
+                };
+                settings.recover(recovery, *restore_backup, &storage_config);
+                commands.remove_resource::<crate::settings_migration::SettingsRecovery>();
+                *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
+                *input_bindings = InputBindings::from_settings(&settings.key_bindings);
+                outbound.write(UiOutbound(settings.to_snapshot_message(None)));
+                outbound.write(UiOutbound(settings.to_sync_message()));
+            }
             UiToCore::LoginSubmit {
                 server_id,
                 username,
@@ -934,26 +1158,24 @@ fn handle_ui_inbound_login(
                     .iter()
                     .find(|s| s.id == *server_id)
                     .cloned();
+                if *remember && !vault.keyring_available && !vault.is_unlocked() {
+                    vault.pending = Some(PendingLogin::Submit {
+                        server_id: *server_id,
+                        username: username.clone(),
+                        password: password.clone(),
+                    });
+                    outbound.write(UiOutbound(CoreToUi::VaultPrompt {
+                        create: vault.can_create(),
+                        error: vault.unreadable().map(str::to_owned),
+                    }));
+                    continue;
+                }
                 if let Some(server) = server {
                     let uname = username.clone();
                     let pw = password.clone();
-                    let uname_task = uname.clone();
-                    let pw_task = pw.clone();
                     let remember = *remember;
                     let cred_id = format!("{}:{}", server.id, uname);
-                    let task: Task<
-                        Result<(network::DecryptedReceiver, network::EncryptedSender), LoginError>,
-                    > = IoTaskPool::get().spawn(async move {
-                        let (host, port) = parse_host_port(&server.address)
-                            .unwrap_or((server.address.clone(), 2610));
-                        match crate::session_prelogin::PreLoginSession::new(&host, port).await {
-                            Ok(lobby) => match lobby.login(&uname_task, &pw_task).await {
-                                Ok((rx, tx)) => Ok((rx, tx)),
-                                Err(code) => Err(code),
-                            },
-                            Err(_) => Err(LoginError::Unknown),
-                        }
-                    });
+                    let task = spawn_login_task(&server, uname.clone(), pw.clone());
                     commands.spawn(LoginTaskEntity(LoginTaskInner {
                         task,
                         remember,
@@ -986,7 +1208,15 @@ fn handle_ui_inbound_login(
                         continue;
                     }
                 };
-                match keyring::get_password(&cred_id) {
+                if !vault.keyring_available && !vault.is_unlocked() {
+                    vault.pending = Some(PendingLogin::Saved { id: cred_id });
+                    outbound.write(UiOutbound(CoreToUi::VaultPrompt {
+                        create: vault.can_create(),
+                        error: vault.unreadable().map(str::to_owned),
+                    }));
+                    continue;
+                }
+                match keyring::get_password(&vault, &cred_id) {
                     Ok(password) => {
                         if let Some(server) =
                             settings.servers.iter().find(|s| s.id == server_id).cloned()
@@ -996,27 +1226,7 @@ fn handle_ui_inbound_login(
                                 server_id
                             );
                             let uname = username.clone();
-                            let pw_task = password.clone();
-                            let uname_for_task = uname.clone();
-                            let task: Task<
-                                Result<
-                                    (network::DecryptedReceiver, network::EncryptedSender),
-                                    LoginError,
-                                >,
-                            > = IoTaskPool::get().spawn(async move {
-                                let (host, port) = parse_host_port(&server.address)
-                                    .unwrap_or((server.address.clone(), 2610));
-                                match crate::session_prelogin::PreLoginSession::new(&host, port)
-                                    .await
-                                {
-                                    Ok(lobby) => match lobby.login(&uname_for_task, &pw_task).await
-                                    {
-                                        Ok((rx, tx)) => Ok((rx, tx)),
-                                        Err(code) => Err(code),
-                                    },
-                                    Err(_) => Err(LoginError::Unknown),
-                                }
-                            });
+                            let task = spawn_login_task(&server, uname.clone(), password);
                             commands.spawn(LoginTaskEntity(LoginTaskInner {
                                 task,
                                 remember: false,
@@ -1055,14 +1265,31 @@ fn handle_ui_inbound_login(
                         current_server_id: settings.gameplay.current_server_id,
                         logins: logins_public,
                         login_error: None,
+                        streamer_mode: settings.gameplay.streamer_mode,
                     }));
                 }
             }
             UiToCore::LoginRemoveSaved { id } => {
-                let _ = keyring::delete_password(id);
+                let _ = keyring::delete_password(&mut vault, id);
                 settings.remove_credential(id, &storage_config);
                 outbound.write(UiOutbound(settings.to_snapshot_message(None)));
             }
+            UiToCore::VaultUnlock { password } => match vault.begin_unlock(password) {
+                Ok(derivation) => {
+                    let task =
+                        AsyncComputeTaskPool::get().spawn(async move { derivation.derive() });
+                    commands.insert_resource(VaultUnlockTask(task));
+                }
+                Err(e) => {
+                    outbound.write(UiOutbound(CoreToUi::VaultPrompt {
+                        create: vault.can_create(),
+                        error: Some(e.to_string()),
+                    }));
+                }
+            },
+            UiToCore::VaultCancel => {
+                vault.pending = None;
+            }
             UiToCore::ServersChangeCurrent { id } => {
                 settings.gameplay.current_server_id = Some(*id);
                 outbound.write(UiOutbound(settings.to_snapshot_message(None)));
@@ -1073,6 +1300,11 @@ fn handle_ui_inbound_login(
                     id: new_id,
                     name: server.name.clone(),
                     address: server.address.clone(),
+                    protocol: None,
+                    icon: None,
+                    news_url: optional_url(&server.news_url),
+                    source: None,
+                    connection: Default::default(),
                 });
                 if settings.gameplay.current_server_id.is_none() {
                     settings.gameplay.current_server_id = Some(new_id);
@@ -1083,6 +1315,7 @@ fn handle_ui_inbound_login(
                 if let Some(s) = settings.servers.iter_mut().find(|s| s.id == server.id) {
                     s.name = server.name.clone();
                     s.address = server.address.clone();
+                    s.news_url = optional_url(&server.news_url);
                 }
                 outbound.write(UiOutbound(settings.to_snapshot_message(None)));
             }
@@ -1104,148 +1337,183 @@ fn handle_ui_inbound_login(
                     settings.audio.music_volume = *music_vol;
                 }
             }
+            UiToCore::CategoryVolumeChange { category, volume } => {
+                settings.audio.set_category_volume(
+                    crate::settings_types::SoundCategory::from_u8(*category),
+                    *volume,
+                );
+            }
+            UiToCore::AudioDeviceChange { device } => {
+                settings.audio.output_device = (!device.is_empty()).then(|| device.clone());
+            }
             UiToCore::ScaleChange { scale } => {
                 settings.graphics.scale = *scale;
             }
+            UiToCore::RenderScalingChange { mode } => {
+                settings.graphics.render_scaling =
+                    crate::settings_types::RenderScaling::from_u8(*mode);
+            }
+            UiToCore::GammaChange { gamma } => {
+                settings.graphics.gamma = gamma.clamp(0.5, 2.0);
+            }
+            UiToCore::XRayModeChange { mode } => {
+                settings.graphics.xray_mode = crate::settings_types::XRayMode::from_u8(*mode);
+            }
+            UiToCore::OpenInteriorsChange { enabled } => {
+                settings.graphics.open_interiors = *enabled;
+            }
+            UiToCore::AttackTelegraphsChange { enabled } => {
+                settings.graphics.attack_telegraphs = *enabled;
+            }
+            UiToCore::DebugOverlayChange { enabled } => {
+                settings.graphics.debug_overlay = *enabled;
+            }
+            UiToCore::PotatoModeChange { enabled } => {
+                crate::plugins::potato_mode::set_potato_mode(&mut settings, *enabled);
+            }
+            UiToCore::GpuAdapterChange {
+                backend,
+                preference,
+            } => {
+                settings.graphics.gpu_backend =
+                    crate::settings_types::GpuBackend::from_u8(*backend);
+                settings.graphics.gpu_preference =
+                    crate::settings_types::GpuPreference::from_u8(*preference);
+            }
+            UiToCore::TextureBudgetChange { mb } => {
+                settings.graphics.texture_budget_mb = *mb;
+            }
+            UiToCore::StreamerModeChange { enabled } => {
+                settings.gameplay.streamer_mode = *enabled;
+                outbound.write(UiOutbound(settings.to_snapshot_message(None)));
+            }
+            UiToCore::StreamOutputChange { enabled } => {
+                settings.stream_output.enabled = *enabled;
+            }
+            UiToCore::DebugConsoleChange { enabled } => {
+                settings.debug_console.enabled = *enabled;
+            }
+            UiToCore::IdleDaydreamChange { minutes } => {
+                settings.gameplay.idle_daydream_minutes = *minutes;
+            }
+            UiToCore::AntiIdleChange {
+                enabled,
+                max_minutes,
+            } => {
+                settings.gameplay.anti_idle = *enabled;
+                settings.gameplay.anti_idle_max_minutes = *max_minutes;
+            }
+            UiToCore::DurabilityWarningChange { percent } => {
+                settings.gameplay.durability_warning_percent = *percent;
+            }
+            UiToCore::SpellQueueWindowChange { ms } => {
+                settings.gameplay.spell_queue_window_ms = *ms;
+            }
+            UiToCore::ChatBubblesChange { enabled } => {
+                settings.gameplay.chat_bubbles = *enabled;
+            }
+            UiToCore::MentionAlertsChange { highlight, sound } => {
+                settings.gameplay.mention_highlight = *highlight;
+                settings.gameplay.mention_sound = *sound;
+            }
+            UiToCore::ChatFilterChange { enabled, words } => {
+                settings.chat_filter.enabled = *enabled;
+                settings.chat_filter.words = crate::chat_filter::parse_word_list(words);
+            }
+            UiToCore::ChatFilterChannelChange { channel, enabled } => {
+                if let Some(slot) = settings.chat_filter.channels.get_mut(*channel as usize) {
+                    *slot = *enabled;
+                }
+            }
+            UiToCore::AutoPickupChange { enabled, gold } => {
+                settings.gameplay.auto_pickup = *enabled;
+                settings.gameplay.auto_pickup_gold = *gold;
+            }
+            UiToCore::DangerousDialogOptionsChange { options } => {
+                settings.gameplay.dangerous_dialog_options =
+                    crate::plugins::dialog_confirm::parse_option_list(options);
+            }
+            UiToCore::HotbarLayoutChange { rows, icon_size } => {
+                use crate::settings_types::{
+                    MAX_HOTBAR_ICON_SIZE, MAX_HOTBAR_ROWS, MIN_HOTBAR_ICON_SIZE, MIN_HOTBAR_ROWS,
+                };
+                settings.layout.hotbar_rows = (*rows).clamp(MIN_HOTBAR_ROWS, MAX_HOTBAR_ROWS);
+                settings.layout.hotbar_icon_size =
+                    (*icon_size).clamp(MIN_HOTBAR_ICON_SIZE, MAX_HOTBAR_ICON_SIZE);
+            }
+            UiToCore::HotbarMoved { position } => {
+                settings.layout.hotbar_position = *position;
+            }
+            UiToCore::ClassicHudChange { enabled } => {
+                settings.layout.classic_hud = *enabled;
+            }
+            UiToCore::HotbarAutoSwitchChange {
+                enabled,
+                town_panel,
+                combat_panel,
+            } => {
+                settings.layout.hotbar_auto_switch = *enabled;
+                settings.layout.hotbar_town_panel = (*town_panel).min(5);
+                settings.layout.hotbar_combat_panel = (*combat_panel).min(5);
+            }
+            UiToCore::NotificationPolicyChange { status, policy } => {
+                if let Some(slot) = settings
+                    .gameplay
+                    .notification_policies
+                    .get_mut(*status as usize)
+                {
+                    *slot = crate::settings_types::NotificationPolicy::from_u8(*policy);
+                }
+            }
+            UiToCore::TurnTapChange { ms } => {
+                settings.input.turn_tap_ms = (*ms).min(500);
+            }
+            UiToCore::DirectionPriorityChange { mode } => {
+                settings.input.direction_priority =
+                    crate::settings_types::DirectionPriority::from_u8(*mode);
+            }
             UiToCore::RebindKey {
                 action,
                 new_key,
                 index,
             } => {
-                use crate::input::{InputBindings, UnifiedInputBindings};
-                let index = *index;
-
-                // Conflict detection: if new_key is already bound to another action, clear that action's binding at that index
-                macro_rules! check_conflict {
-                    ($field:ident) => {
-                        for k in settings.key_bindings.$field.iter_mut() {
-                            if !new_key.is_empty() && k == new_key {
-                                *k = "".to_string();
-                            }
-                        }
-                    };
-                }
+                use crate::input::{GameAction, InputBindings, UnifiedInputBindings};
+                if let Some(action) = GameAction::from_action_id(action) {
+                    settings.key_bindings.rebind(action, *index, new_key);
 
-                check_conflict!(move_up);
-                check_conflict!(move_down);
-                check_conflict!(move_left);
-                check_conflict!(move_right);
-                check_conflict!(inventory);
-                check_conflict!(skills);
-                check_conflict!(spells);
-                check_conflict!(settings);
-                check_conflict!(refresh);
-                check_conflict!(basic_attack);
-                check_conflict!(hotbar_slot_1);
-                check_conflict!(hotbar_slot_2);
-                check_conflict!(hotbar_slot_3);
-                check_conflict!(hotbar_slot_4);
-                check_conflict!(hotbar_slot_5);
-                check_conflict!(hotbar_slot_6);
-                check_conflict!(hotbar_slot_7);
-                check_conflict!(hotbar_slot_8);
-                check_conflict!(hotbar_slot_9);
-                check_conflict!(hotbar_slot_10);
-                check_conflict!(hotbar_slot_11);
-                check_conflict!(hotbar_slot_12);
-                check_conflict!(switch_to_inventory);
-                check_conflict!(switch_to_skills);
-                check_conflict!(switch_to_spells);
-                check_conflict!(switch_to_hotbar_1);
-                check_conflict!(switch_to_hotbar_2);
-                check_conflict!(switch_to_hotbar_3);
-
-                macro_rules! set_field {
-                    ($field:ident) => {
-                        if action == stringify!($field) {
-                            settings.key_bindings.$field[index] = new_key.clone();
-                        }
-                    };
+                    // Refresh the runtime bindings from the updated settings
+                    *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
+                    *input_bindings = InputBindings::from_settings(&settings.key_bindings);
                 }
-
-                set_field!(move_up);
-                set_field!(move_down);
-                set_field!(move_left);
-                set_field!(move_right);
-                set_field!(inventory);
-                set_field!(skills);
-                set_field!(spells);
-                set_field!(settings);
-                set_field!(refresh);
-                set_field!(basic_attack);
-                set_field!(hotbar_slot_1);
-                set_field!(hotbar_slot_2);
-                set_field!(hotbar_slot_3);
-                set_field!(hotbar_slot_4);
-                set_field!(hotbar_slot_5);
-                set_field!(hotbar_slot_6);
-                set_field!(hotbar_slot_7);
-                set_field!(hotbar_slot_8);
-                set_field!(hotbar_slot_9);
-                set_field!(hotbar_slot_10);
-                set_field!(hotbar_slot_11);
-                set_field!(hotbar_slot_12);
-                set_field!(switch_to_inventory);
-                set_field!(switch_to_skills);
-                set_field!(switch_to_spells);
-                set_field!(switch_to_hotbar_1);
-                set_field!(switch_to_hotbar_2);
-                set_field!(switch_to_hotbar_3);
-
-                // Refresh the runtime bindings from the updated settings
-                *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
-                *input_bindings = InputBindings::from_settings(&settings.key_bindings);
             }
             UiToCore::UnbindKey { action, index } => {
-                use crate::input::{InputBindings, UnifiedInputBindings};
-                let index = *index;
+                use crate::input::{GameAction, InputBindings, UnifiedInputBindings};
+                if let Some(action) = GameAction::from_action_id(action) {
+                    settings.key_bindings.unbind(action, *index);
 
-                macro_rules! clear_field {
-                    ($field:ident) => {
-                        if action == stringify!($field) {
-                            settings.key_bindings.$field[index] = "".to_string();
-                        }
-                    };
+                    // Refresh the runtime bindings from the updated settings
+                    *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
+                    *input_bindings = InputBindings::from_settings(&settings.key_bindings);
                 }
+            }
+            UiToCore::KeyPressModeChange { action, mode } => {
+                use crate::input::{GameAction, InputBindings, UnifiedInputBindings};
+                if let Some(action) = GameAction::from_action_id(action).filter(|a| a.is_held()) {
+                    settings.key_bindings.get_mut(action).mode =
+                        crate::settings_types::PressMode::from_u8(*mode);
 
-                clear_field!(move_up);
-                clear_field!(move_down);
-                clear_field!(move_left);
-                clear_field!(move_right);
-                clear_field!(inventory);
-                clear_field!(skills);
-                clear_field!(spells);
-                clear_field!(settings);
-                clear_field!(refresh);
-                clear_field!(basic_attack);
-                clear_field!(hotbar_slot_1);
-                clear_field!(hotbar_slot_2);
-                clear_field!(hotbar_slot_3);
-                clear_field!(hotbar_slot_4);
-                clear_field!(hotbar_slot_5);
-                clear_field!(hotbar_slot_6);
-                clear_field!(hotbar_slot_7);
-                clear_field!(hotbar_slot_8);
-                clear_field!(hotbar_slot_9);
-                clear_field!(hotbar_slot_10);
-                clear_field!(hotbar_slot_11);
-                clear_field!(hotbar_slot_12);
-                clear_field!(switch_to_inventory);
-                clear_field!(switch_to_skills);
-                clear_field!(switch_to_spells);
-                clear_field!(switch_to_hotbar_1);
-                clear_field!(switch_to_hotbar_2);
-                clear_field!(switch_to_hotbar_3);
-
-                // Refresh the runtime bindings from the updated settings
-                *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
-                *input_bindings = InputBindings::from_settings(&settings.key_bindings);
+                    *unified_bindings = UnifiedInputBindings::from_settings(&settings.key_bindings);
+                    *input_bindings = InputBindings::from_settings(&settings.key_bindings);
+                }
             }
             _ => {}
         }
     }
 }
 
+/// Chime for a chat line naming the character, the same alert group invites use.
+const MENTION_SOUND: u8 = 1;
+
 /// Server sends these when group membership changes; we request SelfProfile so the group panel stays in sync.
 fn is_group_change_system_message(msg: &str) -> bool {
     let msg = msg.trim();
@@ -1260,18 +1528,42 @@ fn bridge_chat_events(
     mut outbound: MessageWriter<UiOutbound>,
     mut menu_ctx: ResMut<ActiveMenuContext>,
     outbox: Option<Res<crate::network::PacketOutbox>>,
+    settings: Res<SettingsFile>,
+    session: Option<Res<crate::CurrentSession>>,
+    status: Res<crate::plugins::idle::SocialStatusState>,
+    mut audio_events: MessageWriter<crate::events::AudioEvent>,
 ) {
     use packets::server::{PublicMessageType, ServerMessageType};
 
+    let name = session.as_ref().map_or("", |s| s.username.as_str());
+    let is_mention =
+        |channel: Option<ChatChannel>, text: &str| channel.is_some() && mentions(text, name);
+    let mut mentioned = false;
     let mut to_append: Vec<ChatEntryUi> = Vec::new();
     for evt in chat_events.read() {
         match evt {
             ChatEvent::ServerMessage(pkt) => {
+                let channel = ChatChannel::from_server(pkt.message_type);
+                let filtered = settings.chat_filter.apply(channel, &pkt.message);
+                let mention = is_mention(channel, &pkt.message);
+                mentioned |= mention;
                 if let Some(ref out) = outbox {
                     if is_group_change_system_message(&pkt.message) {
                         out.send(&packets::client::SelfProfileRequest {});
                     }
                 }
+                // Only shown while the chat is hidden, see `plugins::notifications`
+                if pkt.message_type == ServerMessageType::Whisper {
+                    outbound.write(UiOutbound(CoreToUi::Notify {
+                        severity: NotifySeverity::Info,
+                        kind: NotifyKind::Whisper,
+                        text: if settings.gameplay.streamer_mode {
+                            "New whisper".to_string()
+                        } else {
+                            filtered.clone().unwrap_or_else(|| pkt.message.clone())
+                        },
+                    }));
+                }
                 let (show_in_message_box, show_in_action_bar, color) = match pkt.message_type {
                     ServerMessageType::Whisper => (true, false, Some("#60a5fa".to_string())),
                     ServerMessageType::OrangeBar1
@@ -1325,6 +1617,14 @@ fn bridge_chat_events(
                     show_in_message_box,
                     show_in_action_bar,
                     color,
+                    concealed: if settings.gameplay.streamer_mode
+                        && pkt.message_type == ServerMessageType::Whisper
+                    {
+                        Some("[Whisper hidden - click to reveal]".to_string())
+                    } else {
+                        filtered
+                    },
+                    mention: mention && settings.gameplay.mention_highlight,
                 });
             }
             ChatEvent::PublicMessage(pkt) => {
@@ -1332,6 +1632,10 @@ fn bridge_chat_events(
                     continue;
                 }
 
+                let channel = ChatChannel::from_public(pkt.message_type);
+                let mention = is_mention(channel, &pkt.message);
+                mentioned |= mention;
+
                 let color = match pkt.message_type {
                     PublicMessageType::Normal => Some("#d0d0d0".to_string()),
                     PublicMessageType::Shout => Some("#ffeb3b".to_string()),
@@ -1345,6 +1649,8 @@ fn bridge_chat_events(
                     show_in_message_box: true,
                     show_in_action_bar: false,
                     color,
+                    concealed: settings.chat_filter.apply(channel, &pkt.message),
+                    mention: mention && settings.gameplay.mention_highlight,
                 });
             }
             _ => {}
@@ -1353,6 +1659,19 @@ fn bridge_chat_events(
     if !to_append.is_empty() {
         outbound.write(UiOutbound(CoreToUi::ChatAppend { entries: to_append }));
     }
+    if mentioned
+        && settings.gameplay.mention_sound
+        && settings
+            .gameplay
+            .notification_policy(status.status)
+            .plays_sounds()
+    {
+        audio_events.write(crate::events::AudioEvent::PlayEffect {
+            sound: MENTION_SOUND,
+            category: crate::settings_types::SoundCategory::Ambient,
+            origin: crate::events::SoundOrigin::Local,
+        });
+    }
 }
 
 fn bridge_session_events(
@@ -1382,6 +1701,8 @@ fn bridge_session_events(
                         menu_ctx.dialog_id = Some(header.dialog_id);
                         menu_ctx.menu_type = None;
                         menu_ctx.args.clear();
+                        menu_ctx.options.clear();
+                        menu_ctx.has_next = header.has_next_button;
 
                         let mut entries = Vec::new();
                         // Put Previous above Next as requested
@@ -1403,6 +1724,9 @@ fn bridge_session_events(
                                         option.clone(),
                                         100_000 + idx as i32,
                                     ));
+                                    menu_ctx
+                                        .options
+                                        .push((100_000 + idx as i32, option.clone()));
                                 }
                             }
                             packets::server::DisplayDialogPayload::TextEntry { info } => {
@@ -1441,6 +1765,7 @@ fn bridge_session_events(
                     packets::server::DisplayDialog::Close => {
                         menu_ctx.window_type = ActiveWindowType::None;
                         menu_ctx.dialog_id = None;
+                        menu_ctx.has_next = false;
                         outbound.write(UiOutbound(CoreToUi::DisplayMenuClose));
                     }
                 }
@@ -1539,6 +1864,8 @@ fn bridge_session_events(
                 menu_ctx.menu_type = Some(pkt.menu_type);
                 menu_ctx.args.clear();
                 menu_ctx.dialog_id = None;
+                menu_ctx.options.clear();
+                menu_ctx.has_next = false;
 
                 let mut entries = Vec::new();
                 let mut entry_type = crate::webui::ipc::MenuEntryType::TextOptions;
@@ -1547,6 +1874,10 @@ fn bridge_session_events(
                 match &pkt.payload {
                     DisplayMenuPayload::Menu { options } => {
                         menu_ctx.pursuit_id = None;
+                        menu_ctx.options = options
+                            .iter()
+                            .map(|(text, id)| (*id as i32, text.clone()))
+                            .collect();
                         entries = options
                             .iter()
                             .map(|(text, id)| MenuEntryUi::text_option(text.clone(), *id as i32))
@@ -1555,6 +1886,10 @@ fn bridge_session_events(
                     DisplayMenuPayload::MenuWithArgs { args, options } => {
                         menu_ctx.pursuit_id = None;
                         menu_ctx.args = args.clone();
+                        menu_ctx.options = options
+                            .iter()
+                            .map(|(text, id)| (*id as i32, text.clone()))
+                            .collect();
                         entries = options
                             .iter()
                             .map(|(text, id)| MenuEntryUi::text_option(text.clone(), *id as i32))
@@ -1718,7 +2053,12 @@ fn bridge_inventory_events(
     mut inv_state: ResMut<InventoryState>,
     mut eq_state: ResMut<EquipmentState>,
     mut show_profile: MessageWriter<crate::slint_plugin::ShowSelfProfileEvent>,
+    mut outbound: MessageWriter<UiOutbound>,
+    settings: Res<SettingsFile>,
 ) {
+    use crate::slint_support::profile_bridge::is_low_durability;
+
+    let threshold = settings.gameplay.durability_warning_percent;
     let mut equipment_changed = false;
     for evt in inventory_events.read() {
         match evt {
@@ -1759,6 +2099,20 @@ fn bridge_inventory_events(
                 inv_state.0.retain(|i| i.slot != pkt.slot);
             }
             InventoryEvent::Equipment(pkt) => {
+                let was_low = eq_state.0.get(&pkt.slot).is_some_and(|prev| {
+                    prev.name == pkt.name
+                        && is_low_durability(prev.current_durability, prev.max_durability, threshold)
+                });
+                if !was_low
+                    && is_low_durability(pkt.current_durability, pkt.max_durability, threshold)
+                {
+                    outbound.write(UiOutbound(CoreToUi::Toast {
+                        text: format!(
+                            "{} is badly worn ({}/{}), repair it soon",
+                            pkt.name, pkt.current_durability, pkt.max_durability
+                        ),
+                    }));
+                }
                 eq_state.0.insert(pkt.slot, pkt.clone());
                 equipment_changed = true;
             }
@@ -1777,7 +2131,11 @@ fn bridge_inventory_events(
     }
 }
 
-fn update_world_list_filtered(mut state: ResMut<WorldListState>, mut last_version: Local<u32>) {
+fn update_world_list_filtered(
+    mut state: ResMut<WorldListState>,
+    settings: Res<SettingsFile>,
+    mut last_version: Local<u32>,
+) {
     if state.version == *last_version {
         return;
     }
@@ -1820,6 +2178,7 @@ fn update_world_list_filtered(mut state: ResMut<WorldListState>, mut last_versio
             title: m.title.clone(),
             class: format!("{:?}", m.base_class),
             is_master: m.is_master,
+            is_friend: settings.gameplay.is_friend(&m.name),
             color: match m.color {
                 packets::server::WorldListColor::Guilded => [1.0, 0.75, 0.25, 1.0], // Gold-ish
                 packets::server::WorldListColor::Unknown => [1.0, 0.596, 0.0, 1.0], // Orange
@@ -1883,6 +2242,9 @@ pub struct WorldListState {
 // Group state (from SelfProfile + DisplayGroupInvite)
 // ---------------------------------------------------------------------------
 
+/// An unanswered group invite is declined after this long.
+pub const GROUP_INVITE_TIMEOUT_SECS: i32 = 30;
+
 #[derive(Debug, Clone, Default)]
 pub struct PendingGroupInvite {
     pub source_name: String,
@@ -1893,6 +2255,148 @@ pub struct PendingGroupInvite {
 /// One group member: display name and whether the server marks them as leader (asterisk in SelfProfile).
 pub type GroupMemberEntry = (String, bool);
 
+/// Where a dragged stack goes once the player picks a count.
+#[derive(Debug, Clone, Copy)]
+pub enum SplitStackTarget {
+    Ground { x: u16, y: u16 },
+    Creature { id: u32 },
+}
+
+impl SplitStackTarget {
+    /// Largest count the drop packet can carry for a stack of `count`.
+    pub fn max_count(self, count: u32) -> u32 {
+        match self {
+            SplitStackTarget::Ground { .. } => count,
+            SplitStackTarget::Creature { .. } => count.min(u8::MAX as u32),
+        }
+    }
+
+    fn send(self, outbox: &crate::network::PacketOutbox, source_slot: u8, count: u32) {
+        match self {
+            SplitStackTarget::Ground { x, y } => outbox.send(&client::ItemDrop {
+                source_slot,
+                destination_point: (x, y),
+                count: count.min(i32::MAX as u32) as i32,
+            }),
+            SplitStackTarget::Creature { id } => outbox.send(&client::ItemDroppedOnCreature {
+                source_slot,
+                target_id: id,
+                count: count.min(u8::MAX as u32) as u8,
+            }),
+        }
+    }
+}
+
+/// Sends the option picked in an NPC menu or dialog.
+pub(crate) fn send_menu_selection(
+    menu_ctx: &ActiveMenuContext,
+    id: i32,
+    name: &str,
+    outbox: &crate::network::PacketOutbox,
+) {
+    if let Some(dialog_id) = menu_ctx.dialog_id {
+        if let Some(entity_type) = menu_ctx.entity_type {
+            let mut final_dialog_id = id;
+            let args = if id == dialog_id as i32 {
+                packets::client::DialogInteractionArgs::TextResponse {
+                    args: vec![name.to_string()],
+                }
+            } else if id >= 100_000 {
+                final_dialog_id = dialog_id as i32 + 1;
+                packets::client::DialogInteractionArgs::MenuResponse {
+                    option: (id - 100_000 + 1) as u8,
+                }
+            } else {
+                packets::client::DialogInteractionArgs::None
+            };
+
+            outbox.send(&packets::client::DialogInteraction {
+                entity_type,
+                entity_id: menu_ctx.entity_id,
+                pursuit_id: menu_ctx.pursuit_id.unwrap_or(0),
+                dialog_id: final_dialog_id as u16,
+                args,
+            });
+        }
+        return;
+    }
+
+    let is_slot_interaction = matches!(
+        menu_ctx.menu_type,
+        Some(MenuType::ShowPlayerItems)
+            | Some(MenuType::ShowPlayerSpells)
+            | Some(MenuType::ShowPlayerSkills)
+    );
+
+    let args = if is_slot_interaction {
+        packets::client::MenuInteractionArgs::Slot(id as u8)
+    } else {
+        let mut topics = Vec::new();
+        if !menu_ctx.args.is_empty() {
+            topics.push(menu_ctx.args.clone());
+        }
+        if !name.is_empty() {
+            topics.push(name.to_string());
+        }
+
+        if topics.is_empty() {
+            packets::client::MenuInteractionArgs::Slot(0)
+        } else {
+            packets::client::MenuInteractionArgs::Topics(topics)
+        }
+    };
+
+    if let Some(entity_type) = menu_ctx.entity_type {
+        outbox.send(&packets::client::MenuInteraction {
+            entity_type,
+            entity_id: menu_ctx.entity_id,
+            pursuit_id: menu_ctx.pursuit_id.unwrap_or(id as _),
+            args,
+        });
+    } else {
+        tracing::warn!("MenuSelect with no entity_type in context");
+    }
+}
+
+/// Drops or gives `item`, asking for a count first when it's a stack.
+pub(crate) fn drop_item(
+    item: &InventoryItemUi,
+    target: SplitStackTarget,
+    split_stack: &mut PendingSplitStack,
+    outbox: &crate::network::PacketOutbox,
+) {
+    let max = target.max_count(item.count);
+    if item.stackable && max > 1 {
+        // Ask how many to move before sending anything
+        split_stack.0 = Some(SplitStackRequest {
+            source_slot: item.slot,
+            item_name: item.name.clone(),
+            sprite: item.sprite,
+            max,
+            target,
+        });
+    } else {
+        target.send(outbox, item.slot, 1);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitStackRequest {
+    pub source_slot: u8,
+    pub item_name: String,
+    pub sprite: u16,
+    pub max: u32,
+    pub target: SplitStackTarget,
+}
+
+/// Stack drop waiting on the split-stack dialog.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct PendingSplitStack(pub Option<SplitStackRequest>);
+
+fn clear_split_stack(mut split_stack: ResMut<PendingSplitStack>) {
+    split_stack.0 = None;
+}
+
 #[derive(Resource, Default, Debug, Clone)]
 pub struct GroupState {
     /// (display_name, is_leader_from_server). Leader line in group_string has "* " prefix.
@@ -1901,6 +2405,14 @@ pub struct GroupState {
     pub pending_invite: Option<PendingGroupInvite>,
 }
 
+impl GroupState {
+    pub fn is_member(&self, name: &str) -> bool {
+        self.members
+            .iter()
+            .any(|(member, _)| member.eq_ignore_ascii_case(name))
+    }
+}
+
 fn update_skill_cooldowns(
     time: Res<Time>,
     mut timer: Local<Timer>,
@@ -1936,10 +2448,39 @@ fn update_skill_cooldowns(
     }
 }
 
+/// Proficiency from an ability name such as "Assail (Lev:45/100)", as (current, max).
+pub fn ability_proficiency(name: &str) -> Option<(u32, u32)> {
+    let open = name.rfind('(')?;
+    let inner = name[open + 1..].strip_suffix(')')?;
+    let (current, max) = inner.split_once('/')?;
+    let current = current.trim_start_matches(|c: char| !c.is_ascii_digit());
+    let current = current.trim().parse().ok()?;
+    let max = max.trim().parse().ok()?;
+    (max > 0).then_some((current, max))
+}
+
+fn is_mastered(name: &str) -> bool {
+    ability_proficiency(name).is_some_and(|(current, max)| current >= max)
+}
+
+/// Name without the proficiency suffix, for messages.
+fn ability_base_name(name: &str) -> &str {
+    match ability_proficiency(name) {
+        Some(_) => name[..name.rfind('(').unwrap_or(name.len())].trim_end(),
+        None => name,
+    }
+}
+
+/// Spell name without the level suffix panel names may carry, e.g. "ioc (Lev 3/100)".
+pub fn spell_base_name(panel_name: &str) -> &str {
+    panel_name.split(" (").next().unwrap_or_default().trim()
+}
+
 // Bridge skill/spell GameEvents to UI
 fn bridge_ability_events(
     mut ability_events: MessageReader<AbilityEvent>,
     mut state: ResMut<AbilityState>,
+    mut outbound: MessageWriter<UiOutbound>,
 ) {
     for evt in ability_events.read() {
         match evt {
@@ -1967,6 +2508,11 @@ fn bridge_ability_events(
                     continue;
                 };
 
+                // The server ignores presses while the skill is still cooling down
+                if skill.on_cooldown.is_some() {
+                    continue;
+                }
+
                 let Some(cd) = skill.cooldown_secs else {
                     continue;
                 };
@@ -1979,6 +2525,11 @@ fn bridge_ability_events(
             }
             AbilityEvent::AddSkill(pkt) => {
                 if let Some(existing) = state.skills.iter_mut().find(|s| s.slot == pkt.slot) {
+                    if !is_mastered(&existing.name) && is_mastered(&pkt.name) {
+                        outbound.write(UiOutbound(CoreToUi::Toast {
+                            text: format!("{} has reached 100%!", ability_base_name(&pkt.name)),
+                        }));
+                    }
                     existing.name = pkt.name.clone();
                     existing.sprite = pkt.sprite;
 
@@ -2004,6 +2555,14 @@ fn bridge_ability_events(
             }
             AbilityEvent::AddSpell(pkt) => {
                 if let Some(existing) = state.spells.iter_mut().find(|s| s.slot == pkt.slot) {
+                    if !is_mastered(&existing.panel_name) && is_mastered(&pkt.panel_name) {
+                        outbound.write(UiOutbound(CoreToUi::Toast {
+                            text: format!(
+                                "{} has reached 100%!",
+                                ability_base_name(&pkt.panel_name)
+                            ),
+                        }));
+                    }
                     existing.sprite = pkt.sprite;
                     existing.panel_name = pkt.panel_name.clone();
                     existing.prompt = pkt.prompt.clone();
@@ -2138,7 +2697,13 @@ fn clear_input_edges(mut edges: ResMut<KeyboardEdges>) {
     edges.just_released.clear();
 }
 
-fn next_id(mut iter: impl Iterator<Item = u32>) -> u32 {
+/// Blank URL fields from the server editor mean "none".
+fn optional_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    (!url.is_empty()).then(|| url.to_owned())
+}
+
+pub(crate) fn next_id(mut iter: impl Iterator<Item = u32>) -> u32 {
     let mut max = 0u32;
     while let Some(v) = iter.next() {
         if v > max {
@@ -2211,14 +2776,155 @@ fn handle_login_tasks(mut commands: Commands, mut q: Query<(Entity, &mut LoginTa
     }
 }
 
-fn parse_host_port(address: &str) -> Option<(String, u16)> {
-    let mut parts = address.split(':');
-    let host = parts.next()?.to_string();
-    let port = parts
-        .next()
-        .and_then(|p| p.parse::<u16>().ok())
-        .unwrap_or(2610);
-    Some((host, port))
+/// Connects to `server`'s lobby and logs in on the IO pool.
+fn spawn_login_task(
+    server: &ServerEntry,
+    username: String,
+    password: String,
+) -> Task<Result<(network::DecryptedReceiver, network::EncryptedSender), LoginError>> {
+    let address = server.address.clone();
+    IoTaskPool::get().spawn(async move {
+        let (host, port) = network::connect::resolve_server(&address).await;
+        match crate::session_prelogin::PreLoginSession::new(&host, port).await {
+            Ok(lobby) => lobby.login(&username, &password).await,
+            Err(_) => Err(LoginError::Unknown),
+        }
+    })
+}
+
+/// Master password key derivation in flight, see `vault::KeyDerivation`.
+#[derive(Resource)]
+struct VaultUnlockTask(Task<anyhow::Result<DerivedKey>>);
+
+/// Unlocks the credential vault once its key is derived, or asks again.
+fn finish_vault_unlock(
+    mut commands: Commands,
+    task: Option<ResMut<VaultUnlockTask>>,
+    mut vault: ResMut<CredentialVault>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    let Some(mut task) = task else {
+        return;
+    };
+    let Some(derived) = future::block_on(future::poll_once(&mut task.0)) else {
+        return;
+    };
+    commands.remove_resource::<VaultUnlockTask>();
+    match derived.and_then(|derived| vault.finish_unlock(derived)) {
+        Ok(()) => vault.resume = vault.pending.take(),
+        Err(e) => {
+            outbound.write(UiOutbound(CoreToUi::VaultPrompt {
+                create: vault.can_create(),
+                error: Some(e.to_string()),
+            }));
+        }
+    }
+}
+
+/// Replays a login that waited for the credential vault to be unlocked.
+fn resume_vault_login(mut vault: ResMut<CredentialVault>, mut inbound: MessageWriter<UiInbound>) {
+    let Some(pending) = vault.resume.take() else {
+        return;
+    };
+    inbound.write(UiInbound(match pending {
+        PendingLogin::Submit {
+            server_id,
+            username,
+            password,
+        } => UiToCore::LoginSubmit {
+            server_id,
+            username,
+            password,
+            remember: true,
+        },
+        PendingLogin::Saved { id } => UiToCore::LoginUseSaved { id },
+    }));
+}
+
+/// Seconds to wait before retrying a login the server turned away as full.
+const LOGIN_RETRY_SECONDS: u32 = 20;
+
+/// A login waiting to be retried after the server reported it was full.
+#[derive(Resource)]
+struct LoginRetry {
+    remember: bool,
+    cred_id: String,
+    server_id: u32,
+    username: String,
+    password: Option<String>,
+    message: String,
+    timer: Timer,
+    seconds_left: u32,
+}
+
+fn tick_login_retry(
+    mut commands: Commands,
+    time: Res<Time>,
+    retry: Option<ResMut<LoginRetry>>,
+    settings: Res<SettingsFile>,
+    vault: Res<CredentialVault>,
+    mut outbound: MessageWriter<UiOutbound>,
+) {
+    let Some(mut retry) = retry else {
+        return;
+    };
+
+    retry.timer.tick(time.delta());
+    if !retry.timer.is_finished() {
+        let seconds_left = retry.timer.remaining().as_secs_f32().ceil() as u32;
+        if seconds_left != retry.seconds_left {
+            retry.seconds_left = seconds_left;
+            outbound.write(UiOutbound(CoreToUi::LoginQueue {
+                message: retry.message.clone(),
+                seconds_left,
+            }));
+        }
+        return;
+    }
+
+    commands.remove_resource::<LoginRetry>();
+    outbound.write(UiOutbound(CoreToUi::LoginQueue {
+        message: retry.message.clone(),
+        seconds_left: 0,
+    }));
+
+    // Saved logins keep their password in the keyring rather than in memory.
+    let password = match &retry.password {
+        Some(password) => password.clone(),
+        None => match keyring::get_password(&vault, &retry.cred_id) {
+            Ok(password) => password,
+            Err(_) => {
+                outbound.write(UiOutbound(settings.to_snapshot_message(Some(
+                    LoginError::Network("Missing saved password".to_string()),
+                ))));
+                return;
+            }
+        },
+    };
+    let Some(server) = settings.servers.iter().find(|s| s.id == retry.server_id) else {
+        outbound.write(UiOutbound(settings.to_snapshot_message(Some(
+            LoginError::Network("Server missing".to_string()),
+        ))));
+        return;
+    };
+
+    println!(
+        "[webui] LoginRetry: retrying login for user {} on server {}",
+        retry.username, retry.server_id
+    );
+    commands.spawn(LoginTaskEntity(LoginTaskInner {
+        task: spawn_login_task(server, retry.username.clone(), password),
+        remember: retry.remember,
+        cred_id: retry.cred_id.clone(),
+        server_id: retry.server_id,
+        username: retry.username.clone(),
+        password: retry.password.clone(),
+    }));
+}
+
+pub(crate) fn parse_host_port(address: &str) -> Option<(String, u16)> {
+    let (host, port) = network::connect::split_host_port(address);
+    Some((host, port.unwrap_or(network::connect::DEFAULT_PORT)))
 }
 
 fn handle_login_results(
@@ -2228,6 +2934,7 @@ fn handle_login_results(
     mut outbound: MessageWriter<UiOutbound>,
     mut settings: ResMut<SettingsFile>,
     mut next_state: ResMut<NextState<AppState>>,
+    mut vault: ResMut<CredentialVault>,
 ) {
     for (e, mut res) in &mut success_q {
         let (receiver, sender, inner) = {
@@ -2254,20 +2961,59 @@ fn handle_login_results(
         commands.insert_resource(NetEventRx(rx));
 
         let (outbox_tx, outbox_rx) = async_channel::unbounded::<Vec<u8>>();
-        commands.insert_resource(crate::network::PacketOutbox(outbox_tx.clone()));
+        let outbox = crate::network::PacketOutbox::new(outbox_tx.clone());
+        let throttled = outbox.throttled_flag();
+        commands.insert_resource(outbox);
 
         let tx_for_task = tx.clone();
         let mut rx_loop = receiver;
+        // Redirects to another game server swap both halves of the connection.
+        let (swap_tx, swap_rx) = async_channel::unbounded::<network::EncryptedSender>();
+        let server = settings.servers.iter().find(|s| s.id == inner.server_id);
+        let origin_host = server
+            .and_then(|s| parse_host_port(&s.address))
+            .map(|(host, _)| host)
+            .unwrap_or_default();
+        let connection = server.map(|s| s.connection).unwrap_or_default();
 
         let reader_task = IoTaskPool::get().spawn(async move {
             loop {
                 match rx_loop.receive().await {
                     Ok((packet_id, packet_data)) => {
-                        use packets::server;
-                        if let Ok(code) = server::Codes::try_from(packet_id) {
-                            let _ = tx_for_task
-                                .send(crate::events::NetworkEvent::Packet(code, packet_data));
+                        use packets::{TryFromBytes, server};
+                        crate::crash_report::record_opcode(packet_id);
+                        if packet_id == server::Codes::Redirect as u8 {
+                            let followed = match server::Redirect::try_from_bytes(&packet_data) {
+                                Ok(redirect) => {
+                                    crate::session_prelogin::follow_redirect(
+                                        &redirect,
+                                        &origin_host,
+                                    )
+                                    .await
+                                }
+                                Err(e) => Err(anyhow::anyhow!("bad redirect: {:?}", e)),
+                            };
+                            match followed {
+                                Ok((receiver, sender)) => {
+                                    rx_loop = receiver;
+                                    let _ = swap_tx.send(sender).await;
+                                    continue;
+                                }
+                                Err(e) => {
+                                    tracing::warn!("Failed to follow server redirect: {}", e);
+                                    let _ =
+                                        tx_for_task.send(crate::events::NetworkEvent::Disconnected);
+                                    break;
+                                }
+                            }
                         }
+                        let evt = match server::Codes::try_from(packet_id) {
+                            Ok(code) => crate::events::NetworkEvent::Packet(code, packet_data),
+                            Err(_) => {
+                                crate::events::NetworkEvent::UnknownPacket(packet_id, packet_data)
+                            }
+                        };
+                        let _ = tx_for_task.send(evt);
                     }
                     Err(_) => {
                         let _ = tx_for_task.send(crate::events::NetworkEvent::Disconnected);
@@ -2279,17 +3025,60 @@ fn handle_login_results(
 
         // Spawn the background writer task on the IoTaskPool
         let mut tx_loop = sender;
+        let batch_window = std::time::Duration::from_millis(connection.batch_window_ms.into());
+        let configure = move |sender: &mut network::EncryptedSender| {
+            sender.set_nodelay(connection.nodelay).ok();
+            sender.set_batch_window(batch_window);
+        };
+        configure(&mut tx_loop);
+        let mut limiter = crate::network::RateLimiter::new(connection.rate_limits);
         let writer_task = IoTaskPool::get().spawn(async move {
-            while let Ok(packet) = outbox_rx.recv().await {
-                if let Err(_) = tx_loop.send(&packet).await {
-                    break;
+            loop {
+                // Wake for the next packet, when a held one's turn comes, or
+                // when batched packets are due out
+                let flush_wait = tx_loop
+                    .flush_deadline()
+                    .map(|deadline| deadline.saturating_duration_since(std::time::Instant::now()));
+                let wait = match (limiter.next_release(limiter.now()), flush_wait) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+                let received = match wait {
+                    None => match outbox_rx.recv().await {
+                        Ok(packet) => Some(packet),
+                        Err(_) => break,
+                    },
+                    Some(wait) => match async_std::future::timeout(wait, outbox_rx.recv()).await {
+                        Ok(Ok(packet)) => Some(packet),
+                        Ok(Err(_)) => break,
+                        Err(_) => None,
+                    },
+                };
+                while let Ok(mut sender) = swap_rx.try_recv() {
+                    let _ = tx_loop.flush().await;
+                    configure(&mut sender);
+                    tx_loop = sender;
+                }
+                // Packets over their class's limit are held back; the rest,
+                // and held ones whose turn has come, go out now.
+                let now = limiter.now();
+                let mut ready = limiter.release(now);
+                let queued = std::iter::from_fn(|| outbox_rx.try_recv().ok());
+                for packet in received.into_iter().chain(queued) {
+                    ready.extend(limiter.admit(packet, now));
                 }
-                while let Ok(extra_packet) = outbox_rx.try_recv() {
-                    if let Err(_) = tx_loop.send(&extra_packet).await {
+                throttled.store(limiter.is_holding(), std::sync::atomic::Ordering::Relaxed);
+                for packet in &ready {
+                    if let Err(_) = tx_loop.send(packet).await {
                         return;
                     }
                 }
-                let _ = tx_loop.flush().await;
+                // Batched packets wait for a segment's worth or their window
+                let _ = if batch_window.is_zero() {
+                    tx_loop.flush().await
+                } else {
+                    tx_loop.flush_due().await
+                };
             }
         });
 
@@ -2305,7 +3094,9 @@ fn handle_login_results(
                 .map(|d| d.as_millis() as u64)
                 .unwrap_or(0);
             if let Some(pw) = &inner.password {
-                let _ = keyring::set_password(&inner.cred_id, pw);
+                if let Err(e) = keyring::set_password(&mut vault, &inner.cred_id, pw) {
+                    tracing::warn!("Couldn't remember the password: {}", e);
+                }
             }
             // Upsert saved credential record
             if let Some(existing) = settings
@@ -2350,6 +3141,13 @@ fn handle_login_results(
         hotbar_panel_state.current_panel = crate::ecs::hotbar::HotbarPanel::from_u8(saved_panel as u8);
         commands.insert_resource(hotbar_panel_state);
 
+        // Restore this character's HUD window layout
+        let windows = settings
+            .get_window_layout(inner.server_id, &inner.username)
+            .into_iter()
+            .collect();
+        outbound.write(UiOutbound(CoreToUi::WindowLayoutSync { windows }));
+
         next_state.set(AppState::InGame);
         outbound.write(UiOutbound(CoreToUi::EnteredGame));
     }
@@ -2359,6 +3157,25 @@ fn handle_login_results(
             "[webui] LoginResult: failed with code {:?} for user {} on server {}",
             err.0, err.1.username, err.1.server_id
         );
+        if let LoginError::ServerFull(message) = &err.0 {
+            let inner = &err.1;
+            commands.insert_resource(LoginRetry {
+                remember: inner.remember,
+                cred_id: inner.cred_id.clone(),
+                server_id: inner.server_id,
+                username: inner.username.clone(),
+                password: inner.password.clone(),
+                message: message.clone(),
+                timer: Timer::from_seconds(LOGIN_RETRY_SECONDS as f32, TimerMode::Once),
+                seconds_left: LOGIN_RETRY_SECONDS,
+            });
+            outbound.write(UiOutbound(CoreToUi::LoginQueue {
+                message: message.clone(),
+                seconds_left: LOGIN_RETRY_SECONDS,
+            }));
+            commands.entity(e).despawn();
+            continue;
+        }
         // Login failed: keep user on the current screen (login) and emit error
         let logins_public: Vec<SavedCredentialPublic> =
             settings.saved_credentials.iter().map(to_public).collect();
@@ -2367,6 +3184,7 @@ fn handle_login_results(
             current_server_id: settings.gameplay.current_server_id,
             logins: logins_public,
             login_error: Some(err.0.clone()),
+            streamer_mode: settings.gameplay.streamer_mode,
         }));
         commands.entity(e).despawn();
     }
@@ -2377,3 +3195,23 @@ fn sync_settings_to_ui(settings: Res<SettingsFile>, mut outbound: MessageWriter<
         outbound.write(UiOutbound(settings.to_sync_message()));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ability_base_name, ability_proficiency, spell_base_name};
+
+    #[test]
+    fn proficiency_from_ability_name() {
+        assert_eq!(ability_proficiency("Assail (Lev:45/100)"), Some((45, 100)));
+        assert_eq!(ability_proficiency("ard srad (Lev 100/100)"), Some((100, 100)));
+        assert_eq!(ability_proficiency("beag ioc"), None);
+        assert_eq!(ability_proficiency("Odd (Lev:1/0)"), None);
+        assert_eq!(ability_base_name("Assail (Lev:45/100)"), "Assail");
+    }
+
+    #[test]
+    fn level_suffix_is_dropped() {
+        assert_eq!(spell_base_name("ioc (Lev 3/100)"), "ioc");
+        assert_eq!(spell_base_name("beag ioc"), "beag ioc");
+    }
+}
@@ -0,0 +1,358 @@
+//! Encrypted password file used when the system keyring can't be reached.
+//!
+//! Minimal Linux setups often have no Secret Service running, in which case
+//! remembered logins would silently lose their passwords. Instead they go into
+//! `credentials.vault` in the storage root, each sealed with ChaCha20-Poly1305
+//! under a key derived from a master password (PBKDF2-HMAC-SHA256). The key is
+//! kept for the rest of the session once the player has unlocked the vault.
+//! Deriving it takes a good fraction of a second, so it runs as a background
+//! task between `begin_unlock` and `finish_unlock`.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use bevy::prelude::*;
+use ring::aead::{Aad, CHACHA20_POLY1305, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+const VAULT_VERSION: u32 = 1;
+/// Deliberately slow, hence derived off the main thread, once per session.
+const PBKDF2_ITERATIONS: u32 = if cfg!(test) { 1_000 } else { 600_000 };
+const SALT_LEN: usize = 16;
+/// Sealed under the derived key to tell a wrong master password from a right one.
+const CHECK_ID: &str = "__check__";
+const CHECK_PLAINTEXT: &[u8] = b"talgonite";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sealed {
+    nonce: String,
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    iterations: u32,
+    check: Sealed,
+    entries: BTreeMap<String, Sealed>,
+}
+
+/// A login waiting on the master password before it can go ahead.
+#[derive(Debug, Clone)]
+pub enum PendingLogin {
+    /// A new login with "remember me", stored once it succeeds.
+    Submit {
+        server_id: u32,
+        username: String,
+        password: String,
+    },
+    /// A saved login whose password is in the vault.
+    Saved { id: String },
+}
+
+/// Everything needed to derive the vault key, to run on a task pool.
+pub struct KeyDerivation {
+    password: String,
+    salt: Vec<u8>,
+    iterations: u32,
+}
+
+impl KeyDerivation {
+    pub fn derive(self) -> Result<DerivedKey> {
+        Ok(DerivedKey {
+            key: derive_key(&self.password, &self.salt, self.iterations)?,
+            salt: self.salt,
+            iterations: self.iterations,
+        })
+    }
+}
+
+/// A key derived from the master password, for `CredentialVault::finish_unlock`.
+pub struct DerivedKey {
+    key: [u8; 32],
+    salt: Vec<u8>,
+    iterations: u32,
+}
+
+#[derive(Resource)]
+pub struct CredentialVault {
+    path: PathBuf,
+    file: Option<VaultFile>,
+    /// Why an existing vault couldn't be read. It is left alone on disk and
+    /// a new one is never created over it.
+    unreadable: Option<String>,
+    key: Option<[u8; 32]>,
+    /// Whether the system keyring answered when the client started.
+    pub keyring_available: bool,
+    pub pending: Option<PendingLogin>,
+    /// Set once unlocked, for `resume_vault_login` to replay.
+    pub resume: Option<PendingLogin>,
+}
+
+impl FromWorld for CredentialVault {
+    fn from_world(world: &mut World) -> Self {
+        let storage = world.resource::<crate::resources::StorageConfig>();
+        let keyring_available = super::keyring::probe();
+        if !keyring_available {
+            warn!("System keyring unavailable, remembered passwords use the encrypted vault");
+        }
+        Self::open(storage.credential_vault_path(), keyring_available)
+    }
+}
+
+impl CredentialVault {
+    pub fn open(path: PathBuf, keyring_available: bool) -> Self {
+        let (file, unreadable) = match read_vault(&path) {
+            Ok(file) => (file, None),
+            Err(e) => {
+                warn!("Credential vault {} is unreadable: {}", path.display(), e);
+                let reason = format!(
+                    "The password vault at {} can't be read ({}). Move it aside to start a new one.",
+                    path.display(),
+                    e
+                );
+                (None, Some(reason))
+            }
+        };
+        Self {
+            path,
+            file,
+            unreadable,
+            key: None,
+            keyring_available,
+            pending: None,
+            resume: None,
+        }
+    }
+
+    pub fn exists(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Whether unlocking would create a new vault: there is no file yet.
+    pub fn can_create(&self) -> bool {
+        self.file.is_none() && self.unreadable.is_none()
+    }
+
+    /// Why the vault on disk can't be used, when it can't.
+    pub fn unreadable(&self) -> Option<&str> {
+        self.unreadable.as_deref()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// First half of unlocking an existing vault, or creating one protected
+    /// by `password`: the key derivation to run.
+    pub fn begin_unlock(&self, password: &str) -> Result<KeyDerivation> {
+        if let Some(reason) = &self.unreadable {
+            return Err(anyhow!("{}", reason));
+        }
+        let (salt, iterations) = match &self.file {
+            Some(file) => (BASE64.decode(&file.salt)?, file.iterations),
+            None => {
+                if password.is_empty() {
+                    return Err(anyhow!("Choose a master password"));
+                }
+                let mut salt = vec![0u8; SALT_LEN];
+                SystemRandom::new()
+                    .fill(&mut salt)
+                    .map_err(|_| anyhow!("no randomness available"))?;
+                (salt, PBKDF2_ITERATIONS)
+            }
+        };
+        Ok(KeyDerivation {
+            password: password.to_owned(),
+            salt,
+            iterations,
+        })
+    }
+
+    /// Second half of unlocking, with the key `begin_unlock` led to.
+    pub fn finish_unlock(&mut self, derived: DerivedKey) -> Result<()> {
+        match &self.file {
+            Some(file) => match open(&derived.key, CHECK_ID, &file.check) {
+                Ok(check) if check == CHECK_PLAINTEXT => {
+                    self.key = Some(derived.key);
+                    Ok(())
+                }
+                _ => Err(anyhow!("Wrong master password")),
+            },
+            None => {
+                self.file = Some(VaultFile {
+                    version: VAULT_VERSION,
+                    salt: BASE64.encode(&derived.salt),
+                    iterations: derived.iterations,
+                    check: seal(&derived.key, CHECK_ID, CHECK_PLAINTEXT)?,
+                    entries: BTreeMap::new(),
+                });
+                self.key = Some(derived.key);
+                self.save()
+            }
+        }
+    }
+
+    pub fn get(&self, cred_id: &str) -> Result<String> {
+        let key = self
+            .key
+            .as_ref()
+            .ok_or_else(|| anyhow!("vault is locked"))?;
+        let sealed = self
+            .file
+            .as_ref()
+            .and_then(|file| file.entries.get(cred_id))
+            .ok_or_else(|| anyhow!("password not found"))?;
+        Ok(String::from_utf8(open(key, cred_id, sealed)?)?)
+    }
+
+    pub fn set(&mut self, cred_id: &str, password: &str) -> Result<()> {
+        let key = self.key.ok_or_else(|| anyhow!("vault is locked"))?;
+        let sealed = seal(&key, cred_id, password.as_bytes())?;
+        let file = self
+            .file
+            .as_mut()
+            .ok_or_else(|| anyhow!("vault is locked"))?;
+        file.entries.insert(cred_id.to_owned(), sealed);
+        self.save()
+    }
+
+    /// Removing works while locked, entries are only looked up by id.
+    pub fn remove(&mut self, cred_id: &str) -> Result<()> {
+        let removed = self
+            .file
+            .as_mut()
+            .is_some_and(|file| file.entries.remove(cred_id).is_some());
+        if removed { self.save() } else { Ok(()) }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut out = options.open(&self.path)?;
+        // A vault written by an older build may still be world-readable
+        #[cfg(unix)]
+        out.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+        out.write_all(serde_json::to_string_pretty(file)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// `None` when there is no vault yet; an error when there is one that can't be used.
+fn read_vault(path: &Path) -> Result<Option<VaultFile>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let file: VaultFile = serde_json::from_str(&text)?;
+    if file.version != VAULT_VERSION {
+        return Err(anyhow!("unsupported version {}", file.version));
+    }
+    Ok(Some(file))
+}
+
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> Result<[u8; 32]> {
+    let iterations =
+        NonZeroU32::new(iterations).ok_or_else(|| anyhow!("invalid vault parameters"))?;
+    let mut key = [0u8; 32];
+    ring::pbkdf2::derive(
+        ring::pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        password.as_bytes(),
+        &mut key,
+    );
+    Ok(key)
+}
+
+fn cipher(key: &[u8; 32]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&CHACHA20_POLY1305, key).map_err(|_| anyhow!("invalid key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Entries are bound to their credential id, so they can't be swapped around in the file.
+fn seal(key: &[u8; 32], cred_id: &str, plaintext: &[u8]) -> Result<Sealed> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("no randomness available"))?;
+    let mut data = plaintext.to_vec();
+    cipher(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(cred_id.as_bytes()),
+            &mut data,
+        )
+        .map_err(|_| anyhow!("failed to encrypt password"))?;
+    Ok(Sealed {
+        nonce: BASE64.encode(nonce),
+        data: BASE64.encode(data),
+    })
+}
+
+fn open(key: &[u8; 32], cred_id: &str, sealed: &Sealed) -> Result<Vec<u8>> {
+    let nonce = Nonce::try_assume_unique_for_key(&BASE64.decode(&sealed.nonce)?)
+        .map_err(|_| anyhow!("corrupt vault entry"))?;
+    let mut data = BASE64.decode(&sealed.data)?;
+    let plaintext = cipher(key)?
+        .open_in_place(nonce, Aad::from(cred_id.as_bytes()), &mut data)
+        .map_err(|_| anyhow!("failed to decrypt password"))?;
+    Ok(plaintext.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unlock(vault: &mut CredentialVault, password: &str) -> Result<()> {
+        let derived = vault.begin_unlock(password)?.derive()?;
+        vault.finish_unlock(derived)
+    }
+
+    #[test]
+    fn passwords_round_trip_only_with_the_master_password() {
+        let path = std::env::temp_dir().join(format!("talgonite-vault-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut vault = CredentialVault::open(path.clone(), false);
+        assert!(!vault.exists());
+        unlock(&mut vault, "hunter2").unwrap();
+        vault.set("1:Aisling", "secret").unwrap();
+
+        let mut reopened = CredentialVault::open(path.clone(), false);
+        assert!(reopened.exists() && !reopened.is_unlocked());
+        assert!(reopened.get("1:Aisling").is_err());
+        assert!(unlock(&mut reopened, "wrong").is_err());
+        unlock(&mut reopened, "hunter2").unwrap();
+        assert_eq!(reopened.get("1:Aisling").unwrap(), "secret");
+
+        reopened.remove("1:Aisling").unwrap();
+        assert!(reopened.get("1:Aisling").is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn an_unreadable_vault_is_never_replaced() {
+        let path = std::env::temp_dir().join(format!("talgonite-vault-bad-{}", std::process::id()));
+        std::fs::write(&path, "{ not a vault").unwrap();
+
+        let mut vault = CredentialVault::open(path.clone(), false);
+        assert!(!vault.can_create() && vault.unreadable().is_some());
+        assert!(unlock(&mut vault, "hunter2").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{ not a vault");
+        let _ = std::fs::remove_file(&path);
+    }
+}